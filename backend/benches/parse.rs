@@ -0,0 +1,83 @@
+// `cargo bench`, without a bench harness: each case runs a few times and the fastest is printed
+use rssss::rss::parse_rss;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const RUNS: usize = 10;
+
+fn bench(name: &str, document: &str) {
+    let mut fastest = Duration::MAX;
+    for _ in 0..RUNS {
+        let started = Instant::now();
+        let feed = parse_rss(black_box(document.to_string())).unwrap();
+        fastest = fastest.min(started.elapsed());
+        black_box(feed);
+    }
+    println!("{:<48} {:>10.2?}", name, fastest);
+}
+
+fn feed(items: usize, description: impl Fn(usize) -> String) -> String {
+    let items = (0..items)
+        .map(|i| {
+            format!(
+                "<item><title>Item {i}</title><link>https://example.com/{i}</link>\
+                 <description>{}</description></item>",
+                description(i)
+            )
+        })
+        .collect::<String>();
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+         <rss version=\"2.0\"><channel><title>Bench</title>\
+         <link>https://example.com/</link>{}</channel></rss>",
+        items
+    )
+}
+
+fn atom(entries: usize) -> String {
+    let entries = (0..entries)
+        .map(|i| {
+            format!(
+                "<entry><title>Entry {i}</title>\
+                 <link rel=\"alternate\" href=\"https://example.com/{i}\"/>\
+                 <id>urn:example:{i}</id><updated>2024-04-01T00:00:00Z</updated>\
+                 <summary>Summary of entry {i}</summary></entry>"
+            )
+        })
+        .collect::<String>();
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+         <feed xmlns=\"http://www.w3.org/2005/Atom\"><title>Bench</title>\
+         <id>urn:example:feed</id><updated>2024-04-01T00:00:00Z</updated>{}</feed>",
+        entries
+    )
+}
+
+// every element and attribute name of a large feed is compared, in both formats
+fn large_feeds() {
+    bench(
+        "1000 item RSS 2.0 feed",
+        &feed(1_000, |i| format!("Item {i}")),
+    );
+    bench("1000 entry Atom feed", &atom(1_000));
+}
+
+// descriptions without markup are read without the HTML parser, the <br> takes it every time
+fn plain_descriptions() {
+    let plain = |i| {
+        format!(
+            "Plain text of item {i}, fish &amp;amp; chips. {}",
+            "lorem ipsum ".repeat(20)
+        )
+    };
+    bench("500 plain text descriptions", &feed(500, plain));
+    bench(
+        "500 plain text descriptions through html",
+        &feed(500, |i| format!("{}&lt;br&gt;", plain(i))),
+    );
+}
+
+fn main() {
+    large_feeds();
+    plain_descriptions();
+}
@@ -0,0 +1,19 @@
+#!/usr/bin/env bash
+# builds, lints and tests rssss with every feature alone, none and the defaults
+set -euo pipefail
+
+cd "$(dirname "$0")/.."
+
+features=$(sed -n '/^\[features\]/,/^\[/p' Cargo.toml | grep -oE '^[a-z-]+' | grep -v '^default$')
+
+run() {
+  echo "== cargo test $*"
+  cargo clippy --all-targets "$@" -- -D warnings
+  cargo test "$@"
+}
+
+run --no-default-features
+for feature in $features; do
+  run --no-default-features --features "$feature"
+done
+run
@@ -0,0 +1,47 @@
+use crate::hash::fnv1a;
+use crate::rss::Rss;
+use std::collections::BTreeMap;
+
+// what the items of a feed add up to, the same at every url serving them. rssss keeps no guids,
+// items are named by their links, or their titles without one. the scheme and a `www.` are
+// dropped from the links, relative ones were resolved against the url the feed was fetched at.
+// None without items, empty feeds are all alike
+pub fn fingerprint(items: &[Rss]) -> Option<String> {
+    if items.is_empty() {
+        return None;
+    }
+    let mut names = items
+        .iter()
+        .map(|item| match item.link().trim() {
+            "" => item.title().trim(),
+            link => strip_origin(link),
+        })
+        .collect::<Vec<&str>>();
+    names.sort_unstable();
+    Some(format!("{:016x}", fnv1a(names.join("\n").as_bytes())))
+}
+
+fn strip_origin(link: &str) -> &str {
+    let link = link
+        .strip_prefix("https://")
+        .or_else(|| link.strip_prefix("http://"))
+        .unwrap_or(link);
+    link.strip_prefix("www.").unwrap_or(link)
+}
+
+// the indexes of the feeds with the same fingerprint, those alone left out. each group is in
+// the order given and the groups by their first
+pub fn alias_groups(fingerprints: &[Option<String>]) -> Vec<Vec<usize>> {
+    let mut groups = BTreeMap::<&str, Vec<usize>>::new();
+    for (i, fingerprint) in fingerprints.iter().enumerate() {
+        if let Some(fingerprint) = fingerprint {
+            groups.entry(fingerprint).or_default().push(i);
+        }
+    }
+    let mut groups = groups
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .collect::<Vec<Vec<usize>>>();
+    groups.sort_by_key(|group| group[0]);
+    groups
+}
@@ -0,0 +1,911 @@
+use crate::batch;
+use crate::check;
+use crate::error::{ErrorCode, ErrorKind};
+use crate::icon;
+use crate::query::{self, FeedQuery};
+use crate::rss::ParserName;
+use crate::selftest;
+#[cfg(feature = "admin-ui")]
+use crate::status;
+use crate::validate::LintCode;
+use actix_web::http::StatusCode;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+// OpenAPI 3 description of the endpoints, parameters and error kinds come from the code itself
+pub fn openapi() -> Value {
+    let parameters = FeedQuery::PARAMETERS
+        .iter()
+        .map(|name| parameter(name))
+        .collect::<Vec<Value>>();
+    let errors = error_responses();
+    let options = feed_options();
+    let items = json!({
+        "description": "The items, or day groups with group_by. With `Accept: application/x-ndjson` a line each, written as they are parsed unless an option needs the whole feed; an error after the first line is a last line of `{\"error\": ...}`, and a streamed window cut short ends with `{\"has_more\": true, \"continue_from\": ...}` in place of X-Rssss-Continue-From",
+        "headers": paging_headers(),
+        "content": {
+            "application/json": {"schema": {"oneOf": [
+                {"type": "array", "items": {"$ref": "#/components/schemas/Item"}},
+                {"type": "array", "items": {"$ref": "#/components/schemas/DayGroup"}}
+            ]}},
+            "application/x-ndjson": {"schema": {"oneOf": [
+                {"$ref": "#/components/schemas/Item"},
+                {"$ref": "#/components/schemas/DayGroup"},
+                {"type": "object", "properties": {"error": {"$ref": "#/components/schemas/Error"}}},
+                {"type": "object", "properties": {"has_more": {"type": "boolean"}, "continue_from": {"type": "integer"}}}
+            ]}}
+        }
+    });
+    let mut paths = json!({
+        "/feed": {
+            "get": {
+                "summary": "Items of a feed",
+                "parameters": parameters,
+                "responses": not_modified(responses(items.clone(), &errors))
+            },
+            "head": {
+                "summary": "Headers of the items of a feed",
+                "description": "The status and headers the GET answers with, without a body. A cached feed is answered without fetching it, an uncached one is fetched and parsed as for the GET.",
+                "parameters": parameters.clone(),
+                "responses": {
+                    "200": {"description": "As for the GET, X-Rssss-Cache and X-Total-Count included"},
+                    "default": {"description": "The status of the GET's error"}
+                }
+            },
+            "post": {
+                "summary": "Items of a feed, with the parameters as the body",
+                "description": format!(
+                    "For urls too long for a query string or not to be written to access logs. At most {} bytes.",
+                    query::MAX_BODY_SIZE
+                ),
+                "requestBody": {
+                    "required": true,
+                    "content": {
+                        "application/json": {"schema": options.clone()},
+                        "application/x-www-form-urlencoded": {"schema": options}
+                    }
+                },
+                "responses": not_modified(responses(items, &errors))
+            }
+        },
+        "/v2/feed": {
+            "get": {
+                "summary": "Items of a feed with the feed level metadata",
+                "parameters": parameters,
+                "responses": not_modified(coded_responses(
+                    json!({
+                        "description": "The feed envelope",
+                        "headers": paging_headers(),
+                        "content": {"application/json": {"schema": {"$ref": "#/components/schemas/FeedEnvelope"}}}
+                    }),
+                    &errors,
+                ))
+            }
+        },
+        "/feed/icon": {
+            "get": {
+                "summary": "The icon of the feed's site",
+                "description": format!(
+                    "Declared by <link rel=\"icon\"> on the channel link, or /favicon.ico. At most {} bytes.",
+                    icon::ICON_SIZE_LIMIT
+                ),
+                "parameters": [parameter("url"), parameter("case")],
+                "responses": pending(responses(
+                    json!({
+                        "description": "The image",
+                        "headers": {
+                            "X-Rssss-Cache": {"description": "hit, or miss when the job queue was full and the icon was fetched in the request", "schema": {"type": "string"}}
+                        },
+                        "content": {"image/*": {"schema": {"type": "string", "format": "binary"}}}
+                    }),
+                    &errors,
+                ))
+            }
+        },
+        "/feed/validate": {
+            "get": {
+                "summary": "Lints a feed for its publisher instead of returning its items",
+                "description": "Fetched past the caches. A document that can't be parsed is reported with the codes /feed would answer. The feed fetched counts against RSSSS_DAILY_QUOTA_BYTES like that of /feed.",
+                "parameters": [parameter("url"), parameter("timeout_ms"), parameter("case")],
+                "responses": responses(
+                    json!({
+                        "description": "The report",
+                        "content": {"application/json": {"schema": {"$ref": "#/components/schemas/ValidationReport"}}}
+                    }),
+                    &errors,
+                )
+            }
+        },
+        "/feed/parse": {
+            "post": {
+                "summary": "Parses a feed document from the request body",
+                "parameters": [parameter("parser")],
+                "requestBody": {
+                    "required": true,
+                    "content": {"application/xml": {"schema": {
+                        "type": "string",
+                        "maxLength": batch::MAX_DOCUMENT_SIZE
+                    }}}
+                },
+                "responses": responses(
+                    json!({
+                        "description": "The parsed feed",
+                        "content": {"application/json": {"schema": {"$ref": "#/components/schemas/Feed"}}}
+                    }),
+                    &errors,
+                )
+            }
+        },
+        "/feed/parse/batch": {
+            "post": {
+                "summary": "Parses several feed documents",
+                "description": format!(
+                    "At most {} documents of {} bytes each and {} bytes in total.",
+                    batch::MAX_DOCUMENTS,
+                    batch::MAX_DOCUMENT_SIZE,
+                    batch::MAX_TOTAL_SIZE
+                ),
+                "requestBody": {
+                    "required": true,
+                    "content": {
+                        "application/json": {"schema": {
+                            "type": "array",
+                            "items": {"type": "string", "format": "byte"},
+                            "maxItems": batch::MAX_DOCUMENTS
+                        }},
+                        "multipart/form-data": {"schema": {
+                            "type": "object",
+                            "additionalProperties": {"type": "string", "format": "binary"}
+                        }}
+                    }
+                },
+                "responses": responses(
+                    json!({
+                        "description": "A result for every document, in request order",
+                        "content": {"application/json": {"schema": {
+                            "type": "array",
+                            "items": {"$ref": "#/components/schemas/BatchResult"}
+                        }}}
+                    }),
+                    &errors,
+                )
+            }
+        },
+        "/feeds/check": {
+            "get": {
+                "summary": "Checks several feeds without returning their items",
+                "description": format!(
+                    "At most {} urls, {} checked at once. Checks not done by the deadline are reported as timeout or pending. The feeds fetched count against RSSSS_DAILY_QUOTA_BYTES like those of /feed.",
+                    check::MAX_URLS,
+                    check::CONCURRENCY
+                ),
+                "parameters": [
+                    {
+                        "name": "url",
+                        "in": "query",
+                        "required": true,
+                        "description": "The http or https urls of the feeds, the parameter repeated",
+                        "style": "form",
+                        "explode": true,
+                        "schema": {
+                            "type": "array",
+                            "items": {"type": "string", "format": "uri"},
+                            "maxItems": check::MAX_URLS
+                        }
+                    },
+                    parameter("timeout_ms"),
+                    parameter("collapse_aliases")
+                ],
+                "responses": responses(
+                    json!({
+                        "description": "A result for every url, in request order, but for the aliases collapse_aliases folds",
+                        "content": {"application/json": {"schema": {
+                            "type": "array",
+                            "items": {"$ref": "#/components/schemas/CheckResult"}
+                        }}}
+                    }),
+                    &errors,
+                )
+            }
+        },
+        "/selftest": {
+            "get": {
+                "summary": "Whether this server reaches the internet, by fetching RSSSS_SELFTEST_URL",
+                "description": format!(
+                    "Resolves, fetches and finds the outbound address toward the url at once, each check given up after {} seconds or timeout_ms. 404 of kind selftest_disabled without RSSSS_SELFTEST_URL.",
+                    selftest::CHECK_TIMEOUT.as_secs()
+                ),
+                "parameters": [parameter("timeout_ms")],
+                "responses": responses(
+                    json!({
+                        "description": "Each check as far as it got, even when some failed",
+                        "content": {"application/json": {"schema": {"$ref": "#/components/schemas/SelfTest"}}}
+                    }),
+                    &errors,
+                )
+            }
+        },
+        "/health": {
+            "get": {
+                "summary": "Whether the server is up, never requires an API key",
+                "security": [],
+                "responses": {"200": {"description": "Up"}}
+            }
+        }
+    });
+    // those of the features built in
+    let optional: Vec<(&str, Value)> = vec![
+        #[cfg(feature = "metrics")]
+        ("/stats", stats_path()),
+        #[cfg(feature = "admin-ui")]
+        ("/status", status_path()),
+    ];
+    for (path, docs) in optional {
+        paths[path] = docs;
+    }
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "rssss",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Fetches RSS 2.0, RSS 1.0 and Atom feeds and returns their items as JSON."
+        },
+        "paths": paths,
+        "security": [{}, {"bearer": []}, {"api_key": []}],
+        "components": {
+            "schemas": schemas(),
+            "securitySchemes": {
+                "bearer": {"type": "http", "scheme": "bearer", "description": "One of RSSSS_API_KEYS, only required when it is set"},
+                "api_key": {"type": "apiKey", "in": "query", "name": "api_key", "description": "The same key as a parameter"}
+            }
+        }
+    })
+}
+
+#[cfg(feature = "metrics")]
+fn stats_path() -> Value {
+    json!({
+        "get": {
+            "summary": "Fetch statistics per feed url since startup or the last reset",
+            "responses": {
+                "200": {
+                    "description": "The most requested feeds, the others summed up",
+                    "content": {"application/json": {"schema": {"$ref": "#/components/schemas/Stats"}}}
+                }
+            }
+        },
+        "delete": {
+            "summary": "Resets the statistics",
+            "responses": {"204": {"description": "Reset"}}
+        }
+    })
+}
+
+#[cfg(feature = "admin-ui")]
+fn status_path() -> Value {
+    json!({
+        "get": {
+            "summary": "A page for a person: uptime, a summary of the configuration with the keys counted rather than shown, the caches, the jobs, the feeds of /stats and the last errors",
+            "description": format!("The last {} errors made, answered or not, with their messages cut and stripped of control characters", status::RECENT_ERRORS),
+            "responses": {"200": {"description": "The page", "content": {"text/html": {}}}}
+        }
+    })
+}
+
+fn parameter(name: &str) -> Value {
+    let (schema, description) = match name {
+        "url" => (
+            json!({"type": "string", "format": "uri"}),
+            "The http or https url of the feed",
+        ),
+        "expand" => (
+            json!({"type": "boolean", "default": false}),
+            "Replaces stub descriptions with the main text of the linked pages",
+        ),
+        "page" => (
+            json!({"type": "integer", "minimum": 1, "default": 1}),
+            "The page of items, pages out of range are empty",
+        ),
+        "per_page" => (
+            json!({"type": "integer", "minimum": 1, "default": 50}),
+            "The number of items per page, more than 200 is taken as 200",
+        ),
+        "group_by" => (
+            json!({"type": "string", "enum": ["day"]}),
+            "Groups the items by the local date of their pub_date, newest first",
+        ),
+        "sort_by" => (
+            json!({"type": "string", "enum": ["published", "updated"]}),
+            "Sorts the items newest first by pub_date, or by updated falling back to pub_date, before paging. Items without a date come last in feed order",
+        ),
+        "tz" => (
+            json!({"type": "string", "default": "UTC", "example": "+09:00"}),
+            "The offset used for group_by and range, Z, UTC, +09:00, +0900 or -03:30",
+        ),
+        "range" => (
+            json!({"type": "string", "enum": ["today", "yesterday"]}),
+            "Only the items with a pub_date within the day in the zone of tz, from midnight up to the next one. Items without a date are left out",
+        ),
+        "inherit_channel_date" => (
+            json!({"type": "boolean", "default": false}),
+            "Fills missing item dates with the channel date, flagged with date_inherited",
+        ),
+        "case" => (
+            json!({"type": "string", "enum": ["snake", "camel"], "default": "snake"}),
+            "The case of the field names, camel renames e.g. pub_date to pubDate",
+        ),
+        "format" => (
+            json!({"type": "string", "enum": ["plain", "markdown", "html"], "default": "plain"}),
+            "The description as plain text, Markdown, or HTML reduced to safe elements, each cut after 500 characters of text",
+        ),
+        "detect_language" => (
+            json!({"type": "boolean", "default": false}),
+            "Adds detected_language to the items it can tell the language of, from the title and the plain text description",
+        ),
+        "future_dates" => (
+            json!({"type": "string", "enum": ["keep", "clamp", "ignore"], "default": "keep"}),
+            "What is shown of a pub_date or updated more than 48 hours ahead or before 1990: as written, moved to the nearest plausible date, or left out. The item is flagged date_suspect and sorts and groups as undated, with a warning either way",
+        ),
+        "include_category_details" => (
+            json!({"type": "boolean", "default": false}),
+            "Adds categories_detailed to the items with categories, with the term, label and scheme of each",
+        ),
+        "snapshot" => (
+            json!({"type": "string", "pattern": "^[0-9a-f]{16}$"}),
+            "The X-Rssss-Snapshot of an earlier page, to page through the items as they were then. 409 once it has expired",
+        ),
+        "timeout_ms" => (
+            json!({"type": "integer", "minimum": 1}),
+            "Lowers the deadline of the request, RSSSS_REQUEST_TIMEOUT_SECS at most. 504 once it passes",
+        ),
+        "continue_from" => (
+            json!({"oneOf": [{"type": "integer", "minimum": 0}, {"type": "string"}], "default": 0}),
+            "The index of the first item of the response, the X-Rssss-Continue-From of the one before, or the guid of the last item read to start after it. A guid no longer among the items is an empty response. A response holds RSSSS_WINDOW_ITEMS items at most, sorted and paged among themselves, and a feed is read up to RSSSS_MAX_ITEMS items",
+        ),
+        "parser" => (
+            json!({"type": "string", "enum": ParserName::names()}),
+            "Parses with this parser alone rather than trying each in turn, for debugging a document. Its errors are answered as they are, with the line it failed at in parsers on v2. Fetched every time rather than answered from the feed cache",
+        ),
+        "strictness" => (
+            json!({"type": "string", "enum": ["strict", "default", "lenient"], "default": "default"}),
+            "How forgiving the parse is. strict matches element names and the RSS version exactly, takes the declared encoding at its word rather than reading a document declared in a single byte charset that is valid UTF-8 as UTF-8, and fails with strict_mode_warning on any warning, is fetched every time rather than answered from the feed cache, and can't be combined with lenient, partial or inherit_channel_date. lenient turns on lenient and partial and defaults inherit_channel_date to true",
+        ),
+        "lenient" => (
+            json!({"type": "boolean", "default": false}),
+            "Recovers feeds that aren't well-formed XML, fixed up and parsed again or else scanned for items, with a warning saying so. Implied by strictness=lenient",
+        ),
+        "collapse_aliases" => (
+            json!({"type": "boolean", "default": false}),
+            "Leaves out the urls serving the same items as one before them, which lists them in its aliases",
+        ),
+        "partial" => (
+            json!({"type": "boolean", "default": false}),
+            "Answers a feed no parser gets through with the items the furthest one read before its error, at least one, with a warning saying so. Only what is left broken after a fix up with lenient. Implied by strictness=lenient",
+        ),
+        "refresh" => (
+            json!({"type": "boolean", "default": false}),
+            "Fetches a feed that failed to parse again rather than answering with the error of the last try, which is kept for RSSSS_NEGATIVE_CACHE_TTL_SECS and sent with X-Rssss-Cache: negative and the Age of the failed fetch",
+        ),
+        "debug" => (
+            json!({"type": "boolean", "default": false}),
+            "Adds unmapped_elements, cookies_replayed and dropped_items to the /v2/feed envelope, and provenance to each item. Fetched every time rather than answered from the feed cache",
+        ),
+        "include_raw" => (
+            json!({"type": "boolean", "default": false}),
+            "Adds raw to each item, its title, description, link and pub_date as the parser read them, before trimming, unescaping and stripping markup. Only with debug=true, and fetched every time rather than answered from the feed cache",
+        ),
+        "record" => (
+            json!({"type": "boolean", "default": false}),
+            "Fetches the feed past the caches and writes the body to RSSSS_RECORD_DIR, named as RSSSS_FIXTURE_DIR looks it up for a replay without network. 400 without RSSSS_RECORD_DIR or with RSSSS_FIXTURE_DIR",
+        ),
+        _ => (json!({"type": "string"}), ""),
+    };
+    json!({
+        "name": name,
+        "in": "query",
+        "required": name == "url",
+        "description": description,
+        "schema": schema
+    })
+}
+
+// the query parameters as the properties of a body
+fn feed_options() -> Value {
+    let properties = FeedQuery::PARAMETERS
+        .iter()
+        .map(|name| {
+            let parameter = parameter(name);
+            let mut schema = parameter["schema"].clone();
+            schema["description"] = parameter["description"].clone();
+            (name.to_string(), schema)
+        })
+        .collect::<Map<String, Value>>();
+    json!({
+        "type": "object",
+        "required": ["url"],
+        "properties": properties
+    })
+}
+
+fn paging_headers() -> Value {
+    json!({
+        "X-Total-Count": {"description": "The number of items before paging", "schema": {"type": "integer"}},
+        "X-Total-Pages": {"description": "The number of pages", "schema": {"type": "integer"}},
+        "X-Rssss-Cache": {"description": "hit, miss, disk (read back from RSSSS_DATA_DIR), revalidated (a stale disk copy the upstream answered 304 for) or snapshot", "schema": {"type": "string"}},
+        "Age": {"description": "The seconds since the feed was fetched, 0 for a miss", "schema": {"type": "integer"}},
+        "X-Rssss-Continue-From": {"description": "When the feed has more items than the response, the continue_from of the next ones", "schema": {"type": "integer"}},
+        "X-Rssss-Snapshot": {"description": "With page, per_page or snapshot, the token that serves later pages from the same items", "schema": {"type": "string"}},
+        "X-Rssss-Recovery": {"description": "With lenient, how a feed that isn't well-formed XML was read, fixed_up or scanned, and partial with partial", "schema": {"type": "string", "enum": ["fixed_up", "scanned", "partial"]}},
+        "X-Rssss-Mode": {"description": "fixture with RSSSS_FIXTURE_DIR, the feed was read from the file its url maps to rather than fetched, a missing one is a 404 fixture_not_found listing the files there", "schema": {"type": "string", "enum": ["fixture"]}},
+        "X-Quota-Remaining": {"description": "With RSSSS_DAILY_QUOTA_BYTES, the feed bytes the client may still fetch in the last 24 hours. Cache hits don't count", "schema": {"type": "integer"}}
+    })
+}
+
+// invalid parameters and unparsable feeds have no kind and respond 400
+fn error_responses() -> BTreeMap<u16, Vec<ErrorKind>> {
+    let mut errors = BTreeMap::new();
+    errors.insert(StatusCode::BAD_REQUEST.as_u16(), Vec::new());
+    for kind in ErrorKind::ALL {
+        errors
+            .entry(kind.status().as_u16())
+            .or_insert_with(Vec::new)
+            .push(kind);
+    }
+    errors
+}
+
+fn responses(ok: Value, errors: &BTreeMap<u16, Vec<ErrorKind>>) -> Value {
+    responses_of(ok, errors, "#/components/schemas/Error")
+}
+
+// v2 answers with a code per message
+fn coded_responses(ok: Value, errors: &BTreeMap<u16, Vec<ErrorKind>>) -> Value {
+    responses_of(ok, errors, "#/components/schemas/CodedErrors")
+}
+
+fn responses_of(ok: Value, errors: &BTreeMap<u16, Vec<ErrorKind>>, schema: &str) -> Value {
+    let mut responses = Map::new();
+    responses.insert("200".to_string(), ok);
+    for (status, kinds) in errors {
+        let kinds = kinds
+            .iter()
+            .map(|k| serde_json::to_value(k).unwrap())
+            .collect::<Vec<Value>>();
+        let description = if kinds.is_empty() {
+            "Invalid parameters or feed".to_string()
+        } else {
+            format!(
+                "Error of kind {}",
+                kinds
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            )
+        };
+        responses.insert(
+            status.to_string(),
+            json!({
+                "description": description,
+                "content": {"application/json": {"schema": {"$ref": schema}}}
+            }),
+        );
+    }
+    Value::Object(responses)
+}
+
+// the client's validators only go to the upstream of a feed that has no copy here
+fn not_modified(mut responses: Value) -> Value {
+    responses["304"] = json!({
+        "description": "The upstream answered the If-None-Match or If-Modified-Since of the request with 304. They are passed on only when the feed isn't cached, and not across redirects to another origin"
+    });
+    responses
+}
+
+fn pending(mut responses: Value) -> Value {
+    responses["202"] = json!({
+        "description": "Not cached yet, a background job fetches it for the requests after Retry-After",
+        "headers": {
+            "Retry-After": {"schema": {"type": "integer"}},
+            "X-Rssss-Cache": {"description": "pending", "schema": {"type": "string"}}
+        }
+    });
+    responses
+}
+
+// a check of `/selftest`, with what it found once it passed
+fn self_check(found: Value) -> Value {
+    let mut properties = json!({
+        "status": {"type": "string", "enum": ["ok", "failed", "timeout"]},
+        "elapsed_ms": {"type": "integer"},
+        "error": {"type": "string", "description": "Why the check failed or timed out"}
+    });
+    if let (Some(properties), Value::Object(found)) = (properties.as_object_mut(), found) {
+        properties.extend(found);
+    }
+    json!({"type": "object", "required": ["status", "elapsed_ms"], "properties": properties})
+}
+
+fn schemas() -> Value {
+    let kinds = ErrorKind::ALL
+        .iter()
+        .map(|k| serde_json::to_value(k).unwrap())
+        .collect::<Vec<Value>>();
+    let codes = ErrorCode::ALL
+        .iter()
+        .map(|c| serde_json::to_value(c).unwrap())
+        .collect::<Vec<Value>>();
+    let finding_codes = ErrorCode::ALL
+        .iter()
+        .map(|c| serde_json::to_value(c).unwrap())
+        .chain(
+            LintCode::ALL
+                .iter()
+                .map(|c| serde_json::to_value(c).unwrap()),
+        )
+        .collect::<Vec<Value>>();
+    json!({
+        "Item": {
+            "type": "object",
+            "required": ["title", "description", "description_truncated", "description_full_length", "link", "pub_date"],
+            "properties": {
+                "title": {"type": "string"},
+                "description": {"type": "string", "description": "Text without markup unless another format is asked for, truncated after 500 characters of text with an ellipsis. The text is read from the first RSSSS_MARKUP_LIMIT bytes of the markup, 65536 by default, with a warning when there is more"},
+                "description_truncated": {"type": "boolean"},
+                "description_full_length": {"type": "integer", "description": "Characters of the text before truncation"},
+                "link": {"type": "string"},
+                "display_link": {"type": "string", "description": "The link with a unicode host and decoded path, only when it differs"},
+                "guid": {"type": "string", "description": "The guid, Atom id or RSS 1.0 rdf:about of the item, when it has one"},
+                "pub_date": {"type": "string", "nullable": true, "description": "As written in the feed, Atom <updated> when there is no <published>"},
+                "updated": {"type": "string", "description": "Atom <updated> or atom:updated, as written, only present when the feed has it"},
+                "word_count": {"type": "integer"},
+                "reading_time_minutes": {"type": "integer"},
+                "detected_language": {"type": "string", "example": "ja", "description": "The ISO 639-1 code of the language with detect_language, absent for texts under 20 characters and when no language is likely enough"},
+                "raw": {"$ref": "#/components/schemas/RawFields", "description": "Only with include_raw"},
+                "provenance": {"type": "object", "additionalProperties": {"type": "string"}, "description": "Only with debug, the element each field was read from and the precedence rule that let it, like \"content:encoded (only without a description)\""},
+                "audio_duration_secs": {"type": "integer", "description": "From itunes:duration, else the duration of an audio media:content"},
+                "audio_length_bytes": {"type": "integer", "description": "From an audio enclosure, else the fileSize of an audio media:content. Never turned into a duration"},
+                "date_inherited": {"type": "boolean", "description": "Only present, and true, when pub_date came from the channel"},
+                "date_suspect": {"type": "boolean", "description": "Only present, and true, when pub_date or updated is more than 48 hours ahead or before 1990, see future_dates"},
+                "source": {
+                    "type": "object",
+                    "description": "The original publisher of an item in an aggregated feed",
+                    "required": ["title"],
+                    "properties": {
+                        "title": {"type": "string"},
+                        "url": {"type": "string", "description": "The original feed, or its site when the feed isn't given"}
+                    }
+                },
+                "author": {"type": "string", "description": "dc:creator, the RSS author, or the names of the Atom authors joined"},
+                "authors": {"type": "array", "items": {"$ref": "#/components/schemas/Person"}, "description": "Atom only, the feed authors for entries without their own"},
+                "contributors": {"type": "array", "items": {"$ref": "#/components/schemas/Person"}, "description": "Atom only"},
+                "categories": {"type": "array", "items": {"type": "string"}, "description": "The label of each category, else its term. Only present when the item has any"},
+                "categories_detailed": {"type": "array", "items": {"$ref": "#/components/schemas/Category"}, "description": "Only with include_category_details"},
+                "comment_feed_url": {"type": "string", "description": "The feed of the comments on the item, wfw:commentRss of RSS 2.0 and 1.0 resolved against the link. Only present when the item has one"}
+            }
+        },
+        "Channel": {
+            "type": "object",
+            "required": ["link", "ttl", "pub_date", "image"],
+            "properties": {
+                "link": {"type": "string", "nullable": true, "description": "The site of the feed"},
+                "ttl": {"type": "integer", "nullable": true, "description": "Minutes"},
+                "pub_date": {"type": "string", "nullable": true},
+                "image": {
+                    "type": "object",
+                    "nullable": true,
+                    "description": "The branding of the feed, the Atom logo or else its icon",
+                    "required": ["url"],
+                    "properties": {
+                        "url": {"type": "string", "description": "Resolved against the feed url"},
+                        "title": {"type": "string"},
+                        "link": {"type": "string"}
+                    }
+                },
+                "authors": {"type": "array", "items": {"$ref": "#/components/schemas/Person"}, "description": "The Atom feed authors, only present when there is any"},
+                "update_period": {"type": "string", "enum": ["hourly", "daily", "weekly", "monthly", "yearly"], "description": "sy:updatePeriod, only present when declared"},
+                "update_frequency": {"type": "integer", "minimum": 1, "description": "sy:updateFrequency, the updates per period"},
+                "skip_hours": {"type": "array", "items": {"type": "integer", "minimum": 0, "maximum": 23}, "description": "GMT hours not to read the feed in, only present when there is any"},
+                "skip_days": {"type": "array", "items": {"type": "string", "enum": ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"]}},
+                "canonical_url": {"type": "string", "description": "The RSS 2.0 atom:link rel=\"self\", resolved against the feed url"},
+                "canonical_differs": {"type": "boolean", "description": "Only present, and true, when canonical_url is another feed than the one requested, not counting the scheme or a trailing slash"},
+                "copyright": {"type": "string", "description": "The RSS 2.0 copyright, the Atom rights or the RSS 1.0 dc:rights"},
+                "generator": {"$ref": "#/components/schemas/Generator"},
+                "managing_editor": {"type": "string", "description": "RSS 2.0 only, as written, left out when it holds no email address"},
+                "web_master": {"type": "string", "description": "RSS 2.0 only, as written, left out when it holds no email address"}
+            }
+        },
+        "Generator": {
+            "type": "object",
+            "description": "The software that wrote the feed",
+            "required": ["name"],
+            "properties": {
+                "name": {"type": "string", "description": "Empty for an Atom generator with only a uri"},
+                "uri": {"type": "string", "description": "Atom only"},
+                "version": {"type": "string", "description": "Atom only"}
+            }
+        },
+        "Person": {
+            "type": "object",
+            "required": ["name"],
+            "properties": {
+                "name": {"type": "string"},
+                "email": {"type": "string"},
+                "uri": {"type": "string"}
+            }
+        },
+        "Category": {
+            "type": "object",
+            "required": ["term"],
+            "properties": {
+                "term": {"type": "string", "description": "The Atom term, or the text of an RSS category or dc:subject"},
+                "label": {"type": "string", "description": "Atom only, what the term is shown as"},
+                "scheme": {"type": "string", "example": "http://arxiv.org/schemas/atom", "description": "The Atom scheme or the RSS 2.0 domain"}
+            }
+        },
+        "RawFields": {
+            "type": "object",
+            "required": ["title", "description", "link", "pub_date"],
+            "properties": {
+                "title": {"type": "string"},
+                "description": {"type": "string", "description": "Markup and escaped markup as it was, before the description was made text"},
+                "link": {"type": "string"},
+                "pub_date": {"type": "string", "nullable": true}
+            },
+            "description": "As read from the XML events, entities already decoded, each cut to 2048 bytes"
+        },
+        "DayGroup": {
+            "type": "object",
+            "required": ["date", "items"],
+            "properties": {
+                "date": {"type": "string", "description": "YYYY-MM-DD, or unknown for items without a date"},
+                "items": {"type": "array", "items": {"$ref": "#/components/schemas/Item"}}
+            }
+        },
+        "FeedEnvelope": {
+            "type": "object",
+            "required": ["url", "channel", "channel_stats", "fetched_at", "upstream_headers", "has_more"],
+            "properties": {
+                "url": {"type": "string"},
+                "display_url": {"type": "string"},
+                "channel": {"$ref": "#/components/schemas/Channel"},
+                "channel_stats": {"$ref": "#/components/schemas/ChannelStats"},
+                "fetched_at": {"type": "string", "format": "date-time", "description": "When the feed was fetched, or for RSSSS_FIXTURE_DIR when its file was written. Cached copies keep the time of the fetch, the Age header counts the seconds since"},
+                "upstream_headers": {
+                    "type": "object",
+                    "additionalProperties": {"type": "string"},
+                    "description": "Content-Type, Content-Length, Last-Modified, ETag, Cache-Control and Server as sent by the feed host"
+                },
+                "upstream_protocol": {"type": "string", "enum": ["HTTP/0.9", "HTTP/1.0", "HTTP/1.1", "HTTP/2", "HTTP/3"], "description": "The HTTP version the feed was last fetched over, absent for feeds cached before it was recorded"},
+                "upstream_ip": {"type": "string", "description": "The address the feed was last fetched from, absent for feeds cached before it was recorded"},
+                "upstream_ip_family": {"type": "string", "enum": ["ipv4", "ipv6"], "description": "The family of upstream_ip, RSSSS_IP_PREFERENCE sets which is tried first"},
+                "warnings": {"type": "array", "items": {"type": "string"}, "description": "What was off about the document, like repeated channels, only present when there is any"},
+                "fingerprint": {"type": "string", "description": "A hash of the links of the items, the same at every url serving them, so a client merging feeds can tell the http and https or www and bare urls of one feed apart from two feeds. Absent without items"},
+                "snapshot": {"type": "string", "description": "The X-Rssss-Snapshot token, only present for paginated requests"},
+                "has_more": {"type": "boolean", "description": "The feed has more items than this response, see continue_from"},
+                "continue_from": {"type": "integer", "description": "The X-Rssss-Continue-From of the next items"},
+                "unmapped_elements": {"type": "object", "additionalProperties": {"type": "integer"}, "description": "How often each element nothing was read from occurs, by the name the document wrote, the 20 most frequent of the first 200 names. Only present with debug, and empty for a feed the cache read back from disk"},
+                "cookies_replayed": {"type": "integer", "description": "How many cookies set by redirects of the fetch were sent back along it, 0 for a feed the cache read back from disk. Only present with debug, cookies are never kept past the fetch"},
+                "dropped_items": {"type": "array", "items": {"$ref": "#/components/schemas/DroppedItem"}, "description": "The items neither a title nor a link could be read from, left out of the items with a warning each. Only present with debug, and empty for a feed the cache read back from disk"},
+                "items": {"type": "array", "items": {"$ref": "#/components/schemas/Item"}},
+                "groups": {"type": "array", "items": {"$ref": "#/components/schemas/DayGroup"}}
+            }
+        },
+        "Feed": {
+            "type": "object",
+            "required": ["channel", "items"],
+            "properties": {
+                "channel": {"$ref": "#/components/schemas/Channel"},
+                "items": {"type": "array", "items": {"$ref": "#/components/schemas/Item"}},
+                "warnings": {"type": "array", "items": {"type": "string"}, "description": "What was off about the document, like repeated channels, only present when there is any"}
+            }
+        },
+        "BatchResult": {
+            "oneOf": [
+                {"type": "object", "required": ["feed"], "properties": {"feed": {"$ref": "#/components/schemas/Feed"}}},
+                {"type": "object", "required": ["error"], "properties": {"error": {"$ref": "#/components/schemas/Error"}}}
+            ]
+        },
+        "CheckResult": {
+            "type": "object",
+            "required": ["url", "status"],
+            "properties": {
+                "url": {"type": "string", "description": "As requested"},
+                "status": {"type": "string", "enum": ["ok", "redirected", "parse_error", "http_error", "timeout", "pending"]},
+                "detail": {"type": "string", "description": "Why the check failed or didn't finish"},
+                "item_count": {"type": "integer"},
+                "final_url": {"type": "string", "description": "Where the redirects ended, absent for checks not done"},
+                "fingerprint": {"type": "string", "description": "A hash of the links of the items, the same at every url serving them"},
+                "aliases": {"type": "array", "items": {"type": "string"}, "description": "The other urls checked with the same fingerprint, likely the same feed subscribed to twice"}
+            }
+        },
+        "SelfTest": {
+            "type": "object",
+            "required": ["url", "ok", "dns", "fetch", "outbound"],
+            "properties": {
+                "url": {"type": "string", "description": "RSSSS_SELFTEST_URL"},
+                "ok": {"type": "boolean", "description": "Every check passed"},
+                "dns": self_check(json!({
+                    "addresses": {"type": "array", "items": {"type": "string"}, "description": "What the host resolves to"}
+                })),
+                "fetch": self_check(json!({
+                    "http_status": {"type": "integer", "description": "Any answer passes, redirects aren't followed"},
+                    "http_version": {"type": "string", "enum": ["HTTP/1.0", "HTTP/1.1", "HTTP/2"]},
+                    "tls": {"type": "boolean", "description": "Whether a TLS handshake was made, false for http"},
+                    "remote_address": {"type": "string", "description": "The address dialed"}
+                })),
+                "outbound": self_check(json!({
+                    "local_address": {"type": "string", "description": "The address of the interface the host is reached from. Behind NAT feed hosts see another"},
+                    "remote_address": {"type": "string"}
+                }))
+            }
+        },
+        "ChannelStats": {
+            "type": "object",
+            "required": ["item_count"],
+            "description": "Of every item read, not only those of the response",
+            "properties": {
+                "item_count": {"type": "integer", "description": "Undated items included"},
+                "earliest_pub_date": {"type": "string", "format": "date-time", "description": "In UTC, absent without a dated item. Dates flagged date_suspect are left out"},
+                "latest_pub_date": {"type": "string", "format": "date-time"},
+                "median_interval_hours": {"type": "number", "description": "The median time between consecutive dated items to a tenth of an hour, only with 3 or more dated items"}
+            }
+        },
+        "DroppedItem": {
+            "type": "object",
+            "required": ["ordinal"],
+            "properties": {
+                "ordinal": {"type": "integer", "description": "1-based, of the items in the document, dropped ones included"},
+                "description": {"type": "string"},
+                "pub_date": {"type": "string"},
+                "author": {"type": "string"}
+            }
+        },
+        "ValidationReport": {
+            "type": "object",
+            "required": ["url", "final_url", "valid", "item_count", "items_without_date", "items_without_link", "items_without_guid", "unparsable_dates", "unescaped_html", "oversized_fields", "duplicate_guids", "dropped_items", "findings"],
+            "properties": {
+                "url": {"type": "string", "description": "As requested"},
+                "final_url": {"type": "string", "description": "Where the redirects ended"},
+                "valid": {"type": "boolean", "description": "No finding of severity error, /feed serves the feed"},
+                "format": {"type": "string", "enum": ["rss", "atom", "rdf"], "description": "By the root element, absent when it's none of them"},
+                "version": {"type": "string"},
+                "encoding": {"type": "string", "description": "As declared"},
+                "generator": {"$ref": "#/components/schemas/Generator"},
+                "item_count": {"type": "integer", "description": "Parsed, or seen up to where the document breaks"},
+                "items_without_date": {"type": "integer"},
+                "items_without_link": {"type": "integer"},
+                "items_without_guid": {"type": "integer", "description": "RSS 1.0 items are named by rdf:about, Atom entries by id"},
+                "unparsable_dates": {"type": "integer", "description": "Neither RFC 2822 nor RFC 3339"},
+                "unescaped_html": {"type": "integer", "description": "Descriptions with elements in them rather than escaped markup"},
+                "oversized_fields": {"type": "integer", "description": "Fields cut to their limit"},
+                "duplicate_guids": {"type": "integer"},
+                "dropped_items": {"type": "integer", "description": "Items neither a title nor a link could be read from, which /feed leaves out"},
+                "findings": {"type": "array", "items": {"$ref": "#/components/schemas/Finding"}}
+            }
+        },
+        "Finding": {
+            "type": "object",
+            "required": ["severity", "code", "message"],
+            "properties": {
+                "severity": {"type": "string", "enum": ["error", "warning", "info"]},
+                "code": {"type": "string", "enum": finding_codes},
+                "message": {"type": "string"},
+                "item": {"type": "integer", "description": "1-based"},
+                "line": {"type": "integer", "description": "1-based, of the item when about one"},
+                "column": {"type": "integer"}
+            }
+        },
+        "FeedStats": {
+            "type": "object",
+            "required": ["requests", "cache_hit_ratio", "avg_fetch_ms", "p95_fetch_ms", "avg_parse_ms", "last_status", "last_item_count", "bytes", "negative_hits", "slow_parses"],
+            "properties": {
+                "url": {"type": "string", "description": "Absent for the sum of the other feeds"},
+                "requests": {"type": "integer"},
+                "cache_hit_ratio": {"type": "number", "nullable": true},
+                "avg_fetch_ms": {"type": "number", "nullable": true},
+                "p95_fetch_ms": {"type": "number", "nullable": true, "description": "Over the latest fetches of each feed"},
+                "avg_parse_ms": {"type": "number", "nullable": true},
+                "last_status": {"type": "integer", "nullable": true, "description": "Of the upstream, always null for the other feeds"},
+                "last_item_count": {"type": "integer", "nullable": true, "description": "Always null for the other feeds"},
+                "bytes": {"type": "integer", "description": "Feed bodies transferred"},
+                "negative_hits": {"type": "integer", "description": "Requests answered with a cached parse error"},
+                "slow_parses": {"type": "integer", "description": "Parses, and the text of formatted or expanded descriptions, that took longer than RSSSS_SLOW_PARSE_MS"}
+            }
+        },
+        "Stats": {
+            "type": "object",
+            "required": ["since", "feeds", "other", "slow_parse_total", "slow_client_disconnects", "jobs"],
+            "properties": {
+                "since": {"type": "string", "format": "date-time"},
+                "feeds": {"type": "array", "items": {"$ref": "#/components/schemas/FeedStats"}},
+                "other": {"$ref": "#/components/schemas/FeedStats"},
+                "slow_parse_total": {"type": "integer", "description": "rssss_slow_parse_total, the slow_parses of every feed. With RSSSS_REJECT_SLOW_PARSES a feed that parsed slowly is answered with feed_too_expensive until RSSSS_NEGATIVE_CACHE_TTL_SECS is up"},
+                "slow_client_disconnects": {"type": "integer", "description": "rssss_slow_client_disconnects_total, clients disconnected for reading nothing of a /feed response for RSSSS_WRITE_TIMEOUT_SECS"},
+                "jobs": {"$ref": "#/components/schemas/JobStats"}
+            }
+        },
+        "JobStats": {
+            "type": "object",
+            "required": ["queue_depth", "running", "outcomes"],
+            "properties": {
+                "queue_depth": {"type": "integer", "description": "Jobs waiting for one of RSSSS_JOB_WORKERS, at most RSSSS_JOB_QUEUE_SIZE"},
+                "running": {"type": "integer"},
+                "outcomes": {
+                    "type": "object",
+                    "description": "By job kind, icon",
+                    "additionalProperties": {
+                        "type": "object",
+                        "required": ["queued", "deduplicated", "rejected", "succeeded", "failed", "timed_out"],
+                        "properties": {
+                            "queued": {"type": "integer"},
+                            "deduplicated": {"type": "integer", "description": "Asked for while the same job was queued or running"},
+                            "rejected": {"type": "integer", "description": "The queue was full, the request did the work itself"},
+                            "succeeded": {"type": "integer"},
+                            "failed": {"type": "integer", "description": "Done with nothing found, like a site without an icon"},
+                            "timed_out": {"type": "integer", "description": "Took longer than RSSSS_JOB_TIMEOUT_SECS"}
+                        }
+                    }
+                }
+            }
+        },
+        "Error": {
+            "type": "object",
+            "required": ["messages"],
+            "properties": {
+                "kind": {"type": "string", "enum": kinds},
+                "messages": {"type": "array", "items": {"type": "string"}}
+            }
+        },
+        "CodedErrors": {
+            "type": "object",
+            "required": ["errors"],
+            "properties": {
+                "kind": {"type": "string", "enum": kinds},
+                "errors": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["code", "message"],
+                        "properties": {
+                            "code": {"type": "string", "enum": codes, "description": "Stable, unlike the message"},
+                            "message": {"type": "string"}
+                        }
+                    }
+                },
+                "parsers": {
+                    "type": "array",
+                    "description": "For a document no parser got through, how far each one got, in the order they tried",
+                    "items": {
+                        "type": "object",
+                        "required": ["parser", "code", "error", "items_recovered", "failed_at_line"],
+                        "properties": {
+                            "parser": {"type": "string", "enum": ["RSS V2", "Atom", "RSS V1"]},
+                            "code": {"type": "string", "enum": codes},
+                            "error": {"type": "string"},
+                            "items_recovered": {"type": "integer", "description": "The items read before the error"},
+                            "failed_at_line": {"type": "integer"}
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
+
+pub const SWAGGER_UI: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <title>rssss API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+  <script>
+    window.ui = SwaggerUIBundle({ url: "openapi.json", dom_id: "#swagger-ui" });
+  </script>
+</body>
+</html>
+"##;
@@ -0,0 +1,101 @@
+// what an item says about its audio, each as written, from
+// itunes:duration, media:content and <enclosure>
+#[derive(Debug, Clone, Default)]
+pub struct AudioSources {
+    pub itunes_duration: Option<String>,
+    pub media_duration: Option<String>,
+    pub media_type: Option<String>,
+    pub media_medium: Option<String>,
+    pub media_file_size: Option<String>,
+    pub enclosure_length: Option<String>,
+    pub enclosure_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Audio {
+    pub duration_secs: Option<u32>,
+    pub length_bytes: Option<u64>,
+}
+
+impl AudioSources {
+    // the duration of itunes:duration over media:content, which only counts when it's audio.
+    // the length of the enclosure over media:content fileSize, both only of an audio type.
+    // a length alone never turns into a duration, the bitrate is anyone's guess
+    pub fn resolve(&self) -> Audio {
+        let media_is_audio = self.media_type.as_deref().is_some_and(is_audio_type)
+            || self
+                .media_medium
+                .as_deref()
+                .is_some_and(|m| m.trim().eq_ignore_ascii_case("audio"));
+        let duration_secs = self
+            .itunes_duration
+            .as_deref()
+            .and_then(parse_duration)
+            .or_else(|| {
+                self.media_duration
+                    .as_deref()
+                    .filter(|_| media_is_audio)
+                    .and_then(parse_duration)
+            });
+        let enclosure_length = self
+            .enclosure_length
+            .as_deref()
+            .filter(|_| self.enclosure_type.as_deref().is_some_and(is_audio_type))
+            .and_then(parse_length);
+        let length_bytes = enclosure_length.or_else(|| {
+            self.media_file_size
+                .as_deref()
+                .filter(|_| media_is_audio)
+                .and_then(parse_length)
+        });
+        Audio {
+            duration_secs,
+            length_bytes,
+        }
+    }
+}
+
+pub fn is_audio_type(mime: &str) -> bool {
+    mime.trim()
+        .get(..6)
+        .is_some_and(|t| t.eq_ignore_ascii_case("audio/"))
+}
+
+// seconds, MM:SS or HH:MM:SS, a fraction of a second is dropped.
+// zero is what feeds write when they don't know
+pub fn parse_duration(s: &str) -> Option<u32> {
+    let parts = s.trim().split(':').collect::<Vec<&str>>();
+    if parts.len() > 3 {
+        return None;
+    }
+    let (last, leading) = parts.split_last()?;
+    let seconds = match last.split_once('.') {
+        Some((whole, fraction)) if fraction.bytes().all(|b| b.is_ascii_digit()) => number(whole)?,
+        Some(_) => return None,
+        None => number(last)?,
+    };
+    let mut total = 0u32;
+    for (i, part) in leading.iter().enumerate() {
+        let value = number(part)?;
+        // only the first part may run over, as in 90:00
+        if i > 0 && value >= 60 {
+            return None;
+        }
+        total = total.checked_add(value)?.checked_mul(60)?;
+    }
+    if !leading.is_empty() && seconds >= 60 {
+        return None;
+    }
+    Some(total.checked_add(seconds)?).filter(|d| *d > 0)
+}
+
+fn number(s: &str) -> Option<u32> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+fn parse_length(s: &str) -> Option<u64> {
+    s.trim().parse().ok().filter(|l| *l > 0)
+}
@@ -0,0 +1,118 @@
+use crate::config::Config;
+use crate::error::{ApiError, UnauthorizedError};
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::{header, Method};
+use actix_web::{web, HttpMessage, HttpResponse};
+use futures::future::{ready, LocalBoxFuture, Ready};
+use url::form_urlencoded;
+
+// reachable without a key, for load balancers and uptime checks
+const PUBLIC_PATHS: [&str; 1] = ["/health"];
+
+// which of RSSSS_API_KEYS a request came with, in request extensions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApiKey {
+    pub index: usize,
+}
+
+impl ApiKey {
+    // how the key is told apart in logs and quotas, never the key itself
+    pub fn name(&self) -> String {
+        format!("key {}", self.index + 1)
+    }
+}
+
+// requires one of `Config::api_keys` as a bearer token or the api_key parameter, does nothing without keys
+pub struct ApiKeys;
+
+impl<S, B> Transform<S, ServiceRequest> for ApiKeys
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = ApiKeysMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ApiKeysMiddleware { service }))
+    }
+}
+
+pub struct ApiKeysMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for ApiKeysMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let keys = req
+            .app_data::<web::Data<Config>>()
+            .map(|config| config.api_keys.as_slice())
+            .unwrap_or_default();
+        // browsers don't send credentials with a preflight
+        if !keys.is_empty()
+            && !PUBLIC_PATHS.contains(&req.path())
+            && req.method() != Method::OPTIONS
+        {
+            match authorize(&req, keys) {
+                Ok(key) => {
+                    req.extensions_mut().insert(key);
+                }
+                Err(e) => {
+                    let res = HttpResponse::build(e.status())
+                        .insert_header((header::WWW_AUTHENTICATE, "Bearer"))
+                        .json(e);
+                    return Box::pin(ready(Ok(req.into_response(res).map_into_right_body())));
+                }
+            }
+        }
+        let res = self.service.call(req);
+        Box::pin(async move { res.await.map(ServiceResponse::map_into_left_body) })
+    }
+}
+
+fn authorize(req: &ServiceRequest, keys: &[String]) -> Result<ApiKey, ApiError> {
+    let bearer = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split_once(' '))
+        .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("bearer"))
+        .map(|(_, token)| token.trim().to_string());
+    let given = bearer.or_else(|| {
+        form_urlencoded::parse(req.query_string().as_bytes())
+            .find(|(name, _)| name == "api_key")
+            .map(|(_, value)| value.into_owned())
+    });
+    let given = given.ok_or(UnauthorizedError { missing: true })?;
+    // every key is compared, so the time taken doesn't tell which one came close
+    let mut found = None;
+    for (index, key) in keys.iter().enumerate() {
+        if constant_time_eq(key.as_bytes(), given.as_bytes()) {
+            found = Some(ApiKey { index });
+        }
+    }
+    found.ok_or_else(|| UnauthorizedError { missing: false }.into())
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
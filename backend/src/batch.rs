@@ -0,0 +1,149 @@
+use crate::error::{ApiError, PayloadTooLargeError, TooManyDocumentsError};
+use crate::rss::{self, Feed, FieldLimits, ParseOptions, Rss};
+use actix_multipart::Multipart;
+use actix_web::http::header::{HeaderMap, CONTENT_TYPE};
+use actix_web::web::{Bytes, BytesMut, Payload};
+use futures::future::join_all;
+use futures::stream::StreamExt;
+use serde_derive::Serialize;
+
+pub const MAX_DOCUMENTS: usize = 20;
+pub const MAX_DOCUMENT_SIZE: usize = 1_048_576;
+pub const MAX_TOTAL_SIZE: usize = 8_388_608;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchResult {
+    Feed(Box<Feed>),
+    Error(ApiError),
+}
+
+// reads the documents of a multipart upload or a JSON array of base64 strings,
+// a document over the size limit fails alone, exceeding the count or total size fails the batch
+pub async fn read_documents(
+    headers: &HeaderMap,
+    payload: Payload,
+) -> Result<Vec<Result<Bytes, ApiError>>, ApiError> {
+    let is_multipart = headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("multipart/form-data"));
+    if is_multipart {
+        read_multipart(Multipart::new(headers, payload)).await
+    } else {
+        read_json(payload).await
+    }
+}
+
+async fn read_multipart(
+    mut multipart: Multipart,
+) -> Result<Vec<Result<Bytes, ApiError>>, ApiError> {
+    let mut documents = Vec::new();
+    let mut total = 0;
+    while let Some(field) = multipart.next().await {
+        let mut field = field?;
+        if documents.len() == MAX_DOCUMENTS {
+            return Err(TooManyDocumentsError {
+                limit: MAX_DOCUMENTS,
+            }
+            .into());
+        }
+        let mut buf = BytesMut::new();
+        let mut size = 0;
+        while let Some(chunk) = field.next().await {
+            let chunk = chunk?;
+            size += chunk.len();
+            total += chunk.len();
+            if total > MAX_TOTAL_SIZE {
+                return Err(PayloadTooLargeError {
+                    limit: MAX_TOTAL_SIZE,
+                }
+                .into());
+            }
+            // keeps draining an oversized field so the next one can be read
+            if size <= MAX_DOCUMENT_SIZE {
+                buf.extend_from_slice(&chunk);
+            }
+        }
+        documents.push(document(buf.freeze(), size));
+    }
+    Ok(documents)
+}
+
+async fn read_json(payload: Payload) -> Result<Vec<Result<Bytes, ApiError>>, ApiError> {
+    // base64 takes 4 bytes for every 3, plus some room for quotes and separators
+    let body = read_body(payload, MAX_TOTAL_SIZE / 3 * 4 + MAX_DOCUMENTS * 8 + 2).await?;
+    let encoded = serde_json::from_slice::<Vec<String>>(&body)?;
+    if encoded.len() > MAX_DOCUMENTS {
+        return Err(TooManyDocumentsError {
+            limit: MAX_DOCUMENTS,
+        }
+        .into());
+    }
+    let mut documents = Vec::new();
+    let mut total = 0;
+    for e in encoded {
+        match base64::decode(e.trim()) {
+            Ok(buf) => {
+                total += buf.len();
+                if total > MAX_TOTAL_SIZE {
+                    return Err(PayloadTooLargeError {
+                        limit: MAX_TOTAL_SIZE,
+                    }
+                    .into());
+                }
+                let size = buf.len();
+                documents.push(document(Bytes::from(buf), size));
+            }
+            Err(err) => documents.push(Err(err.into())),
+        }
+    }
+    Ok(documents)
+}
+
+pub async fn read_body(mut payload: Payload, limit: usize) -> Result<Bytes, ApiError> {
+    let mut body = BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk?;
+        if body.len() + chunk.len() > limit {
+            return Err(PayloadTooLargeError { limit }.into());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body.freeze())
+}
+
+fn document(buf: Bytes, size: usize) -> Result<Bytes, ApiError> {
+    if size > MAX_DOCUMENT_SIZE {
+        return Err(PayloadTooLargeError {
+            limit: MAX_DOCUMENT_SIZE,
+        }
+        .into());
+    }
+    Ok(buf)
+}
+
+// every document goes to the blocking thread pool, results keep the order of the input
+pub async fn parse_documents(
+    documents: Vec<Result<Bytes, ApiError>>,
+    limits: FieldLimits,
+) -> Vec<BatchResult> {
+    let options = ParseOptions {
+        limits,
+        ..ParseOptions::default()
+    };
+    join_all(documents.into_iter().map(|document| async move {
+        let result = match document {
+            Ok(buf) => rss::parse_rss_async(buf, None, 0, options, None).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(mut feed) => {
+                feed.items.iter_mut().for_each(Rss::drop_category_details);
+                BatchResult::Feed(Box::new(feed))
+            }
+            Err(e) => BatchResult::Error(e),
+        }
+    }))
+    .await
+}
@@ -0,0 +1,16 @@
+use crate::hash::fnv1a;
+use crate::urlnorm::canonical_feed_key;
+use url::Url;
+
+pub const BUCKETS: u8 = 16;
+
+// which of 16 colors a client should give the items of a feed in a merged timeline,
+// FNV-1a of the feed key so it holds across restarts, builds and platforms.
+// changing the hash reassigns every feed
+pub fn source_bucket(url: &str) -> u8 {
+    let key = match Url::parse(url.trim()) {
+        Ok(parsed) => canonical_feed_key(&parsed),
+        Err(_) => url.trim().to_string(),
+    };
+    (fnv1a(key.as_bytes()) % u64::from(BUCKETS)) as u8
+}
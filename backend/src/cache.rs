@@ -0,0 +1,300 @@
+use crate::alias;
+#[cfg(feature = "cache-disk")]
+use crate::disk_cache::DiskCache;
+use crate::error::ApiError;
+use crate::hash::fnv1a;
+use crate::icon::Icon;
+use crate::robots::Robots;
+use crate::rss::{Channel, Feed, ParseOptions};
+use crate::stored::Stored;
+use chrono::{DateTime, Timelike, Utc};
+use linked_hash_map::LinkedHashMap;
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+#[cfg(feature = "cache-disk")]
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    pub capacity: usize,
+    pub default_ttl: Duration,
+    pub min_ttl: Duration,
+    pub max_ttl: Duration,
+    pub gone_ttl: Duration,
+    pub icon_ttl: Duration,
+    // RSSSS_DATA_DIR/cache, no disk cache when unset
+    #[cfg(feature = "cache-disk")]
+    pub disk_dir: Option<PathBuf>,
+    #[cfg(feature = "cache-disk")]
+    pub disk_capacity: u64,
+    // how long a snapshot is kept after it was last paged through
+    pub snapshot_ttl: Duration,
+    // how long a feed that failed to parse is answered with the same error without fetching it
+    pub negative_ttl: Duration,
+    pub negative_capacity: usize,
+    // the robots.txt of each host, with RSSSS_RESPECT_ROBOTS
+    pub robots_ttl: Duration,
+    pub robots_capacity: usize,
+}
+
+impl CacheConfig {
+    // whether fetched feeds are kept on disk, never without the cache-disk feature
+    #[cfg(feature = "cache-disk")]
+    pub fn on_disk(&self) -> bool {
+        self.disk_dir.is_some()
+    }
+
+    #[cfg(not(feature = "cache-disk"))]
+    pub fn on_disk(&self) -> bool {
+        false
+    }
+
+    // precedence: upstream Cache-Control, channel <ttl> (in minutes), the syndication hints, then the default.
+    // an expiry within the skip hours or days is put off to their end, as there is nothing new to read until then
+    pub fn ttl(
+        &self,
+        cache_control: Option<&str>,
+        channel: &Channel,
+        now: DateTime<Utc>,
+    ) -> Duration {
+        let max_ttl = self.max_ttl.max(self.min_ttl);
+        let mut ttl = cache_control
+            .and_then(max_age)
+            .map(Duration::from_secs)
+            .or_else(|| channel.ttl.map(|m| Duration::from_secs(u64::from(m) * 60)))
+            .or_else(|| channel.update_interval())
+            .unwrap_or(self.default_ttl)
+            .clamp(self.min_ttl, max_ttl);
+        while ttl < max_ttl {
+            let expires_at = match chrono::Duration::from_std(ttl) {
+                Ok(ttl) => now + ttl,
+                Err(_) => break,
+            };
+            if !channel.skips(expires_at) {
+                break;
+            }
+            let into_hour = expires_at.minute() * 60 + expires_at.second();
+            ttl += Duration::from_secs(u64::from(3_600 - into_hour));
+        }
+        ttl.min(max_ttl)
+    }
+}
+
+// s-maxage is meant for shared caches like us, so it wins over max-age
+pub fn max_age(cache_control: &str) -> Option<u64> {
+    let directives = cache_control
+        .split(',')
+        .filter_map(|d| d.split_once('='))
+        .map(|(k, v)| (k.trim().to_ascii_lowercase(), v.trim().trim_matches('"')))
+        .collect::<Vec<(String, &str)>>();
+    let find = |key: &str| {
+        directives
+            .iter()
+            .find(|(k, _)| k == key)
+            .and_then(|(_, v)| v.parse().ok())
+    };
+    find("s-maxage").or_else(|| find("max-age"))
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry<V> {
+    pub value: V,
+    pub expires_at: Instant,
+}
+
+impl<V> Entry<V> {
+    pub fn remaining(&self) -> Duration {
+        self.expires_at.saturating_duration_since(Instant::now())
+    }
+}
+
+pub struct Cache<V> {
+    entries: Mutex<LinkedHashMap<String, Entry<V>>>,
+    capacity: usize,
+}
+
+impl<V: Clone> Cache<V> {
+    pub fn new(capacity: usize) -> Cache<V> {
+        Cache {
+            entries: Mutex::new(LinkedHashMap::new()),
+            capacity,
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<Entry<V>> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get_refresh(key) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&self, key: String, value: V, ttl: Duration) -> Entry<V> {
+        let entry = Entry {
+            value,
+            expires_at: Instant::now() + ttl,
+        };
+        if self.capacity == 0 {
+            return entry;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(key, entry.clone());
+        while entries.len() > self.capacity {
+            entries.pop_front();
+        }
+        entry
+    }
+
+    pub fn remove(&self, key: &str) {
+        self.entries.lock().unwrap().remove(key);
+    }
+
+    // expired entries included, they are only let go of when looked up or pushed out
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CachedFeed {
+    pub feed: Feed,
+    // the allowed response headers of the final hop
+    pub upstream_headers: BTreeMap<String, String>,
+    // the HTTP version the feed was fetched over, unknown for entries stored before it was kept
+    pub upstream_protocol: Option<String>,
+    // the address it was fetched from, likewise
+    pub upstream_ip: Option<IpAddr>,
+    // see `alias::fingerprint`, kept to tell the urls serving the same feed
+    pub fingerprint: Option<String>,
+    // the cookies the fetch sent on to later hops, see `CookieJar`
+    pub cookies_replayed: usize,
+    // when the upstream last answered with the feed, or confirmed it with a 304
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl CachedFeed {
+    // every entry starts here, so none goes without `fetched_at`
+    pub fn new(feed: Feed, fetched_at: DateTime<Utc>) -> CachedFeed {
+        CachedFeed {
+            fingerprint: alias::fingerprint(&feed.items),
+            feed,
+            upstream_headers: BTreeMap::new(),
+            upstream_protocol: None,
+            upstream_ip: None,
+            cookies_replayed: 0,
+            fetched_at,
+        }
+    }
+
+    // names what the entry holds, the same feed fetched again gets the same token
+    pub fn snapshot_token(&self, url: &str) -> SnapshotToken {
+        let mut bytes = url.as_bytes().to_vec();
+        bytes.push(0);
+        bytes.extend(serde_json::to_vec(&self.feed).unwrap_or_default());
+        bytes.extend(serde_json::to_vec(&self.upstream_headers).unwrap_or_default());
+        SnapshotToken(format!("{:016x}", fnv1a(&bytes)))
+    }
+}
+
+// a parse that failed, answered again without a fetch until the negative ttl is up
+#[derive(Debug, Clone)]
+pub struct Failure {
+    // the options it was parsed with, None for any options
+    pub options: Option<ParseOptions>,
+    pub error: ApiError,
+    // when the body that failed was fetched
+    pub fetched_at: DateTime<Utc>,
+}
+
+// 16 hex digits, as handed out in X-Rssss-Snapshot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotToken(String);
+
+impl SnapshotToken {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    // snapshots of another url are as good as expired
+    pub fn key(&self, url: &str) -> String {
+        format!("{} {}", self.0, url)
+    }
+}
+
+impl FromStr for SnapshotToken {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() == 16 && s.bytes().all(|b| b.is_ascii_hexdigit()) {
+            Ok(SnapshotToken(s.to_ascii_lowercase()))
+        } else {
+            Err(format!("invalid snapshot: {}", s))
+        }
+    }
+}
+
+pub struct Caches {
+    pub feeds: Cache<CachedFeed>,
+    // by token and url, the entries paginated requests were served from
+    pub snapshots: Cache<CachedFeed>,
+    pub gone: Cache<()>,
+    // the parse errors by url
+    pub failures: Cache<Failure>,
+    // by host, None when the site has no usable icon
+    pub icons: Cache<Option<Icon>>,
+    // by origin, see `robots::origin`
+    pub robots: Cache<Robots>,
+    #[cfg(feature = "cache-disk")]
+    disk: Option<DiskCache>,
+}
+
+impl Caches {
+    pub fn new(config: &CacheConfig) -> Caches {
+        Caches {
+            feeds: Cache::new(config.capacity),
+            snapshots: Cache::new(config.capacity),
+            gone: Cache::new(config.capacity),
+            failures: Cache::new(config.negative_capacity),
+            icons: Cache::new(config.capacity),
+            robots: Cache::new(config.robots_capacity),
+            #[cfg(feature = "cache-disk")]
+            disk: config
+                .disk_dir
+                .clone()
+                .map(|dir| DiskCache::new(dir, config.disk_capacity)),
+        }
+    }
+
+    // what the disk cache has of the feed, never anything without it
+    #[cfg(feature = "cache-disk")]
+    pub async fn load(&self, key: &str) -> Option<Stored> {
+        self.disk.as_ref()?.load(key).await
+    }
+
+    #[cfg(not(feature = "cache-disk"))]
+    pub async fn load(&self, _: &str) -> Option<Stored> {
+        None
+    }
+
+    // `stored` is only called for with a disk cache to keep it in
+    #[cfg(feature = "cache-disk")]
+    pub async fn store(&self, stored: impl FnOnce() -> Stored) {
+        if let Some(disk) = &self.disk {
+            disk.store(stored()).await;
+        }
+    }
+
+    #[cfg(not(feature = "cache-disk"))]
+    pub async fn store(&self, _: impl FnOnce() -> Stored) {}
+}
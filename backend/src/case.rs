@@ -0,0 +1,70 @@
+use actix_web::{HttpResponse, HttpResponseBuilder};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Case {
+    #[default]
+    Snake,
+    Camel,
+}
+
+impl FromStr for Case {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Case, String> {
+        match s {
+            "snake" => Ok(Case::Snake),
+            "camel" => Ok(Case::Camel),
+            _ => Err(format!("unsupported case: {}", s)),
+        }
+    }
+}
+
+// serializes as is for snake case, the structs' own naming,
+// otherwise renames the keys of every object at any depth after serializing
+pub fn json<T: Serialize>(builder: &mut HttpResponseBuilder, case: Case, body: T) -> HttpResponse {
+    match case {
+        Case::Snake => builder.json(body),
+        Case::Camel => match serde_json::to_value(body) {
+            Ok(value) => builder.json(to_camel(value)),
+            Err(e) => HttpResponse::from_error(e),
+        },
+    }
+}
+
+// the JSON of the body with its keys in the case, for bodies not written by `json`
+pub fn to_value<T: Serialize>(case: Case, body: T) -> serde_json::Result<Value> {
+    let value = serde_json::to_value(body)?;
+    Ok(match case {
+        Case::Snake => value,
+        Case::Camel => to_camel(value),
+    })
+}
+
+fn to_camel(value: Value) -> Value {
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .into_iter()
+                .map(|(key, value)| (camel(&key), to_camel(value)))
+                .collect::<Map<String, Value>>(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(to_camel).collect()),
+        value => value,
+    }
+}
+
+fn camel(key: &str) -> String {
+    let mut words = key.split('_');
+    let mut camel = words.next().unwrap_or_default().to_string();
+    for word in words {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            camel.extend(first.to_uppercase());
+            camel.push_str(chars.as_str());
+        }
+    }
+    camel
+}
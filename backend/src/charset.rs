@@ -0,0 +1,74 @@
+use actix_web::web::Bytes;
+use std::ops::Range;
+
+// how far into the document the end of its XML declaration is looked for
+const DECLARATION_LIMIT: usize = 1_024;
+
+// charsets of a byte per character, what content management systems fall back to declaring
+// when the database underneath has long been UTF-8
+const SINGLE_BYTE: [&str; 8] = [
+    "ascii",
+    "koi8-r",
+    "koi8-u",
+    "l1",
+    "latin1",
+    "macintosh",
+    "us-ascii",
+    "x-mac-roman",
+];
+// followed by the number of one of them
+const SINGLE_BYTE_FAMILIES: [&str; 4] = ["cp125", "iso-8859-", "iso8859-", "windows-125"];
+
+fn single_byte(encoding: &str) -> bool {
+    let encoding = encoding.trim().to_ascii_lowercase();
+    SINGLE_BYTE.contains(&encoding.as_str())
+        || SINGLE_BYTE_FAMILIES.iter().any(|family| {
+            encoding.strip_prefix(family).is_some_and(|number| {
+                !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit())
+            })
+        })
+}
+
+// a document declared in a single byte charset that is valid UTF-8 with characters past ASCII
+// is UTF-8, text in those charsets seldom is by chance. ASCII reads the same either way
+pub fn mislabeled_utf8(declared: &str, buf: &[u8]) -> bool {
+    single_byte(declared) && !buf.is_ascii() && std::str::from_utf8(buf).is_ok()
+}
+
+// where the value of `encoding` is in the XML declaration the document starts with
+fn declared(buf: &[u8]) -> Option<Range<usize>> {
+    if !buf.starts_with(b"<?xml") {
+        return None;
+    }
+    let head = &buf[..buf.len().min(DECLARATION_LIMIT)];
+    let end = head.windows(2).position(|w| w == b"?>")?;
+    let declaration = &head[..end];
+    let at = declaration.windows(8).position(|w| w == b"encoding")? + 8;
+    let (offset, quote) = declaration[at..]
+        .iter()
+        .enumerate()
+        .find(|(_, b)| !b.is_ascii_whitespace() && **b != b'=')?;
+    if !matches!(quote, b'"' | b'\'') {
+        return None;
+    }
+    let start = at + offset + 1;
+    let len = declaration.get(start..)?.iter().position(|b| b == quote)?;
+    Some(start..start + len)
+}
+
+// the document declared UTF-8 instead when it is mislabeled, with what it declared
+pub fn prefer_utf8(buf: Bytes) -> (Bytes, Option<String>) {
+    let range = match declared(&buf) {
+        Some(range) => range,
+        None => return (buf, None),
+    };
+    let encoding = String::from_utf8_lossy(&buf[range.clone()]).to_string();
+    if !mislabeled_utf8(&encoding, &buf) {
+        return (buf, None);
+    }
+    let mut relabeled = Vec::with_capacity(buf.len());
+    relabeled.extend_from_slice(&buf[..range.start]);
+    relabeled.extend_from_slice(b"UTF-8");
+    relabeled.extend_from_slice(&buf[range.end..]);
+    (Bytes::from(relabeled), Some(encoding))
+}
@@ -0,0 +1,259 @@
+use crate::alias;
+use crate::config::Config;
+use crate::deadline::{Phase, Progress};
+use crate::headers;
+use crate::hosts::validate_url;
+use crate::rss::{self, ParseOptions};
+use actix_web::http::header::{self, HeaderName};
+use actix_web::rt::time::timeout;
+use awc::error::SendRequestError;
+use awc::SendClientRequest;
+use futures::future;
+use futures::stream::{self, StreamExt};
+use serde_derive::Serialize;
+use std::cell::Cell;
+use std::time::Duration;
+use url::Url;
+
+pub const MAX_URLS: usize = 50;
+// feeds checked at once, the others wait for a slot
+pub const CONCURRENCY: usize = 4;
+// the same as `/feed` takes, a feed over it is broken for rssss
+pub const BODY_LIMIT: usize = 1_048_576;
+const REDIRECT_LIMIT: u8 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    // fine, but at another url than the one checked
+    Redirected,
+    ParseError,
+    HttpError,
+    // started but not finished before the deadline
+    Timeout,
+    // still waiting for a slot at the deadline
+    Pending,
+}
+
+// what `/feeds/check` tells about each url, the items themselves are left out
+#[derive(Debug, Serialize)]
+pub struct CheckResult {
+    pub url: String,
+    pub status: CheckStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub final_url: Option<String>,
+    // see `alias::fingerprint`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+    // the other urls checked that serve the same items, likely subscribed to twice
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+}
+
+impl CheckResult {
+    fn new(url: &str, status: CheckStatus, detail: impl Into<String>) -> CheckResult {
+        CheckResult {
+            url: url.to_string(),
+            status,
+            detail: Some(detail.into()),
+            item_count: None,
+            final_url: None,
+            fingerprint: None,
+            aliases: Vec::new(),
+        }
+    }
+
+    fn at(self, final_url: &Url) -> CheckResult {
+        CheckResult {
+            final_url: Some(final_url.to_string()),
+            ..self
+        }
+    }
+}
+
+// in request order, the checks done by the deadline as they ended and the others as they stood.
+// aliases are reported on each of them, or with `collapse_aliases` folded into the first.
+// the bytes of each check are added to `downloaded` as it ends
+pub async fn check_feeds(
+    urls: &[Url],
+    f: fn(&str, &[(HeaderName, String)]) -> SendClientRequest,
+    config: &Config,
+    limit: Duration,
+    collapse_aliases: bool,
+    downloaded: &Progress,
+) -> Vec<CheckResult> {
+    let states = urls
+        .iter()
+        .map(|_| (Cell::new(false), Progress::default()))
+        .collect::<Vec<(Cell<bool>, Progress)>>();
+    let mut results = urls
+        .iter()
+        .map(|_| None)
+        .collect::<Vec<Option<CheckResult>>>();
+    let checks = stream::iter(urls.iter().zip(&states).enumerate())
+        .map(|(i, (url, (started, progress)))| async move {
+            started.set(true);
+            let result = check_feed(url.clone(), f, config, progress).await;
+            (i, result)
+        })
+        .buffer_unordered(CONCURRENCY)
+        .for_each(|(i, result)| {
+            downloaded.add_downloaded(states[i].1.downloaded() as usize);
+            results[i] = Some(result);
+            future::ready(())
+        });
+    let _ = timeout(limit, checks).await;
+    let results = results
+        .into_iter()
+        .zip(urls.iter().zip(&states))
+        .map(|(result, (url, (started, progress)))| match result {
+            Some(result) => result,
+            None if started.get() => CheckResult::new(
+                url.as_str(),
+                CheckStatus::Timeout,
+                format!(
+                    "deadline of {}ms exceeded while {}",
+                    limit.as_millis(),
+                    progress.phase()
+                ),
+            ),
+            None => CheckResult::new(
+                url.as_str(),
+                CheckStatus::Pending,
+                "not started before the deadline",
+            ),
+        })
+        .collect::<Vec<CheckResult>>();
+    mark_aliases(results, collapse_aliases)
+}
+
+fn mark_aliases(mut results: Vec<CheckResult>, collapse: bool) -> Vec<CheckResult> {
+    let fingerprints = results
+        .iter()
+        .map(|r| r.fingerprint.clone())
+        .collect::<Vec<Option<String>>>();
+    let mut folded = vec![false; results.len()];
+    for group in alias::alias_groups(&fingerprints) {
+        let urls = group
+            .iter()
+            .map(|i| results[*i].url.clone())
+            .collect::<Vec<String>>();
+        for (n, i) in group.iter().enumerate() {
+            results[*i].aliases = urls.iter().filter(|u| **u != urls[n]).cloned().collect();
+            folded[*i] = collapse && n > 0;
+        }
+    }
+    results
+        .into_iter()
+        .zip(folded)
+        .filter(|(_, folded)| !folded)
+        .map(|(result, _)| result)
+        .collect()
+}
+
+// fetched and parsed like `/feed` does, without the caches
+async fn check_feed(
+    mut url: Url,
+    f: fn(&str, &[(HeaderName, String)]) -> SendClientRequest,
+    config: &Config,
+    progress: &Progress,
+) -> CheckResult {
+    let requested = url.to_string();
+    let mut redirects = 0;
+    progress.set(Phase::Fetching);
+    let mut res = loop {
+        let res = match f(url.as_str(), &[]).await {
+            Ok(res) => res,
+            Err(SendRequestError::Timeout) => {
+                return CheckResult::new(&requested, CheckStatus::Timeout, "upstream timed out")
+                    .at(&url)
+            }
+            Err(e) => {
+                return CheckResult::new(&requested, CheckStatus::HttpError, e.to_string()).at(&url)
+            }
+        };
+        if !res.status().is_redirection() {
+            break res;
+        }
+        if redirects == REDIRECT_LIMIT {
+            let detail = format!("more than {} redirects", REDIRECT_LIMIT);
+            return CheckResult::new(&requested, CheckStatus::HttpError, detail).at(&url);
+        }
+        let location = match headers::location(res.headers()) {
+            Ok(location) => location,
+            Err(e) => {
+                return CheckResult::new(&requested, CheckStatus::HttpError, e.to_string()).at(&url)
+            }
+        };
+        let next = match location.as_deref().map(|l| url.join(l.trim())) {
+            Some(Ok(next)) => validate_url(next.as_str(), &config.host_filter),
+            _ => {
+                let detail = format!("{} without a valid location", res.status());
+                return CheckResult::new(&requested, CheckStatus::HttpError, detail).at(&url);
+            }
+        };
+        url = match next {
+            Ok(next) => next,
+            Err(e) => {
+                return CheckResult::new(&requested, CheckStatus::HttpError, e.to_string()).at(&url)
+            }
+        };
+        redirects += 1;
+        progress.set(Phase::Redirect(redirects));
+    };
+    if !res.status().is_success() {
+        let detail = format!("upstream responded {}", res.status());
+        return CheckResult::new(&requested, CheckStatus::HttpError, detail).at(&url);
+    }
+    let body = match res.body().limit(BODY_LIMIT).await {
+        Ok(body) => body,
+        Err(e) => {
+            return CheckResult::new(&requested, CheckStatus::HttpError, e.to_string()).at(&url)
+        }
+    };
+    progress.add_downloaded(body.len());
+    progress.set(Phase::Parsing);
+    let content_type = headers::value(res.headers(), &header::CONTENT_TYPE, &mut Vec::new());
+    let feed = match rss::parse_rss_async(
+        body,
+        content_type.as_deref(),
+        config.parse_blocking_threshold,
+        ParseOptions {
+            limits: config.field_limits,
+            ..ParseOptions::default()
+        },
+        None,
+    )
+    .await
+    {
+        Ok(feed) => feed,
+        Err(e) => {
+            return CheckResult::new(&requested, CheckStatus::ParseError, e.to_string()).at(&url)
+        }
+    };
+    if feed.items.is_empty() {
+        return CheckResult {
+            item_count: Some(0),
+            ..CheckResult::new(&requested, CheckStatus::ParseError, "no items").at(&url)
+        };
+    }
+    let status = if url.as_str() == requested {
+        CheckStatus::Ok
+    } else {
+        CheckStatus::Redirected
+    };
+    CheckResult {
+        url: requested,
+        status,
+        detail: None,
+        item_count: Some(feed.items.len()),
+        final_url: Some(url.to_string()),
+        fingerprint: alias::fingerprint(&feed.items),
+        aliases: Vec::new(),
+    }
+}
@@ -0,0 +1,22 @@
+use chrono::{DateTime, Utc};
+use std::fmt;
+
+// what `fetched_at` is stamped and `Age` counted with, `Config::clock`. the seam tests turn
+// the time forward through
+pub trait Clock: fmt::Debug + Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+// the whole seconds since the data was fetched, 0 rather than negative for a clock set back
+pub fn age(fetched_at: DateTime<Utc>, now: DateTime<Utc>) -> u64 {
+    (now - fetched_at).num_seconds().max(0) as u64
+}
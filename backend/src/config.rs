@@ -0,0 +1,187 @@
+use crate::cache::CacheConfig;
+use crate::clock::{Clock, SystemClock};
+use crate::disconnect::ClientLimits;
+use crate::hosts::HostFilter;
+use crate::jobs::JobsConfig;
+use crate::redirects::RedirectPolicy;
+use crate::rss::{self, FeedParser, FieldLimits, XmlParser};
+use crate::upstream::{IpPreference, UpstreamConfig};
+use std::env;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use url::Url;
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub host_filter: HostFilter,
+    pub cache: CacheConfig,
+    pub expand_limit: usize,
+    // the items of a response at most, `continue_from` serves the next ones
+    pub window_items: usize,
+    // the items a feed is parsed and cached up to, the rest of the document is skipped
+    pub max_items: usize,
+    pub parse_blocking_threshold: usize,
+    // what each item is cut to, RSSSS_MARKUP_LIMIT sets `markup` and the others are fixed
+    pub field_limits: FieldLimits,
+    // RSSSS_SLOW_PARSE_MS, parsing or reading text out of HTML for longer is logged and counted
+    pub slow_parse_threshold: Duration,
+    // RSSSS_REJECT_SLOW_PARSES, a feed that parsed slowly is answered with feed_too_expensive
+    // until the negative cache lets go of it
+    pub reject_slow_parses: bool,
+    pub parser: Arc<dyn FeedParser>,
+    // stamps `fetched_at` and counts the Age of responses from it
+    pub clock: Arc<dyn Clock>,
+    // lets item pages and icons be fetched from loopback and private networks
+    pub allow_private_hosts: bool,
+    // the urls listed by `/stats`, the others are summed up
+    #[cfg(feature = "metrics")]
+    pub stats_top_urls: usize,
+    // the most a `/feed` request may take, `timeout_ms` can only lower it
+    pub request_timeout: Duration,
+    // feed bytes a client may have fetched in 24 hours, no quota when unset
+    pub daily_quota: Option<u64>,
+    // RSSSS_API_KEYS, every endpoint but /health requires one of them when any is set
+    pub api_keys: Vec<String>,
+    // RSSSS_CORS_ORIGINS, the origins browsers may call from, any when unset, see `cors`
+    pub cors_origins: Vec<String>,
+    pub upstream: UpstreamConfig,
+    // RSSSS_RESPECT_ROBOTS, feeds are only fetched where robots.txt lets rssss
+    pub respect_robots: bool,
+    // RSSSS_SELFTEST_URL, a url known to be up for `/selftest` to fetch, which is off without it
+    pub selftest_url: Option<Url>,
+    // RSSSS_FIXTURE_DIR, feeds are read from the files there instead of fetched, see `fixtures`
+    pub fixture_dir: Option<PathBuf>,
+    // RSSSS_RECORD_DIR, where `record=true` writes the bodies it fetched for a later replay
+    pub record_dir: Option<PathBuf>,
+    // the background work of requests, see `jobs`
+    pub jobs: JobsConfig,
+    // what a client is given to send its request and read the response, see `disconnect`
+    pub clients: ClientLimits,
+}
+
+impl Config {
+    pub fn from_env() -> io::Result<Config> {
+        Ok(Config {
+            host_filter: HostFilter::from_env(),
+            cache: CacheConfig {
+                capacity: read_env("RSSSS_CACHE_CAPACITY", 100)?,
+                default_ttl: Duration::from_secs(read_env("RSSSS_CACHE_TTL_SECS", 300)?),
+                min_ttl: Duration::from_secs(read_env("RSSSS_CACHE_MIN_TTL_SECS", 60)?),
+                max_ttl: Duration::from_secs(read_env("RSSSS_CACHE_MAX_TTL_SECS", 86_400)?),
+                gone_ttl: Duration::from_secs(read_env("RSSSS_GONE_CACHE_TTL_SECS", 3_600)?),
+                icon_ttl: Duration::from_secs(read_env("RSSSS_ICON_CACHE_TTL_SECS", 604_800)?),
+                #[cfg(feature = "cache-disk")]
+                disk_dir: env::var_os("RSSSS_DATA_DIR").map(|d| PathBuf::from(d).join("cache")),
+                #[cfg(feature = "cache-disk")]
+                disk_capacity: read_env("RSSSS_DISK_CACHE_MAX_BYTES", 104_857_600)?,
+                snapshot_ttl: Duration::from_secs(read_env("RSSSS_SNAPSHOT_TTL_SECS", 600)?),
+                negative_ttl: Duration::from_secs(read_env("RSSSS_NEGATIVE_CACHE_TTL_SECS", 120)?),
+                negative_capacity: read_env("RSSSS_NEGATIVE_CACHE_CAPACITY", 100)?,
+                robots_ttl: Duration::from_secs(read_env("RSSSS_ROBOTS_CACHE_TTL_SECS", 86_400)?),
+                robots_capacity: read_env("RSSSS_ROBOTS_CACHE_CAPACITY", 1_000)?,
+            },
+            expand_limit: read_env("RSSSS_EXPAND_MAX_ITEMS", 10)?,
+            window_items: read_env("RSSSS_WINDOW_ITEMS", 1_000)?.max(1),
+            max_items: read_env("RSSSS_MAX_ITEMS", 10_000)?.max(1),
+            parse_blocking_threshold: read_env(
+                "RSSSS_PARSE_BLOCKING_THRESHOLD",
+                rss::BLOCKING_THRESHOLD,
+            )?,
+            field_limits: FieldLimits {
+                markup: read_env("RSSSS_MARKUP_LIMIT", FieldLimits::default().markup)?.max(1),
+                ..FieldLimits::default()
+            },
+            slow_parse_threshold: Duration::from_millis(read_env("RSSSS_SLOW_PARSE_MS", 500)?),
+            reject_slow_parses: read_env("RSSSS_REJECT_SLOW_PARSES", false)?,
+            parser: Arc::new(XmlParser),
+            clock: Arc::new(SystemClock),
+            allow_private_hosts: read_env("RSSSS_ALLOW_PRIVATE_HOSTS", false)?,
+            #[cfg(feature = "metrics")]
+            stats_top_urls: read_env("RSSSS_STATS_TOP_URLS", 20)?,
+            request_timeout: Duration::from_secs(read_env("RSSSS_REQUEST_TIMEOUT_SECS", 30)?),
+            daily_quota: Some(read_env("RSSSS_DAILY_QUOTA_BYTES", 0)?).filter(|q| *q > 0),
+            api_keys: env::var("RSSSS_API_KEYS")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|k| !k.is_empty())
+                .map(str::to_string)
+                .collect(),
+            cors_origins: env::var("RSSSS_CORS_ORIGINS")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|o| !o.is_empty())
+                .map(str::to_string)
+                .collect(),
+            upstream: UpstreamConfig {
+                ip_preference: read_env("RSSSS_IP_PREFERENCE", IpPreference::Auto)?,
+                redirects: RedirectPolicy {
+                    budget: Duration::from_secs(read_env("RSSSS_REDIRECT_BUDGET_SECS", 60)?),
+                    ..RedirectPolicy::default()
+                },
+                stall: Duration::from_secs(read_env("RSSSS_UPSTREAM_STALL_SECS", 5)?.max(1)),
+                ..UpstreamConfig::new(
+                    read_env("RSSSS_UPSTREAM_KEEP_ALIVE_SECS", 15)?,
+                    read_env("RSSSS_UPSTREAM_MAX_CONNECTIONS", 100)?,
+                )
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?
+            },
+            respect_robots: read_env("RSSSS_RESPECT_ROBOTS", false)?,
+            selftest_url: selftest_url()?,
+            fixture_dir: env::var_os("RSSSS_FIXTURE_DIR").map(PathBuf::from),
+            record_dir: env::var_os("RSSSS_RECORD_DIR").map(PathBuf::from),
+            jobs: JobsConfig {
+                workers: read_env("RSSSS_JOB_WORKERS", 4)?.max(1),
+                queue_size: read_env("RSSSS_JOB_QUEUE_SIZE", 100)?.max(1),
+                timeout: Duration::from_secs(read_env("RSSSS_JOB_TIMEOUT_SECS", 30)?),
+            },
+            clients: ClientLimits {
+                request: Duration::from_secs(read_env("RSSSS_CLIENT_REQUEST_TIMEOUT_SECS", 5)?),
+                disconnect: Duration::from_secs(read_env(
+                    "RSSSS_CLIENT_DISCONNECT_TIMEOUT_SECS",
+                    1,
+                )?),
+                write: Duration::from_secs(read_env("RSSSS_WRITE_TIMEOUT_SECS", 30)?.max(1)),
+                send_buffer: Some(read_env("RSSSS_CLIENT_SEND_BUFFER_BYTES", 0)?)
+                    .filter(|b| *b > 0),
+            },
+        })
+    }
+}
+
+fn selftest_url() -> io::Result<Option<Url>> {
+    let url = match env::var("RSSSS_SELFTEST_URL") {
+        Ok(url) if !url.trim().is_empty() => url,
+        _ => return Ok(None),
+    };
+    let invalid = |message: String| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("RSSSS_SELFTEST_URL: {}", message),
+        )
+    };
+    let url = url
+        .trim()
+        .parse::<Url>()
+        .map_err(|e| invalid(e.to_string()))?;
+    match url.scheme() {
+        "http" | "https" => Ok(Some(url)),
+        scheme => Err(invalid(format!("unsupported scheme: {}", scheme))),
+    }
+}
+
+pub fn read_env<T: FromStr>(key: &str, default: T) -> io::Result<T>
+where
+    T::Err: std::fmt::Display,
+{
+    match env::var(key) {
+        Ok(v) => v
+            .parse::<T>()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("{}: {}", key, e))),
+        Err(_) => Ok(default),
+    }
+}
@@ -0,0 +1,145 @@
+use actix_web::cookie::Cookie;
+use actix_web::http::header::{self, HeaderMap};
+use std::net::IpAddr;
+use url::Url;
+
+// the cookies set by the hops of one fetch, for the hops after them. some feeds redirect to a
+// page that sets a cookie and back, and loop or 403 without it. a jar lives as long as the fetch
+// it belongs to, nothing is kept across requests, and a cookie only goes where RFC 6265 would send
+// it. public suffixes aren't known, a Domain of a single label is refused instead
+#[derive(Debug, Default)]
+pub struct CookieJar {
+    cookies: Vec<Stored>,
+    replayed: usize,
+}
+
+#[derive(Debug)]
+struct Stored {
+    name: String,
+    value: String,
+    // lowercased, without a leading dot
+    domain: String,
+    // without a Domain attribute, only for the host that set it
+    host_only: bool,
+    path: String,
+    secure: bool,
+}
+
+impl CookieJar {
+    pub const MAX_COOKIES: usize = 50;
+
+    // from each Set-Cookie of a response to `url`, those the url may not set are dropped
+    pub fn store(&mut self, url: &Url, headers: &HeaderMap) {
+        let host = match url.host_str() {
+            Some(host) => host.to_ascii_lowercase(),
+            None => return,
+        };
+        for value in headers.get_all(header::SET_COOKIE) {
+            let cookie = match value.to_str().ok().and_then(|v| Cookie::parse(v).ok()) {
+                Some(cookie) => cookie,
+                None => continue,
+            };
+            let (domain, host_only) = match cookie.domain().map(|d| d.trim_start_matches('.')) {
+                None | Some("") => (host.clone(), true),
+                Some(domain) => {
+                    let domain = domain.to_ascii_lowercase();
+                    if !domain_matches(&host, &domain) || (domain != host && !domain.contains('.'))
+                    {
+                        continue;
+                    }
+                    (domain, false)
+                }
+            };
+            let path = match cookie.path() {
+                Some(path) if path.starts_with('/') => path.to_string(),
+                _ => default_path(url),
+            };
+            self.cookies
+                .retain(|c| !(c.name == cookie.name() && c.domain == domain && c.path == path));
+            if expired(&cookie) {
+                continue;
+            }
+            if self.cookies.len() >= CookieJar::MAX_COOKIES {
+                self.cookies.remove(0);
+            }
+            self.cookies.push(Stored {
+                name: cookie.name().to_string(),
+                value: cookie.value().to_string(),
+                domain,
+                host_only,
+                path,
+                secure: cookie.secure().unwrap_or(false),
+            });
+        }
+    }
+
+    // the Cookie header for a request to `url`, longer paths first as RFC 6265 orders them
+    pub fn header(&mut self, url: &Url) -> Option<String> {
+        let host = url.host_str()?.to_ascii_lowercase();
+        let secure = url.scheme() == "https";
+        let mut cookies = self
+            .cookies
+            .iter()
+            .filter(|c| {
+                let host_matches = if c.host_only {
+                    host == c.domain
+                } else {
+                    domain_matches(&host, &c.domain)
+                };
+                host_matches && path_matches(url.path(), &c.path) && (secure || !c.secure)
+            })
+            .collect::<Vec<&Stored>>();
+        if cookies.is_empty() {
+            return None;
+        }
+        cookies.sort_by_key(|c| std::cmp::Reverse(c.path.len()));
+        self.replayed += cookies.len();
+        Some(
+            cookies
+                .iter()
+                .map(|c| format!("{}={}", c.name, c.value))
+                .collect::<Vec<String>>()
+                .join("; "),
+        )
+    }
+
+    // the cookies sent so far, counted once for each request they went with
+    pub fn replayed(&self) -> usize {
+        self.replayed
+    }
+}
+
+// an address only matches itself, a name itself and the names under it
+fn domain_matches(host: &str, domain: &str) -> bool {
+    if host == domain {
+        return true;
+    }
+    host.parse::<IpAddr>().is_err()
+        && host
+            .strip_suffix(domain)
+            .is_some_and(|rest| rest.ends_with('.'))
+}
+
+fn path_matches(path: &str, cookie_path: &str) -> bool {
+    match path.strip_prefix(cookie_path) {
+        Some(rest) => rest.is_empty() || cookie_path.ends_with('/') || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+// the directory of the request path
+fn default_path(url: &Url) -> String {
+    match url.path().rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(i) => url.path()[..i].to_string(),
+    }
+}
+
+fn expired(cookie: &Cookie) -> bool {
+    if let Some(max_age) = cookie.max_age() {
+        return max_age.is_zero() || max_age.is_negative();
+    }
+    cookie
+        .expires_datetime()
+        .is_some_and(|expires| expires <= actix_web::cookie::time::OffsetDateTime::now_utc())
+}
@@ -0,0 +1,48 @@
+use crate::config::Config;
+use crate::trace::{TRACEPARENT, TRACESTATE};
+use crate::ROUTES;
+use actix_cors::Cors;
+use actix_web::http::header::{self, HeaderName};
+use actix_web::http::Method;
+
+// the request headers the endpoints read, Content-Type for the JSON and form bodies posted
+pub const REQUEST_HEADERS: [HeaderName; 7] = [
+    header::AUTHORIZATION,
+    header::ACCEPT,
+    header::CONTENT_TYPE,
+    header::IF_NONE_MATCH,
+    header::IF_MODIFIED_SINCE,
+    HeaderName::from_static(TRACEPARENT),
+    HeaderName::from_static(TRACESTATE),
+];
+
+pub const EXPOSED_HEADERS: [&str; 3] = ["X-Total-Count", "X-Total-Pages", "X-Quota-Remaining"];
+
+// the methods of `ROUTES` once each, in the order they first appear
+pub fn methods() -> Vec<Method> {
+    let mut methods = Vec::new();
+    for method in ROUTES.iter().flat_map(|(_, methods)| methods.iter()) {
+        if !methods.contains(method) {
+            methods.push(method.clone());
+        }
+    }
+    methods
+}
+
+// RSSSS_CORS_ORIGINS lists the origins a browser may call from, any when it is empty. a
+// preflight from another origin is answered 400 before it gets to the routes
+pub fn cors(config: &Config) -> Cors {
+    let cors = if config.cors_origins.is_empty() {
+        Cors::default().allowed_origin_fn(|_origin, _req_head| true)
+    } else {
+        config
+            .cors_origins
+            .iter()
+            .fold(Cors::default(), |cors, origin| cors.allowed_origin(origin))
+    };
+    cors.allowed_methods(methods())
+        .allowed_headers(REQUEST_HEADERS)
+        .expose_headers(EXPOSED_HEADERS)
+        .supports_credentials()
+        .max_age(3600)
+}
@@ -0,0 +1,127 @@
+use chrono::{DateTime, FixedOffset, NaiveDateTime, SecondsFormat, TimeDelta, TimeZone, Utc};
+use std::str::FromStr;
+
+// RSS 2.0 uses RFC 2822 dates, Atom and Dublin Core use RFC 3339
+pub fn parse_date(date: &str) -> Option<DateTime<FixedOffset>> {
+    let date = date.trim();
+    DateTime::parse_from_rfc2822(date)
+        .or_else(|_| DateTime::parse_from_rfc3339(date))
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(date, "%Y-%m-%dT%H:%M:%S")
+                .ok()
+                .map(|d| Utc.from_utc_datetime(&d).fixed_offset())
+        })
+}
+
+// what is shown of a date `Plausible` flags, `future_dates`. sorting and grouping take the
+// item as undated whichever it is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FutureDates {
+    // as written
+    #[default]
+    Keep,
+    // moved to the nearest plausible date
+    Clamp,
+    // left out
+    Ignore,
+}
+
+impl FromStr for FutureDates {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<FutureDates, String> {
+        match s {
+            "keep" => Ok(FutureDates::Keep),
+            "clamp" => Ok(FutureDates::Clamp),
+            "ignore" => Ok(FutureDates::Ignore),
+            _ => Err(format!("unsupported future_dates: {}", s)),
+        }
+    }
+}
+
+// the range of dates an item may have, anything else is more likely a broken epoch conversion
+// or a zeroed field than a date. `now` is `Config::clock`'s
+#[derive(Debug, Clone, Copy)]
+pub struct Plausible {
+    pub now: DateTime<Utc>,
+    pub policy: FutureDates,
+}
+
+impl Plausible {
+    pub const AHEAD_HOURS: i64 = 48;
+    pub const EARLIEST_YEAR: i32 = 1990;
+
+    pub fn earliest() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(Plausible::EARLIEST_YEAR, 1, 1, 0, 0, 0)
+            .unwrap()
+    }
+    pub fn latest(&self) -> DateTime<Utc> {
+        self.now + TimeDelta::hours(Plausible::AHEAD_HOURS)
+    }
+    // why the date is implausible and what the policy makes of it, None for dates within
+    // the range and the ones that can't be parsed
+    pub fn check(&self, date: &str) -> Option<(String, Option<String>)> {
+        let parsed = parse_date(date)?;
+        let (reason, nearest) = if parsed > self.latest() {
+            let reason = format!("is more than {} hours ahead", Plausible::AHEAD_HOURS);
+            (reason, self.latest())
+        } else if parsed < Plausible::earliest() {
+            let reason = format!("is before {}", Plausible::EARLIEST_YEAR);
+            (reason, Plausible::earliest())
+        } else {
+            return None;
+        };
+        let shown = match self.policy {
+            FutureDates::Keep => Some(date.to_string()),
+            FutureDates::Clamp => Some(nearest.to_rfc3339_opts(SecondsFormat::Secs, true)),
+            FutureDates::Ignore => None,
+        };
+        Some((reason, shown))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TzOffset(pub FixedOffset);
+
+impl Default for TzOffset {
+    fn default() -> TzOffset {
+        TzOffset(FixedOffset::east_opt(0).unwrap())
+    }
+}
+
+// accepts "Z", "UTC", "+09:00", "+0900" and "-03:30",
+// a leading space is taken as "+" since it is how an unescaped "+" arrives in a query string
+impl FromStr for TzOffset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<TzOffset, String> {
+        let invalid = || {
+            format!(
+                "unexpected value: {}, one of Z, UTC or an offset like +09:00, +0900 or -03:30",
+                s
+            )
+        };
+        if s.eq_ignore_ascii_case("z") || s.eq_ignore_ascii_case("utc") {
+            return Ok(TzOffset::default());
+        }
+        let (sign, rest) = match s.chars().next() {
+            Some('+') | Some(' ') => (1, &s[1..]),
+            Some('-') => (-1, &s[1..]),
+            _ => return Err(invalid()),
+        };
+        let (hours, minutes) = match rest.split_once(':') {
+            Some((h, m)) => (h, m),
+            None if rest.len() == 4 => rest.split_at(2),
+            None => (rest, "0"),
+        };
+        let hours = hours.parse::<i32>().map_err(|_| invalid())?;
+        let minutes = minutes.parse::<i32>().map_err(|_| invalid())?;
+        if !(0..=14).contains(&hours) || !(0..60).contains(&minutes) {
+            return Err(invalid());
+        }
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+            .map(TzOffset)
+            .ok_or_else(invalid)
+    }
+}
@@ -0,0 +1,113 @@
+use crate::config::Config;
+use crate::error::{ApiError, DeadlineExceededError, InvalidParameterError};
+use crate::rss::ItemSink;
+use crate::trace::TraceContext;
+use actix_web::rt::time::timeout;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::future::Future;
+use std::rc::Rc;
+use std::time::Duration;
+
+// where a `/feed` request is, for the error when its deadline passes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Phase {
+    #[default]
+    Fetching,
+    Redirect(u8),
+    Parsing,
+    Expanding,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Phase::Fetching => write!(f, "fetching"),
+            Phase::Redirect(n) => write!(f, "following redirect {}", n),
+            Phase::Parsing => write!(f, "parsing"),
+            Phase::Expanding => write!(f, "expanding items"),
+        }
+    }
+}
+
+// shared by the pipeline, which moves it forward, and the deadline, which reports it
+#[derive(Debug, Clone, Default)]
+pub struct Progress {
+    phase: Rc<Cell<Phase>>,
+    // feed bytes fetched from upstream, for the quota
+    downloaded: Rc<Cell<u64>>,
+    // the span of the request, the fetches and the parse are its children
+    trace: Option<TraceContext>,
+    // for a streamed response, taken by the parse
+    sink: Rc<RefCell<Option<ItemSink>>>,
+}
+
+impl Progress {
+    pub fn traced(trace: TraceContext) -> Progress {
+        Progress {
+            trace: Some(trace),
+            ..Progress::default()
+        }
+    }
+
+    pub fn trace(&self) -> Option<&TraceContext> {
+        self.trace.as_ref()
+    }
+
+    pub fn stream_to(&self, sink: ItemSink) {
+        self.sink.replace(Some(sink));
+    }
+
+    // the receiver ends once the sink is taken and the parse is done with it, or it's dropped here
+    pub fn take_sink(&self) -> Option<ItemSink> {
+        self.sink.take()
+    }
+
+    pub fn set(&self, phase: Phase) {
+        self.phase.set(phase);
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase.get()
+    }
+
+    pub fn add_downloaded(&self, bytes: usize) {
+        self.downloaded.set(self.downloaded.get() + bytes as u64);
+    }
+
+    pub fn downloaded(&self) -> u64 {
+        self.downloaded.get()
+    }
+}
+
+// the server max, lowered by `timeout_ms` but never raised
+pub fn limit(config: &Config, timeout_ms: Option<u64>) -> Result<Duration, ApiError> {
+    match timeout_ms {
+        Some(0) => Err(InvalidParameterError {
+            name: "timeout_ms".to_string(),
+            message: "must be 1 or greater".to_string(),
+        }
+        .into()),
+        Some(ms) => Ok(Duration::from_millis(ms).min(config.request_timeout)),
+        None => Ok(config.request_timeout),
+    }
+}
+
+// the future is dropped when the deadline passes, with whatever it was waiting on
+pub async fn with_deadline<T, F>(
+    limit: Duration,
+    progress: &Progress,
+    future: F,
+) -> Result<T, ApiError>
+where
+    F: Future<Output = Result<T, ApiError>>,
+{
+    match timeout(limit, future).await {
+        Ok(output) => output,
+        Err(_) => Err(DeadlineExceededError {
+            limit,
+            phase: progress.phase().to_string(),
+        }
+        .into()),
+    }
+}
@@ -0,0 +1,214 @@
+use crate::stats::Stats;
+use actix_web::body::{BodySize, MessageBody};
+use actix_web::dev::Extensions;
+use actix_web::rt::net::TcpStream;
+use actix_web::rt::time::{sleep, sleep_until};
+use actix_web::web::{self, Bytes};
+use actix_web::HttpRequest;
+use futures::future::{self, Either};
+use futures::pin_mut;
+use log::{info, warn};
+use socket2::SockRef;
+use std::any::Any;
+use std::cell::Cell;
+use std::future::Future;
+use std::net::Shutdown;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+// a second handle on the client's socket, actix keeps polling a handler to the end
+// even when its client has gone, this is how a handler finds out
+#[derive(Clone)]
+struct Connection(Rc<TcpStream>);
+
+// for `HttpServer::on_connect`, with `ClientLimits::send_buffer`
+pub fn on_connect(send_buffer: Option<usize>) -> impl Fn(&dyn Any, &mut Extensions) {
+    move |connection, extensions| {
+        let stream = match connection.downcast_ref::<TcpStream>() {
+            Some(stream) => stream,
+            None => return,
+        };
+        if let Some(size) = send_buffer {
+            if let Err(e) = SockRef::from(stream).set_send_buffer_size(size) {
+                warn!("can't limit the send buffer: {}", e);
+            }
+        }
+        let probe = SockRef::from(stream)
+            .try_clone()
+            .and_then(|socket| TcpStream::from_std(socket.into()));
+        match probe {
+            Ok(probe) => {
+                extensions.insert(Connection(Rc::new(probe)));
+            }
+            Err(e) => warn!("can't watch the connection: {}", e),
+        }
+    }
+}
+
+// None when the client disconnects first, the future is dropped right away with
+// whatever upstream request it is waiting on
+pub async fn or_disconnect<F: Future>(req: &HttpRequest, future: F) -> Option<F::Output> {
+    let connection = match req.conn_data::<Connection>() {
+        Some(connection) => connection.clone(),
+        None => return Some(future.await),
+    };
+    let closed = closed(&connection.0);
+    pin_mut!(future, closed);
+    match future::select(future, closed).await {
+        Either::Left((output, _)) => Some(output),
+        Either::Right(_) => {
+            info!("client disconnected: {}", req.uri());
+            None
+        }
+    }
+}
+
+async fn closed(stream: &TcpStream) {
+    let mut buf = [0; 1];
+    loop {
+        match stream.peek(&mut buf).await {
+            Ok(0) | Err(_) => return,
+            // a pipelined request, it stays readable until actix reads it
+            Ok(_) => sleep(Duration::from_millis(100)).await,
+        }
+    }
+}
+
+// what a client is given before it is let go of, see `HttpServer::client_request_timeout` and
+// `HttpServer::client_disconnect_timeout` for the first two
+#[derive(Debug, Clone)]
+pub struct ClientLimits {
+    // RSSSS_CLIENT_REQUEST_TIMEOUT_SECS, for the head of a request to have been sent
+    pub request: Duration,
+    // RSSSS_CLIENT_DISCONNECT_TIMEOUT_SECS, for a client to close its side after rssss did
+    pub disconnect: Duration,
+    // RSSSS_WRITE_TIMEOUT_SECS, a client that reads nothing of a response for longer is dropped
+    pub write: Duration,
+    // RSSSS_CLIENT_SEND_BUFFER_BYTES, what the kernel may hold of a response for a client,
+    // the write timeout only sees a client stall once that is full, the kernel's own when unset
+    pub send_buffer: Option<usize>,
+}
+
+impl Default for ClientLimits {
+    fn default() -> ClientLimits {
+        ClientLimits {
+            request: Duration::from_secs(5),
+            disconnect: Duration::from_secs(1),
+            write: Duration::from_secs(30),
+            send_buffer: None,
+        }
+    }
+}
+
+// bodies are handed over in chunks of this size at most, a client reading slowly but
+// steadily keeps asking for the next one
+const CHUNK_SIZE: usize = 16 * 1024;
+
+#[derive(Debug)]
+struct Progress {
+    last: Cell<Instant>,
+    // the last chunk was handed over and the next one isn't asked for until the client
+    // reads it, otherwise the body itself is what's being waited on
+    on_client: Cell<bool>,
+    done: Cell<bool>,
+}
+
+// a response body whose client is dropped once it stops reading it for the write timeout
+pub struct Watched<B> {
+    body: B,
+    pending: Bytes,
+    progress: Rc<Progress>,
+    watchdog: Option<(Connection, Duration, web::Data<Stats>)>,
+}
+
+pub fn watch<B: MessageBody>(
+    req: &HttpRequest,
+    body: B,
+    timeout: Duration,
+    stats: web::Data<Stats>,
+) -> Watched<B> {
+    Watched {
+        body,
+        pending: Bytes::new(),
+        progress: Rc::new(Progress {
+            last: Cell::new(Instant::now()),
+            on_client: Cell::new(false),
+            done: Cell::new(false),
+        }),
+        watchdog: req
+            .conn_data::<Connection>()
+            .map(|connection| (connection.clone(), timeout, stats)),
+    }
+}
+
+impl<B: MessageBody + Unpin> MessageBody for Watched<B> {
+    type Error = B::Error;
+
+    fn size(&self) -> BodySize {
+        self.body.size()
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.get_mut();
+        if let Some((connection, timeout, stats)) = this.watchdog.take() {
+            actix_rt::spawn(watchdog(connection, timeout, stats, this.progress.clone()));
+        }
+        let progress = &this.progress;
+        progress.last.set(Instant::now());
+        if this.pending.is_empty() {
+            match Pin::new(&mut this.body).poll_next(cx) {
+                Poll::Ready(Some(Ok(bytes))) => this.pending = bytes,
+                Poll::Ready(None) => {
+                    progress.done.set(true);
+                    return Poll::Ready(None);
+                }
+                other => {
+                    progress.on_client.set(false);
+                    return other;
+                }
+            }
+        }
+        let chunk = this.pending.split_to(this.pending.len().min(CHUNK_SIZE));
+        progress.on_client.set(true);
+        Poll::Ready(Some(Ok(chunk)))
+    }
+}
+
+impl<B> Drop for Watched<B> {
+    fn drop(&mut self) {
+        self.progress.done.set(true);
+    }
+}
+
+async fn watchdog(
+    connection: Connection,
+    timeout: Duration,
+    stats: web::Data<Stats>,
+    progress: Rc<Progress>,
+) {
+    loop {
+        let deadline = if progress.on_client.get() {
+            progress.last.get() + timeout
+        } else {
+            Instant::now() + timeout
+        };
+        sleep_until(deadline.into()).await;
+        if progress.done.get() {
+            return;
+        }
+        if progress.on_client.get() && progress.last.get().elapsed() >= timeout {
+            break;
+        }
+    }
+    // actix's next write fails and it drops the connection
+    if let Err(e) = SockRef::from(&*connection.0).shutdown(Shutdown::Both) {
+        warn!("can't drop the slow client: {}", e);
+    }
+    stats.record_slow_client();
+    info!("dropped a client that read nothing for {:?}", timeout);
+}
@@ -0,0 +1,153 @@
+use crate::hash::fnv1a;
+use crate::stored::{Stored, VERSION};
+use actix_web::web;
+use log::warn;
+use serde_derive::Deserialize;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+#[derive(Deserialize)]
+struct Version {
+    version: u32,
+}
+
+// the last successful fetch of each feed under the data dir, so a restart doesn't start cold.
+// a file per url, named by a hash of it, evicted by mtime which reads touch
+#[derive(Debug, Clone)]
+pub struct DiskCache {
+    dir: PathBuf,
+    capacity: u64,
+    temp_ids: Arc<AtomicU64>,
+}
+
+impl DiskCache {
+    pub fn new(dir: PathBuf, capacity: u64) -> DiskCache {
+        DiskCache {
+            dir,
+            capacity,
+            temp_ids: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn path(&self, url: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:016x}.json", fnv1a(url.as_bytes())))
+    }
+
+    // None for what is missing, unreadable, of another version or another url
+    pub async fn load(&self, url: &str) -> Option<Stored> {
+        let path = self.path(url);
+        let url = url.to_string();
+        web::block(move || load(&path, &url)).await.ok()?
+    }
+
+    // failures are only logged, the memory cache still has the feed
+    pub async fn store(&self, stored: Stored) {
+        let cache = self.clone();
+        let result = web::block(move || {
+            cache.write(&stored)?;
+            cache.evict()
+        })
+        .await;
+        match result {
+            Ok(Ok(())) => (),
+            Ok(Err(e)) => warn!("disk cache {}: {}", self.dir.display(), e),
+            Err(e) => warn!("disk cache {}: {}", self.dir.display(), e),
+        }
+    }
+
+    // written next to the file and renamed over it, so readers never see half of it
+    fn write(&self, stored: &Stored) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(&stored.url);
+        let temp = path.with_extension(format!(
+            "{}.{}.tmp",
+            std::process::id(),
+            self.temp_ids.fetch_add(1, Ordering::Relaxed)
+        ));
+        let result = serde_json::to_vec(stored)
+            .map_err(io::Error::from)
+            .and_then(|json| fs::write(&temp, json))
+            .and_then(|_| fs::rename(&temp, &path));
+        if result.is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+        result
+    }
+
+    // the least recently used files until the rest fit
+    fn evict(&self) -> io::Result<()> {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if path.extension().is_none_or(|e| e != "json") {
+                continue;
+            }
+            let metadata = entry.metadata()?;
+            files.push((metadata.modified()?, metadata.len(), path));
+        }
+        let mut total = files.iter().map(|(_, len, _)| len).sum::<u64>();
+        files.sort();
+        for (_, len, path) in files {
+            if total <= self.capacity {
+                break;
+            }
+            match fs::remove_file(&path) {
+                Ok(()) => total -= len,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => total -= len,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn load(path: &Path, url: &str) -> Option<Stored> {
+    let json = match fs::read(path) {
+        Ok(json) => json,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            warn!("disk cache {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    // the version first, other versions may not deserialize as `Stored` at all
+    let stored = match serde_json::from_slice::<Version>(&json) {
+        Ok(Version { version }) if version != VERSION => {
+            warn!(
+                "disk cache {}: version {}, expected {}",
+                path.display(),
+                version,
+                VERSION
+            );
+            return None;
+        }
+        Ok(_) => serde_json::from_slice::<Stored>(&json),
+        Err(e) => Err(e),
+    };
+    let mut stored = match stored {
+        Ok(stored) if stored.url == url => stored,
+        // another url with the same hash
+        Ok(_) => return None,
+        Err(e) => {
+            warn!("disk cache {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    for (item, markup) in stored.feed.items.iter_mut().zip(&stored.markups) {
+        item.set_markup(markup.clone());
+    }
+    if let Err(e) = File::options()
+        .write(true)
+        .open(path)
+        .and_then(|f| f.set_modified(SystemTime::now()))
+    {
+        warn!("disk cache {}: {}", path.display(), e);
+    }
+    Some(stored)
+}
@@ -0,0 +1,142 @@
+use std::collections::HashSet;
+use url::Url;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Armenian,
+    Hebrew,
+    Arabic,
+    Devanagari,
+    Thai,
+    Hangul,
+    Hiragana,
+    Katakana,
+    Han,
+    Other,
+}
+
+// human readable form of a url: unicode host and decoded path,
+// None when it reads the same as the raw url or can't be shown safely
+pub fn display_url(url: &str) -> Option<String> {
+    let parsed = Url::parse(url).ok()?;
+    let host = parsed.host_str()?;
+    let display_host = display_host(host);
+    let display_path = display_path(parsed.path());
+
+    let mut display = format!("{}://{}", parsed.scheme(), display_host);
+    if let Some(port) = parsed.port() {
+        display.push_str(&format!(":{}", port));
+    }
+    display.push_str(&display_path);
+    if let Some(query) = parsed.query() {
+        display.push('?');
+        display.push_str(query);
+    }
+    if let Some(fragment) = parsed.fragment() {
+        display.push('#');
+        display.push_str(fragment);
+    }
+
+    if display_host == host && display_path == parsed.path() {
+        None
+    } else {
+        Some(display)
+    }
+}
+
+fn display_host(host: &str) -> String {
+    if !host.split('.').any(|label| label.starts_with("xn--")) {
+        return host.to_string();
+    }
+    let (unicode, result) = idna::domain_to_unicode(host);
+    if result.is_err() || unicode.split('.').any(is_mixed_script) {
+        return host.to_string();
+    }
+    unicode
+}
+
+// escapes of the characters that would read as another path decoded, kept as they are
+const KEPT_ESCAPED: &[u8] = b"/?#%";
+
+fn display_path(path: &str) -> String {
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut rest = path.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        let decoded = match (b, tail) {
+            (b'%', [high, low, ..]) => hex(*high).zip(hex(*low)).map(|(h, l)| h * 16 + l),
+            _ => None,
+        };
+        match decoded {
+            Some(d) if !KEPT_ESCAPED.contains(&d) => {
+                bytes.push(d);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(b);
+                rest = tail;
+            }
+        }
+    }
+    match String::from_utf8(bytes) {
+        Ok(decoded) if !decoded.chars().any(char::is_control) => decoded,
+        _ => path.to_string(),
+    }
+}
+
+fn hex(digit: u8) -> Option<u8> {
+    (digit as char).to_digit(16).map(|d| d as u8)
+}
+
+// labels mixing scripts (e.g. Latin and Cyrillic) are a common trick to spoof other domains,
+// only the combinations used by CJK languages are accepted
+fn is_mixed_script(label: &str) -> bool {
+    let scripts = label
+        .chars()
+        .filter_map(script)
+        .collect::<HashSet<Script>>();
+    if scripts.len() <= 1 {
+        return false;
+    }
+    let allowed: [&[Script]; 2] = [
+        &[
+            Script::Latin,
+            Script::Han,
+            Script::Hiragana,
+            Script::Katakana,
+        ],
+        &[Script::Latin, Script::Han, Script::Hangul],
+    ];
+    !allowed
+        .iter()
+        .any(|set| scripts.iter().all(|s| set.contains(s)))
+}
+
+fn script(c: char) -> Option<Script> {
+    if !c.is_alphabetic() {
+        return None;
+    }
+    let script = match c {
+        'a'..='z' | 'A'..='Z' | '\u{00C0}'..='\u{024F}' | '\u{1E00}'..='\u{1EFF}' => Script::Latin,
+        '\u{0370}'..='\u{03FF}' | '\u{1F00}'..='\u{1FFF}' => Script::Greek,
+        '\u{0400}'..='\u{052F}' => Script::Cyrillic,
+        '\u{0530}'..='\u{058F}' => Script::Armenian,
+        '\u{0590}'..='\u{05FF}' => Script::Hebrew,
+        '\u{0600}'..='\u{06FF}' => Script::Arabic,
+        '\u{0900}'..='\u{097F}' => Script::Devanagari,
+        '\u{0E00}'..='\u{0E7F}' => Script::Thai,
+        '\u{1100}'..='\u{11FF}' | '\u{3130}'..='\u{318F}' | '\u{AC00}'..='\u{D7AF}' => {
+            Script::Hangul
+        }
+        '\u{3040}'..='\u{309F}' => Script::Hiragana,
+        '\u{30A0}'..='\u{30FF}' | '\u{31F0}'..='\u{31FF}' | '\u{FF66}'..='\u{FF9F}' => {
+            Script::Katakana
+        }
+        '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '\u{F900}'..='\u{FAFF}' => Script::Han,
+        '\u{20000}'..='\u{2FFFF}' => Script::Han,
+        _ => Script::Other,
+    };
+    Some(script)
+}
@@ -1,72 +1,943 @@
-use actix_web::error::PayloadError;
-use actix_web::ResponseError;
+#[cfg(feature = "admin-ui")]
+use crate::status;
+use actix_multipart::MultipartError;
+use actix_web::error::{BlockingError, PayloadError};
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
 use awc::error::SendRequestError;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
 use serde_derive::Serialize;
+use std::time::Duration;
 use xml::reader::Error as XMLReaderError;
 
 pub struct InvalidRssError {
+    pub code: ErrorCode,
     pub message: String,
 }
 
-#[derive(Serialize, Debug)]
-pub struct Error<T> {
-    messages: Vec<T>,
+pub struct InvalidUrlError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+pub struct InvalidParameterError {
+    pub name: String,
+    pub message: String,
+}
+
+pub struct HostNotAllowedError {
+    pub host: String,
+}
+
+pub struct UpstreamStatusError {
+    pub status: StatusCode,
+    pub user_agent: String,
 }
 
-impl From<XMLReaderError> for Error<String> {
-    fn from(error: XMLReaderError) -> Error<String> {
-        Error {
-            messages: vec![error.to_string()],
+pub struct InvalidRedirectError {
+    pub location: Option<String>,
+}
+
+// a Location longer than `headers::MAX_LOCATION_LEN`, never followed
+pub struct LocationTooLongError {
+    pub len: usize,
+    pub limit: usize,
+}
+
+pub struct PayloadTooLargeError {
+    pub limit: usize,
+}
+
+pub struct TooManyDocumentsError {
+    pub limit: usize,
+}
+
+pub struct IconNotFoundError {
+    pub host: String,
+}
+
+pub struct UnsupportedMediaTypeError {
+    pub content_type: String,
+}
+
+// a document that isn't a feed, or a feed format that isn't supported
+pub struct UnsupportedFormatError {
+    pub message: String,
+}
+
+// a snapshot token no longer or never held by the snapshot cache
+pub struct SnapshotExpiredError;
+
+// keys are never echoed back, only whether one was given
+pub struct UnauthorizedError {
+    pub missing: bool,
+}
+
+pub struct QuotaExceededError {
+    pub limit: u64,
+    pub used: u64,
+    pub resets_at: DateTime<Utc>,
+}
+
+pub struct RedirectLimitError {
+    pub limit: u8,
+}
+
+// the hops of a fetch took longer than `RedirectPolicy::budget`, the hop counted from 0
+pub struct RedirectBudgetError {
+    pub hop: u8,
+    pub url: String,
+    pub budget: Duration,
+}
+
+// a redirect with a body over `RedirectPolicy::hop_bytes`
+pub struct RedirectBodyTooLargeError {
+    pub hop: u8,
+    pub url: String,
+    pub limit: usize,
+}
+
+// with RSSSS_RESPECT_ROBOTS, a path the host's robots.txt keeps rssss from
+pub struct RobotsDisallowedError {
+    pub robots_url: String,
+    pub rule: String,
+}
+
+// a feed body that sent nothing for `UpstreamConfig::stall` and wasn't a whole document yet
+pub struct UpstreamStalledError {
+    pub bytes: usize,
+    pub idle: Duration,
+}
+
+pub struct DeadlineExceededError {
+    pub limit: Duration,
+    // what the request was doing when the time was up
+    pub phase: String,
+}
+
+// `/selftest` without RSSSS_SELFTEST_URL
+pub struct SelftestDisabledError;
+
+// with RSSSS_REJECT_SLOW_PARSES, a feed that took longer than RSSSS_SLOW_PARSE_MS to parse
+pub struct FeedTooExpensiveError {
+    pub elapsed: Duration,
+    pub threshold: Duration,
+}
+
+// with RSSSS_FIXTURE_DIR, a url no file was recorded for
+pub struct FixtureNotFoundError {
+    pub url: String,
+    pub name: String,
+    // the first of the files there are, and how many more
+    pub available: Vec<String>,
+    pub more: usize,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    HostNotAllowed,
+    FeedGone,
+    FeedNotFound,
+    AccessDenied,
+    InvalidRedirect,
+    PayloadTooLarge,
+    IconNotFound,
+    UnsupportedMediaType,
+    UnsupportedFormat,
+    SnapshotExpired,
+    DeadlineExceeded,
+    QuotaExceeded,
+    Unauthorized,
+    DisallowedByRobots,
+    SelftestDisabled,
+    FeedTooExpensive,
+    FixtureNotFound,
+    Internal,
+}
+
+impl ErrorKind {
+    pub const ALL: [ErrorKind; 18] = [
+        ErrorKind::HostNotAllowed,
+        ErrorKind::FeedGone,
+        ErrorKind::FeedNotFound,
+        ErrorKind::AccessDenied,
+        ErrorKind::InvalidRedirect,
+        ErrorKind::PayloadTooLarge,
+        ErrorKind::IconNotFound,
+        ErrorKind::UnsupportedMediaType,
+        ErrorKind::UnsupportedFormat,
+        ErrorKind::SnapshotExpired,
+        ErrorKind::DeadlineExceeded,
+        ErrorKind::QuotaExceeded,
+        ErrorKind::Unauthorized,
+        ErrorKind::DisallowedByRobots,
+        ErrorKind::SelftestDisabled,
+        ErrorKind::FeedTooExpensive,
+        ErrorKind::FixtureNotFound,
+        ErrorKind::Internal,
+    ];
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ErrorKind::HostNotAllowed => StatusCode::FORBIDDEN,
+            ErrorKind::FeedGone => StatusCode::GONE,
+            ErrorKind::FeedNotFound => StatusCode::NOT_FOUND,
+            ErrorKind::AccessDenied => StatusCode::FORBIDDEN,
+            ErrorKind::InvalidRedirect => StatusCode::BAD_GATEWAY,
+            ErrorKind::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            ErrorKind::IconNotFound => StatusCode::NOT_FOUND,
+            ErrorKind::UnsupportedMediaType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ErrorKind::UnsupportedFormat => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ErrorKind::SnapshotExpired => StatusCode::CONFLICT,
+            ErrorKind::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+            ErrorKind::QuotaExceeded => StatusCode::TOO_MANY_REQUESTS,
+            ErrorKind::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorKind::DisallowedByRobots => StatusCode::FORBIDDEN,
+            ErrorKind::SelftestDisabled => StatusCode::NOT_FOUND,
+            ErrorKind::FeedTooExpensive => StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorKind::FixtureNotFound => StatusCode::NOT_FOUND,
+            ErrorKind::Internal => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 }
 
-impl From<PayloadError> for Error<String> {
-    fn from(error: PayloadError) -> Error<String> {
-        Error {
-            messages: vec![error.to_string()],
+// what went wrong for each message, stable while the wording of the messages is not
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    XmlParseError,
+    UnsupportedEncoding,
+    InvalidRootElement,
+    InvalidRootNamespace,
+    UnsupportedRssVersion,
+    UndefinedRssVersion,
+    StrictModeWarning,
+    UnsupportedFormat,
+    InvalidUrl,
+    InvalidUrlScheme,
+    InvalidParameter,
+    HostNotAllowed,
+    FeedGone,
+    FeedNotFound,
+    AccessDenied,
+    InvalidRedirectLocation,
+    RedirectWithoutLocation,
+    RedirectLocationTooLong,
+    RedirectLimitExceeded,
+    RedirectBudgetExceeded,
+    RedirectBodyTooLarge,
+    UpstreamTimeout,
+    UpstreamConnectFailed,
+    UpstreamRequestFailed,
+    UpstreamStalled,
+    BodyTooLarge,
+    InvalidPayload,
+    InvalidMultipart,
+    InvalidBase64,
+    InvalidJson,
+    TooManyDocuments,
+    IconNotFound,
+    UnsupportedMediaType,
+    SnapshotExpired,
+    DeadlineExceeded,
+    ApiKeyRequired,
+    InvalidApiKey,
+    QuotaExceeded,
+    DisallowedByRobots,
+    SelftestDisabled,
+    FeedTooExpensive,
+    FixtureNotFound,
+    Internal,
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 43] = [
+        ErrorCode::XmlParseError,
+        ErrorCode::UnsupportedEncoding,
+        ErrorCode::InvalidRootElement,
+        ErrorCode::InvalidRootNamespace,
+        ErrorCode::UnsupportedRssVersion,
+        ErrorCode::UndefinedRssVersion,
+        ErrorCode::StrictModeWarning,
+        ErrorCode::UnsupportedFormat,
+        ErrorCode::InvalidUrl,
+        ErrorCode::InvalidUrlScheme,
+        ErrorCode::InvalidParameter,
+        ErrorCode::HostNotAllowed,
+        ErrorCode::FeedGone,
+        ErrorCode::FeedNotFound,
+        ErrorCode::AccessDenied,
+        ErrorCode::InvalidRedirectLocation,
+        ErrorCode::RedirectWithoutLocation,
+        ErrorCode::RedirectLocationTooLong,
+        ErrorCode::RedirectLimitExceeded,
+        ErrorCode::RedirectBudgetExceeded,
+        ErrorCode::RedirectBodyTooLarge,
+        ErrorCode::UpstreamTimeout,
+        ErrorCode::UpstreamConnectFailed,
+        ErrorCode::UpstreamRequestFailed,
+        ErrorCode::UpstreamStalled,
+        ErrorCode::BodyTooLarge,
+        ErrorCode::InvalidPayload,
+        ErrorCode::InvalidMultipart,
+        ErrorCode::InvalidBase64,
+        ErrorCode::InvalidJson,
+        ErrorCode::TooManyDocuments,
+        ErrorCode::IconNotFound,
+        ErrorCode::UnsupportedMediaType,
+        ErrorCode::SnapshotExpired,
+        ErrorCode::DeadlineExceeded,
+        ErrorCode::ApiKeyRequired,
+        ErrorCode::InvalidApiKey,
+        ErrorCode::QuotaExceeded,
+        ErrorCode::DisallowedByRobots,
+        ErrorCode::SelftestDisabled,
+        ErrorCode::FeedTooExpensive,
+        ErrorCode::FixtureNotFound,
+        ErrorCode::Internal,
+    ];
+}
+
+/// Every error rssss answers with, by where it came from. Each holds the messages with a code
+/// apiece, v1 responds with the messages only and v2 with the code of each, see `ApiError::coded`.
+///
+/// ```
+/// use rssss::error::{ApiError, ErrorCode};
+///
+/// let e = rssss::rss::parse_rss("<document><title>notes</title></document>").unwrap_err();
+/// assert!(matches!(e, ApiError::Parse(_)));
+/// assert_eq!(e.status().as_u16(), 400);
+/// assert!(e.codes().contains(&ErrorCode::InvalidRootElement));
+/// assert!(e.to_string().contains("invalid root element: document"));
+/// ```
+#[derive(Debug, Clone)]
+pub enum ApiError {
+    // parameters, urls and hosts, keys and quotas, what the client can fix
+    Request(Details),
+    // the upstream couldn't be reached or didn't answer in time
+    Transport(Details),
+    // the upstream answered, but not with a feed
+    UpstreamStatus(Details),
+    // a body too large or not in the encoding it claims
+    Payload(Details),
+    // a document no parser took
+    Parse(Details),
+    Internal(Details),
+}
+
+#[derive(Debug, Clone)]
+pub struct Details {
+    kind: Option<ErrorKind>,
+    messages: Vec<String>,
+    codes: Vec<ErrorCode>,
+    parsers: Vec<ParserDiagnostic>,
+}
+
+// the v1 body, `{"kind": ..., "messages": [...]}`
+#[derive(Serialize)]
+struct Messages<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kind: Option<ErrorKind>,
+    messages: &'a [String],
+}
+
+#[derive(Serialize, Debug)]
+pub struct CodedErrors<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kind: Option<ErrorKind>,
+    errors: Vec<CodedError<'a>>,
+    #[serde(skip_serializing_if = "<[ParserDiagnostic]>::is_empty")]
+    parsers: &'a [ParserDiagnostic],
+}
+
+// how far each parser got with a document none of them could parse, in the order they tried
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ParserDiagnostic {
+    pub parser: &'static str,
+    pub code: ErrorCode,
+    pub error: String,
+    // the items it had read before the error
+    pub items_recovered: usize,
+    pub failed_at_line: u64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CodedError<'a> {
+    code: ErrorCode,
+    message: &'a str,
+}
+
+impl ApiError {
+    fn new(
+        variant: fn(Details) -> ApiError,
+        kind: Option<ErrorKind>,
+        code: ErrorCode,
+        message: String,
+    ) -> ApiError {
+        let error = variant(Details {
+            kind,
+            messages: vec![message],
+            codes: vec![code],
+            parsers: Vec::new(),
+        });
+        // each parser of the chain failing on a document another one takes isn't an error,
+        // the chain records its errors once they are all in, see `rss::parse_document`
+        if !matches!(error, ApiError::Parse(_)) {
+            error.record();
         }
+        error
+    }
+    // for `/status`, nowhere to go without it
+    pub fn record(&self) {
+        #[cfg(feature = "admin-ui")]
+        for (code, message) in self.codes().iter().zip(self.messages()) {
+            status::record_error(*code, message);
+        }
+    }
+    fn details(&self) -> &Details {
+        match self {
+            ApiError::Request(d)
+            | ApiError::Transport(d)
+            | ApiError::UpstreamStatus(d)
+            | ApiError::Payload(d)
+            | ApiError::Parse(d)
+            | ApiError::Internal(d) => d,
+        }
+    }
+    fn details_mut(&mut self) -> &mut Details {
+        match self {
+            ApiError::Request(d)
+            | ApiError::Transport(d)
+            | ApiError::UpstreamStatus(d)
+            | ApiError::Payload(d)
+            | ApiError::Parse(d)
+            | ApiError::Internal(d) => d,
+        }
+    }
+    pub fn kind(&self) -> Option<ErrorKind> {
+        self.details().kind
+    }
+    pub fn codes(&self) -> &[ErrorCode] {
+        &self.details().codes
+    }
+    pub fn messages(&self) -> &[String] {
+        &self.details().messages
+    }
+    pub fn parsers(&self) -> &[ParserDiagnostic] {
+        &self.details().parsers
+    }
+    pub fn with_parsers(mut self, parsers: Vec<ParserDiagnostic>) -> ApiError {
+        self.details_mut().parsers = parsers;
+        self
+    }
+    // the v2 body, `{"kind": ..., "errors": [{"code": ..., "message": ...}]}`
+    pub fn coded(&self) -> CodedErrors<'_> {
+        let details = self.details();
+        CodedErrors {
+            kind: details.kind,
+            errors: details
+                .codes
+                .iter()
+                .zip(details.messages.iter())
+                .map(|(code, message)| CodedError {
+                    code: *code,
+                    message,
+                })
+                .collect(),
+            parsers: &details.parsers,
+        }
+    }
+    // the kind's when there is one, otherwise the variant's. transport errors have always
+    // been answered with 400 and are kept so
+    pub fn status(&self) -> StatusCode {
+        let default = match self {
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::UpstreamStatus(_) => StatusCode::BAD_GATEWAY,
+            ApiError::Request(_)
+            | ApiError::Transport(_)
+            | ApiError::Payload(_)
+            | ApiError::Parse(_) => StatusCode::BAD_REQUEST,
+        };
+        self.kind().map_or(default, |k| k.status())
     }
 }
 
-impl From<SendRequestError> for Error<String> {
-    fn from(error: SendRequestError) -> Error<String> {
-        Error {
-            messages: vec![error.to_string()],
-        }
+impl From<XMLReaderError> for ApiError {
+    fn from(error: XMLReaderError) -> ApiError {
+        ApiError::new(
+            ApiError::Parse,
+            None,
+            ErrorCode::XmlParseError,
+            error.to_string(),
+        )
+    }
+}
+
+impl From<PayloadError> for ApiError {
+    fn from(error: PayloadError) -> ApiError {
+        let code = match error {
+            PayloadError::Overflow => ErrorCode::BodyTooLarge,
+            _ => ErrorCode::InvalidPayload,
+        };
+        ApiError::new(ApiError::Payload, None, code, error.to_string())
+    }
+}
+
+impl From<MultipartError> for ApiError {
+    fn from(error: MultipartError) -> ApiError {
+        ApiError::new(
+            ApiError::Payload,
+            None,
+            ErrorCode::InvalidMultipart,
+            error.to_string(),
+        )
+    }
+}
+
+impl From<base64::DecodeError> for ApiError {
+    fn from(error: base64::DecodeError) -> ApiError {
+        ApiError::new(
+            ApiError::Payload,
+            None,
+            ErrorCode::InvalidBase64,
+            format!("invalid base64: {}", error),
+        )
+    }
+}
+
+impl From<serde_json::Error> for ApiError {
+    fn from(error: serde_json::Error) -> ApiError {
+        ApiError::new(
+            ApiError::Payload,
+            None,
+            ErrorCode::InvalidJson,
+            error.to_string(),
+        )
+    }
+}
+
+impl From<BlockingError> for ApiError {
+    fn from(error: BlockingError) -> ApiError {
+        ApiError::new(
+            ApiError::Internal,
+            Some(ErrorKind::Internal),
+            ErrorCode::Internal,
+            error.to_string(),
+        )
+    }
+}
+
+impl From<SendRequestError> for ApiError {
+    fn from(error: SendRequestError) -> ApiError {
+        let code = match error {
+            SendRequestError::Timeout => ErrorCode::UpstreamTimeout,
+            SendRequestError::Connect(_) => ErrorCode::UpstreamConnectFailed,
+            _ => ErrorCode::UpstreamRequestFailed,
+        };
+        ApiError::new(ApiError::Transport, None, code, error.to_string())
+    }
+}
+
+impl From<InvalidRssError> for ApiError {
+    fn from(error: InvalidRssError) -> ApiError {
+        ApiError::new(ApiError::Parse, None, error.code, error.message)
+    }
+}
+
+impl From<InvalidUrlError> for ApiError {
+    fn from(error: InvalidUrlError) -> ApiError {
+        ApiError::new(ApiError::Request, None, error.code, error.message)
+    }
+}
+
+impl From<InvalidParameterError> for ApiError {
+    fn from(error: InvalidParameterError) -> ApiError {
+        ApiError::new(
+            ApiError::Request,
+            None,
+            ErrorCode::InvalidParameter,
+            format!("invalid parameter {}: {}", error.name, error.message),
+        )
+    }
+}
+
+impl From<HostNotAllowedError> for ApiError {
+    fn from(error: HostNotAllowedError) -> ApiError {
+        ApiError::new(
+            ApiError::Request,
+            Some(ErrorKind::HostNotAllowed),
+            ErrorCode::HostNotAllowed,
+            format!("host is not allowed: {}", error.host),
+        )
     }
 }
 
-impl From<InvalidRssError> for Error<String> {
-    fn from(error: InvalidRssError) -> Error<String> {
-        Error {
-            messages: vec![error.message],
+impl From<UpstreamStatusError> for ApiError {
+    fn from(error: UpstreamStatusError) -> ApiError {
+        let (kind, code, message) = match error.status {
+            StatusCode::GONE => (
+                ErrorKind::FeedGone,
+                ErrorCode::FeedGone,
+                "feed permanently gone".to_string(),
+            ),
+            StatusCode::NOT_FOUND => (
+                ErrorKind::FeedNotFound,
+                ErrorCode::FeedNotFound,
+                "feed not found".to_string(),
+            ),
+            _ => (
+                ErrorKind::AccessDenied,
+                ErrorCode::AccessDenied,
+                format!(
+                    "access denied by feed host — the host may be blocking automated clients (User-Agent: {})",
+                    error.user_agent
+                ),
+            ),
+        };
+        ApiError::new(ApiError::UpstreamStatus, Some(kind), code, message)
+    }
+}
+
+impl From<InvalidRedirectError> for ApiError {
+    fn from(error: InvalidRedirectError) -> ApiError {
+        let (code, message) = match error.location {
+            Some(location) => (
+                ErrorCode::InvalidRedirectLocation,
+                format!("invalid redirect location: {}", sanitize(&location)),
+            ),
+            None => (
+                ErrorCode::RedirectWithoutLocation,
+                "redirect without location".to_string(),
+            ),
+        };
+        ApiError::new(
+            ApiError::UpstreamStatus,
+            Some(ErrorKind::InvalidRedirect),
+            code,
+            message,
+        )
+    }
+}
+
+impl From<LocationTooLongError> for ApiError {
+    fn from(error: LocationTooLongError) -> ApiError {
+        ApiError::new(
+            ApiError::UpstreamStatus,
+            Some(ErrorKind::InvalidRedirect),
+            ErrorCode::RedirectLocationTooLong,
+            format!(
+                "redirect location of {} bytes, longer than {}",
+                error.len, error.limit
+            ),
+        )
+    }
+}
+
+impl From<PayloadTooLargeError> for ApiError {
+    fn from(error: PayloadTooLargeError) -> ApiError {
+        ApiError::new(
+            ApiError::Payload,
+            Some(ErrorKind::PayloadTooLarge),
+            ErrorCode::BodyTooLarge,
+            format!("payload exceeds {} bytes", error.limit),
+        )
+    }
+}
+
+impl From<TooManyDocumentsError> for ApiError {
+    fn from(error: TooManyDocumentsError) -> ApiError {
+        ApiError::new(
+            ApiError::Payload,
+            None,
+            ErrorCode::TooManyDocuments,
+            format!("too many documents, at most {}", error.limit),
+        )
+    }
+}
+
+impl From<IconNotFoundError> for ApiError {
+    fn from(error: IconNotFoundError) -> ApiError {
+        ApiError::new(
+            ApiError::UpstreamStatus,
+            Some(ErrorKind::IconNotFound),
+            ErrorCode::IconNotFound,
+            format!("no icon found for {}", error.host),
+        )
+    }
+}
+
+impl From<UnsupportedMediaTypeError> for ApiError {
+    fn from(error: UnsupportedMediaTypeError) -> ApiError {
+        ApiError::new(
+            ApiError::Payload,
+            Some(ErrorKind::UnsupportedMediaType),
+            ErrorCode::UnsupportedMediaType,
+            format!(
+                "unsupported content type: {}",
+                sanitize(&error.content_type)
+            ),
+        )
+    }
+}
+
+impl From<UnsupportedFormatError> for ApiError {
+    fn from(error: UnsupportedFormatError) -> ApiError {
+        ApiError::new(
+            ApiError::Parse,
+            Some(ErrorKind::UnsupportedFormat),
+            ErrorCode::UnsupportedFormat,
+            error.message,
+        )
+    }
+}
+
+impl From<SnapshotExpiredError> for ApiError {
+    fn from(_: SnapshotExpiredError) -> ApiError {
+        ApiError::new(
+            ApiError::Request,
+            Some(ErrorKind::SnapshotExpired),
+            ErrorCode::SnapshotExpired,
+            "snapshot expired, restart pagination".to_string(),
+        )
+    }
+}
+
+impl From<DeadlineExceededError> for ApiError {
+    fn from(error: DeadlineExceededError) -> ApiError {
+        ApiError::new(
+            ApiError::Transport,
+            Some(ErrorKind::DeadlineExceeded),
+            ErrorCode::DeadlineExceeded,
+            format!(
+                "deadline of {}ms exceeded while {}",
+                error.limit.as_millis(),
+                error.phase
+            ),
+        )
+    }
+}
+
+// answered as an upstream timeout is
+impl From<UpstreamStalledError> for ApiError {
+    fn from(error: UpstreamStalledError) -> ApiError {
+        ApiError::new(
+            ApiError::Transport,
+            None,
+            ErrorCode::UpstreamStalled,
+            format!(
+                "upstream stalled after {} bytes, nothing received for {}ms",
+                error.bytes,
+                error.idle.as_millis()
+            ),
+        )
+    }
+}
+
+impl From<UnauthorizedError> for ApiError {
+    fn from(error: UnauthorizedError) -> ApiError {
+        let (code, message) = if error.missing {
+            (ErrorCode::ApiKeyRequired, "an API key is required")
+        } else {
+            (ErrorCode::InvalidApiKey, "invalid API key")
+        };
+        ApiError::new(
+            ApiError::Request,
+            Some(ErrorKind::Unauthorized),
+            code,
+            message.to_string(),
+        )
+    }
+}
+
+impl From<QuotaExceededError> for ApiError {
+    fn from(error: QuotaExceededError) -> ApiError {
+        ApiError::new(
+            ApiError::Request,
+            Some(ErrorKind::QuotaExceeded),
+            ErrorCode::QuotaExceeded,
+            format!(
+                "daily quota of {} bytes exceeded, {} bytes used, resets at {}",
+                error.limit,
+                error.used,
+                error.resets_at.to_rfc3339_opts(SecondsFormat::Secs, true)
+            ),
+        )
+    }
+}
+
+// the redirects are kept a server error, as they were before it had a message
+impl From<RedirectLimitError> for ApiError {
+    fn from(error: RedirectLimitError) -> ApiError {
+        ApiError::new(
+            ApiError::UpstreamStatus,
+            Some(ErrorKind::Internal),
+            ErrorCode::RedirectLimitExceeded,
+            format!("more than {} redirects", error.limit),
+        )
+    }
+}
+
+// the hop is named, the url is the upstream's and sanitized like any other
+impl From<RedirectBudgetError> for ApiError {
+    fn from(error: RedirectBudgetError) -> ApiError {
+        ApiError::new(
+            ApiError::Transport,
+            Some(ErrorKind::DeadlineExceeded),
+            ErrorCode::RedirectBudgetExceeded,
+            format!(
+                "redirects took more than {}ms, given up at hop {}: {}",
+                error.budget.as_millis(),
+                error.hop,
+                sanitize(&error.url)
+            ),
+        )
+    }
+}
+
+impl From<RedirectBodyTooLargeError> for ApiError {
+    fn from(error: RedirectBodyTooLargeError) -> ApiError {
+        ApiError::new(
+            ApiError::UpstreamStatus,
+            Some(ErrorKind::InvalidRedirect),
+            ErrorCode::RedirectBodyTooLarge,
+            format!(
+                "redirect body over {} bytes at hop {}: {}",
+                error.limit,
+                error.hop,
+                sanitize(&error.url)
+            ),
+        )
+    }
+}
+
+impl From<RobotsDisallowedError> for ApiError {
+    fn from(error: RobotsDisallowedError) -> ApiError {
+        ApiError::new(
+            ApiError::Request,
+            Some(ErrorKind::DisallowedByRobots),
+            ErrorCode::DisallowedByRobots,
+            format!(
+                "disallowed by {}: {}",
+                sanitize(&error.robots_url),
+                sanitize(&error.rule)
+            ),
+        )
+    }
+}
+
+impl From<SelftestDisabledError> for ApiError {
+    fn from(_: SelftestDisabledError) -> ApiError {
+        ApiError::new(
+            ApiError::Request,
+            Some(ErrorKind::SelftestDisabled),
+            ErrorCode::SelftestDisabled,
+            "selftest is disabled, RSSSS_SELFTEST_URL is not set".to_string(),
+        )
+    }
+}
+
+impl From<FeedTooExpensiveError> for ApiError {
+    fn from(error: FeedTooExpensiveError) -> ApiError {
+        ApiError::new(
+            ApiError::Parse,
+            Some(ErrorKind::FeedTooExpensive),
+            ErrorCode::FeedTooExpensive,
+            format!(
+                "feed too expensive to parse, it took {}ms, more than {}ms",
+                error.elapsed.as_millis(),
+                error.threshold.as_millis()
+            ),
+        )
+    }
+}
+
+impl From<FixtureNotFoundError> for ApiError {
+    fn from(error: FixtureNotFoundError) -> ApiError {
+        let available = match (error.available.is_empty(), error.more) {
+            (true, _) => "there are none".to_string(),
+            (false, 0) => format!("there are {}", error.available.join(", ")),
+            (false, more) => format!("there are {} and {} more", error.available.join(", "), more),
+        };
+        ApiError::new(
+            ApiError::Request,
+            Some(ErrorKind::FixtureNotFound),
+            ErrorCode::FixtureNotFound,
+            format!(
+                "no fixture for {}, expected a file named {} in RSSSS_FIXTURE_DIR, {}",
+                sanitize(&error.url),
+                error.name,
+                available
+            ),
+        )
+    }
+}
+
+// makes values taken from upstream safe to echo back
+pub fn sanitize(value: &str) -> String {
+    const LIMIT: usize = 200;
+    let mut sanitized = value
+        .chars()
+        .take(LIMIT)
+        .map(|c| if c.is_control() { '\u{FFFD}' } else { c })
+        .collect::<String>();
+    if value.chars().count() > LIMIT {
+        sanitized.push_str("...");
+    }
+    sanitized
+}
+
+// the messages of all, the first kind and the variant of the first error
+impl From<Vec<ApiError>> for ApiError {
+    fn from(errors: Vec<ApiError>) -> ApiError {
+        let variant = match errors.first() {
+            Some(ApiError::Transport(_)) => ApiError::Transport,
+            Some(ApiError::UpstreamStatus(_)) => ApiError::UpstreamStatus,
+            Some(ApiError::Payload(_)) => ApiError::Payload,
+            Some(ApiError::Parse(_)) => ApiError::Parse,
+            Some(ApiError::Internal(_)) => ApiError::Internal,
+            Some(ApiError::Request(_)) | None => ApiError::Request,
+        };
+        let kind = errors.iter().find_map(ApiError::kind);
+        let mut details = Details {
+            kind,
+            messages: Vec::new(),
+            codes: Vec::new(),
+            parsers: Vec::new(),
+        };
+        for mut error in errors {
+            let error = error.details_mut();
+            details.messages.append(&mut error.messages);
+            details.codes.append(&mut error.codes);
+            details.parsers.append(&mut error.parsers);
         }
+        variant(details)
     }
 }
 
-impl<T> From<Vec<Error<T>>> for Error<T> {
-    fn from(errors: Vec<Error<T>>) -> Error<T> {
-        let mut messages = Vec::new();
-        for error in errors {
-            for message in error.messages {
-                messages.push(message);
-            }
+impl Serialize for ApiError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Messages {
+            kind: self.kind(),
+            messages: self.messages(),
         }
-        Error { messages: messages }
+        .serialize(serializer)
     }
 }
 
-impl<T: std::fmt::Display> std::fmt::Display for Error<T> {
+impl std::fmt::Display for ApiError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let messages = self
-            .messages
-            .iter()
-            .map(|v| v.to_string())
-            .collect::<Vec<String>>()
-            .join(",");
-        write!(f, "{}", messages)
+        write!(f, "{}", self.messages().join(","))
     }
 }
 
-impl<T: std::fmt::Debug + std::fmt::Display> ResponseError for Error<T> {}
+// the v1 body, handlers that know the version answer v2 clients with `coded` themselves
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        self.status()
+    }
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status()).json(self)
+    }
+}
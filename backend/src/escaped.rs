@@ -0,0 +1,101 @@
+use scraper::Html;
+use std::collections::BTreeMap;
+
+// decodes of a description at most, the one every description gets included
+pub const MAX_PASSES: usize = 3;
+
+// elements that come in pairs, only these are taken for escaped markup. void ones like br and img
+// show up alone in code samples as often as in escaped markup
+const PAIRED_TAGS: [&str; 30] = [
+    "a",
+    "b",
+    "blockquote",
+    "div",
+    "em",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "i",
+    "li",
+    "ol",
+    "p",
+    "s",
+    "section",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "sup",
+    "table",
+    "td",
+    "th",
+    "tr",
+    "u",
+    "ul",
+];
+
+// some feeds escape their descriptions twice or more, `&amp;lt;p&amp;gt;`, so a decode leaves
+// `<p>` for a text. the markup is decoded again while it reads as escaped markup, and the number
+// of decodes is returned with it, 1 when it was escaped once as it should be
+pub fn unescape(markup: String) -> (String, usize) {
+    let mut markup = markup;
+    let mut passes = 1;
+    while passes < MAX_PASSES && escapes_markup(&markup) {
+        markup = Html::parse_document(&markup)
+            .root_element()
+            .text()
+            .collect();
+        passes += 1;
+    }
+    (markup, passes)
+}
+
+// escaped elements in balanced pairs, `&lt;div&gt;` alone is a post about HTML rather than
+// markup. a description with live elements is markup already, what it escapes is meant as text
+pub fn escapes_markup(markup: &str) -> bool {
+    let live = markup
+        .match_indices('<')
+        .any(|(i, _)| markup[i + 1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '/'));
+    if live {
+        return false;
+    }
+    let lower = markup.to_ascii_lowercase();
+    // opened and closed per element
+    let mut tags = BTreeMap::<&str, (usize, usize)>::new();
+    for (i, _) in lower.match_indices('&') {
+        // `&lt;`, or escaped again as `&amp;lt;` and so on
+        let rest = lower[i + 1..].trim_start_matches("amp;");
+        let rest = match rest.strip_prefix("lt;") {
+            Some(rest) => rest,
+            None => continue,
+        };
+        let (closing, rest) = match rest.strip_prefix('/') {
+            Some(rest) => (true, rest),
+            None => (false, rest),
+        };
+        let end = rest
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(rest.len());
+        let (name, after) = rest.split_at(end);
+        // `&lt;p&gt;`, `&lt;a href=...`, but not `&lt;pre` for p or `&lt;p2p`
+        if !after.starts_with(['&', ' ', '\t', '\n', '/']) {
+            continue;
+        }
+        let name = match PAIRED_TAGS.iter().find(|tag| **tag == name) {
+            Some(tag) => *tag,
+            None => continue,
+        };
+        let counts = tags.entry(name).or_default();
+        if closing {
+            counts.1 += 1;
+        } else {
+            counts.0 += 1;
+        }
+    }
+    !tags.is_empty() && tags.values().all(|(opened, closed)| opened == closed)
+}
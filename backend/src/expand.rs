@@ -0,0 +1,121 @@
+use crate::hosts::{is_private, validate_url, HostFilter};
+use crate::rss::{FieldLimits, Rss};
+use crate::upstream;
+use crate::watchdog;
+use awc::ClientBuilder;
+use futures::stream::{self, StreamExt};
+use log::{info, warn};
+use scraper::{ElementRef, Html, Selector};
+use std::collections::HashMap;
+use std::time::Duration;
+
+const STUB_DESCRIPTION_LENGTH: usize = 100;
+const CONCURRENCY: usize = 4;
+const PAGE_SIZE_LIMIT: usize = 524_288;
+const PAGE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// replaces stub descriptions with the main text of the linked pages, at most `limit` items.
+// the time taken to read the text out of the pages is returned, the fetches aren't counted
+pub async fn expand(
+    items: &mut [Rss],
+    host_filter: &HostFilter,
+    allow_private: bool,
+    limit: usize,
+    limits: &FieldLimits,
+    user_agent: &str,
+) -> Duration {
+    let targets = items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| {
+            !item.link().is_empty() && item.description().chars().count() < STUB_DESCRIPTION_LENGTH
+        })
+        .map(|(i, item)| (i, item.link().to_string()))
+        .take(limit)
+        .collect::<Vec<(usize, String)>>();
+
+    let pages = stream::iter(targets)
+        .map(|(i, link)| async move {
+            (
+                i,
+                fetch_page(&link, host_filter, allow_private, user_agent).await,
+            )
+        })
+        .buffer_unordered(CONCURRENCY)
+        .collect::<Vec<(usize, Option<String>)>>()
+        .await;
+
+    watchdog::timed(|| {
+        for (i, page) in pages {
+            if let Some(text) = page.as_deref().and_then(extract) {
+                items[i].set_description_within(text, limits);
+            }
+        }
+    })
+    .1
+}
+
+async fn fetch_page(
+    link: &str,
+    host_filter: &HostFilter,
+    allow_private: bool,
+    user_agent: &str,
+) -> Option<String> {
+    let url = validate_url(link, host_filter).ok()?;
+    if !allow_private && is_private(&url) {
+        warn!("skip expanding private address: {}", url);
+        return None;
+    }
+    info!("expand {}", url);
+    let client = ClientBuilder::new()
+        .connector(upstream::connector(!allow_private))
+        .add_default_header(("User-Agent", user_agent))
+        .disable_redirects()
+        .timeout(PAGE_TIMEOUT)
+        .finish();
+    let mut res = client.get(url.as_str()).send().await.ok()?;
+    if !res.status().is_success() {
+        return None;
+    }
+    let body = res.body().limit(PAGE_SIZE_LIMIT).await.ok()?;
+    Some(String::from_utf8_lossy(&body).into_owned())
+}
+
+// picks the largest text block among <article>, <main> and the densest cluster of <p> siblings
+pub fn extract(html: &str) -> Option<String> {
+    let document = Html::parse_document(html);
+    let mut best: Option<(usize, String)> = None;
+    let mut consider = |len: usize, html: String| {
+        if len > 0 && best.as_ref().is_none_or(|(l, _)| len > *l) {
+            best = Some((len, html));
+        }
+    };
+
+    for selector in ["article", "main"] {
+        let selector = Selector::parse(selector).unwrap();
+        for element in document.select(&selector) {
+            consider(text_len(&element), element.inner_html());
+        }
+    }
+
+    let selector = Selector::parse("p").unwrap();
+    let mut clusters = HashMap::new();
+    for element in document.select(&selector) {
+        if let Some(parent) = element.parent() {
+            let (len, html) = clusters
+                .entry(parent.id())
+                .or_insert_with(|| (0, String::new()));
+            *len += text_len(&element);
+            html.push_str(&element.html());
+        }
+    }
+    for (_, (len, html)) in clusters {
+        consider(len, html);
+    }
+
+    best.map(|(_, html)| html)
+}
+
+fn text_len(element: &ElementRef) -> usize {
+    element.text().map(|t| t.trim().chars().count()).sum()
+}
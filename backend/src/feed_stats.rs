@@ -0,0 +1,68 @@
+use crate::dates::parse_date;
+use crate::rss::Rss;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_derive::Serialize;
+use std::time::Duration;
+
+// the dated items the interval is taken of at least, two items make a single interval
+pub const MIN_DATED_ITEMS: usize = 3;
+
+// how often a feed posts and over what time, by the pub_date of its items
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChannelStats {
+    // every item, the undated ones too
+    pub item_count: usize,
+    // RFC 3339 in UTC, of the dated items
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub earliest_pub_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_pub_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub median_interval_hours: Option<f64>,
+}
+
+impl ChannelStats {
+    // what a schedule could poll the feed at, None until it has dated enough items
+    pub fn median_interval(&self) -> Option<Duration> {
+        self.median_interval_hours
+            .map(|hours| Duration::from_secs_f64(hours * 3_600.0))
+    }
+}
+
+// items without a parsable pub_date or with a suspect one are counted, but not dated
+pub fn feed_stats(items: &[Rss]) -> ChannelStats {
+    let mut dates = items
+        .iter()
+        .filter(|item| !item.date_suspect())
+        .filter_map(|item| item.pub_date().and_then(parse_date))
+        .map(|date| date.with_timezone(&Utc))
+        .collect::<Vec<DateTime<Utc>>>();
+    dates.sort();
+    let shown = |date: &DateTime<Utc>| date.to_rfc3339_opts(SecondsFormat::Secs, true);
+    ChannelStats {
+        item_count: items.len(),
+        earliest_pub_date: dates.first().map(shown),
+        latest_pub_date: dates.last().map(shown),
+        median_interval_hours: median_interval_hours(&dates),
+    }
+}
+
+// of the gaps between consecutive dates, sorted, the mean of the middle two for an even count.
+// rounded to a tenth of an hour
+fn median_interval_hours(dates: &[DateTime<Utc>]) -> Option<f64> {
+    if dates.len() < MIN_DATED_ITEMS {
+        return None;
+    }
+    let mut gaps = dates
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).num_seconds())
+        .collect::<Vec<i64>>();
+    gaps.sort_unstable();
+    let middle = gaps.len() / 2;
+    let seconds = if gaps.len() % 2 == 0 {
+        (gaps[middle - 1] + gaps[middle]) as f64 / 2.0
+    } else {
+        gaps[middle] as f64
+    };
+    Some((seconds / 360.0).round() / 10.0)
+}
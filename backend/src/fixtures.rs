@@ -0,0 +1,147 @@
+use crate::config::Config;
+use crate::error::{ApiError, FixtureNotFoundError, InvalidParameterError};
+use actix_web::web::{self, Bytes};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use url::Url;
+
+// names longer than this are cut, most filesystems stop at 255 bytes
+const NAME_LIMIT: usize = 200;
+// the fixtures a not found message lists at most
+const LISTED: usize = 20;
+
+// with RSSSS_FIXTURE_DIR nothing is fetched, a feed url is read from the file its name maps to.
+// the name is the host, port, path and query with everything but ascii letters, digits, `-` and
+// single dots replaced by `_`, the scheme is left out. it never holds a separator or starts with
+// a dot, so a url can't name a file outside the directory however it's encoded
+pub fn fixture_name(url: &Url) -> String {
+    let mut raw = url.host_str().unwrap_or_default().to_string();
+    if let Some(port) = url.port() {
+        raw.push_str(&format!(":{}", port));
+    }
+    raw.push_str(url.path());
+    if let Some(query) = url.query() {
+        raw.push('?');
+        raw.push_str(query);
+    }
+    let mut name = String::with_capacity(raw.len());
+    for c in raw.chars() {
+        let c = match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' => c,
+            // `..` in any encoding ends up a single dot
+            '.' if !name.ends_with('.') => c,
+            '.' => continue,
+            _ => '_',
+        };
+        name.push(c);
+    }
+    let name = name.trim_start_matches('.').trim_end_matches('_');
+    let name = &name[..name.len().min(NAME_LIMIT)];
+    match name {
+        "" => "_".to_string(),
+        name => name.to_string(),
+    }
+}
+
+// None when the name would be anything but a file right under the directory
+pub fn fixture_path(dir: &Path, url: &Url) -> Option<PathBuf> {
+    let name = fixture_name(url);
+    let mut components = Path::new(&name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) => Some(dir.join(name)),
+        _ => None,
+    }
+}
+
+// the file names of the directory, sorted, for a not found message
+pub fn available(dir: &Path) -> Vec<String> {
+    let mut names = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| !name.starts_with('.') && !name.ends_with(".tmp"))
+        .collect::<Vec<String>>();
+    names.sort();
+    names
+}
+
+// the body recorded for the url, or a 404 listing what there is
+// the body with the time the file was last written, which is when it was recorded
+pub async fn replay(dir: &Path, url: &Url) -> Result<(Bytes, Option<DateTime<Utc>>), ApiError> {
+    let dir = dir.to_path_buf();
+    let url = url.clone();
+    web::block(move || {
+        let path = fixture_path(&dir, &url);
+        match path.as_ref().map(fs::read) {
+            Some(Ok(body)) => {
+                let modified = path
+                    .and_then(|path| fs::metadata(path).ok())
+                    .and_then(|metadata| metadata.modified().ok())
+                    .map(DateTime::<Utc>::from);
+                Ok((Bytes::from(body), modified))
+            }
+            Some(Err(e)) if e.kind() != io::ErrorKind::NotFound => {
+                warn!("fixture {}: {}", dir.display(), e);
+                Err(not_found(&dir, &url))
+            }
+            _ => Err(not_found(&dir, &url)),
+        }
+    })
+    .await?
+}
+
+fn not_found(dir: &Path, url: &Url) -> ApiError {
+    let available = available(dir);
+    FixtureNotFoundError {
+        url: url.to_string(),
+        name: fixture_name(url),
+        more: available.len().saturating_sub(LISTED),
+        available: available.into_iter().take(LISTED).collect(),
+    }
+    .into()
+}
+
+// with `record=true`, the body as fetched under the name `replay` looks for. failures are only
+// logged, the feed is served all the same
+pub async fn record(dir: &Path, url: &Url, body: Bytes) {
+    let path = match fixture_path(dir, url) {
+        Some(path) => path,
+        None => return,
+    };
+    let dir = dir.to_path_buf();
+    let result = web::block(move || {
+        fs::create_dir_all(&dir)?;
+        // renamed over the old one, a replay running meanwhile never reads half of it
+        let temp = path.with_extension(format!("{}.tmp", std::process::id()));
+        let result = fs::write(&temp, &body).and_then(|_| fs::rename(&temp, &path));
+        if result.is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+        result.map(|_| path)
+    })
+    .await;
+    match result {
+        Ok(Ok(path)) => info!("recorded {} to {}", url, path.display()),
+        Ok(Err(e)) => warn!("recording {}: {}", url, e),
+        Err(e) => warn!("recording {}: {}", url, e),
+    }
+}
+
+// `record=true` needs RSSSS_RECORD_DIR, and means nothing when fixtures are served
+pub fn check_record(record: bool, config: &Config) -> Result<(), ApiError> {
+    let message = match (record, &config.record_dir, &config.fixture_dir) {
+        (false, _, _) | (true, Some(_), None) => return Ok(()),
+        (true, None, _) => "needs RSSSS_RECORD_DIR to be set",
+        (true, _, Some(_)) => "can't be true with RSSSS_FIXTURE_DIR, nothing is fetched",
+    };
+    Err(InvalidParameterError {
+        name: "record".to_string(),
+        message: message.to_string(),
+    }
+    .into())
+}
@@ -0,0 +1,59 @@
+use crate::dates::{parse_date, TzOffset};
+use crate::rss::Rss;
+use serde_derive::Serialize;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    Day,
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<GroupBy, String> {
+        match s {
+            "day" => Ok(GroupBy::Day),
+            _ => Err(format!("unsupported grouping: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DayGroup {
+    pub date: String,
+    pub items: Vec<Rss>,
+}
+
+// buckets items by their local date in the given offset, newest day first,
+// items without a parsable date or with a suspect one go into a trailing "unknown" bucket
+pub fn group_by_day(items: Vec<Rss>, tz: TzOffset) -> Vec<DayGroup> {
+    let mut days = BTreeMap::new();
+    let mut unknown = Vec::new();
+    for item in items {
+        let date = item.pub_date().filter(|_| !item.date_suspect());
+        match date.and_then(parse_date) {
+            Some(date) => days
+                .entry(date.with_timezone(&tz.0).date_naive())
+                .or_insert_with(Vec::new)
+                .push(item),
+            None => unknown.push(item),
+        }
+    }
+    let mut groups = days
+        .into_iter()
+        .rev()
+        .map(|(date, items)| DayGroup {
+            date: date.format("%Y-%m-%d").to_string(),
+            items,
+        })
+        .collect::<Vec<DayGroup>>();
+    if !unknown.is_empty() {
+        groups.push(DayGroup {
+            date: "unknown".to_string(),
+            items: unknown,
+        });
+    }
+    groups
+}
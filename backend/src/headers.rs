@@ -0,0 +1,64 @@
+use crate::error::{ApiError, LocationTooLongError};
+use actix_web::http::header::{self, HeaderMap, HeaderName};
+use log::warn;
+
+// awc has no setting for the size of a response head. actix-http refuses HTTP/1.1 heads of more
+// than 96 headers or 128 KiB, h2 takes what the server sends. these cap what rssss reads of them
+
+// headers of a response before it's flagged, values are still read from the first of each name
+pub const MAX_HEADERS: usize = 64;
+// bytes of a value read, the rest is cut with an ellipsis
+pub const MAX_VALUE_LEN: usize = 2_048;
+// a longer Location isn't followed at all, a cut one would lead somewhere else
+pub const MAX_LOCATION_LEN: usize = 8_192;
+
+// a warning for a response with more headers than anyone needs
+pub fn count(headers: &HeaderMap, warnings: &mut Vec<String>) {
+    if headers.len() > MAX_HEADERS {
+        warn!("upstream sent {} headers", headers.len());
+        warnings.push(format!(
+            "upstream sent {} headers, more than {}",
+            headers.len(),
+            MAX_HEADERS
+        ));
+    }
+}
+
+// Content-Type, Cache-Control, ETag, Retry-After and the like, on a char boundary and with a
+// warning when cut. only the name is logged, the value is what could flood the log
+pub fn value(headers: &HeaderMap, name: &HeaderName, warnings: &mut Vec<String>) -> Option<String> {
+    let bytes = headers.get(name)?.as_bytes();
+    let mut value = String::from_utf8_lossy(bytes).into_owned();
+    if value.len() > MAX_VALUE_LEN {
+        let mut end = MAX_VALUE_LEN;
+        while !value.is_char_boundary(end) {
+            end -= 1;
+        }
+        value.truncate(end);
+        value.push_str("...");
+        warn!("upstream {} header of {} bytes cut", name, bytes.len());
+        warnings.push(format!(
+            "{} header cut to {} of {} bytes",
+            name,
+            end,
+            bytes.len()
+        ));
+    }
+    Some(value)
+}
+
+// the Location of a redirect as sent, refused rather than cut past MAX_LOCATION_LEN
+pub fn location(headers: &HeaderMap) -> Result<Option<String>, ApiError> {
+    let location = match headers.get(header::LOCATION) {
+        Some(location) => location.as_bytes(),
+        None => return Ok(None),
+    };
+    if location.len() > MAX_LOCATION_LEN {
+        return Err(LocationTooLongError {
+            len: location.len(),
+            limit: MAX_LOCATION_LEN,
+        }
+        .into());
+    }
+    Ok(Some(String::from_utf8_lossy(location).into_owned()))
+}
@@ -0,0 +1,135 @@
+use crate::error::{ApiError, ErrorCode, HostNotAllowedError, InvalidUrlError};
+use log::warn;
+use std::env;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use url::{Host, Url};
+
+#[derive(Debug, Clone, Default)]
+pub struct HostFilter {
+    allowlist: Vec<String>,
+    denylist: Vec<String>,
+}
+
+impl HostFilter {
+    pub fn new(allowlist: Vec<String>, denylist: Vec<String>) -> HostFilter {
+        HostFilter {
+            allowlist: allowlist.iter().filter_map(|p| normalize(p)).collect(),
+            denylist: denylist.iter().filter_map(|p| normalize(p)).collect(),
+        }
+    }
+
+    pub fn from_env() -> HostFilter {
+        HostFilter::new(
+            HostFilter::read_env("RSSSS_HOST_ALLOWLIST"),
+            HostFilter::read_env("RSSSS_HOST_DENYLIST"),
+        )
+    }
+
+    fn read_env(key: &str) -> Vec<String> {
+        env::var(key)
+            .map(|v| v.split(',').map(|p| p.to_string()).collect())
+            .unwrap_or_default()
+    }
+
+    // denylist wins over allowlist, an empty allowlist allows all hosts not denied
+    pub fn is_allowed(&self, host: &str) -> bool {
+        if self.denylist.iter().any(|p| matches(p, host)) {
+            return false;
+        }
+        self.allowlist.is_empty() || self.allowlist.iter().any(|p| matches(p, host))
+    }
+}
+
+// ".example.com" matches subdomains of example.com, "example.com" matches itself only
+pub fn matches(pattern: &str, host: &str) -> bool {
+    let (pattern, host) = match (normalize(pattern), normalize(host)) {
+        (Some(p), Some(h)) => (p, h),
+        _ => return false,
+    };
+    if pattern.starts_with('.') {
+        host.ends_with(&pattern)
+    } else {
+        host == pattern
+    }
+}
+
+fn normalize(pattern: &str) -> Option<String> {
+    let pattern = pattern.trim().trim_end_matches('.');
+    let (dot, domain) = match pattern.strip_prefix('.') {
+        Some(domain) => (".", domain),
+        None => ("", pattern),
+    };
+    if domain.is_empty() {
+        return None;
+    }
+    match Host::parse(domain) {
+        Ok(Host::Domain(d)) => Some(format!("{}{}", dot, d)),
+        Ok(host) if dot.is_empty() => Some(host.to_string()),
+        _ => None,
+    }
+}
+
+pub fn validate_url(url: &str, host_filter: &HostFilter) -> Result<Url, ApiError> {
+    let url = Url::parse(url).map_err(|e| InvalidUrlError {
+        code: ErrorCode::InvalidUrl,
+        message: format!("invalid url: {}", e),
+    })?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(InvalidUrlError {
+            code: ErrorCode::InvalidUrlScheme,
+            message: format!("unsupported url scheme: {}", url.scheme()),
+        }
+        .into());
+    }
+    let host = url.host_str().unwrap_or_default();
+    if !host_filter.is_allowed(host) {
+        warn!("host is not allowed: {}", host);
+        return Err(HostNotAllowedError {
+            host: host.to_string(),
+        }
+        .into());
+    }
+    Ok(url)
+}
+
+// guards secondary fetches (item pages, icons) from reaching the internal network
+pub fn is_private(url: &Url) -> bool {
+    match url.host() {
+        Some(Host::Domain(d)) => d == "localhost" || d.ends_with(".localhost"),
+        Some(Host::Ipv4(ip)) => is_private_ip(&IpAddr::V4(ip)),
+        Some(Host::Ipv6(ip)) => is_private_ip(&IpAddr::V6(ip)),
+        None => true,
+    }
+}
+
+// a name that resolves to any private address is private, whichever address gets dialed
+pub fn private_address(addrs: &[SocketAddr]) -> Option<IpAddr> {
+    addrs.iter().map(SocketAddr::ip).find(is_private_ip)
+}
+
+pub fn is_private_ip(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_private_ipv4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_private_ipv4(&ip),
+            None => is_private_ipv6(ip),
+        },
+    }
+}
+
+fn is_private_ipv4(ip: &Ipv4Addr) -> bool {
+    ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.octets()[0] == 0
+        || (ip.octets()[0] == 100 && (ip.octets()[1] & 0xc0) == 64)
+}
+
+fn is_private_ipv6(ip: &Ipv6Addr) -> bool {
+    ip.is_loopback()
+        || ip.is_unspecified()
+        || (ip.segments()[0] & 0xfe00) == 0xfc00
+        || (ip.segments()[0] & 0xffc0) == 0xfe80
+}
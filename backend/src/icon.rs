@@ -0,0 +1,148 @@
+use crate::headers;
+use crate::hosts::{is_private, validate_url, HostFilter};
+use crate::upstream;
+use actix_web::web::Bytes;
+use awc::ClientBuilder;
+use log::{info, warn};
+use scraper::{Html, Selector};
+use std::time::Duration;
+use url::Url;
+
+pub const ICON_SIZE_LIMIT: usize = 65_536;
+const PAGE_SIZE_LIMIT: usize = 524_288;
+const TIMEOUT: Duration = Duration::from_secs(10);
+const REDIRECT_LIMIT: u8 = 3;
+
+#[derive(Debug, Clone)]
+pub struct Icon {
+    pub content_type: &'static str,
+    pub body: Bytes,
+}
+
+// the icons declared in the site's head in document order, then /favicon.ico
+pub async fn fetch_icon(
+    site: &Url,
+    host_filter: &HostFilter,
+    allow_private: bool,
+    user_agent: &str,
+) -> Option<Icon> {
+    let mut candidates = match fetch(
+        site,
+        host_filter,
+        allow_private,
+        user_agent,
+        PAGE_SIZE_LIMIT,
+    )
+    .await
+    {
+        Some(html) => discover(&String::from_utf8_lossy(&html), site),
+        None => Vec::new(),
+    };
+    if let Ok(favicon) = site.join("/favicon.ico") {
+        candidates.push(favicon);
+    }
+    for candidate in candidates {
+        let body = match fetch(
+            &candidate,
+            host_filter,
+            allow_private,
+            user_agent,
+            ICON_SIZE_LIMIT,
+        )
+        .await
+        {
+            Some(body) => body,
+            None => continue,
+        };
+        match content_type(&body) {
+            Some(content_type) => return Some(Icon { content_type, body }),
+            None => warn!("not an image: {}", candidate),
+        }
+    }
+    None
+}
+
+// <link rel="icon"> and <link rel="shortcut icon">, resolved against the site
+pub fn discover(html: &str, site: &Url) -> Vec<Url> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("head link[rel][href]").unwrap();
+    document
+        .select(&selector)
+        .filter(|link| {
+            link.value()
+                .attr("rel")
+                .unwrap_or_default()
+                .split_ascii_whitespace()
+                .any(|rel| rel.eq_ignore_ascii_case("icon"))
+        })
+        .filter_map(|link| site.join(link.value().attr("href")?.trim()).ok())
+        .collect()
+}
+
+// follows redirects itself so that every hop is checked, bodies over the limit are dropped
+async fn fetch(
+    url: &Url,
+    host_filter: &HostFilter,
+    allow_private: bool,
+    user_agent: &str,
+    limit: usize,
+) -> Option<Bytes> {
+    let client = ClientBuilder::new()
+        .connector(upstream::connector(!allow_private))
+        .add_default_header(("User-Agent", user_agent))
+        .disable_redirects()
+        .timeout(TIMEOUT)
+        .finish();
+    let mut url = url.clone();
+    for _ in 0..=REDIRECT_LIMIT {
+        let checked = validate_url(url.as_str(), host_filter).ok()?;
+        if !allow_private && is_private(&checked) {
+            warn!("skip fetching icon from private address: {}", checked);
+            return None;
+        }
+        info!("icon {}", checked);
+        let mut res = client.get(checked.as_str()).send().await.ok()?;
+        if res.status().is_redirection() {
+            let location = headers::location(res.headers()).ok()??;
+            url = checked.join(location.trim()).ok()?;
+            continue;
+        }
+        if !res.status().is_success() {
+            return None;
+        }
+        return match res.body().limit(limit).await {
+            Ok(body) => Some(body),
+            Err(e) => {
+                warn!("{}: {}", checked, e);
+                None
+            }
+        };
+    }
+    None
+}
+
+// trusts the bytes rather than the upstream header, sites often serve icons as
+// application/octet-stream or an HTML error page as /favicon.ico
+pub fn content_type(body: &[u8]) -> Option<&'static str> {
+    if body.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some("image/png");
+    }
+    if body.starts_with(&[0, 0, 1, 0]) {
+        return Some("image/x-icon");
+    }
+    if body.starts_with(b"GIF87a") || body.starts_with(b"GIF89a") {
+        return Some("image/gif");
+    }
+    if body.starts_with(&[0xff, 0xd8, 0xff]) {
+        return Some("image/jpeg");
+    }
+    if body.len() >= 12 && body.starts_with(b"RIFF") && &body[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+    let text = String::from_utf8_lossy(&body[..body.len().min(1_024)]);
+    let text = text.trim_start_matches('\u{FEFF}').trim_start();
+    if text.starts_with("<svg") || (text.starts_with("<?xml") && text.contains("<svg")) {
+        return Some("image/svg+xml");
+    }
+    None
+}
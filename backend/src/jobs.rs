@@ -0,0 +1,237 @@
+use crate::cache::{Caches, Entry};
+use crate::config::Config;
+use crate::icon::{self, Icon};
+use actix_web::web;
+use futures::channel::mpsc::{self, Receiver, Sender};
+use futures::lock::Mutex as AsyncMutex;
+use futures::StreamExt;
+use log::{info, warn};
+use serde_derive::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use url::Url;
+
+#[derive(Debug, Clone)]
+pub struct JobsConfig {
+    // RSSSS_JOB_WORKERS, the jobs run at once
+    pub workers: usize,
+    // RSSSS_JOB_QUEUE_SIZE, jobs waiting for a worker, the work of a request past it is done in
+    // the request again
+    pub queue_size: usize,
+    // RSSSS_JOB_TIMEOUT_SECS, a job taking longer is dropped
+    pub timeout: Duration,
+}
+
+// the work a response can go without, done after it and stored in the caches for the requests
+// that come later
+#[derive(Debug, Clone)]
+pub enum Job {
+    // the icon of a site into `Caches::icons`
+    Icon { site: Url, host: String },
+}
+
+impl Job {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Job::Icon { .. } => "icon",
+        }
+    }
+
+    // a job already waiting or running for the same key isn't queued again
+    fn key(&self) -> String {
+        match self {
+            Job::Icon { host, .. } => format!("icon:{}", host),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct QueueFullError;
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct JobOutcomes {
+    pub queued: u64,
+    // asked for while the same job was waiting or running
+    pub deduplicated: u64,
+    // the queue was full, the request did the work itself
+    pub rejected: u64,
+    pub succeeded: u64,
+    // done, but with nothing to store, like a site without an icon
+    pub failed: u64,
+    pub timed_out: u64,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct JobStats {
+    pub queue_depth: usize,
+    pub running: usize,
+    // by job kind
+    pub outcomes: BTreeMap<&'static str, JobOutcomes>,
+}
+
+#[derive(Default)]
+struct State {
+    depth: AtomicUsize,
+    running: AtomicUsize,
+    pending: Mutex<HashSet<String>>,
+    outcomes: Mutex<BTreeMap<&'static str, JobOutcomes>>,
+}
+
+impl State {
+    fn count(&self, kind: &'static str, f: impl FnOnce(&mut JobOutcomes)) {
+        f(self.outcomes.lock().unwrap().entry(kind).or_default());
+    }
+}
+
+pub struct Jobs {
+    sender: Mutex<Sender<Job>>,
+    queue_size: usize,
+    state: Arc<State>,
+}
+
+impl Jobs {
+    // the workers run on the runtime this is called from until `Jobs` is dropped
+    pub fn start(config: &Config, caches: web::Data<Caches>) -> Jobs {
+        let queue_size = config.jobs.queue_size.max(1);
+        let (sender, receiver) = mpsc::channel(queue_size);
+        let receiver = Arc::new(AsyncMutex::new(receiver));
+        let state = Arc::new(State::default());
+        for _ in 0..config.jobs.workers.max(1) {
+            actix_rt::spawn(work(
+                receiver.clone(),
+                state.clone(),
+                config.clone(),
+                caches.clone(),
+            ));
+        }
+        Jobs {
+            sender: Mutex::new(sender),
+            queue_size,
+            state,
+        }
+    }
+
+    // Ok also when the same job is waiting already, its result serves both
+    pub fn enqueue(&self, job: Job) -> Result<(), QueueFullError> {
+        let kind = job.kind();
+        let mut pending = self.state.pending.lock().unwrap();
+        if pending.contains(&job.key()) {
+            self.state.count(kind, |o| o.deduplicated += 1);
+            return Ok(());
+        }
+        // the channel makes room for a message per sender on top of its buffer, so it's counted here
+        if self.state.depth.load(Ordering::SeqCst) >= self.queue_size {
+            warn!("job queue full, {} job done in the request", kind);
+            self.state.count(kind, |o| o.rejected += 1);
+            return Err(QueueFullError);
+        }
+        let key = job.key();
+        if self.sender.lock().unwrap().try_send(job).is_err() {
+            self.state.count(kind, |o| o.rejected += 1);
+            return Err(QueueFullError);
+        }
+        self.state.depth.fetch_add(1, Ordering::SeqCst);
+        pending.insert(key);
+        self.state.count(kind, |o| o.queued += 1);
+        Ok(())
+    }
+
+    pub fn snapshot(&self) -> JobStats {
+        JobStats {
+            queue_depth: self.state.depth.load(Ordering::SeqCst),
+            running: self.state.running.load(Ordering::SeqCst),
+            outcomes: self.state.outcomes.lock().unwrap().clone(),
+        }
+    }
+
+    // with the stats, the queue and what's running are left as they are
+    pub fn reset(&self) {
+        self.state.outcomes.lock().unwrap().clear();
+    }
+}
+
+enum Outcome {
+    Succeeded,
+    Failed,
+    TimedOut,
+}
+
+async fn work(
+    receiver: Arc<AsyncMutex<Receiver<Job>>>,
+    state: Arc<State>,
+    config: Config,
+    caches: web::Data<Caches>,
+) {
+    loop {
+        // a single worker waits on the channel at a time, the others on the lock
+        let job = match receiver.lock().await.next().await {
+            Some(job) => job,
+            None => return,
+        };
+        state.depth.fetch_sub(1, Ordering::SeqCst);
+        state.running.fetch_add(1, Ordering::SeqCst);
+        let (kind, key) = (job.kind(), job.key());
+        let outcome =
+            match actix_rt::time::timeout(config.jobs.timeout, run(&job, &config, &caches)).await {
+                Ok(outcome) => outcome,
+                Err(_) => {
+                    warn!("{} job timed out after {:?}", kind, config.jobs.timeout);
+                    expire(&job, &config, &caches);
+                    Outcome::TimedOut
+                }
+            };
+        state.count(kind, |o| match outcome {
+            Outcome::Succeeded => o.succeeded += 1,
+            Outcome::Failed => o.failed += 1,
+            Outcome::TimedOut => o.timed_out += 1,
+        });
+        state.pending.lock().unwrap().remove(&key);
+        state.running.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+async fn run(job: &Job, config: &Config, caches: &Caches) -> Outcome {
+    match job {
+        Job::Icon { site, host } => match fetch_icon(site, host, config, caches).await.value {
+            Some(_) => Outcome::Succeeded,
+            None => {
+                info!("no icon for {}", host);
+                Outcome::Failed
+            }
+        },
+    }
+}
+
+// what a timed out job stores so the requests after it don't queue it again right away
+fn expire(job: &Job, config: &Config, caches: &Caches) {
+    match job {
+        Job::Icon { host, .. } => {
+            caches
+                .icons
+                .insert(host.to_string(), None, config.cache.min_ttl);
+        }
+    }
+}
+
+pub async fn fetch_icon(
+    site: &Url,
+    host: &str,
+    config: &Config,
+    caches: &Caches,
+) -> Entry<Option<Icon>> {
+    let icon = icon::fetch_icon(
+        site,
+        &config.host_filter,
+        config.allow_private_hosts,
+        crate::USER_AGENT,
+    )
+    .await;
+    // misses are retried sooner, the site may just have been down
+    let ttl = match icon {
+        Some(_) => config.cache.icon_ttl,
+        None => config.cache.min_ttl,
+    };
+    caches.icons.insert(host.to_string(), icon, ttl)
+}
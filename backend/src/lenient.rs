@@ -0,0 +1,288 @@
+use actix_web::web::Bytes;
+
+// HTML void elements seen unclosed in descriptions, not `link` or `source` which are RSS elements too
+const VOID_ELEMENTS: [&str; 10] = [
+    "br", "hr", "img", "input", "wbr", "area", "col", "embed", "param", "track",
+];
+
+// HTML entities XML doesn't know, the others are escaped as text
+const HTML_ENTITIES: [(&str, u32); 12] = [
+    ("nbsp", 160),
+    ("copy", 169),
+    ("reg", 174),
+    ("trade", 8482),
+    ("hellip", 8230),
+    ("mdash", 8212),
+    ("ndash", 8211),
+    ("lsquo", 8216),
+    ("rsquo", 8217),
+    ("ldquo", 8220),
+    ("rdquo", 8221),
+    ("middot", 183),
+];
+
+const XML_ENTITIES: [&str; 5] = ["amp", "lt", "gt", "quot", "apos"];
+
+// the mistakes of broken feeds that can be fixed without guessing,
+// CDATA sections and comments are left as they are
+pub fn recover(buf: &[u8]) -> Bytes {
+    let doc = String::from_utf8_lossy(buf);
+    let mut out = String::with_capacity(doc.len() + doc.len() / 16);
+    let mut rest = doc.as_ref();
+    while let Some(c) = rest.chars().next() {
+        if let Some(len) = verbatim(rest) {
+            out.push_str(&rest[..len]);
+            rest = &rest[len..];
+            continue;
+        }
+        match c {
+            '&' => {
+                let (replacement, len) = entity(rest);
+                out.push_str(&replacement);
+                rest = &rest[len..];
+            }
+            '<' => {
+                let len = tag(rest, &mut out);
+                rest = &rest[len..];
+            }
+            c if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => rest = &rest[c.len_utf8()..],
+            c => {
+                out.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    Bytes::from(out)
+}
+
+// the length of a CDATA section or comment at the start, up to the end of the document when unclosed
+fn verbatim(rest: &str) -> Option<usize> {
+    let end = if rest.starts_with("<![CDATA[") {
+        "]]>"
+    } else if rest.starts_with("<!--") {
+        "-->"
+    } else {
+        return None;
+    };
+    Some(rest.find(end).map_or(rest.len(), |i| i + end.len()))
+}
+
+fn entity(rest: &str) -> (String, usize) {
+    let name = rest[1..]
+        .split(';')
+        .next()
+        .filter(|name| rest[1..].len() > name.len() && name.len() <= 32);
+    let Some(name) = name else {
+        return ("&amp;".to_string(), 1);
+    };
+    let numeric = match name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+        Some(hex) => !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()),
+        None => name
+            .strip_prefix('#')
+            .is_some_and(|dec| !dec.is_empty() && dec.chars().all(|c| c.is_ascii_digit())),
+    };
+    if numeric || XML_ENTITIES.contains(&name) {
+        return (format!("&{};", name), name.len() + 2);
+    }
+    match HTML_ENTITIES.iter().find(|(n, _)| *n == name) {
+        Some((_, code)) => (format!("&#{};", code), name.len() + 2),
+        None => ("&amp;".to_string(), 1),
+    }
+}
+
+// copies the tag at the start, self-closing void elements and escaping a `<` that starts none
+fn tag(rest: &str, out: &mut String) -> usize {
+    let after = &rest[1..];
+    let starts_tag = after
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || matches!(c, '/' | '!' | '?' | '_'));
+    if !starts_tag {
+        out.push_str("&lt;");
+        return 1;
+    }
+    let len = tag_len(rest);
+    let tag = &rest[..len];
+    let closing = after.starts_with('/');
+    let name = tag[if closing { 2 } else { 1 }..]
+        .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if !VOID_ELEMENTS.contains(&name.as_str()) {
+        out.push_str(tag);
+    } else if !closing {
+        // self-closed and escaped as the HTML it is, an element would split the text around it.
+        // `</br>` closes nothing then
+        let open = tag.trim_end_matches('>').trim_end_matches('/').trim_end();
+        out.push_str(&open.replace('&', "&amp;").replacen('<', "&lt;", 1));
+        out.push_str(" /&gt;");
+    }
+    len
+}
+
+// up to the `>` outside quoted attribute values
+fn tag_len(rest: &str) -> usize {
+    let mut quote = None;
+    for (i, c) in rest.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '>') => return i + 1,
+            _ => (),
+        }
+    }
+    rest.len()
+}
+
+#[derive(Debug, Default)]
+pub struct ScannedItem {
+    pub title: String,
+    pub link: String,
+    pub description: String,
+    pub pub_date: Option<String>,
+}
+
+// the items of a document too broken to parse, found between their tags.
+// those without a title or a link too, the parse drops them saying so
+pub fn scan_items(buf: &[u8]) -> Vec<ScannedItem> {
+    let doc = String::from_utf8_lossy(buf);
+    let lower = doc.to_ascii_lowercase();
+    let mut items = Vec::new();
+    let mut from = 0;
+    while let Some((start, name)) = find_element(&lower, from, &["item", "entry"]) {
+        let close = format!("</{}", name);
+        let end = lower[start + 1..]
+            .find(&close)
+            .or_else(|| lower[start + 1..].find(&format!("<{}", name)))
+            .map_or(doc.len(), |i| start + 1 + i);
+        let (block, lower_block) = (&doc[start..end], &lower[start..end]);
+        let text = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| element_text(block, lower_block, name, false))
+        };
+        let link = text(&["link"])
+            .filter(|link| !link.is_empty())
+            .or_else(|| attribute(block, lower_block, "link", "href"));
+        let item = ScannedItem {
+            // markup in a title is stray, in a description it's HTML
+            title: element_text(block, lower_block, "title", true).unwrap_or_default(),
+            link: link.unwrap_or_default(),
+            description: text(&["description", "content:encoded", "summary", "content"])
+                .unwrap_or_default(),
+            pub_date: text(&["pubdate", "published", "updated", "dc:date"]),
+        };
+        items.push(item);
+        from = end;
+    }
+    items
+}
+
+// the start of the first of the elements at or after `from`, not one only beginning with their name
+fn find_element<'a>(lower: &str, from: usize, names: &[&'a str]) -> Option<(usize, &'a str)> {
+    names
+        .iter()
+        .filter_map(|name| {
+            let open = format!("<{}", name);
+            let mut at = from;
+            while let Some(i) = lower.get(at..)?.find(&open) {
+                let start = at + i;
+                let next = lower[start + open.len()..].chars().next();
+                if next.is_some_and(|c| c.is_whitespace() || c == '>' || c == '/') {
+                    return Some((start, *name));
+                }
+                at = start + open.len();
+            }
+            None
+        })
+        .min_by_key(|(start, _)| *start)
+}
+
+fn element_text(block: &str, lower: &str, name: &str, strip_tags: bool) -> Option<String> {
+    let (start, _) = find_element(lower, 0, &[name])?;
+    let open_end = start + tag_len(&block[start..]);
+    if block[..open_end].ends_with("/>") {
+        return Some(String::new());
+    }
+    let close = lower[open_end..]
+        .find(&format!("</{}", name))
+        .map_or(block.len(), |i| open_end + i);
+    Some(unwrap_text(&block[open_end..close], strip_tags))
+}
+
+fn attribute(block: &str, lower: &str, name: &str, attribute: &str) -> Option<String> {
+    let (start, _) = find_element(lower, 0, &[name])?;
+    let end = start + tag_len(&block[start..]);
+    let tag = &block[start..end];
+    let at = lower[start..end].find(&format!("{}=", attribute))? + attribute.len() + 1;
+    let quote = tag[at..]
+        .chars()
+        .next()
+        .filter(|c| matches!(c, '"' | '\''))?;
+    let value = tag[at + 1..].split(quote).next()?;
+    Some(decode(value.trim()))
+}
+
+// a CDATA section as is, anything else with its entities decoded
+fn unwrap_text(text: &str, strip_tags: bool) -> String {
+    let trimmed = text.trim();
+    if let Some(cdata) = trimmed.strip_prefix("<![CDATA[") {
+        return cdata.split("]]>").next().unwrap_or_default().to_string();
+    }
+    if !strip_tags {
+        return decode(trimmed);
+    }
+    let mut stripped = String::with_capacity(trimmed.len());
+    let mut rest = trimmed;
+    while let Some(i) = rest.find('<') {
+        stripped.push_str(&rest[..i]);
+        rest = &rest[i + tag_len(&rest[i..])..];
+    }
+    stripped.push_str(rest);
+    decode(stripped.trim())
+}
+
+fn decode(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let (decoded, len) = decode_entity(rest);
+        out.push_str(&decoded);
+        rest = &rest[len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn decode_entity(rest: &str) -> (String, usize) {
+    let Some(name) = rest[1..].split(';').next().filter(|n| n.len() <= 32) else {
+        return ("&".to_string(), 1);
+    };
+    if rest[1..].len() == name.len() {
+        return ("&".to_string(), 1);
+    }
+    let code = match name {
+        "amp" => Some('&' as u32),
+        "lt" => Some('<' as u32),
+        "gt" => Some('>' as u32),
+        "quot" => Some('"' as u32),
+        "apos" => Some('\'' as u32),
+        _ => match name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok(),
+            None => match name.strip_prefix('#') {
+                Some(dec) => dec.parse().ok(),
+                None => HTML_ENTITIES
+                    .iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, code)| *code),
+            },
+        },
+    };
+    match code.and_then(char::from_u32) {
+        Some(c) => (c.to_string(), name.len() + 2),
+        None => ("&".to_string(), 1),
+    }
+}
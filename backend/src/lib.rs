@@ -0,0 +1,1487 @@
+pub mod alias;
+pub mod api_docs;
+pub mod audio;
+pub mod auth;
+pub mod batch;
+pub mod bucket;
+pub mod cache;
+pub mod case;
+pub mod charset;
+pub mod check;
+pub mod clock;
+pub mod config;
+pub mod cookies;
+pub mod cors;
+pub mod dates;
+pub mod deadline;
+pub mod disconnect;
+#[cfg(feature = "cache-disk")]
+pub mod disk_cache;
+pub mod display;
+pub mod error;
+pub mod escaped;
+pub mod expand;
+pub mod feed_stats;
+pub mod fixtures;
+pub mod grouping;
+pub(crate) mod hash;
+pub mod headers;
+pub mod hosts;
+pub mod icon;
+pub mod jobs;
+pub mod language;
+pub mod lenient;
+pub mod markdown;
+pub mod ndjson;
+pub mod page;
+pub mod query;
+pub mod quota;
+pub mod redirects;
+pub mod response;
+pub mod robots;
+pub mod rss;
+pub mod safe_html;
+pub mod selftest;
+pub mod sorting;
+pub mod stall;
+pub mod stats;
+#[cfg(feature = "admin-ui")]
+pub mod status;
+pub mod stored;
+pub mod timeranges;
+pub mod trace;
+pub mod upstream;
+pub mod urlnorm;
+pub mod validate;
+pub mod watchdog;
+pub mod words;
+
+use actix_web::body::BoxBody;
+use actix_web::http::header::{self, HeaderName, HeaderValue};
+use actix_web::http::{Method, StatusCode, Version};
+use actix_web::{
+    web, Error as ActixWebError, HttpRequest, HttpResponse, HttpResponseBuilder, ResponseError,
+    Route,
+};
+use awc::SendClientRequest;
+use cache::{CachedFeed, Caches, Entry, Failure, SnapshotToken};
+use case::Case;
+use chrono::{SecondsFormat, Utc};
+use config::Config;
+use cookies::CookieJar;
+use deadline::{Phase, Progress};
+use error::{
+    ApiError, FeedTooExpensiveError, HostNotAllowedError, IconNotFoundError, QuotaExceededError,
+    SelftestDisabledError, SnapshotExpiredError, UpstreamStatusError,
+};
+use futures::channel::mpsc;
+use futures::{stream, StreamExt};
+use grouping::GroupBy;
+use hosts::{is_private, validate_url};
+use icon::Icon;
+use jobs::{Job, Jobs, QueueFullError};
+use log::info;
+use page::{Page, Window};
+use query::{CheckQuery, FeedQuery, ParseQuery, SelftestQuery};
+use quota::{Quota, Reservation};
+use redirects::Hops;
+use response::{ApiVersion, FeedEnvelope, FeedItems};
+use rss::{Format, ParseOptions, Rss};
+use serde_json::Value;
+use stats::Stats;
+use std::convert::Infallible;
+use std::net::IpAddr;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use stored::Stored;
+use trace::TraceContext;
+use tracing::instrument::Instrumented;
+use tracing::{info_span, Instrument, Span};
+use url::Url;
+use urlnorm::canonical_feed_key;
+use validate::Validated;
+use watchdog::Section;
+
+const USER_AGENT: &str = "rssss";
+
+impl From<ApiError> for HttpResponse {
+    fn from(e: ApiError) -> HttpResponse {
+        e.error_response()
+    }
+}
+
+async fn get_feed(
+    req: HttpRequest,
+    mut query: FeedQuery,
+    config: web::Data<Config>,
+    caches: web::Data<Caches>,
+    stats: web::Data<Stats>,
+    quota: web::Data<Quota>,
+) -> HttpResponse {
+    query.ndjson = ndjson::accepts(req.headers());
+    query.validators = query::validators(req.headers());
+    serve_feed(
+        &req,
+        &query,
+        ApiVersion::V1,
+        &config,
+        &caches,
+        &stats,
+        &quota,
+    )
+    .await
+}
+
+// the status and headers of the GET, without the body. a cached feed is answered without
+// an upstream request, a cold cache still fetches and parses it, which the GET after benefits from
+async fn head_feed(
+    req: HttpRequest,
+    mut query: FeedQuery,
+    config: web::Data<Config>,
+    caches: web::Data<Caches>,
+    stats: web::Data<Stats>,
+    quota: web::Data<Quota>,
+) -> HttpResponse {
+    query.ndjson = ndjson::accepts(req.headers());
+    query.validators = query::validators(req.headers());
+    query.head = true;
+    let res = serve_feed(
+        &req,
+        &query,
+        ApiVersion::V1,
+        &config,
+        &caches,
+        &stats,
+        &quota,
+    )
+    .await;
+    // errors are still built with their body
+    res.set_body(BoxBody::new(()))
+}
+
+// an invalid query is answered with the v2 error body too, so the extractor error is taken here
+async fn get_feed_v2(
+    req: HttpRequest,
+    query: Result<FeedQuery, ApiError>,
+    config: web::Data<Config>,
+    caches: web::Data<Caches>,
+    stats: web::Data<Stats>,
+    quota: web::Data<Quota>,
+) -> HttpResponse {
+    let mut query = match query {
+        Ok(query) => query,
+        Err(e) => return HttpResponse::build(e.status()).json(e.coded()),
+    };
+    query.validators = query::validators(req.headers());
+    serve_feed(
+        &req,
+        &query,
+        ApiVersion::V2,
+        &config,
+        &caches,
+        &stats,
+        &quota,
+    )
+    .await
+}
+
+// the bytes fetched count against the client's quota, whether or not the feed could be served
+async fn serve_feed(
+    req: &HttpRequest,
+    query: &FeedQuery,
+    version: ApiVersion,
+    config: &web::Data<Config>,
+    caches: &web::Data<Caches>,
+    stats: &web::Data<Stats>,
+    quota: &Quota,
+) -> HttpResponse {
+    let client = quota::client(req);
+    let reservation = match reserve(quota, config, &client, check::BODY_LIMIT) {
+        Ok(reservation) => reservation,
+        Err(e) => {
+            let mut builder = over_quota(&e);
+            if config.fixture_dir.is_some() {
+                builder.insert_header(("X-Rssss-Mode", "fixture"));
+            }
+            return error_json(&mut builder, query.case, version, e.into());
+        }
+    };
+    let trace = TraceContext::from_headers(req.headers());
+    let span = info_span!(
+        "feed",
+        trace_id = %trace.trace_id(),
+        span_id = %trace.span_id(),
+        parent_id = %trace.parent_id().unwrap_or_default(),
+    );
+    let progress = Progress::traced(trace);
+    let result = if query.ndjson && ndjson_streamable(query) {
+        let streaming = stream_response(
+            query,
+            version,
+            &progress,
+            config.clone(),
+            caches.clone(),
+            stats.clone(),
+        );
+        disconnect::or_disconnect(req, streaming)
+            .instrument(span)
+            .await
+    } else {
+        let retrieval = retrieve_response(
+            query,
+            version,
+            send_request,
+            &progress,
+            config,
+            caches,
+            stats,
+        );
+        disconnect::or_disconnect(req, retrieval)
+            .instrument(span)
+            .await
+    };
+    reservation.settle(progress.downloaded(), Utc::now());
+    let mut res = match result {
+        Some(Ok(v)) => v,
+        Some(Err(e)) => error_json(&mut HttpResponse::build(e.status()), query.case, version, e),
+        None => return client_closed(),
+    };
+    quota_remaining(&mut res, quota, config, &client);
+    // errors included, a 404 says which file was looked for
+    if config.fixture_dir.is_some() {
+        res.headers_mut().insert(
+            HeaderName::from_static("x-rssss-mode"),
+            HeaderValue::from_static("fixture"),
+        );
+    }
+    let (timeout, stats) = (config.clients.write, stats.clone());
+    res.map_body(|_, body| disconnect::watch(req, body, timeout, stats))
+        .map_into_boxed_body()
+}
+
+// holds back what `most` bytes fetched would take of the client's quota
+fn reserve<'a>(
+    quota: &'a Quota,
+    config: &Config,
+    client: &str,
+    most: usize,
+) -> Result<Reservation<'a>, QuotaExceededError> {
+    let limit = config.daily_quota.unwrap_or(u64::MAX);
+    quota
+        .reserve(client, limit, most as u64, Utc::now())
+        .map_err(|usage| QuotaExceededError {
+            limit,
+            used: usage.used,
+            // nothing fetched yet, what's left is held by the client's requests still fetching
+            resets_at: usage
+                .resets_at
+                .unwrap_or(Utc::now() + chrono::Duration::seconds(1)),
+        })
+}
+
+// the 429 of a client without any quota left
+fn over_quota(e: &QuotaExceededError) -> HttpResponseBuilder {
+    let retry_after = (e.resets_at - Utc::now()).num_seconds().max(1);
+    let mut builder = HttpResponse::TooManyRequests();
+    builder
+        .insert_header((header::RETRY_AFTER, retry_after))
+        .insert_header(("X-Quota-Remaining", 0));
+    builder
+}
+
+fn quota_remaining(res: &mut HttpResponse, quota: &Quota, config: &Config, client: &str) {
+    if let Some(limit) = config.daily_quota {
+        let usage = quota.usage(client, limit, Utc::now());
+        res.headers_mut().insert(
+            HeaderName::from_static("x-quota-remaining"),
+            HeaderValue::from(usage.remaining),
+        );
+    }
+}
+
+fn error_json(
+    builder: &mut HttpResponseBuilder,
+    case: Case,
+    version: ApiVersion,
+    e: ApiError,
+) -> HttpResponse {
+    match version {
+        ApiVersion::V1 => case::json(builder, case, e),
+        ApiVersion::V2 => case::json(builder, case, e.coded()),
+    }
+}
+
+// the options of `/feed` as a body, keeps long urls out of query strings and access logs
+async fn post_feed(
+    req: HttpRequest,
+    payload: web::Payload,
+    config: web::Data<Config>,
+    caches: web::Data<Caches>,
+    stats: web::Data<Stats>,
+    quota: web::Data<Quota>,
+) -> Result<HttpResponse, ActixWebError> {
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok());
+    let query = match batch::read_body(payload, query::MAX_BODY_SIZE).await {
+        Ok(body) => FeedQuery::from_body(content_type, &body),
+        Err(e) => Err(e),
+    };
+    match query {
+        Ok(mut query) => {
+            query.ndjson = ndjson::accepts(req.headers());
+            query.validators = query::validators(req.headers());
+            Ok(serve_feed(
+                &req,
+                &query,
+                ApiVersion::V1,
+                &config,
+                &caches,
+                &stats,
+                &quota,
+            )
+            .await)
+        }
+        Err(e) => Ok(e.into()),
+    }
+}
+
+// nginx's status for a request its client closed, only ever seen in the access log
+fn client_closed() -> HttpResponse {
+    HttpResponse::build(StatusCode::from_u16(499).unwrap()).finish()
+}
+
+async fn parse_feed(
+    query: ParseQuery,
+    payload: web::Payload,
+    config: web::Data<Config>,
+) -> Result<HttpResponse, ActixWebError> {
+    let options = ParseOptions {
+        parser: query.parser,
+        limits: config.field_limits,
+        ..ParseOptions::default()
+    };
+    let result = match batch::read_body(payload, batch::MAX_DOCUMENT_SIZE).await {
+        Ok(buf) => {
+            rss::parse_rss_async(buf, None, config.parse_blocking_threshold, options, None).await
+        }
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(mut feed) => {
+            feed.items.iter_mut().for_each(Rss::drop_category_details);
+            Ok(HttpResponse::Ok().json(feed))
+        }
+        Err(e) => Ok(e.into()),
+    }
+}
+
+async fn parse_feed_batch(
+    req: HttpRequest,
+    payload: web::Payload,
+    config: web::Data<Config>,
+) -> Result<HttpResponse, ActixWebError> {
+    match batch::read_documents(req.headers(), payload).await {
+        Ok(documents) => {
+            let results = batch::parse_documents(documents, config.field_limits).await;
+            Ok(HttpResponse::Ok().json(results))
+        }
+        Err(e) => Ok(e.into()),
+    }
+}
+
+async fn get_icon(
+    req: HttpRequest,
+    query: FeedQuery,
+    config: web::Data<Config>,
+    caches: web::Data<Caches>,
+    stats: web::Data<Stats>,
+    jobs: web::Data<Jobs>,
+) -> HttpResponse {
+    let retrieval = retrieve_icon(&query, send_request, &config, &caches, &stats, &jobs);
+    let (icon, remaining, cache_status) = match disconnect::or_disconnect(&req, retrieval).await {
+        Some(Ok(Some(v))) => v,
+        // fetched by a job, a later request gets it from the cache
+        Some(Ok(None)) => {
+            return HttpResponse::Accepted()
+                .insert_header((header::RETRY_AFTER, "1"))
+                .insert_header((header::CACHE_CONTROL, "no-store"))
+                .insert_header(("X-Rssss-Cache", "pending"))
+                .finish()
+        }
+        Some(Err(e)) => return case::json(&mut HttpResponse::build(e.status()), query.case, e),
+        None => return client_closed(),
+    };
+    HttpResponse::Ok()
+        .content_type(icon.content_type)
+        .insert_header((
+            header::CACHE_CONTROL,
+            format!("public, max-age={}", remaining.as_secs()),
+        ))
+        .insert_header(("X-Rssss-Cache", cache_status))
+        // svg icons may carry scripts
+        .insert_header((
+            header::CONTENT_SECURITY_POLICY,
+            "default-src 'none'; sandbox",
+        ))
+        .insert_header((header::X_CONTENT_TYPE_OPTIONS, "nosniff"))
+        .body(icon.body)
+}
+
+// what rssss makes of a feed, for its publisher rather than its readers
+async fn get_feed_validate(
+    req: HttpRequest,
+    query: FeedQuery,
+    config: web::Data<Config>,
+    quota: web::Data<Quota>,
+) -> HttpResponse {
+    let (url, limit) = match (
+        validate_url(&query.url, &config.host_filter),
+        deadline::limit(&config, query.timeout_ms),
+    ) {
+        (Ok(url), Ok(limit)) => (url, limit),
+        (Err(e), _) | (_, Err(e)) => {
+            return case::json(&mut HttpResponse::build(e.status()), query.case, e)
+        }
+    };
+    // the feed validated is fetched like that of `/feed`
+    let client = quota::client(&req);
+    let reservation = match reserve(&quota, &config, &client, check::BODY_LIMIT) {
+        Ok(reservation) => reservation,
+        Err(e) => return case::json(&mut over_quota(&e), query.case, ApiError::from(e)),
+    };
+    let progress = Progress::default();
+    let validation = validate::validate_feed(
+        url,
+        send_request,
+        &config.host_filter,
+        config.parse_blocking_threshold,
+        config.field_limits,
+        &progress,
+    );
+    let validation = deadline::with_deadline(limit, &progress, validation);
+    let validated = disconnect::or_disconnect(&req, validation).await;
+    reservation.settle(progress.downloaded(), Utc::now());
+    let mut res = match validated {
+        Some(Ok(Validated::Report(report))) => {
+            case::json(&mut HttpResponse::Ok(), query.case, report)
+        }
+        Some(Ok(Validated::Status(status))) => HttpResponse::build(status).finish(),
+        Some(Err(e)) => case::json(&mut HttpResponse::build(e.status()), query.case, e),
+        None => return client_closed(),
+    };
+    quota_remaining(&mut res, &quota, &config, &client);
+    res
+}
+
+// which of several feeds are broken, without their items
+async fn get_feeds_check(
+    req: HttpRequest,
+    query: CheckQuery,
+    config: web::Data<Config>,
+    quota: web::Data<Quota>,
+) -> HttpResponse {
+    let limit = match deadline::limit(&config, query.timeout_ms) {
+        Ok(limit) => limit,
+        Err(e) => return e.into(),
+    };
+    let mut urls = Vec::new();
+    let mut errors = Vec::new();
+    for url in &query.urls {
+        match validate_url(url, &config.host_filter) {
+            Ok(url) => urls.push(url),
+            Err(e) => errors.push(e),
+        }
+    }
+    if !errors.is_empty() {
+        return ApiError::from(errors).into();
+    }
+    // every feed checked is fetched
+    let client = quota::client(&req);
+    let most = check::BODY_LIMIT * urls.len();
+    let reservation = match reserve(&quota, &config, &client, most) {
+        Ok(reservation) => reservation,
+        Err(e) => return over_quota(&e).json(ApiError::from(e)),
+    };
+    let progress = Progress::default();
+    let checks = check::check_feeds(
+        &urls,
+        send_request,
+        &config,
+        limit,
+        query.collapse_aliases,
+        &progress,
+    );
+    let checked = disconnect::or_disconnect(&req, checks).await;
+    reservation.settle(progress.downloaded(), Utc::now());
+    match checked {
+        Some(results) => HttpResponse::Ok().json(results),
+        None => client_closed(),
+    }
+}
+
+// the feeds, and the jobs run for them
+#[cfg(feature = "metrics")]
+#[derive(serde_derive::Serialize)]
+struct StatsResponse {
+    #[serde(flatten)]
+    snapshot: stats::Snapshot,
+    jobs: jobs::JobStats,
+}
+
+#[cfg(feature = "metrics")]
+async fn get_stats(
+    config: web::Data<Config>,
+    stats: web::Data<Stats>,
+    jobs: web::Data<Jobs>,
+) -> HttpResponse {
+    HttpResponse::Ok().json(StatsResponse {
+        snapshot: stats.snapshot(config.stats_top_urls),
+        jobs: jobs.snapshot(),
+    })
+}
+
+#[cfg(feature = "metrics")]
+async fn delete_stats(stats: web::Data<Stats>, jobs: web::Data<Jobs>) -> HttpResponse {
+    stats.reset();
+    jobs.reset();
+    HttpResponse::NoContent().finish()
+}
+
+// the page for a person, of what `/stats` answers with and the caches
+#[cfg(feature = "admin-ui")]
+async fn get_status(
+    config: web::Data<Config>,
+    caches: web::Data<Caches>,
+    stats: web::Data<Stats>,
+    jobs: web::Data<Jobs>,
+) -> HttpResponse {
+    let page = status::render(&status::Status {
+        config: &config,
+        now: Utc::now(),
+        cached_feeds: caches.feeds.len(),
+        cached_snapshots: caches.snapshots.len(),
+        cached_failures: caches.failures.len(),
+        cached_icons: caches.icons.len(),
+        totals: stats.totals(),
+        snapshot: stats.snapshot(config.stats_top_urls),
+        jobs: jobs.snapshot(),
+        errors: status::recent_errors(),
+    });
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(page)
+}
+
+// 200 with whatever the checks found, the checks themselves say what failed
+async fn get_selftest(query: SelftestQuery, config: web::Data<Config>) -> HttpResponse {
+    let url = match &config.selftest_url {
+        Some(url) => url,
+        None => return ApiError::from(SelftestDisabledError).into(),
+    };
+    let limit = match deadline::limit(&config, query.timeout_ms) {
+        Ok(limit) => limit,
+        Err(e) => return e.into(),
+    };
+    HttpResponse::Ok().json(selftest::run(url, limit).await)
+}
+
+async fn get_health() -> HttpResponse {
+    HttpResponse::Ok().finish()
+}
+
+async fn get_openapi() -> HttpResponse {
+    HttpResponse::Ok().json(api_docs::openapi())
+}
+
+#[cfg(feature = "admin-ui")]
+async fn get_docs() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(api_docs::SWAGGER_UI)
+}
+
+// as the HTTP-version header of a request spells it
+pub(crate) fn protocol(version: Version) -> String {
+    match version {
+        Version::HTTP_09 => "HTTP/0.9",
+        Version::HTTP_10 => "HTTP/1.0",
+        Version::HTTP_2 => "HTTP/2",
+        Version::HTTP_3 => "HTTP/3",
+        _ => "HTTP/1.1",
+    }
+    .to_string()
+}
+
+fn dialed_ip(url: &Url) -> Option<IpAddr> {
+    let addr = upstream::dialed(url.host_str()?, url.port_or_known_default()?)?;
+    Some(addr.ip())
+}
+
+fn send_request(url: &str, conditions: &[(HeaderName, String)]) -> SendClientRequest {
+    info!("{}", url);
+    let mut req = upstream::client().get(url);
+    for (name, value) in conditions {
+        req = req.insert_header((name.clone(), value.as_str()));
+    }
+    req.send()
+}
+
+// span names have to be literals
+macro_rules! trace_span {
+    ($name:literal, $trace:expr, $url:expr) => {
+        match $trace {
+            Some(trace) => info_span!(
+                $name,
+                trace_id = %trace.trace_id(),
+                span_id = %trace.span_id(),
+                parent_id = %trace.parent_id().unwrap_or_default(),
+                url = %$url,
+            ),
+            None => Span::none(),
+        }
+    };
+}
+
+// every hop is a span of its own, named to the upstream as the parent of its spans
+pub(crate) fn fetch(
+    f: fn(&str, &[(HeaderName, String)]) -> SendClientRequest,
+    url: &Url,
+    conditions: &[(HeaderName, String)],
+    jar: &mut CookieJar,
+    progress: &Progress,
+) -> Instrumented<SendClientRequest> {
+    let hop = progress.trace().map(TraceContext::child);
+    let mut headers = conditions.to_vec();
+    if let Some(cookie) = jar.header(url) {
+        headers.push((header::COOKIE, cookie));
+    }
+    if let Some(hop) = &hop {
+        headers.extend(hop.headers());
+    }
+    f(url.as_str(), &headers).instrument(trace_span!("upstream_fetch", &hop, url))
+}
+
+// an upstream status without a feed is passed through as is
+enum Retrieved {
+    Feed(Box<Entry<CachedFeed>>, &'static str),
+    Status(StatusCode),
+}
+
+async fn retrieve_response(
+    query: &FeedQuery,
+    version: ApiVersion,
+    f: fn(&str, &[(HeaderName, String)]) -> SendClientRequest,
+    progress: &Progress,
+    config: &Config,
+    caches: &Caches,
+    stats: &Stats,
+) -> Result<HttpResponse, ApiError> {
+    let url = validate_url(&query.url, &config.host_filter)?;
+    fixtures::check_record(query.record, config)?;
+    let feed_key = canonical_feed_key(&url);
+    let page = Page::new(query.page, query.per_page)?;
+    let limit = deadline::limit(config, query.timeout_ms)?;
+    let retrieval = async {
+        // later pages come from the entry the first one was served from, however the feed changed
+        if let Some(token) = &query.snapshot {
+            let key = token.key(&feed_key);
+            let entry = caches.snapshots.get(&key).ok_or(SnapshotExpiredError)?;
+            stats.record_request(&feed_key, true);
+            let entry = caches
+                .snapshots
+                .insert(key, entry.value, config.cache.snapshot_ttl);
+            let snapshot = Some(token.as_str());
+            if query.expand {
+                progress.set(Phase::Expanding);
+            }
+            let served = Served {
+                key: &feed_key,
+                cache_status: "snapshot",
+                snapshot,
+            };
+            return Ok(feed_response(entry, served, &page, query, version, config, stats).await);
+        }
+        if let Some(res) = negative_hit(&url, query, version, config, caches, stats) {
+            return Ok(res);
+        }
+        let retrieved = retrieve_feed(
+            url.clone(),
+            f,
+            &FetchOptions::of(query, config),
+            progress,
+            config,
+            caches,
+            stats,
+        )
+        .await?;
+        match retrieved {
+            Retrieved::Feed(entry, cache_status) => {
+                let paginated = query.page.is_some() || query.per_page.is_some();
+                let token = paginated.then(|| {
+                    let token = entry.value.snapshot_token(&feed_key);
+                    caches.snapshots.insert(
+                        token.key(&feed_key),
+                        entry.value.clone(),
+                        config.cache.snapshot_ttl,
+                    );
+                    token
+                });
+                let snapshot = token.as_ref().map(SnapshotToken::as_str);
+                if query.expand {
+                    progress.set(Phase::Expanding);
+                }
+                let served = Served {
+                    key: &feed_key,
+                    cache_status,
+                    snapshot,
+                };
+                Ok(feed_response(*entry, served, &page, query, version, config, stats).await)
+            }
+            Retrieved::Status(status) => Ok(HttpResponse::build(status).finish()),
+        }
+    };
+    deadline::with_deadline(limit, progress, retrieval).await
+}
+
+// a parse failure is answered from the cache for a while, unless the client asks to refresh
+fn negative_hit(
+    url: &Url,
+    query: &FeedQuery,
+    version: ApiVersion,
+    config: &Config,
+    caches: &Caches,
+    stats: &Stats,
+) -> Option<HttpResponse> {
+    let key = canonical_feed_key(url);
+    // a parse that forgives more may well take what the failed one didn't
+    let entry = caches.failures.get(&key).filter(|entry| {
+        !query.refresh
+            && entry
+                .value
+                .options
+                .is_none_or(|options| options == parse_options(query, config))
+    })?;
+    stats.record_negative_hit(&key);
+    let Failure {
+        error, fetched_at, ..
+    } = entry.value;
+    let mut builder = HttpResponse::build(error.status());
+    builder
+        .insert_header(("X-Rssss-Cache", "negative"))
+        .insert_header((header::AGE, clock::age(fetched_at, config.clock.now())));
+    Some(error_json(&mut builder, query.case, version, error))
+}
+
+// items come out in document order untouched, anything that needs the whole feed can't stream
+fn ndjson_streamable(query: &FeedQuery) -> bool {
+    query.snapshot.is_none()
+        && query.page.is_none()
+        && query.per_page.is_none()
+        && query.sort_by.is_none()
+        && query.group_by.is_none()
+        && query.range.is_none()
+        && !query.expand
+        && !query.inherit_channel_date
+        && query.parse_options.streamable()
+        && query.continue_from.is_none()
+        && !query.head
+}
+
+// each item is written as soon as it's parsed. until the first line the response can still be
+// an error of its own, after it an error is the last line, the status is long gone
+async fn stream_response(
+    query: &FeedQuery,
+    version: ApiVersion,
+    progress: &Progress,
+    config: web::Data<Config>,
+    caches: web::Data<Caches>,
+    stats: web::Data<Stats>,
+) -> Result<HttpResponse, ApiError> {
+    let url = validate_url(&query.url, &config.host_filter)?;
+    fixtures::check_record(query.record, &config)?;
+    let limit = deadline::limit(&config, query.timeout_ms)?;
+    if let Some(res) = negative_hit(&url, query, version, &config, &caches, &stats) {
+        return Ok(res);
+    }
+    let (sink, items) = mpsc::unbounded();
+    progress.stream_to(sink);
+    let progress = progress.clone();
+    let shaping = ndjson::LineOptions {
+        case: query.case,
+        format: query.format,
+        detect_language: query.detect_language,
+        category_details: query.include_category_details,
+        dates: query.plausible(config.clock.now()),
+    };
+    let options = FetchOptions::of(query, &config);
+    let window_items = config.window_items;
+    // owns what it needs, the body outlives the handler
+    let retrieval = async move {
+        let retrieval = retrieve_feed(
+            url,
+            send_request,
+            &options,
+            &progress,
+            &config,
+            &caches,
+            &stats,
+        );
+        let retrieved = deadline::with_deadline(limit, &progress, retrieval).await;
+        // not taken when nothing was parsed, the items end here then
+        progress.take_sink();
+        match retrieved? {
+            Retrieved::Feed(entry, _) => Ok(ndjson::Outcome::Items(entry.value.feed.items)),
+            Retrieved::Status(status) => Ok(ndjson::Outcome::Status(status)),
+        }
+    }
+    .instrument(Span::current());
+    let mut events = ndjson::Events::new(items, retrieval, window_items);
+    match events.next().await {
+        Some(ndjson::Event::Failed(e)) => Err(e),
+        Some(ndjson::Event::Status(status)) => Ok(HttpResponse::build(status).finish()),
+        first => {
+            let lines = stream::iter(first)
+                .chain(events)
+                .map(move |event| Ok::<_, Infallible>(event.into_line(&shaping)));
+            Ok(HttpResponse::Ok()
+                .content_type(ndjson::CONTENT_TYPE)
+                .streaming(lines))
+        }
+    }
+}
+
+// what a request asks of the fetch besides the url
+struct FetchOptions {
+    parse: ParseOptions,
+    validators: Vec<(HeaderName, String)>,
+    // fetched past the caches, and the body written to RSSSS_RECORD_DIR
+    record: bool,
+}
+
+impl FetchOptions {
+    fn of(query: &FeedQuery, config: &Config) -> FetchOptions {
+        FetchOptions {
+            parse: parse_options(query, config),
+            validators: query.validators.clone(),
+            record: query.record,
+        }
+    }
+}
+
+fn parse_options(query: &FeedQuery, config: &Config) -> ParseOptions {
+    ParseOptions {
+        max_items: Some(config.max_items),
+        limits: config.field_limits,
+        ..query.parse_options
+    }
+}
+
+async fn retrieve_feed(
+    url: Url,
+    f: fn(&str, &[(HeaderName, String)]) -> SendClientRequest,
+    options: &FetchOptions,
+    progress: &Progress,
+    config: &Config,
+    caches: &Caches,
+    stats: &Stats,
+) -> Result<Retrieved, ApiError> {
+    if let Some(dir) = &config.fixture_dir {
+        return retrieve_fixture(dir, &url, options, progress, config, stats).await;
+    }
+    let key = canonical_feed_key(&url);
+    let requested = url.clone();
+    let shared = options.parse.shares_cache();
+    let cached = shared && !options.record;
+    if let Some(entry) = caches.feeds.get(&key).filter(|_| cached) {
+        stats.record_request(&key, true);
+        return Ok(Retrieved::Feed(Box::new(entry), "hit"));
+    }
+    let gone = caches.gone.get(&key).is_some();
+    // read only on a memory miss, so about once per url after a restart
+    let stored = if !gone && cached {
+        caches.load(&key).await
+    } else {
+        None
+    };
+    let fresh = stored.as_ref().and_then(Stored::remaining);
+    stats.record_request(&key, gone || fresh.is_some());
+    if gone {
+        return Err(UpstreamStatusError {
+            status: StatusCode::GONE,
+            user_agent: USER_AGENT.to_string(),
+        }
+        .into());
+    }
+    let stored = match (stored, fresh) {
+        (Some(stored), Some(ttl)) => {
+            let fetched_at = stored.fetched();
+            let cached = CachedFeed {
+                upstream_headers: stored.upstream_headers,
+                upstream_protocol: stored.upstream_protocol,
+                upstream_ip: stored.upstream_ip,
+                ..CachedFeed::new(stored.feed, fetched_at)
+            };
+            return Ok(Retrieved::Feed(
+                Box::new(caches.feeds.insert(key, cached, ttl)),
+                "disk",
+            ));
+        }
+        (stored, _) => stored,
+    };
+    // a stale copy is revalidated with its validators rather than fetched again,
+    // without any copy the client's own are passed on and a 304 is the client's
+    let mut hops = match &stored {
+        Some(stored) => Hops {
+            conditions: stored.conditions(),
+            revalidating: true,
+            ..Hops::default()
+        },
+        None => Hops {
+            conditions: options.validators.clone(),
+            forwarded: !options.validators.is_empty(),
+            ..Hops::default()
+        },
+    };
+    let started = Instant::now();
+    progress.set(Phase::Fetching);
+    let (url, mut res) =
+        redirects::follow_redirects(url, f, &mut hops, progress, config, caches).await?;
+    let jar = hops.jar;
+    if let (StatusCode::NOT_MODIFIED, Some(stored)) = (res.status(), stored.as_ref()) {
+        stats.record_fetch(&key, started.elapsed(), res.status().as_u16(), 0);
+        // the stored feed keeps its warnings, what was cut here is only logged
+        let cache_control = headers::value(res.headers(), &header::CACHE_CONTROL, &mut Vec::new());
+        let ttl = config
+            .cache
+            .ttl(cache_control.as_deref(), &stored.feed.channel, Utc::now());
+        let stored = Stored {
+            upstream_protocol: Some(protocol(res.version())),
+            upstream_ip: dialed_ip(&url),
+            ..stored.revalidated(ttl, response::upstream_headers(res.headers()))
+        };
+        // as old as the 304 that confirmed it
+        let cached = CachedFeed {
+            upstream_headers: stored.upstream_headers.clone(),
+            upstream_protocol: stored.upstream_protocol.clone(),
+            upstream_ip: stored.upstream_ip,
+            cookies_replayed: jar.replayed(),
+            ..CachedFeed::new(stored.feed.clone(), config.clock.now())
+        };
+        caches.store(|| stored).await;
+        return Ok(Retrieved::Feed(
+            Box::new(caches.feeds.insert(key, cached, ttl)),
+            "revalidated",
+        ));
+    }
+    if res.status() == StatusCode::NOT_MODIFIED && hops.forwarded && !hops.conditions.is_empty() {
+        stats.record_fetch(&key, started.elapsed(), res.status().as_u16(), 0);
+        return Ok(Retrieved::Status(StatusCode::NOT_MODIFIED));
+    }
+    if res.status().is_success() {
+        let body = stall::read_body(&mut res, 1_048_576, config.upstream.stall).await?;
+        let stalled = body.warning();
+        let b = body.bytes;
+        let fetched_at = config.clock.now();
+        progress.add_downloaded(b.len());
+        stats.record_fetch(&key, started.elapsed(), res.status().as_u16(), b.len());
+        // under the url the client asked for, which is what a replay is asked for too
+        if let (true, Some(dir)) = (options.record, &config.record_dir) {
+            fixtures::record(dir, &requested, b.clone()).await;
+        }
+        let parsing = Instant::now();
+        progress.set(Phase::Parsing);
+        let mut header_warnings = Vec::new();
+        headers::count(res.headers(), &mut header_warnings);
+        let content_type =
+            headers::value(res.headers(), &header::CONTENT_TYPE, &mut header_warnings);
+        let cache_control =
+            headers::value(res.headers(), &header::CACHE_CONTROL, &mut header_warnings);
+        let (parsed, parse_time) = rss::parse_rss_timed(
+            config.parser.clone(),
+            b.clone(),
+            content_type.as_deref(),
+            config.parse_blocking_threshold,
+            options.parse,
+            progress.take_sink(),
+        )
+        .instrument(trace_span!(
+            "parse",
+            progress.trace().map(TraceContext::child),
+            url
+        ))
+        .await;
+        let slow = watchdog::watch(
+            stats,
+            &key,
+            Section::Parse,
+            parse_time,
+            config.slow_parse_threshold,
+        );
+        // this request is answered, the next ones for any options until the negative ttl is up
+        let rejected = slow && config.reject_slow_parses;
+        if rejected {
+            let e = FeedTooExpensiveError {
+                elapsed: parse_time,
+                threshold: config.slow_parse_threshold,
+            };
+            let failure = Failure {
+                options: None,
+                error: e.into(),
+                fetched_at,
+            };
+            caches
+                .failures
+                .insert(key.clone(), failure, config.cache.negative_ttl);
+        }
+        let mut feed = match parsed {
+            Ok(feed) => feed,
+            // the same bytes are likely served again on the next poll
+            Err(e) if rejected => return Err(e),
+            Err(e) => {
+                let failure = Failure {
+                    options: Some(options.parse),
+                    error: e.clone(),
+                    fetched_at,
+                };
+                caches
+                    .failures
+                    .insert(key, failure, config.cache.negative_ttl);
+                return Err(e);
+            }
+        };
+        // whichever way it parsed, the upstream serves a feed again
+        if !rejected {
+            caches.failures.remove(&key);
+        }
+        stats.record_parse(&key, parsing.elapsed(), feed.items.len());
+        feed.warnings.extend(header_warnings);
+        feed.warnings.extend(stalled);
+        if let Some(image) = feed.channel.image.as_mut() {
+            image.resolve(&url);
+        }
+        feed.channel.resolve_canonical(&url, &requested);
+        let ttl = config
+            .cache
+            .ttl(cache_control.as_deref(), &feed.channel, Utc::now());
+        let cached = CachedFeed {
+            upstream_headers: response::upstream_headers(res.headers()),
+            upstream_protocol: Some(protocol(res.version())),
+            upstream_ip: dialed_ip(&url),
+            cookies_replayed: jar.replayed(),
+            ..CachedFeed::new(feed, fetched_at)
+        };
+        if !shared {
+            return Ok(Retrieved::Feed(
+                Box::new(Entry {
+                    value: cached,
+                    expires_at: Instant::now() + ttl,
+                }),
+                "miss",
+            ));
+        }
+        caches
+            .store(|| Stored {
+                upstream_protocol: cached.upstream_protocol.clone(),
+                upstream_ip: cached.upstream_ip,
+                ..Stored::new(
+                    key.clone(),
+                    ttl,
+                    cached.upstream_headers.clone(),
+                    &b,
+                    cached.feed.clone(),
+                )
+            })
+            .await;
+        return Ok(Retrieved::Feed(
+            Box::new(caches.feeds.insert(key, cached, ttl)),
+            "miss",
+        ));
+    }
+    stats.record_fetch(&key, started.elapsed(), res.status().as_u16(), 0);
+    match res.status() {
+        StatusCode::GONE | StatusCode::NOT_FOUND | StatusCode::FORBIDDEN => {
+            if res.status() == StatusCode::GONE {
+                caches.gone.insert(key, (), config.cache.gone_ttl);
+            }
+            Err(UpstreamStatusError {
+                status: res.status(),
+                user_agent: USER_AGENT.to_string(),
+            }
+            .into())
+        }
+        status => Ok(Retrieved::Status(status)),
+    }
+}
+
+// with RSSSS_FIXTURE_DIR, parsed from the file of the url every time, no cache in between
+async fn retrieve_fixture(
+    dir: &Path,
+    url: &Url,
+    options: &FetchOptions,
+    progress: &Progress,
+    config: &Config,
+    stats: &Stats,
+) -> Result<Retrieved, ApiError> {
+    let key = canonical_feed_key(url);
+    stats.record_request(&key, false);
+    let (b, recorded_at) = fixtures::replay(dir, url).await?;
+    progress.set(Phase::Parsing);
+    let parsing = Instant::now();
+    let (parsed, _) = rss::parse_rss_timed(
+        config.parser.clone(),
+        b,
+        None,
+        config.parse_blocking_threshold,
+        options.parse,
+        progress.take_sink(),
+    )
+    .await;
+    let mut feed = parsed?;
+    stats.record_parse(&key, parsing.elapsed(), feed.items.len());
+    if let Some(image) = feed.channel.image.as_mut() {
+        image.resolve(url);
+    }
+    feed.channel.resolve_canonical(url, url);
+    // the data is as old as the recording, a fixture written by hand is taken as fetched now
+    let cached = CachedFeed::new(feed, recorded_at.unwrap_or_else(|| config.clock.now()));
+    Ok(Retrieved::Feed(
+        Box::new(Entry {
+            value: cached,
+            expires_at: Instant::now(),
+        }),
+        "miss",
+    ))
+}
+
+// with RSSSS_RESPECT_ROBOTS every hop is checked, the rules of a host are fetched once per ttl
+pub(crate) async fn check_robots(
+    url: &Url,
+    f: fn(&str, &[(HeaderName, String)]) -> SendClientRequest,
+    config: &Config,
+    caches: &Caches,
+) -> Result<(), ApiError> {
+    if !config.respect_robots {
+        return Ok(());
+    }
+    let origin = robots::origin(url);
+    let robots = match caches.robots.get(&origin) {
+        Some(entry) => entry.value,
+        None => {
+            let robots = robots::fetch(url, f, &config.host_filter, USER_AGENT).await;
+            caches
+                .robots
+                .insert(origin, robots, config.cache.robots_ttl)
+                .value
+        }
+    };
+    Ok(robots.check(url)?)
+}
+
+// the site is the channel link, or the feed's own host when the feed has none
+async fn retrieve_icon(
+    query: &FeedQuery,
+    f: fn(&str, &[(HeaderName, String)]) -> SendClientRequest,
+    config: &Config,
+    caches: &Caches,
+    stats: &Stats,
+    jobs: &Jobs,
+) -> Result<Option<(Icon, Duration, &'static str)>, ApiError> {
+    let url = validate_url(&query.url, &config.host_filter)?;
+    let progress = Progress::default();
+    let entry = match retrieve_feed(
+        url.clone(),
+        f,
+        &FetchOptions::of(query, config),
+        &progress,
+        config,
+        caches,
+        stats,
+    )
+    .await?
+    {
+        Retrieved::Feed(entry, _) => entry,
+        Retrieved::Status(_) => {
+            return Err(IconNotFoundError {
+                host: url.host_str().unwrap_or_default().to_string(),
+            }
+            .into())
+        }
+    };
+    let site = entry
+        .value
+        .feed
+        .channel
+        .link
+        .as_deref()
+        .filter(|l| !l.is_empty())
+        .and_then(|l| url.join(l).ok())
+        .unwrap_or(url);
+    let site = validate_url(site.as_str(), &config.host_filter)?;
+    let host = site.host_str().unwrap_or_default().to_string();
+    if !config.allow_private_hosts && is_private(&site) {
+        return Err(HostNotAllowedError { host }.into());
+    }
+    let (entry, cache_status) = match caches.icons.get(&host) {
+        Some(entry) => (entry, "hit"),
+        None => {
+            let job = Job::Icon {
+                site: site.clone(),
+                host: host.clone(),
+            };
+            match jobs.enqueue(job) {
+                Ok(()) => return Ok(None),
+                // the queue is full, the client waits for the icon as it used to
+                Err(QueueFullError) => {
+                    (jobs::fetch_icon(&site, &host, config, caches).await, "miss")
+                }
+            }
+        }
+    };
+    let remaining = entry.remaining();
+    match entry.value {
+        Some(icon) => Ok(Some((icon, remaining, cache_status))),
+        None => Err(IconNotFoundError { host }.into()),
+    }
+}
+
+// where the entry of a response came from
+struct Served<'a> {
+    key: &'a str,
+    cache_status: &'a str,
+    snapshot: Option<&'a str>,
+}
+
+async fn feed_response(
+    mut entry: Entry<CachedFeed>,
+    served: Served<'_>,
+    page: &Page,
+    query: &FeedQuery,
+    version: ApiVersion,
+    config: &Config,
+    stats: &Stats,
+) -> HttpResponse {
+    let Served {
+        key,
+        cache_status,
+        snapshot,
+    } = served;
+    let plausible = query.plausible(config.clock.now());
+    for (index, item) in entry.value.feed.items.iter_mut().enumerate() {
+        for suspect in item.check_dates(&plausible) {
+            let warning = format!("item {} {}", index + 1, suspect);
+            entry.value.feed.warnings.push(warning);
+        }
+    }
+    // of every item of the feed, before the window takes them
+    let channel_stats =
+        (version == ApiVersion::V2).then(|| feed_stats::feed_stats(&entry.value.feed.items));
+    let remaining = entry.remaining();
+    let start = query
+        .continue_from
+        .as_ref()
+        .map(|from| from.start(&entry.value.feed.items));
+    let window = Window::new(start, config.window_items).apply(entry.value.feed.items);
+    let mut items = window.items;
+    if let Some(range) = query.time_range(config.clock.now()) {
+        items = timeranges::filter(items, &range);
+    }
+    if let Some(by) = query.sort_by {
+        sorting::sort(&mut items, by);
+    }
+    let paged = page.apply(items);
+    let mut items = paged.items;
+    let fetched_at = entry.value.fetched_at;
+    let mut builder = HttpResponse::Ok();
+    builder
+        .insert_header((
+            header::CACHE_CONTROL,
+            format!("max-age={}", remaining.as_secs()),
+        ))
+        .insert_header((header::AGE, clock::age(fetched_at, config.clock.now())))
+        .insert_header(("X-Rssss-Cache", cache_status))
+        .insert_header(("X-Total-Count", paged.total_count))
+        .insert_header(("X-Total-Pages", paged.total_pages));
+    if let Some(token) = snapshot {
+        builder.insert_header(("X-Rssss-Snapshot", token));
+    }
+    if let Some(next) = window.next {
+        builder.insert_header(("X-Rssss-Continue-From", next));
+    }
+    if let Some(recovery) = entry.value.feed.recovery {
+        builder.insert_header(("X-Rssss-Recovery", recovery.as_str()));
+    }
+    // what follows only changes the body
+    if query.head {
+        let content_type = if query.ndjson {
+            ndjson::CONTENT_TYPE
+        } else {
+            "application/json"
+        };
+        return builder.content_type(content_type).finish();
+    }
+    if query.inherit_channel_date {
+        if let Some(date) = &entry.value.feed.channel.pub_date {
+            items.iter_mut().for_each(|item| item.inherit_date(date));
+        }
+    }
+    if !query.include_category_details {
+        items.iter_mut().for_each(Rss::drop_category_details);
+    }
+    let mut text_time = Duration::ZERO;
+    if query.expand {
+        text_time += expand::expand(
+            &mut items,
+            &config.host_filter,
+            config.allow_private_hosts,
+            config.expand_limit,
+            &config.field_limits,
+            USER_AGENT,
+        )
+        .await;
+    }
+    if query.detect_language {
+        text_time += watchdog::timed(|| items.iter_mut().for_each(Rss::detect_language)).1;
+    }
+    if query.format != Format::Plain {
+        text_time += watchdog::timed(|| {
+            items
+                .iter_mut()
+                .for_each(|item| item.apply_format(query.format))
+        })
+        .1;
+    }
+    watchdog::watch(
+        stats,
+        key,
+        Section::Text,
+        text_time,
+        config.slow_parse_threshold,
+    );
+    let items = match query.group_by {
+        Some(GroupBy::Day) => FeedItems::Groups(grouping::group_by_day(items, query.tz)),
+        None => FeedItems::Items(items),
+    };
+    // a line per item or group, whatever kept the feed from streaming
+    if query.ndjson {
+        return match items {
+            FeedItems::Items(items) => ndjson::response(&mut builder, query.case, items),
+            FeedItems::Groups(groups) => ndjson::response(&mut builder, query.case, groups),
+        };
+    }
+    match (version, items) {
+        (ApiVersion::V1, FeedItems::Items(items)) => case::json(&mut builder, query.case, items),
+        (ApiVersion::V1, FeedItems::Groups(groups)) => case::json(&mut builder, query.case, groups),
+        (ApiVersion::V2, items) => case::json(
+            &mut builder,
+            query.case,
+            FeedEnvelope {
+                url: &query.url,
+                display_url: display::display_url(&query.url),
+                channel: &entry.value.feed.channel,
+                channel_stats,
+                fetched_at: fetched_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+                upstream_headers: &entry.value.upstream_headers,
+                upstream_protocol: entry.value.upstream_protocol.as_deref(),
+                upstream_ip: entry.value.upstream_ip,
+                upstream_ip_family: entry.value.upstream_ip.map(|ip| match ip {
+                    IpAddr::V4(_) => "ipv4",
+                    IpAddr::V6(_) => "ipv6",
+                }),
+                warnings: &entry.value.feed.warnings,
+                fingerprint: entry.value.fingerprint.as_deref(),
+                snapshot,
+                has_more: window.next.is_some(),
+                continue_from: window.next,
+                cookies_replayed: query.debug.then_some(entry.value.cookies_replayed),
+                unmapped_elements: query.debug.then(|| {
+                    entry
+                        .value
+                        .feed
+                        .unmapped_elements
+                        .iter()
+                        .map(|(name, count)| (name.clone(), Value::from(*count)))
+                        .collect()
+                }),
+                dropped_items: query.debug.then_some(&entry.value.feed.dropped_items[..]),
+                items,
+            },
+        ),
+    }
+}
+
+// every path with the methods it answers, what `routes` registers and `cors` allows.
+// those of the features left out of the build are not there at all
+pub const ROUTES: &[(&str, &[Method])] = &[
+    ("/feed", &[Method::GET, Method::HEAD, Method::POST]),
+    ("/v2/feed", &[Method::GET]),
+    ("/feed/icon", &[Method::GET]),
+    ("/feed/validate", &[Method::GET]),
+    ("/feed/parse", &[Method::POST]),
+    ("/feed/parse/batch", &[Method::POST]),
+    ("/feeds/check", &[Method::GET]),
+    #[cfg(feature = "metrics")]
+    ("/stats", &[Method::GET, Method::DELETE]),
+    #[cfg(feature = "admin-ui")]
+    ("/status", &[Method::GET]),
+    ("/selftest", &[Method::GET]),
+    ("/health", &[Method::GET]),
+    ("/openapi.json", &[Method::GET]),
+    #[cfg(feature = "admin-ui")]
+    ("/docs", &[Method::GET]),
+];
+
+fn handler(path: &str, method: &Method) -> Route {
+    let route = web::route();
+    match (path, method.as_str()) {
+        ("/feed", "GET") => route.to(get_feed),
+        ("/feed", "HEAD") => route.to(head_feed),
+        ("/feed", "POST") => route.to(post_feed),
+        ("/v2/feed", "GET") => route.to(get_feed_v2),
+        ("/feed/icon", "GET") => route.to(get_icon),
+        ("/feed/validate", "GET") => route.to(get_feed_validate),
+        ("/feed/parse", "POST") => route.to(parse_feed),
+        ("/feed/parse/batch", "POST") => route.to(parse_feed_batch),
+        ("/feeds/check", "GET") => route.to(get_feeds_check),
+        #[cfg(feature = "metrics")]
+        ("/stats", "GET") => route.to(get_stats),
+        #[cfg(feature = "metrics")]
+        ("/stats", "DELETE") => route.to(delete_stats),
+        #[cfg(feature = "admin-ui")]
+        ("/status", "GET") => route.to(get_status),
+        ("/selftest", "GET") => route.to(get_selftest),
+        ("/health", "GET") => route.to(get_health),
+        ("/openapi.json", "GET") => route.to(get_openapi),
+        #[cfg(feature = "admin-ui")]
+        ("/docs", "GET") => route.to(get_docs),
+        _ => panic!("no handler for {} {}", method, path),
+    }
+}
+
+// an OPTIONS that isn't a preflight, those `cors` answers before they get here
+fn options(methods: &'static [Method]) -> HttpResponse {
+    let allow = methods
+        .iter()
+        .chain([&Method::OPTIONS])
+        .map(Method::as_str)
+        .collect::<Vec<&str>>()
+        .join(", ");
+    HttpResponse::NoContent()
+        .insert_header((header::ALLOW, allow))
+        .finish()
+}
+
+pub fn routes(cfg: &mut web::ServiceConfig) {
+    #[cfg(feature = "admin-ui")]
+    status::started();
+    for &(path, methods) in ROUTES {
+        let resource = methods
+            .iter()
+            .fold(web::resource(path), |resource, method| {
+                resource.route(handler(path, method).method(method.clone()))
+            })
+            .route(web::method(Method::OPTIONS).to(move || async move { options(methods) }));
+        cfg.service(resource);
+    }
+}
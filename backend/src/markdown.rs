@@ -0,0 +1,347 @@
+use ego_tree::NodeRef;
+use scraper::{Html, Node};
+
+// converts item HTML to Markdown, keeping at most `limit` characters of visible text
+// like the plain description, the cut text ends with an ellipsis inside whatever encloses it
+pub fn to_markdown(html: &str, limit: usize) -> String {
+    let fragment = Html::parse_fragment(html);
+    let mut converter = Converter {
+        out: String::new(),
+        budget: limit,
+        truncated: false,
+        lists: Vec::new(),
+    };
+    converter.children(*fragment.root_element());
+    collapse_blank_lines(converter.out.trim())
+}
+
+struct List {
+    ordered: bool,
+    count: usize,
+}
+
+struct Converter {
+    out: String,
+    // visible characters left
+    budget: usize,
+    truncated: bool,
+    lists: Vec<List>,
+}
+
+impl Converter {
+    fn children(&mut self, node: NodeRef<Node>) {
+        for child in node.children() {
+            if self.truncated {
+                return;
+            }
+            self.node(child);
+        }
+    }
+
+    fn node(&mut self, node: NodeRef<Node>) {
+        let element = match node.value() {
+            Node::Text(text) => return self.text(text),
+            Node::Element(element) => element,
+            _ => return,
+        };
+        match element.name() {
+            "script" | "style" | "noscript" | "template" | "iframe" | "head" | "title" => (),
+            "br" => {
+                self.trim_trailing_spaces();
+                self.out.push_str("  \n");
+                self.out.push_str(&self.indent());
+            }
+            "p" | "div" | "section" | "article" | "header" | "footer" | "figure" | "figcaption"
+            | "table" | "tr" => {
+                self.block();
+                self.children(node);
+                self.block();
+            }
+            "hr" => {
+                self.block();
+                self.out.push_str("---");
+                self.block();
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = element.name()[1..].parse().unwrap_or(1);
+                self.block();
+                let inner = self.inline(node);
+                let inner = inner.split_whitespace().collect::<Vec<&str>>().join(" ");
+                if !inner.is_empty() {
+                    self.out.push_str(&"#".repeat(level));
+                    self.out.push(' ');
+                    self.out.push_str(&inner);
+                }
+                self.block();
+            }
+            "em" | "i" | "cite" => self.wrap(node, "*", "*"),
+            "strong" | "b" => self.wrap(node, "**", "**"),
+            "s" | "del" | "strike" => self.wrap(node, "~~", "~~"),
+            "code" => self.wrap(node, "`", "`"),
+            "a" => match element
+                .attr("href")
+                .map(str::trim)
+                .filter(|h| !h.is_empty())
+            {
+                Some(href) => {
+                    let inner = self.inline(node);
+                    self.push_wrapped(&inner, "[", &format!("]({})", destination(href)));
+                }
+                None => self.children(node),
+            },
+            "img" => {
+                if let Some(src) = element.attr("src").map(str::trim).filter(|s| !s.is_empty()) {
+                    let alt = escape(element.attr("alt").unwrap_or_default().trim());
+                    self.out
+                        .push_str(&format!("![{}]({})", alt, destination(src)));
+                }
+            }
+            "pre" => {
+                self.block();
+                let code = node
+                    .descendants()
+                    .filter_map(|n| n.value().as_text().map(|t| t.to_string()))
+                    .collect::<String>();
+                let code = self.take_budget(code.trim_end_matches('\n'));
+                let indent = self.indent();
+                self.out.push_str("```\n");
+                for line in code.lines() {
+                    self.out.push_str(&indent);
+                    self.out.push_str(line);
+                    self.out.push('\n');
+                }
+                self.out.push_str(&indent);
+                self.out.push_str("```");
+                self.block();
+            }
+            "blockquote" => {
+                self.block();
+                let mut quote = Converter {
+                    out: String::new(),
+                    budget: self.budget,
+                    truncated: false,
+                    lists: Vec::new(),
+                };
+                quote.children(node);
+                self.budget = quote.budget;
+                self.truncated = quote.truncated;
+                let quote = collapse_blank_lines(quote.out.trim());
+                let indent = self.indent();
+                for (i, line) in quote.lines().enumerate() {
+                    if i > 0 {
+                        self.out.push('\n');
+                        self.out.push_str(&indent);
+                    }
+                    self.out.push_str(format!("> {}", line).trim_end());
+                }
+                self.block();
+            }
+            "ul" | "ol" => {
+                if self.lists.is_empty() {
+                    self.block();
+                }
+                self.lists.push(List {
+                    ordered: element.name() == "ol",
+                    count: 0,
+                });
+                self.children(node);
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.block();
+                }
+            }
+            "li" => {
+                let marker = match self.lists.last_mut() {
+                    Some(list) if list.ordered => {
+                        list.count += 1;
+                        format!("{}. ", list.count)
+                    }
+                    _ => "- ".to_string(),
+                };
+                self.line();
+                // the indent of the enclosing items, then this item's marker
+                let depth = self.lists.len().saturating_sub(1);
+                self.out.push_str(&self.indent_of(depth));
+                self.out.push_str(&marker);
+                self.children(node);
+            }
+            _ => self.children(node),
+        }
+    }
+
+    // whitespace collapses to a space as it renders in a browser
+    fn text(&mut self, text: &str) {
+        let mut collapsed = String::new();
+        if text.starts_with(|c: char| c.is_ascii_whitespace()) {
+            collapsed.push(' ');
+        }
+        collapsed.push_str(
+            &text
+                .split_ascii_whitespace()
+                .collect::<Vec<&str>>()
+                .join(" "),
+        );
+        if text.ends_with(|c: char| c.is_ascii_whitespace()) && !collapsed.ends_with(' ') {
+            collapsed.push(' ');
+        }
+        // spaces at the start of a line or after a space are no text
+        let collapsed = if self.out.ends_with([' ', '\n']) {
+            collapsed.trim_start()
+        } else {
+            collapsed.as_str()
+        };
+        let visible = self.take_budget(collapsed);
+        self.out.push_str(&escape(&visible));
+    }
+
+    // as much of the text as the budget allows, with an ellipsis when that's not all of it
+    fn take_budget(&mut self, text: &str) -> String {
+        if self.truncated {
+            return String::new();
+        }
+        let count = text.chars().count();
+        if count <= self.budget {
+            self.budget -= count;
+            return text.to_string();
+        }
+        let end = text
+            .char_indices()
+            .nth(self.budget)
+            .map_or(text.len(), |(i, _)| i);
+        self.budget = 0;
+        self.truncated = true;
+        format!("{}...", text[..end].trim_end())
+    }
+
+    // the children converted on their own, for what wraps them
+    fn inline(&mut self, node: NodeRef<Node>) -> String {
+        let mut inner = Converter {
+            out: String::new(),
+            budget: self.budget,
+            truncated: false,
+            lists: Vec::new(),
+        };
+        inner.children(node);
+        self.budget = inner.budget;
+        self.truncated = inner.truncated;
+        inner.out
+    }
+
+    fn wrap(&mut self, node: NodeRef<Node>, open: &str, close: &str) {
+        let inner = self.inline(node);
+        self.push_wrapped(&inner, open, close);
+    }
+
+    // markers hug the text, the spaces around it stay outside
+    fn push_wrapped(&mut self, inner: &str, open: &str, close: &str) {
+        let text = inner.split_whitespace().collect::<Vec<&str>>().join(" ");
+        let space_before = inner.starts_with(char::is_whitespace)
+            && !self.out.is_empty()
+            && !self.out.ends_with([' ', '\n']);
+        if space_before {
+            self.out.push(' ');
+        }
+        if text.is_empty() {
+            return;
+        }
+        self.out.push_str(open);
+        self.out.push_str(&text);
+        self.out.push_str(close);
+        if inner.ends_with(char::is_whitespace) {
+            self.out.push(' ');
+        }
+    }
+
+    fn indent_of(&self, depth: usize) -> String {
+        self.lists[..depth.min(self.lists.len())]
+            .iter()
+            .map(|list| if list.ordered { "   " } else { "  " })
+            .collect()
+    }
+
+    // continuation lines of list items line up with the item text
+    fn indent(&self) -> String {
+        self.indent_of(self.lists.len())
+    }
+
+    // nothing but the indent and marker on the current line yet
+    fn at_item_start(&self) -> bool {
+        let line = self.out.rsplit('\n').next().unwrap_or_default().trim();
+        line.is_empty()
+            || line == "-"
+            || line
+                .strip_suffix('.')
+                .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+    }
+
+    fn trim_trailing_spaces(&mut self) {
+        let trimmed = self.out.trim_end_matches(' ').len();
+        self.out.truncate(trimmed);
+    }
+
+    fn line(&mut self) {
+        self.trim_trailing_spaces();
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+    }
+
+    fn block(&mut self) {
+        self.trim_trailing_spaces();
+        if self.out.is_empty() || self.out.ends_with("\n\n") {
+            return;
+        }
+        if !self.lists.is_empty() {
+            // a paragraph inside a list item stays in the item
+            if self.at_item_start() {
+                return;
+            }
+            self.line();
+            self.out.push_str(&self.indent());
+            return;
+        }
+        self.out.push_str(if self.out.ends_with('\n') {
+            "\n"
+        } else {
+            "\n\n"
+        });
+    }
+}
+
+// the characters that would otherwise start emphasis, links or code
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '[' | ']' | '`') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+// wrapped in angle brackets when the url would end the destination early
+fn destination(url: &str) -> String {
+    if url.contains([' ', '(', ')']) {
+        format!("<{}>", url.replace('<', "%3C").replace('>', "%3E"))
+    } else {
+        url.to_string()
+    }
+}
+
+fn collapse_blank_lines(markdown: &str) -> String {
+    let mut collapsed = String::with_capacity(markdown.len());
+    let mut blank = 0;
+    for line in markdown.lines() {
+        if line.trim().is_empty() {
+            blank += 1;
+            continue;
+        }
+        if !collapsed.is_empty() {
+            collapsed.push_str(if blank > 0 { "\n\n" } else { "\n" });
+        }
+        blank = 0;
+        collapsed.push_str(line);
+    }
+    collapsed
+}
@@ -0,0 +1,213 @@
+use crate::case::{self, Case};
+use crate::dates::Plausible;
+use crate::error::ApiError;
+use crate::rss::{Format, Rss};
+use actix_web::http::header::{HeaderMap, ACCEPT};
+use actix_web::http::StatusCode;
+use actix_web::web::{Bytes, BytesMut};
+use actix_web::{HttpResponse, HttpResponseBuilder};
+use futures::channel::mpsc::UnboundedReceiver;
+use futures::stream::Fuse;
+use futures::{Stream, StreamExt};
+use serde::Serialize;
+use serde_json::json;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+pub const CONTENT_TYPE: &str = "application/x-ndjson";
+
+// whether a media range of the Accept header names ndjson, without ruling it out by q=0
+pub fn accepts(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(ACCEPT)
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|range| {
+            let mut parts = range.split(';').map(str::trim);
+            let media_type = parts.next().unwrap_or_default();
+            let refused = parts.any(|p| {
+                p.split_once('=').is_some_and(|(name, q)| {
+                    name.trim().eq_ignore_ascii_case("q") && q.trim().parse::<f64>() == Ok(0.0)
+                })
+            });
+            media_type.eq_ignore_ascii_case(CONTENT_TYPE) && !refused
+        })
+}
+
+// one JSON value and a newline
+pub fn line<T: Serialize>(case: Case, value: T) -> Bytes {
+    match case::to_value(case, value).and_then(|v| serde_json::to_vec(&v)) {
+        Ok(mut line) => {
+            line.push(b'\n');
+            Bytes::from(line)
+        }
+        Err(e) => error_line(case, e.into()),
+    }
+}
+
+// the last line of a stream that failed after it started, in the v1 error shape
+pub fn error_line(case: Case, e: ApiError) -> Bytes {
+    line(case, json!({ "error": e }))
+}
+
+// the values of a body that's already complete, a line each
+pub fn response<T: Serialize>(
+    builder: &mut HttpResponseBuilder,
+    case: Case,
+    values: Vec<T>,
+) -> HttpResponse {
+    let mut body = BytesMut::new();
+    for value in values {
+        body.extend_from_slice(&line(case, value));
+    }
+    builder.content_type(CONTENT_TYPE).body(body.freeze())
+}
+
+// what a retrieval ends with once its items have gone by
+pub enum Outcome {
+    // all the items of the feed, sent only when none were streamed, as for a cached feed
+    Items(Vec<Rss>),
+    // an upstream status without a feed
+    Status(StatusCode),
+}
+
+// what the query asks of each streamed item, as `feed_response` does it for the rest
+#[derive(Debug, Clone, Copy)]
+pub struct LineOptions {
+    pub case: Case,
+    pub format: Format,
+    pub detect_language: bool,
+    pub category_details: bool,
+    pub dates: Plausible,
+}
+
+pub enum Event {
+    Item(Box<Rss>),
+    Status(StatusCode),
+    Failed(ApiError),
+    // the window was full, the rest is asked for from this index
+    More(usize),
+}
+
+impl Event {
+    pub fn into_line(self, options: &LineOptions) -> Bytes {
+        match self {
+            Event::Item(mut item) => {
+                // a line has nowhere for warnings to go, the flag is all that's said
+                item.check_dates(&options.dates);
+                if !options.category_details {
+                    item.drop_category_details();
+                }
+                if options.detect_language {
+                    item.detect_language();
+                }
+                if options.format != Format::Plain {
+                    item.apply_format(options.format);
+                }
+                line(options.case, item)
+            }
+            // there's a status only when nothing was parsed, so it never follows a line
+            Event::Status(_) => Bytes::new(),
+            Event::Failed(e) => error_line(options.case, e),
+            // the headers are long gone, so what X-Rssss-Continue-From says is the last line
+            Event::More(next) => line(
+                options.case,
+                json!({ "has_more": true, "continue_from": next }),
+            ),
+        }
+    }
+}
+
+// the items of a retrieval as the parser sends them up to the window, then how the retrieval
+// ended. items past the window are read and dropped, the retrieval still caches the feed
+pub struct Events<F> {
+    // polled again after its end while the retrieval finishes
+    items: Fuse<UnboundedReceiver<Rss>>,
+    retrieval: Option<Pin<Box<F>>>,
+    outcome: Option<Result<Outcome, ApiError>>,
+    rest: std::vec::IntoIter<Rss>,
+    streamed: bool,
+    window: usize,
+    sent: usize,
+    more: Option<usize>,
+}
+
+impl<F> Events<F>
+where
+    F: Future<Output = Result<Outcome, ApiError>>,
+{
+    // the retrieval has to let go of the sender when it's done, or the items never end
+    pub fn new(items: UnboundedReceiver<Rss>, retrieval: F, window: usize) -> Events<F> {
+        Events {
+            items: items.fuse(),
+            retrieval: Some(Box::pin(retrieval)),
+            outcome: None,
+            rest: Vec::new().into_iter(),
+            streamed: false,
+            window,
+            sent: 0,
+            more: None,
+        }
+    }
+
+    fn within_window(&mut self, item: Rss) -> Option<Event> {
+        if self.sent == self.window {
+            self.more = Some(self.window);
+            return None;
+        }
+        self.sent += 1;
+        Some(Event::Item(Box::new(item)))
+    }
+}
+
+impl<F> Stream for Events<F>
+where
+    F: Future<Output = Result<Outcome, ApiError>>,
+{
+    type Item = Event;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Event>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(item) = this.rest.next() {
+                match this.within_window(item) {
+                    Some(event) => return Poll::Ready(Some(event)),
+                    None => {
+                        this.rest = Vec::new().into_iter();
+                        continue;
+                    }
+                }
+            }
+            if let Some(retrieval) = this.retrieval.as_mut() {
+                if let Poll::Ready(outcome) = retrieval.as_mut().poll(cx) {
+                    this.retrieval = None;
+                    this.outcome = Some(outcome);
+                }
+            }
+            match this.items.poll_next_unpin(cx) {
+                Poll::Ready(Some(item)) => {
+                    this.streamed = true;
+                    if let Some(event) = this.within_window(item) {
+                        return Poll::Ready(Some(event));
+                    }
+                }
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(None) => match this.outcome.take() {
+                    // parsed, the feed is still being cached
+                    None if this.retrieval.is_some() => return Poll::Pending,
+                    Some(Ok(Outcome::Items(items))) if !this.streamed => {
+                        this.rest = items.into_iter();
+                    }
+                    None | Some(Ok(Outcome::Items(_))) => {
+                        return Poll::Ready(this.more.take().map(Event::More))
+                    }
+                    Some(Ok(Outcome::Status(status))) => {
+                        return Poll::Ready(Some(Event::Status(status)))
+                    }
+                    Some(Err(e)) => return Poll::Ready(Some(Event::Failed(e))),
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::error::{ApiError, InvalidParameterError};
+use crate::rss::Rss;
+use std::str::FromStr;
+
+pub struct Page {
+    page: usize,
+    per_page: usize,
+}
+
+pub struct Paged<T> {
+    pub items: Vec<T>,
+    pub total_count: usize,
+    pub total_pages: usize,
+}
+
+impl Page {
+    const DEFAULT_PER_PAGE: usize = 50;
+    const MAX_PER_PAGE: usize = 200;
+
+    pub fn new(page: Option<usize>, per_page: Option<usize>) -> Result<Page, ApiError> {
+        let page = page.unwrap_or(1);
+        if page == 0 {
+            return Err(InvalidParameterError {
+                name: "page".to_string(),
+                message: "must be 1 or greater".to_string(),
+            }
+            .into());
+        }
+        let per_page = per_page.unwrap_or(Page::DEFAULT_PER_PAGE);
+        if per_page == 0 {
+            return Err(InvalidParameterError {
+                name: "per_page".to_string(),
+                message: "must be 1 or greater".to_string(),
+            }
+            .into());
+        }
+        // more than the most is served as the most, like a page past the end is served empty
+        Ok(Page {
+            page,
+            per_page: per_page.min(Page::MAX_PER_PAGE),
+        })
+    }
+
+    // pages out of range are empty rather than an error
+    pub fn apply<T>(&self, items: Vec<T>) -> Paged<T> {
+        let total_count = items.len();
+        let total_pages = total_count.div_ceil(self.per_page);
+        let items = items
+            .into_iter()
+            .skip((self.page - 1).saturating_mul(self.per_page))
+            .take(self.per_page)
+            .collect();
+        Paged {
+            items,
+            total_count,
+            total_pages,
+        }
+    }
+}
+
+// the items of a response at most, the rest served by asking again from where it ended.
+// applied before sorting and paging, which only see the items of the window
+pub struct Window {
+    start: usize,
+    size: usize,
+}
+
+pub struct Windowed<T> {
+    pub items: Vec<T>,
+    // the `continue_from` of the next window, None at the last
+    pub next: Option<usize>,
+}
+
+// where a window starts, an index or the guid of the item before it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContinueFrom {
+    Index(usize),
+    After(String),
+}
+
+impl FromStr for ContinueFrom {
+    type Err = String;
+
+    // a guid that is a number is taken for an index, a negative one too. items keep no control
+    // characters, so no guid has them
+    fn from_str(s: &str) -> Result<ContinueFrom, String> {
+        match s.parse() {
+            Ok(index) => Ok(ContinueFrom::Index(index)),
+            Err(_)
+                if s.trim().is_empty()
+                    || s.parse::<i64>().is_ok()
+                    || s.chars().any(char::is_control) =>
+            {
+                Err("must be an index or a guid".to_string())
+            }
+            Err(_) => Ok(ContinueFrom::After(s.to_string())),
+        }
+    }
+}
+
+impl ContinueFrom {
+    // a guid not among the items starts past them
+    pub fn start(&self, items: &[Rss]) -> usize {
+        match self {
+            ContinueFrom::Index(index) => *index,
+            ContinueFrom::After(guid) => items
+                .iter()
+                .position(|item| item.guid() == Some(guid))
+                .map_or(usize::MAX, |i| i + 1),
+        }
+    }
+}
+
+impl Window {
+    pub fn new(continue_from: Option<usize>, size: usize) -> Window {
+        Window {
+            start: continue_from.unwrap_or(0),
+            size,
+        }
+    }
+
+    // a start past the items is an empty window, as pages out of range are
+    pub fn apply<T>(&self, items: Vec<T>) -> Windowed<T> {
+        let end = self.start.saturating_add(self.size);
+        let next = (end < items.len()).then_some(end);
+        let items = items.into_iter().skip(self.start).take(self.size).collect();
+        Windowed { items, next }
+    }
+}
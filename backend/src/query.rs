@@ -0,0 +1,509 @@
+use crate::cache::SnapshotToken;
+use crate::case::Case;
+use crate::check;
+use crate::dates::{FutureDates, Plausible, TzOffset};
+use crate::error::{sanitize, ApiError, InvalidParameterError, UnsupportedMediaTypeError};
+use crate::grouping::GroupBy;
+use crate::page::ContinueFrom;
+use crate::rss::{Format, ParseOptions, ParserName, Strictness};
+use crate::sorting::SortBy;
+use crate::timeranges::{Range, TimeRange};
+use actix_web::dev::Payload;
+use actix_web::http::header::{self, HeaderMap, HeaderName};
+use actix_web::{FromRequest, HttpRequest};
+use chrono::{DateTime, Utc};
+use futures::future::{ready, Ready};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use url::form_urlencoded;
+
+pub const MAX_BODY_SIZE: usize = 65_536;
+
+// the validators a client kept from the feed itself, passed on as they are
+pub fn validators(headers: &HeaderMap) -> Vec<(HeaderName, String)> {
+    [header::IF_NONE_MATCH, header::IF_MODIFIED_SINCE]
+        .into_iter()
+        .filter_map(|name| {
+            let value = headers.get(&name)?.to_str().ok()?.trim().to_string();
+            (!value.is_empty()).then_some((name, value))
+        })
+        .collect()
+}
+
+// unknown parameters are ignored, invalid and repeated ones are all reported at once
+pub struct FeedQuery {
+    pub url: String,
+    pub expand: bool,
+    pub page: Option<usize>,
+    pub per_page: Option<usize>,
+    pub group_by: Option<GroupBy>,
+    pub sort_by: Option<SortBy>,
+    pub tz: TzOffset,
+    // only the items published within the day, in the zone of `tz`
+    pub range: Option<Range>,
+    pub inherit_channel_date: bool,
+    pub case: Case,
+    pub format: Format,
+    pub detect_language: bool,
+    // `categories_detailed` next to the flat `categories`
+    pub include_category_details: bool,
+    // what is shown of dates outside `Plausible`
+    pub future_dates: FutureDates,
+    pub snapshot: Option<SnapshotToken>,
+    pub timeout_ms: Option<u64>,
+    // where the items past a window start
+    pub continue_from: Option<ContinueFrom>,
+    // `strictness`, with `lenient` and `partial` turning on what they name
+    pub parse_options: ParseOptions,
+    pub refresh: bool,
+    pub debug: bool,
+    // writes the fetched body to RSSSS_RECORD_DIR
+    pub record: bool,
+    // from the Accept header rather than a parameter, `format` already names the description format
+    pub ndjson: bool,
+    // a HEAD, answered with the headers of the GET and no body
+    pub head: bool,
+    // the client's If-None-Match and If-Modified-Since, for the upstream
+    pub validators: Vec<(HeaderName, String)>,
+}
+
+impl FeedQuery {
+    // also what `/openapi.json` documents, a name missing here is ignored like unknown ones
+    pub const PARAMETERS: [&'static str; 25] = [
+        "url",
+        "expand",
+        "page",
+        "per_page",
+        "group_by",
+        "sort_by",
+        "tz",
+        "range",
+        "inherit_channel_date",
+        "case",
+        "format",
+        "detect_language",
+        "include_category_details",
+        "future_dates",
+        "snapshot",
+        "timeout_ms",
+        "continue_from",
+        "strictness",
+        "lenient",
+        "partial",
+        "refresh",
+        "debug",
+        "include_raw",
+        "record",
+        "parser",
+    ];
+
+    pub fn parse(query: &str) -> Result<FeedQuery, ApiError> {
+        FeedQuery::from_params(parse_params(query, &FeedQuery::PARAMETERS)?)
+    }
+
+    // the same parameters as a JSON object or a form, for urls too long for a query string
+    pub fn from_body(content_type: Option<&str>, body: &[u8]) -> Result<FeedQuery, ApiError> {
+        let mime = content_type
+            .and_then(|c| c.split(';').next())
+            .map(|m| m.trim().to_ascii_lowercase());
+        match mime.as_deref() {
+            Some("application/json") => FeedQuery::from_params(json_params(body)?),
+            Some("application/x-www-form-urlencoded") => {
+                FeedQuery::parse(&String::from_utf8_lossy(body))
+            }
+            _ => Err(UnsupportedMediaTypeError {
+                content_type: content_type.unwrap_or_default().to_string(),
+            }
+            .into()),
+        }
+    }
+
+    fn from_params(params: Vec<(&str, String)>) -> Result<FeedQuery, ApiError> {
+        let mut errors = Vec::new();
+        let mut url = None;
+        let mut expand = None;
+        let mut page = None;
+        let mut per_page = None;
+        let mut group_by = None;
+        let mut sort_by = None;
+        let mut tz = None;
+        let mut range = None;
+        let mut inherit_channel_date = None;
+        let mut case = None;
+        let mut format = None;
+        let mut detect_language = None;
+        let mut include_category_details = None;
+        let mut future_dates = None;
+        let mut snapshot = None;
+        let mut timeout_ms = None;
+        let mut continue_from = None;
+        let mut strictness = None;
+        let mut lenient = None;
+        let mut partial = None;
+        let mut refresh = None;
+        let mut debug = None;
+        let mut include_raw = None;
+        let mut record = None;
+        let mut parser = None;
+        for (name, value) in params {
+            match name {
+                "url" => url = Some(value),
+                "expand" => expand = parse_value(name, &value, &mut errors),
+                "page" => page = parse_value(name, &value, &mut errors),
+                "per_page" => per_page = parse_value(name, &value, &mut errors),
+                "group_by" => group_by = parse_value(name, &value, &mut errors),
+                "sort_by" => sort_by = parse_value(name, &value, &mut errors),
+                "tz" => tz = parse_listed(name, &value, &mut errors),
+                "range" => range = parse_value(name, &value, &mut errors),
+                "inherit_channel_date" => {
+                    inherit_channel_date = parse_value(name, &value, &mut errors)
+                }
+                "case" => case = parse_value(name, &value, &mut errors),
+                "format" => format = parse_value(name, &value, &mut errors),
+                "detect_language" => detect_language = parse_value(name, &value, &mut errors),
+                "include_category_details" => {
+                    include_category_details = parse_value(name, &value, &mut errors)
+                }
+                "future_dates" => future_dates = parse_value(name, &value, &mut errors),
+                "snapshot" => snapshot = parse_value(name, &value, &mut errors),
+                "timeout_ms" => timeout_ms = parse_value(name, &value, &mut errors),
+                "continue_from" => continue_from = parse_value(name, &value, &mut errors),
+                "strictness" => strictness = parse_value(name, &value, &mut errors),
+                "lenient" => lenient = parse_value(name, &value, &mut errors),
+                "partial" => partial = parse_value(name, &value, &mut errors),
+                "refresh" => refresh = parse_value(name, &value, &mut errors),
+                "debug" => debug = parse_value(name, &value, &mut errors),
+                "include_raw" => include_raw = parse_value(name, &value, &mut errors),
+                "record" => record = parse_value(name, &value, &mut errors),
+                "parser" => parser = parse_listed(name, &value, &mut errors),
+                _ => (),
+            }
+        }
+        if url.is_none() {
+            errors.push(
+                InvalidParameterError {
+                    name: "url".to_string(),
+                    message: "is required".to_string(),
+                }
+                .into(),
+            );
+        }
+        let strictness = strictness.unwrap_or_default();
+        if strictness == Strictness::Strict {
+            // strict can't also forgive, asking for both is a mistake rather than a preference
+            for (name, value) in [
+                ("lenient", lenient),
+                ("partial", partial),
+                ("inherit_channel_date", inherit_channel_date),
+            ] {
+                if value == Some(true) {
+                    errors.push(
+                        InvalidParameterError {
+                            name: name.to_string(),
+                            message: "can't be true with strictness=strict".to_string(),
+                        }
+                        .into(),
+                    );
+                }
+            }
+        }
+        // what was read only makes sense next to the rest of what debug shows
+        if include_raw == Some(true) && debug != Some(true) {
+            errors.push(
+                InvalidParameterError {
+                    name: "include_raw".to_string(),
+                    message: "requires debug=true".to_string(),
+                }
+                .into(),
+            );
+        }
+        if !errors.is_empty() {
+            return Err(errors.into());
+        }
+        let mut parse_options = ParseOptions::of(strictness);
+        parse_options.recover |= lenient.unwrap_or(false);
+        parse_options.partial |= partial.unwrap_or(false);
+        parse_options.parser = parser;
+        parse_options.keep_raw = include_raw.unwrap_or(false);
+        parse_options.provenance = debug.unwrap_or(false);
+        Ok(FeedQuery {
+            url: url.unwrap_or_default(),
+            expand: expand.unwrap_or(false),
+            page,
+            per_page,
+            group_by,
+            sort_by,
+            tz: tz.unwrap_or_default(),
+            range,
+            inherit_channel_date: inherit_channel_date.unwrap_or(strictness == Strictness::Lenient),
+            case: case.unwrap_or_default(),
+            format: format.unwrap_or_default(),
+            detect_language: detect_language.unwrap_or(false),
+            include_category_details: include_category_details.unwrap_or(false),
+            future_dates: future_dates.unwrap_or_default(),
+            snapshot,
+            timeout_ms,
+            continue_from,
+            parse_options,
+            refresh: refresh.unwrap_or(false),
+            debug: debug.unwrap_or(false),
+            record: record.unwrap_or(false),
+            ndjson: false,
+            head: false,
+            validators: Vec::new(),
+        })
+    }
+
+    pub fn plausible(&self, now: DateTime<Utc>) -> Plausible {
+        Plausible {
+            now,
+            policy: self.future_dates,
+        }
+    }
+
+    pub fn time_range(&self, now: DateTime<Utc>) -> Option<TimeRange> {
+        self.range.map(|range| TimeRange::of(range, now, self.tz))
+    }
+}
+
+// `/feeds/check` takes a `url` per feed, in the order the results are wanted
+pub struct CheckQuery {
+    pub urls: Vec<String>,
+    pub timeout_ms: Option<u64>,
+    pub collapse_aliases: bool,
+}
+
+impl CheckQuery {
+    pub const PARAMETERS: [&'static str; 3] = ["url", "timeout_ms", "collapse_aliases"];
+
+    pub fn parse(query: &str) -> Result<CheckQuery, ApiError> {
+        let urls = form_urlencoded::parse(query.as_bytes())
+            .filter(|(name, _)| name == "url")
+            .map(|(_, value)| value.into_owned())
+            .collect::<Vec<String>>();
+        let mut errors = Vec::new();
+        let mut timeout_ms = None;
+        let mut collapse_aliases = None;
+        for (name, value) in parse_params(query, &CheckQuery::PARAMETERS[1..])? {
+            match name {
+                "timeout_ms" => timeout_ms = parse_value(name, &value, &mut errors),
+                "collapse_aliases" => collapse_aliases = parse_value(name, &value, &mut errors),
+                _ => (),
+            }
+        }
+        let count = match urls.len() {
+            0 => Some("is required".to_string()),
+            n if n > check::MAX_URLS => Some(format!("at most {} urls", check::MAX_URLS)),
+            _ => None,
+        };
+        if let Some(message) = count {
+            errors.push(
+                InvalidParameterError {
+                    name: "url".to_string(),
+                    message,
+                }
+                .into(),
+            );
+        }
+        if !errors.is_empty() {
+            return Err(errors.into());
+        }
+        Ok(CheckQuery {
+            urls,
+            timeout_ms,
+            collapse_aliases: collapse_aliases.unwrap_or(false),
+        })
+    }
+}
+
+// `/selftest` only takes a lower timeout for its checks
+pub struct SelftestQuery {
+    pub timeout_ms: Option<u64>,
+}
+
+impl SelftestQuery {
+    pub const PARAMETERS: [&'static str; 1] = ["timeout_ms"];
+
+    pub fn parse(query: &str) -> Result<SelftestQuery, ApiError> {
+        let mut errors = Vec::new();
+        let mut timeout_ms = None;
+        for (name, value) in parse_params(query, &SelftestQuery::PARAMETERS)? {
+            if name == "timeout_ms" {
+                timeout_ms = parse_value(name, &value, &mut errors);
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors.into());
+        }
+        Ok(SelftestQuery { timeout_ms })
+    }
+}
+
+// `/feed/parse` takes the body as the document, the parameters only pick how it's parsed
+pub struct ParseQuery {
+    pub parser: Option<ParserName>,
+}
+
+impl ParseQuery {
+    pub const PARAMETERS: [&'static str; 1] = ["parser"];
+
+    pub fn parse(query: &str) -> Result<ParseQuery, ApiError> {
+        let mut errors = Vec::new();
+        let mut parser = None;
+        for (name, value) in parse_params(query, &ParseQuery::PARAMETERS)? {
+            if name == "parser" {
+                parser = parse_listed(name, &value, &mut errors);
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors.into());
+        }
+        Ok(ParseQuery { parser })
+    }
+}
+
+// the decoded values of the given names in query order, unknown names are skipped,
+// and a name given more than once is an error as there's no telling which one was meant
+pub fn parse_params<'a>(
+    query: &str,
+    names: &[&'a str],
+) -> Result<Vec<(&'a str, String)>, ApiError> {
+    let mut params = Vec::new();
+    let mut values = BTreeMap::new();
+    for (name, value) in form_urlencoded::parse(query.as_bytes()) {
+        if let Some(name) = names.iter().find(|n| **n == name) {
+            values
+                .entry(*name)
+                .or_insert_with(Vec::new)
+                .push(value.to_string());
+            params.push((*name, value.into_owned()));
+        }
+    }
+    let errors = values
+        .into_iter()
+        .filter(|(_, values)| values.len() > 1)
+        .map(|(name, values)| {
+            let times = match values.len() {
+                2 => "twice".to_string(),
+                n => format!("{} times", n),
+            };
+            let values = values
+                .iter()
+                .map(|v| format!("\"{}\"", sanitize(v)))
+                .collect::<Vec<String>>()
+                .join(", ");
+            InvalidParameterError {
+                name: name.to_string(),
+                message: format!("provided {}: {}", times, values),
+            }
+            .into()
+        })
+        .collect::<Vec<ApiError>>();
+    if !errors.is_empty() {
+        return Err(errors.into());
+    }
+    Ok(params)
+}
+
+// strings as they are, booleans and numbers as written, nulls as if absent
+fn json_params(body: &[u8]) -> Result<Vec<(&'static str, String)>, ApiError> {
+    let object = serde_json::from_slice::<Map<String, Value>>(body)?;
+    let mut params = Vec::new();
+    let mut errors = Vec::new();
+    for (name, value) in object {
+        let name = match FeedQuery::PARAMETERS.iter().find(|n| **n == name) {
+            Some(name) => *name,
+            None => continue,
+        };
+        match value {
+            Value::String(s) => params.push((name, s)),
+            Value::Bool(_) | Value::Number(_) => params.push((name, value.to_string())),
+            Value::Null => (),
+            _ => errors.push(
+                InvalidParameterError {
+                    name: name.to_string(),
+                    message: format!("unexpected value: {}", value),
+                }
+                .into(),
+            ),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors.into());
+    }
+    Ok(params)
+}
+
+fn parse_value<T: FromStr>(name: &str, value: &str, errors: &mut Vec<ApiError>) -> Option<T> {
+    match value.parse() {
+        Ok(v) => Some(v),
+        Err(_) => {
+            errors.push(
+                InvalidParameterError {
+                    name: name.to_string(),
+                    message: format!("unexpected value: {}", value),
+                }
+                .into(),
+            );
+            None
+        }
+    }
+}
+
+// the error lists the valid values, a typo is the likely mistake
+fn parse_listed<T: FromStr<Err = String>>(
+    name: &str,
+    value: &str,
+    errors: &mut Vec<ApiError>,
+) -> Option<T> {
+    match value.parse() {
+        Ok(v) => Some(v),
+        Err(message) => {
+            errors.push(
+                InvalidParameterError {
+                    name: name.to_string(),
+                    message,
+                }
+                .into(),
+            );
+            None
+        }
+    }
+}
+
+impl FromRequest for CheckQuery {
+    type Error = ApiError;
+    type Future = Ready<Result<CheckQuery, ApiError>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(CheckQuery::parse(req.query_string()))
+    }
+}
+
+impl FromRequest for SelftestQuery {
+    type Error = ApiError;
+    type Future = Ready<Result<SelftestQuery, ApiError>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(SelftestQuery::parse(req.query_string()))
+    }
+}
+
+impl FromRequest for ParseQuery {
+    type Error = ApiError;
+    type Future = Ready<Result<ParseQuery, ApiError>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(ParseQuery::parse(req.query_string()))
+    }
+}
+
+impl FromRequest for FeedQuery {
+    type Error = ApiError;
+    type Future = Ready<Result<FeedQuery, ApiError>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(FeedQuery::parse(req.query_string()))
+    }
+}
@@ -0,0 +1,179 @@
+use crate::auth::ApiKey;
+use actix_web::{HttpMessage, HttpRequest};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+// the fetches counted against a quota are those of the last 24 hours
+pub fn window() -> Duration {
+    Duration::hours(24)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Usage {
+    pub used: u64,
+    pub remaining: u64,
+    // when the oldest counted fetch leaves the window
+    pub resets_at: Option<DateTime<Utc>>,
+}
+
+// how many records go by between sweeps of the clients gone quiet for a day
+const SWEEP_EVERY: u64 = 1_024;
+
+#[derive(Debug, Default)]
+struct ClientFetches {
+    // when and how many bytes were fetched, oldest first
+    fetches: VecDeque<(DateTime<Utc>, u64)>,
+    // held back for the requests still fetching
+    reserved: u64,
+}
+
+impl ClientFetches {
+    fn expire(&mut self, now: DateTime<Utc>) {
+        while self
+            .fetches
+            .front()
+            .is_some_and(|(at, _)| *at + window() <= now)
+        {
+            self.fetches.pop_front();
+        }
+    }
+
+    fn usage(&self, limit: u64) -> Usage {
+        let used = self.fetches.iter().map(|(_, bytes)| bytes).sum::<u64>();
+        Usage {
+            used,
+            remaining: limit.saturating_sub(used + self.reserved),
+            resets_at: self.fetches.front().map(|(at, _)| *at + window()),
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        self.fetches.is_empty() && self.reserved == 0
+    }
+}
+
+#[derive(Debug, Default)]
+struct Clients {
+    clients: HashMap<String, ClientFetches>,
+    records: u64,
+}
+
+// the feed bytes fetched from upstream per client, cache hits fetch none
+#[derive(Debug, Default)]
+pub struct Quota {
+    clients: Mutex<Clients>,
+}
+
+// what a request holds back of a client's quota until it's settled with what it fetched,
+// released as unused when dropped unsettled
+#[derive(Debug)]
+pub struct Reservation<'a> {
+    quota: &'a Quota,
+    client: String,
+    bytes: u64,
+}
+
+impl Quota {
+    pub fn usage(&self, client: &str, limit: u64, now: DateTime<Utc>) -> Usage {
+        let mut clients = self.clients.lock().unwrap();
+        let Some(client_fetches) = clients.clients.get_mut(client) else {
+            return Usage {
+                used: 0,
+                remaining: limit,
+                resets_at: None,
+            };
+        };
+        client_fetches.expire(now);
+        let usage = client_fetches.usage(limit);
+        if client_fetches.is_idle() {
+            clients.clients.remove(client);
+        }
+        usage
+    }
+
+    // checks the quota and holds back up to `most` bytes of what is left in one go, so requests
+    // at once can't all pass on the same remainder. the usage it was refused with otherwise
+    pub fn reserve(
+        &self,
+        client: &str,
+        limit: u64,
+        most: u64,
+        now: DateTime<Utc>,
+    ) -> Result<Reservation<'_>, Usage> {
+        let mut clients = self.clients.lock().unwrap();
+        let client_fetches = clients.clients.entry(client.to_string()).or_default();
+        client_fetches.expire(now);
+        let usage = client_fetches.usage(limit);
+        if usage.remaining == 0 {
+            if client_fetches.is_idle() {
+                clients.clients.remove(client);
+            }
+            return Err(usage);
+        }
+        let bytes = usage.remaining.min(most);
+        client_fetches.reserved += bytes;
+        Ok(Reservation {
+            quota: self,
+            client: client.to_string(),
+            bytes,
+        })
+    }
+
+    pub fn record(&self, client: &str, bytes: u64, now: DateTime<Utc>) {
+        let mut clients = self.clients.lock().unwrap();
+        clients.records += 1;
+        // clients gone quiet for a day are forgotten, now and then rather than on every record
+        if clients.records.is_multiple_of(SWEEP_EVERY) {
+            clients.clients.retain(|_, client_fetches| {
+                client_fetches.expire(now);
+                !client_fetches.is_idle()
+            });
+        }
+        let client_fetches = clients.clients.entry(client.to_string()).or_default();
+        client_fetches.expire(now);
+        if bytes > 0 {
+            client_fetches.fetches.push_back((now, bytes));
+        }
+        if client_fetches.is_idle() {
+            clients.clients.remove(client);
+        }
+    }
+
+    fn release(&self, client: &str, bytes: u64) {
+        let mut clients = self.clients.lock().unwrap();
+        if let Some(client_fetches) = clients.clients.get_mut(client) {
+            client_fetches.reserved = client_fetches.reserved.saturating_sub(bytes);
+            if client_fetches.is_idle() {
+                clients.clients.remove(client);
+            }
+        }
+    }
+}
+
+impl Reservation<'_> {
+    // what was held back for the request becomes what it fetched
+    pub fn settle(mut self, bytes: u64, now: DateTime<Utc>) {
+        self.quota
+            .release(&self.client, std::mem::take(&mut self.bytes));
+        self.quota.record(&self.client, bytes, now);
+    }
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        if self.bytes > 0 {
+            self.quota.release(&self.client, self.bytes);
+        }
+    }
+}
+
+// the API key when there are keys, else the peer address as forwarded headers can be made up by anyone
+pub fn client(req: &HttpRequest) -> String {
+    if let Some(key) = req.extensions().get::<ApiKey>() {
+        return key.name();
+    }
+    req.peer_addr()
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
@@ -0,0 +1,152 @@
+use crate::cache::Caches;
+use crate::config::Config;
+use crate::cookies::CookieJar;
+use crate::deadline::{Phase, Progress};
+use crate::error::{
+    ApiError, InvalidRedirectError, RedirectBodyTooLargeError, RedirectBudgetError,
+    RedirectLimitError,
+};
+use crate::headers;
+use crate::hosts::validate_url;
+use crate::{check_robots, fetch};
+use actix_http::encoding::Decoder;
+use actix_http::error::PayloadError;
+use actix_http::{Payload, StatusCode};
+use actix_web::http::header::HeaderName;
+use actix_web::rt::time::timeout;
+use awc::{ClientResponse, SendClientRequest};
+use std::future::Future;
+use std::time::{Duration, Instant};
+use url::Url;
+
+pub type UpstreamResponse = ClientResponse<Decoder<Payload>>;
+
+// what the hops of a feed fetch may take between them, rather than each of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RedirectPolicy {
+    // redirects followed, the next one is an error
+    pub limit: u8,
+    // RSSSS_REDIRECT_BUDGET_SECS, from the first request to the head of the last response
+    pub budget: Duration,
+    // read of the body of each redirect and dropped, so its connection can be reused
+    pub hop_bytes: usize,
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> RedirectPolicy {
+        RedirectPolicy {
+            limit: 3,
+            budget: Duration::from_secs(60),
+            hop_bytes: 16_384,
+        }
+    }
+}
+
+// what every hop sends besides its url
+#[derive(Debug, Default)]
+pub struct Hops {
+    // If-None-Match and If-Modified-Since, of a stored copy or else the client's
+    pub conditions: Vec<(HeaderName, String)>,
+    // the conditions are the client's, for its feed's origin and not whatever it points at
+    pub forwarded: bool,
+    // a stored copy is revalidated, a 304 is the answer to it
+    pub revalidating: bool,
+    // the cookies of earlier hops, for this fetch only
+    pub jar: CookieJar,
+}
+
+impl Hops {
+    // a 304 ends the chain when something was asked of it
+    fn answered(&self, status: StatusCode) -> bool {
+        status == StatusCode::NOT_MODIFIED && (self.revalidating || !self.conditions.is_empty())
+    }
+}
+
+// fetches the url and the redirects it leads to, up to the first response that isn't one.
+// every hop is checked as the first, and the chain shares `RedirectPolicy::budget`
+pub async fn follow_redirects(
+    mut url: Url,
+    f: fn(&str, &[(HeaderName, String)]) -> SendClientRequest,
+    hops: &mut Hops,
+    progress: &Progress,
+    config: &Config,
+    caches: &Caches,
+) -> Result<(Url, UpstreamResponse), ApiError> {
+    let policy = &config.upstream.redirects;
+    let started = Instant::now();
+    let mut counter = 0;
+    loop {
+        let hop = async {
+            check_robots(&url, f, config, caches).await?;
+            Ok(fetch(f, &url, &hops.conditions, &mut hops.jar, progress).await?)
+        };
+        let res = within(policy, started, counter, &url, hop).await?;
+        hops.jar.store(&url, res.headers());
+        if !res.status().is_redirection() || hops.answered(res.status()) {
+            return Ok((url, res));
+        }
+        if counter >= policy.limit {
+            return Err(RedirectLimitError {
+                limit: policy.limit,
+            }
+            .into());
+        }
+        // some servers send raw UTF-8 or spaces, joining percent-encodes them
+        let location = headers::location(res.headers())?;
+        let next = match location.as_deref().map(|l| url.join(l.trim())) {
+            Some(Ok(next)) => validate_url(next.as_str(), &config.host_filter)?,
+            _ => return Err(InvalidRedirectError { location }.into()),
+        };
+        drain(res, policy, started, counter, &url).await?;
+        if hops.forwarded && next.origin() != url.origin() {
+            hops.conditions.clear();
+        }
+        url = next;
+        counter += 1;
+        progress.set(Phase::Redirect(counter));
+    }
+}
+
+// the body of a redirect is read to its end before the next hop, a broken one is dropped
+// with its connection
+async fn drain(
+    mut res: UpstreamResponse,
+    policy: &RedirectPolicy,
+    started: Instant,
+    hop: u8,
+    url: &Url,
+) -> Result<(), ApiError> {
+    let body = res.body().limit(policy.hop_bytes);
+    match within(policy, started, hop, url, async { Ok(body.await) }).await? {
+        Err(PayloadError::Overflow) => Err(RedirectBodyTooLargeError {
+            hop,
+            url: url.to_string(),
+            limit: policy.hop_bytes,
+        }
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+// what is left of the budget for a hop, the hop is dropped when it runs out
+async fn within<T, F>(
+    policy: &RedirectPolicy,
+    started: Instant,
+    hop: u8,
+    url: &Url,
+    future: F,
+) -> Result<T, ApiError>
+where
+    F: Future<Output = Result<T, ApiError>>,
+{
+    let remaining = policy.budget.saturating_sub(started.elapsed());
+    match timeout(remaining, future).await {
+        Ok(output) => output,
+        Err(_) => Err(RedirectBudgetError {
+            hop,
+            url: url.to_string(),
+            budget: policy.budget,
+        }
+        .into()),
+    }
+}
@@ -0,0 +1,89 @@
+use crate::error::sanitize;
+use crate::feed_stats::ChannelStats;
+use crate::grouping::DayGroup;
+use crate::rss::{Channel, DroppedItem, Rss};
+use actix_web::http::header::HeaderMap;
+use serde_derive::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+// an allowlist so cookies and anything else private to the origin never reach clients
+const UPSTREAM_HEADERS: [&str; 6] = [
+    "content-type",
+    "content-length",
+    "last-modified",
+    "etag",
+    "cache-control",
+    "server",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiVersion {
+    V1,
+    V2,
+}
+
+// v1 responds with the bare items array, v2 wraps them with the feed level metadata
+#[derive(Debug, Serialize)]
+pub struct FeedEnvelope<'a> {
+    pub url: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_url: Option<String>,
+    pub channel: &'a Channel,
+    // of all the items read, not only those of the response
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_stats: Option<ChannelStats>,
+    // RFC 3339, when the upstream last answered with the items, the Age header counts from it
+    pub fetched_at: String,
+    pub upstream_headers: &'a BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream_protocol: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream_ip: Option<IpAddr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream_ip_family: Option<&'static str>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    pub warnings: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<&'a str>,
+    // more items were read than the window holds
+    pub has_more: bool,
+    // the `continue_from` of the next window
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub continue_from: Option<usize>,
+    // with `debug`, the cookies set by one hop of the fetch and sent with a later one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cookies_replayed: Option<usize>,
+    // the most frequent first, for `debug`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unmapped_elements: Option<Map<String, Value>>,
+    // for `debug`, what was read of the items that were dropped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dropped_items: Option<&'a [DroppedItem]>,
+    #[serde(flatten)]
+    pub items: FeedItems,
+}
+
+#[derive(Debug, Serialize)]
+pub enum FeedItems {
+    #[serde(rename = "items")]
+    Items(Vec<Rss>),
+    #[serde(rename = "groups")]
+    Groups(Vec<DayGroup>),
+}
+
+pub fn upstream_headers(headers: &HeaderMap) -> BTreeMap<String, String> {
+    UPSTREAM_HEADERS
+        .iter()
+        .filter_map(|name| {
+            let value = headers.get(*name)?;
+            Some((
+                name.to_string(),
+                sanitize(&String::from_utf8_lossy(value.as_bytes())),
+            ))
+        })
+        .collect()
+}
@@ -0,0 +1,225 @@
+use crate::error::RobotsDisallowedError;
+use crate::headers;
+use crate::hosts::{validate_url, HostFilter};
+use actix_web::http::header::HeaderName;
+use actix_web::rt::time::timeout;
+use awc::SendClientRequest;
+use log::{info, warn};
+use std::fmt;
+use std::time::Duration;
+use url::Url;
+
+// robots.txt is small, anything past this is ignored as RFC 9309 allows
+pub const SIZE_LIMIT: usize = 65_536;
+const TIMEOUT: Duration = Duration::from_secs(5);
+// RFC 9309 asks for at least five
+const REDIRECT_LIMIT: u8 = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub allow: bool,
+    pub pattern: String,
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let directive = if self.allow { "Allow" } else { "Disallow" };
+        write!(f, "{}: {}", directive, self.pattern)
+    }
+}
+
+// the rules of one host for rssss, from the groups naming it or else the `*` groups
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Robots {
+    url: String,
+    rules: Vec<Rule>,
+}
+
+impl Robots {
+    // for a robots.txt that's missing or couldn't be fetched
+    pub fn allow_all(url: &str) -> Robots {
+        Robots {
+            url: url.to_string(),
+            rules: Vec::new(),
+        }
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    // the longest matching pattern wins, an allow over a disallow as long
+    pub fn matching(&self, path: &str) -> Option<&Rule> {
+        self.rules
+            .iter()
+            .filter(|rule| matches(&rule.pattern, path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+    }
+
+    pub fn check(&self, url: &Url) -> Result<(), RobotsDisallowedError> {
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        if path == "/robots.txt" {
+            return Ok(());
+        }
+        match self.matching(&path) {
+            Some(rule) if !rule.allow => Err(RobotsDisallowedError {
+                robots_url: self.url.clone(),
+                rule: rule.to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+// what the cache keys the rules by, a robots.txt only speaks for its own scheme, host and port
+pub fn origin(url: &Url) -> String {
+    url.origin().ascii_serialization()
+}
+
+// the groups are matched by the product token of the user agent, case-insensitively.
+// comments, unknown lines and rules before any user-agent line are ignored
+pub fn parse(url: &str, body: &str, user_agent: &str) -> Robots {
+    let token = user_agent
+        .split('/')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    let mut groups: Vec<(Vec<String>, Vec<Rule>)> = Vec::new();
+    let mut in_rules = false;
+    for line in body.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let (key, value) = match line.split_once(':') {
+            Some((key, value)) => (key.trim().to_ascii_lowercase(), value.trim()),
+            None => continue,
+        };
+        match key.as_str() {
+            "user-agent" => {
+                // user-agent lines right after each other share the rules that follow
+                if in_rules || groups.is_empty() {
+                    groups.push((Vec::new(), Vec::new()));
+                    in_rules = false;
+                }
+                if let Some((agents, _)) = groups.last_mut() {
+                    agents.push(value.to_ascii_lowercase());
+                }
+            }
+            "allow" | "disallow" => {
+                in_rules = true;
+                // an empty disallow allows everything, which no rule already does
+                if value.is_empty() {
+                    continue;
+                }
+                if let Some((_, rules)) = groups.last_mut() {
+                    rules.push(Rule {
+                        allow: key == "allow",
+                        pattern: value.to_string(),
+                    });
+                }
+            }
+            _ => (),
+        }
+    }
+    let named = |agent: &str| {
+        groups
+            .iter()
+            .filter(|(agents, _)| agents.iter().any(|a| a == agent))
+            .flat_map(|(_, rules)| rules.iter().cloned())
+            .collect::<Vec<Rule>>()
+    };
+    let ours = groups.iter().any(|(agents, _)| agents.contains(&token));
+    Robots {
+        url: url.to_string(),
+        rules: if ours { named(&token) } else { named("*") },
+    }
+}
+
+// `*` matches any run of characters and a trailing `$` anchors the pattern to the end,
+// otherwise a pattern matches the paths it's a prefix of
+pub fn matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern.as_bytes(), true),
+        None => (pattern.as_bytes(), false),
+    };
+    let path = path.as_bytes();
+    let (mut p, mut s) = (0, 0);
+    // where the last star was and how much of the path it has taken
+    let mut star = None;
+    while s < path.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            star = Some((p, s));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == path[s] {
+            p += 1;
+            s += 1;
+        } else if p == pattern.len() && !anchored {
+            return true;
+        } else if let Some((star_p, star_s)) = star {
+            p = star_p + 1;
+            s = star_s + 1;
+            star = Some((star_p, star_s + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|b| *b == b'*')
+}
+
+// the robots.txt of the url's origin. one that's missing, too slow or broken allows everything
+pub async fn fetch(
+    url: &Url,
+    f: fn(&str, &[(HeaderName, String)]) -> SendClientRequest,
+    host_filter: &HostFilter,
+    user_agent: &str,
+) -> Robots {
+    let robots_url = match url.join("/robots.txt") {
+        Ok(robots_url) => robots_url,
+        Err(_) => return Robots::allow_all(url.as_str()),
+    };
+    info!("robots {}", robots_url);
+    match timeout(TIMEOUT, fetch_body(robots_url.clone(), f, host_filter)).await {
+        Ok(Some(body)) => parse(
+            robots_url.as_str(),
+            &String::from_utf8_lossy(&body),
+            user_agent,
+        ),
+        Ok(None) => Robots::allow_all(robots_url.as_str()),
+        Err(_) => {
+            warn!("robots timed out: {}", robots_url);
+            Robots::allow_all(robots_url.as_str())
+        }
+    }
+}
+
+async fn fetch_body(
+    mut url: Url,
+    f: fn(&str, &[(HeaderName, String)]) -> SendClientRequest,
+    host_filter: &HostFilter,
+) -> Option<Vec<u8>> {
+    for _ in 0..=REDIRECT_LIMIT {
+        let mut res = f(url.as_str(), &[]).await.ok()?;
+        if res.status().is_redirection() {
+            let location = headers::location(res.headers()).ok()??;
+            let next = url.join(location.trim()).ok()?;
+            url = validate_url(next.as_str(), host_filter).ok()?;
+            continue;
+        }
+        if !res.status().is_success() {
+            return None;
+        }
+        // cut rather than refused, the rules at the top still count
+        let mut body = Vec::new();
+        while let Some(chunk) = futures::StreamExt::next(&mut res).await {
+            body.extend_from_slice(&chunk.ok()?);
+            if body.len() >= SIZE_LIMIT {
+                body.truncate(SIZE_LIMIT);
+                break;
+            }
+        }
+        return Some(body);
+    }
+    None
+}
@@ -1,110 +1,1764 @@
-use crate::error::{Error, InvalidRssError};
-use actix_web::web::Bytes;
-use log::warn;
+use crate::audio::AudioSources;
+use crate::dates::Plausible;
+use crate::display::display_url;
+use crate::error::{
+    ApiError, ErrorCode, InvalidRssError, ParserDiagnostic, UnsupportedFormatError,
+};
+use crate::escaped;
+use crate::language;
+use crate::watchdog;
+use crate::words;
+use crate::{charset, lenient, markdown, safe_html};
+use actix_web::web::{self, Bytes};
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use futures::channel::mpsc::UnboundedSender;
+use log::{debug, warn};
 use scraper::Html;
-use serde_derive::Serialize;
-use std::collections::VecDeque;
+use serde_derive::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use url::Url;
 use xml::attribute::OwnedAttribute;
+use xml::common::Position;
 use xml::name::OwnedName;
+use xml::namespace::{Namespace, NS_NO_PREFIX};
 use xml::reader::{EventReader, XmlEvent};
 
-#[derive(Debug, Serialize, Clone)]
+/// An item of any of the formats, a `<item>` or an Atom `<entry>`, as the API answers with it.
+///
+/// ```
+/// use rssss::rss::Rss;
+///
+/// let item = Rss::new("  Release notes ", "https://example.com/notes", "<b>All</b> fixed")
+///     .with_pub_date("2024-05-07T09:00:00Z");
+/// assert_eq!(item.title(), "Release notes");
+/// assert_eq!(item.description(), "All fixed");
+/// assert_eq!(item.markup(), "<b>All</b> fixed");
+/// assert_eq!(item.pub_date(), Some("2024-05-07T09:00:00Z"));
+/// assert_eq!(item.word_count(), Some(2));
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Rss {
     title: String,
     description: String,
+    description_truncated: bool,
+    description_full_length: usize,
     link: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    display_link: Option<String>,
+    // <guid>, Atom's <id> or RSS 1.0's rdf:about, what `continue_from` takes of an item
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    guid: Option<String>,
     pub_date: Option<String>,
+    // Atom <updated> or atom:updated, as written, apart from pub_date
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    updated: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    word_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reading_time_minutes: Option<usize>,
+    // only with detect_language, see `language::detect`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    detected_language: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    date_inherited: bool,
+    // a date outside `Plausible`, the item sorts and groups as undated
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    date_suspect: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<ItemSource>,
+    // the names of `authors` joined, or the single author of formats without structure
+    #[serde(skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    authors: Vec<Person>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    contributors: Vec<Person>,
+    // the names of `categories_detailed`, as the flat list always was
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    categories: Vec<String>,
+    // dropped from responses unless include_category_details asks for them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    categories_detailed: Vec<Category>,
+    // wfw:commentRss, the feed of the comments on the item, resolved against its link
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    comment_feed_url: Option<String>,
+    // see `AudioSources::resolve`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    audio_duration_secs: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    audio_length_bytes: Option<u64>,
+    // with `ParseOptions::keep_raw` only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    raw: Option<Box<RawFields>>,
+    // with `ParseOptions::provenance` only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    provenance: Option<Box<Provenance>>,
+    // the description as written, rendered again for formats other than plain
+    #[serde(skip)]
+    markup: String,
+    // what the field limits cut and the descriptions unescaped, until the feed collects it
+    #[serde(skip)]
+    cut: Vec<String>,
+}
+
+// the most bytes kept of an item's fields, so a broken or hostile feed can't blow up the response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldLimits {
+    pub title: usize,
+    pub link: usize,
+    // and updated
+    pub pub_date: usize,
+    pub author: usize,
+    pub category: usize,
+    pub categories: usize,
+    // of a description's HTML read for its text, far more than `Rss::DESCRIPTION_LIMIT` needs.
+    // the markup itself is kept whole for the other formats. RSSSS_MARKUP_LIMIT
+    pub markup: usize,
+}
+
+impl Default for FieldLimits {
+    fn default() -> FieldLimits {
+        FieldLimits {
+            title: 1_024,
+            link: 4_096,
+            pub_date: 128,
+            author: 512,
+            category: 256,
+            categories: 50,
+            markup: 65_536,
+        }
+    }
+}
+
+// an Atom category, or an RSS category with its domain as the scheme
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Category {
+    pub term: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    // the vocabulary the term is of, such as arXiv's subject classes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheme: Option<String>,
+}
+
+impl Category {
+    pub fn new(term: String, label: Option<String>, scheme: Option<String>) -> Category {
+        let filled = |value: Option<String>| value.map(Rss::trim).filter(|v| !v.is_empty());
+        Category {
+            term: Rss::trim(term),
+            label: filled(label),
+            scheme: filled(scheme),
+        }
+    }
+    // the label when there's one, it's what the term is meant to be shown as
+    pub fn name(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.term)
+    }
+    // by the term or the label, regardless of case
+    pub fn matches(&self, value: &str) -> bool {
+        let value = value.trim();
+        self.term.eq_ignore_ascii_case(value)
+            || self
+                .label
+                .as_deref()
+                .is_some_and(|l| l.eq_ignore_ascii_case(value))
+    }
+}
+
+// the fields of an item as the parser read them, before they were trimmed, unescaped and
+// stripped of markup, for debugging what came of them
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct RawFields {
+    title: String,
+    description: String,
+    link: String,
+    pub_date: Option<String>,
+}
+
+impl RawFields {
+    // bytes of each field kept, cut on a char boundary
+    pub const LIMIT: usize = 2_048;
+
+    // None unless the parse keeps them, so an ordinary parse doesn't copy every field
+    fn capture(
+        options: &ParseOptions,
+        title: &str,
+        description: &str,
+        link: &str,
+        pub_date: Option<&str>,
+    ) -> Option<Box<RawFields>> {
+        if !options.keep_raw {
+            return None;
+        }
+        let capped = |field: &str| {
+            let mut end = field.len().min(RawFields::LIMIT);
+            while !field.is_char_boundary(end) {
+                end -= 1;
+            }
+            field[..end].to_string()
+        };
+        Some(Box::new(RawFields {
+            title: capped(title),
+            description: capped(description),
+            link: capped(link),
+            pub_date: pub_date.map(capped),
+        }))
+    }
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+    pub fn link(&self) -> &str {
+        &self.link
+    }
+    pub fn pub_date(&self) -> Option<&str> {
+        self.pub_date.as_deref()
+    }
+}
+
+// the element each field of an item was last assigned from and the precedence rule that let it,
+// like "content:encoded (only without a description)", for debugging feeds with more than one
+// source for a field
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Provenance(BTreeMap<String, String>);
+
+impl Provenance {
+    // None unless the parse records it, so an ordinary parse formats nothing
+    fn of(options: &ParseOptions) -> Option<Provenance> {
+        options.provenance.then(Provenance::default)
+    }
+    pub fn get(&self, field: &str) -> Option<&str> {
+        self.0.get(field).map(String::as_str)
+    }
+}
+
+// how the parsers assign the fields of an item, what it was read from is noted along the way
+fn set_with_provenance<T>(
+    field: &mut T,
+    value: T,
+    provenance: &mut Option<Provenance>,
+    name: &str,
+    element: &OwnedName,
+    rule: &str,
+) {
+    *field = value;
+    if let Some(provenance) = provenance {
+        let source = format!("{} ({})", qname(element), rule);
+        provenance.0.insert(name.to_string(), source);
+    }
+}
+
+// by the prefix the document wrote, like wfw:commentRss
+fn qname(name: &OwnedName) -> String {
+    match &name.prefix {
+        Some(prefix) => format!("{}:{}", prefix, name.local_name),
+        None => name.local_name.clone(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Plain,
+    Markdown,
+    Html,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s {
+            "plain" => Ok(Format::Plain),
+            "markdown" => Ok(Format::Markdown),
+            "html" => Ok(Format::Html),
+            _ => Err(format!("unsupported format: {}", s)),
+        }
+    }
+}
+
+// how forgiving a parse is, the presets of `ParseOptions`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Strictness {
+    Strict,
+    #[default]
+    Default,
+    Lenient,
+}
+
+impl FromStr for Strictness {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Strictness, String> {
+        match s {
+            "strict" => Ok(Strictness::Strict),
+            "default" => Ok(Strictness::Default),
+            "lenient" => Ok(Strictness::Lenient),
+            _ => Err(format!("unsupported strictness: {}", s)),
+        }
+    }
+}
+
+/// What a parse forgives, in one place rather than a flag per behavior.
+///
+/// ```
+/// use rssss::rss::{parse_rss_with, ParseOptions, Strictness};
+///
+/// let document = "<rss version=\"2.0\"><channel><Item><title>shouting</title></Item></channel></rss>";
+/// let feed = parse_rss_with(document, ParseOptions::default()).unwrap();
+/// assert_eq!(feed.items[0].title(), "shouting");
+///
+/// let strict = ParseOptions::of(Strictness::Strict);
+/// assert!(strict.exact_names);
+/// assert!(parse_rss_with(document, strict).unwrap().items.is_empty());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParseOptions {
+    // element and attribute names only as the specs write them, `Item` isn't an item
+    pub exact_names: bool,
+    // the RSS version as written, ` 2.0` isn't 2.0
+    pub exact_version: bool,
+    // any warning about the document fails the parse
+    pub warnings_as_errors: bool,
+    // documents that aren't well-formed fixed up or scanned, see `parse_lenient`
+    pub recover: bool,
+    // see `partial_feed`
+    pub partial: bool,
+    // the items read at most, the rest of the document is skipped with a warning saying so
+    pub max_items: Option<usize>,
+    // the document from its first byte, see `skip_leading` for what is skipped otherwise
+    pub exact_start: bool,
+    // the one parser tried, the whole chain when None
+    pub parser: Option<ParserName>,
+    // the fields of each item as read are kept alongside, see `RawFields`
+    pub keep_raw: bool,
+    // the encoding as declared even when the bytes say otherwise, see `charset::prefer_utf8`
+    pub exact_encoding: bool,
+    // which element each field of an item came from, see `Provenance`
+    pub provenance: bool,
+    // what each item is cut to
+    pub limits: FieldLimits,
+}
+
+impl ParseOptions {
+    pub fn of(strictness: Strictness) -> ParseOptions {
+        match strictness {
+            Strictness::Strict => ParseOptions {
+                exact_names: true,
+                exact_version: true,
+                warnings_as_errors: true,
+                exact_start: true,
+                exact_encoding: true,
+                ..ParseOptions::default()
+            },
+            Strictness::Default => ParseOptions::default(),
+            Strictness::Lenient => ParseOptions {
+                recover: true,
+                partial: true,
+                ..ParseOptions::default()
+            },
+        }
+    }
+
+    // nothing may be streamed before all of the document is known to pass
+    pub fn streamable(&self) -> bool {
+        !self.recover && !self.partial && !self.warnings_as_errors
+    }
+
+    // a feed parsed strictly may have fewer items than the same document parsed otherwise,
+    // and one parsed otherwise may not pass strictly. strict parses are fetched every time
+    // so are parses with a single parser, keeping the raw fields or the provenance, for debugging
+    // a document. a recovered or partial feed is not what a default parse of it gives either,
+    // which is an error
+    pub fn shares_cache(&self) -> bool {
+        !self.recover
+            && !self.partial
+            && !self.exact_names
+            && !self.exact_version
+            && !self.warnings_as_errors
+            && !self.exact_start
+            && !self.exact_encoding
+            && self.parser.is_none()
+            && !self.keep_raw
+            && !self.provenance
+    }
+}
+
+// an Atom author or contributor
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct Person {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+}
+
+impl Person {
+    fn is_empty(&self) -> bool {
+        self.name.is_empty() && self.email.is_none() && self.uri.is_none()
+    }
+}
+
+// the original publisher of an item in an aggregated feed
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ItemSource {
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+// the branding of a feed, an Atom logo, or its icon when there's no logo
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ChannelImage {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+}
+
+impl ChannelImage {
+    // feeds may use urls relative to themselves
+    pub fn resolve(&mut self, base: &Url) {
+        if let Ok(url) = base.join(&self.url) {
+            self.url = url.to_string();
+        }
+        if let Some(link) = self.link.as_mut() {
+            if let Ok(url) = base.join(link) {
+                *link = url.to_string();
+            }
+        }
+    }
+}
+
+// the software that wrote the feed, from the RSS 2.0 <generator> text or the Atom one
+// with its uri and version attributes
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct Generator {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Channel {
+    // the site of the feed, as written
+    pub link: Option<String>,
+    pub ttl: Option<u32>,
+    pub pub_date: Option<String>,
+    pub image: Option<ChannelImage>,
+    // the Atom feed authors, for the entries without their own
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<Person>,
+    // sy:updatePeriod, hourly, daily, weekly, monthly or yearly
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub update_period: Option<String>,
+    // sy:updateFrequency, the updates per period
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub update_frequency: Option<u32>,
+    // <skipHours> and <skipDays>, in GMT
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skip_hours: Vec<u8>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skip_days: Vec<String>,
+    // the RSS 2.0 <atom:link rel="self">, where the feed says it lives
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canonical_url: Option<String>,
+    // set when the feed was requested at another url, to move the subscription over
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub canonical_differs: bool,
+    // <copyright>, the Atom <rights> or the RSS 1.0 dc:rights
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub copyright: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generator: Option<Generator>,
+    // the RSS 2.0 <managingEditor> and <webMaster>, as written when they hold an address
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub managing_editor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_master: Option<String>,
+}
+
+impl Channel {
+    const PERIODS: [(&'static str, u64); 5] = [
+        ("hourly", 3_600),
+        ("daily", 86_400),
+        ("weekly", 604_800),
+        ("monthly", 2_592_000),
+        ("yearly", 31_536_000),
+    ];
+    const DAYS: [&'static str; 7] = [
+        "Monday",
+        "Tuesday",
+        "Wednesday",
+        "Thursday",
+        "Friday",
+        "Saturday",
+        "Sunday",
+    ];
+
+    fn image_mut(&mut self) -> &mut ChannelImage {
+        self.image.get_or_insert_with(ChannelImage::default)
+    }
+
+    fn set_syndication(&mut self, local_name: &str, data: &str) {
+        let data = data.trim();
+        match local_name {
+            "updatePeriod" => {
+                let period = data.to_ascii_lowercase();
+                if Channel::PERIODS.iter().any(|(p, _)| *p == period) {
+                    self.update_period = Some(period);
+                }
+            }
+            "updateFrequency" => self.update_frequency = data.parse().ok().filter(|f| *f > 0),
+            _ => (),
+        }
+    }
+
+    // 24 is written for midnight now and then
+    fn add_skip_hour(&mut self, data: &str) {
+        if let Ok(hour) = data.trim().parse::<u8>() {
+            if hour <= 24 {
+                self.skip_hours.push(hour % 24);
+                self.skip_hours.sort_unstable();
+                self.skip_hours.dedup();
+            }
+        }
+    }
+
+    fn add_skip_day(&mut self, data: &str) {
+        let day = Channel::DAYS
+            .iter()
+            .find(|d| d.eq_ignore_ascii_case(data.trim()));
+        if let Some(day) = day {
+            if !self.skip_days.iter().any(|d| d == day) {
+                self.skip_days.push(day.to_string());
+            }
+        }
+    }
+
+    // the period over the frequency, which default to daily and 1
+    pub fn update_interval(&self) -> Option<Duration> {
+        if self.update_period.is_none() && self.update_frequency.is_none() {
+            return None;
+        }
+        let period = self.update_period.as_deref().unwrap_or("daily");
+        let secs = Channel::PERIODS
+            .iter()
+            .find(|(p, _)| *p == period)
+            .map_or(86_400, |(_, secs)| *secs);
+        Some(Duration::from_secs(
+            secs / u64::from(self.update_frequency.unwrap_or(1)),
+        ))
+    }
+
+    // whether the feed asks not to be read at the hour of `at`
+    pub fn skips(&self, at: DateTime<Utc>) -> bool {
+        let day = Channel::DAYS[at.weekday().num_days_from_monday() as usize];
+        self.skip_hours.contains(&(at.hour() as u8)) || self.skip_days.iter().any(|d| d == day)
+    }
+
+    // resolved against the url the document came from, compared with the one requested
+    pub fn resolve_canonical(&mut self, base: &Url, requested: &Url) {
+        let canonical = match self.canonical_url.as_deref().map(|c| base.join(c.trim())) {
+            Some(Ok(canonical)) => canonical,
+            _ => return,
+        };
+        self.canonical_differs = same_feed_url(&canonical) != same_feed_url(requested);
+        self.canonical_url = Some(canonical.to_string());
+    }
+
+    // an image without a url is of no use, nor is a generator without a name or uri
+    fn complete(self) -> Channel {
+        let mut channel = self;
+        channel.image = channel.image.filter(|image| !image.url.is_empty());
+        channel.generator = channel
+            .generator
+            .filter(|generator| !generator.name.is_empty() || generator.uri.is_some());
+        channel
+    }
+}
+
+/// The channel of a document and its items, in document order.
+///
+/// ```
+/// let feed = rssss::rss::parse_rss(
+///     r#"<feed xmlns="http://www.w3.org/2005/Atom">
+///         <link href="https://example.com/"/><updated>2024-05-07T09:00:00Z</updated>
+///         <author><name>Ann</name></author>
+///         <entry><title>First</title><link href="https://example.com/1"/></entry>
+///         <entry><title>Second</title><link href="https://example.com/2"/></entry>
+///     </feed>"#,
+/// )
+/// .unwrap();
+/// assert_eq!(feed.channel.link.as_deref(), Some("https://example.com/"));
+/// assert_eq!(feed.channel.pub_date.as_deref(), Some("2024-05-07T09:00:00Z"));
+/// let titles = feed.items.iter().map(|i| i.title()).collect::<Vec<_>>();
+/// assert_eq!(titles, ["First", "Second"]);
+/// // the entries without authors of their own have the feed's
+/// assert_eq!(feed.items[1].author(), Some("Ann"));
+/// assert!(feed.warnings.is_empty());
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Feed {
+    pub channel: Channel,
+    pub items: Vec<Rss>,
+    // what was off about the document but could be parsed anyway
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    // how a document that isn't well-formed was read with `lenient`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recovery: Option<Recovery>,
+    // the elements no field was read from, the most frequent first. only kept in memory
+    #[serde(skip)]
+    pub unmapped_elements: Vec<(String, usize)>,
+    // the items kept out of `items`, each with a warning. only kept in memory
+    #[serde(skip)]
+    pub dropped_items: Vec<DroppedItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Recovery {
+    // fixed up and parsed again
+    FixedUp,
+    // too broken for that, only the items were scanned for
+    Scanned,
+    // with `partial`, the items read before the error
+    Partial,
+}
+
+impl Recovery {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Recovery::FixedUp => "fixed_up",
+            Recovery::Scanned => "scanned",
+            Recovery::Partial => "partial",
+        }
+    }
+}
+
+// an item neither a title nor a link could be read from, which readers can't show or open.
+// what was read of it anyway tells where the document broke
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct DroppedItem {
+    // 1-based, of the items in the document, dropped ones included
+    pub ordinal: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pub_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+}
+
+impl DroppedItem {
+    // of each fragment, as much goes in the warning
+    const WARNING_LIMIT: usize = 80;
+
+    // None for an item worth keeping
+    fn of(ordinal: usize, item: &Rss) -> Option<DroppedItem> {
+        if !item.title.is_empty() || !item.link.trim().is_empty() {
+            return None;
+        }
+        Some(DroppedItem {
+            ordinal,
+            description: Some(item.description.clone()).filter(|d| !d.is_empty()),
+            pub_date: item.pub_date.clone(),
+            author: item.author.clone(),
+        })
+    }
+
+    pub fn warning(&self) -> String {
+        let fragments = [
+            ("description", &self.description),
+            ("pub_date", &self.pub_date),
+            ("author", &self.author),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| {
+            let value = value.as_deref()?;
+            let cut = match value.char_indices().nth(DroppedItem::WARNING_LIMIT) {
+                Some((at, _)) => format!("{}...", &value[..at]),
+                None => value.to_string(),
+            };
+            Some(format!("{} {:?}", name, cut))
+        })
+        .collect::<Vec<String>>();
+        let warning = format!(
+            "item {} dropped, neither a title nor a link could be read from it",
+            self.ordinal
+        );
+        if fragments.is_empty() {
+            warning
+        } else {
+            format!("{}; read: {}", warning, fragments.join(", "))
+        }
+    }
+}
+
+// an item goes to the results unless it is dropped
+fn keep(results: &mut Vec<Rss>, dropped: &mut Vec<DroppedItem>, item: Rss) {
+    match DroppedItem::of(results.len() + dropped.len() + 1, &item) {
+        Some(item) => dropped.push(item),
+        None => results.push(item),
+    }
 }
 
 impl Rss {
-    const RDF_NS: &'static str = "http://purl.org/rss/1.0/";
-    const RDF_SYNTAX_NS: &'static str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+    pub(crate) const RDF_NS: &'static str = "http://purl.org/rss/1.0/";
+    pub(crate) const RDF_SYNTAX_NS: &'static str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
     const ELEMENTS_NS: &'static str = "http://purl.org/dc/elements/1.1/";
-    const CONTENT_NS: &'static str = "http://purl.org/rss/1.0/modules/content/";
+    pub(crate) const CONTENT_NS: &'static str = "http://purl.org/rss/1.0/modules/content/";
+    const SYNDICATION_NS: &'static str = "http://purl.org/rss/1.0/modules/syndication/";
 
-    const ATOM_NS: &'static str = "http://www.w3.org/2005/Atom";
+    pub(crate) const ATOM_NS: &'static str = "http://www.w3.org/2005/Atom";
     const MEDIA_NS: &'static str = "http://search.yahoo.com/mrss/";
+    const ITUNES_NS: &'static str = "http://www.itunes.com/dtds/podcast-1.0.dtd";
+    const WFW_NS: &'static str = "http://wellformedweb.org/CommentAPI/";
 
     const DESCRIPTION_LIMIT: usize = 500;
+    // text nodes read of a description, markup within `FieldLimits::markup` seldom has more
+    const TEXT_NODES: usize = 10_000;
+    // elements open at once in a description, the HTML parser slows down with each level
+    const MARKUP_DEPTH: usize = 256;
+    // neither closed by nor counted for what they hold, HTML closes them on the next one anyway
+    const UNNESTED: [&'static str; 18] = [
+        "area", "br", "col", "dd", "dt", "embed", "hr", "img", "input", "li", "option", "p",
+        "param", "td", "th", "tr", "track", "wbr",
+    ];
 
-    fn new(title: String, description: String, link: String, pub_date: Option<String>) -> Rss {
+    // what a parser read of an item but its description, trimmed. see `described`
+    fn parsed(
+        title: String,
+        link: String,
+        pub_date: Option<String>,
+        source: Option<ItemSource>,
+        author: Option<String>,
+        categories: Vec<Category>,
+    ) -> Rss {
         Rss {
             title: Rss::trim(title),
-            description: Rss::trim(Rss::pick_texts(description)),
-            link: link,
-            pub_date: pub_date,
+            description: String::new(),
+            description_truncated: false,
+            description_full_length: 0,
+            display_link: display_url(&link),
+            link,
+            guid: None,
+            pub_date,
+            updated: None,
+            word_count: None,
+            reading_time_minutes: None,
+            detected_language: None,
+            date_inherited: false,
+            date_suspect: false,
+            source: source.map(|s| ItemSource {
+                title: Rss::trim(s.title),
+                url: s.url,
+            }),
+            author: author.map(Rss::trim).filter(|a| !a.is_empty()),
+            authors: Vec::new(),
+            contributors: Vec::new(),
+            categories: Vec::new(),
+            categories_detailed: categories
+                .into_iter()
+                .filter(|c| !c.name().is_empty())
+                .collect(),
+            comment_feed_url: None,
+            audio_duration_secs: None,
+            audio_length_bytes: None,
+            raw: None,
+            provenance: None,
+            markup: String::new(),
+            cut: Vec::new(),
+        }
+    }
+    // the item with its description unescaped, and cut to `FieldLimits` with its other fields
+    fn described(mut self, description: String, limits: &FieldLimits) -> Rss {
+        let (description, passes) = escaped::unescape(description);
+        if passes > 1 {
+            self.cut.push(format!(
+                "description unescaped {} times, the feed escapes it more than once",
+                passes
+            ));
+        }
+        self.set_description_within(description, limits);
+        self.limit_fields(limits);
+        self
+    }
+    // drops control characters and cuts the fields over their limit,
+    // what was cut is also returned. limiting again only reports what has grown since
+    pub fn limit_fields(&mut self, limits: &FieldLimits) -> Vec<String> {
+        let mut cut = Vec::new();
+        limit(&mut self.title, "title", limits.title, &mut cut);
+        limit(&mut self.link, "link", limits.link, &mut cut);
+        if let Some(display_link) = self.display_link.as_mut() {
+            limit(display_link, "display_link", limits.link, &mut cut);
+        }
+        for (name, date) in [
+            ("pub_date", &mut self.pub_date),
+            ("updated", &mut self.updated),
+        ] {
+            if let Some(date) = date.as_mut() {
+                limit(date, name, limits.pub_date, &mut cut);
+            }
+        }
+        if let Some(author) = self.author.as_mut() {
+            limit(author, "author", limits.author, &mut cut);
+        }
+        if let Some(url) = self.comment_feed_url.as_mut() {
+            limit(url, "comment_feed_url", limits.link, &mut cut);
+        }
+        if let Some(guid) = self.guid.as_mut() {
+            limit(guid, "guid", limits.link, &mut cut);
+        }
+        if self.categories_detailed.len() > limits.categories {
+            cut.push(format!(
+                "categories cut to {} of {}",
+                limits.categories,
+                self.categories_detailed.len()
+            ));
+            self.categories_detailed.truncate(limits.categories);
+        }
+        for category in self.categories_detailed.iter_mut() {
+            limit(&mut category.term, "category", limits.category, &mut cut);
+            if let Some(label) = category.label.as_mut() {
+                limit(label, "category label", limits.category, &mut cut);
+            }
+            if let Some(scheme) = category.scheme.as_mut() {
+                limit(scheme, "category scheme", limits.link, &mut cut);
+            }
+        }
+        self.categories = self
+            .categories_detailed
+            .iter()
+            .map(|c| c.name().to_string())
+            .collect();
+        if let Some(source) = self.source.as_mut() {
+            limit(&mut source.title, "source title", limits.title, &mut cut);
+        }
+        self.cut.extend(cut.iter().cloned());
+        cut
+    }
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+    pub fn link(&self) -> &str {
+        &self.link
+    }
+    pub fn guid(&self) -> Option<&str> {
+        self.guid.as_deref()
+    }
+    fn set_guid(&mut self, guid: Option<String>) {
+        self.guid = guid.map(Rss::trim).filter(|g| !g.is_empty());
+    }
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+    pub fn pub_date(&self) -> Option<&str> {
+        self.pub_date.as_deref()
+    }
+    pub fn updated(&self) -> Option<&str> {
+        self.updated.as_deref()
+    }
+    pub fn comment_feed_url(&self) -> Option<&str> {
+        self.comment_feed_url.as_deref()
+    }
+    // as written when the link isn't absolute either
+    fn set_comment_feed_url(&mut self, url: Option<String>) {
+        let url = url.map(|u| u.trim().to_string()).filter(|u| !u.is_empty());
+        self.comment_feed_url = url.map(|url| {
+            Url::parse(self.link.trim())
+                .and_then(|link| link.join(&url))
+                .map_or(url, |resolved| resolved.to_string())
+        });
+    }
+    pub fn audio_duration_secs(&self) -> Option<u32> {
+        self.audio_duration_secs
+    }
+    pub fn audio_length_bytes(&self) -> Option<u64> {
+        self.audio_length_bytes
+    }
+    pub fn raw(&self) -> Option<&RawFields> {
+        self.raw.as_deref()
+    }
+    pub fn provenance(&self) -> Option<&Provenance> {
+        self.provenance.as_deref()
+    }
+    pub fn categories(&self) -> &[Category] {
+        &self.categories_detailed
+    }
+    // whether any category has the term or the label, see `Category::matches`
+    pub fn has_category(&self, value: &str) -> bool {
+        self.categories_detailed.iter().any(|c| c.matches(value))
+    }
+    // the flat names stay, the details are only sent when asked for
+    pub fn drop_category_details(&mut self) {
+        self.categories_detailed.clear();
+    }
+    /// An item with only these fields, as a parser would have read them.
+    pub fn new(
+        title: impl Into<String>,
+        link: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Rss {
+        Rss::parsed(title.into(), link.into(), None, None, None, Vec::new())
+            .described(description.into(), &FieldLimits::default())
+    }
+    pub fn with_pub_date(mut self, pub_date: impl Into<String>) -> Rss {
+        self.pub_date = Some(pub_date.into());
+        self.limit_fields(&FieldLimits::default());
+        self
+    }
+    pub fn display_link(&self) -> Option<&str> {
+        self.display_link.as_deref()
+    }
+    pub fn description_truncated(&self) -> bool {
+        self.description_truncated
+    }
+    pub fn word_count(&self) -> Option<usize> {
+        self.word_count
+    }
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+    pub fn authors(&self) -> &[Person] {
+        &self.authors
+    }
+    pub fn contributors(&self) -> &[Person] {
+        &self.contributors
+    }
+    pub fn source(&self) -> Option<&ItemSource> {
+        self.source.as_ref()
+    }
+    // flags the dates outside the range and shows them as the policy says,
+    // what was flagged is also returned
+    pub fn check_dates(&mut self, plausible: &Plausible) -> Vec<String> {
+        let mut suspect = Vec::new();
+        for (name, date) in [
+            ("pub_date", &mut self.pub_date),
+            ("updated", &mut self.updated),
+        ] {
+            let Some((reason, shown)) = date.as_deref().and_then(|d| plausible.check(d)) else {
+                continue;
+            };
+            suspect.push(format!(
+                "{} {}: {}",
+                name,
+                reason,
+                date.as_deref().unwrap_or("")
+            ));
+            *date = shown;
+        }
+        self.date_suspect |= !suspect.is_empty();
+        suspect
+    }
+    pub fn date_suspect(&self) -> bool {
+        self.date_suspect
+    }
+    // for feeds dating only the channel, flagged since the item may be older
+    pub fn inherit_date(&mut self, date: &str) {
+        if self.pub_date.is_none() {
+            self.pub_date = Some(date.to_string());
+            self.date_inherited = true;
+        }
+    }
+    // the flat author follows, as the names that are given
+    pub fn set_authors(&mut self, authors: Vec<Person>) {
+        let names = authors
+            .iter()
+            .map(|p| p.name.as_str())
+            .filter(|n| !n.is_empty())
+            .collect::<Vec<&str>>();
+        self.author = Some(names.join(", ")).filter(|a| !a.is_empty());
+        self.authors = authors;
+    }
+    pub fn markup(&self) -> &str {
+        &self.markup
+    }
+    pub fn set_markup(&mut self, markup: String) {
+        self.markup = markup;
+    }
+    pub fn set_description(&mut self, description: String) {
+        self.set_description_within(description, &FieldLimits::default());
+    }
+    pub fn set_description_within(&mut self, description: String, limits: &FieldLimits) {
+        let (texts, cut) = Rss::pick_texts(&description, limits.markup);
+        let texts = strip_controls(&texts);
+        self.cut.extend(cut);
+        self.markup = description;
+        let texts = texts.trim();
+        self.word_count = Some(words::count_words(texts)).filter(|c| *c > 0);
+        self.reading_time_minutes = self.word_count.map(words::reading_time_minutes);
+        self.description_full_length = texts.chars().count();
+        match texts.char_indices().nth(Rss::DESCRIPTION_LIMIT) {
+            Some((end, _)) => {
+                self.description = format!("{}...", texts[..end].trim_end());
+                self.description_truncated = true;
+            }
+            None => {
+                self.description = texts.to_string();
+                self.description_truncated = false;
+            }
+        }
+    }
+    // from the title and the plain text description, so before `apply_format`
+    pub fn detect_language(&mut self) {
+        let text = format!("{}\n{}", self.title, self.description);
+        self.detected_language = language::detect(&text).map(str::to_string);
+    }
+    pub fn detected_language(&self) -> Option<&str> {
+        self.detected_language.as_deref()
+    }
+    // the description from the markup in another format, the plain text counts stay
+    pub fn apply_format(&mut self, format: Format) {
+        match format {
+            Format::Plain => (),
+            Format::Markdown => {
+                self.description =
+                    strip_controls(&markdown::to_markdown(&self.markup, Rss::DESCRIPTION_LIMIT))
+            }
+            Format::Html => {
+                self.description = strip_controls(&safe_html::to_safe_html(
+                    &self.markup,
+                    Rss::DESCRIPTION_LIMIT,
+                ))
+            }
         }
     }
     fn trim(s: String) -> String {
         s.trim_start().trim_end().to_string()
     }
-    fn pick_texts(data: String) -> String {
-        let document = Html::parse_document(data.as_ref());
-        let mut texts = String::new();
-        for text in document.root_element().text() {
-            if texts.len() > Rss::DESCRIPTION_LIMIT {
-                break;
+    // the HTML parser is given the first `limit` bytes up to `Rss::MARKUP_DEPTH` levels and
+    // read up to `Rss::TEXT_NODES`, a hostile description of deeply nested elements would take
+    // it long otherwise. what was left unread is returned with the text
+    fn pick_texts(data: &str, limit: usize) -> (Cow<'_, str>, Option<String>) {
+        if let Some(text) = Rss::plain_text(data) {
+            return (text, None);
+        }
+        let mut end = data.len().min(limit);
+        while !data.is_char_boundary(end) {
+            end -= 1;
+        }
+        let nested = Rss::too_deep(&data[..end]);
+        let document = Html::parse_document(&data[..nested.unwrap_or(end)]);
+        // fused, the traversal starts over once done
+        let mut nodes = document.root_element().text().fuse();
+        let text = nodes.by_ref().take(Rss::TEXT_NODES).collect::<String>();
+        let cut = if let Some(at) = nested {
+            Some(format!(
+                "description markup cut to {} of {} bytes before reading its text, its elements nest more than {} levels deep",
+                at,
+                data.len(),
+                Rss::MARKUP_DEPTH
+            ))
+        } else if end < data.len() {
+            Some(format!(
+                "description markup cut to {} of {} bytes before reading its text",
+                end,
+                data.len()
+            ))
+        } else if nodes.next().is_some() {
+            Some(format!(
+                "description text read from its first {} text nodes",
+                Rss::TEXT_NODES
+            ))
+        } else {
+            None
+        };
+        (Cow::Owned(text), cut)
+    }
+    // where the element past `Rss::MARKUP_DEPTH` open ones starts, by the tags alone
+    fn too_deep(markup: &str) -> Option<usize> {
+        let mut depth = 0usize;
+        for (i, _) in markup.match_indices('<') {
+            let tag = &markup[i + 1..];
+            if tag.starts_with('/') {
+                depth = depth.saturating_sub(1);
+                continue;
+            }
+            if !tag.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                continue;
             }
-            let limit = Rss::DESCRIPTION_LIMIT - texts.len();
-            if text.len() > limit {
-                let mut end: usize = 0;
-                text.chars()
-                    .into_iter()
-                    .take(limit)
-                    .for_each(|x| end += x.len_utf8());
-                texts.push_str(&text[..end]);
-                texts.push_str("...")
-            } else {
-                texts.push_str(text)
+            // up to the next `<` at most, so the scan stays linear in broken markup
+            let tag = &tag[..tag.find(['<', '>']).unwrap_or(tag.len())];
+            let name = tag
+                .split(|c: char| c.is_whitespace() || c == '/')
+                .next()
+                .unwrap_or_default();
+            let closed = tag.ends_with('/');
+            if closed || Rss::UNNESTED.iter().any(|u| u.eq_ignore_ascii_case(name)) {
+                continue;
             }
+            depth += 1;
+            if depth > Rss::MARKUP_DEPTH {
+                return Some(i);
+            }
+        }
+        None
+    }
+    // the text an HTML parser would produce for descriptions without markup,
+    // None whenever the parser could read it differently
+    fn plain_text(data: &str) -> Option<Cow<'_, str>> {
+        if data.contains(['<', '\r', '\0', '\u{FEFF}']) {
+            return None;
+        }
+        // leading whitespace is dropped before the document starts
+        let data = data.trim_start_matches(['\t', '\n', '\x0C', ' ']);
+        if !data.contains('&') {
+            return Some(Cow::Borrowed(data));
+        }
+        let mut decoded = String::with_capacity(data.len());
+        let mut rest = data;
+        while let Some(i) = rest.find('&') {
+            decoded.push_str(&rest[..i]);
+            rest = &rest[i + 1..];
+            // a bare ampersand can't start a reference
+            if !rest.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '#') {
+                decoded.push('&');
+                continue;
+            }
+            let (reference, tail) = rest.split_once(';')?;
+            decoded.push(Rss::decode_reference(reference)?);
+            rest = tail;
+        }
+        decoded.push_str(rest);
+        Some(Cow::Owned(decoded))
+    }
+    fn parse_code(digits: &str, radix: u32) -> Option<u32> {
+        if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+            return None;
+        }
+        u32::from_str_radix(digits, radix).ok()
+    }
+    fn decode_reference(reference: &str) -> Option<char> {
+        let c = match reference {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            "nbsp" => '\u{A0}',
+            _ => {
+                let code = match reference.strip_prefix('#')? {
+                    hex if hex.starts_with(['x', 'X']) => Rss::parse_code(&hex[1..], 16)?,
+                    dec => Rss::parse_code(dec, 10)?,
+                };
+                char::from_u32(code)?
+            }
+        };
+        // C1 codes are remapped and other controls are parse errors, the parser knows best
+        if c.is_control() {
+            return None;
+        }
+        Some(c)
+    }
+}
+
+fn strip_controls(s: &str) -> String {
+    s.chars()
+        .filter(|c| !c.is_control() || matches!(c, '\n' | '\t'))
+        .collect()
+}
+
+// on a char boundary, so a cut field is still valid UTF-8
+fn limit(field: &mut String, name: &str, max: usize, cut: &mut Vec<String>) {
+    if field
+        .chars()
+        .any(|c| c.is_control() && !matches!(c, '\n' | '\t'))
+    {
+        *field = strip_controls(field);
+    }
+    if field.len() <= max {
+        return;
+    }
+    let mut end = max;
+    while !field.is_char_boundary(end) {
+        end -= 1;
+    }
+    cut.push(format!("{} cut to {} of {} bytes", name, end, field.len()));
+    field.truncate(end);
+}
+
+// the cuts of each item, numbered as the item warnings are, then the items dropped
+fn collect_cuts(feed: &mut Feed) {
+    for (i, item) in feed.items.iter_mut().enumerate() {
+        for cut in item.cut.drain(..) {
+            feed.warnings.push(format!("item {} {}", i + 1, cut));
         }
-        texts
     }
+    for dropped in &feed.dropped_items {
+        warn!("dropped item {} of the document", dropped.ordinal);
+        feed.warnings.push(dropped.warning());
+    }
+}
+
+// documents from this size on are parsed on the blocking thread pool
+pub const BLOCKING_THRESHOLD: usize = 65_536;
+
+// where the items go as they are parsed, ahead of the feed they end up in
+pub type ItemSink = UnboundedSender<Rss>;
+
+// what turns documents into feeds, `Config::parser`. the seam tests put a slow parser in through
+pub trait FeedParser: fmt::Debug + Send + Sync {
+    // with a sink, each item is also handed to it as soon as it's parsed
+    fn parse(
+        &self,
+        buf: Bytes,
+        options: ParseOptions,
+        sink: Option<&mut dyn FnMut(Rss)>,
+    ) -> Result<Feed, ApiError>;
 }
 
-pub fn parse_rss(buf: Bytes) -> Result<Vec<Rss>, Error<String>> {
-    let mut errors = Vec::new();
-    let result = parse(&buf, &mut RssV20::new());
-    if result.is_ok() {
-        return result;
+#[derive(Debug, Clone, Copy, Default)]
+pub struct XmlParser;
+
+impl FeedParser for XmlParser {
+    fn parse(
+        &self,
+        buf: Bytes,
+        options: ParseOptions,
+        sink: Option<&mut dyn FnMut(Rss)>,
+    ) -> Result<Feed, ApiError> {
+        match sink {
+            Some(sink) => parse_any(buf, Some(sink), options),
+            None => parse_rss_with(buf, options),
+        }
     }
-    let _ = result.map_err(|e| errors.push(e));
+}
+
+// parsing is CPU-bound, large documents would stall the worker and every request queued on it.
+// only what is streamable goes to the sink, a recovery would send the items again
+pub async fn parse_rss_async(
+    buf: Bytes,
+    content_type: Option<&str>,
+    blocking_threshold: usize,
+    options: ParseOptions,
+    sink: Option<ItemSink>,
+) -> Result<Feed, ApiError> {
+    parse_rss_timed(
+        Arc::new(XmlParser),
+        buf,
+        content_type,
+        blocking_threshold,
+        options,
+        sink,
+    )
+    .await
+    .0
+}
 
-    let result = parse(&buf, &mut Atom::new());
-    if result.is_ok() {
-        return result;
+// with the time the parse itself took, the wait for a blocking thread isn't counted
+pub async fn parse_rss_timed(
+    parser: Arc<dyn FeedParser>,
+    buf: Bytes,
+    content_type: Option<&str>,
+    blocking_threshold: usize,
+    options: ParseOptions,
+    sink: Option<ItemSink>,
+) -> (Result<Feed, ApiError>, Duration) {
+    // before lenient recovery too, which would make a mess of JSON
+    if syntax(content_type, &buf) == Syntax::Json {
+        return (Err(json_unsupported().into()), Duration::ZERO);
     }
-    let _ = result.map_err(|e| errors.push(e));
+    let parse = move |buf| {
+        watchdog::timed(|| match sink.filter(|_| options.streamable()) {
+            Some(sink) => parser.parse(
+                buf,
+                options,
+                Some(&mut |item| {
+                    // a receiver gone is a client gone, the feed is still cached
+                    let _ = sink.unbounded_send(item);
+                }),
+            ),
+            None => parser.parse(buf, options, None),
+        })
+    };
+    if buf.len() < blocking_threshold {
+        return parse(buf);
+    }
+    match web::block(move || parse(buf)).await {
+        Ok(parsed) => parsed,
+        Err(e) => (Err(e.into()), Duration::ZERO),
+    }
+}
 
-    let result = parse(&buf, &mut RssV10::new());
-    if result.is_ok() {
-        return result;
+// for documents that aren't well-formed, fixed up and parsed again or else scanned for items.
+// either way the first warning says so. only what is left broken after fixing up is read partially
+fn parse_lenient(buf: Bytes, options: ParseOptions) -> Result<Feed, ApiError> {
+    let whole = ParseOptions {
+        partial: false,
+        ..options
+    };
+    let strict = match parse_any(buf.clone(), None, whole) {
+        Ok(feed) => return Ok(feed),
+        Err(e) => e,
+    };
+    if let Ok(mut feed) = parse_any(lenient::recover(&buf), None, options) {
+        warn!("[lenient] recovered a document that is not well-formed");
+        feed.recovery.get_or_insert(Recovery::FixedUp);
+        feed.warnings.insert(
+            0,
+            "lenient recovery was used: the document is not well-formed XML and was fixed up before parsing".to_string(),
+        );
+        return Ok(feed);
+    }
+    let (mut items, mut dropped) = (Vec::new(), Vec::new());
+    for item in lenient::scan_items(&buf) {
+        if items.len() == options.max_items.unwrap_or(usize::MAX) {
+            break;
+        }
+        let raw = RawFields::capture(
+            &options,
+            &item.title,
+            &item.description,
+            &item.link,
+            item.pub_date.as_deref(),
+        );
+        let mut rss = Rss::parsed(item.title, item.link, item.pub_date, None, None, Vec::new())
+            .described(item.description, &options.limits);
+        rss.raw = raw;
+        keep(&mut items, &mut dropped, rss);
+    }
+    if items.is_empty() {
+        return Err(strict);
     }
-    let _ = result.map_err(|e| errors.push(e));
+    warn!(
+        "[lenient] scanned {} items from a document that could not be parsed",
+        items.len()
+    );
+    let mut feed = Feed {
+        channel: Channel::default(),
+        items,
+        warnings: vec![
+            "lenient recovery was used: the document could not be parsed, only the title, link, description and date of its items were scanned".to_string(),
+        ],
+        recovery: Some(Recovery::Scanned),
+        unmapped_elements: Vec::new(),
+        dropped_items: dropped,
+    };
+    collect_cuts(&mut feed);
+    Ok(feed)
+}
 
-    return Err(errors.into());
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Syntax {
+    Xml,
+    Json,
 }
 
-fn parse(buf: &Bytes, parser: &mut dyn RssParser) -> Result<Vec<Rss>, Error<String>> {
-    let reader = EventReader::new(buf.as_ref());
+// which parsers a document goes to. the first byte decides when it is `<` or `{`,
+// whatever the Content-Type says, and the Content-Type only when it is neither
+pub fn syntax(content_type: Option<&str>, buf: &[u8]) -> Syntax {
+    let head = buf.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(buf);
+    match head.iter().find(|b| !b.is_ascii_whitespace()) {
+        Some(b'<') => return Syntax::Xml,
+        Some(b'{') => return Syntax::Json,
+        _ => {}
+    }
+    let essence = content_type
+        .and_then(|c| c.split(';').next())
+        .map(|c| c.trim().to_ascii_lowercase());
+    match essence.as_deref() {
+        Some("application/json" | "application/feed+json") => Syntax::Json,
+        _ => Syntax::Xml,
+    }
+}
 
-    let mut root = true;
-    for elem in reader {
-        match elem? {
-            XmlEvent::StartDocument { encoding, .. } => {
-                if encoding.to_uppercase() != "UTF-8" {
-                    return Err(InvalidRssError {
-                        message: format!("[{}] unsupported encoding: {}", parser.name(), encoding),
-                    }
-                    .into());
+fn json_unsupported() -> UnsupportedFormatError {
+    UnsupportedFormatError {
+        message: "this looks like JSON, JSON Feed is not supported".to_string(),
+    }
+}
+
+/// RSS 2.0, RSS 1.0 or Atom, whichever the document turns out to be.
+///
+/// ```
+/// let feed = rssss::rss::parse_rss(
+///     r#"<rss version="2.0"><channel><title>Garden</title><item>
+///         <title>Planting garlic</title><link>https://example.com/garlic</link>
+///         <description>&lt;p&gt;Six weeks before the frost.&lt;/p&gt;</description>
+///         <pubDate>Tue, 07 May 2024 09:00:00 GMT</pubDate>
+///     </item></channel></rss>"#,
+/// )
+/// .unwrap();
+/// let item = &feed.items[0];
+/// assert_eq!(item.title(), "Planting garlic");
+/// assert_eq!(item.description(), "Six weeks before the frost.");
+/// assert_eq!(item.pub_date(), Some("Tue, 07 May 2024 09:00:00 GMT"));
+/// ```
+pub fn parse_rss(buf: impl Into<Bytes>) -> Result<Feed, ApiError> {
+    parse_any(buf.into(), None, ParseOptions::default())
+}
+
+/// With `recover`, a document that isn't well-formed is fixed up or scanned before giving up.
+///
+/// ```
+/// use rssss::rss::{parse_rss_with, ParseOptions, Recovery, Strictness};
+///
+/// let broken = "<rss version=\"2.0\"><channel><item><title>Fish &amp; Chips</title>\
+///               <link>https://example.com/fish</link></item><item><title>cut o";
+/// assert!(parse_rss_with(broken, ParseOptions::default()).is_err());
+///
+/// let feed = parse_rss_with(broken, ParseOptions::of(Strictness::Lenient)).unwrap();
+/// assert_eq!(feed.items[0].title(), "Fish & Chips");
+/// assert!(feed.recovery.is_some());
+/// ```
+pub fn parse_rss_with(buf: impl Into<Bytes>, options: ParseOptions) -> Result<Feed, ApiError> {
+    let buf = buf.into();
+    let feed = if options.recover {
+        parse_lenient(buf, options)
+    } else {
+        parse_any(buf, None, options)
+    }?;
+    if options.warnings_as_errors && !feed.warnings.is_empty() {
+        let errors = feed
+            .warnings
+            .into_iter()
+            .map(|warning| {
+                InvalidRssError {
+                    code: ErrorCode::StrictModeWarning,
+                    message: format!("[strict] {}", warning),
                 }
+                .into()
+            })
+            .collect::<Vec<ApiError>>();
+        return Err(errors.into());
+    }
+    Ok(feed)
+}
+
+// each item is also handed to `sink` as soon as it's parsed. only the parser that takes
+// the root gets to any items, so a failure after some were handed over is still a failure
+pub fn parse_rss_into(buf: impl Into<Bytes>, sink: &mut dyn FnMut(Rss)) -> Result<Feed, ApiError> {
+    parse_any(buf.into(), Some(sink), ParseOptions::default())
+}
+
+fn parse_any(
+    buf: Bytes,
+    sink: Option<&mut dyn FnMut(Rss)>,
+    options: ParseOptions,
+) -> Result<Feed, ApiError> {
+    if syntax(None, &buf) == Syntax::Json {
+        return Err(json_unsupported().into());
+    }
+    let (buf, skipped) = if options.exact_start {
+        (buf, 0)
+    } else {
+        skip_leading(buf)
+    };
+    let (buf, mislabeled) = if options.exact_encoding {
+        (buf, None)
+    } else {
+        charset::prefer_utf8(buf)
+    };
+    let mut feed = parse_document(buf, sink, options)?;
+    if let Some(declared) = mislabeled {
+        warn!("read a document declared {} as UTF-8", declared);
+        feed.warnings.push(format!(
+            "declared {} but content is valid UTF-8; using UTF-8",
+            declared
+        ));
+    }
+    if skipped > 0 {
+        warn!("skipped {} bytes before the document", skipped);
+        feed.warnings.push(format!(
+            "skipped {} bytes of leading garbage before XML",
+            skipped
+        ));
+    }
+    Ok(feed)
+}
+
+// how far into the document its XML declaration is looked for
+const LEADING_LIMIT: usize = 16_384;
+
+// sloppy generators put a BOM, blank lines or PHP notices before the document, which xml-rs
+// takes for text outside the root. the document starts at its XML declaration, or at the first
+// `<` without one. the bytes skipped past the BOM and the leading whitespace are counted
+fn skip_leading(buf: Bytes) -> (Bytes, usize) {
+    let bom = if buf.starts_with(b"\xEF\xBB\xBF") {
+        3
+    } else {
+        0
+    };
+    let text = bom
+        + buf[bom..]
+            .iter()
+            .take_while(|b| b.is_ascii_whitespace())
+            .count();
+    let head = &buf[text..buf.len().min(text + LEADING_LIMIT)];
+    let declaration = head
+        .windows(6)
+        .position(|w| w.starts_with(b"<?xml") && w[5].is_ascii_whitespace())
+        // unless it's in the text of a document that has none
+        .filter(|at| {
+            [&b"<rss"[..], b"<feed", b"<rdf:RDF"]
+                .iter()
+                .all(|root| !head[..*at].windows(root.len()).any(|w| w == *root))
+        });
+    match declaration.or_else(|| head.iter().position(|b| *b == b'<')) {
+        Some(at) => (buf.slice(text + at..), at),
+        // nothing like XML, the parsers can say so
+        None => (buf.slice(text..), 0),
+    }
+}
+
+// a parser of the chain, the items are copied out to the sink as they are read
+type ParseFn =
+    fn(&Bytes, ParseOptions, Option<&mut (dyn FnMut(Rss) + '_)>) -> Result<Feed, Box<Failure>>;
+
+// the parsers in the order the chain tries them, by the name `parser` picks one with
+const PARSERS: [(&str, ParseFn); 3] = [
+    ("rss2", |buf, options, sink| {
+        parse(buf, RssV20::new(options), options.max_items, sink)
+    }),
+    ("atom", |buf, options, sink| {
+        parse(buf, Atom::new(options), options.max_items, sink)
+    }),
+    ("rss1", |buf, options, sink| {
+        parse(buf, RssV10::new(options), options.max_items, sink)
+    }),
+];
+
+// one of `PARSERS`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParserName(&'static str);
+
+impl ParserName {
+    pub fn names() -> Vec<&'static str> {
+        PARSERS.iter().map(|(name, _)| *name).collect()
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+
+impl FromStr for ParserName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ParserName, String> {
+        match PARSERS.iter().find(|(name, _)| *name == s) {
+            Some((name, _)) => Ok(ParserName(name)),
+            None => Err(format!(
+                "unexpected value: {}, one of {}",
+                s,
+                ParserName::names().join(", ")
+            )),
+        }
+    }
+}
+
+fn parse_document(
+    buf: Bytes,
+    mut sink: Option<&mut dyn FnMut(Rss)>,
+    options: ParseOptions,
+) -> Result<Feed, ApiError> {
+    let mut failures = Vec::new();
+    let parsers = PARSERS
+        .iter()
+        .filter(|(name, _)| options.parser.is_none_or(|p| p.0 == *name));
+    for (_, parse) in parsers {
+        match parse(&buf, options, sink.as_deref_mut()) {
+            Ok(feed) => return Ok(feed),
+            Err(failure) => failures.push(failure),
+        }
+    }
+
+    let parsers = failures
+        .iter()
+        .map(|f| f.diagnostic.clone())
+        .collect::<Vec<ParserDiagnostic>>();
+    if options.partial {
+        // the parser that got the furthest, reversed so the first of those as far wins
+        let best = (0..failures.len())
+            .rev()
+            .max_by_key(|i| failures[*i].partial.items.len());
+        if let Some(i) = best.filter(|i| !failures[*i].partial.items.is_empty()) {
+            let failure = *failures.swap_remove(i);
+            let d = &failure.diagnostic;
+            warn!(
+                "[{}] returned {} items read before line {}",
+                d.parser, d.items_recovered, d.failed_at_line
+            );
+            let mut feed = failure.partial;
+            feed.recovery = Some(Recovery::Partial);
+            feed.warnings.insert(
+                0,
+                format!(
+                    "partial results: the document could not be parsed, these are the {} items the {} parser read before failing at line {}: {}",
+                    d.items_recovered, d.parser, d.failed_at_line, d.error
+                ),
+            );
+            return Ok(feed);
+        }
+    }
+    // a pinned parser answers with its own errors rather than a guess at what the document is
+    let guess = unsupported_format(&buf).filter(|_| options.parser.is_none());
+    let error: ApiError = match guess {
+        Some(e) => e.into(),
+        None => failures
+            .into_iter()
+            .map(|f| f.error)
+            .collect::<Vec<ApiError>>()
+            .into(),
+    };
+    if matches!(error, ApiError::Parse(_)) {
+        error.record();
+    }
+    Err(error.with_parsers(parsers))
+}
+
+const RSS090_NS: &str = "http://my.netscape.com/rdf/simple/0.9/";
+const SOAP_NS: [&str; 2] = [
+    "http://schemas.xmlsoap.org/soap/envelope/",
+    "http://www.w3.org/2003/05/soap-envelope",
+];
+
+// formats mistaken for feeds, told apart by the root alone so the error says what the url serves
+fn unsupported_format(buf: &[u8]) -> Option<UnsupportedFormatError> {
+    let head = String::from_utf8_lossy(&buf[..buf.len().min(1_024)]);
+    let head = head
+        .trim_start_matches('\u{FEFF}')
+        .trim_start()
+        .to_ascii_lowercase();
+    // HTML is seldom well-formed enough to get to its root
+    if head.starts_with("<!doctype html") || head.starts_with("<html") {
+        return Some(UnsupportedFormatError {
+            message: "this looks like an HTML page, not a feed".to_string(),
+        });
+    }
+    let (name, namespace) = EventReader::new(buf).into_iter().find_map(|e| match e {
+        Ok(XmlEvent::StartElement {
+            name, namespace, ..
+        }) => Some(Some((name, namespace))),
+        Ok(_) => None,
+        Err(_) => Some(None),
+    })??;
+    let message = match name.local_name.to_ascii_lowercase().as_str() {
+        "rdf" if namespace.get(NS_NO_PREFIX) == Some(RSS090_NS) => "RSS 0.90 is not supported",
+        "opml" => "this looks like OPML, a list of feeds, not a feed",
+        "html" => "this looks like an HTML page, not a feed",
+        "envelope" if name.namespace_ref().is_some_and(|ns| SOAP_NS.contains(&ns)) => {
+            "this looks like a SOAP response, not a feed"
+        }
+        _ => return None,
+    };
+    Some(UnsupportedFormatError {
+        message: message.to_string(),
+    })
+}
+
+// items are only copied out for a sink, the feed takes them over from the parser at the end
+// a parser that gave up, with what it had read by then
+struct Failure {
+    error: ApiError,
+    partial: Feed,
+    diagnostic: ParserDiagnostic,
+}
+
+fn parse<P: RssParser>(
+    buf: &Bytes,
+    mut parser: P,
+    max_items: Option<usize>,
+    sink: Option<&mut (dyn FnMut(Rss) + '_)>,
+) -> Result<Feed, Box<Failure>> {
+    let mut unmapped = Unmapped::default();
+    let read = read(buf, &mut parser, &mut unmapped, max_items, sink);
+    let name = parser.name();
+    let mut feed = parser.into_results();
+    collect_cuts(&mut feed);
+    if let (Ok(true), Some(max)) = (&read, max_items) {
+        warn!("[{}] stopped reading after {} items", name, max);
+        feed.items.truncate(max);
+        feed.warnings.push(format!(
+            "only the first {} items were read, the rest of the feed was skipped",
+            max
+        ));
+    }
+    if let Err((error, line)) = read {
+        let diagnostic = ParserDiagnostic {
+            parser: name,
+            code: error
+                .codes()
+                .first()
+                .copied()
+                .unwrap_or(ErrorCode::XmlParseError),
+            error: error.messages().join("; "),
+            items_recovered: feed.items.len(),
+            failed_at_line: line,
+        };
+        return Err(Box::new(Failure {
+            error,
+            partial: feed,
+            diagnostic,
+        }));
+    }
+    feed.unmapped_elements = unmapped.top();
+    if !feed.items.is_empty()
+        && feed.channel.pub_date.is_some()
+        && feed.items.iter().all(|item| item.pub_date.is_none())
+    {
+        warn!("[{}] only the channel has a date", name);
+    }
+    Ok(feed)
+}
+
+// the events of the document through the parser, up to the end or the line of an error.
+// true when it stopped at `max_items` instead
+fn read<P: RssParser>(
+    buf: &Bytes,
+    parser: &mut P,
+    unmapped: &mut Unmapped,
+    max_items: Option<usize>,
+    mut sink: Option<&mut (dyn FnMut(Rss) + '_)>,
+) -> Result<bool, (ApiError, u64)> {
+    let mut reader = EventReader::new(buf.as_ref());
+    let mut root = true;
+    let mut sent = 0;
+    loop {
+        let event = match reader.next() {
+            Ok(event) => event,
+            Err(e) => {
+                let line = e.position().row + 1;
+                return Err((e.into(), line));
             }
+        };
+        let line = reader.position().row + 1;
+        match event {
+            XmlEvent::StartDocument { encoding, .. } if encoding.to_uppercase() != "UTF-8" => {
+                let error = InvalidRssError {
+                    code: ErrorCode::UnsupportedEncoding,
+                    message: format!("[{}] unsupported encoding: {}", parser.name(), encoding),
+                };
+                return Err((error.into(), line));
+            }
+            // a UTF-8 document goes on
+            XmlEvent::StartDocument { .. } => (),
             XmlEvent::StartElement {
-                name, attributes, ..
+                name,
+                attributes,
+                namespace,
             } => {
+                if !parser.maps(&name) {
+                    unmapped.count(&name);
+                }
                 parser.parse_start_element(name, attributes);
                 if root {
-                    parser.verify_rss()?;
+                    parser.verify_rss(&namespace).map_err(|e| (e, line))?;
                     root = false;
                 }
             }
@@ -113,97 +1767,527 @@ fn parse(buf: &Bytes, parser: &mut dyn RssParser) -> Result<Vec<Rss>, Error<Stri
             }
             XmlEvent::EndElement { name } => {
                 parser.parse_end_element(name);
+                // the item past the limit tells there are more, it is neither sent nor kept
+                if max_items.is_some_and(|max| parser.item_count() > max) {
+                    return Ok(true);
+                }
+                if let Some(sink) = sink.as_deref_mut() {
+                    while let Some(item) = parser.item(sent) {
+                        sink(item);
+                        sent += 1;
+                    }
+                }
             }
+            XmlEvent::EndDocument => return Ok(false),
             _ => (),
         };
     }
-    Ok(parser.get_results())
+}
+
+// the elements the parser had no use for, to tell which extensions feeds carry.
+// a hostile document could make up any number of names, so only so many are counted
+#[derive(Default)]
+struct Unmapped {
+    counts: HashMap<String, usize>,
+}
+
+impl Unmapped {
+    const DISTINCT: usize = 200;
+    const TOP: usize = 20;
+
+    fn count(&mut self, name: &OwnedName) {
+        let key = qname(name);
+        let full = self.counts.len() >= Unmapped::DISTINCT;
+        match self.counts.get_mut(&key) {
+            Some(count) => *count += 1,
+            None if !full => {
+                self.counts.insert(key, 1);
+            }
+            None => (),
+        }
+    }
+
+    fn top(self) -> Vec<(String, usize)> {
+        let mut counts = self.counts.into_iter().collect::<Vec<(String, usize)>>();
+        counts.sort_by(|(a, a_count), (b, b_count)| {
+            (Reverse(a_count), a).cmp(&(Reverse(b_count), b))
+        });
+        counts.truncate(Unmapped::TOP);
+        counts
+    }
+}
+
+// http and https or a trailing slash don't make another feed
+fn same_feed_url(url: &Url) -> (Option<&str>, Option<u16>, &str, Option<&str>) {
+    (
+        url.host_str(),
+        url.port(),
+        url.path().trim_end_matches('/'),
+        url.query(),
+    )
+}
+
+// broken feeds repeat elements, the first non-empty value wins rather than the last
+fn set_first_opt(field: &mut Option<String>, value: String) {
+    if field.as_deref().is_none_or(|f| f.trim().is_empty()) {
+        *field = Some(value);
+    }
+}
+
+// the warnings about elements counted while parsing
+fn count_warnings(channels: usize, mut warnings: Vec<String>) -> Vec<String> {
+    if channels > 1 {
+        warnings.insert(
+            0,
+            format!("document contains {} channel elements", channels),
+        );
+    }
+    warnings
+}
+
+// same as comparing `name.to_string()` without formatting the name
+// meant to be "editor@example.com (Jane Doe)", anything with an address in it goes
+fn contact(data: &str) -> Option<String> {
+    let data = data.trim();
+    let address = data
+        .split_whitespace()
+        .map(|w| w.trim_matches(|c| matches!(c, '<' | '>' | '(' | ')' | ',')))
+        .find(|w| w.contains('@'))?;
+    let (local, domain) = address.split_once('@')?;
+    let valid = !local.is_empty()
+        && !domain.contains('@')
+        && domain
+            .split('.')
+            .all(|label| !label.is_empty() && !label.contains(['/', ':']))
+        && domain.contains('.');
+    valid.then(|| data.to_string())
+}
+
+fn attribute(attrs: &[OwnedAttribute], local_name: &str) -> Option<String> {
+    attrs
+        .iter()
+        .find(|a| is_unqualified(&a.name, local_name))
+        .map(|a| a.value.clone())
+}
+
+fn is_unqualified(name: &OwnedName, local_name: &str) -> bool {
+    name.namespace.is_none() && name.prefix.is_none() && name.local_name == local_name
 }
 
 trait RssParser {
-    fn name(&self) -> &str;
+    fn name(&self) -> &'static str;
     fn parse_start_element(&mut self, _: OwnedName, _: Vec<OwnedAttribute>);
     fn parse_content(&mut self, _: String);
     fn parse_end_element(&mut self, _: OwnedName);
-    fn verify_rss(&self) -> Result<(), Error<String>>;
-    fn get_results(&self) -> Vec<Rss>;
+    fn verify_rss(&self, _: &Namespace) -> Result<(), ApiError>;
+    // whether anything is read from the element or from the elements it holds
+    fn maps(&self, _: &OwnedName) -> bool;
+    // the parser is done, what it holds is moved into the feed
+    fn into_results(self) -> Feed;
+    // the item at the index once the parser is done with it, for streaming
+    fn item(&self, index: usize) -> Option<Rss>;
+    // the items the parser is done with
+    fn item_count(&self) -> usize;
 }
 
 struct RssV20 {
     results: Vec<Rss>,
+    channel: Channel,
     elements: VecDeque<(OwnedName, Vec<OwnedAttribute>)>,
     title: String,
     link: String,
     description: String,
     pub_date: Option<String>,
+    updated: Option<String>,
+    source: Option<ItemSource>,
+    author: Option<String>,
+    categories: Vec<Category>,
+    guid: Option<String>,
+    comment_feed_url: Option<String>,
+    audio: AudioSources,
+    // channel and item link elements seen, for what broken feeds repeat
+    channels: usize,
+    links: usize,
+    warnings: Vec<String>,
+    dropped: Vec<DroppedItem>,
+    // of the item being read
+    provenance: Option<Provenance>,
+    options: ParseOptions,
 }
 
 impl RssV20 {
-    fn new() -> RssV20 {
+    fn new(options: ParseOptions) -> RssV20 {
         RssV20 {
             results: Vec::new(),
+            channel: Channel::default(),
             elements: VecDeque::default(),
             title: String::new(),
             link: String::new(),
             description: String::new(),
             pub_date: Option::default(),
+            updated: Option::default(),
+            source: Option::default(),
+            author: Option::default(),
+            categories: Vec::new(),
+            guid: None,
+            comment_feed_url: None,
+            audio: AudioSources::default(),
+            channels: 0,
+            links: 0,
+            warnings: Vec::new(),
+            dropped: Vec::new(),
+            provenance: Provenance::of(&options),
+            options,
+        }
+    }
+    const ELEMENTS: [&'static str; 25] = [
+        "rss",
+        "channel",
+        "item",
+        "title",
+        "link",
+        "description",
+        "encoded",
+        "pubDate",
+        "lastBuildDate",
+        "ttl",
+        "source",
+        "image",
+        "url",
+        "author",
+        "category",
+        "enclosure",
+        "skipHours",
+        "hour",
+        "skipDays",
+        "day",
+        "copyright",
+        "generator",
+        "managingEditor",
+        "webMaster",
+        "guid",
+    ];
+
+    // RSS is case-sensitive by spec, but real-world feeds aren't. only `exact_names` holds them to it
+    fn local_name<'a>(&self, name: &'a OwnedName) -> &'a str {
+        let local_name = name.local_name.as_str();
+        if self.options.exact_names {
+            return local_name;
+        }
+        match RssV20::ELEMENTS
+            .iter()
+            .find(|e| e.eq_ignore_ascii_case(local_name))
+        {
+            Some(e) => {
+                if *e != local_name {
+                    debug!("[RSS V2] matched element {} as {}", local_name, e);
+                }
+                e
+            }
+            None => local_name,
         }
     }
-    fn is_item(name: &OwnedName) -> bool {
-        name.to_string() == "item"
+    fn is_element(&self, name: &OwnedName, local_name: &str) -> bool {
+        name.namespace.is_none() && name.prefix.is_none() && self.local_name(name) == local_name
+    }
+    fn is_channel(&self, name: &OwnedName) -> bool {
+        self.is_element(name, "channel")
+    }
+    fn is_item(&self, name: &OwnedName) -> bool {
+        self.is_element(name, "item")
+    }
+    fn is_named(&self, name: &str, expected: &str) -> bool {
+        if self.options.exact_names {
+            name == expected
+        } else {
+            name.eq_ignore_ascii_case(expected)
+        }
     }
 }
 
 impl RssParser for RssV20 {
-    fn name(&self) -> &str {
-        return "RSS V2";
+    fn name(&self) -> &'static str {
+        "RSS V2"
     }
     fn parse_start_element(&mut self, name: OwnedName, attrs: Vec<OwnedAttribute>) {
+        let in_item = matches!(self.elements.front(), Some((parent, _)) if self.is_item(parent));
+        if self.is_channel(&name) {
+            self.channels += 1;
+        }
+        if in_item && self.is_element(&name, "link") {
+            self.links += 1;
+        }
+        let in_channel =
+            matches!(self.elements.front(), Some((parent, _)) if self.is_channel(parent));
+        if in_channel
+            && self.channels == 1
+            && name.namespace_ref() == Some(Rss::ATOM_NS)
+            && name.local_name == "link"
+            && attrs
+                .iter()
+                .any(|a| is_unqualified(&a.name, "rel") && a.value.trim() == "self")
+        {
+            if let Some(href) = attrs.iter().find(|a| is_unqualified(&a.name, "href")) {
+                if self.channel.canonical_url.is_none() {
+                    self.channel.canonical_url = Some(href.value.trim().to_string());
+                }
+            }
+        }
+        if in_item && self.is_element(&name, "enclosure") && self.audio.enclosure_length.is_none() {
+            self.audio.enclosure_length = attribute(&attrs, "length");
+            self.audio.enclosure_type = attribute(&attrs, "type");
+        }
+        // item > media:content or item > media:group > media:content, the first one
+        if name.namespace_ref() == Some(Rss::MEDIA_NS)
+            && name.local_name == "content"
+            && self.audio.media_duration.is_none()
+            && self.audio.media_file_size.is_none()
+            && self.elements.iter().take(2).any(|(e, _)| self.is_item(e))
+        {
+            self.audio.media_duration = attribute(&attrs, "duration");
+            self.audio.media_file_size = attribute(&attrs, "fileSize");
+            self.audio.media_type = attribute(&attrs, "type");
+            self.audio.media_medium = attribute(&attrs, "medium");
+        }
+        if in_item && self.is_element(&name, "source") {
+            self.source = Some(ItemSource {
+                title: String::new(),
+                url: attrs
+                    .iter()
+                    .find(|a| is_unqualified(&a.name, "url"))
+                    .map(|a| a.value.clone()),
+            });
+        }
         self.elements.push_front((name, attrs));
     }
     fn parse_content(&mut self, data: String) {
-        if &self.elements.len() < &2 {
+        if self.elements.len() < 2 {
             return;
         }
         let (parent, _) = &self.elements[1];
-        if !RssV20::is_item(parent) {
+        // channel > image > url, title and link
+        if self.is_element(parent, "image")
+            && self.channels == 1
+            && self
+                .elements
+                .get(2)
+                .is_some_and(|(grandparent, _)| self.is_channel(grandparent))
+        {
+            let (name, _) = &self.elements[0];
+            match (name.namespace_ref(), self.local_name(name)) {
+                (None, "url") => self.channel.image_mut().url = data.trim().to_string(),
+                (None, "title") => self.channel.image_mut().title = Some(data),
+                (None, "link") => self.channel.image_mut().link = Some(data.trim().to_string()),
+                _ => (),
+            }
             return;
         }
-        let (name, _) = &self.elements[0];
-        match (name.namespace_ref(), name.local_name.as_str()) {
-            (_, "title") => self.title = data,
-            (_, "link") => self.link = data,
-            (_, "description") => self.description = data,
-            (Some(Rss::CONTENT_NS), "encoded") => {
-                if self.description.is_empty() {
-                    self.description = data;
+        // channel > skipHours > hour and channel > skipDays > day
+        if self.channels == 1
+            && self
+                .elements
+                .get(2)
+                .is_some_and(|(grandparent, _)| self.is_channel(grandparent))
+        {
+            let (name, _) = &self.elements[0];
+            if self.is_element(parent, "skipHours") && self.is_element(name, "hour") {
+                self.channel.add_skip_hour(&data);
+            }
+            if self.is_element(parent, "skipDays") && self.is_element(name, "day") {
+                self.channel.add_skip_day(&data);
+            }
+        }
+        if self.is_channel(parent) {
+            // the metadata of the first channel, the items of all
+            if self.channels > 1 {
+                return;
+            }
+            let (name, _) = &self.elements[0];
+            match (name.namespace_ref(), self.local_name(name)) {
+                (None, "link") => set_first_opt(&mut self.channel.link, data.trim().to_string()),
+                (_, "ttl") => self.channel.ttl = data.trim().parse().ok(),
+                (Some(Rss::SYNDICATION_NS), local_name) => {
+                    self.channel.set_syndication(local_name, &data)
+                }
+                (None, "pubDate") => self.channel.pub_date = Some(data),
+                (None, "lastBuildDate") | (Some(Rss::ELEMENTS_NS), "date")
+                    if self.channel.pub_date.is_none() =>
+                {
+                    self.channel.pub_date = Some(data);
+                }
+                (None, "copyright") => self.channel.copyright = Some(data.trim().to_string()),
+                (None, "generator") => {
+                    self.channel.generator = Some(Generator {
+                        name: data.trim().to_string(),
+                        ..Generator::default()
+                    });
+                }
+                (None, local_name @ ("managingEditor" | "webMaster")) => match contact(&data) {
+                    Some(contact) if local_name == "webMaster" => {
+                        self.channel.web_master = Some(contact)
+                    }
+                    Some(contact) => self.channel.managing_editor = Some(contact),
+                    None => self.warnings.push(format!(
+                        "{} is not an email address: {}",
+                        local_name,
+                        data.trim()
+                    )),
+                },
+                _ => (),
+            }
+            return;
+        }
+        if !self.is_item(parent) {
+            return;
+        }
+        let (name, attrs) = &self.elements[0];
+        let local_name = self.local_name(name);
+        let provenance = &mut self.provenance;
+        match (name.namespace_ref(), local_name) {
+            (_, "title") => set_with_provenance(
+                &mut self.title,
+                data,
+                provenance,
+                "title",
+                name,
+                "the last one",
+            ),
+            (_, "link") if self.link.trim().is_empty() => set_with_provenance(
+                &mut self.link,
+                data,
+                provenance,
+                "link",
+                name,
+                "the first non-empty one",
+            ),
+            (_, "description") => set_with_provenance(
+                &mut self.description,
+                data,
+                provenance,
+                "description",
+                name,
+                "wins over content:encoded",
+            ),
+            (Some(Rss::CONTENT_NS), "encoded") if self.description.is_empty() => {
+                set_with_provenance(
+                    &mut self.description,
+                    data,
+                    provenance,
+                    "description",
+                    name,
+                    "only without a description",
+                );
+            }
+            // the description wins, content:encoded isn't read as anything else
+            (Some(Rss::CONTENT_NS), "encoded") => (),
+            (_, "pubDate") => set_with_provenance(
+                &mut self.pub_date,
+                Some(data),
+                provenance,
+                "pub_date",
+                name,
+                "wins over dc:date",
+            ),
+            // WordPress and friends date items with dc:date only, pubDate wins when both are there
+            (Some(Rss::ELEMENTS_NS), "date") if self.pub_date.is_none() => {
+                set_with_provenance(
+                    &mut self.pub_date,
+                    Some(data),
+                    provenance,
+                    "pub_date",
+                    name,
+                    "only without a pubDate",
+                );
+            }
+            (Some(Rss::ATOM_NS), "updated") => set_with_provenance(
+                &mut self.updated,
+                Some(data),
+                provenance,
+                "updated",
+                name,
+                "the last one",
+            ),
+            (None, "source") => {
+                if let Some(source) = self.source.as_mut() {
+                    source.title = data;
                 }
             }
-            (_, "pubDate") => self.pub_date = Some(data),
+            // <author> is meant to be an email address, dc:creator is usually the name
+            (Some(Rss::ELEMENTS_NS), "creator") => set_with_provenance(
+                &mut self.author,
+                Some(data),
+                provenance,
+                "author",
+                name,
+                "wins over author",
+            ),
+            (None, "author") if self.author.is_none() => set_with_provenance(
+                &mut self.author,
+                Some(data),
+                provenance,
+                "author",
+                name,
+                "only without a dc:creator",
+            ),
+            // the domain names the taxonomy, as Atom's scheme does
+            (None, "category") => {
+                let domain = attribute(attrs, "domain");
+                self.categories.push(Category::new(data, None, domain));
+            }
+            (Some(Rss::ELEMENTS_NS), "subject") => {
+                self.categories.push(Category::new(data, None, None))
+            }
+            (Some(Rss::ITUNES_NS), "duration") => self.audio.itunes_duration = Some(data),
+            (Some(Rss::WFW_NS), "commentRss") => self.comment_feed_url = Some(data),
+            (None, "guid") => self.guid = Some(data),
             _ => (),
         }
     }
     fn parse_end_element(&mut self, name: OwnedName) {
-        if RssV20::is_item(&name) {
-            let rss = Rss::new(
-                self.title.clone(),
-                self.description.clone(),
-                self.link.clone(),
-                self.pub_date.clone(),
+        if self.is_item(&name) {
+            let raw = RawFields::capture(
+                &self.options,
+                &self.title,
+                &self.description,
+                &self.link,
+                self.pub_date.as_deref(),
             );
-            self.results.push(rss);
-
-            self.title = String::new();
-            self.link = String::new();
-            self.description = String::new();
-            self.pub_date = Option::default();
+            let mut rss = Rss::parsed(
+                std::mem::take(&mut self.title),
+                std::mem::take(&mut self.link),
+                self.pub_date.take(),
+                self.source.take(),
+                self.author.take(),
+                std::mem::take(&mut self.categories),
+            )
+            .described(std::mem::take(&mut self.description), &self.options.limits);
+            rss.raw = raw;
+            rss.provenance = std::mem::replace(&mut self.provenance, Provenance::of(&self.options))
+                .map(Box::new);
+            rss.updated = self.updated.take();
+            rss.set_guid(self.guid.take());
+            rss.set_comment_feed_url(self.comment_feed_url.take());
+            let audio = std::mem::take(&mut self.audio).resolve();
+            rss.audio_duration_secs = audio.duration_secs;
+            rss.audio_length_bytes = audio.length_bytes;
+            rss.limit_fields(&self.options.limits);
+            keep(&mut self.results, &mut self.dropped, rss);
+            if self.links > 1 {
+                self.warnings.push(format!(
+                    "item {} contains {} link elements",
+                    self.results.len() + self.dropped.len(),
+                    self.links
+                ));
+            }
+            self.links = 0;
         }
         self.elements.pop_front();
     }
-    fn verify_rss(&self) -> Result<(), Error<String>> {
+    fn verify_rss(&self, _: &Namespace) -> Result<(), ApiError> {
         let (name, attrs) = &self.elements[0];
-        if name.local_name != "rss" {
+        if self.local_name(name) != "rss" {
             return Err(InvalidRssError {
+                code: ErrorCode::InvalidRootElement,
                 message: format!(
                     "[{}] invalid root element: {}",
                     self.name(),
@@ -214,52 +2298,121 @@ impl RssParser for RssV20 {
         }
         let version = attrs
             .iter()
-            .find(|a| a.name.to_string() == "version")
-            .map(|a| a.value.as_ref());
+            .find(|a| a.name.prefix.is_none() && self.is_named(&a.name.local_name, "version"))
+            .map(|a| {
+                if self.options.exact_version {
+                    a.value.as_str()
+                } else {
+                    a.value.trim()
+                }
+            });
         match version {
             Some("2.0") => Ok(()),
             Some(version) => {
                 warn!("unsupported RSS version: {}", version);
                 Err(InvalidRssError {
+                    code: ErrorCode::UnsupportedRssVersion,
                     message: format!("[{}] unsupported RSS version: {}", self.name(), version),
                 }
                 .into())
             }
             None => Err(InvalidRssError {
+                code: ErrorCode::UndefinedRssVersion,
                 message: format!("[{}] undefined RSS version", self.name()),
             }
             .into()),
         }
     }
-    fn get_results(&self) -> Vec<Rss> {
-        self.results.clone()
+    fn maps(&self, name: &OwnedName) -> bool {
+        match (name.namespace_ref(), name.local_name.as_str()) {
+            (None, _) => RssV20::ELEMENTS.contains(&self.local_name(name)),
+            (Some(Rss::CONTENT_NS), "encoded")
+            | (Some(Rss::ELEMENTS_NS), "date" | "creator" | "subject")
+            | (Some(Rss::ATOM_NS), "link" | "updated")
+            | (Some(Rss::SYNDICATION_NS), "updatePeriod" | "updateFrequency")
+            | (Some(Rss::MEDIA_NS), "content" | "group")
+            | (Some(Rss::ITUNES_NS), "duration")
+            | (Some(Rss::WFW_NS), "commentRss") => true,
+            _ => false,
+        }
+    }
+    fn into_results(self) -> Feed {
+        Feed {
+            channel: self.channel.complete(),
+            items: self.results,
+            warnings: count_warnings(self.channels, self.warnings),
+            recovery: None,
+            unmapped_elements: Vec::new(),
+            dropped_items: self.dropped,
+        }
+    }
+    fn item(&self, index: usize) -> Option<Rss> {
+        self.results.get(index).cloned()
+    }
+    fn item_count(&self) -> usize {
+        self.results.len()
     }
 }
 
 struct Atom {
     results: Vec<Rss>,
+    channel: Channel,
     elements: VecDeque<(OwnedName, Vec<OwnedAttribute>)>,
     title: String,
     link: String,
     description: String,
     pub_date: Option<String>,
+    updated: Option<String>,
+    source: Option<ItemSource>,
+    authors: Vec<Person>,
+    contributors: Vec<Person>,
+    // the author or contributor being read, of the entry or the feed
+    person: Option<Person>,
+    categories: Vec<Category>,
+    guid: Option<String>,
+    dropped: Vec<DroppedItem>,
+    // of the item being read
+    provenance: Option<Provenance>,
+    options: ParseOptions,
 }
 
 impl Atom {
-    fn new() -> Atom {
+    fn new(options: ParseOptions) -> Atom {
         Atom {
             results: Vec::new(),
+            channel: Channel::default(),
             elements: VecDeque::default(),
             title: String::new(),
             link: String::new(),
             description: String::new(),
             pub_date: Option::default(),
+            updated: Option::default(),
+            source: Option::default(),
+            authors: Vec::new(),
+            contributors: Vec::new(),
+            person: None,
+            categories: Vec::new(),
+            guid: None,
+            dropped: Vec::new(),
+            provenance: Provenance::of(&options),
+            options,
         }
     }
 
+    // an author or contributor of an entry or the feed, not of an entry's source
+    fn is_person(&self, depth: usize) -> bool {
+        if self.elements.len() < depth + 2 {
+            return false;
+        }
+        let (name, _) = &self.elements[depth];
+        let (parent, _) = &self.elements[depth + 1];
+        (Atom::is_atom_ns(name, "author") || Atom::is_atom_ns(name, "contributor"))
+            && (Atom::is_entry(parent) || Atom::is_feed(parent))
+    }
+
     // for YouTube RSS format
     fn is_media_description(&self) -> bool {
-        if &self.elements.len() < &3 {
+        if self.elements.len() < 3 {
             return false;
         }
         let (name, _) = &self.elements[2];
@@ -271,9 +2424,15 @@ impl Atom {
             return false;
         }
         let (name, _) = &self.elements[0];
-        return Atom::is_media_ns(name, "description");
+        Atom::is_media_ns(name, "description")
     }
 
+    fn is_atom_ns(name: &OwnedName, local_name: &str) -> bool {
+        name.namespace_ref() == Some(Rss::ATOM_NS) && name.local_name == local_name
+    }
+    fn is_feed(name: &OwnedName) -> bool {
+        name.namespace_ref() == Some(Rss::ATOM_NS) && name.local_name == "feed"
+    }
     fn is_entry(name: &OwnedName) -> bool {
         name.namespace_ref() == Some(Rss::ATOM_NS) && name.local_name == "entry"
     }
@@ -283,71 +2442,247 @@ impl Atom {
 }
 
 impl RssParser for Atom {
-    fn name(&self) -> &str {
-        return "Atom";
+    fn name(&self) -> &'static str {
+        "Atom"
     }
     fn parse_start_element(&mut self, name: OwnedName, attrs: Vec<OwnedAttribute>) {
+        let parent = self.elements.front().map(|(parent, _)| parent);
+        if parent.is_some_and(|p| Atom::is_entry(p) || Atom::is_feed(p))
+            && (Atom::is_atom_ns(&name, "author") || Atom::is_atom_ns(&name, "contributor"))
+        {
+            self.person = Some(Person::default());
+        }
+        if parent.is_some_and(Atom::is_entry) && Atom::is_atom_ns(&name, "source") {
+            self.source = Some(ItemSource::default());
+        }
+        // term is required, an empty element without one isn't a category
+        if parent.is_some_and(Atom::is_entry) && Atom::is_atom_ns(&name, "category") {
+            if let Some(term) = attribute(&attrs, "term") {
+                let (label, scheme) = (attribute(&attrs, "label"), attribute(&attrs, "scheme"));
+                self.categories.push(Category::new(term, label, scheme));
+            }
+        }
+        // the links of an entry's source are the original feed's
+        if parent.is_some_and(|p| Atom::is_atom_ns(p, "source")) && Atom::is_atom_ns(&name, "link")
+        {
+            let rel = attrs.iter().find(|a| is_unqualified(&a.name, "rel"));
+            let href = attrs.iter().find(|a| is_unqualified(&a.name, "href"));
+            if let (Some(source), Some(href)) = (self.source.as_mut(), href) {
+                match rel.map(|a| a.value.as_str()) {
+                    Some("self") => source.url = Some(href.value.clone()),
+                    None | Some("alternate") if source.url.is_none() => {
+                        source.url = Some(href.value.clone());
+                    }
+                    _ => (),
+                }
+            }
+            self.elements.push_front((name, attrs));
+            return;
+        }
+        if parent.is_some_and(Atom::is_feed) && Atom::is_atom_ns(&name, "generator") {
+            self.channel.generator = Some(Generator {
+                name: String::new(),
+                uri: attribute(&attrs, "uri").map(|uri| uri.trim().to_string()),
+                version: attribute(&attrs, "version").map(|version| version.trim().to_string()),
+            });
+        }
+        if parent.is_some_and(Atom::is_feed) && Atom::is_atom_ns(&name, "link") {
+            let rel = attrs.iter().find(|a| is_unqualified(&a.name, "rel"));
+            if rel.is_none_or(|a| a.value == "alternate") {
+                if let Some(href) = attrs.iter().find(|a| is_unqualified(&a.name, "href")) {
+                    self.channel.link = Some(href.value.clone());
+                }
+            }
+            self.elements.push_front((name, attrs));
+            return;
+        }
         if name.namespace_ref() == Some(Rss::ATOM_NS)
             && name.local_name == "link"
             && attrs
                 .iter()
                 .find(|a| {
-                    a.name.to_string() == "rel" && a.value != "self" && a.value != "alternate"
+                    is_unqualified(&a.name, "rel") && a.value != "self" && a.value != "alternate"
                 })
                 .is_none()
         {
-            attrs
-                .iter()
-                .find(|a| a.name.to_string() == "href")
-                .map(|a| self.link = a.value.clone());
+            if let Some(a) = attrs.iter().find(|a| is_unqualified(&a.name, "href")) {
+                set_with_provenance(
+                    &mut self.link,
+                    a.value.clone(),
+                    &mut self.provenance,
+                    "link",
+                    &name,
+                    "the href of the last one with no rel but self or alternate",
+                );
+            }
         }
         self.elements.push_front((name, attrs));
     }
     fn parse_content(&mut self, data: String) {
         if self.is_media_description() && self.description.is_empty() {
-            self.description = data;
+            let (name, _) = &self.elements[0];
+            set_with_provenance(
+                &mut self.description,
+                data,
+                &mut self.provenance,
+                "description",
+                name,
+                "of media:group, only without a content",
+            );
+            return;
+        }
+        if self.elements.len() < 2 {
             return;
         }
-        if &self.elements.len() < &2 {
+        if self.is_person(1) {
+            let (name, _) = &self.elements[0];
+            let value = data.trim().to_string();
+            if let Some(person) = self.person.as_mut().filter(|_| !value.is_empty()) {
+                match (name.namespace_ref(), name.local_name.as_str()) {
+                    (Some(Rss::ATOM_NS), "name") => person.name = value,
+                    (Some(Rss::ATOM_NS), "email") => person.email = Some(value),
+                    (Some(Rss::ATOM_NS), "uri") => person.uri = Some(value),
+                    _ => (),
+                }
+            }
             return;
         }
         let (parent, _) = &self.elements[1];
+        if Atom::is_atom_ns(parent, "source") {
+            let (name, _) = &self.elements[0];
+            if let Some(source) = self.source.as_mut() {
+                if Atom::is_atom_ns(name, "title") {
+                    source.title = data;
+                }
+            }
+            return;
+        }
+        if Atom::is_feed(parent) {
+            let (name, _) = &self.elements[0];
+            match (name.namespace_ref(), name.local_name.as_str()) {
+                (Some(Rss::ATOM_NS), "updated") => self.channel.pub_date = Some(data),
+                (Some(Rss::ATOM_NS), "logo") => {
+                    self.channel.image_mut().url = data.trim().to_string()
+                }
+                (Some(Rss::ATOM_NS), "icon") if self.channel.image.is_none() => {
+                    self.channel.image_mut().url = data.trim().to_string();
+                }
+                (Some(Rss::ATOM_NS), "rights") => {
+                    self.channel.copyright = Some(data.trim().to_string())
+                }
+                (Some(Rss::ATOM_NS), "generator") => {
+                    if let Some(generator) = self.channel.generator.as_mut() {
+                        generator.name = data.trim().to_string();
+                    }
+                }
+                _ => (),
+            }
+            return;
+        }
         if Atom::is_entry(parent) {
             let (name, _) = &self.elements[0];
+            let provenance = &mut self.provenance;
             match (name.namespace_ref(), name.local_name.as_str()) {
-                (Some(Rss::ATOM_NS), "title") => self.title = data,
-                (Some(Rss::ATOM_NS), "content") => self.description = data,
-                (Some(Rss::ATOM_NS), "published") => self.pub_date = Some(data),
+                (Some(Rss::ATOM_NS), "title") => set_with_provenance(
+                    &mut self.title,
+                    data,
+                    provenance,
+                    "title",
+                    name,
+                    "the last one",
+                ),
+                (Some(Rss::ATOM_NS), "content") => set_with_provenance(
+                    &mut self.description,
+                    data,
+                    provenance,
+                    "description",
+                    name,
+                    "wins over media:description",
+                ),
+                (Some(Rss::ATOM_NS), "published") => set_with_provenance(
+                    &mut self.pub_date,
+                    Some(data),
+                    provenance,
+                    "pub_date",
+                    name,
+                    "wins over updated",
+                ),
+                // also the date of entries that aren't given a published one
                 (Some(Rss::ATOM_NS), "updated") => {
                     if self.pub_date.is_none() {
-                        self.pub_date = Some(data);
+                        set_with_provenance(
+                            &mut self.pub_date,
+                            Some(data.clone()),
+                            provenance,
+                            "pub_date",
+                            name,
+                            "only without a published",
+                        );
                     }
+                    set_with_provenance(
+                        &mut self.updated,
+                        Some(data),
+                        provenance,
+                        "updated",
+                        name,
+                        "the last one",
+                    );
                 }
+                (Some(Rss::ATOM_NS), "id") => self.guid = Some(data),
                 _ => (),
             }
         }
     }
     fn parse_end_element(&mut self, name: OwnedName) {
+        if self.is_person(0) {
+            let (parent, _) = &self.elements[1];
+            let person = self.person.take().filter(|p| !p.is_empty());
+            match (person, Atom::is_entry(parent)) {
+                (Some(person), true) if Atom::is_atom_ns(&name, "author") => {
+                    self.authors.push(person)
+                }
+                (Some(person), true) => self.contributors.push(person),
+                // feed contributors aren't the entries', only the authors are inherited
+                (Some(person), false) if Atom::is_atom_ns(&name, "author") => {
+                    self.channel.authors.push(person)
+                }
+                _ => (),
+            }
+        }
         if Atom::is_entry(&name) {
-            let rss = Rss::new(
-                self.title.clone(),
-                self.description.clone(),
-                self.link.clone(),
-                self.pub_date.clone(),
+            let raw = RawFields::capture(
+                &self.options,
+                &self.title,
+                &self.description,
+                &self.link,
+                self.pub_date.as_deref(),
             );
-            self.results.push(rss);
-
-            self.title = String::new();
-            self.link = String::new();
-            self.description = String::new();
-            self.pub_date = Option::default();
+            let mut rss = Rss::parsed(
+                std::mem::take(&mut self.title),
+                std::mem::take(&mut self.link),
+                self.pub_date.take(),
+                self.source.take(),
+                None,
+                std::mem::take(&mut self.categories),
+            )
+            .described(std::mem::take(&mut self.description), &self.options.limits);
+            rss.raw = raw;
+            rss.provenance = std::mem::replace(&mut self.provenance, Provenance::of(&self.options))
+                .map(Box::new);
+            rss.updated = self.updated.take();
+            rss.set_authors(std::mem::take(&mut self.authors));
+            rss.contributors = std::mem::take(&mut self.contributors);
+            rss.set_guid(self.guid.take());
+            rss.limit_fields(&self.options.limits);
+            keep(&mut self.results, &mut self.dropped, rss);
         }
         self.elements.pop_front();
     }
-    fn verify_rss(&self) -> Result<(), Error<String>> {
+    fn verify_rss(&self, _: &Namespace) -> Result<(), ApiError> {
         let (name, _) = &self.elements[0];
         if name.local_name != "feed" {
             return Err(InvalidRssError {
+                code: ErrorCode::InvalidRootElement,
                 message: format!(
                     "[{}] invalid root element: {}",
                     self.name(),
@@ -358,6 +2693,7 @@ impl RssParser for Atom {
         }
         if name.namespace_ref() != Some(Rss::ATOM_NS) {
             return Err(InvalidRssError {
+                code: ErrorCode::InvalidRootNamespace,
                 message: format!(
                     "[{}] invalid root namespace: {:?}",
                     self.name(),
@@ -368,86 +2704,305 @@ impl RssParser for Atom {
         }
         Ok(())
     }
-    fn get_results(&self) -> Vec<Rss> {
-        self.results.clone()
+    fn maps(&self, name: &OwnedName) -> bool {
+        matches!(
+            (name.namespace_ref(), name.local_name.as_str()),
+            (
+                Some(Rss::ATOM_NS),
+                "feed"
+                    | "entry"
+                    | "title"
+                    | "link"
+                    | "content"
+                    | "published"
+                    | "updated"
+                    | "author"
+                    | "contributor"
+                    | "name"
+                    | "email"
+                    | "uri"
+                    | "source"
+                    | "logo"
+                    | "icon"
+                    | "rights"
+                    | "generator"
+                    | "category"
+                    | "id"
+            ) | (Some(Rss::MEDIA_NS), "group" | "description")
+        )
+    }
+    // the feed authors may come after the entries, so they are inherited at the end
+    fn into_results(self) -> Feed {
+        let mut items = self.results;
+        for item in items.iter_mut().filter(|i| i.authors.is_empty()) {
+            item.set_authors(self.channel.authors.clone());
+            item.limit_fields(&self.options.limits);
+        }
+        Feed {
+            channel: self.channel.complete(),
+            items,
+            warnings: Vec::new(),
+            recovery: None,
+            unmapped_elements: Vec::new(),
+            dropped_items: self.dropped,
+        }
+    }
+    // with the feed authors seen so far, those after the entry are only in the results
+    fn item(&self, index: usize) -> Option<Rss> {
+        let mut item = self.results.get(index)?.clone();
+        if item.authors.is_empty() {
+            item.set_authors(self.channel.authors.clone());
+            item.limit_fields(&self.options.limits);
+        }
+        Some(item)
+    }
+    fn item_count(&self) -> usize {
+        self.results.len()
     }
 }
 
 struct RssV10 {
     results: Vec<Rss>,
+    channel: Channel,
     elements: VecDeque<(OwnedName, Vec<OwnedAttribute>)>,
     title: String,
     link: String,
     description: String,
     pub_date: Option<String>,
+    updated: Option<String>,
+    source: Option<ItemSource>,
+    author: Option<String>,
+    categories: Vec<Category>,
+    guid: Option<String>,
+    comment_feed_url: Option<String>,
+    // channel and item link elements seen, for what broken feeds repeat
+    channels: usize,
+    links: usize,
+    warnings: Vec<String>,
+    dropped: Vec<DroppedItem>,
+    // of the item being read
+    provenance: Option<Provenance>,
+    options: ParseOptions,
 }
 
 impl RssV10 {
-    fn new() -> RssV10 {
+    fn new(options: ParseOptions) -> RssV10 {
         RssV10 {
             results: Vec::new(),
+            channel: Channel::default(),
             elements: VecDeque::default(),
             title: String::new(),
             link: String::new(),
             description: String::new(),
             pub_date: Option::default(),
+            updated: Option::default(),
+            source: Option::default(),
+            author: Option::default(),
+            categories: Vec::new(),
+            guid: None,
+            comment_feed_url: None,
+            channels: 0,
+            links: 0,
+            warnings: Vec::new(),
+            dropped: Vec::new(),
+            provenance: Provenance::of(&options),
+            options,
         }
     }
-    fn is_item(name: &OwnedName) -> bool {
-        name.local_name.eq_ignore_ascii_case("item") && name.namespace_ref() == Some(Rss::RDF_NS)
+    fn is_channel(&self, name: &OwnedName) -> bool {
+        self.is_named(&name.local_name, "channel") && name.namespace_ref() == Some(Rss::RDF_NS)
+    }
+    fn is_item(&self, name: &OwnedName) -> bool {
+        self.is_named(&name.local_name, "item") && name.namespace_ref() == Some(Rss::RDF_NS)
+    }
+    fn is_named(&self, name: &str, expected: &str) -> bool {
+        if self.options.exact_names {
+            name == expected
+        } else {
+            name.eq_ignore_ascii_case(expected)
+        }
     }
 }
 
 impl RssParser for RssV10 {
-    fn name(&self) -> &str {
-        return "RSS V1";
+    fn name(&self) -> &'static str {
+        "RSS V1"
     }
     fn parse_start_element(&mut self, name: OwnedName, attrs: Vec<OwnedAttribute>) {
+        if self.is_channel(&name) {
+            self.channels += 1;
+        }
+        if self.is_item(&name) {
+            self.guid = attrs
+                .iter()
+                .find(|a| {
+                    a.name.namespace_ref() == Some(Rss::RDF_SYNTAX_NS)
+                        && a.name.local_name == "about"
+                })
+                .map(|a| a.value.clone());
+        }
+        let in_item = matches!(self.elements.front(), Some((parent, _)) if self.is_item(parent));
+        if in_item && name.namespace_ref() == Some(Rss::RDF_NS) && name.local_name == "link" {
+            self.links += 1;
+        }
         self.elements.push_front((name, attrs));
     }
     fn parse_content(&mut self, data: String) {
-        if &self.elements.len() < &2 {
+        if self.elements.len() < 2 {
             return;
         }
         let (parent, _) = &self.elements[1];
-        if !RssV10::is_item(parent) {
+        // the channel refers to an image described next to it
+        if parent.namespace_ref() == Some(Rss::RDF_NS) && parent.local_name == "image" {
+            let (name, _) = &self.elements[0];
+            match (name.namespace_ref(), name.local_name.as_str()) {
+                (Some(Rss::RDF_NS), "url") => {
+                    self.channel.image_mut().url = data.trim().to_string()
+                }
+                (Some(Rss::RDF_NS), "title") => self.channel.image_mut().title = Some(data),
+                (Some(Rss::RDF_NS), "link") => {
+                    self.channel.image_mut().link = Some(data.trim().to_string());
+                }
+                _ => (),
+            }
+            return;
+        }
+        if self.is_channel(parent) {
+            // the metadata of the first channel, the items of all
+            if self.channels > 1 {
+                return;
+            }
+            let (name, _) = &self.elements[0];
+            match (name.namespace_ref(), name.local_name.as_str()) {
+                (Some(Rss::RDF_NS), "link") => {
+                    set_first_opt(&mut self.channel.link, data.trim().to_string())
+                }
+                (Some(Rss::ELEMENTS_NS), "date") => self.channel.pub_date = Some(data),
+                (Some(Rss::ELEMENTS_NS), "rights") => {
+                    self.channel.copyright = Some(data.trim().to_string())
+                }
+                (Some(Rss::SYNDICATION_NS), local_name) => {
+                    self.channel.set_syndication(local_name, &data)
+                }
+                _ => (),
+            }
+            return;
+        }
+        if !self.is_item(parent) {
             return;
         }
         let (name, _) = &self.elements[0];
+        let provenance = &mut self.provenance;
         match (name.namespace_ref(), name.local_name.as_str()) {
-            (Some(Rss::RDF_NS), "title") => self.title = data,
-            (Some(Rss::RDF_NS), "link") => self.link = data,
-            (Some(Rss::RDF_NS), "description") => self.description = data,
-            (Some(Rss::CONTENT_NS), "encoded") => {
-                if self.description.is_empty() {
-                    self.description = data;
-                }
+            (Some(Rss::RDF_NS), "title") => set_with_provenance(
+                &mut self.title,
+                data,
+                provenance,
+                "title",
+                name,
+                "the last one",
+            ),
+            (Some(Rss::RDF_NS), "link") if self.link.trim().is_empty() => set_with_provenance(
+                &mut self.link,
+                data,
+                provenance,
+                "link",
+                name,
+                "the first non-empty one",
+            ),
+            (Some(Rss::RDF_NS), "description") => set_with_provenance(
+                &mut self.description,
+                data,
+                provenance,
+                "description",
+                name,
+                "wins over content:encoded",
+            ),
+            (Some(Rss::CONTENT_NS), "encoded") if self.description.is_empty() => {
+                set_with_provenance(
+                    &mut self.description,
+                    data,
+                    provenance,
+                    "description",
+                    name,
+                    "only without a description",
+                );
             }
-            (Some(Rss::ELEMENTS_NS), "date") => self.pub_date = Some(data),
+            // the description wins, content:encoded isn't read as anything else
+            (Some(Rss::CONTENT_NS), "encoded") => (),
+            (Some(Rss::ELEMENTS_NS), "date") => set_with_provenance(
+                &mut self.pub_date,
+                Some(data),
+                provenance,
+                "pub_date",
+                name,
+                "the last one",
+            ),
+            (Some(Rss::ATOM_NS), "updated") => set_with_provenance(
+                &mut self.updated,
+                Some(data),
+                provenance,
+                "updated",
+                name,
+                "the last one",
+            ),
+            (Some(Rss::ELEMENTS_NS), "creator") => set_with_provenance(
+                &mut self.author,
+                Some(data),
+                provenance,
+                "author",
+                name,
+                "the last one",
+            ),
+            (Some(Rss::ELEMENTS_NS), "subject") => {
+                self.categories.push(Category::new(data, None, None))
+            }
+            (Some(Rss::WFW_NS), "commentRss") => self.comment_feed_url = Some(data),
             _ => (),
         }
     }
     fn parse_end_element(&mut self, name: OwnedName) {
-        if RssV10::is_item(&name) {
-            let rss = Rss::new(
-                self.title.clone(),
-                self.description.clone(),
-                self.link.clone(),
-                self.pub_date.clone(),
+        if self.is_item(&name) {
+            let raw = RawFields::capture(
+                &self.options,
+                &self.title,
+                &self.description,
+                &self.link,
+                self.pub_date.as_deref(),
             );
-            self.results.push(rss);
-
-            self.title = String::new();
-            self.link = String::new();
-            self.description = String::new();
-            self.pub_date = Option::default();
+            let mut rss = Rss::parsed(
+                std::mem::take(&mut self.title),
+                std::mem::take(&mut self.link),
+                self.pub_date.take(),
+                self.source.take(),
+                self.author.take(),
+                std::mem::take(&mut self.categories),
+            )
+            .described(std::mem::take(&mut self.description), &self.options.limits);
+            rss.raw = raw;
+            rss.provenance = std::mem::replace(&mut self.provenance, Provenance::of(&self.options))
+                .map(Box::new);
+            rss.updated = self.updated.take();
+            rss.set_guid(self.guid.take());
+            rss.set_comment_feed_url(self.comment_feed_url.take());
+            rss.limit_fields(&self.options.limits);
+            keep(&mut self.results, &mut self.dropped, rss);
+            if self.links > 1 {
+                self.warnings.push(format!(
+                    "item {} contains {} link elements",
+                    self.results.len() + self.dropped.len(),
+                    self.links
+                ));
+            }
+            self.links = 0;
         }
         self.elements.pop_front();
     }
-    fn verify_rss(&self) -> Result<(), Error<String>> {
+    fn verify_rss(&self, namespace: &Namespace) -> Result<(), ApiError> {
         let (name, _) = &self.elements[0];
-        if !name.local_name.eq_ignore_ascii_case("rdf") {
+        if !self.is_named(&name.local_name, "RDF") {
             return Err(InvalidRssError {
+                code: ErrorCode::InvalidRootElement,
                 message: format!(
                     "[{}] invalid root element: {}",
                     self.name(),
@@ -458,6 +3013,7 @@ impl RssParser for RssV10 {
         }
         if name.namespace_ref() != Some(Rss::RDF_SYNTAX_NS) {
             return Err(InvalidRssError {
+                code: ErrorCode::InvalidRootNamespace,
                 message: format!(
                     "[{}] invalid root namespace: {:?}",
                     self.name(),
@@ -466,9 +3022,51 @@ impl RssParser for RssV10 {
             }
             .into());
         }
+        // RSS 0.90 is RDF too, but with its own elements
+        if namespace.get(NS_NO_PREFIX) == Some(RSS090_NS) {
+            return Err(InvalidRssError {
+                code: ErrorCode::UnsupportedRssVersion,
+                message: format!("[{}] unsupported RSS version: 0.90", self.name()),
+            }
+            .into());
+        }
         Ok(())
     }
-    fn get_results(&self) -> Vec<Rss> {
-        self.results.clone()
+    // the rdf:Seq of the channel only lists its items again
+    fn maps(&self, name: &OwnedName) -> bool {
+        matches!(
+            (name.namespace_ref(), name.local_name.as_str()),
+            (
+                Some(Rss::RDF_NS),
+                "channel" | "item" | "items" | "title" | "link" | "description" | "image" | "url"
+            ) | (Some(Rss::RDF_SYNTAX_NS), "RDF" | "Seq" | "li")
+                | (
+                    Some(Rss::ELEMENTS_NS),
+                    "date" | "creator" | "subject" | "rights"
+                )
+                | (Some(Rss::CONTENT_NS), "encoded")
+                | (Some(Rss::ATOM_NS), "updated")
+                | (Some(Rss::WFW_NS), "commentRss")
+                | (
+                    Some(Rss::SYNDICATION_NS),
+                    "updatePeriod" | "updateFrequency"
+                )
+        )
+    }
+    fn into_results(self) -> Feed {
+        Feed {
+            channel: self.channel.complete(),
+            items: self.results,
+            warnings: count_warnings(self.channels, self.warnings),
+            recovery: None,
+            unmapped_elements: Vec::new(),
+            dropped_items: self.dropped,
+        }
+    }
+    fn item(&self, index: usize) -> Option<Rss> {
+        self.results.get(index).cloned()
+    }
+    fn item_count(&self) -> usize {
+        self.results.len()
     }
 }
@@ -0,0 +1,165 @@
+use ego_tree::NodeRef;
+use scraper::{Html, Node};
+
+// kept with the attributes listed, other elements are unwrapped to their children
+const ELEMENTS: [(&str, &[&str]); 29] = [
+    ("a", &["href", "title"]),
+    ("abbr", &["title"]),
+    ("b", &[]),
+    ("blockquote", &[]),
+    ("br", &[]),
+    ("code", &[]),
+    ("del", &[]),
+    ("em", &[]),
+    ("figcaption", &[]),
+    ("figure", &[]),
+    ("h1", &[]),
+    ("h2", &[]),
+    ("h3", &[]),
+    ("h4", &[]),
+    ("h5", &[]),
+    ("h6", &[]),
+    ("hr", &[]),
+    ("i", &[]),
+    ("img", &["src", "alt", "title"]),
+    ("li", &[]),
+    ("ol", &[]),
+    ("p", &[]),
+    ("pre", &[]),
+    ("q", &[]),
+    ("s", &[]),
+    ("strong", &[]),
+    ("sub", &[]),
+    ("sup", &[]),
+    ("ul", &[]),
+];
+// dropped along with their content
+const DROPPED: [&str; 14] = [
+    "script", "style", "noscript", "template", "iframe", "object", "embed", "head", "title",
+    "form", "input", "button", "select", "textarea",
+];
+const VOID: [&str; 3] = ["br", "hr", "img"];
+const URL_ATTRIBUTES: [&str; 2] = ["href", "src"];
+const SCHEMES: [&str; 3] = ["http", "https", "mailto"];
+
+// item HTML reduced to an allowlist of elements and attributes, with links to http(s) and mail only.
+// at most `limit` characters of visible text are kept, the cut text ends with an ellipsis
+pub fn to_safe_html(html: &str, limit: usize) -> String {
+    let fragment = Html::parse_fragment(html);
+    let mut sanitizer = Sanitizer {
+        out: String::new(),
+        budget: limit,
+        truncated: false,
+    };
+    sanitizer.children(*fragment.root_element());
+    sanitizer.out.trim().to_string()
+}
+
+struct Sanitizer {
+    out: String,
+    // visible characters left
+    budget: usize,
+    truncated: bool,
+}
+
+impl Sanitizer {
+    fn children(&mut self, node: NodeRef<Node>) {
+        for child in node.children() {
+            if self.truncated {
+                return;
+            }
+            self.node(child);
+        }
+    }
+
+    fn node(&mut self, node: NodeRef<Node>) {
+        let element = match node.value() {
+            Node::Text(text) => return self.text(text),
+            Node::Element(element) => element,
+            _ => return,
+        };
+        let name = element.name();
+        if DROPPED.contains(&name) {
+            return;
+        }
+        let attributes = match ELEMENTS.iter().find(|(n, _)| *n == name) {
+            Some((_, attributes)) => *attributes,
+            None => return self.children(node),
+        };
+        // an image without a source or a link without a target is no use
+        let url_required = ["img", "a"].contains(&name);
+        let mut tag = format!("<{}", name);
+        let mut has_url = false;
+        for attribute in attributes {
+            let value = match element.attr(attribute) {
+                Some(value) => value.trim(),
+                None => continue,
+            };
+            if URL_ATTRIBUTES.contains(attribute) {
+                if !is_safe_url(value) {
+                    continue;
+                }
+                has_url = true;
+            }
+            tag.push_str(&format!(" {}=\"{}\"", attribute, escape(value)));
+        }
+        if url_required && !has_url {
+            if name == "a" {
+                self.children(node);
+            }
+            return;
+        }
+        tag.push('>');
+        self.out.push_str(&tag);
+        if VOID.contains(&name) {
+            return;
+        }
+        self.children(node);
+        self.out.push_str(&format!("</{}>", name));
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.truncated {
+            return;
+        }
+        // the whitespace between elements is no text
+        let count = text.chars().count();
+        if text.trim().is_empty() || count <= self.budget {
+            self.budget -= if text.trim().is_empty() { 0 } else { count };
+            self.out.push_str(&escape(text));
+            return;
+        }
+        let end = text
+            .char_indices()
+            .nth(self.budget)
+            .map_or(text.len(), |(i, _)| i);
+        self.budget = 0;
+        self.truncated = true;
+        self.out.push_str(&escape(text[..end].trim_end()));
+        self.out.push_str("...");
+    }
+}
+
+// relative urls are kept as is, absolute ones only with a known scheme
+fn is_safe_url(url: &str) -> bool {
+    match url.split_once(':') {
+        Some((scheme, _)) if !scheme.contains(['/', '?', '#']) => SCHEMES
+            .iter()
+            .any(|s| s.eq_ignore_ascii_case(scheme.trim())),
+        _ => true,
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
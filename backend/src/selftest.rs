@@ -0,0 +1,178 @@
+use crate::upstream;
+use actix_web::http::Version;
+use actix_web::rt::time::timeout;
+use actix_web::web;
+use awc::ClientBuilder;
+use futures::future::join3;
+use serde_derive::Serialize;
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+use url::Url;
+
+// each check gives up after this long, `timeout_ms` can only lower it
+pub const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Failed,
+    Timeout,
+}
+
+// what `/selftest` tells, each check as far as it got
+#[derive(Debug, Serialize)]
+pub struct SelfTest {
+    pub url: String,
+    // every check passed
+    pub ok: bool,
+    pub dns: Check<Dns>,
+    pub fetch: Check<Fetch>,
+    pub outbound: Check<Outbound>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Check<T> {
+    pub status: CheckStatus,
+    pub elapsed_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub result: Option<T>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Dns {
+    pub addresses: Vec<IpAddr>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Fetch {
+    pub http_status: u16,
+    pub http_version: String,
+    // whether a TLS handshake was made, false for http urls
+    pub tls: bool,
+    // the address dialed, see `upstream::dialed`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_address: Option<IpAddr>,
+}
+
+// the address of the interface feed hosts are reached from, behind NAT not the one they see
+#[derive(Debug, Serialize)]
+pub struct Outbound {
+    pub local_address: IpAddr,
+    pub remote_address: IpAddr,
+}
+
+// the checks run at once, each with its own timeout, so a stalled one doesn't hold up the others
+pub async fn run(url: &Url, limit: Duration) -> SelfTest {
+    let limit = limit.min(CHECK_TIMEOUT);
+    let (dns, fetch, outbound) = join3(
+        check(limit, resolve(url)),
+        check(limit, fetch(url, limit)),
+        check(limit, outbound(url)),
+    )
+    .await;
+    SelfTest {
+        url: url.to_string(),
+        ok: [dns.status, fetch.status, outbound.status]
+            .iter()
+            .all(|s| *s == CheckStatus::Ok),
+        dns,
+        fetch,
+        outbound,
+    }
+}
+
+async fn check<T, F>(limit: Duration, future: F) -> Check<T>
+where
+    F: Future<Output = Result<T, String>>,
+{
+    let started = Instant::now();
+    let outcome = timeout(limit, future).await;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    let (status, error, result) = match outcome {
+        Ok(Ok(result)) => (CheckStatus::Ok, None, Some(result)),
+        Ok(Err(e)) => (CheckStatus::Failed, Some(e), None),
+        Err(_) => (
+            CheckStatus::Timeout,
+            Some(format!("no answer within {} ms", limit.as_millis())),
+            None,
+        ),
+    };
+    Check {
+        status,
+        elapsed_ms,
+        error,
+        result,
+    }
+}
+
+async fn lookup(url: &Url) -> Result<Vec<SocketAddr>, String> {
+    let host = url.host_str().ok_or("url has no host")?.to_string();
+    let port = url.port_or_known_default().ok_or("url has no port")?;
+    let addrs = web::block(move || (host, port).to_socket_addrs().map(Vec::from_iter))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    if addrs.is_empty() {
+        return Err("no address".to_string());
+    }
+    Ok(addrs)
+}
+
+async fn resolve(url: &Url) -> Result<Dns, String> {
+    let mut addresses = lookup(url).await?;
+    addresses.dedup_by_key(|addr| addr.ip());
+    Ok(Dns {
+        addresses: addresses.iter().map(SocketAddr::ip).collect(),
+    })
+}
+
+// a connection of its own rather than a pooled one, which would say nothing of the network now
+async fn fetch(url: &Url, limit: Duration) -> Result<Fetch, String> {
+    let client = ClientBuilder::new()
+        .connector(upstream::connector(false).max_http_version(Version::HTTP_2))
+        .add_default_header(("User-Agent", crate::USER_AGENT))
+        .disable_redirects()
+        // past the check's own timeout, which is to end it first
+        .timeout(limit * 2)
+        .finish();
+    let res = client
+        .get(url.as_str())
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(Fetch {
+        http_status: res.status().as_u16(),
+        http_version: crate::protocol(res.version()),
+        tls: url.scheme() == "https",
+        remote_address: url
+            .host_str()
+            .zip(url.port_or_known_default())
+            .and_then(|(host, port)| upstream::dialed(host, port))
+            .map(|addr| addr.ip()),
+    })
+}
+
+// connecting a UDP socket sends nothing, it only has the kernel pick the route
+async fn outbound(url: &Url) -> Result<Outbound, String> {
+    let remote = lookup(url).await?[0];
+    let local = bind_toward(remote).map_err(|e| e.to_string())?;
+    Ok(Outbound {
+        local_address: local.ip(),
+        remote_address: remote.ip(),
+    })
+}
+
+fn bind_toward(remote: SocketAddr) -> io::Result<SocketAddr> {
+    let any = match remote {
+        SocketAddr::V4(_) => "0.0.0.0:0",
+        SocketAddr::V6(_) => "[::]:0",
+    };
+    let socket = UdpSocket::bind(any)?;
+    socket.connect(remote)?;
+    socket.local_addr()
+}
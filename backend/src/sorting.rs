@@ -0,0 +1,35 @@
+use crate::dates::parse_date;
+use crate::rss::Rss;
+use std::cmp::Reverse;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortBy {
+    Published,
+    Updated,
+}
+
+impl FromStr for SortBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<SortBy, String> {
+        match s {
+            "published" => Ok(SortBy::Published),
+            "updated" => Ok(SortBy::Updated),
+            _ => Err(format!("unsupported sort: {}", s)),
+        }
+    }
+}
+
+// newest first, items without a parsable date keep their order after the others, as do
+// the ones with a `date_suspect`.
+// an item never updated was last updated when it was published
+pub fn sort(items: &mut [Rss], by: SortBy) {
+    items.sort_by_cached_key(|item| {
+        let date = match by {
+            SortBy::Published => item.pub_date(),
+            SortBy::Updated => item.updated().or(item.pub_date()),
+        };
+        Reverse(date.filter(|_| !item.date_suspect()).and_then(parse_date))
+    });
+}
@@ -0,0 +1,95 @@
+use crate::error::{ApiError, UpstreamStalledError};
+use crate::redirects::UpstreamResponse;
+use actix_http::error::PayloadError;
+use actix_web::rt::time::timeout;
+use actix_web::web::{Bytes, BytesMut};
+use futures::stream::StreamExt;
+use std::time::Duration;
+
+// the roots the parsers take, by local name
+const ROOTS: [&[u8]; 3] = [b"rss", b"feed", b"RDF"];
+// how long the end of a body that ends the document is waited for, rather than the whole `idle`
+const END_GRACE: Duration = Duration::from_millis(50);
+
+// the body of a feed as far as it was read
+#[derive(Debug)]
+pub struct Body {
+    pub bytes: Bytes,
+    // the upstream kept it open past the end of the document, which was read without the rest
+    pub stalled: bool,
+}
+
+impl Body {
+    pub fn warning(&self) -> Option<String> {
+        self.stalled.then(|| {
+            format!(
+                "upstream stalled after {} bytes, read up to the end of the document",
+                self.bytes.len()
+            )
+        })
+    }
+}
+
+// reads the body until it ends, or until nothing came for `idle`. once what came ends with the
+// end tag of a feed root, nothing more is waited for past `END_GRACE`, the body is taken as it
+// is and its connection dropped
+pub async fn read_body(
+    res: &mut UpstreamResponse,
+    limit: usize,
+    idle: Duration,
+) -> Result<Body, ApiError> {
+    let mut body = BytesMut::new();
+    loop {
+        let wait = if document_ended(&body) {
+            idle.min(END_GRACE)
+        } else {
+            idle
+        };
+        match timeout(wait, res.next()).await {
+            Ok(Some(chunk)) => {
+                let chunk = chunk?;
+                if body.len() + chunk.len() > limit {
+                    return Err(PayloadError::Overflow.into());
+                }
+                body.extend_from_slice(&chunk);
+            }
+            Ok(None) => {
+                return Ok(Body {
+                    bytes: body.freeze(),
+                    stalled: false,
+                })
+            }
+            Err(_) if document_ended(&body) => {
+                return Ok(Body {
+                    bytes: body.freeze(),
+                    stalled: true,
+                })
+            }
+            Err(_) => {
+                return Err(UpstreamStalledError {
+                    bytes: body.len(),
+                    idle,
+                }
+                .into())
+            }
+        }
+    }
+}
+
+// whether the last thing in the bytes, whitespace aside, is `</rss>`, `</feed>` or `</rdf:RDF>`,
+// prefixed or not. an end tag in the middle of a body that keeps coming is no end, what comes
+// within `END_GRACE` is read on
+pub fn document_ended(bytes: &[u8]) -> bool {
+    let Some(rest) = bytes.trim_ascii_end().strip_suffix(b">") else {
+        return false;
+    };
+    let Some(start) = rest.windows(2).rposition(|w| w == b"</") else {
+        return false;
+    };
+    let name = rest[start + 2..].trim_ascii_end();
+    let local = match name.iter().position(|b| *b == b':') {
+        Some(colon) => &name[colon + 1..],
+        None => name,
+    };
+    ROOTS.contains(&local)
+}
@@ -0,0 +1,261 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_derive::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+// bounds the memory, urls seen after the limit is reached are counted as other
+pub const MAX_URLS: usize = 1_000;
+// latencies kept per url for the percentile
+const SAMPLES: usize = 100;
+const SHARDS: usize = 16;
+
+#[derive(Debug, Clone, Default)]
+struct Counters {
+    requests: u64,
+    cache_hits: u64,
+    fetches: u64,
+    fetch_time: Duration,
+    latencies: VecDeque<Duration>,
+    parses: u64,
+    parse_time: Duration,
+    bytes: u64,
+    negative_hits: u64,
+    slow_parses: u64,
+    last_status: Option<u16>,
+    last_item_count: Option<usize>,
+}
+
+impl Counters {
+    fn merge(&mut self, other: &Counters) {
+        self.requests += other.requests;
+        self.cache_hits += other.cache_hits;
+        self.fetches += other.fetches;
+        self.fetch_time += other.fetch_time;
+        self.latencies.extend(other.latencies.iter());
+        self.parses += other.parses;
+        self.parse_time += other.parse_time;
+        self.bytes += other.bytes;
+        self.negative_hits += other.negative_hits;
+        self.slow_parses += other.slow_parses;
+    }
+
+    fn snapshot(&self, url: Option<String>) -> FeedStats {
+        FeedStats {
+            url,
+            requests: self.requests,
+            cache_hit_ratio: ratio(self.cache_hits, self.requests),
+            avg_fetch_ms: average_ms(self.fetch_time, self.fetches),
+            p95_fetch_ms: p95_ms(&self.latencies),
+            avg_parse_ms: average_ms(self.parse_time, self.parses),
+            last_status: self.last_status,
+            last_item_count: self.last_item_count,
+            bytes: self.bytes,
+            negative_hits: self.negative_hits,
+            slow_parses: self.slow_parses,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct FeedStats {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    pub requests: u64,
+    pub cache_hit_ratio: Option<f64>,
+    pub avg_fetch_ms: Option<f64>,
+    pub p95_fetch_ms: Option<f64>,
+    pub avg_parse_ms: Option<f64>,
+    pub last_status: Option<u16>,
+    pub last_item_count: Option<usize>,
+    pub bytes: u64,
+    pub negative_hits: u64,
+    // parses and text over RSSSS_SLOW_PARSE_MS, see `watchdog`
+    pub slow_parses: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Snapshot {
+    pub since: String,
+    // by request count, most requested first
+    pub feeds: Vec<FeedStats>,
+    pub other: FeedStats,
+    // rssss_slow_parse_total, the slow_parses of every feed
+    pub slow_parse_total: u64,
+    // rssss_slow_client_disconnects_total, clients dropped for reading too slowly,
+    // see `disconnect::watch`
+    pub slow_client_disconnects: u64,
+}
+
+struct Shard {
+    urls: HashMap<String, Counters>,
+    // what didn't fit in `urls`
+    overflow: Counters,
+}
+
+// sharded by url so concurrent requests for different feeds rarely wait on each other
+pub struct Stats {
+    shards: Vec<Mutex<Shard>>,
+    urls_per_shard: usize,
+    since: Mutex<DateTime<Utc>>,
+    slow_clients: AtomicU64,
+}
+
+impl Stats {
+    pub fn new(max_urls: usize) -> Stats {
+        Stats {
+            shards: (0..SHARDS)
+                .map(|_| {
+                    Mutex::new(Shard {
+                        urls: HashMap::new(),
+                        overflow: Counters::default(),
+                    })
+                })
+                .collect(),
+            urls_per_shard: max_urls.div_ceil(SHARDS),
+            since: Mutex::new(Utc::now()),
+            slow_clients: AtomicU64::new(0),
+        }
+    }
+
+    fn update(&self, url: &str, f: impl FnOnce(&mut Counters)) {
+        let mut hasher = DefaultHasher::new();
+        url.hash(&mut hasher);
+        let mut shard = self.shards[hasher.finish() as usize % SHARDS]
+            .lock()
+            .unwrap();
+        let shard = &mut *shard;
+        if !shard.urls.contains_key(url) && shard.urls.len() >= self.urls_per_shard {
+            f(&mut shard.overflow);
+            return;
+        }
+        f(shard.urls.entry(url.to_string()).or_default());
+    }
+
+    pub fn record_request(&self, url: &str, cache_hit: bool) {
+        self.update(url, |c| {
+            c.requests += 1;
+            if cache_hit {
+                c.cache_hits += 1;
+            }
+        });
+    }
+
+    // answered with the error of an earlier parse, neither a hit nor a fetch
+    pub fn record_negative_hit(&self, url: &str) {
+        self.update(url, |c| {
+            c.requests += 1;
+            c.negative_hits += 1;
+        });
+    }
+
+    // the time until the body of the last hop was read
+    pub fn record_fetch(&self, url: &str, latency: Duration, status: u16, bytes: usize) {
+        self.update(url, |c| {
+            c.fetches += 1;
+            c.fetch_time += latency;
+            if c.latencies.len() == SAMPLES {
+                c.latencies.pop_front();
+            }
+            c.latencies.push_back(latency);
+            c.last_status = Some(status);
+            c.bytes += bytes as u64;
+        });
+    }
+
+    pub fn record_parse(&self, url: &str, time: Duration, item_count: usize) {
+        self.update(url, |c| {
+            c.parses += 1;
+            c.parse_time += time;
+            c.last_item_count = Some(item_count);
+        });
+    }
+
+    pub fn record_slow_parse(&self, url: &str) {
+        self.update(url, |c| c.slow_parses += 1);
+    }
+
+    // not by url, a slow client is slow whatever it asks for
+    pub fn record_slow_client(&self) {
+        self.slow_clients.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // the `top` most requested urls, the rest summed up without their last values
+    pub fn snapshot(&self, top: usize) -> Snapshot {
+        let mut urls = Vec::new();
+        let mut other = Counters::default();
+        for shard in &self.shards {
+            let shard = shard.lock().unwrap();
+            urls.extend(shard.urls.iter().map(|(u, c)| (u.clone(), c.clone())));
+            other.merge(&shard.overflow);
+        }
+        urls.sort_by(|(a_url, a), (b_url, b)| b.requests.cmp(&a.requests).then(a_url.cmp(b_url)));
+        let feeds = urls
+            .iter()
+            .take(top)
+            .map(|(url, c)| c.snapshot(Some(url.clone())))
+            .collect();
+        for (_, c) in urls.iter().skip(top) {
+            other.merge(c);
+        }
+        let slow_parse_total = other.slow_parses
+            + urls
+                .iter()
+                .take(top)
+                .map(|(_, c)| c.slow_parses)
+                .sum::<u64>();
+        Snapshot {
+            since: self
+                .since
+                .lock()
+                .unwrap()
+                .to_rfc3339_opts(SecondsFormat::Secs, true),
+            feeds,
+            other: other.snapshot(None),
+            slow_parse_total,
+            slow_client_disconnects: self.slow_clients.load(Ordering::Relaxed),
+        }
+    }
+
+    // every url summed up, for the hit ratio of the whole cache
+    pub fn totals(&self) -> FeedStats {
+        let mut totals = Counters::default();
+        for shard in &self.shards {
+            let shard = shard.lock().unwrap();
+            shard.urls.values().for_each(|c| totals.merge(c));
+            totals.merge(&shard.overflow);
+        }
+        totals.snapshot(None)
+    }
+
+    pub fn reset(&self) {
+        for shard in &self.shards {
+            let mut shard = shard.lock().unwrap();
+            shard.urls.clear();
+            shard.overflow = Counters::default();
+        }
+        self.slow_clients.store(0, Ordering::Relaxed);
+        *self.since.lock().unwrap() = Utc::now();
+    }
+}
+
+fn ratio(n: u64, total: u64) -> Option<f64> {
+    (total > 0).then(|| n as f64 / total as f64)
+}
+
+fn average_ms(total: Duration, count: u64) -> Option<f64> {
+    (count > 0).then(|| total.as_secs_f64() * 1_000.0 / count as f64)
+}
+
+// nearest rank
+fn p95_ms(latencies: &VecDeque<Duration>) -> Option<f64> {
+    let mut sorted = latencies.iter().copied().collect::<Vec<Duration>>();
+    sorted.sort();
+    let rank = (sorted.len() * 95).div_ceil(100);
+    sorted
+        .get(rank.checked_sub(1)?)
+        .map(|d| d.as_secs_f64() * 1_000.0)
+}
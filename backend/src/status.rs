@@ -0,0 +1,317 @@
+use crate::config::Config;
+use crate::error::{sanitize, ErrorCode};
+use crate::jobs::JobStats;
+use crate::stats::{FeedStats, Snapshot};
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+
+// the errors `/status` lists, the oldest are let go of
+pub const RECENT_ERRORS: usize = 50;
+
+static RECENT: Mutex<VecDeque<RecentError>> = Mutex::new(VecDeque::new());
+static STARTED: OnceLock<DateTime<Utc>> = OnceLock::new();
+
+#[derive(Debug, Clone)]
+pub struct RecentError {
+    pub at: DateTime<Utc>,
+    pub code: ErrorCode,
+    // see `error::sanitize`, upstreams write some of them
+    pub message: String,
+}
+
+// every error as it's made, whether it ends up answered or in a batch result
+pub fn record_error(code: ErrorCode, message: &str) {
+    let mut recent = RECENT.lock().unwrap();
+    if recent.len() == RECENT_ERRORS {
+        recent.pop_front();
+    }
+    recent.push_back(RecentError {
+        at: Utc::now(),
+        code,
+        message: sanitize(message),
+    });
+}
+
+// newest first
+pub fn recent_errors() -> Vec<RecentError> {
+    RECENT.lock().unwrap().iter().rev().cloned().collect()
+}
+
+// when the routes were first configured, which is as good as when the server started
+pub fn started() -> DateTime<Utc> {
+    *STARTED.get_or_init(Utc::now)
+}
+
+// what the page shows, from what `/stats` answers with and the caches
+pub struct Status<'a> {
+    pub config: &'a Config,
+    pub now: DateTime<Utc>,
+    pub cached_feeds: usize,
+    pub cached_snapshots: usize,
+    pub cached_failures: usize,
+    pub cached_icons: usize,
+    // of every url, the top ones included
+    pub totals: FeedStats,
+    pub snapshot: Snapshot,
+    pub jobs: JobStats,
+    pub errors: Vec<RecentError>,
+}
+
+const TEMPLATE: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <title>rssss status</title>
+  <style>
+    body { font-family: sans-serif; margin: 2em; }
+    table { border-collapse: collapse; margin-bottom: 2em; }
+    th, td { border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: left; }
+    .empty { color: #777; }
+  </style>
+</head>
+<body>
+  <h1>rssss {version}</h1>
+  <p>Up for {uptime}, since {started}</p>
+  <h2>Configuration</h2>
+  {config}
+  <h2>Caches</h2>
+  {caches}
+  <h2>Jobs</h2>
+  {jobs}
+  <h2>Feeds</h2>
+  {feeds}
+  <h2>Recent errors</h2>
+  {errors}
+</body>
+</html>
+"##;
+
+pub fn render(status: &Status) -> String {
+    let started = started();
+    fill(
+        TEMPLATE,
+        &[
+            ("version", env!("CARGO_PKG_VERSION").to_string()),
+            ("uptime", uptime(status.now - started)),
+            (
+                "started",
+                started.to_rfc3339_opts(SecondsFormat::Secs, true),
+            ),
+            ("config", config_table(status.config)),
+            ("caches", caches_table(status)),
+            ("jobs", jobs_table(&status.jobs)),
+            ("feeds", feeds_table(&status.snapshot)),
+            ("errors", errors_table(&status.errors)),
+        ],
+    )
+}
+
+// in a single pass, a url that reads like a placeholder stays as it is
+fn fill(template: &str, values: &[(&str, String)]) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(at) = rest.find('{') {
+        filled.push_str(&rest[..at]);
+        rest = &rest[at + 1..];
+        let value = values.iter().find(|(name, _)| {
+            rest.strip_prefix(name)
+                .is_some_and(|after| after.starts_with('}'))
+        });
+        match value {
+            Some((name, value)) => {
+                filled.push_str(value);
+                rest = &rest[name.len() + 1..];
+            }
+            None => filled.push('{'),
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+fn uptime(elapsed: chrono::Duration) -> String {
+    let secs = elapsed.num_seconds().max(0);
+    let (days, hours, minutes) = (secs / 86_400, secs % 86_400 / 3_600, secs % 3_600 / 60);
+    match (days, hours, minutes) {
+        (0, 0, 0) => format!("{}s", secs),
+        (0, 0, _) => format!("{}m {}s", minutes, secs % 60),
+        (0, _, _) => format!("{}h {}m", hours, minutes),
+        _ => format!("{}d {}h", days, hours),
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn table(head: &[&str], rows: Vec<Vec<String>>, empty: &str) -> String {
+    if rows.is_empty() {
+        return format!("<p class=\"empty\">{}</p>", empty);
+    }
+    let head = head
+        .iter()
+        .map(|h| format!("<th>{}</th>", h))
+        .collect::<String>();
+    let rows = rows
+        .iter()
+        .map(|row| {
+            let cells = row
+                .iter()
+                .map(|cell| format!("<td>{}</td>", escape(cell)))
+                .collect::<String>();
+            format!("<tr>{}</tr>", cells)
+        })
+        .collect::<String>();
+    format!("<table><tr>{}</tr>{}</table>", head, rows)
+}
+
+fn on_off(on: bool) -> String {
+    (if on { "on" } else { "off" }).to_string()
+}
+
+// the keys counted rather than shown, and of paths and urls only whether they are set
+fn config_table(config: &Config) -> String {
+    let rows = [
+        ("feed cache entries", config.cache.capacity.to_string()),
+        (
+            "feed cache ttl",
+            format!("{}s", config.cache.default_ttl.as_secs()),
+        ),
+        ("disk cache", on_off(config.cache.on_disk())),
+        ("items per response", config.window_items.to_string()),
+        ("items per feed", config.max_items.to_string()),
+        (
+            "request timeout",
+            format!("{}s", config.request_timeout.as_secs()),
+        ),
+        (
+            "write timeout",
+            format!("{}s", config.clients.write.as_secs()),
+        ),
+        (
+            "daily quota",
+            config
+                .daily_quota
+                .map_or("none".to_string(), |q| format!("{} bytes", q)),
+        ),
+        ("api keys", config.api_keys.len().to_string()),
+        ("cors origins", config.cors_origins.len().to_string()),
+        ("private hosts", on_off(config.allow_private_hosts)),
+        ("robots.txt", on_off(config.respect_robots)),
+        ("selftest", on_off(config.selftest_url.is_some())),
+        ("fixtures", on_off(config.fixture_dir.is_some())),
+        ("recording", on_off(config.record_dir.is_some())),
+        ("job workers", config.jobs.workers.to_string()),
+    ];
+    table(
+        &["setting", "value"],
+        rows.into_iter()
+            .map(|(name, value)| vec![name.to_string(), value])
+            .collect(),
+        "",
+    )
+}
+
+fn ratio(ratio: Option<f64>) -> String {
+    ratio.map_or("-".to_string(), |r| format!("{:.1}%", r * 100.0))
+}
+
+fn caches_table(status: &Status) -> String {
+    let rows = vec![
+        vec!["feeds".to_string(), status.cached_feeds.to_string()],
+        vec!["snapshots".to_string(), status.cached_snapshots.to_string()],
+        vec!["failures".to_string(), status.cached_failures.to_string()],
+        vec!["icons".to_string(), status.cached_icons.to_string()],
+        vec![
+            "hit ratio".to_string(),
+            ratio(status.totals.cache_hit_ratio),
+        ],
+    ];
+    table(&["cache", "entries"], rows, "")
+}
+
+fn jobs_table(jobs: &JobStats) -> String {
+    let summary = format!(
+        "<p>{} running, {} queued</p>",
+        jobs.running, jobs.queue_depth
+    );
+    let rows = jobs
+        .outcomes
+        .iter()
+        .map(|(kind, o)| {
+            [
+                kind.to_string(),
+                o.queued.to_string(),
+                o.succeeded.to_string(),
+                o.failed.to_string(),
+                o.timed_out.to_string(),
+                o.rejected.to_string(),
+            ]
+            .to_vec()
+        })
+        .collect();
+    summary
+        + &table(
+            &[
+                "kind",
+                "queued",
+                "succeeded",
+                "failed",
+                "timed out",
+                "rejected",
+            ],
+            rows,
+            "No jobs run yet",
+        )
+}
+
+// what each feed last answered with, most requested first
+fn feeds_table(snapshot: &Snapshot) -> String {
+    let rows = snapshot
+        .feeds
+        .iter()
+        .map(|feed| {
+            vec![
+                feed.url.clone().unwrap_or_default(),
+                feed.requests.to_string(),
+                ratio(feed.cache_hit_ratio),
+                feed.last_status.map_or("-".to_string(), |s| s.to_string()),
+                feed.last_item_count
+                    .map_or("-".to_string(), |c| c.to_string()),
+            ]
+        })
+        .collect();
+    table(
+        &["url", "requests", "hit ratio", "last status", "last items"],
+        rows,
+        "No feeds requested yet",
+    )
+}
+
+fn errors_table(errors: &[RecentError]) -> String {
+    let rows = errors
+        .iter()
+        .map(|e| {
+            vec![
+                e.at.to_rfc3339_opts(SecondsFormat::Secs, true),
+                serde_json::to_value(e.code)
+                    .ok()
+                    .and_then(|code| code.as_str().map(str::to_string))
+                    .unwrap_or_default(),
+                e.message.clone(),
+            ]
+        })
+        .collect();
+    table(&["at", "code", "message"], rows, "No errors recorded")
+}
@@ -0,0 +1,104 @@
+use crate::rss::Feed;
+use actix_web::http::header::{self, HeaderName};
+use actix_web::web::Bytes;
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// bumped whenever the layout of `Stored` or what the parsers produce changes,
+// files of other versions are treated as missing and overwritten
+pub const VERSION: u32 = 10;
+
+// a feed as the disk cache keeps it, fetching revalidates one whether or not the cache is built
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Stored {
+    pub version: u32,
+    pub url: String,
+    // seconds since the epoch, the entry is fresh until `fetched_at + ttl`
+    pub fetched_at: u64,
+    pub ttl: u64,
+    pub upstream_headers: BTreeMap<String, String>,
+    // the last successful body as received, base64
+    pub body: String,
+    pub feed: Feed,
+    // the item descriptions as written, which items don't serialize
+    pub markups: Vec<String>,
+    // the HTTP version of the last fetch, absent from entries written before it was kept
+    #[serde(default)]
+    pub upstream_protocol: Option<String>,
+    #[serde(default)]
+    pub upstream_ip: Option<IpAddr>,
+}
+
+impl Stored {
+    pub fn new(
+        url: String,
+        ttl: Duration,
+        upstream_headers: BTreeMap<String, String>,
+        body: &Bytes,
+        feed: Feed,
+    ) -> Stored {
+        Stored {
+            version: VERSION,
+            url,
+            fetched_at: now(),
+            ttl: ttl.as_secs(),
+            upstream_headers,
+            body: base64::encode(body),
+            markups: feed.items.iter().map(|i| i.markup().to_string()).collect(),
+            feed,
+            upstream_protocol: None,
+            upstream_ip: None,
+        }
+    }
+
+    // `fetched_at` as a time
+    pub fn fetched(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.fetched_at as i64, 0).unwrap_or_default()
+    }
+
+    // what is left of the ttl, None once it's over
+    pub fn remaining(&self) -> Option<Duration> {
+        (self.fetched_at + self.ttl)
+            .checked_sub(now())
+            .filter(|s| *s > 0)
+            .map(Duration::from_secs)
+    }
+
+    // the request headers revalidating this copy
+    pub fn conditions(&self) -> Vec<(HeaderName, String)> {
+        let mut conditions = Vec::new();
+        if let Some(etag) = self.upstream_headers.get("etag") {
+            conditions.push((header::IF_NONE_MATCH, etag.clone()));
+        }
+        if let Some(last_modified) = self.upstream_headers.get("last-modified") {
+            conditions.push((header::IF_MODIFIED_SINCE, last_modified.clone()));
+        }
+        conditions
+    }
+
+    // fresh again after a 304, which may come with new validators
+    pub fn revalidated(&self, ttl: Duration, headers: BTreeMap<String, String>) -> Stored {
+        let mut upstream_headers = self.upstream_headers.clone();
+        for name in ["etag", "last-modified", "cache-control"] {
+            if let Some(value) = headers.get(name) {
+                upstream_headers.insert(name.to_string(), value.clone());
+            }
+        }
+        Stored {
+            fetched_at: now(),
+            ttl: ttl.as_secs(),
+            upstream_headers,
+            ..self.clone()
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
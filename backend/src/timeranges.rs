@@ -0,0 +1,72 @@
+use crate::dates::{parse_date, TzOffset};
+use crate::rss::Rss;
+use chrono::{DateTime, Days, NaiveDate, TimeZone, Utc};
+use std::str::FromStr;
+
+// a day in the zone of `tz`, counted back from the one `Config::clock` is in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Range {
+    Today,
+    Yesterday,
+}
+
+impl FromStr for Range {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Range, String> {
+        match s {
+            "today" => Ok(Range::Today),
+            "yesterday" => Ok(Range::Yesterday),
+            _ => Err(format!("unsupported range: {}", s)),
+        }
+    }
+}
+
+// from `since` up to but not including `until`, so an item at midnight is in the day it starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeRange {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+}
+
+impl TimeRange {
+    // the whole of a local date, an offset has no DST so every day is 24 hours long
+    pub fn day(date: NaiveDate, tz: TzOffset) -> TimeRange {
+        let midnight = |date: NaiveDate| {
+            tz.0.from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        TimeRange {
+            since: midnight(date),
+            until: midnight(date + Days::new(1)),
+        }
+    }
+
+    pub fn of(range: Range, now: DateTime<Utc>, tz: TzOffset) -> TimeRange {
+        let today = now.with_timezone(&tz.0).date_naive();
+        let date = match range {
+            Range::Today => today,
+            Range::Yesterday => today - Days::new(1),
+        };
+        TimeRange::day(date, tz)
+    }
+
+    pub fn contains(&self, date: DateTime<Utc>) -> bool {
+        self.since <= date && date < self.until
+    }
+}
+
+// the items published within the range, one without a parsable date or with a suspect one
+// can't be said to be and is left out
+pub fn filter(items: Vec<Rss>, range: &TimeRange) -> Vec<Rss> {
+    items
+        .into_iter()
+        .filter(|item| {
+            item.pub_date()
+                .filter(|_| !item.date_suspect())
+                .and_then(parse_date)
+                .is_some_and(|date| range.contains(date.with_timezone(&Utc)))
+        })
+        .collect()
+}
@@ -0,0 +1,129 @@
+use actix_web::http::header::{HeaderMap, HeaderName};
+use rand::Rng;
+
+pub const TRACEPARENT: &str = "traceparent";
+pub const TRACESTATE: &str = "tracestate";
+// what the spec asks vendors to accept at least, longer ones are dropped rather than cut
+const TRACESTATE_LIMIT: usize = 512;
+const SAMPLED: u8 = 0x01;
+
+// the W3C trace context of one of our spans, https://www.w3.org/TR/trace-context/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceContext {
+    trace_id: String,
+    span_id: String,
+    parent_id: Option<String>,
+    flags: u8,
+    state: Option<String>,
+}
+
+impl TraceContext {
+    // the span of a request, in the client's trace when it sent a valid traceparent
+    pub fn from_headers(headers: &HeaderMap) -> TraceContext {
+        let parent = headers
+            .get(TRACEPARENT)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_traceparent);
+        match parent {
+            Some((trace_id, parent_id, flags)) => {
+                // several tracestate headers are one list
+                let state = headers
+                    .get_all(TRACESTATE)
+                    .filter_map(|v| v.to_str().ok())
+                    .map(str::trim)
+                    .filter(|v| !v.is_empty())
+                    .collect::<Vec<&str>>()
+                    .join(",");
+                TraceContext {
+                    trace_id,
+                    span_id: random_id::<8>(),
+                    parent_id: Some(parent_id),
+                    flags,
+                    state: Some(state).filter(|s| !s.is_empty() && s.len() <= TRACESTATE_LIMIT),
+                }
+            }
+            None => TraceContext::root(),
+        }
+    }
+
+    pub fn root() -> TraceContext {
+        TraceContext {
+            trace_id: random_id::<16>(),
+            span_id: random_id::<8>(),
+            parent_id: None,
+            flags: SAMPLED,
+            state: None,
+        }
+    }
+
+    pub fn child(&self) -> TraceContext {
+        TraceContext {
+            span_id: random_id::<8>(),
+            parent_id: Some(self.span_id.clone()),
+            ..self.clone()
+        }
+    }
+
+    pub fn trace_id(&self) -> &str {
+        &self.trace_id
+    }
+
+    pub fn span_id(&self) -> &str {
+        &self.span_id
+    }
+
+    pub fn parent_id(&self) -> Option<&str> {
+        self.parent_id.as_deref()
+    }
+
+    pub fn traceparent(&self) -> String {
+        format!("00-{}-{}-{:02x}", self.trace_id, self.span_id, self.flags)
+    }
+
+    // to send upstream, naming this span as the parent of the feed host's
+    pub fn headers(&self) -> Vec<(HeaderName, String)> {
+        let mut headers = vec![(HeaderName::from_static(TRACEPARENT), self.traceparent())];
+        if let Some(state) = &self.state {
+            headers.push((HeaderName::from_static(TRACESTATE), state.clone()));
+        }
+        headers
+    }
+}
+
+// the trace id, parent id and flags of a traceparent. later versions may add fields,
+// version 00 may not, and all-zero ids are invalid
+pub fn parse_traceparent(value: &str) -> Option<(String, String, u8)> {
+    let parts = value.trim().split('-').collect::<Vec<&str>>();
+    let (version, trace_id, parent_id, flags) = match parts[..] {
+        [version, trace_id, parent_id, flags, ..] => (version, trace_id, parent_id, flags),
+        _ => return None,
+    };
+    let valid = is_hex(version, 2)
+        && version != "ff"
+        && (version != "00" || parts.len() == 4)
+        && is_hex(trace_id, 32)
+        && is_hex(parent_id, 16)
+        && is_hex(flags, 2)
+        && trace_id.bytes().any(|b| b != b'0')
+        && parent_id.bytes().any(|b| b != b'0');
+    if !valid {
+        return None;
+    }
+    let flags = u8::from_str_radix(flags, 16).ok()?;
+    Some((trace_id.to_string(), parent_id.to_string(), flags))
+}
+
+fn is_hex(s: &str, len: usize) -> bool {
+    s.len() == len && s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+fn random_id<const N: usize>() -> String {
+    let mut rng = rand::thread_rng();
+    loop {
+        let mut bytes = [0u8; N];
+        rng.fill(&mut bytes[..]);
+        if bytes.iter().any(|b| *b != 0) {
+            return bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        }
+    }
+}
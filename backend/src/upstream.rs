@@ -0,0 +1,258 @@
+use crate::hosts::private_address;
+use crate::redirects::RedirectPolicy;
+use actix_service::Service;
+use actix_tls::connect::{ConnectError, ConnectInfo, Connection};
+use actix_web::http::{Uri, Version};
+use actix_web::rt::net::TcpStream;
+use actix_web::rt::time::sleep;
+use actix_web::web;
+use awc::{Client, ClientBuilder, Connector};
+use futures::future::{self, Either, LocalBoxFuture};
+use futures::stream::{FuturesUnordered, StreamExt};
+use log::debug;
+use std::collections::HashMap;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+// how long an attempt may stall before the next address is tried alongside, as in RFC 8305
+pub const ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+// how feed fetches reuse their connections
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpstreamConfig {
+    // an idle connection is closed after this long
+    pub keep_alive: Duration,
+    // connections open at once, awc counts them per scheme rather than per host
+    pub max_connections: usize,
+    pub ip_preference: IpPreference,
+    // for the fetch of a feed, see `redirects`
+    pub redirects: RedirectPolicy,
+    // RSSSS_UPSTREAM_STALL_SECS, a feed body that sends nothing for this long is given up,
+    // see `stall`
+    pub stall: Duration,
+}
+
+impl Default for UpstreamConfig {
+    fn default() -> UpstreamConfig {
+        UpstreamConfig {
+            keep_alive: Duration::from_secs(15),
+            max_connections: 100,
+            ip_preference: IpPreference::Auto,
+            redirects: RedirectPolicy::default(),
+            stall: Duration::from_secs(5),
+        }
+    }
+}
+
+impl UpstreamConfig {
+    // awc would take 0 connections as no limit and a 0 keep-alive as no reuse, neither is meant
+    pub fn new(keep_alive_secs: u64, max_connections: usize) -> Result<UpstreamConfig, String> {
+        if keep_alive_secs == 0 || keep_alive_secs > 3_600 {
+            return Err(format!(
+                "keep-alive must be 1 to 3600 seconds: {}",
+                keep_alive_secs
+            ));
+        }
+        if max_connections == 0 {
+            return Err("max connections must be 1 or more".to_string());
+        }
+        Ok(UpstreamConfig {
+            keep_alive: Duration::from_secs(keep_alive_secs),
+            max_connections,
+            ..UpstreamConfig::default()
+        })
+    }
+}
+
+// which address family is dialed first, the other is still tried when it fails or stalls
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IpPreference {
+    // alternating, IPv6 first
+    #[default]
+    Auto,
+    Ipv4,
+    Ipv6,
+}
+
+impl FromStr for IpPreference {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<IpPreference, String> {
+        match s {
+            "auto" => Ok(IpPreference::Auto),
+            "ipv4" => Ok(IpPreference::Ipv4),
+            "ipv6" => Ok(IpPreference::Ipv6),
+            _ => Err(format!("unsupported ip preference: {}", s)),
+        }
+    }
+}
+
+static CONFIG: OnceLock<UpstreamConfig> = OnceLock::new();
+
+// the address last connected to for each host and port, pooled connections reuse it
+static DIALED: Mutex<Option<HashMap<(String, u16), SocketAddr>>> = Mutex::new(None);
+
+thread_local! {
+    // a client per worker thread, so its connections are reused across requests
+    static CLIENT: Client = build(CONFIG.get().cloned().unwrap_or_default());
+}
+
+// takes effect for the clients built after it, the first call wins
+pub fn configure(config: UpstreamConfig) {
+    let _ = CONFIG.set(config);
+}
+
+pub fn client() -> Client {
+    CLIENT.with(Client::clone)
+}
+
+// for the one-off clients of secondary fetches, `guard` refuses names with private addresses
+pub fn connector(guard: bool) -> Connector<Dialer> {
+    let preference = CONFIG.get().map(|c| c.ip_preference).unwrap_or_default();
+    Connector::new().connector(Dialer { preference, guard })
+}
+
+// the address a url was last fetched from
+pub fn dialed(host: &str, port: u16) -> Option<SocketAddr> {
+    DIALED
+        .lock()
+        .unwrap()
+        .as_ref()?
+        .get(&(host.to_string(), port))
+        .copied()
+}
+
+// HTTP/2 when the server agrees to it over ALPN, HTTP/1.1 otherwise
+fn build(config: UpstreamConfig) -> Client {
+    let connector = Connector::new()
+        .connector(Dialer {
+            preference: config.ip_preference,
+            guard: false,
+        })
+        .max_http_version(Version::HTTP_2)
+        .conn_keep_alive(config.keep_alive)
+        // lives at least as long as it may idle
+        .conn_lifetime(config.keep_alive.max(Duration::from_secs(75)))
+        .limit(config.max_connections);
+    ClientBuilder::new()
+        .connector(connector)
+        .add_default_header(("User-Agent", crate::USER_AGENT))
+        .disable_redirects()
+        .timeout(Duration::from_secs(60))
+        .finish()
+}
+
+// resolves to every address of both families and races them
+#[derive(Debug, Clone)]
+pub struct Dialer {
+    preference: IpPreference,
+    guard: bool,
+}
+
+impl Service<ConnectInfo<Uri>> for Dialer {
+    type Response = Connection<Uri, TcpStream>;
+    type Error = ConnectError;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::always_ready!();
+
+    fn call(&self, req: ConnectInfo<Uri>) -> Self::Future {
+        let dialer = self.clone();
+        Box::pin(async move {
+            let host = req.hostname().to_string();
+            let port = req.port();
+            let lookup = (host.clone(), port);
+            let resolved = web::block(move || lookup.to_socket_addrs().map(Vec::from_iter))
+                .await
+                .map_err(|e| ConnectError::Resolver(Box::new(e)))?
+                .map_err(|e| ConnectError::Resolver(Box::new(e)))?;
+            let addrs = candidates(&host, resolved, dialer.preference, dialer.guard)
+                .map_err(|e| ConnectError::Resolver(Box::new(e)))?;
+            let (stream, addr) = race(addrs).await.map_err(ConnectError::Io)?;
+            debug!("connected to {} at {}", host, addr);
+            DIALED
+                .lock()
+                .unwrap()
+                .get_or_insert_with(HashMap::new)
+                .insert((host, port), addr);
+            Ok(Connection::new(req.request().clone(), stream))
+        })
+    }
+}
+
+// the resolved addresses in the order to dial them, refused when `guard` is set and any is private
+pub fn candidates(
+    host: &str,
+    resolved: Vec<SocketAddr>,
+    preference: IpPreference,
+    guard: bool,
+) -> io::Result<Vec<SocketAddr>> {
+    if resolved.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} has no address", host),
+        ));
+    }
+    if let Some(ip) = private_address(&resolved).filter(|_| guard) {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} resolves to a private address: {}", host, ip),
+        ));
+    }
+    let (v6, v4): (Vec<SocketAddr>, Vec<SocketAddr>) =
+        resolved.into_iter().partition(SocketAddr::is_ipv6);
+    Ok(match preference {
+        IpPreference::Ipv4 => v4.into_iter().chain(v6).collect(),
+        IpPreference::Ipv6 => v6.into_iter().chain(v4).collect(),
+        IpPreference::Auto => {
+            let mut ordered = Vec::with_capacity(v6.len() + v4.len());
+            let (mut v6, mut v4) = (v6.into_iter(), v4.into_iter());
+            loop {
+                match (v6.next(), v4.next()) {
+                    (None, None) => break,
+                    (a, b) => ordered.extend(a.into_iter().chain(b)),
+                }
+            }
+            ordered
+        }
+    })
+}
+
+// the next address is dialed as soon as an attempt fails or after it stalled for ATTEMPT_DELAY,
+// the first connection made wins
+async fn race(addrs: Vec<SocketAddr>) -> io::Result<(TcpStream, SocketAddr)> {
+    let mut addrs = addrs.into_iter();
+    let mut attempts = FuturesUnordered::new();
+    let mut last_error = None;
+    loop {
+        if let Some(addr) = addrs.next() {
+            attempts.push(async move { (TcpStream::connect(addr).await, addr) });
+        } else if attempts.is_empty() {
+            return Err(
+                last_error.unwrap_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no address"))
+            );
+        }
+        let more = addrs.len() > 0;
+        let next = attempts.next();
+        let outcome = if more {
+            match future::select(next, Box::pin(sleep(ATTEMPT_DELAY))).await {
+                Either::Left((outcome, _)) => outcome,
+                // stalled, the next address joins the race
+                Either::Right(_) => continue,
+            }
+        } else {
+            next.await
+        };
+        match outcome {
+            Some((Ok(stream), addr)) => return Ok((stream, addr)),
+            Some((Err(e), addr)) => {
+                debug!("failed to connect to {}: {}", addr, e);
+                last_error = Some(e);
+            }
+            None => (),
+        }
+    }
+}
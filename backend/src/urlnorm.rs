@@ -0,0 +1,65 @@
+use percent_encoding::percent_decode;
+use url::{form_urlencoded, Url};
+
+// parameters that only say where a link was clicked, matched case-insensitively
+const TRACKING: [&str; 9] = [
+    "fbclid", "gclid", "dclid", "msclkid", "yclid", "igshid", "mc_cid", "mc_eid", "_ga",
+];
+const TRACKING_PREFIX: &str = "utm_";
+
+// what "the same feed" is keyed by, in the caches, the negative cache, snapshots, stats and
+// source buckets. the url is still fetched as given, the key only decides what is shared.
+//
+// the url crate already lowercases the scheme and host, writes the host in punycode,
+// drops the default port and gives an empty path its slash. on top of that the fragment
+// and tracking parameters are dropped and the rest sorted by name, repeated names keeping
+// their order. the query is decoded once and encoded again as a form, so `+` and `%20`
+// or `%2f` and `%2F` are one key while `%2520` stays apart from `%20`.
+//
+// keys are stable across releases, the disk cache and the buckets clients color feeds by
+// rely on them. tests/urlnorm.rs pins them, a change there is a breaking change
+pub fn canonical_feed_key(url: &Url) -> String {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    let query = url.query().map(canonical_query).unwrap_or_default();
+    url.set_query(if query.is_empty() { None } else { Some(&query) });
+    url.to_string()
+}
+
+fn canonical_query(query: &str) -> String {
+    let mut pairs = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((name, value)) => (decode(name), decode(value)),
+            None => (decode(pair), Vec::new()),
+        })
+        .filter(|(name, _)| !is_tracking(name))
+        .collect::<Vec<(Vec<u8>, Vec<u8>)>>();
+    pairs.sort_by(|(a, _), (b, _)| a.cmp(b));
+    pairs
+        .iter()
+        .map(|(name, value)| {
+            let name = form_urlencoded::byte_serialize(name).collect::<String>();
+            if value.is_empty() {
+                // `?rss` and `?rss=` are one feed
+                name
+            } else {
+                let value = form_urlencoded::byte_serialize(value).collect::<String>();
+                format!("{}={}", name, value)
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+// bytes rather than text, invalid UTF-8 must not turn two parameters into one
+fn decode(s: &str) -> Vec<u8> {
+    let s = s.replace('+', " ");
+    percent_decode(s.as_bytes()).collect()
+}
+
+fn is_tracking(name: &[u8]) -> bool {
+    let name = String::from_utf8_lossy(name).to_ascii_lowercase();
+    name.starts_with(TRACKING_PREFIX) || TRACKING.contains(&name.as_str())
+}
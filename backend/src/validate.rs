@@ -0,0 +1,569 @@
+use crate::check::BODY_LIMIT;
+use crate::dates::parse_date;
+use crate::deadline::{Phase, Progress};
+use crate::error::{
+    ApiError, ErrorCode, InvalidRedirectError, RedirectLimitError, UpstreamStatusError,
+};
+use crate::headers;
+use crate::hosts::{validate_url, HostFilter};
+use crate::rss::{self, DroppedItem, Feed, FieldLimits, Generator, ParseOptions, Rss, Syntax};
+use actix_web::http::header::{self, HeaderName};
+use actix_web::http::StatusCode;
+use actix_web::web;
+use awc::SendClientRequest;
+use serde_derive::Serialize;
+use std::collections::HashMap;
+use url::Url;
+use xml::attribute::OwnedAttribute;
+use xml::common::{Position, TextPosition};
+use xml::name::OwnedName;
+use xml::reader::{EventReader, XmlEvent};
+
+const REDIRECT_LIMIT: u8 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    // rssss can't serve the feed
+    Error,
+    // served, but readers lose something
+    Warning,
+    // worth knowing, nothing is lost
+    Info,
+}
+
+// what a finding is about, stable while the wording of the messages is not
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintCode {
+    NoItems,
+    MissingDate,
+    UnparsableDate,
+    MissingLink,
+    MissingGuid,
+    DuplicateGuid,
+    UnescapedHtml,
+    FieldTooLong,
+    DroppedItem,
+    DocumentWarning,
+}
+
+impl LintCode {
+    pub const ALL: [LintCode; 10] = [
+        LintCode::NoItems,
+        LintCode::MissingDate,
+        LintCode::UnparsableDate,
+        LintCode::MissingLink,
+        LintCode::MissingGuid,
+        LintCode::DuplicateGuid,
+        LintCode::UnescapedHtml,
+        LintCode::FieldTooLong,
+        LintCode::DroppedItem,
+        LintCode::DocumentWarning,
+    ];
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            LintCode::MissingGuid => Severity::Info,
+            _ => Severity::Warning,
+        }
+    }
+}
+
+// a document that can't be parsed is reported with the codes of the error `/feed` would answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum FindingCode {
+    Parse(ErrorCode),
+    Lint(LintCode),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    pub code: FindingCode,
+    pub message: String,
+    // 1-based, of the item the finding is about
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item: Option<usize>,
+    // 1-based, where in the document, of the item when about one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedFormat {
+    Rss,
+    Atom,
+    Rdf,
+}
+
+// what `/feed/validate` makes of a feed, the items themselves are left out
+#[derive(Debug, Serialize)]
+pub struct ValidationReport {
+    pub url: String,
+    pub final_url: String,
+    // no finding of severity error, the feed is served by `/feed`
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<FeedFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+    // parser quirks go with the software that wrote the feed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generator: Option<Generator>,
+    pub item_count: usize,
+    pub items_without_date: usize,
+    pub items_without_link: usize,
+    pub items_without_guid: usize,
+    pub unparsable_dates: usize,
+    pub unescaped_html: usize,
+    pub oversized_fields: usize,
+    pub duplicate_guids: usize,
+    // items `/feed` leaves out, see `rss::DroppedItem`
+    pub dropped_items: usize,
+    pub findings: Vec<Finding>,
+}
+
+// an upstream status without a document is passed through, as `/feed` does
+pub enum Validated {
+    Report(Box<ValidationReport>),
+    Status(StatusCode),
+}
+
+// fetched past the caches, a report about the document must be about the document served now
+pub async fn validate_feed(
+    url: Url,
+    f: fn(&str, &[(HeaderName, String)]) -> SendClientRequest,
+    host_filter: &HostFilter,
+    parse_blocking_threshold: usize,
+    limits: FieldLimits,
+    progress: &Progress,
+) -> Result<Validated, ApiError> {
+    let requested = url.to_string();
+    let mut url = url;
+    let mut redirects = 0;
+    progress.set(Phase::Fetching);
+    let mut res = loop {
+        let res = f(url.as_str(), &[]).await?;
+        if !res.status().is_redirection() {
+            break res;
+        }
+        if redirects == REDIRECT_LIMIT {
+            return Err(RedirectLimitError {
+                limit: REDIRECT_LIMIT,
+            }
+            .into());
+        }
+        let location = headers::location(res.headers())?;
+        url = match location.as_deref().map(|l| url.join(l.trim())) {
+            Some(Ok(next)) => validate_url(next.as_str(), host_filter)?,
+            _ => return Err(InvalidRedirectError { location }.into()),
+        };
+        redirects += 1;
+        progress.set(Phase::Redirect(redirects));
+    };
+    match res.status() {
+        StatusCode::GONE | StatusCode::NOT_FOUND | StatusCode::FORBIDDEN => {
+            return Err(UpstreamStatusError {
+                status: res.status(),
+                user_agent: "rssss".to_string(),
+            }
+            .into())
+        }
+        status if !status.is_success() => return Ok(Validated::Status(status)),
+        _ => (),
+    }
+    let body = res.body().limit(BODY_LIMIT).await?;
+    progress.add_downloaded(body.len());
+    progress.set(Phase::Parsing);
+    let content_type = headers::value(res.headers(), &header::CONTENT_TYPE, &mut Vec::new());
+    let parsed = rss::parse_rss_async(
+        body.clone(),
+        content_type.as_deref(),
+        parse_blocking_threshold,
+        ParseOptions {
+            limits,
+            ..ParseOptions::default()
+        },
+        None,
+    )
+    .await;
+    let scan = match rss::syntax(content_type.as_deref(), &body) {
+        Syntax::Json => Scan::default(),
+        Syntax::Xml if body.len() < parse_blocking_threshold => scan(&body),
+        Syntax::Xml => web::block(move || scan(&body)).await?,
+    };
+    Ok(Validated::Report(Box::new(report(
+        requested,
+        url.to_string(),
+        scan,
+        parsed,
+    ))))
+}
+
+// the findings of the parse and the scan, parse failures first
+fn report(
+    url: String,
+    final_url: String,
+    scan: Scan,
+    parsed: Result<Feed, ApiError>,
+) -> ValidationReport {
+    let mut report = ValidationReport {
+        url,
+        final_url,
+        valid: parsed.is_ok(),
+        format: scan.format,
+        version: scan.version.clone(),
+        encoding: scan.encoding.clone(),
+        generator: None,
+        item_count: scan.items.len(),
+        items_without_date: 0,
+        items_without_link: 0,
+        items_without_guid: 0,
+        unparsable_dates: 0,
+        unescaped_html: 0,
+        oversized_fields: 0,
+        duplicate_guids: 0,
+        dropped_items: 0,
+        findings: Vec::new(),
+    };
+    let feed = match parsed {
+        Ok(feed) => feed,
+        Err(e) => {
+            report.findings = parse_findings(&e, &scan);
+            lint_scan(&mut report, &scan);
+            return report;
+        }
+    };
+    report.item_count = feed.items.len();
+    report.generator = feed.channel.generator.clone();
+    if feed.items.is_empty() {
+        report.findings.push(lint(
+            LintCode::NoItems,
+            "the feed has no items".to_string(),
+            None,
+            &scan,
+        ));
+    }
+    for (i, item) in feed.items.iter().enumerate() {
+        lint_item(&mut report, i, item, &scan);
+    }
+    lint_scan(&mut report, &scan);
+    let dropped = feed
+        .dropped_items
+        .iter()
+        .map(DroppedItem::warning)
+        .collect::<Vec<String>>();
+    for warning in &feed.warnings {
+        let item = warned_item(warning);
+        let code = if warning.contains(" cut to ") {
+            report.oversized_fields += 1;
+            LintCode::FieldTooLong
+        } else if dropped.contains(warning) {
+            report.dropped_items += 1;
+            LintCode::DroppedItem
+        } else {
+            LintCode::DocumentWarning
+        };
+        report
+            .findings
+            .push(lint(code, warning.clone(), item, &scan));
+    }
+    // in document order, the ones about the whole feed first
+    report
+        .findings
+        .sort_by_key(|f| (f.severity != Severity::Error, f.item.unwrap_or(0)));
+    report
+}
+
+// each parser that didn't take the root says so, those are noise once the format is known
+fn parse_findings(e: &ApiError, scan: &Scan) -> Vec<Finding> {
+    let mut findings: Vec<Finding> = Vec::new();
+    for (code, message) in e.codes().iter().zip(e.messages()) {
+        let other_format = scan.format.is_some()
+            && matches!(
+                code,
+                ErrorCode::InvalidRootElement | ErrorCode::InvalidRootNamespace
+            );
+        if other_format {
+            continue;
+        }
+        let (message, position) = match (code, &scan.error) {
+            (ErrorCode::XmlParseError, Some((message, position))) => {
+                (message.clone(), Some(*position))
+            }
+            _ => (message.clone(), None),
+        };
+        if findings.iter().any(|f| f.message == message) {
+            continue;
+        }
+        findings.push(Finding {
+            severity: Severity::Error,
+            code: FindingCode::Parse(*code),
+            message,
+            item: None,
+            line: position.map(|p| p.row + 1),
+            column: position.map(|p| p.column + 1),
+        });
+    }
+    findings
+}
+
+fn lint_item(report: &mut ValidationReport, i: usize, item: &Rss, scan: &Scan) {
+    match item.pub_date() {
+        None => {
+            report.items_without_date += 1;
+            report.findings.push(lint(
+                LintCode::MissingDate,
+                "the item has no date".to_string(),
+                Some(i + 1),
+                scan,
+            ));
+        }
+        Some(date) if parse_date(date).is_none() => {
+            report.unparsable_dates += 1;
+            report.findings.push(lint(
+                LintCode::UnparsableDate,
+                format!("the date \"{}\" is neither RFC 2822 nor RFC 3339", date),
+                Some(i + 1),
+                scan,
+            ));
+        }
+        Some(_) => (),
+    }
+    if item.link().trim().is_empty() {
+        report.items_without_link += 1;
+        report.findings.push(lint(
+            LintCode::MissingLink,
+            "the item has no link".to_string(),
+            Some(i + 1),
+            scan,
+        ));
+    }
+}
+
+// what only the document itself tells, the parsed items keep no guids or markup
+fn lint_scan(report: &mut ValidationReport, scan: &Scan) {
+    let mut seen = HashMap::new();
+    for (i, item) in scan.items.iter().enumerate() {
+        match item
+            .guid
+            .as_deref()
+            .map(str::trim)
+            .filter(|g| !g.is_empty())
+        {
+            None => {
+                report.items_without_guid += 1;
+                report.findings.push(lint(
+                    LintCode::MissingGuid,
+                    "the item has no guid, readers tell it apart by its link".to_string(),
+                    Some(i + 1),
+                    scan,
+                ));
+            }
+            Some(guid) => match seen.get(guid) {
+                Some(first) => {
+                    report.duplicate_guids += 1;
+                    report.findings.push(lint(
+                        LintCode::DuplicateGuid,
+                        format!("the guid \"{}\" is also the guid of item {}", guid, first),
+                        Some(i + 1),
+                        scan,
+                    ));
+                }
+                None => {
+                    seen.insert(guid, i + 1);
+                }
+            },
+        }
+        if let Some(element) = &item.html {
+            report.unescaped_html += 1;
+            report.findings.push(lint(
+                LintCode::UnescapedHtml,
+                format!(
+                    "the description contains a <{}> element, HTML has to be escaped or in CDATA",
+                    element
+                ),
+                Some(i + 1),
+                scan,
+            ));
+        }
+    }
+}
+
+fn lint(code: LintCode, message: String, item: Option<usize>, scan: &Scan) -> Finding {
+    let position = item.and_then(|i| scan.items.get(i - 1)).map(|i| i.position);
+    Finding {
+        severity: code.severity(),
+        code: FindingCode::Lint(code),
+        message,
+        item,
+        line: position.map(|p| p.row + 1),
+        column: position.map(|p| p.column + 1),
+    }
+}
+
+// the parser numbers its warnings about items as "item 3 ..."
+fn warned_item(warning: &str) -> Option<usize> {
+    let rest = warning.strip_prefix("item ")?;
+    let number = rest.split(' ').next()?;
+    number.parse().ok().filter(|n| *n > 0)
+}
+
+// the document as it is written, apart from what the parser makes of it
+#[derive(Debug, Default)]
+struct Scan {
+    format: Option<FeedFormat>,
+    version: Option<String>,
+    encoding: Option<String>,
+    items: Vec<ScannedItem>,
+    // where the document stops being well-formed
+    error: Option<(String, TextPosition)>,
+}
+
+#[derive(Debug)]
+struct ScannedItem {
+    position: TextPosition,
+    guid: Option<String>,
+    // the first element in the description, which should have been text
+    html: Option<String>,
+}
+
+// the items by the element of the format, up to where the document breaks
+fn scan(buf: &[u8]) -> Scan {
+    let mut scan = Scan::default();
+    let mut reader = EventReader::new(buf);
+    let mut depth = 0;
+    // the depth of the open item, description and guid
+    let mut item = None;
+    let mut description = None;
+    let mut guid = None;
+    loop {
+        let event = match reader.next() {
+            Ok(event) => event,
+            Err(e) => {
+                scan.error = Some((e.msg().to_string(), e.position()));
+                break;
+            }
+        };
+        // where the event starts
+        let position = reader.position();
+        match event {
+            XmlEvent::StartDocument { encoding, .. } => scan.encoding = Some(encoding),
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => {
+                depth += 1;
+                if depth == 1 {
+                    (scan.format, scan.version) = root(&name, &attributes);
+                    continue;
+                }
+                let current = item.and_then(|_| scan.items.last_mut());
+                match current {
+                    Some(current) if description.is_some() => {
+                        current.html.get_or_insert(name.local_name);
+                    }
+                    Some(current) => {
+                        if is_guid(scan.format, &name) {
+                            guid = Some(depth);
+                            current.guid.get_or_insert_with(String::new);
+                        } else if is_description(scan.format, &name, &attributes) {
+                            description = Some(depth);
+                        }
+                    }
+                    None if is_item(scan.format, &name) => {
+                        item = Some(depth);
+                        scan.items.push(ScannedItem {
+                            position,
+                            guid: match scan.format {
+                                Some(FeedFormat::Rdf) => about(&attributes),
+                                _ => None,
+                            },
+                            html: None,
+                        });
+                    }
+                    None => (),
+                }
+            }
+            XmlEvent::Characters(text) | XmlEvent::CData(text) if guid.is_some() => {
+                if let Some(current) = scan.items.last_mut() {
+                    current.guid.get_or_insert_with(String::new).push_str(&text);
+                }
+            }
+            XmlEvent::EndElement { .. } => {
+                for open in [&mut item, &mut description, &mut guid] {
+                    if *open == Some(depth) {
+                        *open = None;
+                    }
+                }
+                depth -= 1;
+            }
+            XmlEvent::EndDocument => break,
+            _ => (),
+        }
+    }
+    scan
+}
+
+fn root(name: &OwnedName, attributes: &[OwnedAttribute]) -> (Option<FeedFormat>, Option<String>) {
+    match (name.namespace.as_deref(), name.local_name.as_str()) {
+        (None, "rss") => {
+            let version = attributes
+                .iter()
+                .find(|a| a.name.namespace.is_none() && a.name.local_name == "version")
+                .map(|a| a.value.clone());
+            (Some(FeedFormat::Rss), version)
+        }
+        (Some(Rss::ATOM_NS), "feed") => (Some(FeedFormat::Atom), Some("1.0".to_string())),
+        (Some(Rss::RDF_SYNTAX_NS), "RDF") => (Some(FeedFormat::Rdf), Some("1.0".to_string())),
+        _ => (None, None),
+    }
+}
+
+fn is_item(format: Option<FeedFormat>, name: &OwnedName) -> bool {
+    matches!(
+        (format, name.namespace.as_deref(), name.local_name.as_str()),
+        (Some(FeedFormat::Rss), None, "item")
+            | (Some(FeedFormat::Atom), Some(Rss::ATOM_NS), "entry")
+            | (Some(FeedFormat::Rdf), Some(Rss::RDF_NS), "item")
+    )
+}
+
+fn is_guid(format: Option<FeedFormat>, name: &OwnedName) -> bool {
+    matches!(
+        (format, name.namespace.as_deref(), name.local_name.as_str()),
+        (Some(FeedFormat::Rss), None, "guid") | (Some(FeedFormat::Atom), Some(Rss::ATOM_NS), "id")
+    )
+}
+
+// xhtml content is markup by design
+fn is_description(
+    format: Option<FeedFormat>,
+    name: &OwnedName,
+    attributes: &[OwnedAttribute],
+) -> bool {
+    match (format, name.namespace.as_deref(), name.local_name.as_str()) {
+        (_, Some(Rss::CONTENT_NS), "encoded") => true,
+        (Some(FeedFormat::Rss), None, "description") => true,
+        (Some(FeedFormat::Rdf), Some(Rss::RDF_NS), "description") => true,
+        (Some(FeedFormat::Atom), Some(Rss::ATOM_NS), "summary" | "content") => !attributes
+            .iter()
+            .any(|a| a.name.local_name == "type" && a.value.trim() == "xhtml"),
+        _ => false,
+    }
+}
+
+fn about(attributes: &[OwnedAttribute]) -> Option<String> {
+    attributes
+        .iter()
+        .find(|a| {
+            a.name.namespace.as_deref() == Some(Rss::RDF_SYNTAX_NS) && a.name.local_name == "about"
+        })
+        .map(|a| a.value.clone())
+}
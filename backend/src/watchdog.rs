@@ -0,0 +1,50 @@
+use crate::stats::Stats;
+use log::warn;
+use std::time::{Duration, Instant};
+
+// parsing and reading the text out of HTML run on the worker, a feed that takes long at either
+// holds up every request queued behind it. they are timed on the monotonic clock, and those over
+// RSSSS_SLOW_PARSE_MS are logged and counted, see `Config::reject_slow_parses` for more
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    Parse,
+    // descriptions in another format and the text of expanded pages
+    Text,
+}
+
+impl Section {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Section::Parse => "parse",
+            Section::Text => "text",
+        }
+    }
+}
+
+pub fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let started = Instant::now();
+    let value = f();
+    (value, started.elapsed())
+}
+
+// true when the section took longer than the threshold
+pub fn watch(
+    stats: &Stats,
+    url: &str,
+    section: Section,
+    elapsed: Duration,
+    threshold: Duration,
+) -> bool {
+    if elapsed <= threshold {
+        return false;
+    }
+    warn!(
+        "[watchdog] {} of {} took {}ms, over {}ms",
+        section.as_str(),
+        url,
+        elapsed.as_millis(),
+        threshold.as_millis()
+    );
+    stats.record_slow_parse(url);
+    true
+}
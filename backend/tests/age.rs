@@ -0,0 +1,175 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use chrono::{DateTime, Utc};
+use rssss::config::Config;
+#[cfg(feature = "cache-disk")]
+use rssss::disk_cache::DiskCache;
+use rssss::fixtures::fixture_name;
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use support::{app, config, feed_uri, fixture, get, url_uri, TestClock, TestResponse};
+
+const NOW: &str = "2024-05-01T09:00:00Z";
+
+fn clocked() -> (Config, Arc<TestClock>) {
+    let clock = TestClock::at(NOW);
+    let mut config = config();
+    config.clock = clock.clone();
+    (config, clock)
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rssss-age-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn age(res: &TestResponse) -> u64 {
+    res.headers
+        .get("Age")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+// the Age header counts from the fetched_at of the envelope
+fn assert_age(res: &TestResponse, fetched_at: &str, secs: u64) {
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+    assert_eq!(res.json["fetched_at"], fetched_at, "{}", res.json);
+    assert_eq!(age(res), secs);
+    let fetched_at = fetched_at.parse::<DateTime<Utc>>().unwrap();
+    let now = NOW.parse::<DateTime<Utc>>().unwrap();
+    assert!(now - fetched_at <= chrono::Duration::seconds(secs as i64));
+}
+
+#[actix_rt::test]
+async fn counts_the_age_of_memory_hits_from_the_fetch() {
+    let (config, clock) = clocked();
+    let app = app(config).await;
+    let uri = feed_uri("/v2/feed", "/fixtures/rss2_planet.xml", "");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "miss");
+    assert_age(&res, NOW, 0);
+
+    clock.advance(42);
+    let res = get(&app, &uri).await;
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "hit");
+    assert_eq!(res.json["fetched_at"], NOW);
+    assert_eq!(age(&res), 42);
+
+    // v1 has no envelope, the header is there all the same
+    let res = get(&app, &feed_uri("/feed", "/fixtures/rss2_planet.xml", "")).await;
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "hit");
+    assert_eq!(age(&res), 42);
+}
+
+#[actix_rt::test]
+async fn pages_of_a_snapshot_keep_the_time_of_the_first() {
+    let (config, clock) = clocked();
+    let app = app(config).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_planet.xml", "&per_page=1"),
+    )
+    .await;
+    assert_age(&res, NOW, 0);
+    let token = res.json["snapshot"].as_str().unwrap().to_string();
+
+    clock.advance(90);
+    let params = format!("&per_page=1&page=2&snapshot={}", token);
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_planet.xml", &params),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "snapshot");
+    assert_eq!(res.json["fetched_at"], NOW);
+    assert_eq!(age(&res), 90);
+}
+
+#[cfg(feature = "cache-disk")]
+#[actix_rt::test]
+async fn disk_copies_are_as_old_as_their_fetch() {
+    let dir = temp_dir("disk");
+    let (mut config, _) = clocked();
+    config.cache.disk_dir = Some(dir.clone());
+    let url = support::mock_url("/fixtures/rss2_planet.xml");
+    let uri = feed_uri("/v2/feed", "/fixtures/rss2_planet.xml", "");
+    get(&app(config.clone()).await, &uri).await;
+
+    // fetched 100 seconds before the clock, and fresh for years
+    let disk = DiskCache::new(dir, u64::MAX);
+    let mut stored = disk.load(&url).await.unwrap();
+    let now = NOW.parse::<DateTime<Utc>>().unwrap();
+    stored.fetched_at = now.timestamp() as u64 - 100;
+    stored.ttl = 20 * 365 * 86_400;
+    disk.store(stored).await;
+
+    let res = get(&app(config).await, &uri).await;
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "disk");
+    assert_age(&res, "2024-05-01T08:58:20Z", 100);
+}
+
+#[cfg(feature = "cache-disk")]
+#[actix_rt::test]
+async fn a_revalidated_copy_is_as_old_as_the_304() {
+    let dir = temp_dir("revalidate");
+    let (mut config, _) = clocked();
+    config.cache.disk_dir = Some(dir.clone());
+    let url = support::mock_url("/headers/rss2_wordpress.xml");
+    let uri = feed_uri("/v2/feed", "/headers/rss2_wordpress.xml", "");
+    get(&app(config.clone()).await, &uri).await;
+
+    let disk = DiskCache::new(dir, u64::MAX);
+    let mut stored = disk.load(&url).await.unwrap();
+    stored.fetched_at = 0;
+    disk.store(stored).await;
+
+    let res = get(&app(config).await, &uri).await;
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "revalidated");
+    assert_age(&res, NOW, 0);
+}
+
+#[actix_rt::test]
+async fn a_fixture_is_as_old_as_its_file() {
+    const OFFLINE: &str = "https://offline.example/feed.xml";
+    let dir = temp_dir("fixture");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(fixture_name(&OFFLINE.parse().unwrap()));
+    fs::write(&path, fixture("rss2_planet.xml")).unwrap();
+    let recorded = NOW.parse::<DateTime<Utc>>().unwrap() - chrono::Duration::hours(1);
+    File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(SystemTime::from(recorded))
+        .unwrap();
+
+    let (mut config, _) = clocked();
+    config.fixture_dir = Some(dir);
+    let res = get(&app(config).await, &url_uri("/v2/feed", OFFLINE, "")).await;
+    assert_eq!(res.headers.get("X-Rssss-Mode").unwrap(), "fixture");
+    assert_age(&res, "2024-05-01T08:00:00Z", 3_600);
+}
+
+#[actix_rt::test]
+async fn a_cached_failure_is_as_old_as_the_fetch_that_failed() {
+    let (mut config, clock) = clocked();
+    config.cache.negative_ttl = Duration::from_secs(60);
+    let app = app(config).await;
+    let uri = feed_uri("/v2/feed", "/fixtures/not_a_feed.html", "");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    assert!(res.headers.get("Age").is_none());
+
+    clock.advance(7);
+    let res = get(&app, &uri).await;
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "negative");
+    assert_eq!(age(&res), 7);
+}
@@ -0,0 +1,62 @@
+use actix_web::web::Bytes;
+use rssss::alias::{alias_groups, fingerprint};
+use rssss::rss::{parse_rss, Rss};
+
+fn items(links: &[&str]) -> Vec<Rss> {
+    let items = links
+        .iter()
+        .enumerate()
+        .map(|(i, link)| {
+            format!(
+                "<item><title>Item {}</title><link>{}</link></item>",
+                i, link
+            )
+        })
+        .collect::<String>();
+    let buf = format!(
+        "<rss version=\"2.0\"><channel><title>Feed</title>{}</channel></rss>",
+        items
+    );
+    parse_rss(Bytes::from(buf)).unwrap().items
+}
+
+#[test]
+fn the_same_items_anywhere_have_the_same_fingerprint() {
+    let a = fingerprint(&items(&["http://example.com/1", "http://example.com/2"]));
+    // in another order, over https and at www
+    let b = fingerprint(&items(&[
+        "https://www.example.com/2",
+        "https://example.com/1",
+    ]));
+    assert!(a.is_some());
+    assert_eq!(a, b);
+    assert_ne!(a, fingerprint(&items(&["http://example.com/1"])));
+    assert_ne!(
+        a,
+        fingerprint(&items(&["http://example.com/1", "http://example.org/2"]))
+    );
+}
+
+#[test]
+fn items_without_links_go_by_their_titles() {
+    let a = fingerprint(&items(&["", ""]));
+    assert!(a.is_some());
+    assert_eq!(a, fingerprint(&items(&["", ""])));
+    assert_ne!(a, fingerprint(&items(&[""])));
+}
+
+#[test]
+fn empty_feeds_are_nobody_s_alias() {
+    assert_eq!(fingerprint(&[]), None);
+    let groups = alias_groups(&[None, None, Some("a".to_string())]);
+    assert!(groups.is_empty());
+}
+
+#[test]
+fn groups_in_the_order_given() {
+    let fingerprints = ["b", "a", "b", "c", "a", "b"]
+        .iter()
+        .map(|f| Some(f.to_string()))
+        .collect::<Vec<Option<String>>>();
+    assert_eq!(alias_groups(&fingerprints), [vec![0, 2, 5], vec![1, 4]]);
+}
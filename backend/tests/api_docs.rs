@@ -0,0 +1,88 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use rssss::error::ErrorKind;
+use rssss::query::FeedQuery;
+use support::{app, config, get};
+
+#[actix_rt::test]
+async fn documents_every_feed_query_parameter() {
+    let app = app(config()).await;
+    let res = get(&app, "/openapi.json").await;
+    assert_eq!(res.status, StatusCode::OK);
+    for path in ["/feed", "/v2/feed"] {
+        let parameters = res.json["paths"][path]["get"]["parameters"]
+            .as_array()
+            .unwrap();
+        let names = parameters
+            .iter()
+            .map(|p| p["name"].as_str().unwrap())
+            .collect::<Vec<&str>>();
+        assert_eq!(names, FeedQuery::PARAMETERS);
+        for parameter in parameters {
+            assert!(
+                !parameter["description"].as_str().unwrap().is_empty(),
+                "{} of {} has no description",
+                parameter["name"],
+                path
+            );
+        }
+    }
+}
+
+// a parameter only takes effect once it is listed, so the list can't fall behind the parser
+#[test]
+fn parses_every_listed_parameter() {
+    let query = FeedQuery::PARAMETERS
+        .iter()
+        .filter(|name| **name != "url")
+        .map(|name| format!("{}=%00", name))
+        .collect::<Vec<String>>()
+        .join("&");
+    let error = match FeedQuery::parse(&format!("url=x&{}", query)) {
+        Ok(_) => panic!("invalid values were accepted"),
+        Err(e) => e.to_string(),
+    };
+    for name in FeedQuery::PARAMETERS.iter().filter(|name| **name != "url") {
+        assert!(
+            error.contains(&format!("invalid parameter {}:", name)),
+            "{} is not parsed: {}",
+            name,
+            error
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn documents_an_error_response_for_every_kind() {
+    let app = app(config()).await;
+    let res = get(&app, "/openapi.json").await;
+    let responses = &res.json["paths"]["/feed"]["get"]["responses"];
+    assert!(responses["400"].is_object());
+    for kind in ErrorKind::ALL {
+        let response = &responses[kind.status().as_str()];
+        assert_eq!(
+            response["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/Error"
+        );
+        let name = serde_json::to_value(kind).unwrap();
+        assert!(response["description"]
+            .as_str()
+            .unwrap()
+            .contains(name.as_str().unwrap()));
+    }
+}
+
+#[cfg(feature = "admin-ui")]
+#[actix_rt::test]
+async fn serves_the_docs_page() {
+    let app = app(config()).await;
+    let res = actix_web::test::call_service(
+        &app,
+        actix_web::test::TestRequest::get()
+            .uri("/docs")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
@@ -0,0 +1,144 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use rssss::audio::{parse_duration, Audio, AudioSources};
+use serde_json::Value;
+use support::{app, config, feed_uri, get};
+
+fn sources() -> AudioSources {
+    AudioSources {
+        itunes_duration: Some("45:00".to_string()),
+        media_duration: Some("1200".to_string()),
+        media_type: Some("audio/ogg".to_string()),
+        media_medium: None,
+        media_file_size: Some("1000".to_string()),
+        enclosure_length: Some("2000".to_string()),
+        enclosure_type: Some("audio/mpeg".to_string()),
+    }
+}
+
+#[test]
+fn parses_durations_as_feeds_write_them() {
+    for (duration, expected) in [
+        ("3600", Some(3_600)),
+        (" 1:02:03 ", Some(3_723)),
+        ("59:59", Some(3_599)),
+        ("90:00", Some(5_400)),
+        ("01:05", Some(65)),
+        ("1834.5", Some(1_834)),
+        ("1:00:00.250", Some(3_600)),
+        ("0", None),
+        ("00:00:00", None),
+        ("1:60", None),
+        ("1:75:00", None),
+        ("1:2:3:4", None),
+        ("-5", None),
+        ("1h 2m", None),
+        ("", None),
+    ] {
+        assert_eq!(parse_duration(duration), expected, "{}", duration);
+    }
+}
+
+#[test]
+fn itunes_wins_over_media_and_the_enclosure_over_file_size() {
+    assert_eq!(
+        sources().resolve(),
+        Audio {
+            duration_secs: Some(2_700),
+            length_bytes: Some(2_000),
+        }
+    );
+    let media_only = AudioSources {
+        itunes_duration: None,
+        enclosure_length: None,
+        ..sources()
+    };
+    assert_eq!(
+        media_only.resolve(),
+        Audio {
+            duration_secs: Some(1_200),
+            length_bytes: Some(1_000),
+        }
+    );
+    // an unreadable itunes:duration falls through to the next source
+    let unreadable = AudioSources {
+        itunes_duration: Some("soon".to_string()),
+        ..sources()
+    };
+    assert_eq!(unreadable.resolve().duration_secs, Some(1_200));
+}
+
+#[test]
+fn a_length_is_never_turned_into_a_duration() {
+    let enclosure_only = AudioSources {
+        itunes_duration: None,
+        media_duration: None,
+        media_file_size: None,
+        ..sources()
+    };
+    assert_eq!(
+        enclosure_only.resolve(),
+        Audio {
+            duration_secs: None,
+            length_bytes: Some(2_000),
+        }
+    );
+}
+
+#[test]
+fn only_audio_types_count() {
+    let video = AudioSources {
+        itunes_duration: None,
+        media_type: Some("video/mp4".to_string()),
+        enclosure_type: Some("video/mp4".to_string()),
+        ..sources()
+    };
+    assert_eq!(video.resolve(), Audio::default());
+    let medium = AudioSources {
+        media_type: None,
+        media_medium: Some("audio".to_string()),
+        ..video
+    };
+    assert_eq!(
+        medium.resolve(),
+        Audio {
+            duration_secs: Some(1_200),
+            length_bytes: Some(1_000),
+        }
+    );
+    let untyped = AudioSources {
+        enclosure_type: None,
+        media_type: None,
+        ..sources()
+    };
+    assert_eq!(untyped.resolve().length_bytes, None);
+}
+
+#[actix_rt::test]
+async fn resolves_the_audio_of_podcast_items() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed", "/fixtures/rss2_podcast.xml", "")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    let audio = res
+        .json
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| {
+            (
+                item["audio_duration_secs"].clone(),
+                item["audio_length_bytes"].clone(),
+            )
+        })
+        .collect::<Vec<(Value, Value)>>();
+    assert_eq!(
+        audio,
+        [
+            (Value::from(3_723), Value::from(59_578_368)),
+            (Value::Null, Value::from(31_457_280)),
+            (Value::from(1_834), Value::from(14_680_064)),
+            (Value::Null, Value::Null),
+        ]
+    );
+}
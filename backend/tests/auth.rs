@@ -0,0 +1,96 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use rssss::config::Config;
+use support::{app, config, feed_uri, get, get_with};
+
+fn keyed_config() -> Config {
+    let mut config = config();
+    config.api_keys = vec!["first-secret".to_string(), "second-secret".to_string()];
+    config
+}
+
+#[actix_rt::test]
+async fn rejects_requests_without_a_key() {
+    let app = app(keyed_config()).await;
+    let res = get(&app, &feed_uri("/feed", "/fixtures/youtube.xml", "")).await;
+    assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(res.json["kind"], "unauthorized");
+    assert_eq!(res.json["messages"][0], "an API key is required");
+    assert_eq!(res.headers.get("WWW-Authenticate").unwrap(), "Bearer");
+
+    let res = get(&app, "/openapi.json").await;
+    assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+}
+
+#[actix_rt::test]
+async fn rejects_a_wrong_key() {
+    let app = app(keyed_config()).await;
+    let uri = feed_uri("/feed", "/fixtures/youtube.xml", "");
+    let res = get_with(&app, &uri, "Authorization", "Bearer first-secreT").await;
+    assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(res.json["messages"][0], "invalid API key");
+    // never echoed back
+    assert!(!String::from_utf8_lossy(&res.body).contains("secreT"));
+
+    let uri = feed_uri("/feed", "/fixtures/youtube.xml", "&api_key=first");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(res.json["messages"][0], "invalid API key");
+
+    // a key under another scheme isn't one
+    let uri = feed_uri("/feed", "/fixtures/youtube.xml", "");
+    let res = get_with(&app, &uri, "Authorization", "Basic second-secret").await;
+    assert_eq!(res.json["messages"][0], "an API key is required");
+}
+
+#[actix_rt::test]
+async fn accepts_any_of_the_keys() {
+    let app = app(keyed_config()).await;
+    let uri = feed_uri("/feed", "/fixtures/youtube.xml", "");
+    let res = get_with(&app, &uri, "Authorization", "Bearer second-secret").await;
+    assert_eq!(res.status, StatusCode::OK);
+    let res = get_with(&app, &uri, "Authorization", "bearer first-secret").await;
+    assert_eq!(res.status, StatusCode::OK);
+
+    let uri = feed_uri("/v2/feed", "/fixtures/youtube.xml", "&api_key=first-secret");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.json["items"].as_array().unwrap().len(), 1);
+}
+
+#[actix_rt::test]
+async fn keys_the_quota_by_api_key() {
+    let mut config = keyed_config();
+    config.daily_quota = Some(1_000_000);
+    let app = app(config).await;
+    let uri = feed_uri("/feed", "/fixtures/youtube.xml", "");
+    let first = get_with(&app, &uri, "Authorization", "Bearer first-secret").await;
+    let uri = feed_uri("/feed", "/fixtures/rss2_wordpress.xml", "");
+    let second = get_with(&app, &uri, "Authorization", "Bearer second-secret").await;
+    let remaining = |res: &support::TestResponse| {
+        let remaining = res.headers.get("X-Quota-Remaining").unwrap();
+        1_000_000 - remaining.to_str().unwrap().parse::<usize>().unwrap()
+    };
+    assert_eq!(remaining(&first), support::fixture("youtube.xml").len());
+    assert_eq!(
+        remaining(&second),
+        support::fixture("rss2_wordpress.xml").len()
+    );
+}
+
+#[actix_rt::test]
+async fn leaves_health_open() {
+    let app = app(keyed_config()).await;
+    let res = get(&app, "/health").await;
+    assert_eq!(res.status, StatusCode::OK);
+}
+
+#[actix_rt::test]
+async fn requires_nothing_without_keys() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed", "/fixtures/youtube.xml", "")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    let res = get(&app, "/health").await;
+    assert_eq!(res.status, StatusCode::OK);
+}
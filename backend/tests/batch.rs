@@ -0,0 +1,126 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use rssss::batch::{MAX_DOCUMENTS, MAX_DOCUMENT_SIZE};
+use serde_json::Value;
+use support::{app, config, fixture, post_as};
+
+const BOUNDARY: &str = "rssss-batch-boundary";
+
+fn multipart(documents: &[Vec<u8>]) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (i, document) in documents.iter().enumerate() {
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}.xml\"\r\n\
+                 Content-Type: application/xml\r\n\r\n",
+                BOUNDARY, i
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(document);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{}--\r\n", BOUNDARY).as_bytes());
+    body
+}
+
+fn documents() -> Vec<Vec<u8>> {
+    vec![
+        fixture("rss2_wordpress.xml").into_bytes(),
+        b"<document><title>notes</title></document>".to_vec(),
+        vec![b' '; MAX_DOCUMENT_SIZE + 1],
+    ]
+}
+
+// the wordpress feed, a document that isn't a feed and one over the size limit, in that order
+fn assert_results(results: &Value) {
+    let results = results.as_array().unwrap();
+    assert_eq!(results.len(), 3, "{:?}", results);
+
+    let titles = results[0]["feed"]["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["title"].as_str().unwrap())
+        .collect::<Vec<&str>>();
+    assert_eq!(
+        titles,
+        ["Release Notes for 2.4", "Meet the Team: Documentation"]
+    );
+    assert!(results[0].get("error").is_none());
+
+    assert!(results[1].get("feed").is_none());
+    assert_eq!(
+        results[1]["error"]["messages"][0],
+        "[RSS V2] invalid root element: document"
+    );
+
+    assert_eq!(results[2]["error"]["kind"], "payload_too_large");
+    assert_eq!(
+        results[2]["error"]["messages"][0],
+        format!("payload exceeds {} bytes", MAX_DOCUMENT_SIZE)
+    );
+}
+
+#[actix_rt::test]
+async fn parses_each_document_of_a_multipart_upload_apart() {
+    let app = app(config()).await;
+    let content_type = format!("multipart/form-data; boundary={}", BOUNDARY);
+    let res = post_as(
+        &app,
+        "/feed/parse/batch",
+        &content_type,
+        multipart(&documents()),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_results(&res.json);
+}
+
+#[actix_rt::test]
+async fn parses_each_document_of_a_json_array_apart() {
+    let app = app(config()).await;
+    let encoded = documents()
+        .iter()
+        .map(base64::encode)
+        .collect::<Vec<String>>();
+    let res = post_as(
+        &app,
+        "/feed/parse/batch",
+        "application/json",
+        serde_json::to_vec(&encoded).unwrap(),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_results(&res.json);
+}
+
+#[actix_rt::test]
+async fn fails_the_batch_over_the_document_count() {
+    let app = app(config()).await;
+    let content_type = format!("multipart/form-data; boundary={}", BOUNDARY);
+    let documents = vec![fixture("rss2_wordpress.xml").into_bytes(); MAX_DOCUMENTS + 1];
+    let res = post_as(
+        &app,
+        "/feed/parse/batch",
+        &content_type,
+        multipart(&documents),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        res.json["messages"][0],
+        format!("too many documents, at most {}", MAX_DOCUMENTS)
+    );
+
+    let encoded = vec![base64::encode("<rss/>"); MAX_DOCUMENTS + 1];
+    let res = post_as(
+        &app,
+        "/feed/parse/batch",
+        "application/json",
+        serde_json::to_vec(&encoded).unwrap(),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+}
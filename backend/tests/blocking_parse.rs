@@ -0,0 +1,48 @@
+mod support;
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+use support::{config, feed_uri, serve};
+
+// the whole response of a GET, and when it ended
+fn fetch(addr: SocketAddr, uri: &str) -> (String, Instant) {
+    let mut client = TcpStream::connect(addr).unwrap();
+    client
+        .set_read_timeout(Some(Duration::from_secs(30)))
+        .unwrap();
+    let head = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        uri, addr
+    );
+    client.write_all(head.as_bytes()).unwrap();
+    let mut response = Vec::new();
+    client.read_to_end(&mut response).unwrap();
+    (
+        String::from_utf8_lossy(&response).to_string(),
+        Instant::now(),
+    )
+}
+
+// parsed inline, the trivial feed waits out the parse of /large, most of a second
+#[test]
+fn a_large_parse_does_not_hold_up_a_trivial_feed() {
+    // a single worker, which the parse would block
+    let addr = serve(config());
+    let large = thread::spawn(move || fetch(addr, &feed_uri("/feed", "/large", "&per_page=1")));
+    // past the fetch of /large and into its parse
+    thread::sleep(Duration::from_millis(150));
+    let started = Instant::now();
+    let (trivial, trivial_done) =
+        fetch(addr, &feed_uri("/feed", "/fixtures/rss2_wordpress.xml", ""));
+    let (large, large_done) = large.join().unwrap();
+    assert!(trivial.starts_with("HTTP/1.1 200 OK"), "{}", trivial);
+    assert!(large.starts_with("HTTP/1.1 200 OK"), "{}", &large[..200]);
+    assert!(trivial_done < large_done);
+    assert!(
+        trivial_done - started < Duration::from_millis(250),
+        "{:?}",
+        trivial_done - started
+    );
+}
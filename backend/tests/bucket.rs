@@ -0,0 +1,27 @@
+use rssss::bucket::{source_bucket, BUCKETS};
+
+#[test]
+fn pins_urls_to_buckets() {
+    // a failure here means every client reshuffles its colors
+    assert_eq!(source_bucket("https://example.com/feed.xml"), 7);
+    assert_eq!(source_bucket("https://blog.github.com/feed/"), 14);
+    assert_eq!(source_bucket("https://b.hatena.ne.jp/hotentry.rss"), 0);
+    assert_eq!(source_bucket("https://www.publickey1.jp/atom.xml"), 13);
+    assert_eq!(source_bucket("http://localhost:8080/rss"), 13);
+}
+
+#[test]
+fn normalizes_the_url_first() {
+    let bucket = source_bucket("https://example.com/feed.xml");
+    assert_eq!(source_bucket("HTTPS://Example.COM/feed.xml"), bucket);
+    assert_eq!(source_bucket("https://example.com:443/feed.xml"), bucket);
+    assert_eq!(source_bucket(" https://example.com/feed.xml#top"), bucket);
+}
+
+#[test]
+fn stays_in_range() {
+    for n in 0..200 {
+        assert!(source_bucket(&format!("https://example.com/{}.xml", n)) < BUCKETS);
+    }
+    assert!(source_bucket("not a url") < BUCKETS);
+}
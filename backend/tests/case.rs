@@ -0,0 +1,69 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use serde_json::Value;
+use support::{app, assert_golden, config, feed_uri, get, TestClock};
+
+async fn envelope(params: &str) -> Value {
+    let mut config = config();
+    config.clock = TestClock::at("2025-01-01T09:00:00Z");
+    let app = app(config).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_wordpress.xml", params),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK);
+    let mut json = res.json;
+    // the mock server listens on a random port
+    json["url"] = "http://mock/fixtures/rss2_wordpress.xml".into();
+    json
+}
+
+#[actix_rt::test]
+async fn snake_case_is_the_default() {
+    assert_golden("v2_rss2_wordpress.snake.json", &envelope("").await);
+    assert_eq!(envelope("&case=snake").await, envelope("").await);
+}
+
+#[actix_rt::test]
+async fn camel_case_renames_nested_fields() {
+    assert_golden(
+        "v2_rss2_wordpress.camel.json",
+        &envelope("&case=camel").await,
+    );
+}
+
+#[actix_rt::test]
+async fn camel_case_applies_to_groups() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri(
+            "/feed",
+            "/fixtures/rss1_hatena.xml",
+            "&case=camel&group_by=day",
+        ),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK);
+    let item = &res.json[0]["items"][0];
+    assert_eq!(item["pubDate"], "2024-05-07T18:00:00+09:00");
+    assert!(item["readingTimeMinutes"].is_number());
+    assert!(item.get("pub_date").is_none());
+}
+
+#[actix_rt::test]
+async fn rejects_unknown_cases() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/feed", "/fixtures/rss2_wordpress.xml", "&case=kebab"),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        res.json["messages"][0],
+        "invalid parameter case: unexpected value: kebab"
+    );
+}
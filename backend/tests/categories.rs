@@ -0,0 +1,132 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::web::Bytes;
+use rssss::rss::{parse_rss, Category, Rss};
+use serde_json::json;
+use support::{app, config, feed_uri, fixture, get, post};
+
+fn item(document: &str) -> Rss {
+    parse_rss(Bytes::from(document.to_string()))
+        .unwrap()
+        .items
+        .remove(0)
+}
+
+#[actix_rt::test]
+async fn keeps_the_schemes_of_arxiv_categories_on_request() {
+    let app = app(config()).await;
+    let params = "&include_category_details=true";
+    let uri = feed_uri("/feed", "/fixtures/atom_arxiv.xml", params);
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    let scheme = "http://arxiv.org/schemas/atom";
+    assert_eq!(
+        res.json[0]["categories"],
+        json!(["cs.LG", "stat.ML", "68T05"])
+    );
+    assert_eq!(
+        res.json[0]["categories_detailed"],
+        json!([
+            {"term": "cs.LG", "scheme": scheme},
+            {"term": "stat.ML", "scheme": scheme},
+            {"term": "68T05", "scheme": scheme},
+        ])
+    );
+    // arxiv:primary_category isn't Atom's, and a category without a term isn't one
+    assert_eq!(res.json[1]["categories"], json!(["cs.IR"]));
+    assert_eq!(
+        res.json[1]["categories_detailed"],
+        json!([{"term": "cs.IR", "scheme": scheme}])
+    );
+}
+
+#[actix_rt::test]
+async fn flattens_github_categories_to_their_labels() {
+    let app = app(config()).await;
+    let uri = feed_uri("/feed", "/fixtures/atom_github_changelog.xml", "");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(
+        res.json[0]["categories"],
+        json!(["Improvement", "Code Security"])
+    );
+    assert_eq!(res.json[1]["categories"], json!(["retired", "actions"]));
+    assert!(res.json[0].get("categories_detailed").is_none());
+
+    let params = "&include_category_details=true&case=camel";
+    let uri = feed_uri("/v2/feed", "/fixtures/atom_github_changelog.xml", params);
+    let res = get(&app, &uri).await;
+    let items = &res.json["items"];
+    assert_eq!(
+        items[0]["categoriesDetailed"][1],
+        json!({
+            "term": "code-security",
+            "label": "Code Security",
+            "scheme": "https://github.blog/changelog/topic/",
+        })
+    );
+    // blank labels and schemes are left out
+    assert_eq!(
+        items[1]["categoriesDetailed"][0],
+        json!({"term": "retired"})
+    );
+}
+
+#[test]
+fn maps_the_rss_domain_to_the_scheme() {
+    let rss = item(
+        r#"<rss version="2.0" xmlns:dc="http://purl.org/dc/elements/1.1/"><channel><title>t</title><item>
+        <title>Shares</title>
+        <category domain="http://www.fool.com/cusips">MSFT</category>
+        <category domain="">Markets</category>
+        <dc:subject>Software</dc:subject>
+        </item></channel></rss>"#,
+    );
+    assert_eq!(
+        rss.categories(),
+        [
+            Category {
+                term: "MSFT".to_string(),
+                label: None,
+                scheme: Some("http://www.fool.com/cusips".to_string()),
+            },
+            Category::new("Markets".to_string(), None, None),
+            Category::new("Software".to_string(), None, None),
+        ]
+    );
+    let value = serde_json::to_value(&rss).unwrap();
+    assert_eq!(value["categories"], json!(["MSFT", "Markets", "Software"]));
+}
+
+#[test]
+fn matches_the_term_or_the_label() {
+    let feed = parse_rss(Bytes::from(fixture("atom_github_changelog.xml"))).unwrap();
+    let first = &feed.items[0];
+    assert!(first.has_category("code-security"));
+    assert!(first.has_category("code security"));
+    assert!(first.has_category(" Improvement "));
+    assert!(!first.has_category("actions"));
+    // the scheme isn't a name of the category
+    assert!(!first.has_category("https://github.blog/changelog/topic/"));
+    assert!(feed.items[1].has_category("ACTIONS"));
+}
+
+#[actix_rt::test]
+async fn leaves_the_details_out_of_posted_feeds() {
+    let app = app(config()).await;
+    let res = post(&app, "/feed/parse", fixture("atom_arxiv.xml")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    let item = &res.json["items"][0];
+    assert_eq!(item["categories"], json!(["cs.LG", "stat.ML", "68T05"]));
+    assert!(item.get("categories_detailed").is_none());
+}
+
+#[actix_rt::test]
+async fn rejects_a_flag_that_isnt_a_boolean() {
+    let app = app(config()).await;
+    let params = "&include_category_details=yes";
+    let uri = feed_uri("/feed", "/fixtures/atom_arxiv.xml", params);
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+}
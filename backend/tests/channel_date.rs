@@ -0,0 +1,66 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use serde_json::Value;
+use support::{app, config, feed_uri, get};
+
+// each dating only the channel and the second of its two items
+const FIXTURES: [(&str, &str, &str); 3] = [
+    (
+        "/fixtures/rss2_dateless.xml",
+        "Tue, 14 May 2024 07:30:00 +0000",
+        "Mon, 13 May 2024 12:00:00 +0000",
+    ),
+    (
+        "/fixtures/atom_dateless.xml",
+        "2024-05-14T07:30:00Z",
+        "2024-05-13T12:00:00Z",
+    ),
+    (
+        "/fixtures/rss1_dateless.xml",
+        "2024-05-14T07:30:00+00:00",
+        "2024-05-13T12:00:00+00:00",
+    ),
+];
+
+async fn feed(fixture: &str, params: &str) -> Value {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/v2/feed", fixture, params)).await;
+    assert_eq!(res.status, StatusCode::OK, "{}: {}", fixture, res.json);
+    res.json
+}
+
+#[actix_rt::test]
+async fn captures_the_channel_date() {
+    for (fixture, channel_date, _) in FIXTURES {
+        let json = feed(fixture, "").await;
+        assert_eq!(json["channel"]["pub_date"], channel_date, "{}", fixture);
+    }
+}
+
+#[actix_rt::test]
+async fn leaves_undated_items_undated_by_default() {
+    for (fixture, _, item_date) in FIXTURES {
+        let json = feed(fixture, "").await;
+        let items = &json["items"];
+        assert!(items[0]["pub_date"].is_null(), "{}", fixture);
+        assert!(items[0].get("date_inherited").is_none(), "{}", fixture);
+        assert_eq!(items[1]["pub_date"], item_date, "{}", fixture);
+
+        let json = feed(fixture, "&inherit_channel_date=false").await;
+        assert!(json["items"][0]["pub_date"].is_null(), "{}", fixture);
+    }
+}
+
+#[actix_rt::test]
+async fn dates_undated_items_by_the_channel_when_asked() {
+    for (fixture, channel_date, item_date) in FIXTURES {
+        let json = feed(fixture, "&inherit_channel_date=true").await;
+        let items = &json["items"];
+        assert_eq!(items[0]["pub_date"], channel_date, "{}", fixture);
+        assert_eq!(items[0]["date_inherited"], true, "{}", fixture);
+        // an item's own date is kept
+        assert_eq!(items[1]["pub_date"], item_date, "{}", fixture);
+        assert!(items[1].get("date_inherited").is_none(), "{}", fixture);
+    }
+}
@@ -0,0 +1,92 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use rssss::charset::{mislabeled_utf8, prefer_utf8};
+use rssss::rss::parse_rss;
+use support::{app, config, feed_uri, get};
+
+const MISLABELED: &str = "/charset/ISO-8859-1/rss1_hatena.xml";
+
+#[test]
+fn leaves_genuine_latin1_alone() {
+    // "café" and "naïve" a byte per character
+    let latin1 = b"<title>caf\xE9 na\xEFve</title>";
+    assert!(!mislabeled_utf8("ISO-8859-1", latin1));
+    assert!(!mislabeled_utf8("windows-1252", latin1));
+}
+
+#[test]
+fn takes_valid_utf8_past_ascii_for_utf8() {
+    let utf8 = "<title>café naïve</title>".as_bytes();
+    assert!(mislabeled_utf8("ISO-8859-1", utf8));
+    assert!(mislabeled_utf8("iso-8859-15", utf8));
+    assert!(mislabeled_utf8("Windows-1252", utf8));
+    assert!(mislabeled_utf8("us-ascii", utf8));
+    // a charset of more than a byte per character could have meant those bytes
+    assert!(!mislabeled_utf8("Shift_JIS", utf8));
+    assert!(!mislabeled_utf8("UTF-16", utf8));
+    assert!(!mislabeled_utf8("ISO-8859-", utf8));
+}
+
+#[test]
+fn has_nothing_to_say_about_ascii() {
+    let ascii = b"<title>cafe naive</title>";
+    assert!(!mislabeled_utf8("ISO-8859-1", ascii));
+    assert!(!mislabeled_utf8("windows-1252", ascii));
+}
+
+#[test]
+fn rewrites_only_the_declaration() {
+    let document = "<?xml version='1.0' encoding = 'latin1'?><title>café</title>";
+    let (relabeled, declared) = prefer_utf8(document.into());
+    assert_eq!(declared.as_deref(), Some("latin1"));
+    assert_eq!(
+        relabeled,
+        "<?xml version='1.0' encoding = 'UTF-8'?><title>café</title>"
+    );
+
+    let latin1 = &b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><title>caf\xE9</title>"[..];
+    let (unchanged, declared) = prefer_utf8(latin1.into());
+    assert_eq!(declared, None);
+    assert_eq!(unchanged, latin1);
+}
+
+#[actix_rt::test]
+async fn reads_a_mislabeled_feed_as_utf8() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/v2/feed", MISLABELED, "")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    let labeled = get(&app, &feed_uri("/v2/feed", "/fixtures/rss1_hatena.xml", "")).await;
+    assert_eq!(res.json["items"], labeled.json["items"]);
+    assert!(labeled.json.get("warnings").is_none());
+    assert_eq!(
+        res.json["warnings"][0],
+        "declared ISO-8859-1 but content is valid UTF-8; using UTF-8"
+    );
+}
+
+#[actix_rt::test]
+async fn honors_the_declaration_when_strict() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed", MISLABELED, "&strictness=strict")).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert!(res.json["messages"]
+        .as_array()
+        .unwrap()
+        .contains(&"[RSS V1] unsupported encoding: ISO-8859-1".into()));
+}
+
+#[test]
+fn still_rejects_what_isnt_utf8() {
+    let document = &b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?>\
+        <rss version=\"2.0\"><channel><title>caf\xE9</title></channel></rss>"[..];
+    let error = parse_rss(document).unwrap_err();
+    assert!(
+        error
+            .messages()
+            .iter()
+            .any(|m| m.ends_with("unsupported encoding: ISO-8859-1")),
+        "{:?}",
+        error.messages()
+    );
+}
@@ -0,0 +1,184 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use serde_json::json;
+use support::{app, config, get, mock_url};
+use url::form_urlencoded;
+
+fn check_uri(paths: &[&str], params: &str) -> String {
+    let urls = paths
+        .iter()
+        .map(|path| {
+            let url =
+                form_urlencoded::byte_serialize(mock_url(path).as_bytes()).collect::<String>();
+            format!("url={}", url)
+        })
+        .collect::<Vec<String>>()
+        .join("&");
+    format!("/feeds/check?{}{}", urls, params)
+}
+
+#[actix_rt::test]
+async fn tells_each_feed_apart() {
+    let app = app(config()).await;
+    let paths = [
+        "/fixtures/rss2_wordpress.xml",
+        "/redirect/1/youtube.xml",
+        "/fixtures/not_a_feed.html",
+        "/fixtures/rss2_empty.xml",
+        "/status/500",
+    ];
+    let mut res = get(&app, &check_uri(&paths, "")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    // only the feeds with items have one
+    let fingerprints = res
+        .json
+        .as_array_mut()
+        .unwrap()
+        .iter_mut()
+        .map(|r| r.as_object_mut().unwrap().remove("fingerprint").is_some())
+        .collect::<Vec<bool>>();
+    assert_eq!(fingerprints, [true, true, false, false, false]);
+    assert_eq!(
+        res.json,
+        json!([
+            {
+                "url": mock_url("/fixtures/rss2_wordpress.xml"),
+                "status": "ok",
+                "item_count": 2,
+                "final_url": mock_url("/fixtures/rss2_wordpress.xml")
+            },
+            {
+                "url": mock_url("/redirect/1/youtube.xml"),
+                "status": "redirected",
+                "item_count": 1,
+                "final_url": mock_url("/fixtures/youtube.xml")
+            },
+            {
+                "url": mock_url("/fixtures/not_a_feed.html"),
+                "status": "parse_error",
+                "detail": "this looks like an HTML page, not a feed",
+                "final_url": mock_url("/fixtures/not_a_feed.html")
+            },
+            {
+                "url": mock_url("/fixtures/rss2_empty.xml"),
+                "status": "parse_error",
+                "detail": "no items",
+                "item_count": 0,
+                "final_url": mock_url("/fixtures/rss2_empty.xml")
+            },
+            {
+                "url": mock_url("/status/500"),
+                "status": "http_error",
+                "detail": "upstream responded 500 Internal Server Error",
+                "final_url": mock_url("/status/500")
+            }
+        ])
+    );
+}
+
+#[actix_rt::test]
+async fn returns_what_finished_by_the_deadline() {
+    let app = app(config()).await;
+    // four at a time, the last one never gets a slot
+    let paths = [
+        "/fixtures/youtube.xml",
+        "/slow/2000/youtube.xml",
+        "/slow/2000/rss1_hatena.xml",
+        "/slow-redirect/2000/youtube.xml",
+        "/slow/2000/rss2_wordpress.xml",
+        "/fixtures/rss2_wordpress.xml",
+    ];
+    let res = get(&app, &check_uri(&paths, "&timeout_ms=300")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    let statuses = res
+        .json
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["status"].as_str().unwrap())
+        .collect::<Vec<&str>>();
+    assert_eq!(
+        statuses,
+        ["ok", "timeout", "timeout", "timeout", "timeout", "pending"]
+    );
+    assert_eq!(
+        res.json[1]["detail"],
+        "deadline of 300ms exceeded while fetching"
+    );
+    assert_eq!(
+        res.json[3]["detail"],
+        "deadline of 300ms exceeded while following redirect 1"
+    );
+    assert_eq!(res.json[5]["detail"], "not started before the deadline");
+}
+
+#[actix_rt::test]
+async fn rejects_missing_invalid_and_too_many_urls() {
+    let app = app(config()).await;
+    let res = get(&app, "/feeds/check").await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        res.json["messages"][0],
+        "invalid parameter url: is required"
+    );
+
+    let res = get(&app, "/feeds/check?url=ftp%3A%2F%2Fexample.com%2F&url=nope").await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.json["messages"].as_array().unwrap().len(), 2);
+
+    let paths = ["/fixtures/youtube.xml"; 51];
+    let res = get(&app, &check_uri(&paths, "")).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        res.json["messages"][0],
+        "invalid parameter url: at most 50 urls"
+    );
+}
+
+#[actix_rt::test]
+async fn reports_the_urls_serving_the_same_feed() {
+    let app = app(config()).await;
+    let paths = [
+        "/fixtures/youtube.xml",
+        "/fixtures/rss2_wordpress.xml",
+        "/redirect/1/youtube.xml",
+        "/fixtures/youtube.xml#again",
+    ];
+    let res = get(&app, &check_uri(&paths, "")).await;
+    let aliases = |i: usize| res.json[i]["aliases"].clone();
+    assert_eq!(
+        aliases(0),
+        json!([
+            mock_url("/redirect/1/youtube.xml"),
+            mock_url("/fixtures/youtube.xml#again")
+        ])
+    );
+    assert!(res.json[1].get("aliases").is_none());
+    assert_eq!(
+        aliases(2),
+        json!([
+            mock_url("/fixtures/youtube.xml"),
+            mock_url("/fixtures/youtube.xml#again")
+        ])
+    );
+    assert_eq!(res.json[0]["fingerprint"], res.json[2]["fingerprint"]);
+
+    // folded into the first of them
+    let res = get(&app, &check_uri(&paths, "&collapse_aliases=true")).await;
+    let urls = res
+        .json
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["url"].as_str().unwrap().to_string())
+        .collect::<Vec<String>>();
+    assert_eq!(
+        urls,
+        [
+            mock_url("/fixtures/youtube.xml"),
+            mock_url("/fixtures/rss2_wordpress.xml")
+        ]
+    );
+    assert_eq!(res.json[0]["aliases"].as_array().unwrap().len(), 2);
+}
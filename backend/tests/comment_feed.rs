@@ -0,0 +1,73 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::web::Bytes;
+use rssss::rss::parse_rss;
+use support::{app, config, feed_uri, get};
+
+#[actix_rt::test]
+async fn surfaces_the_comment_feed_of_each_post() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_wordpress_comments.xml", ""),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+    let items = res.json["items"].as_array().unwrap();
+    assert_eq!(
+        items[0]["comment_feed_url"],
+        "https://garden.example.org/2024/10/saving-seed-runner-beans/feed/"
+    );
+    // relative to the post
+    assert_eq!(
+        items[1]["comment_feed_url"],
+        "https://garden.example.org/2024/10/mulching-before-the-frost/feed/"
+    );
+    assert!(items[2].get("comment_feed_url").is_none());
+
+    let res = get(
+        &app,
+        &feed_uri(
+            "/v2/feed",
+            "/fixtures/rss2_wordpress_comments.xml",
+            "&case=camel",
+        ),
+    )
+    .await;
+    assert!(res.json["items"][0]["commentFeedUrl"].is_string());
+}
+
+#[test]
+fn reads_wfw_comment_rss_of_rss_1_0() {
+    let feed = parse_rss(Bytes::from_static(
+        b"<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\" \
+          xmlns=\"http://purl.org/rss/1.0/\" xmlns:wfw=\"http://wellformedweb.org/CommentAPI/\">\
+          <channel rdf:about=\"https://example.com/\"><title>Notes</title></channel>\
+          <item rdf:about=\"https://example.com/a\"><title>A</title>\
+          <link>https://example.com/posts/a</link>\
+          <wfw:commentRss>../comments/a.rss</wfw:commentRss></item>\
+          <item rdf:about=\"https://example.com/b\"><title>B</title>\
+          <link>https://example.com/posts/b</link></item></rdf:RDF>",
+    ))
+    .unwrap();
+    assert_eq!(
+        feed.items[0].comment_feed_url(),
+        Some("https://example.com/comments/a.rss")
+    );
+    assert_eq!(feed.items[1].comment_feed_url(), None);
+}
+
+#[test]
+fn keeps_a_relative_comment_feed_of_a_relative_link() {
+    let feed = parse_rss(Bytes::from_static(
+        b"<rss version=\"2.0\" xmlns:wfw=\"http://wellformedweb.org/CommentAPI/\"><channel>\
+          <title>Relative</title><item><title>A</title><link>/posts/a</link>\
+          <wfw:commentRss>/posts/a/feed</wfw:commentRss></item>\
+          <item><title>B</title><link>/posts/b</link><wfw:commentRss> </wfw:commentRss></item>\
+          </channel></rss>",
+    ))
+    .unwrap();
+    assert_eq!(feed.items[0].comment_feed_url(), Some("/posts/a/feed"));
+    assert_eq!(feed.items[1].comment_feed_url(), None);
+}
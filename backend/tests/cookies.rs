@@ -0,0 +1,137 @@
+mod support;
+
+use actix_web::http::header::{HeaderMap, HeaderValue, SET_COOKIE};
+use actix_web::http::StatusCode;
+use rssss::cookies::CookieJar;
+use support::{app, config, cookie_log, get, mock_localhost_url, mock_url, url_uri};
+use url::Url;
+
+fn set(jar: &mut CookieJar, url: &str, cookies: &[&str]) {
+    let mut headers = HeaderMap::new();
+    for cookie in cookies {
+        headers.append(SET_COOKIE, HeaderValue::from_str(cookie).unwrap());
+    }
+    jar.store(&Url::parse(url).unwrap(), &headers);
+}
+
+fn sent(jar: &mut CookieJar, url: &str) -> Option<String> {
+    jar.header(&Url::parse(url).unwrap())
+}
+
+#[actix_rt::test]
+async fn replays_the_cookie_of_a_redirect() {
+    let app = app(config()).await;
+    let url = mock_url("/cookie-gate/dance/rss2_planet.xml");
+    let res = get(&app, &url_uri("/v2/feed", &url, "&debug=true")).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+    assert_eq!(res.json["cookies_replayed"], 1);
+    let (sets, seen) = cookie_log("dance");
+    assert_eq!(sets, 1);
+    // the stray cookie is for another path
+    assert_eq!(seen, [None, Some("gate=dance".to_string())]);
+    // only with debug
+    let res = get(&app, &url_uri("/v2/feed", &url, "")).await;
+    assert!(res.json.get("cookies_replayed").is_none());
+}
+
+#[actix_rt::test]
+async fn keeps_cookies_to_the_fetch_and_host_that_set_them() {
+    let app = app(config()).await;
+    // set for 127.0.0.1, then off to localhost which has to set its own
+    let url = mock_url(&format!("/cookie-set/localhost/leak/{}", "rss2_planet.xml"));
+    let res = get(&app, &url_uri("/feed", &url, "")).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+    let (sets, seen) = cookie_log("leak");
+    assert_eq!(sets, 2);
+    assert_eq!(seen, [None, Some("gate=leak".to_string())]);
+
+    // a fetch of its own starts without them
+    let url = mock_localhost_url("/cookie-gate/leak/rss2_wordpress.xml");
+    let res = get(&app, &url_uri("/feed", &url, "")).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+    let (sets, seen) = cookie_log("leak");
+    assert_eq!(sets, 3);
+    assert_eq!(seen[2], None);
+}
+
+#[test]
+fn sends_cookies_only_where_they_were_meant_for() {
+    let mut jar = CookieJar::default();
+    set(
+        &mut jar,
+        "https://blog.example.com/feed/rss",
+        &[
+            "host=1",
+            "wide=2; Domain=.Example.com; Path=/",
+            "secure=3; Secure; Domain=example.com; Path=/",
+            "deep=4; Path=/feed/rss/more",
+        ],
+    );
+    assert_eq!(
+        sent(&mut jar, "https://blog.example.com/feed/x").as_deref(),
+        Some("host=1; wide=2; secure=3")
+    );
+    assert_eq!(
+        sent(&mut jar, "https://blog.example.com/feed/rss/more/x").as_deref(),
+        Some("deep=4; host=1; wide=2; secure=3")
+    );
+    // a host-only cookie stays with its host, a Domain one goes to the hosts under it
+    assert_eq!(
+        sent(&mut jar, "https://www.example.com/feed/x").as_deref(),
+        Some("wide=2; secure=3")
+    );
+    // Secure ones only over https
+    assert_eq!(
+        sent(&mut jar, "http://www.example.com/").as_deref(),
+        Some("wide=2")
+    );
+    // the default path is the directory of the request, /feed doesn't match /feedx
+    assert_eq!(
+        sent(&mut jar, "https://blog.example.com/feedx").as_deref(),
+        Some("wide=2; secure=3")
+    );
+    assert_eq!(sent(&mut jar, "https://example.org/"), None);
+}
+
+#[test]
+fn refuses_cookies_for_other_domains() {
+    let mut jar = CookieJar::default();
+    set(
+        &mut jar,
+        "https://feeds.example.com/",
+        &[
+            "other=1; Domain=example.org",
+            "tld=2; Domain=com",
+            "sub=3; Domain=deeper.feeds.example.com",
+            "suffix=4; Domain=ample.com",
+        ],
+    );
+    assert_eq!(sent(&mut jar, "https://feeds.example.com/"), None);
+    assert_eq!(sent(&mut jar, "https://example.org/"), None);
+    assert_eq!(sent(&mut jar, "https://deeper.feeds.example.com/"), None);
+
+    // an address only sets cookies for itself
+    set(&mut jar, "http://127.0.0.1/", &["ip=5; Domain=0.0.1"]);
+    assert_eq!(sent(&mut jar, "http://127.0.0.1/"), None);
+    assert_eq!(jar.replayed(), 0);
+}
+
+#[test]
+fn a_later_cookie_replaces_or_removes_an_earlier_one() {
+    let mut jar = CookieJar::default();
+    let url = "https://example.com/a";
+    set(&mut jar, url, &["session=1; Path=/", "gone=1; Path=/"]);
+    set(
+        &mut jar,
+        url,
+        &["session=2; Path=/", "gone=; Path=/; Max-Age=0"],
+    );
+    assert_eq!(sent(&mut jar, url).as_deref(), Some("session=2"));
+    set(
+        &mut jar,
+        url,
+        &["session=3; Path=/; Expires=Thu, 01 Jan 1970 00:00:00 GMT"],
+    );
+    assert_eq!(sent(&mut jar, url), None);
+    assert_eq!(jar.replayed(), 1);
+}
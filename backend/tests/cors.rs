@@ -0,0 +1,142 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use rssss::config::Config;
+use rssss::ROUTES;
+use support::{app, config, feed_uri, get_with, options};
+
+const ORIGIN: &str = "https://reader.example";
+
+fn preflight<'a>(method: &'a str, headers: &'a str) -> [(&'a str, &'a str); 3] {
+    [
+        ("Origin", ORIGIN),
+        ("Access-Control-Request-Method", method),
+        ("Access-Control-Request-Headers", headers),
+    ]
+}
+
+fn allowed(res: &support::TestResponse, name: &str) -> Vec<String> {
+    res.headers
+        .get(name)
+        .unwrap_or_else(|| panic!("no {}", name))
+        .to_str()
+        .unwrap()
+        .split(',')
+        .map(|v| v.trim().to_ascii_lowercase())
+        .collect()
+}
+
+fn restricted_config() -> Config {
+    let mut config = config();
+    config.cors_origins = vec![ORIGIN.to_string()];
+    config
+}
+
+#[actix_rt::test]
+async fn answers_a_preflight_for_every_route() {
+    let app = app(config()).await;
+    for (path, methods) in ROUTES {
+        for method in methods.iter() {
+            let headers = preflight(method.as_str(), "content-type");
+            let res = options(&app, path, &headers).await;
+            assert_eq!(res.status, StatusCode::OK, "{} {}", method, path);
+            assert_eq!(
+                res.headers.get("Access-Control-Allow-Origin").unwrap(),
+                ORIGIN
+            );
+            let methods = allowed(&res, "Access-Control-Allow-Methods");
+            assert!(
+                methods.contains(&method.as_str().to_ascii_lowercase()),
+                "{} {}: {:?}",
+                method,
+                path,
+                methods
+            );
+        }
+    }
+}
+
+#[actix_rt::test]
+async fn allows_json_bodies_to_be_posted() {
+    let app = app(config()).await;
+    for path in ["/feed", "/feed/parse", "/feed/parse/batch"] {
+        let headers = preflight("POST", "Content-Type, Authorization, traceparent");
+        let res = options(&app, path, &headers).await;
+        assert_eq!(res.status, StatusCode::OK, "{}", path);
+        let headers = allowed(&res, "Access-Control-Allow-Headers");
+        for name in ["content-type", "authorization", "traceparent"] {
+            assert!(headers.contains(&name.to_string()), "{:?}", headers);
+        }
+        assert_eq!(res.headers.get("Access-Control-Max-Age").unwrap(), "3600");
+    }
+}
+
+#[actix_rt::test]
+async fn refuses_headers_it_does_not_read() {
+    let app = app(config()).await;
+    let headers = preflight("POST", "content-type, x-unknown");
+    let res = options(&app, "/feed/parse", &headers).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn refuses_a_preflight_from_another_origin() {
+    let app = app(restricted_config()).await;
+    let res = options(&app, "/feed/parse", &preflight("POST", "content-type")).await;
+    assert_eq!(res.status, StatusCode::OK);
+
+    let headers = [
+        ("Origin", "https://elsewhere.example"),
+        ("Access-Control-Request-Method", "POST"),
+        ("Access-Control-Request-Headers", "content-type"),
+    ];
+    for path in ["/feed", "/feed/parse", "/feeds/check", "/no/such/route"] {
+        let res = options(&app, path, &headers).await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST, "{}", path);
+        assert!(res.headers.get("Access-Control-Allow-Origin").is_none());
+    }
+}
+
+#[actix_rt::test]
+async fn answers_a_preflight_without_an_api_key() {
+    let mut config = restricted_config();
+    config.api_keys = vec!["secret".to_string()];
+    let app = app(config).await;
+    let res = options(&app, "/feed/parse", &preflight("POST", "content-type")).await;
+    assert_eq!(res.status, StatusCode::OK);
+
+    // the request that follows still needs one
+    let uri = feed_uri("/feed", "/fixtures/youtube.xml", "");
+    let res = get_with(&app, &uri, "Origin", ORIGIN).await;
+    assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+}
+
+#[actix_rt::test]
+async fn lists_the_methods_of_a_plain_options() {
+    let app = app(config()).await;
+    let res = options(&app, "/feed", &[]).await;
+    assert_eq!(res.status, StatusCode::NO_CONTENT);
+    assert_eq!(
+        res.headers.get("Allow").unwrap(),
+        "GET, HEAD, POST, OPTIONS"
+    );
+
+    let res = options(&app, "/feed/parse", &[]).await;
+    assert_eq!(res.headers.get("Allow").unwrap(), "POST, OPTIONS");
+}
+
+#[actix_rt::test]
+async fn exposes_the_paging_and_quota_headers() {
+    let mut config = restricted_config();
+    config.daily_quota = Some(1_000_000);
+    let app = app(config).await;
+    let uri = feed_uri("/feed", "/fixtures/youtube.xml", "");
+    let res = get_with(&app, &uri, "Origin", ORIGIN).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(
+        res.headers.get("Access-Control-Allow-Origin").unwrap(),
+        ORIGIN
+    );
+    let exposed = allowed(&res, "Access-Control-Expose-Headers");
+    assert!(exposed.contains(&"x-quota-remaining".to_string()));
+}
@@ -0,0 +1,172 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use chrono::{TimeZone, Utc};
+use rssss::config::Config;
+use rssss::dates::{FutureDates, Plausible};
+use serde_json::{json, Value};
+use support::{app, config, feed_uri, get, TestClock};
+
+const FIXTURE: &str = "/fixtures/rss2_dates.xml";
+
+// 48 hours before the "at the limit" item
+fn fixed() -> Config {
+    let mut config = config();
+    config.clock = TestClock::at("2024-05-01T09:00:00Z");
+    config
+}
+
+fn titles(items: &Value) -> Vec<&str> {
+    items
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["title"].as_str().unwrap())
+        .collect()
+}
+
+fn suspects(items: &Value) -> Vec<bool> {
+    items
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item.get("date_suspect").is_some_and(|s| s == true))
+        .collect()
+}
+
+#[test]
+fn flags_what_is_past_either_end_of_the_range() {
+    let plausible = Plausible {
+        now: Utc.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap(),
+        policy: FutureDates::Keep,
+    };
+    assert_eq!(plausible.check("2024-05-03T09:00:00Z"), None);
+    assert_eq!(plausible.check("2024-05-03T18:00:00+09:00"), None);
+    assert_eq!(plausible.check("1990-01-01T00:00:00Z"), None);
+    assert_eq!(
+        plausible.check("2024-05-03T09:00:01Z"),
+        Some((
+            "is more than 48 hours ahead".to_string(),
+            Some("2024-05-03T09:00:01Z".to_string())
+        ))
+    );
+    assert_eq!(
+        plausible.check("Sun, 31 Dec 1989 23:59:59 GMT"),
+        Some((
+            "is before 1990".to_string(),
+            Some("Sun, 31 Dec 1989 23:59:59 GMT".to_string())
+        ))
+    );
+    // what can't be parsed isn't a date to judge
+    assert_eq!(plausible.check("someday"), None);
+}
+
+#[actix_rt::test]
+async fn keeps_suspect_dates_but_sorts_them_as_undated() {
+    let app = app(fixed()).await;
+    let uri = feed_uri("/v2/feed", FIXTURE, "&sort_by=published");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    let items = &res.json["items"];
+    assert_eq!(
+        titles(items),
+        [
+            "at the limit",
+            "ordinary",
+            "broken epoch",
+            "zeroed",
+            "past the limit"
+        ]
+    );
+    assert_eq!(suspects(items), [false, false, true, true, true]);
+    assert_eq!(items[2]["pub_date"], "Sun, 07 Feb 2106 06:28:15 GMT");
+    assert_eq!(items[4]["updated"], "1989-12-31T23:59:59Z");
+    // numbered as the items are in the feed
+    assert_eq!(
+        res.json["warnings"],
+        json!([
+            "item 1 pub_date is more than 48 hours ahead: Sun, 07 Feb 2106 06:28:15 GMT",
+            "item 3 pub_date is before 1990: Thu, 01 Jan 1970 00:00:00 GMT",
+            "item 5 pub_date is more than 48 hours ahead: 2024-05-03T09:00:01Z",
+            "item 5 updated is before 1990: 1989-12-31T23:59:59Z",
+        ])
+    );
+}
+
+#[actix_rt::test]
+async fn clamps_suspect_dates_to_the_nearest_plausible_one() {
+    let app = app(fixed()).await;
+    let uri = feed_uri("/v2/feed", FIXTURE, "&future_dates=clamp");
+    let res = get(&app, &uri).await;
+    let items = &res.json["items"];
+    assert_eq!(suspects(items), [true, false, true, false, true]);
+    assert_eq!(items[0]["pub_date"], "2024-05-03T09:00:00Z");
+    assert_eq!(items[2]["pub_date"], "1990-01-01T00:00:00Z");
+    assert_eq!(items[4]["pub_date"], "2024-05-03T09:00:00Z");
+    assert_eq!(items[4]["updated"], "1990-01-01T00:00:00Z");
+    // the warnings still say what the feed had
+    assert_eq!(
+        res.json["warnings"][0],
+        "item 1 pub_date is more than 48 hours ahead: Sun, 07 Feb 2106 06:28:15 GMT"
+    );
+
+    // a clamped date sorts as undated as well
+    let uri = feed_uri("/feed", FIXTURE, "&future_dates=clamp&group_by=day");
+    let res = get(&app, &uri).await;
+    let groups = res.json.as_array().unwrap();
+    let unknown = groups.last().unwrap();
+    assert_eq!(unknown["date"], "unknown");
+    assert_eq!(
+        titles(&unknown["items"]),
+        ["broken epoch", "zeroed", "past the limit"]
+    );
+}
+
+#[actix_rt::test]
+async fn leaves_out_suspect_dates_when_ignored() {
+    let app = app(fixed()).await;
+    let uri = feed_uri("/feed", FIXTURE, "&future_dates=ignore");
+    let res = get(&app, &uri).await;
+    assert_eq!(suspects(&res.json), [true, false, true, false, true]);
+    assert!(res.json[0]["pub_date"].is_null());
+    assert!(res.json[2]["pub_date"].is_null());
+    assert!(res.json[4]["pub_date"].is_null());
+    assert!(res.json[4].get("updated").is_none());
+    assert_eq!(res.json[3]["pub_date"], "2024-05-03T09:00:00Z");
+    assert_eq!(res.json[3]["updated"], "1990-01-01T00:00:00Z");
+}
+
+#[actix_rt::test]
+async fn judges_by_the_clock_of_the_request() {
+    let clock = TestClock::at("2024-05-01T09:00:00Z");
+    let app = app(Config {
+        clock: clock.clone(),
+        ..config()
+    })
+    .await;
+    let uri = feed_uri("/v2/feed", FIXTURE, "");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.json["warnings"].as_array().unwrap().len(), 4);
+
+    // a second later the cached item's pub_date is within range, its updated is not
+    clock.advance(1);
+    let res = get(&app, &uri).await;
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "hit");
+    assert_eq!(
+        res.json["warnings"][2],
+        "item 5 updated is before 1990: 1989-12-31T23:59:59Z"
+    );
+    assert_eq!(res.json["warnings"].as_array().unwrap().len(), 3);
+}
+
+#[actix_rt::test]
+async fn rejects_an_unknown_policy() {
+    let app = app(fixed()).await;
+    let uri = feed_uri("/feed", FIXTURE, "&future_dates=drop");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        res.json["messages"][0],
+        "invalid parameter future_dates: unexpected value: drop"
+    );
+}
@@ -0,0 +1,70 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use std::time::Duration;
+use support::{app, config, feed_uri, get};
+
+async fn timed_out(path: &str, params: &str) -> String {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed", path, params)).await;
+    assert_eq!(res.status, StatusCode::GATEWAY_TIMEOUT);
+    assert_eq!(res.json["kind"], "deadline_exceeded");
+    res.json["messages"][0].as_str().unwrap().to_string()
+}
+
+#[actix_rt::test]
+async fn reports_a_deadline_passed_while_fetching() {
+    let message = timed_out("/slow/2000/youtube.xml", "&timeout_ms=200").await;
+    assert_eq!(message, "deadline of 200ms exceeded while fetching");
+}
+
+#[actix_rt::test]
+async fn reports_a_deadline_passed_while_following_a_redirect() {
+    let message = timed_out("/slow-redirect/2000/youtube.xml", "&timeout_ms=200").await;
+    assert_eq!(
+        message,
+        "deadline of 200ms exceeded while following redirect 1"
+    );
+}
+
+#[actix_rt::test]
+async fn reports_a_deadline_passed_while_parsing() {
+    let message = timed_out("/large", "&timeout_ms=250").await;
+    assert_eq!(message, "deadline of 250ms exceeded while parsing");
+}
+
+#[actix_rt::test]
+async fn never_raises_the_server_deadline() {
+    let mut config = config();
+    config.request_timeout = Duration::from_millis(200);
+    let app = app(config).await;
+    let uri = feed_uri("/v2/feed", "/slow/2000/youtube.xml", "&timeout_ms=60000");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::GATEWAY_TIMEOUT);
+    assert_eq!(res.json["errors"][0]["code"], "deadline_exceeded");
+    assert_eq!(
+        res.json["errors"][0]["message"],
+        "deadline of 200ms exceeded while fetching"
+    );
+}
+
+#[actix_rt::test]
+async fn serves_feeds_within_the_deadline() {
+    let app = app(config()).await;
+    let uri = feed_uri("/feed", "/slow/100/youtube.xml", "&timeout_ms=5000");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.json.as_array().unwrap().len(), 1);
+}
+
+#[actix_rt::test]
+async fn rejects_a_zero_timeout() {
+    let app = app(config()).await;
+    let uri = feed_uri("/feed", "/fixtures/youtube.xml", "&timeout_ms=0");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        res.json["messages"][0],
+        "invalid parameter timeout_ms: must be 1 or greater"
+    );
+}
@@ -0,0 +1,60 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use serde_json::Value;
+use support::{app, config, post};
+
+async fn parse_description(description: &str) -> Value {
+    let document = format!(
+        r#"<?xml version="1.0"?><rss version="2.0"><channel><item><title>t</title><description><![CDATA[{}]]></description></item></channel></rss>"#,
+        description
+    );
+    let app = app(config()).await;
+    let res = post(&app, "/feed/parse", document).await;
+    assert_eq!(res.status, StatusCode::OK);
+    res.json["items"][0].clone()
+}
+
+#[actix_rt::test]
+async fn keeps_descriptions_at_the_limit_whole() {
+    let item = parse_description(&"a".repeat(500)).await;
+    assert_eq!(item["description"], "a".repeat(500));
+    assert_eq!(item["description_truncated"], false);
+    assert_eq!(item["description_full_length"], 500);
+}
+
+#[actix_rt::test]
+async fn counts_characters_not_bytes() {
+    let item = parse_description(&"あ".repeat(500)).await;
+    assert_eq!(item["description"], "あ".repeat(500));
+    assert_eq!(item["description_truncated"], false);
+    assert_eq!(item["description_full_length"], 500);
+}
+
+#[actix_rt::test]
+async fn truncates_just_over_the_limit() {
+    let item = parse_description(&"a".repeat(501)).await;
+    assert_eq!(item["description"], format!("{}...", "a".repeat(500)));
+    assert_eq!(item["description_truncated"], true);
+    assert_eq!(item["description_full_length"], 501);
+}
+
+#[actix_rt::test]
+async fn truncates_far_over_the_limit_across_elements() {
+    let item = parse_description(&"<p>word word</p>".repeat(1000)).await;
+    // the cut falls right after a space, which is trimmed before the ellipsis
+    assert_eq!(
+        item["description"],
+        format!("{}word...", "word word".repeat(55))
+    );
+    assert_eq!(item["description_truncated"], true);
+    assert_eq!(item["description_full_length"], 9000);
+}
+
+#[actix_rt::test]
+async fn measures_the_trimmed_text() {
+    let item = parse_description("  <p> hello </p>\n").await;
+    assert_eq!(item["description"], "hello");
+    assert_eq!(item["description_truncated"], false);
+    assert_eq!(item["description_full_length"], 5);
+}
@@ -0,0 +1,47 @@
+mod support;
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::{Duration, Instant};
+use support::{config, drip_counts, mock_url, serve, url_uri};
+
+fn request(id: &str) -> (TcpStream, String) {
+    let addr = serve(config());
+    let client = TcpStream::connect(addr).unwrap();
+    let uri = url_uri("/feed", &mock_url(&format!("/drip/{}/youtube.xml", id)), "");
+    let head = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        uri, addr
+    );
+    (client, head)
+}
+
+fn wait_for_upstream(id: &str) {
+    let started = Instant::now();
+    while drip_counts(id).0 == 0 {
+        assert!(started.elapsed() < Duration::from_secs(5), "never fetched");
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn reads_the_whole_upstream_body_for_a_connected_client() {
+    let (mut client, head) = request("connected");
+    client.write_all(head.as_bytes()).unwrap();
+    let mut response = String::new();
+    client.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+    assert_eq!(drip_counts("connected"), (1, 1));
+}
+
+#[test]
+fn drops_the_upstream_fetch_when_the_client_disconnects() {
+    let (mut client, head) = request("disconnected");
+    client.write_all(head.as_bytes()).unwrap();
+    wait_for_upstream("disconnected");
+    drop(client);
+    // the drip takes a second, give it more than that to have been read to the end
+    thread::sleep(Duration::from_millis(1_500));
+    assert_eq!(drip_counts("disconnected"), (1, 0));
+}
@@ -0,0 +1,172 @@
+#![cfg(feature = "cache-disk")]
+
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::web::Bytes;
+use rssss::disk_cache::DiskCache;
+use rssss::rss::{Channel, Feed};
+use rssss::stored::{Stored, VERSION};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use support::{app, config, feed_uri, get, mock_url};
+
+// a fresh directory per test, left behind to be inspected
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rssss-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn disk_config(dir: &Path) -> rssss::config::Config {
+    let mut config = config();
+    config.cache.disk_dir = Some(dir.to_path_buf());
+    config
+}
+
+fn empty_feed() -> Feed {
+    Feed {
+        channel: Channel::default(),
+        items: Vec::new(),
+        warnings: Vec::new(),
+        recovery: None,
+        unmapped_elements: Vec::new(),
+        dropped_items: Vec::new(),
+    }
+}
+
+#[actix_rt::test]
+async fn serves_stored_feeds_after_a_restart() {
+    let dir = temp_dir("restart");
+    let uri = feed_uri("/v2/feed", "/fixtures/rss2_wordpress.xml", "");
+    let first = get(&app(disk_config(&dir)).await, &uri).await;
+    assert_eq!(first.status, StatusCode::OK);
+    assert_eq!(first.headers.get("X-Rssss-Cache").unwrap(), "miss");
+
+    // new caches over the same directory, as after a restart
+    let restarted = app(disk_config(&dir)).await;
+    let res = get(&restarted, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "disk");
+    assert_eq!(res.json, first.json);
+
+    // then from memory
+    let res = get(&restarted, &uri).await;
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "hit");
+}
+
+#[actix_rt::test]
+async fn formats_stored_feeds_from_their_markup() {
+    let dir = temp_dir("format");
+    let uri = feed_uri(
+        "/v2/feed",
+        "/fixtures/rss2_wordpress.xml",
+        "&format=markdown",
+    );
+    let first = get(&app(disk_config(&dir)).await, &uri).await;
+    assert_eq!(first.headers.get("X-Rssss-Cache").unwrap(), "miss");
+
+    let res = get(&app(disk_config(&dir)).await, &uri).await;
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "disk");
+    assert_eq!(res.json["items"], first.json["items"]);
+    assert!(res.json["items"][1]["description"]
+        .as_str()
+        .unwrap()
+        .contains("[handbook](https://docs.example.com/)"));
+}
+
+#[actix_rt::test]
+async fn revalidates_stale_copies_with_their_validators() {
+    let dir = temp_dir("revalidate");
+    let url = mock_url("/headers/rss2_wordpress.xml");
+    let uri = feed_uri("/v2/feed", "/headers/rss2_wordpress.xml", "");
+    let res = get(&app(disk_config(&dir)).await, &uri).await;
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "miss");
+
+    let disk = DiskCache::new(dir.clone(), u64::MAX);
+    let mut stored = disk.load(&url).await.unwrap();
+    assert_eq!(stored.upstream_headers["etag"], "\"v1\"");
+    stored.fetched_at = 0;
+    disk.store(stored).await;
+
+    let res = get(&app(disk_config(&dir)).await, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "revalidated");
+    assert_eq!(res.json["items"].as_array().unwrap().len(), 2);
+    assert_eq!(res.json["upstream_headers"]["etag"], "\"v1\"");
+    assert_eq!(res.json["upstream_headers"]["cache-control"], "max-age=600");
+
+    // stored fresh again
+    let stored = disk.load(&url).await.unwrap();
+    assert_eq!(stored.ttl, 600);
+    let res = get(&app(disk_config(&dir)).await, &uri).await;
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "disk");
+}
+
+#[actix_rt::test]
+async fn overwrites_corrupt_files() {
+    let dir = temp_dir("corrupt");
+    let url = mock_url("/fixtures/rss1_hatena.xml");
+    let disk = DiskCache::new(dir.clone(), u64::MAX);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(disk.path(&url), "{\"version\": 1, \"url\": ").unwrap();
+    assert!(disk.load(&url).await.is_none());
+
+    let uri = feed_uri("/feed", "/fixtures/rss1_hatena.xml", "");
+    let res = get(&app(disk_config(&dir)).await, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "miss");
+    assert_eq!(disk.load(&url).await.unwrap().feed.items.len(), 2);
+}
+
+#[actix_rt::test]
+async fn overwrites_files_of_other_versions() {
+    let dir = temp_dir("version");
+    let url = mock_url("/fixtures/atom_github_releases.xml");
+    let disk = DiskCache::new(dir.clone(), u64::MAX);
+    fs::create_dir_all(&dir).unwrap();
+    let old = serde_json::json!({"version": VERSION + 1, "url": url, "items": []});
+    fs::write(disk.path(&url), old.to_string()).unwrap();
+    assert!(disk.load(&url).await.is_none());
+
+    let uri = feed_uri("/feed", "/fixtures/atom_github_releases.xml", "");
+    let res = get(&app(disk_config(&dir)).await, &uri).await;
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "miss");
+    assert_eq!(disk.load(&url).await.unwrap().version, VERSION);
+}
+
+#[actix_rt::test]
+async fn evicts_the_least_recently_used_files() {
+    let dir = temp_dir("evict");
+    let stored = |url: &str| {
+        Stored::new(
+            url.to_string(),
+            Duration::from_secs(300),
+            BTreeMap::new(),
+            &Bytes::from(vec![b'x'; 1_000]),
+            empty_feed(),
+        )
+    };
+    let disk = DiskCache::new(dir.clone(), u64::MAX);
+    disk.store(stored("http://a.example/feed")).await;
+    let size = fs::metadata(disk.path("http://a.example/feed"))
+        .unwrap()
+        .len();
+
+    // room for two
+    let disk = DiskCache::new(dir.clone(), size * 5 / 2);
+    std::thread::sleep(Duration::from_millis(20));
+    disk.store(stored("http://b.example/feed")).await;
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(disk.load("http://a.example/feed").await.is_some());
+    std::thread::sleep(Duration::from_millis(20));
+    disk.store(stored("http://c.example/feed")).await;
+
+    assert!(disk.load("http://a.example/feed").await.is_some());
+    assert!(disk.load("http://b.example/feed").await.is_none());
+    assert!(disk.load("http://c.example/feed").await.is_some());
+    // no temporary files left behind
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+}
@@ -0,0 +1,69 @@
+use rssss::display::display_url;
+
+#[test]
+fn restores_japanese_hosts() {
+    assert_eq!(
+        display_url("https://xn--r8jz45g.jp/feed").as_deref(),
+        Some("https://例え.jp/feed")
+    );
+    // katakana, hiragana and kanji mix in Japanese names
+    assert_eq!(
+        display_url("https://xn--eckwd4c7cu47r2wf.jp/").as_deref(),
+        Some("https://ドメイン名例.jp/")
+    );
+}
+
+#[test]
+fn restores_cyrillic_hosts() {
+    assert_eq!(
+        display_url("https://xn--e1afmkfd.xn--p1ai:8080/rss?x=1#top").as_deref(),
+        Some("https://пример.рф:8080/rss?x=1#top")
+    );
+}
+
+#[test]
+fn keeps_confusable_hosts_in_punycode() {
+    // a Cyrillic "а" in front of Latin "pple"
+    assert_eq!(display_url("https://xn--pple-43d.com/feed"), None);
+    assert_eq!(
+        display_url("https://xn--pple-43d.com/%E6%97%A5%E8%A8%98").as_deref(),
+        Some("https://xn--pple-43d.com/日記")
+    );
+}
+
+#[test]
+fn leaves_urls_that_read_the_same_alone() {
+    assert_eq!(display_url("https://example.com/feed?page=2"), None);
+    assert_eq!(display_url("not a url"), None);
+}
+
+#[test]
+fn decodes_utf8_paths() {
+    assert_eq!(
+        display_url("https://example.com/%E3%83%96%E3%83%AD%E3%82%B0/%D0%BD%D0%BE%D0%B2%D0%BE%D1%81%D1%82%D0%B8")
+            .as_deref(),
+        Some("https://example.com/ブログ/новости")
+    );
+    assert_eq!(
+        display_url("https://example.com/a%20b").as_deref(),
+        Some("https://example.com/a b")
+    );
+}
+
+#[test]
+fn leaves_paths_with_control_characters_or_invalid_utf8_encoded() {
+    assert_eq!(display_url("https://example.com/a%0Ab"), None);
+    assert_eq!(display_url("https://example.com/%E6%97%A5%00"), None);
+    assert_eq!(display_url("https://example.com/%E6%97"), None);
+    assert_eq!(display_url("https://example.com/%FF"), None);
+}
+
+#[test]
+fn keeps_reserved_characters_escaped() {
+    // decoded, they would read as another path, a query, a fragment or another escape
+    assert_eq!(
+        display_url("https://example.com/a%2Fb%3Fc%23d%2541/%E6%97%A5").as_deref(),
+        Some("https://example.com/a%2Fb%3Fc%23d%2541/日")
+    );
+    assert_eq!(display_url("https://example.com/a%2fb"), None);
+}
@@ -0,0 +1,100 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use rssss::rss::{parse_rss, parse_rss_with, ParseOptions, Recovery};
+use serde_json::json;
+use support::{app, config, feed_uri, fixture, get};
+
+const BROKEN_ITEM: &str = "/fixtures/rss2_broken_item.xml";
+const WARNING: &str = "item 4 dropped, neither a title nor a link could be read from it; \
+    read: description \"Post number 4, its title and link are in a leftover div.\", \
+    pub_date \"Thu, 04 Apr 2024 09:00:00 +0000\"";
+
+#[test]
+fn drops_the_item_its_title_and_link_were_lost_from() {
+    let feed = parse_rss(fixture("rss2_broken_item.xml")).unwrap();
+    assert_eq!(feed.items.len(), 9);
+    assert_eq!(feed.items[2].title(), "A new release");
+    assert_eq!(feed.items[3].title(), "Trains and timetables");
+    assert_eq!(feed.dropped_items.len(), 1);
+    assert_eq!(feed.dropped_items[0].ordinal, 4);
+    assert_eq!(feed.warnings, [WARNING]);
+}
+
+#[test]
+fn keeps_an_item_with_only_a_title_or_a_link() {
+    let feed = parse_rss(
+        "<rss version=\"2.0\"><channel><title>t</title>\
+         <item><title>Only a title</title></item>\
+         <item><link>https://example.com/only-a-link</link></item>\
+         <item><description>Neither</description></item>\
+         </channel></rss>",
+    )
+    .unwrap();
+    assert_eq!(feed.items.len(), 2);
+    assert_eq!(feed.dropped_items[0].ordinal, 3);
+    assert_eq!(
+        feed.warnings,
+        ["item 3 dropped, neither a title nor a link could be read from it; read: description \"Neither\""]
+    );
+}
+
+#[test]
+fn counts_the_items_the_lenient_scan_drops() {
+    let document = "<rss version=\"2.0\"><channel><title>t</title>\
+        <item><title>First <b>post</title><link>https://example.com/1</link></item>\
+        <item><description>No title & no link</item>\
+        <item><title>Third</title><link>https://example.com/3</link></item>\
+        </channel>";
+    let lenient = ParseOptions {
+        recover: true,
+        ..ParseOptions::default()
+    };
+    let feed = parse_rss_with(document, lenient).unwrap();
+    assert_eq!(feed.recovery, Some(Recovery::Scanned));
+    assert_eq!(feed.items.len(), 2);
+    assert_eq!(feed.dropped_items[0].ordinal, 2);
+    assert!(feed
+        .warnings
+        .iter()
+        .any(|w| w.starts_with("item 2 dropped")));
+}
+
+#[actix_rt::test]
+async fn lists_the_dropped_items_with_debug() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/v2/feed", BROKEN_ITEM, "&debug=true")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.json["items"].as_array().unwrap().len(), 9);
+    assert_eq!(res.json["warnings"], json!([WARNING]));
+    assert_eq!(
+        res.json["dropped_items"],
+        json!([{
+            "ordinal": 4,
+            "description": "Post number 4, its title and link are in a leftover div.",
+            "pub_date": "Thu, 04 Apr 2024 09:00:00 +0000"
+        }])
+    );
+
+    let res = get(&app, &feed_uri("/v2/feed", BROKEN_ITEM, "")).await;
+    assert_eq!(res.json["warnings"], json!([WARNING]));
+    assert!(res.json.get("dropped_items").is_none());
+}
+
+#[actix_rt::test]
+async fn reports_the_dropped_items_when_validating() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed/validate", BROKEN_ITEM, "")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.json["item_count"], 9);
+    assert_eq!(res.json["dropped_items"], 1);
+    let finding = res.json["findings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|f| f["code"] == "dropped_item")
+        .unwrap();
+    assert_eq!(finding["severity"], "warning");
+    assert_eq!(finding["item"], 4);
+    assert_eq!(finding["message"], WARNING);
+}
@@ -0,0 +1,508 @@
+mod support;
+
+use actix_web::body::to_bytes;
+use actix_web::http::StatusCode;
+use actix_web::web::{self, Bytes};
+use actix_web::ResponseError;
+use awc::error::SendRequestError;
+use chrono::Utc;
+use rssss::error::*;
+use rssss::hosts::{validate_url, HostFilter};
+use rssss::rss::{parse_rss, parse_rss_with, ParseOptions, Strictness};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::time::Duration;
+use support::{app, config, feed_uri, get, url_uri};
+
+fn parse(document: &str) -> ApiError {
+    match parse_rss(Bytes::from(document.to_string())) {
+        Ok(_) => panic!("parsed {}", document),
+        Err(e) => e,
+    }
+}
+
+fn url(url: &str) -> ApiError {
+    validate_url(
+        url,
+        &HostFilter::new(Vec::new(), vec!["denied.example".to_string()]),
+    )
+    .unwrap_err()
+}
+
+// every way an error comes to be, with the code it is expected to carry
+async fn errors() -> Vec<(ApiError, ErrorCode)> {
+    vec![
+        (parse("<rss version=\"2.0\">"), ErrorCode::XmlParseError),
+        (
+            parse("<?xml version=\"1.0\" encoding=\"EUC-JP\"?><rss version=\"2.0\"/>"),
+            ErrorCode::UnsupportedEncoding,
+        ),
+        (parse("<channel/>"), ErrorCode::InvalidRootElement),
+        (parse("<feed/>"), ErrorCode::InvalidRootNamespace),
+        (
+            parse("<rss version=\"0.91\"/>"),
+            ErrorCode::UnsupportedRssVersion,
+        ),
+        (parse("<rss/>"), ErrorCode::UndefinedRssVersion),
+        (
+            parse_rss_with(
+                Bytes::from_static(
+                    b"<rss version=\"2.0\"><channel><title>A</title></channel>\
+                      <channel><title>B</title></channel></rss>",
+                ),
+                ParseOptions::of(Strictness::Strict),
+            )
+            .unwrap_err(),
+            ErrorCode::StrictModeWarning,
+        ),
+        (
+            parse("{\"version\": \"https://jsonfeed.org/version/1.1\"}"),
+            ErrorCode::UnsupportedFormat,
+        ),
+        (url("not a url"), ErrorCode::InvalidUrl),
+        (url("ftp://example.com/feed"), ErrorCode::InvalidUrlScheme),
+        (
+            url("https://denied.example/feed"),
+            ErrorCode::HostNotAllowed,
+        ),
+        (
+            InvalidParameterError {
+                name: "page".to_string(),
+                message: "must be at least 1".to_string(),
+            }
+            .into(),
+            ErrorCode::InvalidParameter,
+        ),
+        (upstream(StatusCode::GONE), ErrorCode::FeedGone),
+        (upstream(StatusCode::NOT_FOUND), ErrorCode::FeedNotFound),
+        (upstream(StatusCode::FORBIDDEN), ErrorCode::AccessDenied),
+        (
+            InvalidRedirectError {
+                location: Some("http://[".to_string()),
+            }
+            .into(),
+            ErrorCode::InvalidRedirectLocation,
+        ),
+        (
+            InvalidRedirectError { location: None }.into(),
+            ErrorCode::RedirectWithoutLocation,
+        ),
+        (
+            RedirectLimitError { limit: 3 }.into(),
+            ErrorCode::RedirectLimitExceeded,
+        ),
+        (SendRequestError::Timeout.into(), ErrorCode::UpstreamTimeout),
+        (
+            SendRequestError::Connect(awc::error::ConnectError::Unresolved).into(),
+            ErrorCode::UpstreamConnectFailed,
+        ),
+        (
+            SendRequestError::TunnelNotSupported.into(),
+            ErrorCode::UpstreamRequestFailed,
+        ),
+        (
+            UpstreamStalledError {
+                bytes: 512,
+                idle: Duration::from_secs(5),
+            }
+            .into(),
+            ErrorCode::UpstreamStalled,
+        ),
+        (
+            PayloadTooLargeError { limit: 1024 }.into(),
+            ErrorCode::BodyTooLarge,
+        ),
+        (
+            actix_http::error::PayloadError::Overflow.into(),
+            ErrorCode::BodyTooLarge,
+        ),
+        (
+            actix_http::error::PayloadError::Incomplete(None).into(),
+            ErrorCode::InvalidPayload,
+        ),
+        (
+            actix_multipart::MultipartError::Boundary.into(),
+            ErrorCode::InvalidMultipart,
+        ),
+        (
+            base64::decode("!").unwrap_err().into(),
+            ErrorCode::InvalidBase64,
+        ),
+        (
+            serde_json::from_str::<serde_json::Value>("{")
+                .unwrap_err()
+                .into(),
+            ErrorCode::InvalidJson,
+        ),
+        (
+            TooManyDocumentsError { limit: 10 }.into(),
+            ErrorCode::TooManyDocuments,
+        ),
+        (
+            IconNotFoundError {
+                host: "example.com".to_string(),
+            }
+            .into(),
+            ErrorCode::IconNotFound,
+        ),
+        (
+            UnsupportedMediaTypeError {
+                content_type: "text/plain".to_string(),
+            }
+            .into(),
+            ErrorCode::UnsupportedMediaType,
+        ),
+        (SnapshotExpiredError.into(), ErrorCode::SnapshotExpired),
+        (
+            DeadlineExceededError {
+                limit: Duration::from_millis(100),
+                phase: "fetching".to_string(),
+            }
+            .into(),
+            ErrorCode::DeadlineExceeded,
+        ),
+        (
+            RobotsDisallowedError {
+                robots_url: "https://example.com/robots.txt".to_string(),
+                rule: "Disallow: /feed".to_string(),
+            }
+            .into(),
+            ErrorCode::DisallowedByRobots,
+        ),
+        (SelftestDisabledError.into(), ErrorCode::SelftestDisabled),
+        (
+            FeedTooExpensiveError {
+                elapsed: Duration::from_millis(900),
+                threshold: Duration::from_millis(500),
+            }
+            .into(),
+            ErrorCode::FeedTooExpensive,
+        ),
+        (
+            LocationTooLongError {
+                len: 9_000,
+                limit: 8_192,
+            }
+            .into(),
+            ErrorCode::RedirectLocationTooLong,
+        ),
+        (
+            RedirectBudgetError {
+                hop: 2,
+                url: "https://example.com/slow".to_string(),
+                budget: Duration::from_secs(60),
+            }
+            .into(),
+            ErrorCode::RedirectBudgetExceeded,
+        ),
+        (
+            RedirectBodyTooLargeError {
+                hop: 0,
+                url: "https://example.com/moved".to_string(),
+                limit: 16_384,
+            }
+            .into(),
+            ErrorCode::RedirectBodyTooLarge,
+        ),
+        (
+            FixtureNotFoundError {
+                url: "https://example.com/feed".to_string(),
+                name: "example.com_feed".to_string(),
+                available: vec!["example.com_rss".to_string()],
+                more: 0,
+            }
+            .into(),
+            ErrorCode::FixtureNotFound,
+        ),
+        (
+            UnauthorizedError { missing: true }.into(),
+            ErrorCode::ApiKeyRequired,
+        ),
+        (
+            UnauthorizedError { missing: false }.into(),
+            ErrorCode::InvalidApiKey,
+        ),
+        (
+            QuotaExceededError {
+                limit: 1,
+                used: 2,
+                resets_at: Utc::now(),
+            }
+            .into(),
+            ErrorCode::QuotaExceeded,
+        ),
+        (
+            web::block(|| panic!("lost the blocking thread"))
+                .await
+                .unwrap_err()
+                .into(),
+            ErrorCode::Internal,
+        ),
+    ]
+}
+
+fn upstream(status: StatusCode) -> ApiError {
+    UpstreamStatusError {
+        status,
+        user_agent: "rssss".to_string(),
+    }
+    .into()
+}
+
+#[actix_rt::test]
+async fn every_error_carries_its_code() {
+    for (error, code) in errors().await {
+        // a document no parser takes has a message from each of them
+        assert!(error.codes().contains(&code), "{:?}", error);
+    }
+}
+
+#[actix_rt::test]
+async fn every_code_is_reachable() {
+    let reached = errors()
+        .await
+        .into_iter()
+        .map(|(_, code)| code)
+        .collect::<HashSet<ErrorCode>>();
+    let all = ErrorCode::ALL.into_iter().collect::<HashSet<ErrorCode>>();
+    assert_eq!(reached, all);
+}
+
+fn variant(error: &ApiError) -> &'static str {
+    match error {
+        ApiError::Request(_) => "request",
+        ApiError::Transport(_) => "transport",
+        ApiError::UpstreamStatus(_) => "upstream_status",
+        ApiError::Payload(_) => "payload",
+        ApiError::Parse(_) => "parse",
+        ApiError::Internal(_) => "internal",
+    }
+}
+
+// where each code comes from, a new code has to be placed here
+fn expected_variant(code: ErrorCode) -> &'static str {
+    match code {
+        ErrorCode::XmlParseError
+        | ErrorCode::UnsupportedEncoding
+        | ErrorCode::InvalidRootElement
+        | ErrorCode::InvalidRootNamespace
+        | ErrorCode::UnsupportedRssVersion
+        | ErrorCode::UndefinedRssVersion
+        | ErrorCode::StrictModeWarning
+        | ErrorCode::UnsupportedFormat
+        | ErrorCode::FeedTooExpensive => "parse",
+        ErrorCode::InvalidUrl
+        | ErrorCode::InvalidUrlScheme
+        | ErrorCode::InvalidParameter
+        | ErrorCode::HostNotAllowed
+        | ErrorCode::SnapshotExpired
+        | ErrorCode::ApiKeyRequired
+        | ErrorCode::InvalidApiKey
+        | ErrorCode::QuotaExceeded
+        | ErrorCode::DisallowedByRobots
+        | ErrorCode::SelftestDisabled
+        | ErrorCode::FixtureNotFound => "request",
+        ErrorCode::UpstreamTimeout
+        | ErrorCode::UpstreamConnectFailed
+        | ErrorCode::UpstreamRequestFailed
+        | ErrorCode::UpstreamStalled
+        | ErrorCode::DeadlineExceeded
+        | ErrorCode::RedirectBudgetExceeded => "transport",
+        ErrorCode::FeedGone
+        | ErrorCode::FeedNotFound
+        | ErrorCode::AccessDenied
+        | ErrorCode::InvalidRedirectLocation
+        | ErrorCode::RedirectWithoutLocation
+        | ErrorCode::RedirectLocationTooLong
+        | ErrorCode::RedirectLimitExceeded
+        | ErrorCode::RedirectBodyTooLarge
+        | ErrorCode::IconNotFound => "upstream_status",
+        ErrorCode::BodyTooLarge
+        | ErrorCode::InvalidPayload
+        | ErrorCode::InvalidMultipart
+        | ErrorCode::InvalidBase64
+        | ErrorCode::InvalidJson
+        | ErrorCode::TooManyDocuments
+        | ErrorCode::UnsupportedMediaType => "payload",
+        ErrorCode::Internal => "internal",
+    }
+}
+
+#[actix_rt::test]
+async fn every_error_has_the_variant_of_its_code() {
+    for (error, code) in errors().await {
+        assert_eq!(variant(&error), expected_variant(code), "{:?}", error);
+    }
+}
+
+async fn v1_body(error: &ApiError) -> Value {
+    let body = to_bytes(error.error_response().into_body()).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[actix_rt::test]
+async fn every_error_renders_its_status_and_messages() {
+    for (error, _) in errors().await {
+        let res = error.error_response();
+        assert_eq!(res.status(), error.status(), "{:?}", error);
+        let mut expected = json!({"messages": error.messages()});
+        if let Some(kind) = error.kind() {
+            expected["kind"] = serde_json::to_value(kind).unwrap();
+        }
+        assert_eq!(v1_body(&error).await, expected);
+    }
+}
+
+#[actix_rt::test]
+async fn each_variant_responds_as_before() {
+    let cases: Vec<(ApiError, &str, StatusCode, Value)> = vec![
+        (
+            InvalidParameterError {
+                name: "page".to_string(),
+                message: "must be positive".to_string(),
+            }
+            .into(),
+            "request",
+            StatusCode::BAD_REQUEST,
+            json!({"messages": ["invalid parameter page: must be positive"]}),
+        ),
+        (
+            HostNotAllowedError {
+                host: "denied.example".to_string(),
+            }
+            .into(),
+            "request",
+            StatusCode::FORBIDDEN,
+            json!({"kind": "host_not_allowed", "messages": ["host is not allowed: denied.example"]}),
+        ),
+        (
+            SendRequestError::Timeout.into(),
+            "transport",
+            StatusCode::BAD_REQUEST,
+            json!({"messages": ["Timeout while waiting for response"]}),
+        ),
+        (
+            DeadlineExceededError {
+                limit: Duration::from_millis(100),
+                phase: "fetching".to_string(),
+            }
+            .into(),
+            "transport",
+            StatusCode::GATEWAY_TIMEOUT,
+            json!({"kind": "deadline_exceeded", "messages": ["deadline of 100ms exceeded while fetching"]}),
+        ),
+        (
+            upstream(StatusCode::GONE),
+            "upstream_status",
+            StatusCode::GONE,
+            json!({"kind": "feed_gone", "messages": ["feed permanently gone"]}),
+        ),
+        (
+            RedirectLimitError { limit: 3 }.into(),
+            "upstream_status",
+            StatusCode::INTERNAL_SERVER_ERROR,
+            json!({"kind": "internal", "messages": ["more than 3 redirects"]}),
+        ),
+        (
+            PayloadTooLargeError { limit: 10 }.into(),
+            "payload",
+            StatusCode::PAYLOAD_TOO_LARGE,
+            json!({"kind": "payload_too_large", "messages": ["payload exceeds 10 bytes"]}),
+        ),
+        (
+            InvalidRssError {
+                code: ErrorCode::UndefinedRssVersion,
+                message: "[RSS V2] undefined RSS version".to_string(),
+            }
+            .into(),
+            "parse",
+            StatusCode::BAD_REQUEST,
+            json!({"messages": ["[RSS V2] undefined RSS version"]}),
+        ),
+        (
+            web::block(|| panic!("lost the blocking thread"))
+                .await
+                .unwrap_err()
+                .into(),
+            "internal",
+            StatusCode::INTERNAL_SERVER_ERROR,
+            json!({"kind": "internal", "messages": ["Blocking thread pool is shut down unexpectedly"]}),
+        ),
+    ];
+    for (error, expected_variant, status, body) in cases {
+        assert_eq!(variant(&error), expected_variant, "{:?}", error);
+        assert_eq!(error.status(), status, "{:?}", error);
+        assert_eq!(error.error_response().status(), status, "{:?}", error);
+        assert_eq!(v1_body(&error).await, body);
+        assert_eq!(error.to_string(), error.messages().join(","));
+    }
+}
+
+#[test]
+fn merged_errors_take_the_variant_of_the_first() {
+    let error = ApiError::from(vec![url("not a url"), upstream(StatusCode::NOT_FOUND)]);
+    assert_eq!(variant(&error), "request");
+    // and the first kind, which decides the status
+    assert_eq!(error.status(), StatusCode::NOT_FOUND);
+    let error = ApiError::from(vec![upstream(StatusCode::GONE), url("not a url")]);
+    assert_eq!(variant(&error), "upstream_status");
+}
+
+#[test]
+fn codes_follow_merged_messages() {
+    let error = ApiError::from(vec![url("not a url"), upstream(StatusCode::NOT_FOUND)]);
+    assert_eq!(
+        error.codes(),
+        [ErrorCode::InvalidUrl, ErrorCode::FeedNotFound]
+    );
+    let body = serde_json::to_value(error.coded()).unwrap();
+    assert_eq!(body["kind"], "feed_not_found");
+    assert_eq!(body["errors"][0]["code"], "invalid_url");
+    assert_eq!(body["errors"][1]["code"], "feed_not_found");
+    assert_eq!(body["errors"][1]["message"], "feed not found");
+}
+
+#[actix_rt::test]
+async fn v2_responds_with_codes() {
+    let app = app(config()).await;
+    let res = get(&app, &url_uri("/v2/feed", "ftp://example.com/feed", "")).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.json["errors"][0]["code"], "invalid_url_scheme");
+    assert_eq!(
+        res.json["errors"][0]["message"],
+        "unsupported url scheme: ftp"
+    );
+    assert!(res.json.get("messages").is_none());
+
+    let res = get(&app, &feed_uri("/v2/feed", "/redirect/10/youtube.xml", "")).await;
+    assert_eq!(res.status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(res.json["errors"][0]["code"], "redirect_limit_exceeded");
+}
+
+#[actix_rt::test]
+async fn v2_codes_invalid_queries() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &url_uri("/v2/feed", "https://example.com/feed", "&page=x"),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.json["errors"][0]["code"], "invalid_parameter");
+}
+
+#[actix_rt::test]
+async fn v1_keeps_its_error_shape() {
+    let app = app(config()).await;
+    let res = get(&app, &url_uri("/feed", "ftp://example.com/feed", "")).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        res.json,
+        serde_json::json!({"messages": ["unsupported url scheme: ftp"]})
+    );
+
+    let res = get(&app, &feed_uri("/feed", "/redirect/10/youtube.xml", "")).await;
+    assert_eq!(res.status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(res.json["kind"], "internal");
+    assert!(res.json.get("errors").is_none());
+}
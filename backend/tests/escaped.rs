@@ -0,0 +1,122 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use rssss::escaped::{self, escapes_markup};
+use serde_json::Value;
+use support::{app, config, feed_uri, get};
+
+async fn feed(fixture: &str, params: &str) -> Value {
+    let app = app(config()).await;
+    let path = format!("/fixtures/{}", fixture);
+    let res = get(&app, &feed_uri("/v2/feed", &path, params)).await;
+    assert_eq!(res.status, StatusCode::OK, "{}: {}", fixture, res.json);
+    res.json
+}
+
+#[actix_rt::test]
+async fn decodes_an_escaped_description_once() {
+    let json = feed("rss2_escaped_once.xml", "").await;
+    assert_eq!(
+        json["items"][0]["description"],
+        "Bring your spare seeds & cuttings to the hut."
+    );
+    assert!(json.get("warnings").is_none(), "{}", json);
+}
+
+#[actix_rt::test]
+async fn decodes_a_description_escaped_twice_again() {
+    let json = feed("rss2_escaped_twice.xml", "").await;
+    let once = feed("rss2_escaped_once.xml", "").await;
+    assert_eq!(
+        json["items"][0]["description"],
+        once["items"][0]["description"]
+    );
+    assert_eq!(
+        json["items"][1]["description"],
+        "Empty the butts before the first frost."
+    );
+    // three decodes at most, what is left is shown as it is
+    assert_eq!(
+        json["items"][2]["description"],
+        "<p>Keys are with the secretary.</p>"
+    );
+    assert_eq!(
+        json["warnings"],
+        serde_json::json!([
+            "item 1 description unescaped 2 times, the feed escapes it more than once",
+            "item 2 description unescaped 3 times, the feed escapes it more than once",
+            "item 3 description unescaped 3 times, the feed escapes it more than once"
+        ])
+    );
+
+    // the markup is the decoded one, other formats render the elements
+    let json = feed("rss2_escaped_twice.xml", "&format=html").await;
+    assert_eq!(
+        json["items"][0]["description"],
+        "<p>Bring your spare seeds &amp; cuttings to the <strong>hut</strong>.</p>"
+    );
+}
+
+#[actix_rt::test]
+async fn leaves_code_samples_alone() {
+    let json = feed("rss2_escaped_code.xml", "").await;
+    let descriptions = json["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["description"].as_str().unwrap())
+        .collect::<Vec<&str>>();
+    assert_eq!(
+        descriptions,
+        [
+            "<div> starts a block, <span> doesn't.",
+            "Wrap the text in <p>...</p> and close it.",
+            "<div></div>",
+        ]
+    );
+    assert!(json.get("warnings").is_none(), "{}", json);
+}
+
+#[test]
+fn takes_balanced_escaped_elements_for_markup() {
+    for markup in [
+        "&lt;p&gt;Hello&lt;/p&gt;",
+        "&LT;P&GT;Hello&LT;/P&GT;",
+        "&lt;a href=\"/x\"&gt;x&lt;/a&gt; and &lt;br&gt;",
+        "&amp;lt;p&amp;gt;Hello&amp;lt;/p&amp;gt;",
+    ] {
+        assert!(escapes_markup(markup), "{}", markup);
+    }
+    for markup in [
+        "Hello",
+        "&lt;div&gt; starts a block",
+        "&lt;p&gt;one&lt;/p&gt; and &lt;em&gt;two",
+        // not the elements they start like
+        "&lt;pre&gt;&lt;/pre&gt; and &lt;p2p&gt;&lt;/p2p&gt;",
+        "&lt;br&gt;&lt;img src=x&gt;",
+        "<p>&lt;p&gt;...&lt;/p&gt;</p>",
+        "1 &lt; 2 &amp;&amp; 3 &gt; 2",
+    ] {
+        assert!(!escapes_markup(markup), "{}", markup);
+    }
+}
+
+#[test]
+fn unescapes_up_to_the_limit() {
+    assert_eq!(
+        escaped::unescape("&lt;p&gt;a &amp;amp; b&lt;/p&gt;".to_string()),
+        ("<p>a &amp; b</p>".to_string(), 2)
+    );
+    assert_eq!(
+        escaped::unescape("<p>a &amp; b</p>".to_string()),
+        ("<p>a &amp; b</p>".to_string(), 1)
+    );
+    let deep = "&amp;amp;amp;lt;b&amp;amp;amp;gt;x&amp;amp;amp;lt;/b&amp;amp;amp;gt;";
+    assert_eq!(
+        escaped::unescape(deep.to_string()),
+        (
+            "&amp;lt;b&amp;gt;x&amp;lt;/b&amp;gt;".to_string(),
+            escaped::MAX_PASSES
+        )
+    );
+}
@@ -0,0 +1,104 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use rssss::expand::{expand, extract};
+use rssss::hosts::HostFilter;
+use rssss::rss::{FieldLimits, Rss};
+use support::{app, config, feed_uri, fixture, get, mock_url};
+
+const STUB: &str = "Read more";
+
+fn stubs(count: usize) -> Vec<Rss> {
+    (0..count)
+        .map(|i| {
+            let link = mock_url(&format!("/site/article.html?item={}", i));
+            Rss::new(i.to_string(), link, STUB)
+        })
+        .collect()
+}
+
+fn filter(allow: &[&str], deny: &[&str]) -> HostFilter {
+    let list = |hosts: &[&str]| hosts.iter().map(|h| h.to_string()).collect();
+    HostFilter::new(list(allow), list(deny))
+}
+
+fn limits() -> FieldLimits {
+    FieldLimits::default()
+}
+
+fn expanded(item: &Rss) -> bool {
+    item.description().starts_with("Harbour festival returns")
+}
+
+#[test]
+fn extracts_the_article_rather_than_the_page() {
+    let html = extract(&fixture("article.html")).unwrap();
+    assert!(
+        html.contains("<h1>Harbour festival returns</h1>"),
+        "{}",
+        html
+    );
+    assert!(!html.contains("News"), "{}", html);
+    assert_eq!(extract("<html><body><nav></nav></body></html>"), None);
+}
+
+#[actix_rt::test]
+async fn expands_stub_descriptions_from_the_linked_pages() {
+    let mut items = stubs(3);
+    let long = Rss::new("long", mock_url("/site/article.html"), "word ".repeat(30));
+    items.push(long.clone());
+    expand(&mut items, &filter(&[], &[]), true, 10, &limits(), "rssss").await;
+    assert!(items[..3].iter().all(expanded), "{:?}", items);
+    // a description of its own is kept
+    assert_eq!(items[3].description(), long.description());
+}
+
+#[actix_rt::test]
+async fn expands_at_most_the_limit() {
+    let mut items = stubs(3);
+    expand(&mut items, &filter(&[], &[]), true, 2, &limits(), "rssss").await;
+    assert_eq!(
+        items.iter().map(expanded).collect::<Vec<bool>>(),
+        [true, true, false]
+    );
+}
+
+#[actix_rt::test]
+async fn refuses_pages_at_private_addresses() {
+    let mut items = stubs(2);
+    expand(&mut items, &filter(&[], &[]), false, 10, &limits(), "rssss").await;
+    assert!(items.iter().all(|item| item.description() == STUB));
+}
+
+#[actix_rt::test]
+async fn refuses_pages_of_hosts_not_allowed() {
+    for hosts in [filter(&[], &["127.0.0.1"]), filter(&["example.com"], &[])] {
+        let mut items = stubs(2);
+        expand(&mut items, &hosts, true, 10, &limits(), "rssss").await;
+        assert!(items.iter().all(|item| item.description() == STUB));
+    }
+}
+
+#[actix_rt::test]
+async fn expands_feed_items_only_when_asked() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed", "/stubs/2", "")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.json[0]["description"], STUB);
+    assert_eq!(res.json[1]["description"], STUB);
+
+    let res = get(&app, &feed_uri("/feed", "/stubs/2", "&expand=true")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    for item in res.json.as_array().unwrap() {
+        let description = item["description"].as_str().unwrap();
+        assert!(
+            description.starts_with("Harbour festival returns"),
+            "{}",
+            description
+        );
+    }
+
+    // the expansion isn't cached as the feed
+    let res = get(&app, &feed_uri("/feed", "/stubs/2", "&expand=false")).await;
+    assert_eq!(res.json[0]["description"], STUB);
+}
@@ -0,0 +1,735 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::web::Bytes;
+use rssss::rss::{parse_rss, syntax, Channel, Syntax};
+use serde_json::{json, Value};
+use support::{app, config, feed_uri, get, mock_url};
+use url::Url;
+
+#[actix_rt::test]
+async fn rss2_prefers_description_and_falls_back_to_content_encoded() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed", "/fixtures/rss2_wordpress.xml", "")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    let items = res.json.as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["title"], "Release Notes for 2.4");
+    assert_eq!(
+        items[0]["link"],
+        "https://blog.example.com/2024/05/release-notes-2-4/"
+    );
+    assert_eq!(items[0]["pub_date"], "Tue, 07 May 2024 09:00:00 +0000");
+    assert!(items[0]["description"]
+        .as_str()
+        .unwrap()
+        .starts_with("Version 2.4 is out"));
+    assert!(items[1]["description"]
+        .as_str()
+        .unwrap()
+        .starts_with("This month we sat down with the people who write our handbook."));
+}
+
+#[actix_rt::test]
+async fn atom_takes_alternate_links_and_updated_dates() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/feed", "/fixtures/atom_github_releases.xml", ""),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK);
+    let items = res.json.as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["title"], "v1.2.0");
+    assert_eq!(
+        items[0]["link"],
+        "https://github.com/example/widget/releases/tag/v1.2.0"
+    );
+    assert_eq!(items[0]["pub_date"], "2024-05-07T09:00:00Z");
+    assert_eq!(
+        items[0]["description"],
+        "Features\n\nAdd streaming mode by @octocat in #42"
+    );
+}
+
+#[actix_rt::test]
+async fn rss1_reads_dublin_core_dates() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed", "/fixtures/rss1_hatena.xml", "")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    let items = res.json.as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["title"], "Rustの非同期処理を完全に理解する");
+    assert_eq!(items[0]["pub_date"], "2024-05-07T18:00:00+09:00");
+    assert_eq!(
+        items[1]["description"],
+        "実行計画を読みながら原因を切り分けます。"
+    );
+}
+
+#[actix_rt::test]
+async fn youtube_uses_media_description() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed", "/fixtures/youtube.xml", "")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    let items = res.json.as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(
+        items[0]["link"],
+        "https://www.youtube.com/watch?v=aaaaaaaaaaa"
+    );
+    assert_eq!(items[0]["pub_date"], "2024-05-06T15:00:00+00:00");
+    assert_eq!(
+        items[0]["description"],
+        "We build a small feed reader from scratch, from fetching to rendering."
+    );
+}
+
+#[actix_rt::test]
+async fn v2_wraps_items_with_feed_metadata() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_wordpress.xml", ""),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.json["url"], mock_url("/fixtures/rss2_wordpress.xml"));
+    assert_eq!(res.json["channel"]["link"], "https://blog.example.com");
+    assert_eq!(
+        res.json["channel"]["pub_date"],
+        "Tue, 07 May 2024 09:12:44 +0000"
+    );
+    assert_eq!(res.json["items"].as_array().unwrap().len(), 2);
+}
+
+#[actix_rt::test]
+async fn follows_redirect_chains_up_to_the_limit() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed", "/redirect/2/youtube.xml", "")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.json.as_array().unwrap().len(), 1);
+
+    let res = get(&app, &feed_uri("/feed", "/redirect/10/youtube.xml", "")).await;
+    assert_eq!(res.status, StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[actix_rt::test]
+async fn waits_for_slow_upstreams() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed", "/slow/300/youtube.xml", "")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.json.as_array().unwrap().len(), 1);
+}
+
+#[actix_rt::test]
+async fn decodes_gzip_responses() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed", "/gzip/rss1_hatena.xml", "")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.json.as_array().unwrap().len(), 2);
+}
+
+#[actix_rt::test]
+async fn rejects_non_utf8_encodings() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/feed", "/charset/ISO-8859-1/rss2_wordpress.xml", ""),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert!(res.json["messages"]
+        .as_array()
+        .unwrap()
+        .contains(&"[RSS V2] unsupported encoding: ISO-8859-1".into()));
+}
+
+#[actix_rt::test]
+async fn reports_upstream_statuses() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed", "/status/404", "")).await;
+    assert_eq!(res.status, StatusCode::NOT_FOUND);
+    assert_eq!(res.json["kind"], "feed_not_found");
+
+    let res = get(&app, &feed_uri("/feed", "/status/410", "")).await;
+    assert_eq!(res.status, StatusCode::GONE);
+    assert_eq!(res.json["kind"], "feed_gone");
+
+    let res = get(&app, &feed_uri("/feed", "/status/503", "")).await;
+    assert_eq!(res.status, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(res.json.is_null());
+}
+
+// 304 is a redirection status without a location
+#[actix_rt::test]
+async fn treats_not_modified_as_an_invalid_redirect() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed", "/status/304", "")).await;
+    assert_eq!(res.status, StatusCode::BAD_GATEWAY);
+    assert_eq!(res.json["kind"], "invalid_redirect");
+}
+
+#[actix_rt::test]
+async fn serves_repeated_requests_from_the_cache() {
+    let app = app(config()).await;
+    let uri = feed_uri("/feed", "/fixtures/youtube.xml", "");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "miss");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "hit");
+}
+
+#[actix_rt::test]
+async fn exposes_only_allowed_upstream_headers() {
+    let app = app(config()).await;
+    let uri = feed_uri("/v2/feed", "/headers/youtube.xml", "");
+    for _ in 0..2 {
+        let res = get(&app, &uri).await;
+        let headers = res.json["upstream_headers"].as_object().unwrap();
+        assert_eq!(headers["etag"], "\"v1\"");
+        assert_eq!(headers["last-modified"], "Tue, 07 May 2024 09:00:00 GMT");
+        assert_eq!(headers["server"], "mock");
+        assert_eq!(headers["content-type"], "application/xml; charset=utf-8");
+        assert!(headers.contains_key("content-length"));
+        assert!(!headers.contains_key("set-cookie"));
+        assert!(!headers.contains_key("x-internal-token"));
+        assert!(!res.json.to_string().contains("secret"));
+    }
+}
+
+#[actix_rt::test]
+async fn rss2_attributes_aggregated_items_to_their_source() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed", "/fixtures/rss2_planet.xml", "")).await;
+    let items = res.json.as_array().unwrap();
+    assert_eq!(items[0]["source"]["title"], "Alice's Blog");
+    assert_eq!(items[0]["source"]["url"], "https://alice.example.com/feed/");
+    assert!(items[1].get("source").is_none());
+}
+
+#[actix_rt::test]
+async fn atom_reads_sources_without_taking_their_links() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed", "/fixtures/atom_planet.xml", "")).await;
+    let items = res.json.as_array().unwrap();
+    assert_eq!(
+        items[0]["link"],
+        "https://alice.example.com/2024/05/profiling-async-code/"
+    );
+    assert_eq!(items[0]["title"], "Profiling async code");
+    assert_eq!(items[0]["pub_date"], "2024-05-07T08:00:00Z");
+    assert_eq!(items[0]["source"]["title"], "Alice's Blog");
+    assert_eq!(
+        items[0]["source"]["url"],
+        "https://alice.example.com/feed/atom/"
+    );
+    assert_eq!(items[1]["link"], "https://bob.example.net/release-day");
+    assert_eq!(items[1]["source"]["title"], "Bob");
+    assert_eq!(items[1]["source"]["url"], "https://bob.example.net/");
+}
+
+#[actix_rt::test]
+async fn atom_entries_inherit_the_feed_authors_only_without_their_own() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/atom_github_tags.xml", ""),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK);
+    let example = json!({"name": "example", "uri": "https://github.com/example"});
+    assert_eq!(res.json["channel"]["authors"], json!([example]));
+
+    let items = res.json["items"].as_array().unwrap();
+    assert_eq!(
+        items[0]["authors"],
+        json!([
+            {"name": "octocat", "email": "octocat@example.com", "uri": "https://github.com/octocat"},
+            {"name": "hubot"}
+        ])
+    );
+    assert_eq!(items[0]["author"], "octocat, hubot");
+    assert_eq!(
+        items[0]["contributors"],
+        json!([{"name": "monalisa", "uri": "https://github.com/monalisa"}])
+    );
+
+    // the author of the source is the mirror's, and feed contributors aren't inherited
+    assert_eq!(items[1]["authors"], json!([example]));
+    assert_eq!(items[1]["author"], "example");
+    assert!(items[1].get("contributors").is_none());
+}
+
+#[actix_rt::test]
+async fn atom_reads_entry_authors() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/feed", "/fixtures/atom_github_releases.xml", ""),
+    )
+    .await;
+    let items = res.json.as_array().unwrap();
+    assert_eq!(items[0]["authors"], json!([{"name": "octocat"}]));
+    assert_eq!(items[0]["author"], "octocat");
+}
+
+#[actix_rt::test]
+async fn rss2_reads_the_channel_image() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_wordpress.xml", ""),
+    )
+    .await;
+    assert_eq!(
+        res.json["channel"]["image"],
+        json!({
+            "url": "https://blog.example.com/wp-content/uploads/2024/01/cropped-icon-32x32.png",
+            "title": "Example Blog",
+            "link": "https://blog.example.com/"
+        })
+    );
+}
+
+#[actix_rt::test]
+async fn atom_prefers_the_logo_over_the_icon() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/v2/feed", "/fixtures/atom_logo.xml", "")).await;
+    assert_eq!(
+        res.json["channel"]["image"],
+        json!({"url": "https://news.example.org/images/logo.svg"})
+    );
+}
+
+#[actix_rt::test]
+async fn rss1_reads_the_image_next_to_the_channel() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/v2/feed", "/fixtures/rss1_image.xml", "")).await;
+    assert_eq!(
+        res.json["channel"]["image"],
+        json!({
+            "url": "https://tech.example.net/images/topics/topictech.gif",
+            "title": "Example Tech",
+            "link": "https://tech.example.net/"
+        })
+    );
+    assert_eq!(
+        res.json["items"][0]["title"],
+        "Compilers are getting faster"
+    );
+}
+
+#[actix_rt::test]
+async fn resolves_relative_image_urls_against_the_feed() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_relative_image.xml", ""),
+    )
+    .await;
+    assert_eq!(
+        res.json["channel"]["image"],
+        json!({
+            "url": mock_url("/images/logo.png"),
+            "title": "Relative Image",
+            "link": mock_url("/")
+        })
+    );
+}
+
+#[actix_rt::test]
+async fn feeds_without_an_image_have_none() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/v2/feed", "/fixtures/youtube.xml", "")).await;
+    assert!(res.json["channel"]["image"].is_null());
+}
+
+#[actix_rt::test]
+async fn rss2_falls_back_to_dublin_core() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/feed", "/fixtures/rss2_wordpress_export.xml", ""),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK);
+    let items = res.json.as_array().unwrap();
+    assert_eq!(items.len(), 3);
+
+    // pubDate wins over dc:date, whichever comes first
+    assert_eq!(items[0]["pub_date"], "Fri, 10 May 2024 18:00:00 +0000");
+    assert_eq!(items[2]["pub_date"], "Sat, 20 Apr 2024 10:00:00 +0000");
+    assert_eq!(items[1]["pub_date"], "2024-04-27T16:45:00Z");
+
+    // dc:creator wins over author
+    assert_eq!(items[0]["author"], "maria");
+    assert_eq!(items[1]["author"], "sam");
+    assert_eq!(items[2]["author"], "editor@garden.example.org (Sam Green)");
+
+    assert_eq!(items[0]["categories"], json!(["Vegetables", "tomatoes"]));
+    assert_eq!(items[1]["categories"], json!(["Events"]));
+    assert!(items[2].get("categories").is_none());
+}
+
+#[actix_rt::test]
+async fn rss1_reads_dublin_core_subjects() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed", "/fixtures/rss1_hatena.xml", "")).await;
+    let items = res.json.as_array().unwrap();
+    assert_eq!(items[0]["categories"], json!(["テクノロジー"]));
+}
+
+async fn unsupported(name: &str) -> Value {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed", &format!("/fixtures/{}", name), "")).await;
+    assert_eq!(res.status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    assert_eq!(res.json["kind"], "unsupported_format");
+    res.json["messages"].clone()
+}
+
+#[actix_rt::test]
+async fn reports_rss090_as_unsupported() {
+    assert_eq!(
+        unsupported("rss090_netscape.xml").await,
+        json!(["RSS 0.90 is not supported"])
+    );
+}
+
+#[actix_rt::test]
+async fn reports_opml_as_unsupported() {
+    assert_eq!(
+        unsupported("subscriptions.opml").await,
+        json!(["this looks like OPML, a list of feeds, not a feed"])
+    );
+}
+
+#[actix_rt::test]
+async fn reports_html_as_unsupported() {
+    assert_eq!(
+        unsupported("not_a_feed.html").await,
+        json!(["this looks like an HTML page, not a feed"])
+    );
+}
+
+#[actix_rt::test]
+async fn reports_soap_envelopes_as_unsupported() {
+    assert_eq!(
+        unsupported("soap_fault.xml").await,
+        json!(["this looks like a SOAP response, not a feed"])
+    );
+}
+
+#[actix_rt::test]
+async fn reports_json_as_unsupported() {
+    assert_eq!(
+        unsupported("json_feed.json").await,
+        json!(["this looks like JSON, JSON Feed is not supported"])
+    );
+}
+
+#[actix_rt::test]
+async fn sniffs_json_behind_a_misleading_content_type() {
+    let app = app(config()).await;
+    for path in [
+        "/typed/text/html/json_feed.json",
+        "/typed/application/feed+json/json_feed.json",
+        "/typed/application/json/rss2_wordpress.xml",
+    ] {
+        let res = get(&app, &feed_uri("/feed", path, "")).await;
+        let expected = if path.ends_with(".json") {
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        } else {
+            StatusCode::OK
+        };
+        assert_eq!(res.status, expected, "{}", path);
+    }
+}
+
+#[test]
+fn picks_the_parsers_from_the_content_type_and_first_byte() {
+    let cases: [(Option<&str>, &str, Syntax); 12] = [
+        (None, "<rss/>", Syntax::Xml),
+        (None, "{}", Syntax::Json),
+        (None, "  \n\t{}", Syntax::Json),
+        (None, "\u{FEFF}<?xml version=\"1.0\"?>", Syntax::Xml),
+        (None, "", Syntax::Xml),
+        (Some("application/json"), "{}", Syntax::Json),
+        (Some("application/json"), "<rss/>", Syntax::Xml),
+        (
+            Some("Application/Feed+JSON; charset=utf-8"),
+            "",
+            Syntax::Json,
+        ),
+        (Some("application/json"), "not a document", Syntax::Json),
+        (Some("text/html"), "{}", Syntax::Json),
+        (Some("text/html"), "not a document", Syntax::Xml),
+        (Some("application/xml"), " {}", Syntax::Json),
+    ];
+    for (content_type, body, expected) in cases {
+        assert_eq!(
+            syntax(content_type, body.as_bytes()),
+            expected,
+            "{:?} {:?}",
+            content_type,
+            body
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn reports_unknown_roots_per_parser() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed", "/fixtures/sitemap.xml", "")).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert!(res.json.get("kind").is_none());
+    assert!(res.json["messages"]
+        .as_array()
+        .unwrap()
+        .contains(&"[RSS V2] invalid root element: urlset".into()));
+}
+
+#[actix_rt::test]
+async fn rss2_reads_the_copyright_generator_and_contacts() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_metadata.xml", ""),
+    )
+    .await;
+    let channel = &res.json["channel"];
+    assert_eq!(channel["copyright"], "Copyright 2024 Example Garden Club");
+    assert_eq!(channel["generator"], json!({"name": "Blogger"}));
+    assert_eq!(
+        channel["managing_editor"],
+        "editor@garden.example.com (Jane Doe)"
+    );
+    // without an address it's left out, and said so
+    assert!(channel.get("web_master").is_none());
+    assert_eq!(
+        res.json["warnings"],
+        json!(["webMaster is not an email address: webmaster at garden dot example dot com"])
+    );
+}
+
+#[actix_rt::test]
+async fn atom_reads_the_rights_and_generator_attributes() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/atom_metadata.xml", ""),
+    )
+    .await;
+    let channel = &res.json["channel"];
+    assert_eq!(channel["copyright"], "© 2024 Example Org, CC BY 4.0");
+    assert_eq!(
+        channel["generator"],
+        json!({"name": "Jekyll", "uri": "https://jekyllrb.com/", "version": "4.3.3"})
+    );
+    assert!(channel.get("managing_editor").is_none());
+}
+
+#[actix_rt::test]
+async fn rss1_reads_dublin_core_rights() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss1_metadata.xml", ""),
+    )
+    .await;
+    let channel = &res.json["channel"];
+    assert_eq!(channel["copyright"], "Copyright (C) 2024 Example Diary");
+    assert!(channel.get("generator").is_none());
+}
+
+#[actix_rt::test]
+async fn rss2_takes_the_first_channel_and_items_of_all() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_two_channels.xml", ""),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(
+        res.json["channel"],
+        json!({
+            "link": "https://radio.example.com/",
+            "ttl": 30,
+            "pub_date": null,
+            "image": null
+        })
+    );
+    let items = res.json["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    // the first non-empty link
+    assert_eq!(items[1]["link"], "https://radio.example.com/episodes/11");
+    assert_eq!(
+        res.json["warnings"],
+        json!([
+            "document contains 2 channel elements",
+            "item 2 contains 3 link elements"
+        ])
+    );
+}
+
+#[actix_rt::test]
+async fn rss1_takes_the_first_link() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss1_repeated_links.xml", ""),
+    )
+    .await;
+    assert_eq!(res.json["channel"]["link"], "https://notes.example.jp/");
+    assert_eq!(
+        res.json["items"][0]["link"],
+        "https://notes.example.jp/2024/05/10"
+    );
+    assert_eq!(
+        res.json["warnings"],
+        json!(["item 1 contains 2 link elements"])
+    );
+}
+
+#[actix_rt::test]
+async fn well_formed_feeds_have_no_warnings() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_wordpress.xml", ""),
+    )
+    .await;
+    assert!(res.json.get("warnings").is_none());
+}
+
+#[actix_rt::test]
+async fn rss2_reports_a_canonical_url_other_than_the_requested_one() {
+    let app = app(config()).await;
+    let uri = feed_uri("/v2/feed", "/fixtures/rss2_wordpress.xml", "");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(
+        res.json["channel"]["canonical_url"],
+        "https://blog.example.com/feed/"
+    );
+    assert_eq!(res.json["channel"]["canonical_differs"], true);
+}
+
+#[actix_rt::test]
+async fn rss2_resolves_a_relative_self_link() {
+    let app = app(config()).await;
+    let uri = feed_uri("/v2/feed", "/fixtures/icon_plain.xml", "");
+    let res = get(&app, &uri).await;
+    assert_eq!(
+        res.json["channel"]["canonical_url"],
+        mock_url("/fixtures/icon_plain.xml")
+    );
+    assert!(res.json["channel"].get("canonical_differs").is_none());
+
+    // requested through an alias that redirects
+    let uri = feed_uri("/v2/feed", "/redirect/1/icon_plain.xml", "");
+    let res = get(&app, &uri).await;
+    assert_eq!(
+        res.json["channel"]["canonical_url"],
+        mock_url("/fixtures/icon_plain.xml")
+    );
+    assert_eq!(res.json["channel"]["canonical_differs"], true);
+}
+
+#[test]
+fn canonical_url_ignores_the_scheme_and_a_trailing_slash() {
+    let requested = Url::parse("http://blog.example.com/feed").unwrap();
+    let differs = |canonical: &str| {
+        let mut channel = Channel {
+            canonical_url: Some(canonical.to_string()),
+            ..Channel::default()
+        };
+        channel.resolve_canonical(&requested, &requested);
+        channel.canonical_differs
+    };
+    assert!(!differs("https://blog.example.com/feed/"));
+    assert!(!differs("http://BLOG.example.com/feed"));
+    assert!(!differs("/feed/"));
+    assert!(differs("https://blog.example.com/feed/atom/"));
+    assert!(differs("https://feeds.example.com/feed"));
+    assert!(differs("https://blog.example.com/feed?format=rss"));
+}
+
+#[actix_rt::test]
+async fn debug_counts_the_elements_nothing_was_read_from() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_extensions.xml", "&debug=true"),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK);
+    let unmapped = res.json["unmapped_elements"].as_object().unwrap();
+    assert_eq!(
+        unmapped.iter().collect::<Vec<(&String, &Value)>>(),
+        [
+            (&"post-id".to_string(), &json!(3)),
+            (&"comments".to_string(), &json!(2)),
+            (&"slash:comments".to_string(), &json!(2)),
+            (&"language".to_string(), &json!(1)),
+        ]
+    );
+
+    // the same cached feed, without the counts unless asked for
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_extensions.xml", ""),
+    )
+    .await;
+    assert!(res.json.get("unmapped_elements").is_none());
+    let res = get(
+        &app,
+        &feed_uri("/feed", "/fixtures/rss2_extensions.xml", "&debug=true"),
+    )
+    .await;
+    assert_eq!(res.json.as_array().unwrap().len(), 3);
+}
+
+#[test]
+fn unmapped_elements_are_capped() {
+    let names = (0..250)
+        .map(|i| format!("<x{0}>{0}</x{0}>", i))
+        .collect::<String>();
+    let buf = format!(
+        "<rss version=\"2.0\"><channel><title>Many</title>{}\
+         <item><title>One</title><late/><late/></item></channel></rss>",
+        names
+    );
+    let feed = parse_rss(Bytes::from(buf)).unwrap();
+    assert_eq!(feed.unmapped_elements.len(), 20);
+    // names past the first 200 aren't counted however often they come
+    assert!(feed
+        .unmapped_elements
+        .iter()
+        .all(|(name, _)| name != "late"));
+    assert_eq!(feed.unmapped_elements[0], ("x0".to_string(), 1));
+}
+
+#[actix_rt::test]
+async fn tells_forbidden_missing_and_gone_feeds_apart() {
+    let app = app(config()).await;
+    for (path, status, kind) in [
+        ("/status/403", StatusCode::FORBIDDEN, "access_denied"),
+        ("/status/404", StatusCode::NOT_FOUND, "feed_not_found"),
+        ("/status/410", StatusCode::GONE, "feed_gone"),
+    ] {
+        let res = get(&app, &feed_uri("/feed", path, "")).await;
+        assert_eq!(res.status, status, "{}", path);
+        assert_eq!(res.json["kind"], kind, "{}", path);
+
+        let res = get(&app, &feed_uri("/v2/feed", path, "")).await;
+        assert_eq!(res.status, status, "{}", path);
+        assert_eq!(res.json["errors"][0]["code"], kind, "{}", path);
+    }
+    // a refusal may be of automated clients, so it says who asked
+    let res = get(&app, &feed_uri("/feed", "/status/403", "")).await;
+    let message = res.json["messages"][0].as_str().unwrap();
+    assert!(message.ends_with("(User-Agent: rssss)"), "{}", message);
+}
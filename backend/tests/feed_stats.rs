@@ -0,0 +1,139 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use rssss::feed_stats::{feed_stats, ChannelStats};
+use rssss::rss::Rss;
+use serde_json::json;
+use std::time::Duration;
+use support::{app, config, feed_uri, get};
+
+fn dated(dates: &[&str]) -> Vec<Rss> {
+    dates
+        .iter()
+        .enumerate()
+        .map(|(i, date)| {
+            Rss::new(format!("{}", i), format!("https://example.com/{}", i), "")
+                .with_pub_date(*date)
+        })
+        .collect()
+}
+
+fn undated(count: usize) -> Vec<Rss> {
+    (0..count)
+        .map(|i| Rss::new("undated", format!("https://example.com/u{}", i), ""))
+        .collect()
+}
+
+#[test]
+fn counts_a_feed_without_dates() {
+    let mut items = undated(2);
+    items.extend(dated(&["someday"]));
+    assert_eq!(
+        feed_stats(&items),
+        ChannelStats {
+            item_count: 3,
+            earliest_pub_date: None,
+            latest_pub_date: None,
+            median_interval_hours: None,
+        }
+    );
+    assert_eq!(feed_stats(&[]).item_count, 0);
+}
+
+#[test]
+fn spans_a_single_date_without_an_interval() {
+    let mut items = dated(&["Wed, 01 May 2024 09:00:00 +0900"]);
+    items.extend(undated(1));
+    let stats = feed_stats(&items);
+    assert_eq!(stats.item_count, 2);
+    // normalized to UTC
+    assert_eq!(
+        stats.earliest_pub_date.as_deref(),
+        Some("2024-05-01T00:00:00Z")
+    );
+    assert_eq!(stats.latest_pub_date, stats.earliest_pub_date);
+    assert_eq!(stats.median_interval_hours, None);
+}
+
+#[test]
+fn takes_an_interval_from_three_dated_items() {
+    let two = feed_stats(&dated(&["2024-05-01T00:00:00Z", "2024-05-02T00:00:00Z"]));
+    assert_eq!(two.median_interval_hours, None);
+
+    let three = feed_stats(&dated(&[
+        "2024-05-01T00:00:00Z",
+        "2024-05-02T00:00:00Z",
+        "2024-05-03T00:00:00Z",
+    ]));
+    assert_eq!(three.median_interval_hours, Some(24.0));
+    assert_eq!(three.median_interval(), Some(Duration::from_secs(86_400)));
+}
+
+#[test]
+fn takes_the_median_of_irregular_spacing() {
+    // in feed order, newest first, with gaps of 1, 2, 168 and 3 hours once sorted
+    let items = dated(&[
+        "2024-05-09T06:00:00Z",
+        "2024-05-09T03:00:00Z",
+        "2024-05-02T00:00:00Z",
+        "2024-05-01T23:00:00Z",
+        "2024-05-01T21:00:00Z",
+    ]);
+    let stats = feed_stats(&items);
+    assert_eq!(stats.item_count, 5);
+    assert_eq!(
+        stats.earliest_pub_date.as_deref(),
+        Some("2024-05-01T21:00:00Z")
+    );
+    assert_eq!(
+        stats.latest_pub_date.as_deref(),
+        Some("2024-05-09T06:00:00Z")
+    );
+    // of 1, 2, 3 and 168, the long gap doesn't pull it up as a mean would
+    assert_eq!(stats.median_interval_hours, Some(2.5));
+
+    let mut weekly = dated(&[
+        "2024-01-01T10:00:00Z",
+        "2024-01-08T09:00:00Z",
+        "2024-01-15T10:20:00Z",
+        "2024-02-01T10:00:00Z",
+    ]);
+    weekly.extend(undated(3));
+    let stats = feed_stats(&weekly);
+    assert_eq!(stats.item_count, 7);
+    assert_eq!(stats.median_interval_hours, Some(169.3));
+}
+
+#[actix_rt::test]
+async fn describes_every_item_of_the_feed() {
+    let app = app(config()).await;
+    let uri = feed_uri("/v2/feed", "/fixtures/rss2_podcast.xml", "&per_page=1");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.json["items"].as_array().unwrap().len(), 1);
+    assert_eq!(
+        res.json["channel_stats"],
+        json!({
+            "item_count": 4,
+            "earliest_pub_date": "2024-05-15T06:00:00Z",
+            "latest_pub_date": "2024-06-05T06:00:00Z",
+            "median_interval_hours": 168.0,
+        })
+    );
+}
+
+#[actix_rt::test]
+async fn counts_but_does_not_date_suspect_items() {
+    let app = app(config()).await;
+    let uri = feed_uri("/v2/feed", "/fixtures/rss2_dates.xml", "");
+    let res = get(&app, &uri).await;
+    // the broken epoch, the zeroed date and the item updated before 1990
+    assert_eq!(
+        res.json["channel_stats"],
+        json!({
+            "item_count": 5,
+            "earliest_pub_date": "2024-05-01T08:00:00Z",
+            "latest_pub_date": "2024-05-03T09:00:00Z",
+        })
+    );
+}
@@ -0,0 +1,113 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use rssss::markdown::to_markdown;
+use rssss::safe_html::to_safe_html;
+use support::{app, config, feed_uri, get};
+
+#[test]
+fn markdown_nests_lists() {
+    let html = "<ul><li>one<ul><li>inner</li></ul></li><li>two<ol><li>first</li><li>second</li></ol></li></ul>";
+    assert_eq!(
+        to_markdown(html, 500),
+        "- one\n  - inner\n- two\n  1. first\n  2. second"
+    );
+}
+
+#[test]
+fn markdown_keeps_links_inside_emphasis() {
+    let html = "<p>read <em>the <a href=\"https://example.com/a b\">notes</a></em> first</p>";
+    assert_eq!(
+        to_markdown(html, 500),
+        "read *the [notes](<https://example.com/a b>)* first"
+    );
+}
+
+#[test]
+fn markdown_keeps_images_without_alt_text() {
+    let html = "<p>before</p><figure><img src=\"https://example.com/a.png\"></figure>";
+    assert_eq!(
+        to_markdown(html, 500),
+        "before\n\n![](https://example.com/a.png)"
+    );
+}
+
+#[test]
+fn markdown_escapes_text_and_drops_scripts() {
+    let html = "<p>2 * 3 = [six]</p><script>alert(1)</script>";
+    assert_eq!(to_markdown(html, 500), "2 \\* 3 = \\[six\\]");
+}
+
+#[test]
+fn markdown_truncates_visible_text_inside_markup() {
+    let html = "<p>plain <strong>bold words here</strong> after</p>";
+    assert_eq!(to_markdown(html, 10), "plain **bold...**");
+}
+
+#[test]
+fn safe_html_drops_scripts_handlers_and_unsafe_links() {
+    let html = "<p onclick=\"x()\">hi <a href=\"javascript:alert(1)\">there</a> \
+                <a href=\"/relative\" target=\"_blank\">ok</a></p>\
+                <script>alert(1)</script><img src=\"data:image/png;base64,AA\">";
+    assert_eq!(
+        to_safe_html(html, 500),
+        "<p>hi there <a href=\"/relative\">ok</a></p>"
+    );
+}
+
+#[test]
+fn safe_html_unwraps_unknown_elements_and_escapes_text() {
+    let html = "<div class=\"x\"><span>a &lt; b</span> <strong>\"c\"</strong></div>";
+    assert_eq!(
+        to_safe_html(html, 500),
+        "a &lt; b <strong>&quot;c&quot;</strong>"
+    );
+}
+
+#[test]
+fn safe_html_truncates_and_closes_open_elements() {
+    let html = "<ul><li>first item</li><li>second item</li></ul>";
+    assert_eq!(
+        to_safe_html(html, 15),
+        "<ul><li>first item</li><li>secon...</li></ul>"
+    );
+}
+
+#[actix_rt::test]
+async fn feeds_format_descriptions_on_request() {
+    let app = app(config()).await;
+    let uri = feed_uri("/feed", "/fixtures/rss2_wordpress.xml", "&format=markdown");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(
+        res.json[1]["description"],
+        "This month we sat down with the people who write our [handbook](https://docs.example.com/).\n\n\
+         ![](https://blog.example.com/wp-content/uploads/team.jpg)\n\n\
+         They told us how a page goes from a draft to the published docs."
+    );
+
+    let uri = feed_uri("/feed", "/fixtures/rss2_wordpress.xml", "&format=html");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(
+        res.json[1]["description"],
+        "<p>This month we sat down with the people who write our <a href=\"https://docs.example.com/\">handbook</a>.</p>\n\
+         <figure><img src=\"https://blog.example.com/wp-content/uploads/team.jpg\" alt=\"\"></figure>\n\
+         <p>They told us how a page goes from a draft to the published docs.</p>"
+    );
+
+    // the default stays plain text, whatever was asked for before
+    let res = get(&app, &feed_uri("/feed", "/fixtures/rss2_wordpress.xml", "")).await;
+    assert!(res.json[1]["description"]
+        .as_str()
+        .unwrap()
+        .starts_with("This month we sat down with the people who write our handbook."));
+}
+
+#[actix_rt::test]
+async fn rejects_unknown_formats() {
+    let app = app(config()).await;
+    let uri = feed_uri("/feed", "/fixtures/rss2_wordpress.xml", "&format=rtf");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,105 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use serde_json::Value;
+use support::{app, config, feed_uri, get};
+
+// items at 00:00 and 23:59 in India, one of them given in GMT
+const FIXTURE: &str = "/fixtures/rss2_day_boundaries.xml";
+
+fn days(groups: &Value) -> Vec<(&str, Vec<&str>)> {
+    groups
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|group| {
+            let titles = group["items"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|item| item["title"].as_str().unwrap())
+                .collect();
+            (group["date"].as_str().unwrap(), titles)
+        })
+        .collect()
+}
+
+#[actix_rt::test]
+async fn groups_by_the_local_day_of_the_offset() {
+    let app = app(config()).await;
+    let uri = feed_uri("/v2/feed", FIXTURE, "&group_by=day&tz=%2B05:30");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+    assert_eq!(
+        days(&res.json["groups"]),
+        [
+            ("2024-05-03", vec!["may 3 00:00 ist"]),
+            (
+                "2024-05-02",
+                vec![
+                    "may 2 00:00 ist",
+                    "may 2 00:00 ist in gmt",
+                    "may 2 23:59 ist"
+                ]
+            ),
+            ("2024-05-01", vec!["may 1 00:00 ist", "may 1 23:59 ist"]),
+            ("unknown", vec!["undated"]),
+        ]
+    );
+}
+
+#[actix_rt::test]
+async fn groups_the_same_items_by_the_utc_day_by_default() {
+    let app = app(config()).await;
+    for params in ["&group_by=day", "&group_by=day&tz=Z"] {
+        let res = get(&app, &feed_uri("/v2/feed", FIXTURE, params)).await;
+        assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+        assert_eq!(
+            days(&res.json["groups"]),
+            [
+                ("2024-05-02", vec!["may 2 23:59 ist", "may 3 00:00 ist"]),
+                (
+                    "2024-05-01",
+                    vec![
+                        "may 1 23:59 ist",
+                        "may 2 00:00 ist",
+                        "may 2 00:00 ist in gmt"
+                    ]
+                ),
+                ("2024-04-30", vec!["may 1 00:00 ist"]),
+                ("unknown", vec!["undated"]),
+            ],
+            "{}",
+            params
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn groups_under_v1_as_the_body() {
+    let app = app(config()).await;
+    let uri = feed_uri("/feed", FIXTURE, "&group_by=day&tz=%2B0530");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    let days = days(&res.json);
+    assert_eq!(days[0], ("2024-05-03", vec!["may 3 00:00 ist"]));
+    assert_eq!(days.len(), 4);
+}
+
+#[actix_rt::test]
+async fn rejects_a_bad_tz() {
+    let app = app(config()).await;
+    for tz in ["Asia/Tokyo", "%2B15:00", "%2B09:60", "9", ""] {
+        let params = format!("&group_by=day&tz={}", tz);
+        let res = get(&app, &feed_uri("/v2/feed", FIXTURE, &params)).await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST, "{}", tz);
+        let error = &res.json["errors"][0];
+        assert_eq!(error["code"], "invalid_parameter", "{}", tz);
+        assert!(
+            error["message"].as_str().unwrap().contains("tz"),
+            "{}: {}",
+            tz,
+            error
+        );
+    }
+}
@@ -0,0 +1,85 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use support::{app, config, feed_uri, get, head};
+
+fn titles(json: &serde_json::Value) -> Vec<&str> {
+    json.as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["title"].as_str().unwrap())
+        .collect()
+}
+
+#[actix_rt::test]
+async fn fetches_a_cold_feed_but_sends_no_body() {
+    let app = app(config()).await;
+    let uri = feed_uri("/feed", "/growing/head-cold/rss2_wordpress.xml", "");
+    let res = head(&app, &uri, &[]).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert!(res.body.is_empty());
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "miss");
+    assert_eq!(res.headers.get("Content-Type").unwrap(), "application/json");
+    let count = res.headers.get("X-Total-Count").unwrap().clone();
+
+    // the GET after is answered from what the HEAD fetched
+    let res = get(&app, &uri).await;
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "hit");
+    assert_eq!(res.headers.get("X-Total-Count").unwrap(), count);
+    assert_eq!(titles(&res.json)[0], "new 1");
+}
+
+#[actix_rt::test]
+async fn answers_a_cached_feed_with_the_headers_of_the_get() {
+    let app = app(config()).await;
+    let uri = feed_uri(
+        "/feed",
+        "/growing/head-warm/rss2_wordpress.xml",
+        "&per_page=2",
+    );
+    let first = get(&app, &uri).await;
+    assert_eq!(first.headers.get("X-Rssss-Cache").unwrap(), "miss");
+    let res = head(&app, &uri, &[]).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert!(res.body.is_empty());
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "hit");
+    for name in [
+        "Cache-Control",
+        "X-Total-Count",
+        "X-Total-Pages",
+        "X-Rssss-Snapshot",
+    ] {
+        assert_eq!(res.headers.get(name), first.headers.get(name), "{}", name);
+    }
+
+    // the upstream wasn't asked again, it would have added "new 2"
+    let res = get(&app, &uri).await;
+    assert_eq!(titles(&res.json)[0], "new 1");
+}
+
+#[actix_rt::test]
+async fn keeps_the_content_type_of_ndjson() {
+    let app = app(config()).await;
+    let uri = feed_uri("/feed", "/fixtures/rss2_wordpress.xml", "");
+    let res = head(&app, &uri, &[("Accept", "application/x-ndjson")]).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert!(res.body.is_empty());
+    assert_eq!(
+        res.headers.get("Content-Type").unwrap(),
+        "application/x-ndjson"
+    );
+}
+
+#[actix_rt::test]
+async fn answers_errors_without_their_body() {
+    let app = app(config()).await;
+    let res = head(&app, "/feed?url=not%20a%20url", &[]).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert!(res.body.is_empty());
+
+    let uri = feed_uri("/feed", "/fixtures/missing.xml", "");
+    let res = head(&app, &uri, &[]).await;
+    let get = get(&app, &uri).await;
+    assert_eq!(res.status, get.status);
+    assert!(res.body.is_empty());
+}
@@ -0,0 +1,151 @@
+mod support;
+
+use actix_web::http::header::{self, HeaderMap, HeaderValue};
+use actix_web::http::StatusCode;
+use rssss::headers::{self as upstream_headers, MAX_HEADERS, MAX_LOCATION_LEN, MAX_VALUE_LEN};
+use serde_json::Value;
+use support::{app, config, feed_uri, get};
+
+fn warnings(json: &Value) -> Vec<&str> {
+    json["warnings"]
+        .as_array()
+        .map(|w| w.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+#[actix_rt::test]
+async fn cuts_long_values_with_a_warning() {
+    let app = app(config()).await;
+    let path = "/oversized/content-type/3000/rss2_planet.xml";
+    let res = get(&app, &feed_uri("/v2/feed", path, "")).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+    assert_eq!(
+        warnings(&res.json),
+        ["content-type header cut to 2048 of 3000 bytes"]
+    );
+    // what is echoed was short already
+    let echoed = res.json["upstream_headers"]["content-type"]
+        .as_str()
+        .unwrap();
+    assert!(echoed.len() <= 203, "{}", echoed.len());
+
+    // still read for what it starts with
+    let path = "/oversized/cache-control/5000/rss2_planet.xml";
+    let res = get(&app, &feed_uri("/v2/feed", path, "")).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+    let cache_control = res.headers.get("Cache-Control").unwrap().to_str().unwrap();
+    assert!(
+        ["max-age=599", "max-age=600"].contains(&cache_control),
+        "{}",
+        cache_control
+    );
+    assert_eq!(
+        warnings(&res.json),
+        ["cache-control header cut to 2048 of 5000 bytes"]
+    );
+}
+
+#[actix_rt::test]
+async fn reads_values_up_to_the_cap_as_they_are() {
+    let app = app(config()).await;
+    let path = format!("/oversized/content-type/{}/rss2_planet.xml", MAX_VALUE_LEN);
+    let res = get(&app, &feed_uri("/v2/feed", &path, "")).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+    assert!(warnings(&res.json).is_empty(), "{}", res.json);
+
+    // never read, so never warned about
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/oversized/etag/4000/rss2_planet.xml", ""),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert!(warnings(&res.json).is_empty(), "{}", res.json);
+    let etag = res.json["upstream_headers"]["etag"].as_str().unwrap();
+    assert!(etag.len() <= 203, "{}", etag.len());
+}
+
+#[actix_rt::test]
+async fn flags_a_crowd_of_headers() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/crowded/80/rss2_planet.xml", ""),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+    let warnings = warnings(&res.json);
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(
+        warnings[0].starts_with("upstream sent 8"),
+        "{}",
+        warnings[0]
+    );
+    assert!(
+        warnings[0].ends_with(&format!("headers, more than {}", MAX_HEADERS)),
+        "{}",
+        warnings[0]
+    );
+
+    // past what the HTTP/1.1 decoder takes the response isn't read at all
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/crowded/200/rss2_planet.xml", ""),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST, "{}", res.json);
+    assert_eq!(res.json["errors"][0]["code"], "upstream_request_failed");
+}
+
+#[actix_rt::test]
+async fn refuses_to_follow_a_location_too_long() {
+    let app = app(config()).await;
+    let path = format!("/long-redirect/{}/rss2_planet.xml", MAX_LOCATION_LEN + 1);
+    let res = get(&app, &feed_uri("/v2/feed", &path, "")).await;
+    assert_eq!(res.status, StatusCode::BAD_GATEWAY, "{}", res.json);
+    assert_eq!(res.json["kind"], "invalid_redirect");
+    assert_eq!(res.json["errors"][0]["code"], "redirect_location_too_long");
+    assert_eq!(
+        res.json["errors"][0]["message"],
+        "redirect location of 8193 bytes, longer than 8192"
+    );
+
+    // the check and validate endpoints don't follow it either
+    let res = get(&app, &feed_uri("/feed/validate", &path, "")).await;
+    assert_eq!(res.status, StatusCode::BAD_GATEWAY, "{}", res.json);
+    let res = get(&app, &feed_uri("/feeds/check", &path, "")).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+    let detail = res.json[0]["detail"].as_str().unwrap_or_default();
+    assert!(detail.contains("longer than 8192"), "{}", res.json);
+
+    let path = format!("/long-redirect/{}/rss2_planet.xml", MAX_LOCATION_LEN);
+    let res = get(&app, &feed_uri("/v2/feed", &path, "")).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+}
+
+#[test]
+fn cuts_on_a_char_boundary() {
+    let mut headers = HeaderMap::new();
+    // a multibyte char across the cap
+    let value = format!("{}é{}", "a".repeat(MAX_VALUE_LEN - 1), "b".repeat(10));
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_bytes(value.as_bytes()).unwrap(),
+    );
+    let mut warnings = Vec::new();
+    let cut = upstream_headers::value(&headers, &header::CONTENT_TYPE, &mut warnings).unwrap();
+    assert_eq!(cut, format!("{}...", "a".repeat(MAX_VALUE_LEN - 1)));
+    assert_eq!(
+        warnings,
+        [format!(
+            "content-type header cut to {} of {} bytes",
+            MAX_VALUE_LEN - 1,
+            value.len()
+        )]
+    );
+    assert_eq!(
+        upstream_headers::value(&headers, &header::ETAG, &mut warnings),
+        None
+    );
+    assert_eq!(upstream_headers::location(&headers).ok(), Some(None));
+}
@@ -0,0 +1,74 @@
+use rssss::error::ErrorCode;
+use rssss::hosts::{matches, validate_url, HostFilter};
+
+fn filter(allow: &[&str], deny: &[&str]) -> HostFilter {
+    let list = |hosts: &[&str]| hosts.iter().map(|h| h.to_string()).collect();
+    HostFilter::new(list(allow), list(deny))
+}
+
+#[test]
+fn matches_hosts_whatever_their_case() {
+    assert!(matches("Example.COM", "example.com"));
+    assert!(matches("example.com", "EXAMPLE.com"));
+    assert!(matches(".Example.com", "News.EXAMPLE.com"));
+}
+
+#[test]
+fn matches_unicode_hosts_to_their_punycode() {
+    assert!(matches("例え.jp", "xn--r8jz45g.jp"));
+    assert!(matches("xn--r8jz45g.jp", "例え.jp"));
+    assert!(matches(".例え.jp", "blog.xn--r8jz45g.jp"));
+    assert!(!matches("例え.jp", "例.jp"));
+}
+
+#[test]
+fn matches_subdomains_only_with_a_leading_dot() {
+    assert!(matches(".example.com", "news.example.com"));
+    assert!(matches(".example.com", "a.b.example.com"));
+    assert!(!matches(".example.com", "notexample.com"));
+    assert!(!matches(".example.com", "example.com"));
+    assert!(!matches("example.com", "news.example.com"));
+    assert!(!matches("example.com", "notexample.com"));
+}
+
+#[test]
+fn ignores_a_trailing_dot() {
+    assert!(matches("example.com.", "example.com"));
+    assert!(matches("example.com", "example.com."));
+    assert!(matches(".example.com.", "news.example.com."));
+}
+
+#[test]
+fn never_matches_an_empty_or_invalid_pattern() {
+    assert!(!matches("", "example.com"));
+    assert!(!matches(".", "example.com"));
+    assert!(!matches("exa mple.com", "exa mple.com"));
+}
+
+#[test]
+fn denies_over_allowing() {
+    let hosts = filter(&[".example.com"], &["ads.example.com"]);
+    assert!(hosts.is_allowed("news.example.com"));
+    assert!(!hosts.is_allowed("ads.example.com"));
+    assert!(!hosts.is_allowed("ADS.example.com."));
+    assert!(!hosts.is_allowed("example.org"));
+
+    let hosts = filter(&["example.com"], &["example.com"]);
+    assert!(!hosts.is_allowed("example.com"));
+}
+
+#[test]
+fn allows_every_host_not_denied_without_an_allowlist() {
+    let hosts = filter(&[], &[".example.com"]);
+    assert!(hosts.is_allowed("example.org"));
+    assert!(!hosts.is_allowed("news.example.com"));
+    assert!(filter(&[], &[]).is_allowed("example.com"));
+}
+
+#[test]
+fn refuses_urls_of_hosts_not_allowed() {
+    let hosts = filter(&["例え.jp"], &[]);
+    assert!(validate_url("https://例え.jp/feed", &hosts).is_ok());
+    let e = validate_url("https://example.com/feed", &hosts).unwrap_err();
+    assert_eq!(e.codes(), [ErrorCode::HostNotAllowed]);
+}
@@ -0,0 +1,74 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use support::{
+    app, config, feed_uri, get, get_eventually, mock_localhost_url, url_uri, ICON_ICO, ICON_PNG,
+};
+
+#[actix_rt::test]
+async fn serves_the_first_declared_icon_that_is_an_image() {
+    let app = app(config()).await;
+    let uri = feed_uri("/feed/icon", "/fixtures/icon_linked.xml", "");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::ACCEPTED);
+    assert_eq!(res.headers.get("Retry-After").unwrap(), "1");
+    assert!(res.body.is_empty());
+
+    let res = get_eventually(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.headers.get("Content-Type").unwrap(), "image/png");
+    assert_eq!(
+        res.headers.get("X-Content-Type-Options").unwrap(),
+        "nosniff"
+    );
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "hit");
+    assert_eq!(res.body, ICON_PNG);
+
+    let res = get(&app, &uri).await;
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "hit");
+    assert_eq!(res.body, ICON_PNG);
+}
+
+#[actix_rt::test]
+async fn falls_back_to_favicon_ico() {
+    let app = app(config()).await;
+    let url = mock_localhost_url("/fixtures/icon_plain.xml");
+    let res = get_eventually(&app, &url_uri("/feed/icon", &url, "")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.headers.get("Content-Type").unwrap(), "image/x-icon");
+    assert_eq!(res.body, ICON_ICO);
+}
+
+#[actix_rt::test]
+async fn reports_oversized_and_non_image_icons_as_not_found() {
+    let app = app(config()).await;
+    let res = get_eventually(
+        &app,
+        &feed_uri("/feed/icon", "/fixtures/icon_broken.xml", ""),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::NOT_FOUND);
+    assert_eq!(res.json["kind"], "icon_not_found");
+}
+
+#[actix_rt::test]
+async fn reports_feeds_that_cannot_be_fetched_as_not_found() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed/icon", "/status/503", "")).await;
+    assert_eq!(res.status, StatusCode::NOT_FOUND);
+    assert_eq!(res.json["kind"], "icon_not_found");
+}
+
+#[actix_rt::test]
+async fn refuses_sites_on_private_addresses() {
+    let mut config = config();
+    config.allow_private_hosts = false;
+    let app = app(config).await;
+    let res = get(
+        &app,
+        &feed_uri("/feed/icon", "/fixtures/icon_linked.xml", ""),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::FORBIDDEN);
+    assert_eq!(res.json["kind"], "host_not_allowed");
+}
@@ -0,0 +1,142 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::web;
+use rssss::cache::Caches;
+use rssss::jobs::{Job, Jobs};
+#[cfg(feature = "metrics")]
+use serde_json::json;
+use std::time::Duration;
+#[cfg(feature = "metrics")]
+use support::delete;
+use support::{app, app_with_caches, config, feed_uri, get, get_eventually, mock_url, ICON_PNG};
+
+fn icon_job(path: &str, host: &str) -> Job {
+    Job::Icon {
+        site: mock_url(path).parse().unwrap(),
+        host: host.to_string(),
+    }
+}
+
+// until a job has stored the icon of the host
+async fn cached_icon(caches: &Caches, host: &str) -> Option<Vec<u8>> {
+    for _ in 0..100 {
+        if let Some(entry) = caches.icons.get(host) {
+            return entry.value.map(|icon| icon.body.to_vec());
+        }
+        actix_rt::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("no icon stored for {}", host);
+}
+
+#[actix_rt::test]
+async fn stores_the_icon_for_the_requests_after_it() {
+    let caches = web::Data::new(Caches::new(&config().cache));
+    let app = app_with_caches(config(), caches.clone()).await;
+    let uri = feed_uri("/feed/icon", "/fixtures/icon_linked.xml", "");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::ACCEPTED);
+    assert_eq!(res.headers.get("Cache-Control").unwrap(), "no-store");
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "pending");
+    // asked again before the job is done, it isn't queued twice
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::ACCEPTED);
+
+    assert_eq!(
+        cached_icon(&caches, "127.0.0.1").await.as_deref(),
+        Some(ICON_PNG)
+    );
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "hit");
+
+    #[cfg(feature = "metrics")]
+    {
+        let res = get(&app, "/stats").await;
+        assert_eq!(res.json["jobs"]["queue_depth"], 0);
+        assert_eq!(res.json["jobs"]["running"], 0);
+        assert_eq!(
+            res.json["jobs"]["outcomes"]["icon"],
+            json!({
+                "queued": 1,
+                "deduplicated": 1,
+                "rejected": 0,
+                "succeeded": 1,
+                "failed": 0,
+                "timed_out": 0
+            })
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn counts_sites_without_an_icon_as_failed() {
+    let app = app(config()).await;
+    let uri = feed_uri("/feed/icon", "/fixtures/icon_broken.xml", "");
+    let res = get_eventually(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::NOT_FOUND);
+    assert_eq!(res.json["kind"], "icon_not_found");
+
+    #[cfg(feature = "metrics")]
+    {
+        let res = get(&app, "/stats").await;
+        assert_eq!(res.json["jobs"]["outcomes"]["icon"]["failed"], 1);
+        assert_eq!(res.json["jobs"]["outcomes"]["icon"]["succeeded"], 0);
+
+        // reset with the stats
+        assert_eq!(delete(&app, "/stats").await.status, StatusCode::NO_CONTENT);
+        let res = get(&app, "/stats").await;
+        assert_eq!(res.json["jobs"]["outcomes"], json!({}));
+    }
+}
+
+#[actix_rt::test]
+async fn stores_a_miss_for_a_job_that_timed_out() {
+    let mut config = config();
+    config.jobs.timeout = Duration::from_millis(200);
+    let caches = web::Data::new(Caches::new(&config.cache));
+    let jobs = Jobs::start(&config, caches.clone());
+    jobs.enqueue(icon_job("/slow/2000/icon_linked.html", "slow.test"))
+        .unwrap();
+
+    assert_eq!(cached_icon(&caches, "slow.test").await, None);
+    let outcomes = &jobs.snapshot().outcomes["icon"];
+    assert_eq!(outcomes.timed_out, 1);
+    assert_eq!(outcomes.succeeded, 0);
+}
+
+#[actix_rt::test]
+async fn rejects_jobs_past_the_queue_size() {
+    let mut config = config();
+    config.jobs.workers = 1;
+    config.jobs.queue_size = 1;
+    let caches = web::Data::new(Caches::new(&config.cache));
+    let jobs = Jobs::start(&config, caches.clone());
+    // the worker doesn't take the first before this test awaits something
+    assert!(jobs
+        .enqueue(icon_job("/site/icon_linked.html", "first.test"))
+        .is_ok());
+    assert!(jobs
+        .enqueue(icon_job("/site/icon_linked.html", "first.test"))
+        .is_ok());
+    assert!(jobs
+        .enqueue(icon_job("/site/icon_linked.html", "second.test"))
+        .is_err());
+    assert_eq!(jobs.snapshot().queue_depth, 1);
+
+    assert_eq!(
+        cached_icon(&caches, "first.test").await.as_deref(),
+        Some(ICON_PNG)
+    );
+    assert!(caches.icons.get("second.test").is_none());
+    let outcomes = &jobs.snapshot().outcomes["icon"];
+    assert_eq!(
+        (
+            outcomes.queued,
+            outcomes.deduplicated,
+            outcomes.rejected,
+            outcomes.succeeded
+        ),
+        (1, 1, 1, 1)
+    );
+}
@@ -0,0 +1,96 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::web::Bytes;
+use rssss::language::detect;
+use rssss::rss::parse_rss;
+use std::time::{Duration, Instant};
+use support::{app, config, feed_uri, get};
+
+#[test]
+fn detects_english_and_japanese() {
+    assert_eq!(
+        detect("The cloves should go into the ground about six weeks before the soil freezes."),
+        Some("en")
+    );
+    assert_eq!(
+        detect("今日は朝から雨が降っていたので、家で本を読んで過ごしました。"),
+        Some("ja")
+    );
+}
+
+#[test]
+fn leaves_out_short_and_ambiguous_texts() {
+    // 19 characters
+    assert_eq!(detect("  The quick brown fox  "), None);
+    assert_eq!(detect("雨が降っていたので家で本を読んだ"), None);
+    assert_eq!(detect("1234567890 1234567890 ---- !!!"), None);
+    // a pangram has too few common words to be sure of
+    assert_eq!(
+        detect("The quick brown fox jumps over the lazy dog while the farmer watches."),
+        None
+    );
+}
+
+#[actix_rt::test]
+async fn adds_detected_language_on_request() {
+    let app = app(config()).await;
+    let uri = feed_uri(
+        "/feed",
+        "/fixtures/rss2_languages.xml",
+        "&detect_language=true",
+    );
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.json[0]["detected_language"], "en");
+    assert_eq!(res.json[1]["detected_language"], "ja");
+    // the markup of the description isn't counted
+    assert!(res.json[2].get("detected_language").is_none());
+
+    let uri = feed_uri("/feed", "/fixtures/rss2_languages.xml", "");
+    let res = get(&app, &uri).await;
+    assert!(res.json[0].get("detected_language").is_none());
+}
+
+#[actix_rt::test]
+async fn detects_before_the_description_is_formatted() {
+    let app = app(config()).await;
+    let params = "&detect_language=true&format=html&case=camel";
+    let uri = feed_uri("/v2/feed", "/fixtures/rss2_languages.xml", params);
+    let res = get(&app, &uri).await;
+    assert_eq!(res.json["items"][1]["detectedLanguage"], "ja");
+    assert!(res.json["items"][1]["description"]
+        .as_str()
+        .unwrap()
+        .starts_with("<p>"));
+}
+
+#[test]
+fn detects_a_200_item_feed_quickly() {
+    let item = |i: usize| {
+        let text = if i.is_multiple_of(2) {
+            "&lt;p&gt;A long walk along the coast ends at a small harbour where the boats come in at dusk.&lt;/p&gt;"
+        } else {
+            "&lt;p&gt;週末は海沿いの道をのんびり歩いて、夕暮れの小さな港まで行ってきました。&lt;/p&gt;"
+        };
+        format!(
+            "<item><title>Item {}</title><link>https://example.com/{}</link><description>{}</description></item>",
+            i, i, text
+        )
+    };
+    let doc = format!(
+        "<rss version=\"2.0\"><channel><title>t</title><link>https://example.com/</link><description>d</description>{}</channel></rss>",
+        (0..200).map(item).collect::<String>()
+    );
+    let mut items = parse_rss(Bytes::from(doc)).unwrap().items;
+    assert_eq!(items.len(), 200);
+    let started = Instant::now();
+    items.iter_mut().for_each(|item| item.detect_language());
+    let elapsed = started.elapsed();
+    for (i, item) in items.iter().enumerate() {
+        let expected = if i.is_multiple_of(2) { "en" } else { "ja" };
+        assert_eq!(item.detected_language(), Some(expected));
+    }
+    // a few milliseconds in a release build, the bound leaves room for debug builds and slow CI
+    assert!(elapsed < Duration::from_millis(500), "{:?}", elapsed);
+}
@@ -0,0 +1,107 @@
+use actix_web::web::Bytes;
+use rssss::rss::{parse_rss, Feed};
+
+const ITEMS: usize = 1_000;
+
+// every item with its names qualified in the ways the parsers tell apart, and look-alikes of
+// the elements and attributes in another namespace that mustn't be taken for them
+fn rss2() -> String {
+    let items = (0..ITEMS)
+        .map(|i| {
+            format!(
+                "<item>\
+                 <title>Item {i}</title>\
+                 <link>https://example.com/items/{i}</link>\
+                 <description><![CDATA[<p>Body of <b>item</b> {i} &amp; more</p>]]></description>\
+                 <pubDate>Mon, {day:02} Apr 2024 {hour:02}:00:00 +0000</pubDate>\
+                 <dc:creator>Author {author}</dc:creator>\
+                 <category domain=\"https://example.com/tags\">tag{tag}</category>\
+                 <x:item><x:title>not an item</x:title></x:item>\
+                 <x:link>https://example.com/not-the-link/{i}</x:link>\
+                 </item>",
+                day = i % 28 + 1,
+                hour = i % 24,
+                author = i % 7,
+                tag = i % 5,
+            )
+        })
+        .collect::<String>();
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+         <rss version=\"2.0\" x:version=\"9\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\" \
+         xmlns:x=\"urn:example:x\"><channel>\
+         <title>Large</title><link>https://example.com/</link>\
+         <description>A thousand items</description>\
+         <x:channel><x:title>not the channel</x:title></x:channel>\
+         {items}</channel></rss>"
+    )
+}
+
+fn atom() -> String {
+    let entries = (0..ITEMS)
+        .map(|i| {
+            format!(
+                "<entry>\
+                 <title>Entry {i}</title>\
+                 <link x:rel=\"enclosure\" href=\"https://example.com/entries/{i}\"/>\
+                 <link rel=\"replies\" href=\"https://example.com/entries/{i}/comments\"/>\
+                 <link rel=\"related\" x:href=\"https://example.com/not-the-link/{i}\"/>\
+                 <id>urn:example:{i}</id>\
+                 <updated>2024-04-{day:02}T{hour:02}:00:00Z</updated>\
+                 <summary type=\"html\">&lt;p&gt;Summary of {i}&lt;/p&gt;</summary>\
+                 <author><name>Author {author}</name></author>\
+                 </entry>",
+                day = i % 28 + 1,
+                hour = i % 24,
+                author = i % 7,
+            )
+        })
+        .collect::<String>();
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+         <feed xmlns=\"http://www.w3.org/2005/Atom\" xmlns:x=\"urn:example:x\">\
+         <title>Large</title><link href=\"https://example.com/\"/>\
+         <id>urn:example:feed</id><updated>2024-04-28T00:00:00Z</updated>\
+         {entries}</feed>"
+    )
+}
+
+// of the feed as JSON, FNV-1a as it's stable across toolchains unlike `DefaultHasher`. the same
+// before and after names were compared without formatting them, a change to the output of
+// either document changes it
+fn digest(feed: &Feed) -> u64 {
+    serde_json::to_vec(feed)
+        .unwrap()
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+            (hash ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+fn parse(document: String) -> Feed {
+    parse_rss(Bytes::from(document)).unwrap()
+}
+
+#[test]
+fn reads_every_item_of_a_large_rss2_feed() {
+    let feed = parse(rss2());
+    assert_eq!(feed.channel.link.as_deref(), Some("https://example.com/"));
+    assert_eq!(feed.items.len(), ITEMS);
+    assert!(feed.warnings.is_empty(), "{:?}", feed.warnings);
+    let item = &feed.items[999];
+    assert_eq!(item.title(), "Item 999");
+    assert_eq!(item.link(), "https://example.com/items/999");
+    assert_eq!(item.description(), "Body of item 999 & more");
+    assert_eq!(item.author(), Some("Author 5"));
+    assert_eq!(digest(&feed), 0x7afa_b1f0_da68_15b6);
+}
+
+#[test]
+fn reads_every_entry_of_a_large_atom_feed() {
+    let feed = parse(atom());
+    assert_eq!(feed.items.len(), ITEMS);
+    let item = &feed.items[999];
+    assert_eq!(item.title(), "Entry 999");
+    assert_eq!(item.link(), "https://example.com/entries/999");
+    assert_eq!(digest(&feed), 0x76b0_ca9b_5e0a_95a8);
+}
@@ -0,0 +1,103 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::web::Bytes;
+use rssss::rss::{parse_rss, parse_rss_with, ParseOptions, Strictness};
+use support::{app, config, feed_uri, get};
+
+#[actix_rt::test]
+async fn reads_past_a_bom_and_blank_lines() {
+    let app = app(config()).await;
+    for fixture in ["rss2_bom.xml", "rss2_leading_whitespace.xml"] {
+        let path = format!("/fixtures/{}", fixture);
+        let res = get(&app, &feed_uri("/v2/feed", &path, "")).await;
+        assert_eq!(res.status, StatusCode::OK, "{}: {}", fixture, res.json);
+        assert_eq!(
+            res.json["items"].as_array().unwrap().len(),
+            2,
+            "{}",
+            fixture
+        );
+        assert_eq!(
+            res.json["items"][0]["title"], "Hall Bookings for the Winter",
+            "{}",
+            fixture
+        );
+        // nothing to tell about those
+        assert!(res.json.get("warnings").is_none(), "{}", fixture);
+    }
+}
+
+#[actix_rt::test]
+async fn skips_php_notices_with_a_warning() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_php_notice.xml", ""),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+    assert_eq!(res.json["items"].as_array().unwrap().len(), 2);
+    assert_eq!(
+        res.json["warnings"][0],
+        "skipped 298 bytes of leading garbage before XML"
+    );
+}
+
+#[actix_rt::test]
+async fn strict_keeps_the_failure() {
+    let app = app(config()).await;
+    for fixture in ["rss2_bom.xml", "rss2_php_notice.xml"] {
+        let path = format!("/fixtures/{}", fixture);
+        let res = get(&app, &feed_uri("/v2/feed", &path, "&strictness=strict")).await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST, "{}", fixture);
+        assert_ne!(
+            res.json["errors"][0]["code"], "strict_mode_warning",
+            "{}",
+            fixture
+        );
+    }
+    // whitespace before the declaration was never a failure
+    let res = get(
+        &app,
+        &feed_uri(
+            "/v2/feed",
+            "/fixtures/rss2_leading_whitespace.xml",
+            "&strictness=strict",
+        ),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+}
+
+#[test]
+fn skips_text_before_the_first_element() {
+    let feed = parse_rss(Bytes::from_static(
+        b"Deprecated: mysql_connect()\n<rss version=\"2.0\"><channel><title>T</title>\
+          <item><title>A</title></item></channel></rss>",
+    ))
+    .unwrap();
+    assert_eq!(feed.items.len(), 1);
+    assert_eq!(
+        feed.warnings,
+        ["skipped 28 bytes of leading garbage before XML"]
+    );
+}
+
+#[test]
+fn a_declaration_in_the_text_is_not_the_start() {
+    let feed = parse_rss(Bytes::from_static(
+        b"<rss version=\"2.0\"><channel><title>T</title><item><title>A</title>\
+          <description><![CDATA[start with <?xml version=\"1.0\"?>]]></description>\
+          </item></channel></rss>",
+    ))
+    .unwrap();
+    assert_eq!(feed.items.len(), 1);
+    assert!(feed.warnings.is_empty());
+
+    let e = parse_rss_with(
+        Bytes::from_static(b"\xEF\xBB\xBF<rss version=\"2.0\"><channel/></rss>"),
+        ParseOptions::of(Strictness::Strict),
+    );
+    assert!(e.is_err());
+}
@@ -0,0 +1,137 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use serde_json::Value;
+use support::{app, config, feed_uri, get};
+
+fn titles(items: &Value) -> Vec<&str> {
+    items
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["title"].as_str().unwrap())
+        .collect()
+}
+
+#[actix_rt::test]
+async fn fails_on_documents_that_are_not_well_formed_by_default() {
+    let app = app(config()).await;
+    for path in [
+        "/fixtures/rss2_broken_entities.xml",
+        "/fixtures/rss2_mismatched_tags.xml",
+    ] {
+        let res = get(&app, &feed_uri("/feed", path, "")).await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST, "{}", path);
+    }
+}
+
+#[actix_rt::test]
+async fn fixes_up_entities_control_characters_and_void_elements() {
+    let app = app(config()).await;
+    let uri = feed_uri(
+        "/v2/feed",
+        "/fixtures/rss2_broken_entities.xml",
+        "&lenient=true",
+    );
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.headers.get("X-Rssss-Recovery").unwrap(), "fixed_up");
+    assert_eq!(
+        res.json["warnings"][0],
+        "lenient recovery was used: the document is not well-formed XML and was fixed up before parsing"
+    );
+    assert_eq!(
+        res.json["channel"]["link"],
+        "https://deals.example.com/?utm_source=rss&utm_medium=feed"
+    );
+    let items = &res.json["items"];
+    assert_eq!(
+        titles(items),
+        ["Headphones for < $50", "Coffee grinder 30% off"]
+    );
+    assert_eq!(
+        items[0]["link"],
+        "https://deals.example.com/item?id=12&ref=rss"
+    );
+    // the text on both sides of the unclosed <br> is kept
+    assert_eq!(
+        items[0]["description"],
+        "Noise cancelling.Ships\u{a0}free © Example DealsWhile stocks last."
+    );
+    // CDATA is left alone
+    assert_eq!(items[1]["description"], "Burr grinder & scale  bundle.");
+}
+
+#[actix_rt::test]
+async fn scans_for_items_when_fixing_up_is_not_enough() {
+    let app = app(config()).await;
+    let uri = feed_uri(
+        "/v2/feed",
+        "/fixtures/rss2_mismatched_tags.xml",
+        "&lenient=true",
+    );
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.headers.get("X-Rssss-Recovery").unwrap(), "scanned");
+    assert_eq!(
+        res.json["warnings"][0],
+        "lenient recovery was used: the document could not be parsed, only the title, link, description and date of its items were scanned"
+    );
+    let items = &res.json["items"];
+    assert_eq!(
+        titles(items),
+        [
+            "Build fails on ARM",
+            "Release 2.0 <announcement>",
+            "Unclosed item at the end"
+        ]
+    );
+    assert_eq!(
+        items[0]["link"],
+        "https://forum.example.org/t/build-fails-on-arm/501"
+    );
+    assert_eq!(
+        items[0]["description"],
+        "After upgrading the toolchain the build fails."
+    );
+    assert_eq!(items[0]["pub_date"], "Thu, 09 May 2024 22:10:00 +0000");
+    assert_eq!(
+        items[1]["description"],
+        "Thanks to everyone who tested the \"beta\"."
+    );
+    assert_eq!(items[2]["description"], "");
+}
+
+#[actix_rt::test]
+async fn leaves_well_formed_feeds_alone() {
+    let app = app(config()).await;
+    let uri = feed_uri("/v2/feed", "/fixtures/atom_planet.xml", "&lenient=true");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert!(res.headers.get("X-Rssss-Recovery").is_none());
+    assert!(res.json.get("warnings").is_none());
+}
+
+#[actix_rt::test]
+async fn still_fails_on_documents_without_items() {
+    let app = app(config()).await;
+    let uri = feed_uri("/feed", "/fixtures/not_a_feed.html", "&lenient=true");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    assert_eq!(
+        res.json["messages"][0],
+        "this looks like an HTML page, not a feed"
+    );
+}
+
+#[actix_rt::test]
+async fn a_lenient_feed_is_not_the_answer_to_a_default_request() {
+    let app = app(config()).await;
+    let path = "/fixtures/rss2_broken_entities.xml";
+    let res = get(&app, &feed_uri("/v2/feed", path, "&lenient=true")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    let res = get(&app, &feed_uri("/v2/feed", path, "")).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.json["errors"][0]["code"], "xml_parse_error");
+    assert!(res.headers.get("X-Rssss-Recovery").is_none());
+}
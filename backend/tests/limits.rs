@@ -0,0 +1,123 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::web::Bytes;
+use rssss::rss::{parse_rss, FieldLimits, Rss};
+use serde_json::{json, Value};
+use support::{app, config, post};
+
+fn rss2(item: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?><rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom"><channel><title>t</title><item>{}</item></channel></rss>"#,
+        item
+    )
+}
+
+fn item(document: String) -> Rss {
+    parse_rss(Bytes::from(document)).unwrap().items.remove(0)
+}
+
+fn categories(count: usize) -> String {
+    (1..=count)
+        .map(|n| format!("<category>c{}</category>", n))
+        .collect()
+}
+
+#[actix_rt::test]
+async fn cuts_oversized_fields_of_posted_feeds() {
+    let app = app(config()).await;
+    let document = rss2(&format!(
+        "<title>{}</title><link>https://example.com/{}</link>{}",
+        "t".repeat(2_000),
+        "l".repeat(5_000),
+        categories(60)
+    ));
+    let res = post(&app, "/feed/parse", document).await;
+    assert_eq!(res.status, StatusCode::OK);
+    let item = &res.json["items"][0];
+    assert_eq!(item["title"].as_str().unwrap().len(), 1_024);
+    assert_eq!(item["link"].as_str().unwrap().len(), 4_096);
+    assert_eq!(item["categories"].as_array().unwrap().len(), 50);
+    assert_eq!(
+        res.json["warnings"],
+        json!([
+            "item 1 title cut to 1024 of 2000 bytes",
+            "item 1 link cut to 4096 of 5020 bytes",
+            "item 1 categories cut to 50 of 60",
+        ])
+    );
+}
+
+#[actix_rt::test]
+async fn strips_control_characters() {
+    let app = app(config()).await;
+    let document = rss2(
+        "<title>a&#x7F;b\u{85}c</title><link>https://example.com/&#x9F;x</link>\
+         <description>line&#x80;one\nline two</description><author>me\u{9C}</author>",
+    );
+    let res = post(&app, "/feed/parse", document).await;
+    assert_eq!(res.status, StatusCode::OK);
+    let item = &res.json["items"][0];
+    assert_eq!(item["title"], "abc");
+    assert_eq!(item["link"], "https://example.com/x");
+    assert_eq!(item["description"], "lineone\nline two");
+    assert_eq!(item["author"], "me");
+    assert!(res.json.get("warnings").is_none());
+}
+
+#[test]
+fn cuts_each_field_to_its_limit() {
+    let mut rss = item(rss2(
+        "<title>title over</title><link>https://example.com/</link>\
+         <pubDate>Fri, 10 May 2024 09:00:00 GMT</pubDate>\
+         <atom:updated>2024-05-12T08:00:00Z</atom:updated>\
+         <author>an author</author><category>long category</category><category>b</category>\
+         <source url=\"https://example.com/feed\">source over</source>",
+    ));
+    let limits = FieldLimits {
+        title: 5,
+        link: 8,
+        pub_date: 3,
+        author: 2,
+        category: 4,
+        categories: 1,
+        ..FieldLimits::default()
+    };
+    assert_eq!(
+        rss.limit_fields(&limits),
+        [
+            "title cut to 5 of 10 bytes",
+            "link cut to 8 of 20 bytes",
+            "pub_date cut to 3 of 29 bytes",
+            "updated cut to 3 of 20 bytes",
+            "author cut to 2 of 9 bytes",
+            "categories cut to 1 of 2",
+            "category cut to 4 of 13 bytes",
+            "source title cut to 5 of 11 bytes",
+        ]
+    );
+    let value = serde_json::to_value(&rss).unwrap();
+    assert_eq!(value["title"], "title");
+    assert_eq!(value["link"], "https://");
+    assert_eq!(value["pub_date"], "Fri");
+    assert_eq!(value["updated"], "202");
+    assert_eq!(value["author"], "an");
+    assert_eq!(value["categories"], json!(["long"]));
+    assert_eq!(value["source"]["title"], "sourc");
+    // within the limits now, so there's nothing more to report
+    assert!(rss.limit_fields(&limits).is_empty());
+}
+
+#[test]
+fn cuts_on_char_boundaries() {
+    let mut rss = item(rss2("<title>日本語</title>"));
+    let limits = FieldLimits {
+        title: 7,
+        ..FieldLimits::default()
+    };
+    assert_eq!(rss.limit_fields(&limits), ["title cut to 6 of 9 bytes"]);
+    assert_eq!(
+        serde_json::to_value(&rss).unwrap()["title"],
+        Value::from("日本")
+    );
+}
@@ -0,0 +1,190 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::web::Bytes;
+use rssss::rss::{parse_rss, parse_rss_with, FieldLimits, ParseOptions, Rss};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use support::{app, config, feed_uri, get};
+
+// a description escaped in a CDATA section, as feeds carry HTML
+fn feed(description: &str) -> Bytes {
+    Bytes::from(format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+         <rss version=\"2.0\"><channel><title>t</title>\
+         <item><title>One</title><link>https://example.com/1</link>\
+         <description><![CDATA[{}]]></description></item>\
+         </channel></rss>",
+        description
+    ))
+}
+
+// a hostile description of about `bytes`, every level opened with a word and none closed early
+fn nested(bytes: usize) -> String {
+    let depth = bytes / 27;
+    format!(
+        "{}{}",
+        "<div class=\"level\">deeper ".repeat(depth),
+        "</div>".repeat(depth)
+    )
+}
+
+fn unlimited() -> FieldLimits {
+    FieldLimits {
+        markup: usize::MAX,
+        ..FieldLimits::default()
+    }
+}
+
+#[test]
+fn cuts_huge_markup_before_reading_its_text() {
+    let markup = "<p>lorem ipsum</p>".repeat(10_000);
+    let feed = parse_rss(feed(&markup)).unwrap();
+    let item = &feed.items[0];
+    assert_eq!(
+        feed.warnings,
+        ["item 1 description markup cut to 65536 of 180000 bytes before reading its text"]
+    );
+    assert!(item.description().starts_with("lorem ipsumlorem ipsum"));
+    assert!(item.description_truncated());
+    // the other formats are given it whole
+    assert_eq!(item.markup(), markup);
+}
+
+#[test]
+fn cuts_markup_to_the_limit_of_the_options() {
+    let markup = "<p>lorem ipsum</p>".repeat(100);
+    let options = ParseOptions {
+        limits: FieldLimits {
+            markup: 1_000,
+            ..FieldLimits::default()
+        },
+        ..ParseOptions::default()
+    };
+    let feed = parse_rss_with(feed(&markup), options).unwrap();
+    assert_eq!(
+        feed.warnings,
+        ["item 1 description markup cut to 1000 of 1800 bytes before reading its text"]
+    );
+    assert_eq!(feed.items[0].markup(), markup);
+}
+
+#[actix_rt::test]
+async fn cuts_markup_to_the_configured_limit() {
+    let mut config = config();
+    config.field_limits.markup = 200;
+    let app = app(config).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_wordpress.xml", ""),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK);
+    let warnings = &res.json["warnings"];
+    assert!(
+        warnings.as_array().unwrap().iter().any(|w| w
+            .as_str()
+            .unwrap()
+            .contains("description markup cut to 200 of ")),
+        "{}",
+        warnings
+    );
+}
+
+#[test]
+fn cuts_deeply_nested_markup() {
+    let markup = nested(900_000);
+    let feed = parse_rss(feed(&markup)).unwrap();
+    let item = &feed.items[0];
+    let at = 256 * "<div class=\"level\">deeper ".len();
+    assert_eq!(
+        feed.warnings,
+        [format!(
+            "item 1 description markup cut to {} of {} bytes before reading its text, \
+             its elements nest more than 256 levels deep",
+            at,
+            markup.len()
+        )]
+    );
+    assert!(item.description().starts_with("deeper deeper"));
+}
+
+#[test]
+fn counts_only_the_elements_that_nest() {
+    // unclosed paragraphs and list items, and void elements, don't nest
+    let markup = format!(
+        "<ul>{}</ul>{}<div>{}</div>",
+        "<li>item".repeat(500),
+        "<p>para<br>graph<img src=\"x.png\"/>".repeat(500),
+        "<span>".repeat(200)
+    );
+    let feed = parse_rss(feed(&markup)).unwrap();
+    assert!(feed.warnings.is_empty(), "{:?}", feed.warnings);
+}
+
+#[test]
+fn reads_a_bounded_number_of_text_nodes() {
+    let markup = "a<br>".repeat(12_000);
+    let feed = parse_rss(feed(&markup)).unwrap();
+    assert_eq!(
+        feed.warnings,
+        ["item 1 description text read from its first 10000 text nodes"]
+    );
+    let item = serde_json::to_value(&feed.items[0]).unwrap();
+    assert_eq!(item["description_full_length"], 10_000);
+}
+
+#[test]
+fn leaves_ordinary_descriptions_as_they_were() {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut compared = 0;
+    for entry in fs::read_dir(&dir).unwrap() {
+        let path = entry.unwrap().path();
+        let buf = Bytes::from(fs::read(&path).unwrap());
+        let feed = match parse_rss(buf) {
+            Ok(feed) => feed,
+            Err(_) => continue,
+        };
+        assert!(
+            !feed
+                .warnings
+                .iter()
+                .any(|w| w.contains("description markup") || w.contains("text nodes")),
+            "{}",
+            path.display()
+        );
+        for item in feed.items {
+            let mut unbounded = item.clone();
+            unbounded.set_description_within(item.markup().to_string(), &unlimited());
+            assert_eq!(
+                serde_json::to_value(&item).unwrap(),
+                serde_json::to_value(&unbounded).unwrap(),
+                "{}",
+                path.display()
+            );
+            compared += 1;
+        }
+    }
+    assert!(compared > 50, "{}", compared);
+}
+
+// what a feed of a hundred such descriptions costs, without the limits a single one of them
+// takes over a minute
+#[test]
+fn bounds_the_time_hostile_markup_takes() {
+    let markup = nested(900_000);
+    let mut item = Rss::new("One", "https://example.com/1", "");
+    let started = Instant::now();
+    for _ in 0..100 {
+        item.set_description_within(markup.clone(), &FieldLimits::default());
+    }
+    let elapsed = started.elapsed();
+    assert!(
+        elapsed < Duration::from_secs(20),
+        "read the text of 100 descriptions of {} bytes nested {} levels deep in {:?}",
+        markup.len(),
+        markup.len() / 27,
+        elapsed
+    );
+}
@@ -0,0 +1,117 @@
+mod support;
+
+use actix_web::http::header::{HeaderMap, HeaderValue, ACCEPT, CONTENT_TYPE};
+use actix_web::http::StatusCode;
+use rssss::ndjson::accepts;
+use serde_json::Value;
+use support::{app, config, feed_uri, get, get_with, TestResponse};
+
+const NDJSON: &str = "application/x-ndjson";
+
+fn lines(res: &TestResponse) -> Vec<Value> {
+    assert_eq!(
+        res.headers.get(CONTENT_TYPE).and_then(|v| v.to_str().ok()),
+        Some(NDJSON)
+    );
+    let body = std::str::from_utf8(&res.body).unwrap();
+    assert!(body.ends_with('\n'), "{}", body);
+    body.lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn accepts_ndjson_among_other_media_ranges() {
+    for (accept, expected) in [
+        ("application/x-ndjson", true),
+        ("application/json, application/x-ndjson;q=0.5", true),
+        ("Application/X-NDJSON ; charset=utf-8", true),
+        ("application/x-ndjson;q=0", false),
+        ("application/json", false),
+        ("*/*", false),
+    ] {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_static(accept));
+        assert_eq!(accepts(&headers), expected, "{}", accept);
+    }
+}
+
+#[actix_rt::test]
+async fn writes_the_items_a_line_each() {
+    let app = app(config()).await;
+    let uri = feed_uri("/feed", "/fixtures/youtube.xml", "");
+    let json = get(&app, &uri).await.json;
+
+    let res = get_with(&app, &uri, "Accept", NDJSON).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(Value::Array(lines(&res)), json);
+
+    // from the cache, where nothing is parsed to stream
+    let res = get_with(&app, &uri, "Accept", NDJSON).await;
+    assert_eq!(Value::Array(lines(&res)), json);
+}
+
+#[actix_rt::test]
+async fn ends_with_an_error_line_when_the_parse_fails_midway() {
+    let app = app(config()).await;
+    let res = get_with(
+        &app,
+        &feed_uri("/feed", "/fixtures/rss2_truncated.xml", ""),
+        "Accept",
+        NDJSON,
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK);
+    let lines = lines(&res);
+    let titles = lines[..2]
+        .iter()
+        .map(|line| line["title"].as_str().unwrap())
+        .collect::<Vec<&str>>();
+    assert_eq!(titles, ["Version 3.1", "Version 3.0"]);
+    assert_eq!(lines.len(), 3);
+    assert!(lines[2]["error"]["messages"][0].is_string(), "{}", lines[2]);
+}
+
+#[actix_rt::test]
+async fn fails_as_usual_before_the_first_line() {
+    let app = app(config()).await;
+    let res = get_with(
+        &app,
+        &feed_uri("/feed", "/fixtures/rss2_mismatched_tags.xml", ""),
+        "Accept",
+        NDJSON,
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert!(res.json["messages"][0].is_string());
+
+    let res = get_with(
+        &app,
+        &feed_uri("/feed", "/status/404", ""),
+        "Accept",
+        NDJSON,
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::NOT_FOUND);
+    assert!(res.json["messages"][0].is_string());
+}
+
+#[actix_rt::test]
+async fn options_on_the_whole_feed_still_answer_lines() {
+    let app = app(config()).await;
+    let uri = feed_uri(
+        "/feed",
+        "/fixtures/youtube.xml",
+        "&sort_by=published&case=camel",
+    );
+    let json = get(&app, &uri).await.json;
+    let res = get_with(&app, &uri, "Accept", NDJSON).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(Value::Array(lines(&res)), json);
+    assert!(json[0].get("pubDate").is_some());
+
+    let uri = feed_uri("/feed", "/fixtures/youtube.xml", "&group_by=day");
+    let json = get(&app, &uri).await.json;
+    let res = get_with(&app, &uri, "Accept", NDJSON).await;
+    assert_eq!(Value::Array(lines(&res)), json);
+}
@@ -0,0 +1,96 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use std::time::Duration;
+use support::{app, config, feed_uri, flaky_fetches, get, TestResponse};
+
+fn cache_status(res: &TestResponse) -> Option<&str> {
+    res.headers
+        .get("X-Rssss-Cache")
+        .and_then(|v| v.to_str().ok())
+}
+
+#[actix_rt::test]
+async fn answers_a_parse_failure_from_the_cache_until_the_window_passes() {
+    let mut config = config();
+    config.cache.negative_ttl = Duration::from_millis(200);
+    let app = app(config).await;
+    let uri = feed_uri("/feed", "/flaky/window/10/youtube.xml", "");
+
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(cache_status(&res), None);
+    let message = res.json["messages"][0].clone();
+
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(cache_status(&res), Some("negative"));
+    assert_eq!(res.json["messages"][0], message);
+    assert_eq!(flaky_fetches("window"), 1);
+
+    actix_rt::time::sleep(Duration::from_millis(300)).await;
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(cache_status(&res), None);
+    assert_eq!(flaky_fetches("window"), 2);
+
+    #[cfg(feature = "metrics")]
+    {
+        let res = get(&app, "/stats").await;
+        let feed = &res.json["feeds"][0];
+        assert_eq!(feed["requests"], 3);
+        assert_eq!(feed["negative_hits"], 1);
+    }
+}
+
+#[actix_rt::test]
+async fn refresh_fetches_again_and_a_success_clears_the_failure() {
+    let mut config = config();
+    // nothing to be served from the positive cache
+    config.cache.capacity = 0;
+    let app = app(config).await;
+    let uri = feed_uri("/feed", "/flaky/refresh/1/youtube.xml", "");
+
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    let res = get(&app, &uri).await;
+    assert_eq!(cache_status(&res), Some("negative"));
+    assert_eq!(flaky_fetches("refresh"), 1);
+
+    let res = get(&app, &format!("{}&refresh=true", uri)).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(cache_status(&res), Some("miss"));
+    assert_eq!(flaky_fetches("refresh"), 2);
+
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(cache_status(&res), Some("miss"));
+    assert_eq!(flaky_fetches("refresh"), 3);
+}
+
+#[actix_rt::test]
+async fn a_lenient_request_is_not_answered_with_a_strict_failure() {
+    let app = app(config()).await;
+    let uri = feed_uri("/feed", "/flaky/lenient/1/youtube.xml", "");
+
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    let res = get(&app, &format!("{}&lenient=true", uri)).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(flaky_fetches("lenient"), 2);
+    // nor is a default one answered with the lenient feed, it's fetched again
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(cache_status(&res), Some("miss"));
+    assert_eq!(flaky_fetches("lenient"), 3);
+}
+
+#[actix_rt::test]
+async fn v2_answers_the_cached_failure_with_codes() {
+    let app = app(config()).await;
+    let uri = feed_uri("/v2/feed", "/flaky/v2/10/youtube.xml", "");
+    get(&app, &uri).await;
+    let res = get(&app, &uri).await;
+    assert_eq!(cache_status(&res), Some("negative"));
+    assert_eq!(res.json["errors"][0]["code"], "xml_parse_error");
+}
@@ -0,0 +1,117 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use serde_json::Value;
+use support::{app, config, get, mock_url, url_uri, TestResponse};
+
+fn titles(json: &Value) -> Vec<usize> {
+    json["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["title"].as_str().unwrap().parse().unwrap())
+        .collect()
+}
+
+fn header<'a>(res: &'a TestResponse, name: &str) -> &'a str {
+    res.headers.get(name).unwrap().to_str().unwrap()
+}
+
+#[actix_rt::test]
+async fn pages_through_every_item_once() {
+    let app = app(config()).await;
+    let url = mock_url("/many/120");
+    // 50 a page by default
+    for (params, expected) in [
+        ("", (0..50).collect::<Vec<usize>>()),
+        ("&page=1", (0..50).collect()),
+        ("&page=2", (50..100).collect()),
+        ("&page=3", (100..120).collect()),
+    ] {
+        let res = get(&app, &url_uri("/v2/feed", &url, params)).await;
+        assert_eq!(res.status, StatusCode::OK, "{}", params);
+        assert_eq!(titles(&res.json), expected, "{}", params);
+        assert_eq!(header(&res, "X-Total-Count"), "120", "{}", params);
+        assert_eq!(header(&res, "X-Total-Pages"), "3", "{}", params);
+        // there being another page is no more to read
+        assert_eq!(res.json["has_more"], false, "{}", params);
+    }
+}
+
+#[actix_rt::test]
+async fn serves_pages_past_the_end_empty() {
+    let app = app(config()).await;
+    let url = mock_url("/many/120");
+    for params in ["&page=4", "&page=1000", "&page=2&per_page=200"] {
+        let res = get(&app, &url_uri("/v2/feed", &url, params)).await;
+        assert_eq!(res.status, StatusCode::OK, "{}", params);
+        assert!(titles(&res.json).is_empty(), "{}", params);
+        assert_eq!(header(&res, "X-Total-Count"), "120", "{}", params);
+    }
+    let res = get(&app, &url_uri("/feed", &url, "&page=4")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.json, serde_json::json!([]));
+}
+
+#[actix_rt::test]
+async fn clamps_per_page_to_200() {
+    let app = app(config()).await;
+    let url = mock_url("/many/450");
+    let res = get(&app, &url_uri("/v2/feed", &url, "&per_page=7")).await;
+    assert_eq!(titles(&res.json), (0..7).collect::<Vec<usize>>());
+    assert_eq!(header(&res, "X-Total-Pages"), "65");
+    for per_page in ["200", "201", "100000"] {
+        let params = format!("&page=2&per_page={}", per_page);
+        let res = get(&app, &url_uri("/v2/feed", &url, &params)).await;
+        assert_eq!(res.status, StatusCode::OK, "{}", per_page);
+        assert_eq!(
+            titles(&res.json),
+            (200..400).collect::<Vec<usize>>(),
+            "{}",
+            per_page
+        );
+        assert_eq!(header(&res, "X-Total-Pages"), "3", "{}", per_page);
+    }
+}
+
+#[actix_rt::test]
+async fn rejects_pages_that_are_no_numbers() {
+    let app = app(config()).await;
+    let url = mock_url("/many/10");
+    for (params, name) in [
+        ("&page=0", "page"),
+        ("&page=-1", "page"),
+        ("&page=two", "page"),
+        ("&per_page=0", "per_page"),
+        ("&per_page=1.5", "per_page"),
+    ] {
+        let res = get(&app, &url_uri("/v2/feed", &url, params)).await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST, "{}", params);
+        let error = &res.json["errors"][0];
+        assert_eq!(error["code"], "invalid_parameter", "{}", params);
+        assert!(
+            error["message"].as_str().unwrap().contains(name),
+            "{}: {}",
+            params,
+            error
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn has_more_when_the_window_is_cut_short_on_every_page() {
+    let mut config = config();
+    config.window_items = 100;
+    let app = app(config).await;
+    let url = mock_url("/many/250");
+    for (page, expected) in [(1, 0..50), (2, 50..100), (3, 100..100)] {
+        let params = format!("&page={}", page);
+        let res = get(&app, &url_uri("/v2/feed", &url, &params)).await;
+        assert_eq!(titles(&res.json), expected.collect::<Vec<usize>>());
+        // the pages are of the window alone
+        assert_eq!(header(&res, "X-Total-Count"), "100", "{}", page);
+        assert_eq!(header(&res, "X-Total-Pages"), "2", "{}", page);
+        assert_eq!(res.json["has_more"], true, "{}", page);
+        assert_eq!(res.json["continue_from"], 100, "{}", page);
+    }
+}
@@ -0,0 +1,135 @@
+use actix_web::web::Bytes;
+use rssss::rss::{parse_rss, parse_rss_into};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fs;
+use std::path::PathBuf;
+
+// counts what each thread allocates, so the tests of this binary can run side by side
+struct Counting;
+
+#[derive(Clone, Copy, Default)]
+struct Usage {
+    live: isize,
+    peak: isize,
+    allocations: usize,
+}
+
+thread_local! {
+    static USAGE: Cell<Usage> = const {
+        Cell::new(Usage {
+            live: 0,
+            peak: 0,
+            allocations: 0,
+        })
+    };
+}
+
+fn track(bytes: isize, allocation: bool) {
+    let _ = USAGE.try_with(|usage| {
+        let mut u = usage.get();
+        u.live += bytes;
+        u.peak = u.peak.max(u.live);
+        u.allocations += usize::from(allocation);
+        usage.set(u);
+    });
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            track(layout.size() as isize, true);
+        }
+        ptr
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        track(-(layout.size() as isize), false);
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = System.realloc(ptr, layout, new_size);
+        if !new.is_null() {
+            track(new_size as isize - layout.size() as isize, true);
+        }
+        new
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+// the peak bytes over what was live before, and the number of allocations
+fn measure<T>(f: impl FnOnce() -> T) -> (T, usize, usize) {
+    let before = USAGE.with(|usage| {
+        let mut u = usage.get();
+        u.peak = u.live;
+        usage.set(u);
+        u
+    });
+    let value = f();
+    let after = USAGE.with(Cell::get);
+    (
+        value,
+        (after.peak - before.live) as usize,
+        after.allocations - before.allocations,
+    )
+}
+
+// a single text node of `size` bytes, as a feed with the limits raised may carry
+fn huge_item(size: usize) -> Bytes {
+    let text = "lorem ipsum dolor sit amet ".repeat(size / 27);
+    Bytes::from(format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+         <rss version=\"2.0\"><channel><title>Huge</title>\
+         <item><title>One</title><link>https://example.com/1</link>\
+         <description>{}</description></item>\
+         </channel></rss>",
+        text
+    ))
+}
+
+#[test]
+fn a_huge_text_node_is_not_copied_per_step() {
+    const SIZE: usize = 8 * 1024 * 1024;
+    let buf = huge_item(SIZE);
+    let (feed, peak, allocations) = measure(|| parse_rss(buf.clone()).unwrap());
+    assert_eq!(feed.items.len(), 1);
+    assert_eq!(feed.items[0].markup().len(), SIZE / 27 * 27);
+    // the node as read and its text without controls, copying it along the way took 4 times
+    assert!(peak < 3 * SIZE, "peak {} for {}", peak, SIZE);
+    // 181 when this was written, bounded loosely so the parser may change a little
+    assert!(allocations < 1_000, "{} allocations", allocations);
+}
+
+fn json<T: serde::Serialize>(value: &T) -> serde_json::Value {
+    serde_json::to_value(value).unwrap()
+}
+
+#[test]
+fn streamed_items_are_the_parsed_ones() {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut names = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.ends_with(".xml"))
+        .collect::<Vec<String>>();
+    names.sort();
+    for name in names {
+        let buf = Bytes::from(fs::read(dir.join(&name)).unwrap());
+        let mut streamed = Vec::new();
+        let parsed = parse_rss(buf.clone());
+        let into = parse_rss_into(buf, &mut |item| streamed.push(item));
+        match (parsed, into) {
+            (Ok(parsed), Ok(into)) => {
+                assert_eq!(json(&parsed), json(&into), "{}", name);
+                // Atom entries get the feed authors given after them only in the results
+                if !name.starts_with("atom") && !name.starts_with("youtube") {
+                    assert_eq!(json(&parsed.items), json(&streamed), "{}", name);
+                }
+            }
+            (Err(parsed), Err(into)) => assert_eq!(parsed.codes(), into.codes(), "{}", name),
+            (parsed, into) => panic!("{}: {:?} and {:?}", name, parsed.is_ok(), into.is_ok()),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use rssss::rss::ParserName;
+use serde_json::json;
+use support::{app, config, feed_uri, fixture, get, get_with, post};
+
+#[actix_rt::test]
+async fn parses_with_each_parser_alone() {
+    let app = app(config()).await;
+    for (parser, name) in [
+        ("rss2", "rss2_planet.xml"),
+        ("atom", "atom_planet.xml"),
+        ("rss1", "rss1_hatena.xml"),
+    ] {
+        let path = format!("/fixtures/{}", name);
+        let params = format!("&parser={}", parser);
+        let pinned = get(&app, &feed_uri("/v2/feed", &path, &params)).await;
+        assert_eq!(pinned.status, StatusCode::OK, "{}: {}", parser, pinned.json);
+        // the same feed the chain gets to
+        let chained = get(&app, &feed_uri("/v2/feed", &path, "")).await;
+        assert_eq!(pinned.json, chained.json, "{}", parser);
+
+        let res = post(
+            &app,
+            &format!("/feed/parse?{}", &params[1..]),
+            fixture(name),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::OK, "{}: {}", parser, res.json);
+    }
+}
+
+#[actix_rt::test]
+async fn answers_with_the_errors_of_the_pinned_parser() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_planet.xml", "&parser=atom"),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.json["errors"][0]["code"], "invalid_root_element");
+    assert_eq!(
+        res.json["errors"][0]["message"],
+        "[Atom] invalid root element: rss"
+    );
+    // the others aren't tried
+    let parsers = res.json["parsers"].as_array().unwrap();
+    assert_eq!(parsers.len(), 1, "{}", res.json);
+    assert_eq!(parsers[0]["parser"], "Atom");
+    assert_eq!(parsers[0]["failed_at_line"], 2);
+
+    // where it broke in a document that isn't well-formed
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_truncated.xml", "&parser=rss2"),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.json["errors"][0]["code"], "xml_parse_error");
+    assert_eq!(res.json["parsers"][0]["failed_at_line"], 19);
+    assert_eq!(res.json["parsers"][0]["items_recovered"], 2);
+}
+
+#[actix_rt::test]
+async fn says_what_the_parser_found_rather_than_what_the_document_looks_like() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/v2/feed", "/fixtures/soap_fault.xml", "")).await;
+    assert_eq!(res.json["errors"][0]["code"], "unsupported_format");
+
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/soap_fault.xml", "&parser=rss2"),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.json["errors"][0]["code"], "invalid_root_element");
+    assert_eq!(
+        res.json["errors"][0]["message"],
+        "[RSS V2] invalid root element: Envelope"
+    );
+}
+
+#[actix_rt::test]
+async fn lists_the_parsers_for_an_unknown_one() {
+    let app = app(config()).await;
+    let message = "invalid parameter parser: unexpected value: rss3, one of rss2, atom, rss1";
+    let res = get(
+        &app,
+        &feed_uri("/feed", "/fixtures/rss2_planet.xml", "&parser=rss3"),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.json["messages"], json!([message]));
+
+    let res = post(&app, "/feed/parse?parser=rss3", fixture("rss2_planet.xml")).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.json["messages"], json!([message]));
+
+    assert_eq!(ParserName::names(), ["rss2", "atom", "rss1"]);
+}
+
+#[actix_rt::test]
+async fn parses_past_the_feed_cache() {
+    let app = app(config()).await;
+    let uri = feed_uri("/v2/feed", "/fixtures/rss2_planet.xml", "");
+    get(&app, &uri).await;
+    let res = get(&app, &uri).await;
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "hit");
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_planet.xml", "&parser=rss2"),
+    )
+    .await;
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "miss");
+}
+
+#[actix_rt::test]
+async fn pins_the_parser_of_a_streamed_feed() {
+    let app = app(config()).await;
+    let uri = feed_uri("/feed", "/fixtures/rss2_planet.xml", "&parser=atom");
+    let res = get_with(&app, &uri, "Accept", "application/x-ndjson").await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        res.json["messages"],
+        json!(["[Atom] invalid root element: rss"])
+    );
+
+    let uri = feed_uri("/feed", "/fixtures/rss2_planet.xml", "&parser=rss2");
+    let res = get_with(&app, &uri, "Accept", "application/x-ndjson").await;
+    assert_eq!(res.status, StatusCode::OK);
+    let lines = std::str::from_utf8(&res.body).unwrap().lines().count();
+    assert_eq!(lines, get(&app, &uri).await.json.as_array().unwrap().len());
+}
@@ -0,0 +1,104 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::web::Bytes;
+use rssss::error::ErrorCode;
+use rssss::rss::{parse_rss_with, ParseOptions, Recovery};
+use support::{app, config, feed_uri, get};
+
+#[actix_rt::test]
+async fn says_how_far_each_parser_got() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_truncated.xml", ""),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    let parsers = &res.json["parsers"];
+    assert_eq!(parsers.as_array().unwrap().len(), 3);
+    assert_eq!(parsers[0]["parser"], "RSS V2");
+    assert_eq!(parsers[0]["code"], "xml_parse_error");
+    assert_eq!(parsers[0]["items_recovered"], 2);
+    assert_eq!(parsers[0]["failed_at_line"], 19);
+    // the others stop at the root
+    for parser in &parsers.as_array().unwrap()[1..] {
+        assert_eq!(parser["code"], "invalid_root_element", "{}", parser);
+        assert_eq!(parser["items_recovered"], 0, "{}", parser);
+        assert_eq!(parser["failed_at_line"], 2, "{}", parser);
+    }
+    assert_eq!(parsers[1]["parser"], "Atom");
+    assert_eq!(parsers[2]["parser"], "RSS V1");
+
+    // v1 keeps to the messages
+    let res = get(&app, &feed_uri("/feed", "/fixtures/rss2_truncated.xml", "")).await;
+    assert!(res.json.get("parsers").is_none());
+}
+
+#[actix_rt::test]
+async fn partial_answers_with_the_items_read_before_the_error() {
+    let app = app(config()).await;
+    // a failure cached for the strict parse isn't the answer to a partial one
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_truncated.xml", ""),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_truncated.xml", "&partial=true"),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+    assert_eq!(res.headers.get("X-Rssss-Recovery").unwrap(), "partial");
+    let titles = res.json["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["title"].as_str().unwrap())
+        .collect::<Vec<&str>>();
+    assert_eq!(titles, ["Version 3.1", "Version 3.0"]);
+    let warning = res.json["warnings"][0].as_str().unwrap();
+    assert!(warning.starts_with("partial results:"), "{}", warning);
+    assert!(warning.contains("RSS V2 parser"), "{}", warning);
+    assert!(warning.contains("line 19"), "{}", warning);
+}
+
+#[actix_rt::test]
+async fn a_partial_feed_is_not_the_answer_to_a_default_request() {
+    let app = app(config()).await;
+    let path = "/fixtures/rss2_truncated.xml";
+    let res = get(&app, &feed_uri("/v2/feed", path, "&partial=true")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    let res = get(&app, &feed_uri("/v2/feed", path, "")).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.json["errors"][0]["code"], "xml_parse_error");
+    assert!(res.json.get("items").is_none());
+}
+
+#[test]
+fn without_an_item_partial_is_still_an_error() {
+    let partial = ParseOptions {
+        partial: true,
+        ..ParseOptions::default()
+    };
+    let buf = Bytes::from_static(
+        b"<rss version=\"2.0\"><channel><title>Cut</title><item><title>One</title>",
+    );
+    let e = parse_rss_with(buf, partial).unwrap_err();
+    assert_eq!(e.parsers().len(), 3);
+    assert_eq!(e.parsers()[0].code, ErrorCode::XmlParseError);
+    assert_eq!(e.parsers()[0].items_recovered, 0);
+
+    let feed = parse_rss_with(
+        Bytes::from_static(
+            b"<feed xmlns=\"http://www.w3.org/2005/Atom\"><title>Cut</title>\
+              <entry><title>One</title></entry><entry><title>Tw",
+        ),
+        partial,
+    )
+    .unwrap();
+    assert_eq!(feed.recovery, Some(Recovery::Partial));
+    assert_eq!(feed.items.len(), 1);
+}
@@ -0,0 +1,81 @@
+use rssss::rss::Rss;
+use serde_json::Value;
+
+// the item with the description read, by the fast path where it applies
+fn read(description: &str) -> Value {
+    let mut item = Rss::new("t", "https://example.com/", "");
+    item.set_description(description.to_string());
+    serde_json::to_value(&item).unwrap()
+}
+
+// the same text behind a <br>, which adds none but always takes the HTML parser
+fn parsed(description: &str) -> Value {
+    read(&format!("{}<br>", description))
+}
+
+fn assert_same_as_parsed(descriptions: &[&str]) {
+    for description in descriptions {
+        assert_eq!(read(description), parsed(description), "{:?}", description);
+    }
+}
+
+#[test]
+fn reads_plain_text_as_the_html_parser_does() {
+    let long = "word ".repeat(150);
+    assert_same_as_parsed(&[
+        "",
+        "Hello world",
+        "  \n\t leading and trailing whitespace \n ",
+        "line one\nline two\n\n  indented",
+        "日本語のテキスト、カタカナも。",
+        "Привет, мир",
+        "quotes \" and ' and > alone",
+        "\u{A0}non-breaking lead",
+        &long,
+    ]);
+}
+
+#[test]
+fn reads_entities_as_the_html_parser_does() {
+    assert_same_as_parsed(&[
+        "Fish &amp; chips",
+        "&lt;b&gt;not bold&lt;/b&gt;",
+        "&quot;quoted&quot; &apos;too&apos;",
+        "a&nbsp;b",
+        "&#169; 2024 &#x1F600; &#X41;",
+        "AT&T & friends &",
+        "&&amp;",
+        "100 &# 200",
+        "&amp;amp; stays once decoded",
+        "  &amp; leading",
+    ]);
+}
+
+#[test]
+fn leaves_what_it_can_not_decode_to_the_html_parser() {
+    // named references past the few it knows, and ones without their semicolon
+    assert_same_as_parsed(&[
+        "&copy; Example",
+        "&eacute;t&eacute;",
+        "&amp without a semicolon",
+        "&#0; &#xD800; &#1114112;",
+        "&unknown; entity",
+        "carriage\r\nreturn",
+        "nul\0byte",
+    ]);
+}
+
+#[test]
+fn reads_markup_as_before() {
+    let cases = [
+        ("<p>Hello <b>world</b></p>", "Hello world"),
+        ("<p>a &amp; b</p>", "a & b"),
+        ("  <div>\n  spaced\n</div>  ", "spaced"),
+        ("before<br>after", "beforeafter"),
+        ("<script>alert(1)</script>shown", "alert(1)shown"),
+        ("x &lt; <i>y</i>", "x < y"),
+    ];
+    for (markup, text) in cases {
+        assert_eq!(read(markup)["description"], text, "{:?}", markup);
+    }
+}
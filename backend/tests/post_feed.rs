@@ -0,0 +1,106 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use rssss::query::MAX_BODY_SIZE;
+use serde_json::json;
+use support::{app, config, feed_uri, get, mock_url, post_as, TestResponse};
+use url::form_urlencoded;
+
+const FORM: &str = "application/x-www-form-urlencoded";
+const JSON: &str = "application/json";
+
+fn assert_same(a: &TestResponse, b: &TestResponse) {
+    assert_eq!(a.status, b.status);
+    assert_eq!(a.json, b.json);
+    for name in ["X-Total-Count", "X-Total-Pages"] {
+        assert_eq!(a.headers.get(name), b.headers.get(name), "{}", name);
+    }
+}
+
+#[actix_rt::test]
+async fn json_and_form_bodies_match_the_query_string() {
+    let app = app(config()).await;
+    let url = mock_url("/fixtures/rss2_wordpress.xml");
+    let by_query = get(
+        &app,
+        &feed_uri(
+            "/feed",
+            "/fixtures/rss2_wordpress.xml",
+            "&page=2&per_page=1&group_by=day&tz=%2B09:00&case=camel",
+        ),
+    )
+    .await;
+    assert_eq!(by_query.status, StatusCode::OK);
+
+    let body = json!({
+        "url": url,
+        "page": 2,
+        "per_page": "1",
+        "group_by": "day",
+        "tz": "+09:00",
+        "case": "camel",
+        "expand": false,
+        "inherit_channel_date": null
+    });
+    let by_json = post_as(&app, "/feed", JSON, body.to_string()).await;
+    assert_same(&by_query, &by_json);
+
+    let form = form_urlencoded::Serializer::new(String::new())
+        .append_pair("url", &url)
+        .append_pair("page", "2")
+        .append_pair("per_page", "1")
+        .append_pair("group_by", "day")
+        .append_pair("tz", "+09:00")
+        .append_pair("case", "camel")
+        .finish();
+    let by_form = post_as(&app, "/feed", &format!("{}; charset=utf-8", FORM), form).await;
+    assert_same(&by_query, &by_form);
+}
+
+#[actix_rt::test]
+async fn bodies_are_validated_like_query_strings() {
+    let app = app(config()).await;
+    let res = post_as(&app, "/feed", JSON, r#"{"url": "x", "page": 0}"#).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    let by_query = get(&app, "/feed?url=x&page=0").await;
+    assert_eq!(res.json, by_query.json);
+
+    let res = post_as(&app, "/feed", FORM, "url=a&url=b").await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        res.json["messages"],
+        json!([r#"invalid parameter url: provided twice: "a", "b""#])
+    );
+
+    let res = post_as(&app, "/feed", JSON, r#"{"page": 1}"#).await;
+    assert_eq!(
+        res.json["messages"],
+        json!(["invalid parameter url: is required"])
+    );
+
+    let res = post_as(&app, "/feed", JSON, r#"{"url": ["a", "b"]}"#).await;
+    assert_eq!(
+        res.json["messages"],
+        json!([r#"invalid parameter url: unexpected value: ["a","b"]"#])
+    );
+
+    let res = post_as(&app, "/feed", JSON, "[]").await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+async fn rejects_other_content_types() {
+    let app = app(config()).await;
+    let res = post_as(&app, "/feed", "text/plain", "url=x").await;
+    assert_eq!(res.status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    assert_eq!(res.json["kind"], "unsupported_media_type");
+}
+
+#[actix_rt::test]
+async fn rejects_oversized_bodies() {
+    let app = app(config()).await;
+    let url = format!("https://example.com/?{}", "a".repeat(MAX_BODY_SIZE));
+    let res = post_as(&app, "/feed", JSON, json!({ "url": url }).to_string()).await;
+    assert_eq!(res.status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(res.json["kind"], "payload_too_large");
+}
@@ -0,0 +1,130 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use rssss::rss::{parse_rss_with, ParseOptions, Provenance};
+use serde_json::json;
+use support::{app, config, feed_uri, fixture, get, get_with};
+
+fn parsed(name: &str) -> Vec<Provenance> {
+    let options = ParseOptions {
+        provenance: true,
+        ..ParseOptions::default()
+    };
+    parse_rss_with(fixture(name), options)
+        .unwrap()
+        .items
+        .iter()
+        .map(|item| item.provenance().unwrap().clone())
+        .collect()
+}
+
+#[test]
+fn tells_which_of_the_rss2_elements_won() {
+    let items = parsed("rss2_provenance.xml");
+    let only = &items[0];
+    assert_eq!(
+        only.get("description"),
+        Some("content:encoded (only without a description)")
+    );
+    assert_eq!(
+        only.get("pub_date"),
+        Some("dc:date (only without a pubDate)")
+    );
+    assert_eq!(
+        only.get("author"),
+        Some("author (only without a dc:creator)")
+    );
+    assert_eq!(only.get("updated"), None);
+
+    let twice = &items[1];
+    assert_eq!(
+        twice.get("description"),
+        Some("description (wins over content:encoded)")
+    );
+    assert_eq!(twice.get("pub_date"), Some("pubDate (wins over dc:date)"));
+    assert_eq!(twice.get("author"), Some("dc:creator (wins over author)"));
+    assert_eq!(twice.get("link"), Some("link (the first non-empty one)"));
+    assert_eq!(twice.get("title"), Some("title (the last one)"));
+    assert_eq!(twice.get("updated"), Some("atom:updated (the last one)"));
+}
+
+#[test]
+fn tells_which_of_the_atom_elements_won() {
+    let items = parsed("atom_provenance.xml");
+    let only = &items[0];
+    assert_eq!(
+        only.get("description"),
+        Some("media:description (of media:group, only without a content)")
+    );
+    assert_eq!(
+        only.get("pub_date"),
+        Some("updated (only without a published)")
+    );
+    assert_eq!(
+        only.get("link"),
+        Some("link (the href of the last one with no rel but self or alternate)")
+    );
+
+    let both = &items[1];
+    assert_eq!(
+        both.get("description"),
+        Some("content (wins over media:description)")
+    );
+    assert_eq!(both.get("pub_date"), Some("published (wins over updated)"));
+    assert_eq!(both.get("updated"), Some("updated (the last one)"));
+}
+
+#[test]
+fn tells_which_of_the_rss1_elements_won() {
+    let items = parsed("rss1_hatena.xml");
+    assert_eq!(items[0].get("link"), Some("link (the first non-empty one)"));
+    assert_eq!(items[0].get("pub_date"), Some("dc:date (the last one)"));
+}
+
+#[test]
+fn records_nothing_unless_asked() {
+    let feed = parse_rss_with(fixture("rss2_provenance.xml"), ParseOptions::default()).unwrap();
+    assert!(feed.items.iter().all(|item| item.provenance().is_none()));
+}
+
+#[actix_rt::test]
+async fn annotates_the_items_in_debug_mode() {
+    let app = app(config()).await;
+    let uri = feed_uri("/v2/feed", "/fixtures/rss2_provenance.xml", "&debug=true");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(
+        res.json["items"][1]["provenance"],
+        json!({
+            "title": "title (the last one)",
+            "link": "link (the first non-empty one)",
+            "description": "description (wins over content:encoded)",
+            "pub_date": "pubDate (wins over dc:date)",
+            "updated": "atom:updated (the last one)",
+            "author": "dc:creator (wins over author)",
+        })
+    );
+
+    // nor is a feed parsed with it cached for the requests without
+    let res = get(
+        &app,
+        &feed_uri("/feed", "/fixtures/rss2_provenance.xml", ""),
+    )
+    .await;
+    assert!(res.json[0].get("provenance").is_none());
+}
+
+#[actix_rt::test]
+async fn annotates_the_items_of_a_streamed_feed() {
+    let app = app(config()).await;
+    let uri = feed_uri("/feed", "/fixtures/rss2_provenance.xml", "&debug=true");
+    let res = get_with(&app, &uri, "Accept", "application/x-ndjson").await;
+    assert_eq!(res.status, StatusCode::OK);
+    let lines = std::str::from_utf8(&res.body)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect::<Vec<serde_json::Value>>();
+    assert_eq!(lines[1]["provenance"]["title"], "title (the last one)");
+    assert_eq!(serde_json::Value::Array(lines), get(&app, &uri).await.json);
+}
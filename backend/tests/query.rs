@@ -0,0 +1,161 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use rssss::query::{parse_params, FeedQuery};
+use serde_json::{json, Value};
+use support::{app, config, get};
+
+fn messages(query: &str, names: &[&str]) -> Value {
+    let e = parse_params(query, names).err().unwrap();
+    serde_json::to_value(e).unwrap()["messages"].clone()
+}
+
+#[test]
+fn rejects_duplicates_with_every_value() {
+    assert_eq!(
+        messages("url=a&expand=true&url=b", &["url", "expand"]),
+        json!([r#"invalid parameter url: provided twice: "a", "b""#])
+    );
+    assert_eq!(
+        messages("page=1&page=2&page=3&url=a&url=a", &["url", "page"]),
+        json!([
+            r#"invalid parameter page: provided 3 times: "1", "2", "3""#,
+            r#"invalid parameter url: provided twice: "a", "a""#
+        ])
+    );
+}
+
+#[test]
+fn ignores_duplicates_of_unknown_names() {
+    let params = parse_params("utm_source=a&utm_source=b&url=c", &["url"]).unwrap();
+    assert_eq!(params, vec![("url", "c".to_string())]);
+}
+
+#[test]
+fn keeps_empty_values() {
+    let params = parse_params("url=&expand&page=1", &["url", "expand"]).unwrap();
+    assert_eq!(
+        params,
+        vec![("url", String::new()), ("expand", String::new())]
+    );
+    assert_eq!(
+        messages("url=&url", &["url"]),
+        json!([r#"invalid parameter url: provided twice: "", """#])
+    );
+}
+
+#[test]
+fn decodes_names_and_values() {
+    let params = parse_params(
+        "url=http%3A%2F%2Fexample.com%2F%3Fa%3D1%26b%3D2&tz=%2B09%3A00",
+        &["url", "tz"],
+    )
+    .unwrap();
+    assert_eq!(
+        params,
+        vec![
+            ("url", "http://example.com/?a=1&b=2".to_string()),
+            ("tz", "+09:00".to_string())
+        ]
+    );
+    // an unencoded plus is a space in form encoding
+    let params = parse_params("tz=+09:00", &["tz"]).unwrap();
+    assert_eq!(params, vec![("tz", " 09:00".to_string())]);
+    // an encoded name is the same parameter
+    assert_eq!(
+        messages("%75rl=a&url=b", &["url"]),
+        json!([r#"invalid parameter url: provided twice: "a", "b""#])
+    );
+}
+
+#[test]
+fn sanitizes_echoed_values() {
+    assert_eq!(
+        messages("url=a%0Ab&url=%FF", &["url"]),
+        json!(["invalid parameter url: provided twice: \"a\u{FFFD}b\", \"\u{FFFD}\""])
+    );
+}
+
+#[test]
+fn feed_query_reports_duplicates_before_values() {
+    assert!(FeedQuery::parse("url=a&page=x").is_err());
+    let e = FeedQuery::parse("url=a&url=b&page=x").err().unwrap();
+    assert_eq!(
+        serde_json::to_value(e).unwrap()["messages"],
+        json!([r#"invalid parameter url: provided twice: "a", "b""#])
+    );
+}
+
+#[actix_rt::test]
+async fn feed_endpoints_reject_duplicate_urls() {
+    let app = app(config()).await;
+    for endpoint in ["/feed", "/v2/feed", "/feed/icon"] {
+        let uri = format!(
+            "{}?url=http%3A%2F%2Fa.example%2F&url=http%3A%2F%2Fb.example%2F",
+            endpoint
+        );
+        let res = get(&app, &uri).await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST);
+        let message =
+            r#"invalid parameter url: provided twice: "http://a.example/", "http://b.example/""#;
+        if endpoint == "/v2/feed" {
+            assert_eq!(
+                res.json["errors"],
+                json!([{"code": "invalid_parameter", "message": message}])
+            );
+        } else {
+            assert_eq!(res.json["messages"], json!([message]));
+        }
+    }
+}
+
+#[test]
+fn feed_query_reports_every_invalid_value() {
+    let e = FeedQuery::parse(
+        "page=0x&url=a&per_page=-5&expand=maybe&tz=Mars&group_by=week&sort_by=title&case=kebab",
+    )
+    .err()
+    .unwrap();
+    assert_eq!(
+        serde_json::to_value(e).unwrap()["messages"],
+        json!([
+            "invalid parameter page: unexpected value: 0x",
+            "invalid parameter per_page: unexpected value: -5",
+            "invalid parameter expand: unexpected value: maybe",
+            "invalid parameter tz: unexpected value: Mars, one of Z, UTC or an offset like +09:00, +0900 or -03:30",
+            "invalid parameter group_by: unexpected value: week",
+            "invalid parameter sort_by: unexpected value: title",
+            "invalid parameter case: unexpected value: kebab",
+        ])
+    );
+}
+
+#[actix_rt::test]
+async fn feed_endpoints_report_every_invalid_parameter() {
+    let app = app(config()).await;
+    // the url missing as well as values that don't parse
+    let query = "page=two&per_page=none&include_raw=true&tz=Mars&strictness=strict&lenient=true";
+    let res = get(&app, &format!("/v2/feed?{}", query)).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    let errors = res.json["errors"].as_array().unwrap();
+    assert!(errors.iter().all(|e| e["code"] == "invalid_parameter"));
+    let names = errors
+        .iter()
+        .map(|e| e["message"].as_str().unwrap().split(':').next().unwrap())
+        .collect::<Vec<&str>>();
+    assert_eq!(
+        names,
+        [
+            "invalid parameter page",
+            "invalid parameter per_page",
+            "invalid parameter tz",
+            "invalid parameter url",
+            "invalid parameter lenient",
+            "invalid parameter include_raw",
+        ]
+    );
+
+    let res = get(&app, &format!("/feed?{}", query)).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.json["messages"].as_array().unwrap().len(), 6);
+}
@@ -0,0 +1,180 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use chrono::{Duration, TimeZone, Utc};
+use rssss::quota::{Quota, Usage};
+use support::{app, config, feed_uri, fixture, get, TestResponse};
+
+fn header<'a>(res: &'a TestResponse, name: &str) -> &'a str {
+    res.headers.get(name).unwrap().to_str().unwrap()
+}
+
+#[test]
+fn counts_the_fetches_of_the_last_24_hours() {
+    let quota = Quota::default();
+    let start = Utc.with_ymd_and_hms(2024, 5, 10, 9, 0, 0).unwrap();
+    quota.record("a", 600, start);
+    quota.record("a", 300, start + Duration::hours(12));
+    // cache hits fetch nothing
+    quota.record("a", 0, start + Duration::hours(13));
+    assert_eq!(
+        quota.usage("a", 1_000, start + Duration::hours(13)),
+        Usage {
+            used: 900,
+            remaining: 100,
+            resets_at: Some(start + Duration::hours(24)),
+        }
+    );
+    assert_eq!(
+        quota.usage("a", 1_000, start + Duration::hours(24)),
+        Usage {
+            used: 300,
+            remaining: 700,
+            resets_at: Some(start + Duration::hours(36)),
+        }
+    );
+    assert_eq!(
+        quota.usage("a", 1_000, start + Duration::hours(36)),
+        Usage {
+            used: 0,
+            remaining: 1_000,
+            resets_at: None,
+        }
+    );
+}
+
+#[test]
+fn counts_each_client_apart() {
+    let quota = Quota::default();
+    let now = Utc.with_ymd_and_hms(2024, 5, 10, 9, 0, 0).unwrap();
+    quota.record("a", 1_500, now);
+    quota.record("b", 200, now);
+    assert_eq!(quota.usage("a", 1_000, now).remaining, 0);
+    assert_eq!(quota.usage("b", 1_000, now).remaining, 800);
+    assert_eq!(quota.usage("c", 1_000, now).used, 0);
+}
+
+#[test]
+fn holds_back_what_requests_at_once_may_fetch() {
+    let quota = Quota::default();
+    let now = Utc.with_ymd_and_hms(2024, 5, 10, 9, 0, 0).unwrap();
+    let first = quota.reserve("a", 1_000, 600, now).unwrap();
+    let second = quota.reserve("a", 1_000, 600, now).unwrap();
+    // nothing left that isn't held by the two
+    assert_eq!(
+        quota.reserve("a", 1_000, 600, now).unwrap_err(),
+        Usage {
+            used: 0,
+            remaining: 0,
+            resets_at: None,
+        }
+    );
+    assert!(quota.reserve("b", 1_000, 600, now).is_ok());
+
+    first.settle(100, now);
+    assert_eq!(quota.usage("a", 1_000, now).used, 100);
+    assert_eq!(quota.usage("a", 1_000, now).remaining, 500);
+    // a request that ended without settling fetched nothing
+    drop(second);
+    assert_eq!(quota.usage("a", 1_000, now).remaining, 900);
+}
+
+#[actix_rt::test]
+async fn rejects_requests_once_the_quota_is_used_up() {
+    let youtube = fixture("youtube.xml").len() as u64;
+    let mut config = config();
+    config.daily_quota = Some(youtube + 10);
+    let app = app(config).await;
+
+    let uri = feed_uri("/feed", "/fixtures/youtube.xml", "");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(header(&res, "X-Quota-Remaining"), "10");
+
+    // served from the cache, so free
+    let res = get(&app, &uri).await;
+    assert_eq!(header(&res, "X-Rssss-Cache"), "hit");
+    assert_eq!(header(&res, "X-Quota-Remaining"), "10");
+
+    // the fetch that goes over is still served
+    let other = feed_uri("/feed", "/fixtures/rss2_wordpress.xml", "");
+    let res = get(&app, &other).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(header(&res, "X-Quota-Remaining"), "0");
+
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(res.json["kind"], "quota_exceeded");
+    let message = res.json["messages"][0].as_str().unwrap();
+    let used = youtube + fixture("rss2_wordpress.xml").len() as u64;
+    assert!(message.starts_with(&format!(
+        "daily quota of {} bytes exceeded, {} bytes used, resets at ",
+        youtube + 10,
+        used
+    )));
+    assert_eq!(header(&res, "X-Quota-Remaining"), "0");
+    let retry_after = header(&res, "Retry-After").parse::<i64>().unwrap();
+    assert!((86_390..=86_400).contains(&retry_after));
+}
+
+#[actix_rt::test]
+async fn counts_the_feeds_checked() {
+    let youtube = fixture("youtube.xml").len() as u64;
+    let mut config = config();
+    config.daily_quota = Some(youtube + 10);
+    let app = app(config).await;
+
+    let uri = feed_uri("/feeds/check", "/fixtures/youtube.xml", "");
+    for _ in 0..2 {
+        let res = get(&app, &uri).await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert_eq!(res.json[0]["status"], "ok");
+    }
+    for uri in [uri, feed_uri("/feed", "/fixtures/youtube.xml", "")] {
+        let res = get(&app, &uri).await;
+        assert_eq!(res.status, StatusCode::TOO_MANY_REQUESTS, "{}", uri);
+        let message = res.json["messages"][0].as_str().unwrap();
+        let used = format!("{} bytes used", youtube * 2);
+        assert!(message.contains(&used), "{}", message);
+    }
+}
+
+#[actix_rt::test]
+async fn counts_the_feeds_validated() {
+    let youtube = fixture("youtube.xml").len() as u64;
+    let mut config = config();
+    config.daily_quota = Some(youtube + 10);
+    let app = app(config).await;
+
+    let uri = feed_uri("/feed/validate", "/fixtures/youtube.xml", "");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(header(&res, "X-Quota-Remaining"), "10");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(header(&res, "X-Quota-Remaining"), "0");
+
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(res.json["kind"], "quota_exceeded");
+    assert!(header(&res, "Retry-After").parse::<i64>().unwrap() > 0);
+}
+
+#[actix_rt::test]
+async fn counts_bytes_of_feeds_that_failed_to_parse() {
+    let mut config = config();
+    config.daily_quota = Some(1_000_000);
+    let app = app(config).await;
+    let res = get(&app, &feed_uri("/feed", "/fixtures/not_a_feed.html", "")).await;
+    assert_eq!(res.status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    let remaining = 1_000_000 - fixture("not_a_feed.html").len();
+    assert_eq!(header(&res, "X-Quota-Remaining"), remaining.to_string());
+}
+
+#[actix_rt::test]
+async fn leaves_responses_alone_without_a_quota() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed", "/fixtures/youtube.xml", "")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert!(res.headers.get("X-Quota-Remaining").is_none());
+}
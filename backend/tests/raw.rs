@@ -0,0 +1,134 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::web::Bytes;
+use rssss::rss::{parse_rss_with, ParseOptions, RawFields};
+use support::{app, config, feed_uri, get, get_with};
+
+const PARAMS: &str = "&debug=true&include_raw=true";
+
+#[actix_rt::test]
+async fn includes_the_fields_as_read_next_to_the_processed_ones() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_raw.xml", PARAMS),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK);
+    let item = &res.json["items"][0];
+    let raw = &item["raw"];
+
+    assert_eq!(
+        item["title"],
+        "Fish & Chips \u{2013} Friday\u{2019}s Social"
+    );
+    assert_eq!(
+        raw["title"],
+        "\n\t\t\tFish & Chips \u{2013} Friday\u{2019}s Social\n\t\t"
+    );
+    assert_eq!(
+        raw["link"],
+        "  https://allotments.example.org/social?a=1&b=2  "
+    );
+    assert_eq!(raw["pub_date"], " Fri, 04 Oct 2024 18:00:00 +0000 ");
+    // escaped twice, and the markup still in it
+    assert_eq!(
+        raw["description"],
+        "&lt;p&gt;Tickets &amp;amp; raffle at the &lt;strong&gt;hut&lt;/strong&gt; &amp;mdash; bring a plate.&lt;/p&gt;"
+    );
+    assert_eq!(
+        item["description"],
+        "Tickets & raffle at the hut \u{2014} bring a plate."
+    );
+}
+
+#[actix_rt::test]
+async fn leaves_raw_out_otherwise() {
+    let app = app(config()).await;
+    for params in ["", "&debug=true", "&include_raw=false&debug=true"] {
+        let res = get(
+            &app,
+            &feed_uri("/v2/feed", "/fixtures/rss2_raw.xml", params),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::OK);
+        assert!(res.json["items"][0].get("raw").is_none(), "{}", params);
+    }
+    // nor is a feed parsed with them cached for the requests without
+    let res = get(&app, &feed_uri("/feed", "/fixtures/rss2_raw.xml", PARAMS)).await;
+    assert!(res.json[0].get("raw").is_some());
+    let res = get(&app, &feed_uri("/feed", "/fixtures/rss2_raw.xml", "")).await;
+    assert!(res.json[0].get("raw").is_none());
+}
+
+#[actix_rt::test]
+async fn requires_debug() {
+    let app = app(config()).await;
+    let uri = feed_uri("/v2/feed", "/fixtures/rss2_raw.xml", "&include_raw=true");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.json["errors"][0]["code"], "invalid_parameter");
+    assert_eq!(
+        res.json["errors"][0]["message"],
+        "invalid parameter include_raw: requires debug=true"
+    );
+}
+
+#[test]
+fn keeps_raw_fields_of_every_format_only_when_asked() {
+    let keep = ParseOptions {
+        keep_raw: true,
+        ..ParseOptions::default()
+    };
+    for name in ["rss2_raw.xml", "atom_metadata.xml", "rss1_metadata.xml"] {
+        let document = Bytes::from(support::fixture(name));
+        let feed = parse_rss_with(document.clone(), keep).unwrap();
+        assert!(
+            feed.items.iter().all(|item| item.raw().is_some()),
+            "{}",
+            name
+        );
+        let feed = parse_rss_with(document, ParseOptions::default()).unwrap();
+        assert!(
+            feed.items.iter().all(|item| item.raw().is_none()),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn caps_each_raw_field() {
+    let keep = ParseOptions {
+        keep_raw: true,
+        ..ParseOptions::default()
+    };
+    // a two byte character across the cap
+    let description = format!("{}é{}", "a".repeat(RawFields::LIMIT - 1), "b".repeat(100));
+    let document = format!(
+        "<rss version=\"2.0\"><channel><title>t</title><item><title>x</title><link>https://example.com/</link><description>{}</description></item></channel></rss>",
+        description
+    );
+    let feed = parse_rss_with(Bytes::from(document), keep).unwrap();
+    let raw = feed.items[0].raw().unwrap();
+    assert_eq!(raw.description(), "a".repeat(RawFields::LIMIT - 1));
+    assert_eq!(raw.title(), "x");
+    assert_eq!(raw.pub_date(), None);
+}
+
+#[actix_rt::test]
+async fn keeps_the_raw_fields_of_a_streamed_feed() {
+    let app = app(config()).await;
+    let uri = feed_uri("/feed", "/fixtures/rss2_raw.xml", PARAMS);
+    let res = get_with(&app, &uri, "Accept", "application/x-ndjson").await;
+    assert_eq!(res.status, StatusCode::OK);
+    let first = std::str::from_utf8(&res.body)
+        .unwrap()
+        .lines()
+        .next()
+        .unwrap();
+    let first: serde_json::Value = serde_json::from_str(first).unwrap();
+    assert!(first.get("raw").is_some(), "{}", first);
+    assert_eq!(first["raw"], get(&app, &uri).await.json[0]["raw"]);
+}
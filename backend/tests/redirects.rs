@@ -0,0 +1,142 @@
+mod support;
+
+use actix_web::http::header::HeaderName;
+use actix_web::http::StatusCode;
+use awc::SendClientRequest;
+use rssss::cache::Caches;
+use rssss::config::Config;
+use rssss::deadline::Progress;
+use rssss::error::{ApiError, ErrorCode};
+use rssss::redirects::{follow_redirects, Hops, UpstreamResponse};
+use rssss::upstream;
+use std::time::Duration;
+use support::{app, config, feed_uri, get, mock_url};
+use url::Url;
+
+fn send(url: &str, _conditions: &[(HeaderName, String)]) -> SendClientRequest {
+    upstream::client().get(url).send()
+}
+
+fn budgeted(millis: u64) -> Config {
+    let mut config = config();
+    config.upstream.redirects.budget = Duration::from_millis(millis);
+    config
+}
+
+async fn follow(path: &str, config: &Config) -> Result<(Url, UpstreamResponse), ApiError> {
+    let url = Url::parse(&mock_url(path)).unwrap();
+    let caches = Caches::new(&config.cache);
+    let mut hops = Hops::default();
+    follow_redirects(url, send, &mut hops, &Progress::default(), config, &caches).await
+}
+
+fn failure(result: Result<(Url, UpstreamResponse), ApiError>) -> ApiError {
+    match result {
+        Ok((url, _)) => panic!("followed to {}", url),
+        Err(e) => e,
+    }
+}
+
+#[actix_rt::test]
+async fn follows_a_slow_chain_within_the_budget() {
+    let config = budgeted(2_000);
+    let (url, res) = follow("/slow-hops/50/2/youtube.xml", &config)
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(url.path().ends_with("/fixtures/youtube.xml"), "{}", url);
+}
+
+#[actix_rt::test]
+async fn shares_the_budget_across_the_hops() {
+    // each hop takes 300ms, well within the budget on its own
+    let config = budgeted(500);
+    let e = failure(follow("/slow-hops/300/2/youtube.xml", &config).await);
+    assert_eq!(e.codes(), [ErrorCode::RedirectBudgetExceeded]);
+    assert_eq!(e.status(), StatusCode::GATEWAY_TIMEOUT);
+    let message = &e.messages()[0];
+    assert!(
+        message.starts_with("redirects took more than 500ms, given up at hop 1: "),
+        "{}",
+        message
+    );
+    assert!(
+        message.ends_with("/slow-hops/300/1/youtube.xml"),
+        "{}",
+        message
+    );
+}
+
+#[actix_rt::test]
+async fn counts_the_first_fetch_against_the_budget() {
+    let config = budgeted(200);
+    let e = failure(follow("/slow/1000/youtube.xml", &config).await);
+    assert!(e.messages()[0].contains("given up at hop 0"), "{:?}", e);
+}
+
+#[actix_rt::test]
+async fn drains_the_body_of_a_redirect() {
+    let config = config();
+    let (url, res) = follow("/bulky-redirect/10000/youtube.xml", &config)
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(url.path().ends_with("/fixtures/youtube.xml"));
+}
+
+#[actix_rt::test]
+async fn refuses_a_redirect_body_over_the_cap() {
+    let config = config();
+    let bytes = config.upstream.redirects.hop_bytes + 1;
+    let path = format!("/bulky-redirect/{}/youtube.xml", bytes);
+    let e = failure(follow(&path, &config).await);
+    assert_eq!(e.codes(), [ErrorCode::RedirectBodyTooLarge]);
+    assert_eq!(e.status(), StatusCode::BAD_GATEWAY);
+    assert!(
+        e.messages()[0].starts_with("redirect body over 16384 bytes at hop 0: "),
+        "{:?}",
+        e
+    );
+}
+
+#[actix_rt::test]
+async fn stops_at_the_redirect_limit() {
+    let config = config();
+    let e = failure(follow("/redirect/3/youtube.xml", &config).await);
+    assert_eq!(e.codes(), [ErrorCode::RedirectLimitExceeded]);
+    assert!(follow("/redirect/2/youtube.xml", &config).await.is_ok());
+}
+
+#[actix_rt::test]
+async fn answers_a_chain_over_the_budget_with_504() {
+    let app = app(budgeted(500)).await;
+    let uri = feed_uri("/v2/feed", "/slow-hops/300/2/youtube.xml", "");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::GATEWAY_TIMEOUT);
+    assert_eq!(res.json["kind"], "deadline_exceeded");
+    assert_eq!(res.json["errors"][0]["code"], "redirect_budget_exceeded");
+}
+
+#[actix_rt::test]
+async fn follows_a_location_of_raw_utf8() {
+    let config = config();
+    let (url, res) = follow("/raw-redirect/japanese", &config).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        url.as_str(),
+        mock_url("/echo/%E6%97%A5%E6%9C%AC%E8%AA%9E/%E3%83%95%E3%82%A3%E3%83%BC%E3%83%89.xml")
+    );
+}
+
+#[actix_rt::test]
+async fn follows_a_location_with_a_space() {
+    let config = config();
+    let (url, res) = follow("/raw-redirect/space", &config).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(url.as_str(), mock_url("/echo/my%20feed.xml"));
+
+    let app = app(config).await;
+    let res = get(&app, &feed_uri("/v2/feed", "/raw-redirect/space", "")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.json["items"].as_array().unwrap().len(), 2);
+}
@@ -0,0 +1,223 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use rssss::config::Config;
+use rssss::fixtures::{self, fixture_name, fixture_path};
+use std::fs;
+use std::path::{Path, PathBuf};
+use support::{app, config, feed_uri, fixture, get, mock_url, url_uri};
+use url::Url;
+
+const PLANET: &str = "/fixtures/rss2_planet.xml";
+const OFFLINE: &str = "https://feeds.example.invalid/planet/rss.xml?lang=en";
+
+// a fresh directory per test, left behind to be inspected
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rssss-replay-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn replay_config(dir: &Path) -> Config {
+    Config {
+        fixture_dir: Some(dir.to_path_buf()),
+        ..config()
+    }
+}
+
+fn record_config(dir: &Path) -> Config {
+    Config {
+        record_dir: Some(dir.to_path_buf()),
+        ..config()
+    }
+}
+
+fn url(url: &str) -> Url {
+    Url::parse(url).unwrap()
+}
+
+#[test]
+fn maps_urls_to_file_names() {
+    for (from, to) in [
+        (OFFLINE, "feeds.example.invalid_planet_rss.xml_lang_en"),
+        ("http://Example.COM/feed.xml", "example.com_feed.xml"),
+        // the scheme doesn't matter, an explicit port does
+        ("https://example.com/feed.xml", "example.com_feed.xml"),
+        ("http://example.com:8080/a/b", "example.com_8080_a_b"),
+        ("http://example.com/", "example.com"),
+        ("http://[::1]:3000/feed", "___1__3000_feed"),
+        (
+            "http://example.com/caf%C3%A9...xml",
+            "example.com_caf_C3_A9.xml",
+        ),
+    ] {
+        assert_eq!(fixture_name(&url(from)), to, "{}", from);
+    }
+    let long = format!("http://example.com/{}", "a".repeat(500));
+    assert_eq!(fixture_name(&url(&long)).len(), 200);
+}
+
+#[test]
+fn keeps_traversals_in_the_directory() {
+    let dir = Path::new("/srv/fixtures");
+    for (from, to) in [
+        (
+            "http://example.com/../../etc/passwd",
+            "example.com_etc_passwd",
+        ),
+        (
+            "http://example.com/%2e%2e/%2e%2e/etc/passwd",
+            "example.com_etc_passwd",
+        ),
+        (
+            "http://example.com/..%2f..%2fetc%2fpasswd",
+            "example.com_._2f._2fetc_2fpasswd",
+        ),
+        (
+            "http://example.com/..\\..\\etc\\passwd",
+            "example.com_etc_passwd",
+        ),
+        ("http://..../", "_"),
+    ] {
+        let from = url(from);
+        assert_eq!(fixture_name(&from), to, "{}", from);
+        let path = fixture_path(dir, &from).unwrap();
+        assert_eq!(path.parent(), Some(dir), "{}", from);
+        assert!(!path.to_string_lossy().contains(".."), "{}", from);
+    }
+}
+
+#[actix_rt::test]
+async fn serves_a_feed_from_its_fixture() {
+    let dir = temp_dir("serve");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join(fixture_name(&url(OFFLINE))),
+        fixture("rss2_planet.xml"),
+    )
+    .unwrap();
+    let app = app(replay_config(&dir)).await;
+    for endpoint in ["/feed", "/v2/feed"] {
+        let res = get(&app, &url_uri(endpoint, OFFLINE, "")).await;
+        assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+        assert_eq!(res.headers.get("X-Rssss-Mode").unwrap(), "fixture");
+        assert_eq!(res.headers.get("Cache-Control").unwrap(), "max-age=0");
+    }
+    let res = get(&app, &url_uri("/v2/feed", OFFLINE, "")).await;
+    assert!(!res.json["items"].as_array().unwrap().is_empty());
+    assert!(res.json["upstream_protocol"].is_null());
+
+    // read again on every request, edits show up right away
+    fs::write(
+        dir.join(fixture_name(&url(OFFLINE))),
+        fixture("rss2_wordpress.xml"),
+    )
+    .unwrap();
+    let edited = get(&app, &url_uri("/v2/feed", OFFLINE, "")).await;
+    assert_eq!(edited.status, StatusCode::OK);
+    assert_ne!(edited.json["items"], res.json["items"]);
+}
+
+#[actix_rt::test]
+async fn lists_the_fixtures_for_an_unknown_url() {
+    let dir = temp_dir("unknown");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("example.com_rss"), fixture("rss2_planet.xml")).unwrap();
+    fs::write(dir.join("example.org_atom"), fixture("rss2_planet.xml")).unwrap();
+    let replaying = app(replay_config(&dir)).await;
+    let res = get(&replaying, &url_uri("/v2/feed", OFFLINE, "")).await;
+    assert_eq!(res.status, StatusCode::NOT_FOUND, "{}", res.json);
+    assert_eq!(res.headers.get("X-Rssss-Mode").unwrap(), "fixture");
+    assert_eq!(res.json["kind"], "fixture_not_found");
+    assert_eq!(res.json["errors"][0]["code"], "fixture_not_found");
+    assert_eq!(
+        res.json["errors"][0]["message"],
+        "no fixture for https://feeds.example.invalid/planet/rss.xml?lang=en, expected a file \
+         named feeds.example.invalid_planet_rss.xml_lang_en in RSSSS_FIXTURE_DIR, there are \
+         example.com_rss, example.org_atom"
+    );
+
+    // a directory that isn't there has none
+    let missing = app(replay_config(&temp_dir("missing"))).await;
+    let res = get(&missing, &url_uri("/feed", OFFLINE, "")).await;
+    assert_eq!(res.status, StatusCode::NOT_FOUND);
+    let message = res.json["messages"][0].as_str().unwrap();
+    assert!(message.ends_with("there are none"), "{}", message);
+}
+
+#[actix_rt::test]
+async fn never_reads_outside_the_directory() {
+    let root = temp_dir("traversal");
+    let dir = root.join("fixtures");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(root.join("passwd"), fixture("rss2_planet.xml")).unwrap();
+    let app = app(replay_config(&dir)).await;
+    for from in [
+        "http://example.com/../passwd",
+        "http://example.com/%2e%2e/passwd",
+        "http://example.com/..%2fpasswd",
+    ] {
+        let res = get(&app, &url_uri("/feed", from, "")).await;
+        assert_eq!(res.status, StatusCode::NOT_FOUND, "{}", from);
+    }
+    assert_eq!(fixtures::available(&dir), Vec::<String>::new());
+}
+
+#[actix_rt::test]
+async fn records_a_fetch_for_a_replay() {
+    let dir = temp_dir("record");
+    let recording = app(record_config(&dir)).await;
+    let uri = feed_uri("/v2/feed", PLANET, "");
+    // cached first, record fetches all the same
+    let fetched = get(&recording, &uri).await;
+    assert_eq!(fetched.status, StatusCode::OK, "{}", fetched.json);
+    assert_eq!(fixtures::available(&dir), Vec::<String>::new());
+    let res = get(&recording, &feed_uri("/v2/feed", PLANET, "&record=true")).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "miss");
+    assert!(res.headers.get("X-Rssss-Mode").is_none());
+
+    let url = url(&mock_url(PLANET));
+    assert_eq!(fixtures::available(&dir), [fixture_name(&url)]);
+    assert_eq!(
+        fs::read_to_string(fixture_path(&dir, &url).unwrap()).unwrap(),
+        fixture("rss2_planet.xml")
+    );
+
+    // and served as it was fetched, whatever became of the upstream
+    let replayed = get(&app(replay_config(&dir)).await, &uri).await;
+    assert_eq!(replayed.status, StatusCode::OK);
+    assert_eq!(replayed.headers.get("X-Rssss-Mode").unwrap(), "fixture");
+    assert_eq!(replayed.json["items"], fetched.json["items"]);
+    assert_eq!(replayed.json["channel"], fetched.json["channel"]);
+}
+
+#[actix_rt::test]
+async fn record_needs_a_record_dir_and_a_fetch() {
+    let res = get(
+        &app(config()).await,
+        &feed_uri("/feed", PLANET, "&record=true"),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        res.json["messages"][0],
+        "invalid parameter record: needs RSSSS_RECORD_DIR to be set"
+    );
+
+    let dir = temp_dir("both");
+    let config = Config {
+        record_dir: Some(dir.clone()),
+        ..replay_config(&dir)
+    };
+    let res = get(
+        &app(config).await,
+        &feed_uri("/feed", PLANET, "&record=true"),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        res.json["messages"][0],
+        "invalid parameter record: can't be true with RSSSS_FIXTURE_DIR, nothing is fetched"
+    );
+}
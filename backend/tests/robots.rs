@@ -0,0 +1,150 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use rssss::config::Config;
+use rssss::robots::{matches, parse, Robots};
+use support::{
+    app, config, feed_uri, get, mock_localhost_url, robots_fetches, url_uri, ROBOTS_TXT,
+};
+use url::Url;
+
+fn robots_config() -> Config {
+    Config {
+        respect_robots: true,
+        ..config()
+    }
+}
+
+fn rules(body: &str) -> Robots {
+    parse("https://example.com/robots.txt", body, "rssss")
+}
+
+fn allowed(robots: &Robots, path: &str) -> bool {
+    robots
+        .check(&Url::parse(&format!("https://example.com{}", path)).unwrap())
+        .is_ok()
+}
+
+#[test]
+fn patterns_match_by_prefix_star_and_end() {
+    for (pattern, path, expected) in [
+        ("/", "/feed", true),
+        ("/feed", "/feeds/1", true),
+        ("/feed", "/fee", false),
+        ("/*.xml", "/a/b/feed.xml", true),
+        ("/*.xml", "/feed.xml?page=2", true),
+        ("/*.xml$", "/feed.xml?page=2", false),
+        ("/*.xml$", "/feed.xml", true),
+        ("/feed$", "/feed/", false),
+        ("/a*b*c", "/a-b-b-c-d", true),
+        ("/a*b*c", "/a-c-b", false),
+        ("*", "/anything", true),
+    ] {
+        assert_eq!(matches(pattern, path), expected, "{} {}", pattern, path);
+    }
+}
+
+#[test]
+fn the_longest_match_wins_and_allow_wins_a_tie() {
+    let robots = rules(
+        "User-agent: *\n\
+         Disallow: /feeds/\n\
+         Allow: /feeds/public/\n\
+         Disallow: /feeds/public/draft\n\
+         Allow: /same\n\
+         Disallow: /same\n",
+    );
+    assert!(!allowed(&robots, "/feeds/1.xml"));
+    assert!(allowed(&robots, "/feeds/public/1.xml"));
+    assert!(!allowed(&robots, "/feeds/public/draft.xml"));
+    assert!(allowed(&robots, "/same"));
+    assert!(allowed(&robots, "/other"));
+    assert_eq!(
+        robots.matching("/feeds/public/1.xml").unwrap().to_string(),
+        "Allow: /feeds/public/"
+    );
+}
+
+#[test]
+fn groups_are_chosen_by_the_product_token() {
+    let robots = parse(
+        "https://example.com/robots.txt",
+        ROBOTS_TXT,
+        "RSSSS/1.0 (+https://example.com)",
+    );
+    assert_eq!(robots.rules().len(), 3);
+    assert!(allowed(&robots, "/fixtures/youtube.xml"));
+    assert!(!allowed(&robots, "/fixtures/private.xml"));
+
+    // anyone else falls back to the `*` group
+    let other = parse("https://example.com/robots.txt", ROBOTS_TXT, "other");
+    assert!(!allowed(&other, "/fixtures/youtube.xml"));
+    // robots.txt itself stays readable
+    assert!(allowed(&other, "/robots.txt"));
+}
+
+#[test]
+fn an_empty_disallow_and_unknown_lines_allow() {
+    let robots = rules(
+        "# nothing to hide\n\
+         Sitemap: https://example.com/sitemap.xml\n\
+         User-agent: *\n\
+         Crawl-delay: 10\n\
+         Disallow:\n",
+    );
+    assert!(robots.rules().is_empty());
+    assert!(allowed(&robots, "/feed"));
+}
+
+// the mock's fetch counter is shared by the binary, so everything that reads it as 127.0.0.1 is here
+#[actix_rt::test]
+async fn refuses_disallowed_paths_and_caches_the_rules() {
+    let app = app(robots_config()).await;
+    let before = robots_fetches();
+
+    let res = get(&app, &feed_uri("/feed", "/fixtures/youtube.xml", "")).await;
+    assert_eq!(res.status, StatusCode::OK);
+
+    let res = get(&app, &feed_uri("/v2/feed", "/status/404", "")).await;
+    assert_eq!(res.status, StatusCode::FORBIDDEN);
+    assert_eq!(res.json["errors"][0]["code"], "disallowed_by_robots");
+    let message = res.json["errors"][0]["message"].as_str().unwrap();
+    assert!(message.contains("/robots.txt"), "{}", message);
+    assert!(message.contains("Disallow: /status/"), "{}", message);
+
+    // a redirect into a disallowed path is refused too
+    let res = get(&app, &feed_uri("/feed", "/redirect/1/private.xml", "")).await;
+    assert_eq!(res.status, StatusCode::FORBIDDEN);
+    let message = res.json["messages"][0].as_str().unwrap();
+    assert!(
+        message.contains("Disallow: /fixtures/private"),
+        "{}",
+        message
+    );
+
+    assert_eq!(robots_fetches() - before, 1);
+}
+
+#[actix_rt::test]
+async fn a_missing_robots_txt_allows_everything() {
+    let app = app(robots_config()).await;
+    let res = get(
+        &app,
+        &url_uri("/feed", &mock_localhost_url("/fixtures/youtube.xml"), ""),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK);
+    let res = get(
+        &app,
+        &url_uri("/feed", &mock_localhost_url("/status/404"), ""),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::NOT_FOUND);
+}
+
+#[actix_rt::test]
+async fn robots_are_ignored_unless_asked_for() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed", "/status/404", "")).await;
+    assert_eq!(res.status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,122 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use chrono::{DateTime, TimeZone, Utc};
+use rssss::rss::Channel;
+use serde_json::json;
+use std::time::Duration;
+use support::{app, config, feed_uri, get};
+
+// a Friday
+fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 5, 10, hour, minute, 0).unwrap()
+}
+
+#[actix_rt::test]
+async fn parses_skip_hours_and_days() {
+    let app = app(config()).await;
+    let uri = feed_uri("/v2/feed", "/fixtures/rss2_skip_hours.xml", "");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.json["channel"]["skip_hours"], json!([0, 1, 2]));
+    assert_eq!(
+        res.json["channel"]["skip_days"],
+        json!(["Saturday", "Sunday"])
+    );
+    assert!(res.json["channel"].get("update_period").is_none());
+}
+
+#[actix_rt::test]
+async fn parses_the_syndication_module() {
+    let app = app(config()).await;
+    let uri = feed_uri("/v2/feed", "/fixtures/rss1_syndication.xml", "");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.json["channel"]["update_period"], "weekly");
+    assert_eq!(res.json["channel"]["update_frequency"], 2);
+    assert!(res.json["channel"].get("skip_hours").is_none());
+}
+
+#[test]
+fn derives_the_ttl_from_the_syndication_hints() {
+    let cache = config().cache;
+    let hourly = Channel {
+        update_period: Some("hourly".to_string()),
+        update_frequency: Some(4),
+        ..Channel::default()
+    };
+    assert_eq!(
+        cache.ttl(None, &hourly, at(12, 0)),
+        Duration::from_secs(900)
+    );
+    // clamped by the server-wide max
+    let weekly = Channel {
+        update_period: Some("weekly".to_string()),
+        ..Channel::default()
+    };
+    assert_eq!(
+        cache.ttl(None, &weekly, at(12, 0)),
+        Duration::from_secs(86_400)
+    );
+    // the frequency alone is per day
+    let twice = Channel {
+        update_frequency: Some(2),
+        ..Channel::default()
+    };
+    assert_eq!(
+        cache.ttl(None, &twice, at(12, 0)),
+        Duration::from_secs(43_200)
+    );
+    // the channel ttl wins
+    let both = Channel {
+        ttl: Some(10),
+        ..hourly
+    };
+    assert_eq!(cache.ttl(None, &both, at(12, 0)), Duration::from_secs(600));
+}
+
+#[test]
+fn puts_off_an_expiry_within_skip_hours() {
+    let cache = config().cache;
+    let channel = Channel {
+        skip_hours: vec![0, 1, 2],
+        ..Channel::default()
+    };
+    // expires at 23:55, before the skip hours
+    assert_eq!(
+        cache.ttl(None, &channel, at(23, 50)),
+        Duration::from_secs(300)
+    );
+    // would expire at 00:03, so not until 03:00
+    assert_eq!(
+        cache.ttl(None, &channel, at(23, 58)),
+        Duration::from_secs(3 * 3_600 + 2 * 60)
+    );
+    assert_eq!(
+        cache.ttl(Some("max-age=120"), &channel, at(1, 30)),
+        Duration::from_secs(90 * 60)
+    );
+    // past them again
+    assert_eq!(
+        cache.ttl(None, &channel, at(3, 0)),
+        Duration::from_secs(300)
+    );
+}
+
+#[test]
+fn puts_off_an_expiry_within_skip_days_up_to_the_max() {
+    let cache = config().cache;
+    let channel = Channel {
+        skip_days: vec!["Saturday".to_string()],
+        ..Channel::default()
+    };
+    assert_eq!(
+        cache.ttl(None, &channel, at(12, 0)),
+        Duration::from_secs(300)
+    );
+    // the whole of Saturday is skipped, but no entry lives longer than max_ttl
+    assert_eq!(
+        cache.ttl(None, &channel, at(23, 58)),
+        Duration::from_secs(86_400)
+    );
+}
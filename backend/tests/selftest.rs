@@ -0,0 +1,96 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use rssss::config::Config;
+use std::net::TcpListener;
+use std::time::Instant;
+use support::{app, config, get, get_with, mock_url};
+use url::Url;
+
+fn selftest_config(url: &str) -> Config {
+    Config {
+        selftest_url: Some(Url::parse(url).unwrap()),
+        ..config()
+    }
+}
+
+#[actix_rt::test]
+async fn is_off_without_a_url() {
+    let app = app(config()).await;
+    let res = get(&app, "/selftest").await;
+    assert_eq!(res.status, StatusCode::NOT_FOUND);
+    assert_eq!(res.json["kind"], "selftest_disabled");
+}
+
+#[actix_rt::test]
+async fn reports_each_check() {
+    let url = mock_url("/fixtures/rss2_planet.xml");
+    let app = app(selftest_config(&url)).await;
+    let res = get(&app, "/selftest").await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+    assert_eq!(res.json["url"], url);
+    assert_eq!(res.json["ok"], true, "{}", res.json);
+    for check in ["dns", "fetch", "outbound"] {
+        assert_eq!(res.json[check]["status"], "ok", "{}", res.json);
+        assert!(res.json[check]["elapsed_ms"].is_u64(), "{}", res.json);
+        assert!(res.json[check].get("error").is_none(), "{}", res.json);
+    }
+    assert_eq!(res.json["dns"]["addresses"][0], "127.0.0.1");
+    let fetch = &res.json["fetch"];
+    assert_eq!(fetch["http_status"], 200);
+    assert_eq!(fetch["http_version"], "HTTP/1.1");
+    assert_eq!(fetch["tls"], false);
+    assert_eq!(fetch["remote_address"], "127.0.0.1");
+    assert_eq!(res.json["outbound"]["local_address"], "127.0.0.1");
+    assert_eq!(res.json["outbound"]["remote_address"], "127.0.0.1");
+}
+
+#[actix_rt::test]
+async fn answers_with_the_checks_done_rather_than_waiting() {
+    let app = app(selftest_config(&mock_url("/slow/3000/rss2_planet.xml"))).await;
+    let started = Instant::now();
+    let res = get(&app, "/selftest?timeout_ms=200").await;
+    assert!(started.elapsed().as_millis() < 2_000);
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.json["ok"], false);
+    assert_eq!(res.json["fetch"]["status"], "timeout");
+    assert_eq!(res.json["fetch"]["error"], "no answer within 200 ms");
+    assert!(res.json["fetch"].get("http_status").is_none());
+    assert_eq!(res.json["dns"]["status"], "ok");
+    assert_eq!(res.json["outbound"]["status"], "ok");
+}
+
+#[actix_rt::test]
+async fn reports_a_failed_fetch() {
+    // a port nothing listens on any more
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let app = app(selftest_config(&format!("http://127.0.0.1:{}/", port))).await;
+    let res = get(&app, "/selftest").await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.json["ok"], false);
+    assert_eq!(res.json["fetch"]["status"], "failed", "{}", res.json);
+    assert!(res.json["fetch"]["error"].is_string());
+    assert_eq!(res.json["dns"]["status"], "ok");
+}
+
+#[actix_rt::test]
+async fn requires_a_key_when_keys_are_set() {
+    let mut config = selftest_config(&mock_url("/fixtures/rss2_planet.xml"));
+    config.api_keys = vec!["secret".to_string()];
+    let app = app(config).await;
+    let res = get(&app, "/selftest").await;
+    assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+    let res = get_with(&app, "/selftest", "Authorization", "Bearer secret").await;
+    assert_eq!(res.status, StatusCode::OK);
+}
+
+#[actix_rt::test]
+async fn rejects_an_invalid_timeout() {
+    let app = app(selftest_config(&mock_url("/fixtures/rss2_planet.xml"))).await;
+    let res = get(&app, "/selftest?timeout_ms=0").await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+}
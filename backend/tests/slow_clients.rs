@@ -0,0 +1,132 @@
+// dropped clients are only told of by `/stats`
+#![cfg(feature = "metrics")]
+
+mod support;
+
+use rssss::config::Config;
+use serde_json::Value;
+use socket2::{Domain, Socket, Type};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+use support::{config, feed_uri, serve};
+
+// about half a megabyte of JSON, the raw fields are the bulk of it
+const JSON: &str = "/wordy/100";
+const JSON_PARAMS: &str = "&per_page=100&include_raw=true&debug=true";
+
+// a kernel left to size the send buffer takes the whole response off rssss's hands
+fn timing_out(write_timeout: u64) -> Config {
+    let mut config = config();
+    config.clients.write = Duration::from_secs(write_timeout);
+    config.clients.send_buffer = Some(16_384);
+    config
+}
+
+// with a receive buffer this small the server can't get far ahead of the reader
+fn connect(addr: SocketAddr, uri: &str, accept: &str) -> TcpStream {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket.set_recv_buffer_size(4_096).unwrap();
+    socket.connect(&addr.into()).unwrap();
+    let mut client = TcpStream::from(socket);
+    let head = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nAccept: {}\r\nConnection: close\r\n\r\n",
+        uri, addr, accept
+    );
+    client.write_all(head.as_bytes()).unwrap();
+    client
+}
+
+// what is left to read until the server closes, a reset counts as closed
+fn read_rest(client: &mut TcpStream) -> Vec<u8> {
+    client
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+    let mut rest = Vec::new();
+    if let Err(e) = client.read_to_end(&mut rest) {
+        assert_eq!(e.kind(), ErrorKind::ConnectionReset, "{}", e);
+    }
+    rest
+}
+
+fn slow_client_disconnects(addr: SocketAddr) -> Value {
+    let mut client = TcpStream::connect(addr).unwrap();
+    let head = format!(
+        "GET /stats HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        addr
+    );
+    client.write_all(head.as_bytes()).unwrap();
+    let mut response = String::new();
+    client.read_to_string(&mut response).unwrap();
+    let (_, body) = response.split_once("\r\n\r\n").unwrap();
+    serde_json::from_str::<Value>(body).unwrap()["slow_client_disconnects"].clone()
+}
+
+fn wait_for_disconnect(addr: SocketAddr) {
+    let started = Instant::now();
+    while slow_client_disconnects(addr) == 0 {
+        assert!(started.elapsed() < Duration::from_secs(10), "never dropped");
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+fn content_length(head: &str) -> usize {
+    head.lines()
+        .find_map(|line| line.strip_prefix("content-length: "))
+        .unwrap()
+        .trim()
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn drops_a_client_that_stops_reading() {
+    let addr = serve(timing_out(1));
+    let uri = feed_uri("/feed", JSON, JSON_PARAMS);
+    let mut client = connect(addr, &uri, "application/json");
+    let mut start = [0; 1_024];
+    client.read_exact(&mut start).unwrap();
+    let start = String::from_utf8_lossy(&start).to_string();
+    assert!(start.starts_with("HTTP/1.1 200 OK"), "{}", start);
+
+    wait_for_disconnect(addr);
+    let rest = read_rest(&mut client);
+    let (head, body) = start.split_once("\r\n\r\n").unwrap();
+    assert!(body.len() + rest.len() < content_length(head));
+    assert_eq!(slow_client_disconnects(addr), 1);
+}
+
+#[test]
+fn drops_a_stalled_ndjson_stream() {
+    let mut config = timing_out(1);
+    config.window_items = 10_000;
+    let addr = serve(config);
+    // every item of /large, streamed as it's parsed
+    let uri = feed_uri("/feed", "/large", "");
+    let mut client = connect(addr, &uri, "application/x-ndjson");
+    let mut start = [0; 1_024];
+    client.read_exact(&mut start).unwrap();
+    assert!(start.starts_with(b"HTTP/1.1 200 OK"));
+
+    wait_for_disconnect(addr);
+    let rest = read_rest(&mut client);
+    // a chunked body is ended by an empty chunk, which never came
+    assert!(!rest.ends_with(b"0\r\n\r\n"));
+}
+
+#[test]
+fn keeps_a_client_that_pauses_for_less_than_the_timeout() {
+    let addr = serve(timing_out(2));
+    let uri = feed_uri("/feed", JSON, JSON_PARAMS);
+    let mut client = connect(addr, &uri, "application/json");
+    let mut start = [0; 1_024];
+    client.read_exact(&mut start).unwrap();
+    thread::sleep(Duration::from_millis(1_000));
+
+    let rest = read_rest(&mut client);
+    let start = String::from_utf8_lossy(&start).to_string();
+    let (head, body) = start.split_once("\r\n\r\n").unwrap();
+    assert_eq!(body.len() + rest.len(), content_length(head));
+    assert_eq!(slow_client_disconnects(addr), 0);
+}
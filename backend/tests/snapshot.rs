@@ -0,0 +1,136 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use std::time::Duration;
+use support::{app, config, feed_uri, get};
+
+// every request goes upstream, and the growing feed has changed by then
+fn uncached_config() -> rssss::config::Config {
+    let mut config = config();
+    config.cache.default_ttl = Duration::ZERO;
+    config.cache.min_ttl = Duration::ZERO;
+    config.cache.max_ttl = Duration::ZERO;
+    config
+}
+
+fn titles(json: &serde_json::Value) -> Vec<&str> {
+    json.as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["title"].as_str().unwrap())
+        .collect()
+}
+
+#[actix_rt::test]
+async fn later_pages_come_from_the_snapshot_of_the_first() {
+    let app = app(uncached_config()).await;
+    let path = "/growing/pages/rss2_wordpress.xml";
+    let res = get(&app, &feed_uri("/feed", path, "&per_page=2")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(titles(&res.json), ["new 1", "Release Notes for 2.4"]);
+    let token = res
+        .headers
+        .get("X-Rssss-Snapshot")
+        .unwrap()
+        .to_str()
+        .unwrap();
+
+    // without the token the new item pushes the last one of page 1 onto page 2
+    let res = get(&app, &feed_uri("/feed", path, "&per_page=2&page=2")).await;
+    assert_eq!(
+        titles(&res.json),
+        ["Release Notes for 2.4", "Meet the Team: Documentation"]
+    );
+
+    let params = format!("&per_page=2&page=2&snapshot={}", token);
+    let res = get(&app, &feed_uri("/feed", path, &params)).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "snapshot");
+    assert_eq!(res.headers.get("X-Rssss-Snapshot").unwrap(), token);
+    assert_eq!(res.headers.get("X-Total-Count").unwrap(), "3");
+    assert_eq!(titles(&res.json), ["Meet the Team: Documentation"]);
+}
+
+#[actix_rt::test]
+async fn v2_names_the_snapshot_in_the_envelope() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_wordpress.xml", "&page=1"),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK);
+    let token = res.headers.get("X-Rssss-Snapshot").unwrap();
+    assert_eq!(res.json["snapshot"], token.to_str().unwrap());
+
+    // the same content names the same snapshot
+    let res2 = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_wordpress.xml", "&page=2"),
+    )
+    .await;
+    assert_eq!(res2.json["snapshot"], res.json["snapshot"]);
+}
+
+#[actix_rt::test]
+async fn unpaginated_requests_have_no_snapshot() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/fixtures/rss2_wordpress.xml", ""),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert!(res.headers.get("X-Rssss-Snapshot").is_none());
+    assert!(res.json.get("snapshot").is_none());
+}
+
+#[actix_rt::test]
+async fn rejects_expired_and_unknown_snapshots() {
+    let mut config = uncached_config();
+    config.cache.snapshot_ttl = Duration::ZERO;
+    let app = app(config).await;
+    let path = "/growing/expired/rss2_wordpress.xml";
+    let res = get(&app, &feed_uri("/feed", path, "&per_page=2")).await;
+    let token = res
+        .headers
+        .get("X-Rssss-Snapshot")
+        .unwrap()
+        .to_str()
+        .unwrap();
+
+    for token in [token, "0123456789abcdef"] {
+        let params = format!("&per_page=2&page=2&snapshot={}", token);
+        let res = get(&app, &feed_uri("/feed", path, &params)).await;
+        assert_eq!(res.status, StatusCode::CONFLICT);
+        assert_eq!(res.json["kind"], "snapshot_expired");
+        assert_eq!(
+            res.json["messages"][0],
+            "snapshot expired, restart pagination"
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn snapshots_belong_to_their_url() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/feed", "/fixtures/rss2_wordpress.xml", "&per_page=1"),
+    )
+    .await;
+    let token = res
+        .headers
+        .get("X-Rssss-Snapshot")
+        .unwrap()
+        .to_str()
+        .unwrap();
+
+    let params = format!("&page=2&snapshot={}", token);
+    let res = get(
+        &app,
+        &feed_uri("/feed", "/fixtures/rss1_hatena.xml", &params),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::CONFLICT);
+}
@@ -0,0 +1,88 @@
+// the /v2/feed envelope of every feed in the corpus, compared with tests/golden/snapshots.
+// a change to what the parsers produce shows up here as a diff to review. to accept one:
+//
+//   UPDATE_GOLDEN=1 cargo test --test snapshots
+//   git diff tests/golden/snapshots
+//
+// a feed joins the corpus as a fixture with its names, hosts and texts made up, and a line below
+mod support;
+
+use actix_web::http::StatusCode;
+use rssss::config::Config;
+use serde_json::Value;
+use support::{app, assert_golden, config, feed_uri, get, mock_url, TestClock};
+
+// fixture and the parameters it's fetched with, the same on every run
+const CORPUS: [(&str, &str); 20] = [
+    ("rss2_wordpress.xml", ""),
+    ("rss2_planet.xml", ""),
+    ("rss2_podcast.xml", ""),
+    ("rss2_extensions.xml", ""),
+    ("rss2_metadata.xml", ""),
+    ("rss2_wordpress_comments.xml", ""),
+    ("rss2_escaped_twice.xml", ""),
+    ("rss1_hatena.xml", ""),
+    ("rss1_syndication.xml", ""),
+    ("rss1_metadata.xml", ""),
+    ("atom_github_releases.xml", ""),
+    ("atom_planet.xml", ""),
+    ("atom_metadata.xml", ""),
+    ("youtube.xml", ""),
+    // broken, but recoverable
+    ("rss2_php_notice.xml", ""),
+    ("rss2_two_channels.xml", ""),
+    ("rss2_broken_item.xml", ""),
+    ("rss2_broken_entities.xml", "&strictness=lenient"),
+    ("rss2_truncated.xml", "&strictness=lenient"),
+    ("rss2_mismatched_tags.xml", "&strictness=lenient"),
+];
+
+// fetched at the same time on every run
+fn fixed() -> Config {
+    let mut config = config();
+    config.clock = TestClock::at("2025-01-01T09:00:00Z");
+    config
+}
+
+// the mock server listens on another port every run, it's named upstream.test in snapshots
+fn normalize(json: &Value) -> Value {
+    let origin = mock_url("");
+    let host = origin.trim_start_matches("http://");
+    let text = serde_json::to_string(json)
+        .unwrap()
+        .replace(host, "upstream.test");
+    serde_json::from_str(&text).unwrap()
+}
+
+#[actix_rt::test]
+async fn matches_the_snapshots() {
+    let app = app(fixed()).await;
+    for (fixture, params) in CORPUS {
+        let path = format!("/fixtures/{}", fixture);
+        // debug adds the elements no field took, a parser that starts taking one changes it
+        let params = format!("&debug=true{}", params);
+        let res = get(&app, &feed_uri("/v2/feed", &path, &params)).await;
+        assert_eq!(res.status, StatusCode::OK, "{}: {}", fixture, res.json);
+        let name = fixture.rsplit_once('.').map_or(fixture, |(stem, _)| stem);
+        assert_golden(&format!("snapshots/{}.json", name), &normalize(&res.json));
+    }
+}
+
+#[actix_rt::test]
+async fn snapshots_are_stable() {
+    // a fresh app, nothing cached, and the feeds in another order
+    let (once, again) = (app(fixed()).await, app(fixed()).await);
+    let mut first = Vec::new();
+    for (fixture, params) in CORPUS.iter().rev() {
+        let path = format!("/fixtures/{}", fixture);
+        let params = format!("&debug=true{}", params);
+        let res = get(&once, &feed_uri("/v2/feed", &path, &params)).await;
+        first.push(normalize(&res.json));
+    }
+    for ((fixture, params), first) in CORPUS.iter().rev().zip(first) {
+        let path = format!("/fixtures/{}", fixture);
+        let params = format!("&debug=true{}", params);
+        let res = get(&again, &feed_uri("/v2/feed", &path, &params)).await;
+        assert_eq!(normalize(&res.json), first, "{}", fixture);
+    }
+}
@@ -0,0 +1,115 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use serde_json::{json, Value};
+use support::{app, config, feed_uri, get};
+
+async fn fetch_items(mock_path: &str, params: &str) -> Value {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed", mock_path, params)).await;
+    assert_eq!(res.status, StatusCode::OK);
+    res.json
+}
+
+fn field(items: &Value, name: &str) -> Vec<Value> {
+    items
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|i| i.get(name).cloned().unwrap_or(Value::Null))
+        .collect()
+}
+
+#[actix_rt::test]
+async fn atom_keeps_updated_apart_from_published() {
+    let items = fetch_items("/fixtures/atom_updated.xml", "").await;
+    assert_eq!(
+        field(&items, "pub_date"),
+        [
+            json!("2024-05-01T09:00:00Z"),
+            // published falls back to updated as before
+            json!("2024-05-05T09:00:00Z"),
+            json!("2024-05-10T09:00:00Z"),
+            Value::Null,
+        ]
+    );
+    assert_eq!(
+        field(&items, "updated"),
+        [
+            json!("2024-05-12T08:00:00Z"),
+            json!("2024-05-05T09:00:00Z"),
+            Value::Null,
+            Value::Null,
+        ]
+    );
+}
+
+#[actix_rt::test]
+async fn rss2_takes_atom_updated() {
+    let items = fetch_items("/fixtures/rss2_atom_updated.xml", "").await;
+    assert_eq!(
+        field(&items, "pub_date"),
+        [
+            json!("Wed, 01 May 2024 09:00:00 GMT"),
+            json!("Fri, 10 May 2024 09:00:00 GMT"),
+        ]
+    );
+    assert_eq!(
+        field(&items, "updated"),
+        [json!("2024-05-12T08:00:00Z"), Value::Null]
+    );
+}
+
+#[actix_rt::test]
+async fn sorts_by_published() {
+    let items = fetch_items("/fixtures/atom_updated.xml", "&sort_by=published").await;
+    assert_eq!(
+        field(&items, "title"),
+        [
+            json!("Only published"),
+            json!("Only updated"),
+            json!("Both"),
+            json!("Undated"),
+        ]
+    );
+}
+
+#[actix_rt::test]
+async fn sorts_by_updated_falling_back_to_published() {
+    let items = fetch_items("/fixtures/atom_updated.xml", "&sort_by=updated").await;
+    assert_eq!(
+        field(&items, "title"),
+        [
+            json!("Both"),
+            json!("Only published"),
+            json!("Only updated"),
+            json!("Undated"),
+        ]
+    );
+    let items = fetch_items("/fixtures/rss2_atom_updated.xml", "&sort_by=updated").await;
+    assert_eq!(
+        field(&items, "title"),
+        [json!("Revised"), json!("Untouched")]
+    );
+}
+
+#[actix_rt::test]
+async fn sorts_before_paging() {
+    let items = fetch_items(
+        "/fixtures/atom_updated.xml",
+        "&sort_by=updated&per_page=1&page=2",
+    )
+    .await;
+    assert_eq!(field(&items, "title"), [json!("Only published")]);
+}
+
+#[actix_rt::test]
+async fn rejects_unknown_sorts() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/feed", "/fixtures/atom_updated.xml", "&sort_by=title"),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+}
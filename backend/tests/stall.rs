@@ -0,0 +1,106 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use rssss::config::Config;
+use rssss::stall::document_ended;
+use rssss::upstream::UpstreamConfig;
+use std::time::{Duration, Instant};
+use support::{app, config, feed_uri, fixture, get};
+
+fn stalling_after(millis: u64) -> Config {
+    Config {
+        upstream: UpstreamConfig {
+            stall: Duration::from_millis(millis),
+            ..UpstreamConfig::default()
+        },
+        ..config()
+    }
+}
+
+#[test]
+fn ends_with_the_end_tag_of_a_feed_root() {
+    assert!(document_ended(b"<rss><channel/></rss>"));
+    assert!(document_ended(b"<feed></feed >\r\n\t "));
+    assert!(document_ended(b"<atom:feed></atom:feed>"));
+    assert!(document_ended(b"<rdf:RDF></rdf:RDF>\n"));
+    assert!(!document_ended(b"<rss><channel><item></item>"));
+    assert!(!document_ended(b"<rss><channel></channel></rs"));
+    assert!(!document_ended(b"<rss><title>a</rss> b</title>"));
+    assert!(!document_ended(b""));
+}
+
+#[actix_rt::test]
+async fn answers_with_a_document_the_upstream_keeps_open() {
+    let app = app(stalling_after(300)).await;
+    let uri = feed_uri("/v2/feed", "/stall/1000000/rss2_wordpress.xml", "");
+    let started = Instant::now();
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(res.json["items"].as_array().unwrap().len(), 2);
+    assert_eq!(
+        res.json["warnings"],
+        serde_json::json!([format!(
+            "upstream stalled after {} bytes, read up to the end of the document",
+            fixture("rss2_wordpress.xml").len()
+        )])
+    );
+}
+
+#[actix_rt::test]
+async fn answers_once_the_document_ends_without_waiting_for_the_stall() {
+    let app = app(stalling_after(10_000)).await;
+    let uri = feed_uri("/v2/feed", "/stall/1000000/rss2_wordpress.xml", "");
+    let started = Instant::now();
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    let elapsed = started.elapsed();
+    assert!(elapsed < Duration::from_secs(2), "{:?}", elapsed);
+    assert_eq!(res.json["items"].as_array().unwrap().len(), 2);
+    assert!(res.json["warnings"][0]
+        .as_str()
+        .unwrap()
+        .starts_with("upstream stalled after "));
+}
+
+#[actix_rt::test]
+async fn fails_a_document_cut_short_by_a_stall() {
+    let app = app(stalling_after(300)).await;
+    let uri = feed_uri("/v2/feed", "/stall/1000/rss2_wordpress.xml", "");
+    let started = Instant::now();
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(res.json["errors"][0]["code"], "upstream_stalled");
+    assert_eq!(
+        res.json["errors"][0]["message"],
+        "upstream stalled after 1000 bytes, nothing received for 300ms"
+    );
+}
+
+#[actix_rt::test]
+async fn waits_for_a_trickle_that_keeps_coming() {
+    let app = app(stalling_after(500)).await;
+    let uri = feed_uri("/v2/feed", "/trickle/100/rss2_wordpress.xml", "");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.json["items"].as_array().unwrap().len(), 2);
+    assert!(res.json.get("warnings").is_none());
+}
+
+#[actix_rt::test]
+async fn fails_a_trickle_slower_than_the_stall() {
+    let app = app(stalling_after(200)).await;
+    let uri = feed_uri("/v2/feed", "/trickle/1000/rss2_wordpress.xml", "");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.json["errors"][0]["code"], "upstream_stalled");
+    // the first of its 10 chunks
+    let chunk = fixture("rss2_wordpress.xml").len().div_ceil(10);
+    assert_eq!(
+        res.json["errors"][0]["message"],
+        format!(
+            "upstream stalled after {} bytes, nothing received for 200ms",
+            chunk
+        )
+    );
+}
@@ -0,0 +1,168 @@
+#[cfg(feature = "metrics")]
+mod support;
+
+#[cfg(feature = "metrics")]
+use actix_web::http::StatusCode;
+use rssss::stats::{FeedStats, Stats};
+use std::time::Duration;
+#[cfg(feature = "metrics")]
+use support::{app, config, delete, feed_uri, fixture, get, mock_url};
+
+fn ms(n: u64) -> Duration {
+    Duration::from_millis(n)
+}
+
+fn assert_close(actual: Option<f64>, expected: f64) {
+    let actual = actual.unwrap();
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn aggregates_per_url() {
+    let stats = Stats::new(100);
+    stats.record_request("a", false);
+    stats.record_fetch("a", ms(10), 200, 100);
+    stats.record_parse("a", ms(2), 5);
+    stats.record_request("a", true);
+    stats.record_request("a", false);
+    stats.record_fetch("a", ms(20), 200, 200);
+    stats.record_parse("a", ms(4), 6);
+
+    let snapshot = stats.snapshot(10);
+    assert_eq!(snapshot.feeds.len(), 1);
+    let a = &snapshot.feeds[0];
+    assert_eq!(a.url.as_deref(), Some("a"));
+    assert_eq!(a.requests, 3);
+    assert_close(a.cache_hit_ratio, 1.0 / 3.0);
+    assert_close(a.avg_fetch_ms, 15.0);
+    assert_close(a.p95_fetch_ms, 20.0);
+    assert_close(a.avg_parse_ms, 3.0);
+    assert_eq!(a.last_status, Some(200));
+    assert_eq!(a.last_item_count, Some(6));
+    assert_eq!(a.bytes, 300);
+}
+
+#[test]
+fn takes_the_95th_percentile_of_the_latest_fetches() {
+    let stats = Stats::new(100);
+    for n in 1..=100 {
+        stats.record_fetch("a", ms(n), 200, 0);
+    }
+    assert_close(stats.snapshot(10).feeds[0].p95_fetch_ms, 95.0);
+
+    // only the latest 100 are kept, the average still covers every fetch
+    for n in 101..=120 {
+        stats.record_fetch("a", ms(n), 200, 0);
+    }
+    let a = &stats.snapshot(10).feeds[0];
+    assert_close(a.p95_fetch_ms, 115.0);
+    assert_close(a.avg_fetch_ms, 60.5);
+}
+
+#[test]
+fn sums_up_urls_beyond_the_top() {
+    let stats = Stats::new(100);
+    for (url, requests, latency, bytes) in [("a", 3, 10, 100), ("b", 2, 20, 10), ("c", 2, 40, 1)] {
+        for _ in 0..requests {
+            stats.record_request(url, false);
+        }
+        stats.record_fetch(url, ms(latency), 200, bytes);
+        stats.record_parse(url, ms(latency / 10), 1);
+    }
+    stats.record_request("b", true);
+
+    let snapshot = stats.snapshot(1);
+    let urls = snapshot
+        .feeds
+        .iter()
+        .map(|f| f.url.as_deref().unwrap())
+        .collect::<Vec<&str>>();
+    assert_eq!(urls, ["a"]);
+    assert_eq!(
+        snapshot.other,
+        FeedStats {
+            url: None,
+            requests: 5,
+            cache_hit_ratio: Some(0.2),
+            avg_fetch_ms: Some(30.0),
+            p95_fetch_ms: Some(40.0),
+            avg_parse_ms: Some(3.0),
+            last_status: None,
+            last_item_count: None,
+            bytes: 11,
+            negative_hits: 0,
+            slow_parses: 0,
+        }
+    );
+
+    // a and b are tied at 3 requests, ties are ordered by url
+    let urls = stats
+        .snapshot(3)
+        .feeds
+        .iter()
+        .map(|f| f.url.clone().unwrap())
+        .collect::<Vec<String>>();
+    assert_eq!(urls, ["a", "b", "c"]);
+}
+
+#[test]
+fn counts_urls_over_the_limit_as_other() {
+    let stats = Stats::new(16);
+    for n in 0..100 {
+        stats.record_request(&format!("https://example.com/{}", n), false);
+    }
+    let snapshot = stats.snapshot(100);
+    assert!(snapshot.feeds.len() <= 16);
+    let listed = snapshot.feeds.iter().map(|f| f.requests).sum::<u64>();
+    assert_eq!(listed + snapshot.other.requests, 100);
+}
+
+#[test]
+fn resets_everything() {
+    let stats = Stats::new(16);
+    for n in 0..100 {
+        stats.record_request(&format!("https://example.com/{}", n), false);
+    }
+    stats.reset();
+    let snapshot = stats.snapshot(100);
+    assert!(snapshot.feeds.is_empty());
+    assert_eq!(snapshot.other.requests, 0);
+    assert_eq!(snapshot.other.cache_hit_ratio, None);
+}
+
+#[cfg(feature = "metrics")]
+#[actix_rt::test]
+async fn records_feed_requests() {
+    let app = app(config()).await;
+    let uri = feed_uri("/feed", "/fixtures/youtube.xml", "");
+    get(&app, &uri).await;
+    get(&app, &uri).await;
+    get(&app, &feed_uri("/feed", "/status/404", "")).await;
+
+    let res = get(&app, "/stats").await;
+    assert_eq!(res.status, StatusCode::OK);
+    let feeds = res.json["feeds"].as_array().unwrap();
+    assert_eq!(feeds.len(), 2);
+    assert_eq!(feeds[0]["url"], mock_url("/fixtures/youtube.xml"));
+    assert_eq!(feeds[0]["requests"], 2);
+    assert_eq!(feeds[0]["cache_hit_ratio"], 0.5);
+    assert_eq!(feeds[0]["last_status"], 200);
+    assert_eq!(feeds[0]["last_item_count"], 1);
+    assert_eq!(feeds[0]["bytes"], fixture("youtube.xml").len());
+    assert!(feeds[0]["avg_fetch_ms"].is_f64());
+    assert!(feeds[0]["avg_parse_ms"].is_f64());
+    assert_eq!(feeds[1]["url"], mock_url("/status/404"));
+    assert_eq!(feeds[1]["last_status"], 404);
+    assert!(feeds[1]["last_item_count"].is_null());
+    assert_eq!(res.json["other"]["requests"], 0);
+
+    let res = delete(&app, "/stats").await;
+    assert_eq!(res.status, StatusCode::NO_CONTENT);
+    let res = get(&app, "/stats").await;
+    assert!(res.json["feeds"].as_array().unwrap().is_empty());
+}
@@ -0,0 +1,91 @@
+#![cfg(feature = "admin-ui")]
+
+mod support;
+
+use actix_web::http::{header, StatusCode};
+use rssss::config::Config;
+use rssss::error::{ApiError, InvalidParameterError};
+use rssss::status::{self, RECENT_ERRORS};
+use support::{app, config, feed_uri, get, get_with};
+
+fn page(body: &[u8]) -> String {
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[actix_rt::test]
+async fn renders_a_server_that_has_done_nothing_yet() {
+    let app = app(config()).await;
+    let res = get(&app, "/status").await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(
+        res.headers.get(header::CONTENT_TYPE).unwrap(),
+        "text/html; charset=utf-8"
+    );
+    let page = page(&res.body);
+    assert!(page.contains(&format!("<h1>rssss {}</h1>", env!("CARGO_PKG_VERSION"))));
+    assert!(page.contains("<td>items per response</td><td>1000</td>"));
+    assert!(page.contains("<td>feeds</td><td>0</td>"));
+    assert!(page.contains("<td>hit ratio</td><td>-</td>"));
+    assert!(page.contains("<p>0 running, 0 queued</p>"));
+    assert!(page.contains("No jobs run yet"));
+    assert!(page.contains("No feeds requested yet"));
+}
+
+#[actix_rt::test]
+async fn renders_the_feeds_and_their_errors() {
+    let app = app(config()).await;
+    let uri = feed_uri("/feed", "/fixtures/rss2_wordpress.xml", "");
+    get(&app, &uri).await;
+    get(&app, &uri).await;
+    get(&app, &feed_uri("/feed", "/status/404", "")).await;
+
+    let page = page(&get(&app, "/status").await.body);
+    assert!(page.contains("<td>feeds</td><td>1</td>"));
+    // a hit of the three requests
+    assert!(page.contains("<td>hit ratio</td><td>33.3%</td>"));
+    assert!(page.contains("/fixtures/rss2_wordpress.xml</td><td>2</td><td>50.0%</td><td>200</td>"));
+    assert!(page.contains("/status/404</td><td>1</td><td>0.0%</td><td>404</td><td>-</td>"));
+    assert!(page.contains("<td>feed_not_found</td>"));
+}
+
+#[actix_rt::test]
+async fn escapes_and_bounds_the_recent_errors() {
+    for i in 0..RECENT_ERRORS + 10 {
+        let _ = ApiError::from(InvalidParameterError {
+            name: format!("<script>{}", i),
+            message: "a\nb".to_string(),
+        });
+    }
+    let errors = status::recent_errors();
+    assert_eq!(errors.len(), RECENT_ERRORS);
+    let newest = errors
+        .iter()
+        .find(|e| {
+            e.message
+                .contains(&format!("<script>{}", RECENT_ERRORS + 9))
+        })
+        .unwrap();
+    assert!(newest.message.ends_with("a\u{FFFD}b"));
+
+    let app = app(config()).await;
+    let page = page(&get(&app, "/status").await.body);
+    assert!(!page.contains("<script>"));
+    assert!(page.contains("&lt;script&gt;"));
+}
+
+#[actix_rt::test]
+async fn counts_the_api_keys_rather_than_showing_them() {
+    let app = app(Config {
+        api_keys: vec!["secret-one".to_string(), "secret-two".to_string()],
+        ..config()
+    })
+    .await;
+    let res = get(&app, "/status").await;
+    assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+
+    let res = get_with(&app, "/status", "Authorization", "Bearer secret-one").await;
+    assert_eq!(res.status, StatusCode::OK);
+    let page = page(&res.body);
+    assert!(page.contains("<td>api keys</td><td>2</td>"));
+    assert!(!page.contains("secret"));
+}
@@ -0,0 +1,187 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::web::Bytes;
+use rssss::error::ErrorCode;
+use rssss::rss::{parse_rss_with, ParseOptions, Strictness};
+use support::{app, config, feed_uri, get};
+
+const BROKEN: &str = "/fixtures/rss2_strictness.xml";
+
+#[actix_rt::test]
+async fn the_same_broken_feed_at_each_strictness() {
+    let app = app(config()).await;
+
+    // ` 2.0` isn't 2.0 as written
+    let res = get(&app, &feed_uri("/v2/feed", BROKEN, "&strictness=strict")).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST, "{}", res.json);
+    let parser = &res.json["parsers"][0];
+    assert_eq!(parser["code"], "unsupported_rss_version");
+    assert_eq!(parser["items_recovered"], 0);
+    assert_eq!(parser["failed_at_line"], 2);
+
+    // the version is trimmed, the &nbsp; in the third item isn't XML
+    for params in ["", "&strictness=default"] {
+        let res = get(&app, &feed_uri("/v2/feed", BROKEN, params)).await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST, "{}", params);
+        let parser = &res.json["parsers"][0];
+        assert_eq!(parser["code"], "xml_parse_error", "{}", params);
+        assert_eq!(parser["items_recovered"], 2, "{}", params);
+        assert_eq!(parser["failed_at_line"], 18, "{}", params);
+    }
+
+    let res = get(&app, &feed_uri("/v2/feed", BROKEN, "&strictness=lenient")).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+    assert_eq!(res.headers.get("X-Rssss-Recovery").unwrap(), "fixed_up");
+    let items = res.json["items"].as_array().unwrap();
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[2]["title"], "Version 2.9\u{a0}beta");
+    // and the item without a date has the channel's
+    assert_eq!(items[2]["pub_date"], "Wed, 15 May 2024 09:00:00 +0000");
+}
+
+#[actix_rt::test]
+async fn a_failure_is_only_cached_for_the_same_strictness() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/v2/feed", BROKEN, "&strictness=strict")).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    for (params, status) in [
+        ("&strictness=strict", StatusCode::BAD_REQUEST),
+        ("", StatusCode::BAD_REQUEST),
+        ("&strictness=lenient", StatusCode::OK),
+    ] {
+        let res = get(&app, &feed_uri("/v2/feed", BROKEN, params)).await;
+        assert_eq!(res.status, status, "{}", params);
+        let negative = res
+            .headers
+            .get("X-Rssss-Cache")
+            .is_some_and(|v| v == "negative");
+        assert_eq!(negative, params == "&strictness=strict", "{}", params);
+    }
+}
+
+#[actix_rt::test]
+async fn strict_fails_on_warnings() {
+    let app = app(config()).await;
+    // and the feed cached without warnings_as_errors isn't the answer
+    let uri = feed_uri("/v2/feed", "/fixtures/rss2_two_channels.xml", "");
+    assert_eq!(get(&app, &uri).await.status, StatusCode::OK);
+    let res = get(
+        &app,
+        &feed_uri(
+            "/v2/feed",
+            "/fixtures/rss2_two_channels.xml",
+            "&strictness=strict",
+        ),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    let error = &res.json["errors"][0];
+    assert_eq!(error["code"], "strict_mode_warning");
+    assert!(
+        error["message"].as_str().unwrap().starts_with("[strict] "),
+        "{}",
+        error
+    );
+}
+
+#[actix_rt::test]
+async fn strict_can_not_be_asked_to_forgive() {
+    let app = app(config()).await;
+    for params in [
+        "&strictness=strict&lenient=true",
+        "&strictness=strict&partial=true",
+        "&strictness=strict&inherit_channel_date=true",
+    ] {
+        let res = get(&app, &feed_uri("/v2/feed", BROKEN, params)).await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST, "{}", params);
+        assert_eq!(
+            res.json["errors"][0]["code"], "invalid_parameter",
+            "{}",
+            params
+        );
+    }
+    let res = get(&app, &feed_uri("/v2/feed", BROKEN, "&strictness=loose")).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    // false is as good as leaving them out
+    let res = get(
+        &app,
+        &feed_uri(
+            "/v2/feed",
+            "/fixtures/rss2_planet.xml",
+            "&strictness=strict&lenient=false",
+        ),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+}
+
+#[test]
+fn strict_names_elements_as_the_spec_does() {
+    let buf = Bytes::from_static(
+        b"<rss version=\"2.0\"><channel><title>Cased</title>\
+          <Item><title>One</title></Item><item><title>Two</title></item></channel></rss>",
+    );
+    let feed = parse_rss_with(buf.clone(), ParseOptions::default()).unwrap();
+    assert_eq!(feed.items.len(), 2);
+    let feed = parse_rss_with(buf, ParseOptions::of(Strictness::Strict)).unwrap();
+    assert_eq!(feed.items.len(), 1);
+
+    let e = parse_rss_with(
+        Bytes::from_static(b"<rss VERSION=\"2.0\"><channel/></rss>"),
+        ParseOptions::of(Strictness::Strict),
+    )
+    .unwrap_err();
+    assert_eq!(e.parsers()[0].code, ErrorCode::UndefinedRssVersion);
+}
+
+#[actix_rt::test]
+async fn reads_elements_in_any_case_and_a_padded_version() {
+    let app = app(config()).await;
+    let uri = feed_uri("/v2/feed", "/fixtures/rss2_mixed_case.xml", "");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+    assert_eq!(res.json["channel"]["ttl"], 90);
+    let items = res.json["items"].as_array().unwrap();
+    let read = items
+        .iter()
+        .map(|item| {
+            (
+                item["title"].as_str().unwrap(),
+                item["link"].as_str().unwrap(),
+                item["description"].as_str().unwrap(),
+                item["pub_date"].as_str().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        read,
+        [
+            (
+                "Spring sale starts",
+                "https://shop.example.com/news/spring-sale",
+                "Everything 20% off until Sunday.",
+                "Mon, 06 May 2024 09:00:00 +0900",
+            ),
+            (
+                "New branch in Sapporo",
+                "https://shop.example.com/news/sapporo",
+                "Opening on the first of June.",
+                "Wed, 01 May 2024 18:30:00 +0900",
+            ),
+        ]
+    );
+
+    // held to the spec, neither the root nor the version are what it names
+    let res = get(
+        &app,
+        &feed_uri(
+            "/v2/feed",
+            "/fixtures/rss2_mixed_case.xml",
+            "&strictness=strict",
+        ),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.json["parsers"][0]["code"], "invalid_root_element");
+}
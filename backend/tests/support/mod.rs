@@ -0,0 +1,1074 @@
+// shared by the integration tests, each test binary uses a different subset
+#![allow(dead_code)]
+
+use actix_http::Request;
+use actix_web::body::MessageBody;
+use actix_web::dev::{Service, ServiceResponse};
+use actix_web::http::header::{self, HeaderMap};
+use actix_web::http::StatusCode;
+use actix_web::web::Bytes;
+use actix_web::{test, web, App, HttpRequest, HttpResponse, HttpServer};
+use chrono::{DateTime, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures::stream::{self, StreamExt};
+use rssss::auth::ApiKeys;
+use rssss::cache::{CacheConfig, Caches};
+use rssss::clock::{Clock, SystemClock};
+use rssss::config::Config;
+use rssss::cors;
+use rssss::disconnect::{self, ClientLimits};
+use rssss::hosts::HostFilter;
+use rssss::jobs::{Jobs, JobsConfig};
+use rssss::quota::Quota;
+use rssss::rss::{self, FieldLimits, XmlParser};
+use rssss::stats::{self, Stats};
+use rssss::upstream::UpstreamConfig;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+use url::form_urlencoded;
+
+pub fn fixture(name: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name);
+    fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+}
+
+// compares with tests/golden/{name}, run with UPDATE_GOLDEN=1 to rewrite the file instead
+pub fn assert_golden(name: &str, value: &Value) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(name);
+    let actual = serde_json::to_string_pretty(value).unwrap() + "\n";
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, &actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "{}: {}, run with UPDATE_GOLDEN=1 to write it",
+            path.display(),
+            e
+        )
+    });
+    if actual == expected {
+        return;
+    }
+    // the first line that differs, a whole feed is too much to read in a panic
+    let (actual, expected) = (
+        actual.lines().collect::<Vec<&str>>(),
+        expected.lines().collect::<Vec<&str>>(),
+    );
+    let line = (0..actual.len().max(expected.len()))
+        .find(|i| actual.get(*i) != expected.get(*i))
+        .unwrap_or_default();
+    panic!(
+        "{} differs at line {}\n  expected: {}\n  actual:   {}\n\
+         run with UPDATE_GOLDEN=1 to accept it and review the change with git diff",
+        path.display(),
+        line + 1,
+        expected.get(line).unwrap_or(&"<end>"),
+        actual.get(line).unwrap_or(&"<end>")
+    );
+}
+
+pub fn config() -> Config {
+    Config {
+        host_filter: HostFilter::new(Vec::new(), Vec::new()),
+        cache: CacheConfig {
+            capacity: 100,
+            default_ttl: Duration::from_secs(300),
+            min_ttl: Duration::from_secs(60),
+            max_ttl: Duration::from_secs(86_400),
+            gone_ttl: Duration::from_secs(3_600),
+            icon_ttl: Duration::from_secs(604_800),
+            #[cfg(feature = "cache-disk")]
+            disk_dir: None,
+            #[cfg(feature = "cache-disk")]
+            disk_capacity: 104_857_600,
+            snapshot_ttl: Duration::from_secs(600),
+            negative_ttl: Duration::from_secs(120),
+            negative_capacity: 100,
+            robots_ttl: Duration::from_secs(86_400),
+            robots_capacity: 1_000,
+        },
+        expand_limit: 10,
+        window_items: 1_000,
+        max_items: 10_000,
+        field_limits: FieldLimits::default(),
+        parse_blocking_threshold: rss::BLOCKING_THRESHOLD,
+        slow_parse_threshold: Duration::from_millis(500),
+        reject_slow_parses: false,
+        parser: Arc::new(XmlParser),
+        clock: Arc::new(SystemClock),
+        // the mock upstream listens on loopback
+        allow_private_hosts: true,
+        #[cfg(feature = "metrics")]
+        stats_top_urls: 20,
+        request_timeout: Duration::from_secs(30),
+        daily_quota: None,
+        api_keys: Vec::new(),
+        cors_origins: Vec::new(),
+        upstream: UpstreamConfig::default(),
+        respect_robots: false,
+        selftest_url: None,
+        fixture_dir: None,
+        record_dir: None,
+        jobs: JobsConfig {
+            workers: 2,
+            queue_size: 100,
+            timeout: Duration::from_secs(30),
+        },
+        clients: ClientLimits::default(),
+    }
+}
+
+// a clock turned forward by hand, for `fetched_at` and Age that don't change with the run
+#[derive(Debug)]
+pub struct TestClock(Mutex<DateTime<Utc>>);
+
+impl TestClock {
+    pub fn at(time: &str) -> Arc<TestClock> {
+        Arc::new(TestClock(Mutex::new(time.parse().unwrap())))
+    }
+
+    pub fn advance(&self, secs: i64) {
+        *self.0.lock().unwrap() += chrono::Duration::seconds(secs);
+    }
+}
+
+impl Clock for TestClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.0.lock().unwrap()
+    }
+}
+
+// the real routes with their own caches and stats, so tests don't share state
+pub async fn app(
+    config: Config,
+) -> impl Service<Request, Response = ServiceResponse<impl MessageBody>, Error = actix_web::Error> {
+    let caches = web::Data::new(Caches::new(&config.cache));
+    app_with_caches(config, caches).await
+}
+
+// for tests that look into the caches, what jobs store in particular
+pub async fn app_with_caches(
+    config: Config,
+    caches: web::Data<Caches>,
+) -> impl Service<Request, Response = ServiceResponse<impl MessageBody>, Error = actix_web::Error> {
+    let stats = web::Data::new(Stats::new(stats::MAX_URLS));
+    let jobs = web::Data::new(Jobs::start(&config, caches.clone()));
+    let cors = cors::cors(&config);
+    test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(caches)
+            .app_data(stats)
+            .app_data(web::Data::new(Quota::default()))
+            .app_data(jobs)
+            .wrap(ApiKeys)
+            .wrap(cors)
+            .configure(rssss::routes),
+    )
+    .await
+}
+
+// the real routes behind a real server, for what needs a connection
+pub fn serve(config: Config) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let caches = web::Data::new(Caches::new(&config.cache));
+    let stats = web::Data::new(Stats::new(stats::MAX_URLS));
+    let quota = web::Data::new(Quota::default());
+    let clients = config.clients.clone();
+    thread::spawn(move || {
+        actix_rt::System::new().block_on(async move {
+            let jobs = web::Data::new(Jobs::start(&config, caches.clone()));
+            HttpServer::new(move || {
+                App::new()
+                    .app_data(web::Data::new(config.clone()))
+                    .app_data(caches.clone())
+                    .app_data(stats.clone())
+                    .app_data(quota.clone())
+                    .app_data(jobs.clone())
+                    .wrap(ApiKeys)
+                    .wrap(cors::cors(&config))
+                    .configure(rssss::routes)
+            })
+            .on_connect(disconnect::on_connect(clients.send_buffer))
+            .client_request_timeout(clients.request)
+            .client_disconnect_timeout(clients.disconnect)
+            .workers(1)
+            .listen(listener)
+            .unwrap()
+            .run()
+            .await
+        })
+    });
+    addr
+}
+
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+    // null for empty and non JSON bodies
+    pub json: Value,
+}
+
+pub async fn get<S, B>(app: &S, uri: &str) -> TestResponse
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    call(app, test::TestRequest::get().uri(uri).to_request()).await
+}
+
+// answered 202 while a job does the work, asked again until the job is done
+pub async fn get_eventually<S, B>(app: &S, uri: &str) -> TestResponse
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    for _ in 0..100 {
+        let res = get(app, uri).await;
+        if res.status != StatusCode::ACCEPTED {
+            return res;
+        }
+        actix_rt::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("still pending after 5s: {}", uri);
+}
+
+pub async fn get_with<S, B>(app: &S, uri: &str, name: &str, value: &str) -> TestResponse
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let req = test::TestRequest::get()
+        .uri(uri)
+        .insert_header((name, value))
+        .to_request();
+    call(app, req).await
+}
+
+pub async fn get_with_headers<S, B>(app: &S, uri: &str, headers: &[(&str, &str)]) -> TestResponse
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let mut req = test::TestRequest::get().uri(uri);
+    for (name, value) in headers {
+        req = req.append_header((*name, *value));
+    }
+    call(app, req.to_request()).await
+}
+
+pub async fn post<S, B>(app: &S, uri: &str, body: impl Into<Vec<u8>>) -> TestResponse
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let req = test::TestRequest::post()
+        .uri(uri)
+        .set_payload(body.into())
+        .to_request();
+    call(app, req).await
+}
+
+pub async fn post_as<S, B>(
+    app: &S,
+    uri: &str,
+    content_type: &str,
+    body: impl Into<Vec<u8>>,
+) -> TestResponse
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let req = test::TestRequest::post()
+        .uri(uri)
+        .insert_header((header::CONTENT_TYPE, content_type))
+        .set_payload(body.into())
+        .to_request();
+    call(app, req).await
+}
+
+pub async fn delete<S, B>(app: &S, uri: &str) -> TestResponse
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    call(app, test::TestRequest::delete().uri(uri).to_request()).await
+}
+
+pub async fn head<S, B>(app: &S, uri: &str, headers: &[(&str, &str)]) -> TestResponse
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let mut req = test::TestRequest::default()
+        .method(actix_web::http::Method::HEAD)
+        .uri(uri);
+    for (name, value) in headers {
+        req = req.append_header((*name, *value));
+    }
+    call(app, req.to_request()).await
+}
+
+pub async fn options<S, B>(app: &S, uri: &str, headers: &[(&str, &str)]) -> TestResponse
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let mut req = test::TestRequest::default()
+        .method(actix_web::http::Method::OPTIONS)
+        .uri(uri);
+    for (name, value) in headers {
+        req = req.append_header((*name, *value));
+    }
+    call(app, req.to_request()).await
+}
+
+async fn call<S, B>(app: &S, req: Request) -> TestResponse
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let res = test::call_service(app, req).await;
+    let status = res.status();
+    let headers = res.headers().clone();
+    let body = test::read_body(res).await;
+    let is_json = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    let json = if !is_json || body.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_slice(&body)
+            .unwrap_or_else(|e| panic!("{}: {}", e, String::from_utf8_lossy(&body)))
+    };
+    TestResponse {
+        status,
+        headers,
+        body,
+        json,
+    }
+}
+
+// `/feed` or `/v2/feed` pointed at a path of the mock server
+pub fn feed_uri(endpoint: &str, mock_path: &str, params: &str) -> String {
+    url_uri(endpoint, &mock_url(mock_path), params)
+}
+
+pub fn url_uri(endpoint: &str, url: &str, params: &str) -> String {
+    let url = form_urlencoded::byte_serialize(url.as_bytes()).collect::<String>();
+    format!("{}?url={}{}", endpoint, url, params)
+}
+
+pub fn mock_url(path: &str) -> String {
+    format!("http://{}{}", mock_server(), path)
+}
+
+// the same server under another host name, for what is served by host
+pub fn mock_localhost_url(path: &str) -> String {
+    format!("http://localhost:{}{}", mock_server().port(), path)
+}
+
+pub const ICON_PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\0\x10\0\0\0\x10";
+pub const ICON_ICO: &[u8] = b"\0\0\x01\0\x01\0\x10\x10\0\0\x01\0\x20\0";
+
+// upstream feed server, started once per test binary on its own thread and runtime
+//   /fixtures/{name}           the fixture as is
+//   /redirect/{hops}/{name}    a chain of 302s ending at the fixture
+//   /slow/{millis}/{name}      the fixture after a delay
+//   /slow-redirect/{millis}/{name}  a 302 to /slow/{millis}/{name}
+//   /large                     an RSS 2.0 feed just under the body limit, slow to parse
+//   /many/{count}              an RSS 2.0 feed of that many tiny items, numbered from 0 with
+//                              the guids item-0, item-1 and on
+//   /gzip/{name}               the fixture gzip encoded, 406 if the client can't take it
+//   /charset/{charset}/{name}  the fixture declaring another encoding than its UTF-8 bytes
+//   /status/{code}             an empty response with the status
+//   /headers/{name}            the fixture with cache validators, a cookie and a custom header,
+//                              304 when the etag matches
+//   /site/{name}               the fixture as an HTML page
+//   /icons/{name}              icon.png, huge.png over the size limit or page.png that is HTML
+//   /favicon.ico               an ICO, only when requested as localhost
+//   /drip/{id}/{name}          the fixture in 10 chunks 100ms apart, counted by `drip_counts`
+//   /cookie-gate/{id}/{name}   the fixture with the cookie gate={id}, else a 302 to
+//                              /cookie-set/{its own host}/{id}/{name}, see `cookie_log`
+//   /cookie-set/{host}/{id}/{name}  sets gate={id} for /cookie-gate and a stray cookie for
+//                              another path, then a 302 to /cookie-gate/{id}/{name} at the host
+//   /growing/{id}/{name}       the RSS fixture with a new item on top for every request of the id
+//   /typed/{type}/{subtype}/{name}  the fixture with that Content-Type
+//   /conn/{id}/{name}          the fixture, the client ports of the id listed by `connection_ports`
+//   /robots.txt                rules for rssss as 127.0.0.1, counted by `robots_fetches`,
+//                              404 as localhost
+//   /conditional/{id}/{name}   as /headers/{name}, the validators of the id listed by
+//                              `conditional_requests`
+//   /moved/{host}/{id}/{name}  a 302 to /conditional/{id}/{name} on the host, 127.0.0.1 or localhost
+//   /oversized/{header}/{bytes}/{name}  the fixture with the header padded to that many bytes
+//   /crowded/{count}/{name}    the fixture with that many more headers
+//   /long-redirect/{bytes}/{name}  a 302 to /fixtures/{name} with a query padding it to the bytes
+//   /slow-hops/{millis}/{hops}/{name}  a chain of 302s ending at the fixture, each after a delay
+//   /bulky-redirect/{bytes}/{name}  a 302 to /fixtures/{name} with a body of that many bytes
+//   /stall/{bytes}/{name}      the first bytes of the fixture, all of it for more, and then
+//                              nothing without ever ending
+//   /trickle/{millis}/{name}   the fixture in 10 chunks that many milliseconds apart
+//   /raw-redirect/{kind}       a 302 with a Location as some servers send it, unencoded:
+//                              "japanese" to /echo/日本語/フィード.xml, "space" to /echo/my feed.xml
+//   /echo/{path}               the RSS 2.0 wordpress fixture at any path
+//   /stubs/{count}             an RSS 2.0 feed of that many items with stub descriptions, each
+//                              linking to /site/article.html on the host it was requested as
+fn mock_server() -> SocketAddr {
+    static ADDR: OnceLock<SocketAddr> = OnceLock::new();
+    *ADDR.get_or_init(|| {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            actix_rt::System::new().block_on(async move {
+                HttpServer::new(|| App::new().configure(mock_routes))
+                    .workers(1)
+                    .listen(listener)
+                    .unwrap()
+                    .run()
+                    .await
+            })
+        });
+        addr
+    })
+}
+
+fn mock_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/fixtures/{name}", web::get().to(serve_fixture))
+        .route("/redirect/{hops}/{name}", web::get().to(serve_redirect))
+        .route("/slow/{millis}/{name}", web::get().to(serve_slow))
+        .route(
+            "/slow-redirect/{millis}/{name}",
+            web::get().to(serve_slow_redirect),
+        )
+        .route("/large", web::get().to(serve_large))
+        .route("/many/{count}", web::get().to(serve_many))
+        .route("/wordy/{count}", web::get().to(serve_wordy))
+        .route("/gzip/{name}", web::get().to(serve_gzip))
+        .route("/charset/{charset}/{name}", web::get().to(serve_charset))
+        .route("/status/{code}", web::get().to(serve_status))
+        .route("/headers/{name}", web::get().to(serve_headers))
+        .route("/site/{name}", web::get().to(serve_site))
+        .route("/icons/{name}", web::get().to(serve_icon))
+        .route("/favicon.ico", web::get().to(serve_favicon))
+        .route("/robots.txt", web::get().to(serve_robots))
+        .route("/conditional/{id}/{name}", web::get().to(serve_conditional))
+        .route("/moved/{host}/{id}/{name}", web::get().to(serve_moved))
+        .route("/drip/{id}/{name}", web::get().to(serve_drip))
+        .route("/cookie-gate/{id}/{name}", web::get().to(serve_cookie_gate))
+        .route(
+            "/cookie-set/{host}/{id}/{name}",
+            web::get().to(serve_cookie_set),
+        )
+        .route("/growing/{id}/{name}", web::get().to(serve_growing))
+        .route("/typed/{type}/{subtype}/{name}", web::get().to(serve_typed))
+        .route("/conn/{id}/{name}", web::get().to(serve_conn))
+        .route("/flaky/{id}/{failures}/{name}", web::get().to(serve_flaky))
+        .route("/traced/{id}/{name}", web::get().to(serve_traced))
+        .route(
+            "/oversized/{header}/{bytes}/{name}",
+            web::get().to(serve_oversized),
+        )
+        .route("/crowded/{count}/{name}", web::get().to(serve_crowded))
+        .route(
+            "/long-redirect/{bytes}/{name}",
+            web::get().to(serve_long_redirect),
+        )
+        .route(
+            "/slow-hops/{millis}/{hops}/{name}",
+            web::get().to(serve_slow_hops),
+        )
+        .route(
+            "/bulky-redirect/{bytes}/{name}",
+            web::get().to(serve_bulky_redirect),
+        )
+        .route("/stall/{bytes}/{name}", web::get().to(serve_stall))
+        .route("/trickle/{millis}/{name}", web::get().to(serve_trickle))
+        .route("/raw-redirect/{kind}", web::get().to(serve_raw_redirect))
+        .route("/echo/{path:.*}", web::get().to(serve_echo))
+        .route("/stubs/{count}", web::get().to(serve_stubs));
+}
+
+fn xml(body: String) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("application/xml; charset=utf-8")
+        .body(body)
+}
+
+async fn serve_fixture(name: web::Path<String>) -> HttpResponse {
+    xml(fixture(&name))
+}
+
+async fn serve_typed(path: web::Path<(String, String, String)>) -> HttpResponse {
+    let (kind, subtype, name) = path.into_inner();
+    HttpResponse::Ok()
+        .content_type(format!("{}/{}", kind, subtype))
+        .body(fixture(&name))
+}
+
+// a value that still means something, padded with a parameter
+async fn serve_oversized(path: web::Path<(String, usize, String)>) -> HttpResponse {
+    let (name, bytes, fixture_name) = path.into_inner();
+    let value = match name.as_str() {
+        "content-type" => "application/rss+xml; charset=utf-8; x=",
+        "cache-control" => "max-age=600, x=",
+        _ => "\"",
+    };
+    let value = format!("{}{}", value, "a".repeat(bytes.saturating_sub(value.len())));
+    HttpResponse::Ok()
+        .insert_header((name, value))
+        .body(fixture(&fixture_name))
+}
+
+async fn serve_crowded(path: web::Path<(usize, String)>) -> HttpResponse {
+    let (count, name) = path.into_inner();
+    let mut builder = HttpResponse::Ok();
+    builder.content_type("application/xml; charset=utf-8");
+    for i in 0..count {
+        builder.insert_header((format!("x-filler-{}", i), "1"));
+    }
+    builder.body(fixture(&name))
+}
+
+async fn serve_long_redirect(path: web::Path<(usize, String)>) -> HttpResponse {
+    let (bytes, name) = path.into_inner();
+    let location = format!("/fixtures/{}?pad=", name);
+    let location = format!(
+        "{}{}",
+        location,
+        "a".repeat(bytes.saturating_sub(location.len()))
+    );
+    HttpResponse::Found()
+        .insert_header((header::LOCATION, location))
+        .finish()
+}
+
+async fn serve_slow_hops(path: web::Path<(u64, u32, String)>) -> HttpResponse {
+    let (millis, hops, name) = path.into_inner();
+    actix_rt::time::sleep(Duration::from_millis(millis)).await;
+    let location = match hops {
+        0 => format!("/fixtures/{}", name),
+        n => format!("/slow-hops/{}/{}/{}", millis, n - 1, name),
+    };
+    HttpResponse::Found()
+        .insert_header((header::LOCATION, location))
+        .finish()
+}
+
+async fn serve_bulky_redirect(path: web::Path<(usize, String)>) -> HttpResponse {
+    let (bytes, name) = path.into_inner();
+    HttpResponse::Found()
+        .insert_header((header::LOCATION, format!("/fixtures/{}", name)))
+        .content_type("text/html")
+        .body("a".repeat(bytes))
+}
+
+async fn serve_redirect(path: web::Path<(u32, String)>) -> HttpResponse {
+    let (hops, name) = path.into_inner();
+    let location = match hops {
+        0 => format!("/fixtures/{}", name),
+        n => format!("/redirect/{}/{}", n - 1, name),
+    };
+    HttpResponse::Found()
+        .insert_header((header::LOCATION, location))
+        .finish()
+}
+
+async fn serve_slow(path: web::Path<(u64, String)>) -> HttpResponse {
+    let (millis, name) = path.into_inner();
+    actix_rt::time::sleep(Duration::from_millis(millis)).await;
+    xml(fixture(&name))
+}
+
+async fn serve_slow_redirect(path: web::Path<(u64, String)>) -> HttpResponse {
+    let (millis, name) = path.into_inner();
+    HttpResponse::Found()
+        .insert_header((header::LOCATION, format!("/slow/{}/{}", millis, name)))
+        .finish()
+}
+
+async fn serve_large() -> HttpResponse {
+    let item = "<item><title>An item</title><link>https://example.com/item</link>\
+                <description>&lt;p&gt;A description of a few words, repeated.&lt;/p&gt;</description></item>";
+    let count = 1_000_000 / item.len() - 10;
+    xml(format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?><rss version="2.0"><channel><title>Large</title>{}</channel></rss>"#,
+        item.repeat(count)
+    ))
+}
+
+async fn serve_many(count: web::Path<usize>) -> HttpResponse {
+    let items = (0..count.into_inner())
+        .map(|i| {
+            format!(
+                "<item><title>{}</title><link>https://example.com/{}</link><guid>item-{}</guid></item>",
+                i, i, i
+            )
+        })
+        .collect::<String>();
+    xml(format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?><rss version="2.0"><channel><title>Many</title>{}</channel></rss>"#,
+        items
+    ))
+}
+
+// items with titles and descriptions longer than what is kept of them
+async fn serve_wordy(count: web::Path<usize>) -> HttpResponse {
+    let words = "many words ".repeat(400);
+    let items = (0..count.into_inner())
+        .map(|i| {
+            format!(
+                "<item><title>{} {}</title><link>https://example.com/{}</link><description>{}</description></item>",
+                i, words, i, words
+            )
+        })
+        .collect::<String>();
+    xml(format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?><rss version="2.0"><channel><title>Wordy</title>{}</channel></rss>"#,
+        items
+    ))
+}
+
+async fn serve_gzip(req: HttpRequest, name: web::Path<String>) -> HttpResponse {
+    let accepts_gzip = req
+        .headers()
+        .get(header::ACCEPT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("gzip"));
+    if !accepts_gzip {
+        return HttpResponse::NotAcceptable().finish();
+    }
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(fixture(&name).as_bytes()).unwrap();
+    HttpResponse::Ok()
+        .content_type("application/xml; charset=utf-8")
+        .insert_header((header::CONTENT_ENCODING, "gzip"))
+        .body(encoder.finish().unwrap())
+}
+
+async fn serve_charset(path: web::Path<(String, String)>) -> HttpResponse {
+    let (charset, name) = path.into_inner();
+    let body = fixture(&name).replacen(
+        r#"encoding="UTF-8""#,
+        &format!(r#"encoding="{}""#, charset),
+        1,
+    );
+    HttpResponse::Ok()
+        .content_type(format!("application/xml; charset={}", charset))
+        .body(body)
+}
+
+async fn serve_status(code: web::Path<u16>) -> HttpResponse {
+    HttpResponse::build(StatusCode::from_u16(code.into_inner()).unwrap()).finish()
+}
+
+const LAST_MODIFIED: &str = "Tue, 07 May 2024 09:00:00 GMT";
+
+async fn serve_headers(req: HttpRequest, name: web::Path<String>) -> HttpResponse {
+    let header = |name| req.headers().get(name).and_then(|v| v.to_str().ok());
+    let matches = header(header::IF_NONE_MATCH) == Some("\"v1\"")
+        || header(header::IF_MODIFIED_SINCE) == Some(LAST_MODIFIED);
+    if matches {
+        return HttpResponse::NotModified()
+            .insert_header((header::ETAG, "\"v1\""))
+            .insert_header((header::CACHE_CONTROL, "max-age=600"))
+            .finish();
+    }
+    HttpResponse::Ok()
+        .content_type("application/xml; charset=utf-8")
+        .insert_header((header::ETAG, "\"v1\""))
+        .insert_header((header::LAST_MODIFIED, LAST_MODIFIED))
+        .insert_header((header::SERVER, "mock"))
+        .insert_header((header::SET_COOKIE, "session=secret; HttpOnly"))
+        .insert_header(("X-Internal-Token", "secret"))
+        .body(fixture(&name))
+}
+
+async fn serve_site(name: web::Path<String>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(fixture(&name))
+}
+
+async fn serve_icon(name: web::Path<String>) -> HttpResponse {
+    match name.as_str() {
+        "icon.png" => HttpResponse::Ok().content_type("image/png").body(ICON_PNG),
+        "huge.png" => {
+            let mut body = ICON_PNG.to_vec();
+            body.resize(100_000, 0);
+            HttpResponse::Ok().content_type("image/png").body(body)
+        }
+        "page.png" => HttpResponse::Ok()
+            .content_type("image/png")
+            .body("<!DOCTYPE html><title>Not Found</title>"),
+        _ => HttpResponse::NotFound().finish(),
+    }
+}
+
+async fn serve_favicon(req: HttpRequest) -> HttpResponse {
+    let host = req
+        .headers()
+        .get(header::HOST)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    if host.starts_with("localhost") {
+        HttpResponse::Ok()
+            .content_type("application/octet-stream")
+            .body(ICON_ICO)
+    } else {
+        HttpResponse::NotFound().finish()
+    }
+}
+
+pub const ROBOTS_TXT: &str = "\
+User-agent: *
+Disallow: /
+
+# rssss may read the fixtures, but not the private ones
+User-agent: Googlebot
+User-agent: rssss
+Allow: /fixtures/
+Disallow: /fixtures/private
+Disallow: /status/
+";
+
+static ROBOTS: Mutex<usize> = Mutex::new(0);
+
+// how many times /robots.txt was fetched as 127.0.0.1
+pub fn robots_fetches() -> usize {
+    *ROBOTS.lock().unwrap()
+}
+
+async fn serve_robots(req: HttpRequest) -> HttpResponse {
+    let host = req
+        .headers()
+        .get(header::HOST)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    if host.starts_with("localhost") {
+        return HttpResponse::NotFound().finish();
+    }
+    *ROBOTS.lock().unwrap() += 1;
+    HttpResponse::Ok()
+        .content_type("text/plain")
+        .body(ROBOTS_TXT)
+}
+
+static DRIPS: Mutex<BTreeMap<String, (usize, usize)>> = Mutex::new(BTreeMap::new());
+
+// how many `/drip/{id}/...` responses were started and sent to the end
+pub fn drip_counts(id: &str) -> (usize, usize) {
+    DRIPS.lock().unwrap().get(id).copied().unwrap_or_default()
+}
+
+async fn serve_drip(path: web::Path<(String, String)>) -> HttpResponse {
+    let (id, name) = path.into_inner();
+    DRIPS.lock().unwrap().entry(id.clone()).or_default().0 += 1;
+    let body = fixture(&name).into_bytes();
+    let size = body.len().div_ceil(10);
+    let chunks = body
+        .chunks(size)
+        .map(Bytes::copy_from_slice)
+        .collect::<Vec<Bytes>>();
+    let count = chunks.len();
+    let stream = stream::iter(chunks.into_iter().enumerate()).then(move |(i, chunk)| {
+        let id = id.clone();
+        async move {
+            if i > 0 {
+                actix_rt::time::sleep(Duration::from_millis(100)).await;
+            }
+            if i + 1 == count {
+                DRIPS.lock().unwrap().entry(id).or_default().1 += 1;
+            }
+            Ok::<Bytes, actix_web::Error>(chunk)
+        }
+    });
+    HttpResponse::Ok()
+        .content_type("application/xml; charset=utf-8")
+        .streaming(stream)
+}
+
+async fn serve_stall(path: web::Path<(usize, String)>) -> HttpResponse {
+    let (bytes, name) = path.into_inner();
+    let mut body = fixture(&name).into_bytes();
+    body.truncate(bytes);
+    let stream = stream::once(async move { Ok::<Bytes, actix_web::Error>(Bytes::from(body)) })
+        .chain(stream::pending());
+    HttpResponse::Ok()
+        .content_type("application/xml; charset=utf-8")
+        .streaming(stream)
+}
+
+async fn serve_trickle(path: web::Path<(u64, String)>) -> HttpResponse {
+    let (millis, name) = path.into_inner();
+    let body = fixture(&name).into_bytes();
+    let size = body.len().div_ceil(10);
+    let chunks = body
+        .chunks(size)
+        .map(Bytes::copy_from_slice)
+        .collect::<Vec<Bytes>>();
+    let stream = stream::iter(chunks.into_iter().enumerate()).then(move |(i, chunk)| async move {
+        if i > 0 {
+            actix_rt::time::sleep(Duration::from_millis(millis)).await;
+        }
+        Ok::<Bytes, actix_web::Error>(chunk)
+    });
+    HttpResponse::Ok()
+        .content_type("application/xml; charset=utf-8")
+        .streaming(stream)
+}
+
+async fn serve_raw_redirect(kind: web::Path<String>) -> HttpResponse {
+    let location = match kind.as_str() {
+        "japanese" => "/echo/日本語/フィード.xml",
+        "space" => "/echo/my feed.xml",
+        _ => return HttpResponse::NotFound().finish(),
+    };
+    HttpResponse::Found()
+        .insert_header((
+            header::LOCATION,
+            header::HeaderValue::from_bytes(location.as_bytes()).unwrap(),
+        ))
+        .finish()
+}
+
+async fn serve_echo() -> HttpResponse {
+    xml(fixture("rss2_wordpress.xml"))
+}
+
+async fn serve_stubs(req: HttpRequest, count: web::Path<usize>) -> HttpResponse {
+    let host = req
+        .headers()
+        .get(header::HOST)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    let items = (0..count.into_inner())
+        .map(|i| {
+            format!(
+                "<item><title>{}</title><link>http://{}/site/article.html?item={}</link><description>Read more</description></item>",
+                i, host, i
+            )
+        })
+        .collect::<String>();
+    xml(format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?><rss version="2.0"><channel><title>Stubs</title>{}</channel></rss>"#,
+        items
+    ))
+}
+
+static GROWING: Mutex<BTreeMap<String, usize>> = Mutex::new(BTreeMap::new());
+
+// the newest first, "new 3", "new 2" and "new 1" on the third request
+async fn serve_growing(path: web::Path<(String, String)>) -> HttpResponse {
+    let (id, name) = path.into_inner();
+    let count = {
+        let mut growing = GROWING.lock().unwrap();
+        let count = growing.entry(id).or_default();
+        *count += 1;
+        *count
+    };
+    let items = (1..=count)
+        .rev()
+        .map(|n| {
+            format!(
+                "<item><title>new {}</title><link>https://example.com/new/{}</link></item>",
+                n, n
+            )
+        })
+        .collect::<String>();
+    xml(fixture(&name).replacen("<item>", &format!("{}<item>", items), 1))
+}
+
+static CONNECTIONS: Mutex<BTreeMap<String, Vec<u16>>> = Mutex::new(BTreeMap::new());
+
+// the client port of every `/conn/{id}/...` request, a reused connection repeats its port
+pub fn connection_ports(id: &str) -> Vec<u16> {
+    CONNECTIONS
+        .lock()
+        .unwrap()
+        .get(id)
+        .cloned()
+        .unwrap_or_default()
+}
+
+async fn serve_conn(req: HttpRequest, path: web::Path<(String, String)>) -> HttpResponse {
+    let (id, name) = path.into_inner();
+    let port = req.peer_addr().map(|a| a.port()).unwrap_or_default();
+    CONNECTIONS
+        .lock()
+        .unwrap()
+        .entry(id)
+        .or_default()
+        .push(port);
+    xml(fixture(&name))
+}
+
+static FLAKY: Mutex<BTreeMap<String, usize>> = Mutex::new(BTreeMap::new());
+
+// how many times `/flaky/{id}/...` was fetched
+pub fn flaky_fetches(id: &str) -> usize {
+    FLAKY.lock().unwrap().get(id).copied().unwrap_or_default()
+}
+
+// something that isn't XML for the first `failures` requests, the fixture after that
+async fn serve_flaky(path: web::Path<(String, usize, String)>) -> HttpResponse {
+    let (id, failures, name) = path.into_inner();
+    let count = {
+        let mut flaky = FLAKY.lock().unwrap();
+        let count = flaky.entry(id).or_default();
+        *count += 1;
+        *count
+    };
+    if count <= failures {
+        xml("<rss version=\"2.0\"><channel>".to_string())
+    } else {
+        xml(fixture(&name))
+    }
+}
+
+// traceparent and tracestate
+type TraceHeaders = (Option<String>, Option<String>);
+
+static TRACED: Mutex<BTreeMap<String, Vec<TraceHeaders>>> = Mutex::new(BTreeMap::new());
+
+// the trace headers of every `/traced/{id}/...` request
+pub fn trace_headers(id: &str) -> Vec<TraceHeaders> {
+    TRACED.lock().unwrap().get(id).cloned().unwrap_or_default()
+}
+
+async fn serve_traced(req: HttpRequest, path: web::Path<(String, String)>) -> HttpResponse {
+    let (id, name) = path.into_inner();
+    let header = |name: &str| {
+        req.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    };
+    TRACED
+        .lock()
+        .unwrap()
+        .entry(id)
+        .or_default()
+        .push((header("traceparent"), header("tracestate")));
+    xml(fixture(&name))
+}
+
+// If-None-Match and If-Modified-Since
+type Validators = (Option<String>, Option<String>);
+
+static CONDITIONAL: Mutex<BTreeMap<String, Vec<Validators>>> = Mutex::new(BTreeMap::new());
+
+// the validators of every `/conditional/{id}/...` request
+pub fn conditional_requests(id: &str) -> Vec<Validators> {
+    CONDITIONAL
+        .lock()
+        .unwrap()
+        .get(id)
+        .cloned()
+        .unwrap_or_default()
+}
+
+async fn serve_conditional(req: HttpRequest, path: web::Path<(String, String)>) -> HttpResponse {
+    let (id, name) = path.into_inner();
+    let header = |name| {
+        req.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    };
+    CONDITIONAL.lock().unwrap().entry(id).or_default().push((
+        header(header::IF_NONE_MATCH),
+        header(header::IF_MODIFIED_SINCE),
+    ));
+    serve_headers(req, web::Path::from(name)).await
+}
+
+async fn serve_moved(req: HttpRequest, path: web::Path<(String, String, String)>) -> HttpResponse {
+    let (host, id, name) = path.into_inner();
+    let port = req.app_config().local_addr().port();
+    HttpResponse::Found()
+        .insert_header((
+            header::LOCATION,
+            format!("http://{}:{}/conditional/{}/{}", host, port, id, name),
+        ))
+        .finish()
+}
+
+// by id, the cookies set and the Cookie header of each request to the gate
+type CookieLog = (usize, Vec<Option<String>>);
+
+static COOKIES: Mutex<BTreeMap<String, CookieLog>> = Mutex::new(BTreeMap::new());
+
+pub fn cookie_log(id: &str) -> CookieLog {
+    COOKIES.lock().unwrap().get(id).cloned().unwrap_or_default()
+}
+
+async fn serve_cookie_gate(req: HttpRequest, path: web::Path<(String, String)>) -> HttpResponse {
+    let (id, name) = path.into_inner();
+    let cookie = req
+        .headers()
+        .get(header::COOKIE)
+        .map(|v| v.to_str().unwrap().to_string());
+    COOKIES
+        .lock()
+        .unwrap()
+        .entry(id.clone())
+        .or_default()
+        .1
+        .push(cookie.clone());
+    let gate = format!("gate={}", id);
+    if cookie.is_some_and(|c| c.split("; ").any(|c| c == gate)) {
+        return xml(fixture(&name));
+    }
+    let host = req
+        .connection_info()
+        .host()
+        .split(':')
+        .next()
+        .unwrap()
+        .to_string();
+    HttpResponse::Found()
+        .insert_header((
+            header::LOCATION,
+            format!("/cookie-set/{}/{}/{}", host, id, name),
+        ))
+        .finish()
+}
+
+async fn serve_cookie_set(
+    req: HttpRequest,
+    path: web::Path<(String, String, String)>,
+) -> HttpResponse {
+    let (host, id, name) = path.into_inner();
+    COOKIES.lock().unwrap().entry(id.clone()).or_default().0 += 1;
+    let port = req.app_config().local_addr().port();
+    HttpResponse::Found()
+        .append_header((
+            header::SET_COOKIE,
+            format!("gate={}; Path=/cookie-gate; HttpOnly", id),
+        ))
+        .append_header((header::SET_COOKIE, "stray=1; Path=/elsewhere"))
+        .insert_header((
+            header::LOCATION,
+            format!("http://{}:{}/cookie-gate/{}/{}", host, port, id, name),
+        ))
+        .finish()
+}
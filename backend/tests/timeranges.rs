@@ -0,0 +1,174 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use chrono::{DateTime, NaiveDate, Utc};
+use rssss::config::Config;
+use rssss::dates::TzOffset;
+use rssss::timeranges::{Range, TimeRange};
+use serde_json::Value;
+use support::{app, config, feed_uri, get, TestClock};
+
+const FIXTURE: &str = "/fixtures/rss2_day_boundaries.xml";
+
+fn tz(offset: &str) -> TzOffset {
+    offset.parse().unwrap()
+}
+
+fn at(time: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(time).unwrap().to_utc()
+}
+
+// 17:30 in India, 08:30 in Newfoundland
+fn fixed() -> Config {
+    let mut config = config();
+    config.clock = TestClock::at("2024-05-02T12:00:00Z");
+    config
+}
+
+fn titles(items: &Value) -> Vec<&str> {
+    items
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["title"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn reads_the_ranges() {
+    assert_eq!("today".parse(), Ok(Range::Today));
+    assert_eq!("yesterday".parse(), Ok(Range::Yesterday));
+    assert_eq!(
+        "tomorrow".parse::<Range>(),
+        Err("unsupported range: tomorrow".to_string())
+    );
+}
+
+#[test]
+fn starts_the_day_at_local_midnight() {
+    let may_2 = NaiveDate::from_ymd_opt(2024, 5, 2).unwrap();
+    for (offset, since, until) in [
+        ("Z", "2024-05-02T00:00:00Z", "2024-05-03T00:00:00Z"),
+        ("+09:00", "2024-05-01T15:00:00Z", "2024-05-02T15:00:00Z"),
+        ("+05:30", "2024-05-01T18:30:00Z", "2024-05-02T18:30:00Z"),
+        ("-03:30", "2024-05-02T03:30:00Z", "2024-05-03T03:30:00Z"),
+        ("+14:00", "2024-05-01T10:00:00Z", "2024-05-02T10:00:00Z"),
+    ] {
+        assert_eq!(
+            TimeRange::day(may_2, tz(offset)),
+            TimeRange {
+                since: at(since),
+                until: at(until),
+            },
+            "{}",
+            offset
+        );
+    }
+}
+
+#[test]
+fn keeps_23_59_and_leaves_the_next_midnight_to_the_next_day() {
+    let may_2 = NaiveDate::from_ymd_opt(2024, 5, 2).unwrap();
+    for offset in ["+00:00", "+09:00", "+05:30", "-03:30", "-10:00"] {
+        let range = TimeRange::day(may_2, tz(offset));
+        let local = |time: &str| at(&format!("{}{}", time, offset));
+        assert!(!range.contains(local("2024-05-01T23:59:00")), "{}", offset);
+        assert!(range.contains(local("2024-05-02T00:00:00")), "{}", offset);
+        assert!(range.contains(local("2024-05-02T23:59:00")), "{}", offset);
+        assert!(range.contains(local("2024-05-02T23:59:59")), "{}", offset);
+        assert!(!range.contains(local("2024-05-03T00:00:00")), "{}", offset);
+    }
+}
+
+#[test]
+fn counts_today_from_the_local_date_of_now() {
+    // still May 1 in Newfoundland, already May 2 in India
+    let now = at("2024-05-02T02:00:00Z");
+    let newfoundland = TimeRange::of(Range::Today, now, tz("-03:30"));
+    assert_eq!(newfoundland.since, at("2024-05-01T00:00:00-03:30"));
+    let india = TimeRange::of(Range::Today, now, tz("+05:30"));
+    assert_eq!(india.since, at("2024-05-02T00:00:00+05:30"));
+    let yesterday = TimeRange::of(Range::Yesterday, now, tz("+05:30"));
+    assert_eq!(yesterday.since, at("2024-05-01T00:00:00+05:30"));
+    assert_eq!(yesterday.until, india.since);
+}
+
+#[test]
+fn takes_midnight_itself_as_the_new_day() {
+    let midnight = at("2024-05-02T00:00:00+05:30");
+    let today = TimeRange::of(Range::Today, midnight, tz("+05:30"));
+    assert_eq!(today.since, midnight);
+    let just_before = TimeRange::of(Range::Today, at("2024-05-01T23:59:59+05:30"), tz("+05:30"));
+    assert_eq!(just_before.until, midnight);
+}
+
+#[actix_rt::test]
+async fn filters_the_items_of_today() {
+    let app = app(fixed()).await;
+    let uri = feed_uri("/v2/feed", FIXTURE, "&range=today&tz=%2B05:30");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(
+        titles(&res.json["items"]),
+        [
+            "may 2 00:00 ist",
+            "may 2 00:00 ist in gmt",
+            "may 2 23:59 ist"
+        ]
+    );
+    assert_eq!(res.headers.get("X-Total-Count").unwrap(), "3");
+}
+
+#[actix_rt::test]
+async fn filters_the_items_of_yesterday() {
+    let app = app(fixed()).await;
+    let uri = feed_uri("/v2/feed", FIXTURE, "&range=yesterday&tz=%2B05:30");
+    let res = get(&app, &uri).await;
+    assert_eq!(
+        titles(&res.json["items"]),
+        ["may 1 00:00 ist", "may 1 23:59 ist"]
+    );
+}
+
+#[actix_rt::test]
+async fn resolves_the_day_in_the_zone_asked_for() {
+    let app = app(fixed()).await;
+    let uri = feed_uri("/v2/feed", FIXTURE, "&range=yesterday&tz=-03:30");
+    let res = get(&app, &uri).await;
+    assert_eq!(
+        titles(&res.json["items"]),
+        [
+            "may 1 23:59 ist",
+            "may 2 00:00 ist",
+            "may 2 00:00 ist in gmt"
+        ]
+    );
+    // UTC without a tz
+    let uri = feed_uri("/v2/feed", FIXTURE, "&range=today");
+    let res = get(&app, &uri).await;
+    assert_eq!(
+        titles(&res.json["items"]),
+        ["may 2 23:59 ist", "may 3 00:00 ist"]
+    );
+}
+
+#[actix_rt::test]
+async fn names_the_valid_offsets() {
+    let app = app(fixed()).await;
+    let uri = feed_uri("/feed", FIXTURE, "&range=today&tz=Asia/Kolkata");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        res.json["messages"][0],
+        "invalid parameter tz: unexpected value: Asia/Kolkata, \
+         one of Z, UTC or an offset like +09:00, +0900 or -03:30"
+    );
+
+    let uri = feed_uri("/feed", FIXTURE, "&range=tomorrow");
+    let res = get(&app, &uri).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        res.json["messages"][0],
+        "invalid parameter range: unexpected value: tomorrow"
+    );
+}
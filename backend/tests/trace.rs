@@ -0,0 +1,197 @@
+mod support;
+
+use actix_web::http::header::HeaderMap;
+use actix_web::http::StatusCode;
+use rssss::trace::{parse_traceparent, TraceContext};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use support::{app, config, feed_uri, get, get_with_headers, trace_headers};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::subscriber::Interest;
+use tracing::{Event, Metadata, Subscriber};
+
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+const PARENT_ID: &str = "00f067aa0ba902b7";
+
+fn traceparent() -> String {
+    format!("00-{}-{}-01", TRACE_ID, PARENT_ID)
+}
+
+#[test]
+fn parses_traceparents_by_the_spec() {
+    assert_eq!(
+        parse_traceparent(&traceparent()),
+        Some((TRACE_ID.to_string(), PARENT_ID.to_string(), 1))
+    );
+    // later versions may add fields
+    assert!(parse_traceparent(&format!("01-{}-{}-00-extra", TRACE_ID, PARENT_ID)).is_some());
+    for invalid in [
+        format!("00-{}-{}-01-extra", TRACE_ID, PARENT_ID),
+        format!("ff-{}-{}-01", TRACE_ID, PARENT_ID),
+        format!("00-{}-{}-01", "0".repeat(32), PARENT_ID),
+        format!("00-{}-{}-01", TRACE_ID, "0".repeat(16)),
+        format!("00-{}-{}-01", TRACE_ID.to_uppercase(), PARENT_ID),
+        format!("00-{}-{}-1", TRACE_ID, PARENT_ID),
+        format!("00-{}-01", TRACE_ID),
+        "".to_string(),
+    ] {
+        assert_eq!(parse_traceparent(&invalid), None, "{}", invalid);
+    }
+}
+
+#[test]
+fn continues_the_trace_of_the_request_or_starts_one() {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "traceparent".parse().unwrap(),
+        traceparent().parse().unwrap(),
+    );
+    headers.append("tracestate".parse().unwrap(), "a=1".parse().unwrap());
+    headers.append("tracestate".parse().unwrap(), "b=2".parse().unwrap());
+    let trace = TraceContext::from_headers(&headers);
+    assert_eq!(trace.trace_id(), TRACE_ID);
+    assert_eq!(trace.parent_id(), Some(PARENT_ID));
+    assert_ne!(trace.span_id(), PARENT_ID);
+    let child = trace.child();
+    assert_eq!(child.trace_id(), TRACE_ID);
+    assert_eq!(child.parent_id(), Some(trace.span_id()));
+    let sent = child
+        .headers()
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect::<Vec<(String, String)>>();
+    assert_eq!(
+        sent,
+        [
+            (
+                "traceparent".to_string(),
+                format!("00-{}-{}-01", TRACE_ID, child.span_id())
+            ),
+            ("tracestate".to_string(), "a=1,b=2".to_string()),
+        ]
+    );
+
+    let mut broken = HeaderMap::new();
+    broken.insert("traceparent".parse().unwrap(), "00-nope".parse().unwrap());
+    broken.insert("tracestate".parse().unwrap(), "a=1".parse().unwrap());
+    let root = TraceContext::from_headers(&broken);
+    assert_ne!(root.trace_id(), TRACE_ID);
+    assert_eq!(root.parent_id(), None);
+    assert_eq!(root.headers().len(), 1);
+    assert!(parse_traceparent(&root.traceparent()).is_some());
+}
+
+#[actix_rt::test]
+async fn propagates_the_trace_to_the_upstream() {
+    let app = app(config()).await;
+    let uri = feed_uri("/feed", "/traced/continued/youtube.xml", "");
+    let res = get_with_headers(
+        &app,
+        &uri,
+        &[("traceparent", &traceparent()), ("tracestate", "vendor=x")],
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK);
+    let sent = trace_headers("continued");
+    assert_eq!(sent.len(), 1);
+    let (traceparent, tracestate) = &sent[0];
+    let (trace_id, parent_id, flags) = parse_traceparent(traceparent.as_deref().unwrap()).unwrap();
+    assert_eq!(trace_id, TRACE_ID);
+    // the fetch is a span of ours, not the client's
+    assert_ne!(parent_id, PARENT_ID);
+    assert_eq!(flags, 1);
+    assert_eq!(tracestate.as_deref(), Some("vendor=x"));
+}
+
+#[actix_rt::test]
+async fn starts_a_trace_without_one_to_continue() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed", "/traced/root/youtube.xml", "")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    let sent = trace_headers("root");
+    let (traceparent, tracestate) = &sent[0];
+    let (trace_id, _, _) = parse_traceparent(traceparent.as_deref().unwrap()).unwrap();
+    assert_ne!(trace_id, TRACE_ID);
+    assert_eq!(*tracestate, None);
+}
+
+type Spans = Arc<Mutex<Vec<(&'static str, BTreeMap<String, String>)>>>;
+
+// keeps the name and fields of the spans of rssss
+struct Recorder {
+    spans: Spans,
+    next_id: AtomicU64,
+}
+
+struct Fields<'a>(&'a mut BTreeMap<String, String>);
+
+impl Visit for Fields<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+impl Subscriber for Recorder {
+    fn register_callsite(&self, _: &'static Metadata<'static>) -> Interest {
+        Interest::sometimes()
+    }
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.is_span() && metadata.target().starts_with("rssss")
+    }
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut fields = BTreeMap::new();
+        span.record(&mut Fields(&mut fields));
+        self.spans
+            .lock()
+            .unwrap()
+            .push((span.metadata().name(), fields));
+        Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed))
+    }
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+    fn event(&self, _: &Event<'_>) {}
+    fn enter(&self, _: &Id) {}
+    fn exit(&self, _: &Id) {}
+}
+
+#[actix_rt::test]
+async fn spans_the_request_the_fetches_and_the_parse() {
+    let spans = Spans::default();
+    let _guard = tracing::subscriber::set_default(Recorder {
+        spans: spans.clone(),
+        next_id: AtomicU64::new(1),
+    });
+    let app = app(config()).await;
+    let uri = feed_uri("/feed", "/redirect/1/youtube.xml", "");
+    let res = get_with_headers(&app, &uri, &[("traceparent", &traceparent())]).await;
+    assert_eq!(res.status, StatusCode::OK);
+
+    let spans = spans.lock().unwrap();
+    let names = spans.iter().map(|(name, _)| *name).collect::<Vec<&str>>();
+    // /redirect/1 goes by /redirect/0 to the fixture
+    assert_eq!(
+        names,
+        [
+            "feed",
+            "upstream_fetch",
+            "upstream_fetch",
+            "upstream_fetch",
+            "parse"
+        ]
+    );
+    let (_, feed) = &spans[0];
+    assert_eq!(feed["parent_id"], PARENT_ID);
+    for (_, fields) in spans.iter() {
+        assert_eq!(fields["trace_id"], TRACE_ID);
+    }
+    for (_, fields) in &spans[1..] {
+        assert_eq!(fields["parent_id"], feed["span_id"]);
+    }
+    assert!(spans[1].1["url"].ends_with("/redirect/1/youtube.xml"));
+    assert!(spans[2].1["url"].ends_with("/redirect/0/youtube.xml"));
+    assert!(spans[3].1["url"].ends_with("/fixtures/youtube.xml"));
+}
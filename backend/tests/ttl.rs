@@ -0,0 +1,113 @@
+mod support;
+
+use chrono::{DateTime, TimeZone, Utc};
+use rssss::cache::{max_age, CacheConfig};
+use rssss::rss::Channel;
+use std::time::Duration;
+use support::config;
+
+fn now() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap()
+}
+
+// a default of 5 minutes, kept between 1 minute and a day
+fn cache() -> CacheConfig {
+    config().cache
+}
+
+fn with_ttl(minutes: u32) -> Channel {
+    Channel {
+        ttl: Some(minutes),
+        ..Channel::default()
+    }
+}
+
+fn secs(secs: u64) -> Duration {
+    Duration::from_secs(secs)
+}
+
+#[test]
+fn reads_max_age_from_cache_control() {
+    assert_eq!(max_age("max-age=600"), Some(600));
+    assert_eq!(
+        max_age("public, MAX-AGE = \"90\", must-revalidate"),
+        Some(90)
+    );
+    // s-maxage is ours as a shared cache
+    assert_eq!(max_age("max-age=600, s-maxage=30"), Some(30));
+    assert_eq!(max_age("s-maxage=soon, max-age=600"), Some(600));
+    assert_eq!(max_age("no-cache"), None);
+    assert_eq!(max_age("max-age=-1"), None);
+}
+
+#[test]
+fn takes_cache_control_first() {
+    let cache = cache();
+    assert_eq!(
+        cache.ttl(Some("max-age=600"), &with_ttl(30), now()),
+        secs(600)
+    );
+    // a header without a max-age gives way to the channel
+    assert_eq!(
+        cache.ttl(Some("no-cache"), &with_ttl(30), now()),
+        secs(1_800)
+    );
+}
+
+#[test]
+fn takes_the_channel_ttl_in_minutes_without_cache_control() {
+    assert_eq!(cache().ttl(None, &with_ttl(30), now()), secs(1_800));
+}
+
+#[test]
+fn falls_back_to_the_default() {
+    assert_eq!(cache().ttl(None, &Channel::default(), now()), secs(300));
+    assert_eq!(
+        cache().ttl(Some("private"), &Channel::default(), now()),
+        secs(300)
+    );
+}
+
+#[test]
+fn clamps_to_the_min_ttl() {
+    let cache = cache();
+    assert_eq!(
+        cache.ttl(Some("max-age=0"), &Channel::default(), now()),
+        secs(60)
+    );
+    assert_eq!(cache.ttl(None, &with_ttl(0), now()), secs(60));
+    let cache = CacheConfig {
+        default_ttl: secs(10),
+        ..cache
+    };
+    assert_eq!(cache.ttl(None, &Channel::default(), now()), secs(60));
+}
+
+#[test]
+fn clamps_to_the_max_ttl() {
+    let cache = cache();
+    assert_eq!(
+        cache.ttl(Some("max-age=31536000"), &Channel::default(), now()),
+        secs(86_400)
+    );
+    assert_eq!(cache.ttl(None, &with_ttl(10_080), now()), secs(86_400));
+    let cache = CacheConfig {
+        default_ttl: secs(604_800),
+        ..cache
+    };
+    assert_eq!(cache.ttl(None, &Channel::default(), now()), secs(86_400));
+}
+
+#[test]
+fn keeps_the_min_ttl_when_it_is_over_the_max() {
+    let cache = CacheConfig {
+        min_ttl: secs(600),
+        max_ttl: secs(120),
+        ..cache()
+    };
+    assert_eq!(
+        cache.ttl(Some("max-age=30"), &Channel::default(), now()),
+        secs(600)
+    );
+    assert_eq!(cache.ttl(None, &with_ttl(60), now()), secs(600));
+}
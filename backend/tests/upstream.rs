@@ -0,0 +1,185 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use rssss::redirects::RedirectPolicy;
+use rssss::upstream::{candidates, IpPreference, UpstreamConfig};
+use std::net::SocketAddr;
+use std::time::Duration;
+use support::{app, config, connection_ports, feed_uri, get, mock_localhost_url, url_uri};
+
+#[test]
+fn accepts_keep_alive_and_connection_limits_in_range() {
+    assert_eq!(
+        UpstreamConfig::new(30, 8),
+        Ok(UpstreamConfig {
+            keep_alive: Duration::from_secs(30),
+            max_connections: 8,
+            ip_preference: IpPreference::Auto,
+            redirects: RedirectPolicy::default(),
+            stall: Duration::from_secs(5),
+        })
+    );
+    assert_eq!(UpstreamConfig::new(1, 1).unwrap().max_connections, 1);
+    assert_eq!(
+        UpstreamConfig::new(3_600, 1).unwrap().keep_alive,
+        Duration::from_secs(3_600)
+    );
+}
+
+#[test]
+fn rejects_a_keep_alive_out_of_range() {
+    assert!(UpstreamConfig::new(0, 8).is_err());
+    assert!(UpstreamConfig::new(3_601, 8).is_err());
+}
+
+#[test]
+fn rejects_no_connections() {
+    assert!(UpstreamConfig::new(30, 0).is_err());
+}
+
+#[actix_rt::test]
+async fn reuses_the_connection_for_later_fetches() {
+    let app = app(config()).await;
+    for name in ["rss2_wordpress.xml", "rss1_hatena.xml", "atom_planet.xml"] {
+        let res = get(
+            &app,
+            &feed_uri("/feed", &format!("/conn/reuse/{}", name), ""),
+        )
+        .await;
+        assert_eq!(res.status, StatusCode::OK);
+    }
+    let ports = connection_ports("reuse");
+    assert_eq!(ports.len(), 3);
+    assert!(ports.iter().all(|p| *p == ports[0]), "{:?}", ports);
+}
+
+#[actix_rt::test]
+async fn reports_the_protocol_the_feed_was_fetched_over() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/conn/protocol/rss2_wordpress.xml", ""),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK);
+    // the mock server speaks plain HTTP, so ALPN never offers h2
+    assert_eq!(res.json["upstream_protocol"], "HTTP/1.1");
+}
+
+#[test]
+fn parses_the_ip_preference() {
+    assert_eq!("auto".parse(), Ok(IpPreference::Auto));
+    assert_eq!("ipv4".parse(), Ok(IpPreference::Ipv4));
+    assert_eq!("ipv6".parse(), Ok(IpPreference::Ipv6));
+    assert!("IPv6".parse::<IpPreference>().is_err());
+    assert_eq!(IpPreference::default(), IpPreference::Auto);
+}
+
+fn addrs(addrs: &[&str]) -> Vec<SocketAddr> {
+    addrs.iter().map(|a| a.parse().unwrap()).collect()
+}
+
+const RESOLVED: [&str; 5] = [
+    "93.184.216.34:443",
+    "93.184.216.35:443",
+    "[2606:2800:220:1::1]:443",
+    "93.184.216.36:443",
+    "[2606:2800:220:1::2]:443",
+];
+
+#[test]
+fn alternates_the_families_ipv6_first() {
+    assert_eq!(
+        candidates("example.com", addrs(&RESOLVED), IpPreference::Auto, true).unwrap(),
+        addrs(&[
+            "[2606:2800:220:1::1]:443",
+            "93.184.216.34:443",
+            "[2606:2800:220:1::2]:443",
+            "93.184.216.35:443",
+            "93.184.216.36:443",
+        ])
+    );
+}
+
+#[test]
+fn dials_the_preferred_family_first() {
+    assert_eq!(
+        candidates("example.com", addrs(&RESOLVED), IpPreference::Ipv4, true).unwrap(),
+        addrs(&[
+            "93.184.216.34:443",
+            "93.184.216.35:443",
+            "93.184.216.36:443",
+            "[2606:2800:220:1::1]:443",
+            "[2606:2800:220:1::2]:443",
+        ])
+    );
+    // the other family stays a fallback, IPv6-only hosts still work with ipv4
+    assert_eq!(
+        candidates(
+            "example.com",
+            addrs(&["[2606:2800:220:1::1]:443"]),
+            IpPreference::Ipv4,
+            true
+        )
+        .unwrap(),
+        addrs(&["[2606:2800:220:1::1]:443"])
+    );
+    assert_eq!(
+        candidates("example.com", addrs(&RESOLVED), IpPreference::Ipv6, true).unwrap()[..2],
+        addrs(&["[2606:2800:220:1::1]:443", "[2606:2800:220:1::2]:443"])
+    );
+}
+
+#[test]
+fn refuses_a_private_address_among_any_candidates() {
+    for private in ["10.0.0.1:443", "[fd00::1]:443", "[::ffff:127.0.0.1]:443"] {
+        let mut resolved = addrs(&RESOLVED);
+        resolved.push(private.parse().unwrap());
+        for preference in [IpPreference::Auto, IpPreference::Ipv4, IpPreference::Ipv6] {
+            let e = candidates("rebind.example", resolved.clone(), preference, true).unwrap_err();
+            assert!(
+                e.to_string()
+                    .starts_with("rebind.example resolves to a private address"),
+                "{}",
+                e
+            );
+        }
+        // allowed when private hosts are
+        assert!(candidates("rebind.example", resolved, IpPreference::Auto, false).is_ok());
+    }
+}
+
+#[test]
+fn fails_without_addresses() {
+    assert!(candidates("example.com", Vec::new(), IpPreference::Auto, false).is_err());
+}
+
+#[actix_rt::test]
+async fn reports_the_address_the_feed_was_fetched_from() {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/v2/feed", "/conn/address/rss2_wordpress.xml", ""),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.json["upstream_ip"], "127.0.0.1");
+    assert_eq!(res.json["upstream_ip_family"], "ipv4");
+}
+
+#[actix_rt::test]
+async fn falls_back_to_the_family_that_answers() {
+    // localhost may resolve to ::1 first, where the mock server doesn't listen
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &url_uri(
+            "/v2/feed",
+            &mock_localhost_url("/conn/fallback/rss2_wordpress.xml"),
+            "",
+        ),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.json["upstream_ip"], "127.0.0.1");
+}
@@ -0,0 +1,189 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use serde_json::Value;
+use support::{app, config, feed_uri, get, url_uri};
+
+async fn validate(fixture: &str) -> Value {
+    let app = app(config()).await;
+    let res = get(
+        &app,
+        &feed_uri("/feed/validate", &format!("/fixtures/{}", fixture), ""),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+    res.json
+}
+
+// (item, code) of each finding, in report order
+fn findings(report: &Value) -> Vec<(Option<u64>, String)> {
+    report["findings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| (f["item"].as_u64(), f["code"].as_str().unwrap().to_string()))
+        .collect()
+}
+
+fn finding<'a>(report: &'a Value, code: &str) -> &'a Value {
+    report["findings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|f| f["code"] == code)
+        .unwrap_or_else(|| panic!("no {} in {}", code, report))
+}
+
+#[actix_rt::test]
+async fn reports_what_readers_lose() {
+    let report = validate("rss2_lint.xml").await;
+    assert_eq!(report["valid"], true);
+    assert_eq!(report["format"], "rss");
+    assert_eq!(report["version"], "2.0");
+    assert_eq!(report["encoding"], "UTF-8");
+    assert_eq!(report["item_count"], 4);
+    assert_eq!(report["items_without_date"], 1);
+    assert_eq!(report["items_without_link"], 1);
+    assert_eq!(report["items_without_guid"], 1);
+    assert_eq!(report["unparsable_dates"], 1);
+    assert_eq!(report["unescaped_html"], 1);
+    assert_eq!(report["oversized_fields"], 1);
+    assert_eq!(report["duplicate_guids"], 1);
+    assert_eq!(
+        findings(&report),
+        [
+            (Some(2), "missing_date".to_string()),
+            (Some(2), "missing_guid".to_string()),
+            (Some(3), "unparsable_date".to_string()),
+            (Some(3), "duplicate_guid".to_string()),
+            (Some(3), "unescaped_html".to_string()),
+            (Some(4), "missing_link".to_string()),
+            (Some(4), "field_too_long".to_string()),
+        ]
+    );
+}
+
+#[actix_rt::test]
+async fn findings_carry_a_severity_and_the_position_of_their_item() {
+    let report = validate("rss2_lint.xml").await;
+    let guid = finding(&report, "missing_guid");
+    assert_eq!(guid["severity"], "info");
+    assert_eq!(
+        (&guid["line"], &guid["column"]),
+        (&Value::from(12), &Value::from(2))
+    );
+    let duplicate = finding(&report, "duplicate_guid");
+    assert_eq!(duplicate["severity"], "warning");
+    assert_eq!(duplicate["line"], 16);
+    assert!(duplicate["message"].as_str().unwrap().contains("item 1"));
+    let html = finding(&report, "unescaped_html");
+    assert!(html["message"].as_str().unwrap().contains("<b>"));
+}
+
+#[actix_rt::test]
+async fn a_broken_document_is_an_error_at_its_position() {
+    let report = validate("rss2_truncated.xml").await;
+    assert_eq!(report["valid"], false);
+    assert_eq!(report["format"], "rss");
+    let error = &report["findings"][0];
+    assert_eq!(error["severity"], "error");
+    assert_eq!(error["code"], "xml_parse_error");
+    assert_eq!(error["line"], 19);
+    assert!(error["column"].as_u64().unwrap() > 1);
+    // the other parsers' complaints about the root are left out
+    assert!(report["findings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|f| f["code"] != "invalid_root_element" && f["code"] != "invalid_root_namespace"));
+    // what was read up to there is still linted
+    assert_eq!(report["item_count"], 3);
+    assert_eq!(report["unescaped_html"], 1);
+}
+
+#[actix_rt::test]
+async fn a_clean_feed_of_each_format() {
+    let app = app(config()).await;
+    for (fixture, format, version) in [
+        ("youtube.xml", "atom", "1.0"),
+        ("rss1_hatena.xml", "rdf", "1.0"),
+        ("rss2_planet.xml", "rss", "2.0"),
+    ] {
+        let uri = feed_uri("/feed/validate", &format!("/fixtures/{}", fixture), "");
+        let report = get(&app, &uri).await.json;
+        assert_eq!(report["valid"], true, "{}", fixture);
+        assert_eq!(report["format"], format, "{}", fixture);
+        assert_eq!(report["version"], version, "{}", fixture);
+        let items = get(
+            &app,
+            &feed_uri("/feed", &format!("/fixtures/{}", fixture), ""),
+        )
+        .await;
+        assert_eq!(
+            report["item_count"].as_u64().unwrap() as usize,
+            items.json.as_array().unwrap().len(),
+            "{}",
+            fixture
+        );
+        assert!(
+            report["findings"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .all(|f| f["severity"] != "error"),
+            "{}",
+            fixture
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn names_the_generator_of_the_feed() {
+    let report = validate("atom_metadata.xml").await;
+    assert_eq!(report["generator"]["name"], "Jekyll");
+    assert_eq!(report["generator"]["version"], "4.3.3");
+
+    let report = validate("rss2_metadata.xml").await;
+    assert_eq!(report["generator"]["name"], "Blogger");
+    let warning = finding(&report, "document_warning");
+    assert!(warning["message"].as_str().unwrap().contains("webMaster"));
+
+    let report = validate("rss1_metadata.xml").await;
+    assert!(report.get("generator").is_none());
+}
+
+#[actix_rt::test]
+async fn rss1_items_are_named_by_their_about() {
+    let report = validate("rss1_hatena.xml").await;
+    assert_eq!(report["items_without_guid"], 0);
+}
+
+#[actix_rt::test]
+async fn what_isnt_a_feed_is_reported_with_the_code_of_feed() {
+    let report = validate("not_a_feed.html").await;
+    assert_eq!(report["valid"], false);
+    assert_eq!(report["findings"][0]["code"], "unsupported_format");
+    assert_eq!(report["item_count"], 0);
+
+    let report = validate("rss2_two_channels.xml").await;
+    assert_eq!(report["valid"], true);
+    assert_eq!(finding(&report, "document_warning")["severity"], "warning");
+
+    let report = validate("rss2_empty.xml").await;
+    assert_eq!(finding(&report, "no_items")["severity"], "warning");
+}
+
+#[actix_rt::test]
+async fn upstream_failures_answer_as_feed_does() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/feed/validate", "/status/404", "")).await;
+    assert_eq!(res.status, StatusCode::NOT_FOUND);
+    let res = get(&app, &feed_uri("/feed/validate", "/status/503", "")).await;
+    assert_eq!(res.status, StatusCode::SERVICE_UNAVAILABLE);
+    let res = get(
+        &app,
+        &url_uri("/feed/validate", "ftp://example.com/feed", ""),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,72 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use support::{app, conditional_requests, config, feed_uri, get_with_headers};
+
+const ETAG: &str = "\"v1\"";
+const LAST_MODIFIED: &str = "Tue, 07 May 2024 09:00:00 GMT";
+
+fn validators(etag: Option<&str>, last_modified: Option<&str>) -> (Option<String>, Option<String>) {
+    (etag.map(str::to_string), last_modified.map(str::to_string))
+}
+
+#[actix_rt::test]
+async fn passes_the_validators_on_and_answers_304_as_the_upstream() {
+    let app = app(config()).await;
+    let uri = feed_uri("/feed", "/conditional/unchanged/youtube.xml", "");
+    let res = get_with_headers(
+        &app,
+        &uri,
+        &[
+            ("If-None-Match", ETAG),
+            ("If-Modified-Since", LAST_MODIFIED),
+        ],
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::NOT_MODIFIED);
+    assert!(res.body.is_empty());
+    assert_eq!(
+        conditional_requests("unchanged"),
+        [validators(Some(ETAG), Some(LAST_MODIFIED))]
+    );
+
+    let uri = feed_uri("/v2/feed", "/conditional/since/youtube.xml", "");
+    let res = get_with_headers(&app, &uri, &[("If-Modified-Since", LAST_MODIFIED)]).await;
+    assert_eq!(res.status, StatusCode::NOT_MODIFIED);
+    assert_eq!(
+        conditional_requests("since"),
+        [validators(None, Some(LAST_MODIFIED))]
+    );
+}
+
+#[actix_rt::test]
+async fn a_changed_feed_is_served_and_cached_as_usual() {
+    let app = app(config()).await;
+    let uri = feed_uri("/feed", "/conditional/changed/youtube.xml", "");
+    let res = get_with_headers(&app, &uri, &[("If-None-Match", "\"v0\"")]).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert!(!res.json.as_array().unwrap().is_empty());
+
+    // the cached copy is fresher than anything the client has, the upstream isn't asked
+    let res = get_with_headers(&app, &uri, &[("If-None-Match", ETAG)]).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(
+        conditional_requests("changed"),
+        [validators(Some("\"v0\""), None)]
+    );
+}
+
+#[actix_rt::test]
+async fn validators_follow_redirects_within_the_origin_only() {
+    let app = app(config()).await;
+    let headers = [("If-None-Match", ETAG)];
+    let uri = feed_uri("/feed", "/moved/127.0.0.1/same/youtube.xml", "");
+    let res = get_with_headers(&app, &uri, &headers).await;
+    assert_eq!(res.status, StatusCode::NOT_MODIFIED);
+    assert_eq!(conditional_requests("same"), [validators(Some(ETAG), None)]);
+
+    let uri = feed_uri("/feed", "/moved/localhost/other/youtube.xml", "");
+    let res = get_with_headers(&app, &uri, &headers).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(conditional_requests("other"), [validators(None, None)]);
+}
@@ -0,0 +1,128 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::web::Bytes;
+use rssss::config::Config;
+use rssss::error::ApiError;
+use rssss::rss::{Feed, FeedParser, ParseOptions, Rss, XmlParser};
+use rssss::stats::Stats;
+use rssss::watchdog::{self, Section};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use support::{app, config, feed_uri, get};
+
+const PLANET: &str = "/fixtures/rss2_planet.xml";
+
+// parses as usual after holding up the worker
+#[derive(Debug)]
+struct Sluggish(Duration);
+
+impl FeedParser for Sluggish {
+    fn parse(
+        &self,
+        buf: Bytes,
+        options: ParseOptions,
+        sink: Option<&mut dyn FnMut(Rss)>,
+    ) -> Result<Feed, ApiError> {
+        thread::sleep(self.0);
+        XmlParser.parse(buf, options, sink)
+    }
+}
+
+fn sluggish_config(reject: bool) -> Config {
+    Config {
+        slow_parse_threshold: Duration::from_millis(50),
+        reject_slow_parses: reject,
+        parser: Arc::new(Sluggish(Duration::from_millis(150))),
+        ..config()
+    }
+}
+
+#[cfg(feature = "metrics")]
+#[actix_rt::test]
+async fn counts_a_slow_parse() {
+    let app = app(sluggish_config(false)).await;
+    let res = get(&app, &feed_uri("/v2/feed", PLANET, "")).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+    let stats = get(&app, "/stats").await.json;
+    assert_eq!(stats["slow_parse_total"], 1, "{}", stats);
+    assert_eq!(stats["feeds"][0]["slow_parses"], 1, "{}", stats);
+
+    // served from the cache after that, and not rejected without the flag
+    let res = get(&app, &feed_uri("/v2/feed", PLANET, "")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "hit");
+    assert_eq!(get(&app, "/stats").await.json["slow_parse_total"], 1);
+}
+
+#[cfg(feature = "metrics")]
+#[actix_rt::test]
+async fn a_fast_parse_is_not_counted() {
+    let app = app(config()).await;
+    let res = get(&app, &feed_uri("/v2/feed", PLANET, "")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    let stats = get(&app, "/stats").await.json;
+    assert_eq!(stats["slow_parse_total"], 0);
+    assert_eq!(stats["feeds"][0]["slow_parses"], 0);
+}
+
+#[actix_rt::test]
+async fn rejects_a_slow_feed_on_later_requests() {
+    let app = app(sluggish_config(true)).await;
+    let res = get(&app, &feed_uri("/v2/feed", PLANET, "")).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.json);
+
+    // whatever the options, until the negative cache lets go of it
+    for params in ["", "&strictness=lenient"] {
+        let res = get(&app, &feed_uri("/v2/feed", PLANET, params)).await;
+        assert_eq!(res.status, StatusCode::UNPROCESSABLE_ENTITY, "{}", params);
+        assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "negative");
+        assert_eq!(res.json["kind"], "feed_too_expensive");
+        assert_eq!(res.json["errors"][0]["code"], "feed_too_expensive");
+        let message = res.json["errors"][0]["message"].as_str().unwrap();
+        assert!(
+            message.starts_with("feed too expensive to parse"),
+            "{}",
+            message
+        );
+    }
+
+    // refresh parses it again, as slowly
+    let res = get(&app, &feed_uri("/v2/feed", PLANET, "&refresh=true")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    #[cfg(feature = "metrics")]
+    assert_eq!(get(&app, "/stats").await.json["slow_parse_total"], 1);
+}
+
+#[test]
+fn watches_sections_against_the_threshold() {
+    let stats = Stats::new(10);
+    let threshold = Duration::from_millis(100);
+    let (_, elapsed) = watchdog::timed(|| thread::sleep(Duration::from_millis(120)));
+    assert!(elapsed >= Duration::from_millis(120));
+    assert!(watchdog::watch(
+        &stats,
+        "a",
+        Section::Parse,
+        elapsed,
+        threshold
+    ));
+    assert!(!watchdog::watch(
+        &stats,
+        "a",
+        Section::Text,
+        threshold,
+        threshold
+    ));
+    assert!(watchdog::watch(
+        &stats,
+        "b",
+        Section::Text,
+        Duration::from_secs(1),
+        threshold
+    ));
+    let snapshot = stats.snapshot(1);
+    assert_eq!(snapshot.slow_parse_total, 2);
+    assert_eq!(snapshot.other.slow_parses, 1);
+}
@@ -0,0 +1,213 @@
+mod support;
+
+use actix_web::http::StatusCode;
+use actix_web::web::Bytes;
+use rssss::config::Config;
+use rssss::rss::{parse_rss_with, ParseOptions};
+use serde_json::{json, Value};
+use support::{app, config, get, get_with, mock_url, url_uri};
+
+const COUNT: usize = 5_000;
+
+fn titles(json: &Value) -> Vec<usize> {
+    json.as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["title"].as_str().unwrap().parse().unwrap())
+        .collect()
+}
+
+#[actix_rt::test]
+async fn windows_neither_overlap_nor_skip_items() {
+    let app = app(config()).await;
+    let url = mock_url(&format!("/many/{}", COUNT));
+    let mut seen = Vec::new();
+    let mut continue_from = String::new();
+    let mut windows = 0;
+    loop {
+        // a window is paged through like a feed of its own
+        let mut next = None;
+        for page in 1..=5 {
+            let params = format!("{}&page={}&per_page=200", continue_from, page);
+            let res = get(&app, &url_uri("/v2/feed", &url, &params)).await;
+            assert_eq!(res.status, StatusCode::OK);
+            // the first window fetches, the others are served from the same parse
+            if page == 1 {
+                let cache = res.headers.get("X-Rssss-Cache").unwrap();
+                assert_eq!(cache, if windows == 0 { "miss" } else { "hit" });
+            }
+            assert_eq!(res.headers.get("X-Total-Pages").unwrap(), "5");
+            seen.extend(titles(&res.json["items"]));
+            next = res.json["continue_from"].as_u64();
+            assert_eq!(res.json["has_more"], next.is_some());
+            assert_eq!(
+                res.headers
+                    .get("X-Rssss-Continue-From")
+                    .map(|v| v.to_str().unwrap().to_string()),
+                next.map(|n| n.to_string())
+            );
+        }
+        windows += 1;
+        match next {
+            Some(next) => continue_from = format!("&continue_from={}", next),
+            None => break,
+        }
+    }
+    assert_eq!(windows, 5);
+    assert_eq!(seen, (0..COUNT).collect::<Vec<usize>>());
+
+    // past the end is empty rather than an error
+    let res = get(&app, &url_uri("/v2/feed", &url, "&continue_from=9000")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert!(res.json["items"].as_array().unwrap().is_empty());
+    assert_eq!(res.json["has_more"], false);
+}
+
+#[actix_rt::test]
+async fn pages_are_of_the_window() {
+    let app = app(config()).await;
+    let url = mock_url(&format!("/many/{}", COUNT));
+    let res = get(
+        &app,
+        &url_uri("/feed", &url, "&continue_from=1000&page=2&per_page=10"),
+    )
+    .await;
+    assert_eq!(titles(&res.json), (1_010..1_020).collect::<Vec<usize>>());
+    assert_eq!(res.headers.get("X-Total-Count").unwrap(), "1000");
+    assert_eq!(res.headers.get("X-Rssss-Continue-From").unwrap(), "2000");
+}
+
+#[actix_rt::test]
+async fn stops_reading_at_the_item_limit() {
+    let app = app(Config {
+        max_items: 2_500,
+        ..config()
+    })
+    .await;
+    let url = mock_url(&format!("/many/{}", COUNT));
+    let res = get(
+        &app,
+        &url_uri("/v2/feed", &url, "&continue_from=2000&page=3&per_page=200"),
+    )
+    .await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(
+        titles(&res.json["items"]),
+        (2_400..2_500).collect::<Vec<usize>>()
+    );
+    assert_eq!(res.json["has_more"], false);
+    assert_eq!(
+        res.json["warnings"][0],
+        "only the first 2500 items were read, the rest of the feed was skipped"
+    );
+}
+
+#[test]
+fn what_follows_the_limit_is_not_read() {
+    // the document breaks after the limit, the parser never gets there
+    let buf = Bytes::from_static(
+        b"<rss version=\"2.0\"><channel><title>Cut</title>\
+          <item><title>0</title></item><item><title>1</title></item>\
+          <item><title>2</title></item><item>&nbsp;",
+    );
+    let options = ParseOptions {
+        max_items: Some(2),
+        ..ParseOptions::default()
+    };
+    let feed = parse_rss_with(buf.clone(), options).unwrap();
+    assert_eq!(feed.items.len(), 2);
+    assert_eq!(feed.warnings.len(), 1);
+    // at the limit exactly there is nothing more
+    let options = ParseOptions {
+        max_items: Some(3),
+        ..ParseOptions::default()
+    };
+    assert!(parse_rss_with(buf, options).is_err());
+}
+
+#[actix_rt::test]
+async fn rejects_an_invalid_continuation() {
+    let app = app(config()).await;
+    let url = mock_url("/fixtures/rss2_planet.xml");
+    let res = get(&app, &url_uri("/v2/feed", &url, "&continue_from=-1")).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+}
+
+fn lines(body: &[u8]) -> Vec<Value> {
+    std::str::from_utf8(body)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[actix_rt::test]
+async fn streams_a_window_of_a_feed_read_up_to_the_limit() {
+    let app = app(Config {
+        max_items: 2_500,
+        ..config()
+    })
+    .await;
+    let url = mock_url(&format!("/many/{}", COUNT));
+    let uri = url_uri("/feed", &url, "");
+    // streamed as it's parsed, then from the cache
+    for cache in ["parsed", "cached"] {
+        let res = get_with(&app, &uri, "Accept", "application/x-ndjson").await;
+        assert_eq!(res.status, StatusCode::OK);
+        let mut lines = lines(&res.body);
+        let last = lines.pop().unwrap();
+        assert_eq!(
+            last,
+            json!({"has_more": true, "continue_from": 1_000}),
+            "{}",
+            cache
+        );
+        assert_eq!(
+            titles(&Value::Array(lines)),
+            (0..1_000).collect::<Vec<usize>>(),
+            "{}",
+            cache
+        );
+    }
+    // what the stream cached was read up to the limit alone
+    let res = get(&app, &url_uri("/v2/feed", &url, "&continue_from=2000")).await;
+    assert_eq!(res.headers.get("X-Rssss-Cache").unwrap(), "hit");
+    assert_eq!(res.headers.get("X-Total-Count").unwrap(), "500");
+    assert_eq!(res.json["has_more"], false);
+}
+
+#[actix_rt::test]
+async fn streams_a_feed_within_the_window_without_a_last_line() {
+    let app = app(config()).await;
+    let uri = url_uri("/feed", &mock_url("/many/1000"), "");
+    let res = get_with(&app, &uri, "Accept", "application/x-ndjson").await;
+    let lines = lines(&res.body);
+    assert_eq!(
+        titles(&Value::Array(lines)),
+        (0..1_000).collect::<Vec<usize>>()
+    );
+}
+
+#[actix_rt::test]
+async fn continues_after_the_item_of_a_guid() {
+    let app = app(config()).await;
+    let url = mock_url("/many/2500");
+    let res = get(&app, &url_uri("/feed", &url, "&continue_from=item-999")).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(titles(&res.json), (1000..1050).collect::<Vec<usize>>());
+    assert_eq!(res.headers.get("X-Total-Count").unwrap(), "1000");
+    assert_eq!(res.headers.get("X-Rssss-Continue-From").unwrap(), "2000");
+    assert_eq!(res.json[0]["guid"], "item-1000");
+
+    let res = get(&app, &url_uri("/feed", &url, "&continue_from=item-2000")).await;
+    assert_eq!(titles(&res.json), (2001..2051).collect::<Vec<usize>>());
+    assert_eq!(res.headers.get("X-Total-Count").unwrap(), "499");
+    assert!(res.headers.get("X-Rssss-Continue-From").is_none());
+
+    // a guid no longer in the feed is past its items, as an index would be
+    for params in ["&continue_from=item-2499", "&continue_from=gone"] {
+        let res = get(&app, &url_uri("/feed", &url, params)).await;
+        assert_eq!(res.status, StatusCode::OK, "{}", params);
+        assert_eq!(res.json, json!([]), "{}", params);
+    }
+}
@@ -0,0 +1,59 @@
+use rssss::rss::Rss;
+use rssss::words::{count_words, reading_time_minutes};
+
+#[test]
+fn counts_latin_words_by_whitespace() {
+    assert_eq!(count_words("The quick brown fox"), 4);
+    assert_eq!(count_words("  spaced\tout\n\nwords  "), 3);
+    // punctuation alone is no word, and doesn't split one
+    assert_eq!(count_words("don't stop - e-mail, please!"), 4);
+    assert_eq!(count_words("Café déjà vu 2024"), 4);
+}
+
+#[test]
+fn counts_japanese_by_two_characters_a_word() {
+    assert_eq!(count_words("日本語"), 2);
+    assert_eq!(count_words("ひらがなとカタカナ"), 5);
+    // punctuation isn't counted
+    assert_eq!(count_words("今日は、晴れ。"), 3);
+    assert_eq!(count_words("東京 大阪"), 2);
+}
+
+#[test]
+fn counts_mixed_script_text_apiece() {
+    // "Rust" and "1.80", then 11 characters of Japanese
+    assert_eq!(count_words("Rustのバージョン1.80がリリース"), 2 + 6);
+    assert_eq!(count_words("東京Tower"), 2);
+    assert_eq!(count_words("新しいiPhoneを買った"), 1 + 4);
+    // Korean is written with spaces, so counted as words
+    assert_eq!(count_words("안녕하세요 세계"), 2);
+}
+
+#[test]
+fn counts_nothing_in_empty_text() {
+    assert_eq!(count_words(""), 0);
+    assert_eq!(count_words(" \n\t "), 0);
+    assert_eq!(count_words("... — !!"), 0);
+}
+
+#[test]
+fn rounds_reading_time_up_at_200_words_a_minute() {
+    assert_eq!(reading_time_minutes(0), 0);
+    assert_eq!(reading_time_minutes(1), 1);
+    assert_eq!(reading_time_minutes(200), 1);
+    assert_eq!(reading_time_minutes(201), 2);
+    assert_eq!(reading_time_minutes(1_000), 5);
+}
+
+#[test]
+fn counts_the_whole_description_rather_than_what_is_shown() {
+    let mut item = Rss::new("Long", "https://example.com/long", "");
+    item.set_description(format!("<p>{}</p>", "word ".repeat(450)));
+    assert!(item.description_truncated());
+    assert_eq!(item.word_count(), Some(450));
+    let json = serde_json::to_value(&item).unwrap();
+    assert_eq!(json["reading_time_minutes"], 3);
+
+    item.set_description("<p> </p>".to_string());
+    assert_eq!(item.word_count(), None);
+}